@@ -0,0 +1,129 @@
+//! Parse-to-`Value`, parse-to-struct and serialize-struct, for each format and
+//! document shape. Run with:
+//!
+//! ```text
+//! cargo bench -p merde-benches
+//! cargo bench -p merde-benches --features serde_json   # with serde_json, for comparison
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use merde::{Deserialize, DynDeserializerExt, DynSerialize, Value};
+use merde_benches::{Documents, Message, Record, Shape, Tree};
+
+fn parse_value(c: &mut Criterion) {
+    let docs = Documents::new();
+    docs.check();
+    let mut group = c.benchmark_group("parse_value");
+    for shape in docs.shapes() {
+        group.throughput(Throughput::Bytes(shape.json.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("merde_json", shape.name),
+            &shape,
+            |b, s| b.iter(|| merde::json::from_str::<Value>(black_box(&s.json)).unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("merde_msgpack", shape.name),
+            &shape,
+            |b, s| b.iter(|| merde::msgpack::from_slice::<Value>(black_box(&s.msgpack)).unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("merde_yaml", shape.name),
+            &shape,
+            |b, s| b.iter(|| merde::yaml::from_str::<Value>(black_box(&s.yaml)).unwrap()),
+        );
+        #[cfg(feature = "serde_json")]
+        group.bench_with_input(
+            BenchmarkId::new("serde_json", shape.name),
+            &shape,
+            |b, s| {
+                b.iter(|| serde_json::from_str::<serde_json::Value>(black_box(&s.json)).unwrap())
+            },
+        );
+    }
+    group.finish();
+}
+
+fn parse_struct_shape<T>(c: &mut Criterion, shape: &Shape)
+where
+    T: for<'s> Deserialize<'s> + ShapeType,
+{
+    let mut group = c.benchmark_group("parse_struct");
+    group.throughput(Throughput::Bytes(shape.json.len() as u64));
+    group.bench_with_input(BenchmarkId::new("merde_json", shape.name), shape, |b, s| {
+        b.iter(|| merde::json::from_str::<T>(black_box(&s.json)).unwrap())
+    });
+    // all shapes are shallow enough to skip the metastack
+    group.bench_with_input(
+        BenchmarkId::new("merde_json_flat", shape.name),
+        shape,
+        |b, s| {
+            b.iter(|| {
+                merde::json::JsonDeserializer::new(black_box(&s.json))
+                    .deserialize_sync_flat::<T>()
+                    .unwrap()
+            })
+        },
+    );
+    group.bench_with_input(
+        BenchmarkId::new("merde_msgpack", shape.name),
+        shape,
+        |b, s| b.iter(|| merde::msgpack::from_slice::<T>(black_box(&s.msgpack)).unwrap()),
+    );
+    group.bench_with_input(BenchmarkId::new("merde_yaml", shape.name), shape, |b, s| {
+        b.iter(|| merde::yaml::from_str::<T>(black_box(&s.yaml)).unwrap())
+    });
+    #[cfg(feature = "serde_json")]
+    group.bench_with_input(BenchmarkId::new("serde_json", shape.name), shape, |b, s| {
+        b.iter(|| serde_json::from_str::<T>(black_box(&s.json)).unwrap())
+    });
+    group.finish();
+}
+
+fn parse_struct(c: &mut Criterion) {
+    let docs = Documents::new();
+    let [records, messages, tree] = docs.shapes();
+    parse_struct_shape::<Vec<Record>>(c, &records);
+    parse_struct_shape::<Vec<Message>>(c, &messages);
+    parse_struct_shape::<Tree>(c, &tree);
+}
+
+fn serialize_struct_shape<T>(c: &mut Criterion, name: &str, value: &T)
+where
+    T: DynSerialize + ShapeType,
+{
+    let mut group = c.benchmark_group("serialize_struct");
+    group.bench_with_input(BenchmarkId::new("merde_json", name), value, |b, v| {
+        b.iter(|| merde::json::to_vec(black_box(v)).unwrap())
+    });
+    group.bench_with_input(BenchmarkId::new("merde_msgpack", name), value, |b, v| {
+        b.iter(|| merde::msgpack::to_vec(black_box(v)).unwrap())
+    });
+    #[cfg(feature = "serde_json")]
+    group.bench_with_input(BenchmarkId::new("serde_json", name), value, |b, v| {
+        b.iter(|| serde_json::to_vec(black_box(v)).unwrap())
+    });
+    group.finish();
+}
+
+fn serialize_struct(c: &mut Criterion) {
+    let docs = Documents::new();
+    serialize_struct_shape(c, "records", &docs.records);
+    serialize_struct_shape(c, "messages", &docs.messages);
+    serialize_struct_shape(c, "tree", &docs.tree);
+}
+
+/// The bounds serde_json needs, when it's benchmarked too.
+#[cfg(feature = "serde_json")]
+trait ShapeType: serde::Serialize + serde::de::DeserializeOwned {}
+#[cfg(feature = "serde_json")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> ShapeType for T {}
+
+#[cfg(not(feature = "serde_json"))]
+trait ShapeType {}
+#[cfg(not(feature = "serde_json"))]
+impl<T> ShapeType for T {}
+
+criterion_group!(benches, parse_value, parse_struct, serialize_struct);
+criterion_main!(benches);
@@ -0,0 +1,169 @@
+//! Documents of various shapes, shared by the benchmarks in `benches/`.
+//!
+//! Every shape comes as a Rust value (for serialization benchmarks) and as
+//! encoded inputs (for deserialization benchmarks). With the `serde_json`
+//! feature, the types also implement serde's traits, so that serde_json can
+//! be measured on the very same documents.
+
+use merde::Deserialize;
+
+/// A flat record, many times over: the typical API response.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+    pub score: f64,
+    pub active: bool,
+    pub tags: Vec<String>,
+}
+
+merde::derive! {
+    impl (Serialize, Deserialize) for struct Record { id, name, email, score, active, tags }
+}
+
+/// Long strings with escapes and non-ASCII text: stresses string decoding.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Message {
+    pub author: String,
+    pub body: String,
+}
+
+merde::derive! {
+    impl (Serialize, Deserialize) for struct Message { author, body }
+}
+
+/// A balanced tree: stresses nesting rather than breadth.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tree {
+    pub label: String,
+    pub children: Vec<Tree>,
+}
+
+merde::derive! {
+    impl (Serialize, Deserialize) for struct Tree { label, children }
+}
+
+pub fn records(count: usize) -> Vec<Record> {
+    (0..count)
+        .map(|i| Record {
+            id: i as u64,
+            name: format!("user {i}"),
+            email: format!("user{i}@example.org"),
+            score: i as f64 * 0.75,
+            active: i % 3 != 0,
+            tags: (0..i % 4).map(|t| format!("tag{t}")).collect(),
+        })
+        .collect()
+}
+
+pub fn messages(count: usize) -> Vec<Message> {
+    (0..count)
+        .map(|i| Message {
+            author: format!("author {i}"),
+            body: format!(
+                "line one\nline \"two\"\ttabbed \\ backslashed, héhé, 日本語 {i} {}",
+                "lorem ipsum ".repeat(i % 16)
+            ),
+        })
+        .collect()
+}
+
+pub fn tree(depth: usize) -> Tree {
+    Tree {
+        label: format!("depth {depth}"),
+        children: if depth == 0 {
+            vec![]
+        } else {
+            vec![tree(depth - 1), tree(depth - 1)]
+        },
+    }
+}
+
+/// A document shape, and its encodings.
+pub struct Shape {
+    pub name: &'static str,
+    pub json: String,
+    pub msgpack: Vec<u8>,
+    /// merde_yaml can't serialize, but JSON is valid YAML (flow style)
+    pub yaml: String,
+}
+
+impl Shape {
+    fn new(name: &'static str, value: &dyn merde::DynSerialize) -> Self {
+        let json = merde::json::to_string(value).unwrap();
+        Self {
+            name,
+            msgpack: merde::msgpack::to_vec(value).unwrap(),
+            yaml: json.clone(),
+            json,
+        }
+    }
+}
+
+/// The document shapes every benchmark runs on, with their values.
+pub struct Documents {
+    pub records: Vec<Record>,
+    pub messages: Vec<Message>,
+    pub tree: Tree,
+}
+
+impl Documents {
+    pub fn new() -> Self {
+        Self {
+            records: records(1000),
+            messages: messages(500),
+            tree: tree(10),
+        }
+    }
+
+    pub fn shapes(&self) -> [Shape; 3] {
+        [
+            Shape::new("records", &self.records),
+            Shape::new("messages", &self.messages),
+            Shape::new("tree", &self.tree),
+        ]
+    }
+}
+
+impl Default for Documents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Documents {
+    /// Checks that every encoding of every shape deserializes back to the
+    /// original value, or the benchmarks wouldn't be comparing the same work.
+    pub fn check(&self) {
+        let [records, messages, tree] = self.shapes();
+        check_shape(&records, &self.records);
+        check_shape(&messages, &self.messages);
+        check_shape(&tree, &self.tree);
+    }
+}
+
+fn check_shape<T>(shape: &Shape, expected: &T)
+where
+    T: for<'s> Deserialize<'s> + PartialEq + std::fmt::Debug,
+{
+    let json: T = merde::json::from_str(&shape.json).unwrap();
+    assert_eq!(&json, expected, "{}: json", shape.name);
+    let msgpack: T = merde::msgpack::from_slice(&shape.msgpack).unwrap();
+    assert_eq!(&msgpack, expected, "{}: msgpack", shape.name);
+    let yaml: T = merde::yaml::from_str(&shape.yaml).unwrap();
+    assert_eq!(&yaml, expected, "{}: yaml", shape.name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_agree() {
+        Documents::new().check();
+    }
+}
@@ -0,0 +1,146 @@
+//! Glue between axum and merde: decoding request bodies, encoding responses,
+//! and turning errors into structured responses.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use merde::{Deserialize, DynSerialize, Format, IntoStatic, Map, MerdeError, Value};
+
+/// A request body in one of the formats merde supports, picked from its
+/// `Content-Type` header. It's decoded separately (see [`Payload::decode`]),
+/// so that the decoded value can borrow from it.
+pub struct Payload {
+    format: Format,
+    bytes: Bytes,
+}
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequest<S> for Payload {
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, ApiError> {
+        let format = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(merde::negotiate)
+            .ok_or(ApiError::UnsupportedMediaType)?;
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| ApiError::BadRequest(MerdeError::Io(std::io::Error::other(e))))?;
+        Ok(Self { format, bytes })
+    }
+}
+
+impl Payload {
+    /// Deserializes the body, borrowing strings from it where possible.
+    pub fn decode<'s, T: Deserialize<'s>>(&'s self) -> Result<T, ApiError> {
+        merde::from_bytes_any(self.format, &self.bytes)
+            .map_err(|e| ApiError::BadRequest(e.into_static()))
+    }
+}
+
+/// A response body, in the format the client asked for via its `Accept`
+/// header (JSON if it didn't ask for anything we know).
+pub struct Reply<T> {
+    pub status: StatusCode,
+    pub format: Format,
+    pub value: T,
+}
+
+impl<T: DynSerialize> Reply<T> {
+    pub fn new(headers: &HeaderMap, value: T) -> Self {
+        let format = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|accept| accept.split(',').find_map(merde::negotiate))
+            .unwrap_or(Format::Json);
+        Self {
+            status: StatusCode::OK,
+            format,
+            value,
+        }
+    }
+
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl<T: DynSerialize> IntoResponse for Reply<T> {
+    fn into_response(self) -> Response {
+        match merde::to_vec_any(self.format, &self.value) {
+            Ok(body) => encoded(self.status, self.format, body),
+            Err(e) => ApiError::Internal(e).into_response(),
+        }
+    }
+}
+
+fn encoded(status: StatusCode, format: Format, body: Vec<u8>) -> Response {
+    let content_type = HeaderValue::from_static(format.content_type());
+    (status, [(header::CONTENT_TYPE, content_type)], body).into_response()
+}
+
+/// Everything a handler can fail with. Errors are sent to clients as JSON,
+/// like `{"error": {"code": "missing_property", "message": "...", "property": "title"}}`.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request body couldn't be read or deserialized
+    BadRequest(MerdeError<'static>),
+    /// The request body isn't in a format we know
+    UnsupportedMediaType,
+    NotFound,
+    /// Something that isn't the client's fault, like a cache entry we wrote
+    /// ourselves that doesn't deserialize anymore
+    Internal(MerdeError<'static>),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, error) = match &self {
+            ApiError::BadRequest(e) => (StatusCode::BAD_REQUEST, Value::from(error_map(e))),
+            ApiError::UnsupportedMediaType => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                Map::new()
+                    .with("code", "unsupported_media_type")
+                    .with("message", "send JSON or MessagePack")
+                    .into(),
+            ),
+            ApiError::NotFound => (
+                StatusCode::NOT_FOUND,
+                Map::new()
+                    .with("code", "not_found")
+                    .with("message", "no such note")
+                    .into(),
+            ),
+            ApiError::Internal(e) => {
+                eprintln!("internal error: {e}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Map::new()
+                        .with("code", "internal")
+                        .with("message", "internal server error")
+                        .into(),
+                )
+            }
+        };
+        let body = Map::new().with("error", error);
+        // only maps of strings: this can't fail
+        let body = merde::json::to_vec(&body).unwrap_or_default();
+        encoded(status, Format::Json, body)
+    }
+}
+
+/// `MerdeError`'s `Serialize` impl already gives a structured error: go through
+/// a `Value` so it can be embedded in the response.
+fn error_map(e: &MerdeError<'static>) -> Map<'static> {
+    let json = merde::json::to_vec(e).unwrap_or_default();
+    match merde::json::from_bytes::<Map>(&json) {
+        Ok(map) => map.into_static(),
+        Err(_) => Map::new().with("code", e.code()),
+    }
+}
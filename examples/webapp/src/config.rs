@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use merde::{config::ConfigLoader, env::EnvDeserializer, Map, MerdeError};
+
+/// The service's configuration: built-in defaults, overridden by a YAML file,
+/// overridden by environment variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Address to listen on, like `127.0.0.1:3000`
+    pub listen: String,
+    pub cache: CacheConfig,
+}
+
+merde::derive! {
+    impl (Serialize, Deserialize) for struct Config { listen, cache }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// How many notes to keep, the oldest ones are evicted first
+    pub max_entries: u64,
+}
+
+merde::derive! {
+    impl (Serialize, Deserialize) for struct CacheConfig { max_entries }
+}
+
+impl Config {
+    /// Loads the configuration from `file` (if it exists), then from `env`, which
+    /// is usually `EnvDeserializer::from_env().with_prefix("WEBAPP_")`.
+    pub fn load(
+        file: impl Into<PathBuf>,
+        env: EnvDeserializer,
+    ) -> Result<Self, MerdeError<'static>> {
+        ConfigLoader::new()
+            .with_defaults(
+                Map::new()
+                    .with("listen", "127.0.0.1:3000")
+                    .with("cache", Map::new().with("max_entries", 1000u64)),
+            )
+            .with_optional_file(file)
+            .with_env_vars(env)
+            .load()
+    }
+}
@@ -0,0 +1,25 @@
+//! A small notes service, showing how merde's crates fit together:
+//!
+//!   * [`config`]: YAML config file with an environment variable overlay (merde_config, merde_yaml, merde_env)
+//!   * [`api`]: JSON or MessagePack request and response bodies, picked from headers, and
+//!     `MerdeError`s turned into structured error responses
+//!   * [`notes`]: zero-copy request bodies, RFC 3339 timestamps, and notes cached as MessagePack
+
+pub mod api;
+pub mod config;
+pub mod notes;
+
+pub use config::Config;
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+/// Builds the service's router.
+pub fn app(config: &Config) -> Router {
+    Router::new()
+        .route("/notes", post(notes::create_note))
+        .route("/notes/:id", get(notes::get_note))
+        .with_state(notes::AppState::new(config))
+}
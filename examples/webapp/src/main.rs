@@ -0,0 +1,25 @@
+use merde::env::EnvDeserializer;
+use merde_webapp_example::{app, Config};
+
+#[tokio::main]
+async fn main() {
+    let config_path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "config.yaml".to_string());
+    let config = match Config::load(
+        config_path,
+        EnvDeserializer::from_env().with_prefix("WEBAPP_"),
+    ) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("invalid configuration: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let listener = tokio::net::TcpListener::bind(&config.listen)
+        .await
+        .unwrap_or_else(|e| panic!("could not listen on {}: {e}", config.listen));
+    eprintln!("listening on http://{}", config.listen);
+    axum::serve(listener, app(&config)).await.unwrap();
+}
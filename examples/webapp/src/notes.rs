@@ -0,0 +1,110 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+};
+use merde::{time::Rfc3339, CowStr};
+use time::OffsetDateTime;
+
+use crate::{
+    api::{ApiError, Payload, Reply},
+    Config,
+};
+
+/// A note, as stored and returned by the API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    pub tags: Option<Vec<String>>,
+    pub created_at: Rfc3339<OffsetDateTime>,
+}
+
+merde::derive! {
+    impl (Serialize, Deserialize) for struct Note { id, title, body, tags, created_at }
+}
+
+/// The body of `POST /notes`: it borrows from the request body, so strings
+/// without escapes aren't copied until they're stored.
+#[derive(Debug)]
+pub struct NewNote<'s> {
+    pub title: CowStr<'s>,
+    pub body: CowStr<'s>,
+    pub tags: Option<Vec<CowStr<'s>>>,
+}
+
+merde::derive! {
+    impl (Deserialize) for struct NewNote<'s> { title, body, tags }
+}
+
+/// Notes, stored as MessagePack, the way they'd be stored in an external
+/// cache (memcached, redis, etc.).
+#[derive(Clone)]
+pub struct AppState {
+    inner: Arc<Mutex<Store>>,
+}
+
+struct Store {
+    max_entries: usize,
+    next_id: u64,
+    entries: BTreeMap<u64, Vec<u8>>,
+}
+
+impl AppState {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Store {
+                max_entries: config.cache.max_entries.max(1) as usize,
+                next_id: 1,
+                entries: BTreeMap::new(),
+            })),
+        }
+    }
+}
+
+pub async fn create_note(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Payload,
+) -> Result<Reply<Note>, ApiError> {
+    let new: NewNote = payload.decode()?;
+
+    let mut store = state.inner.lock().unwrap();
+    let note = Note {
+        id: store.next_id,
+        title: new.title.into(),
+        body: new.body.into(),
+        tags: new
+            .tags
+            .map(|tags| tags.into_iter().map(String::from).collect()),
+        created_at: Rfc3339(OffsetDateTime::now_utc()),
+    };
+    store.next_id += 1;
+
+    let encoded = merde::msgpack::to_vec(&note).map_err(ApiError::Internal)?;
+    if store.entries.len() >= store.max_entries {
+        store.entries.pop_first();
+    }
+    store.entries.insert(note.id, encoded);
+
+    Ok(Reply::new(&headers, note).with_status(StatusCode::CREATED))
+}
+
+pub async fn get_note(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+) -> Result<Reply<Note>, ApiError> {
+    let store = state.inner.lock().unwrap();
+    let encoded = store.entries.get(&id).ok_or(ApiError::NotFound)?;
+    let note: Note = merde::msgpack::from_slice(encoded).map_err(|e| {
+        use merde::IntoStatic;
+        ApiError::Internal(e.into_static())
+    })?;
+    Ok(Reply::new(&headers, note))
+}
@@ -0,0 +1,101 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+    Router,
+};
+use merde::{env::EnvDeserializer, CowStr, Map, Value};
+use merde_webapp_example::{app, notes::Note, Config};
+use tower::ServiceExt;
+
+fn test_app() -> Router {
+    let config = Config::load("does-not-exist.yaml", EnvDeserializer::new([])).unwrap();
+    app(&config)
+}
+
+async fn send(app: &Router, req: Request<Body>) -> (StatusCode, Option<String>, Vec<u8>) {
+    let res = app.clone().oneshot(req).await.unwrap();
+    let status = res.status();
+    let content_type = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, content_type, body.to_vec())
+}
+
+fn post_json(body: &str) -> Request<Body> {
+    Request::post("/notes")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_create_and_get() {
+    let app = test_app();
+
+    let (status, content_type, body) = send(
+        &app,
+        post_json(r#"{"title": "groceries", "body": "eggs\nmilk", "tags": ["home"]}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+    let created: Note = merde::json::from_bytes_owned(&body).unwrap();
+    assert_eq!(created.title, "groceries");
+    assert_eq!(created.body, "eggs\nmilk");
+
+    // round-trips through the msgpack cache, and back as msgpack
+    let req = Request::get(format!("/notes/{}", created.id))
+        .header(header::ACCEPT, "text/html, application/msgpack")
+        .body(Body::empty())
+        .unwrap();
+    let (status, content_type, body) = send(&app, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/msgpack"));
+    let fetched: Note = merde::msgpack::from_slice(&body).unwrap();
+    assert_eq!(fetched, created);
+}
+
+#[tokio::test]
+async fn test_errors() {
+    let app = test_app();
+
+    let (status, _, body) = send(&app, post_json(r#"{"body": "no title"}"#)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let body: Map = merde::json::from_bytes(&body).unwrap();
+    let error = body.get(&CowStr::from("error")).unwrap().as_map().unwrap();
+    assert_eq!(
+        error.get(&CowStr::from("code")),
+        Some(&Value::from("missing_property"))
+    );
+    assert_eq!(
+        error.get(&CowStr::from("property")),
+        Some(&Value::from("title"))
+    );
+
+    let req = Request::post("/notes")
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(Body::from("hi"))
+        .unwrap();
+    assert_eq!(send(&app, req).await.0, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let req = Request::get("/notes/404").body(Body::empty()).unwrap();
+    assert_eq!(send(&app, req).await.0, StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_config_layers() {
+    let dir = std::env::temp_dir().join(format!("merde-webapp-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("config.yaml");
+    std::fs::write(&file, "listen: 0.0.0.0:8080\ncache:\n  max_entries: 50\n").unwrap();
+
+    let env = EnvDeserializer::new([("WEBAPP_CACHE__MAX_ENTRIES".to_string(), "10".to_string())])
+        .with_prefix("WEBAPP_");
+    let config = Config::load(&file, env).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(config.listen, "0.0.0.0:8080");
+    assert_eq!(config.cache.max_entries, 10);
+}
@@ -0,0 +1,20 @@
+//! Arbitrary text → `Value`: must never panic, and whatever parses must
+//! survive a round-trip.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use merde::Value;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(value) = merde::json::from_str::<Value>(input) else {
+        return;
+    };
+
+    let encoded = merde::json::to_string(&value).unwrap();
+    let decoded: Value = merde::json::from_str(&encoded).unwrap();
+    assert_eq!(value, decoded, "re-encoded as {encoded}");
+});
@@ -0,0 +1,22 @@
+//! Arbitrary bytes → `Value`: must never panic (think length prefixes that
+//! point past the end of the input), and whatever parses must survive a
+//! round-trip (up to integer representations).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use merde::Value;
+use merde_fuzz::{normalize, Format};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(value) = merde::msgpack::from_slice::<Value>(data) else {
+        return;
+    };
+
+    let encoded = merde::msgpack::to_vec(&value).unwrap();
+    let decoded: Value = merde::msgpack::from_slice(&encoded).unwrap();
+    assert_eq!(
+        normalize(value, Format::Msgpack),
+        normalize(decoded, Format::Msgpack)
+    );
+});
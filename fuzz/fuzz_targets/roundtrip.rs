@@ -0,0 +1,26 @@
+//! Structured fuzzing: the input seeds a random `Value`, which must come back
+//! unchanged after being serialized and deserialized by every format that can
+//! serialize.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use merde::{arbitrary::Rng, Value};
+use merde_fuzz::{normalize, Format};
+
+fuzz_target!(|data: &[u8]| {
+    let value = Value::arbitrary_with(&mut Rng::from_bytes(data), 4, 6);
+
+    let encoded = merde::msgpack::to_vec(&value).unwrap();
+    let decoded: Value = merde::msgpack::from_slice(&encoded).unwrap();
+    assert_eq!(
+        normalize(value.clone(), Format::Msgpack),
+        normalize(decoded, Format::Msgpack),
+        "msgpack"
+    );
+
+    let value = normalize(value, Format::Json);
+    let encoded = merde::json::to_string(&value).unwrap();
+    let decoded: Value = merde::json::from_str(&encoded).unwrap();
+    assert_eq!(value, decoded, "json: {encoded}");
+});
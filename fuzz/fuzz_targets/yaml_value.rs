@@ -0,0 +1,13 @@
+//! Arbitrary text → `Value`: must never panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use merde::Value;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = merde::yaml::from_str::<Value>(input);
+});
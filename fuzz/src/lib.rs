@@ -0,0 +1,44 @@
+//! Helpers shared by the fuzz targets.
+
+use merde::{Map, Value};
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Msgpack,
+    Json,
+}
+
+/// Maps values to what a format can represent:
+///
+///   * msgpack reads non-negative integers back as `U64`
+///   * JSON has no bytes (they're replaced with nulls), and numbers are read
+///     as `f64` (so large integers lose precision)
+pub fn normalize(value: Value<'_>, format: Format) -> Value<'_> {
+    match (value, format) {
+        (Value::I64(i), Format::Msgpack) if i >= 0 => Value::U64(i as u64),
+        (Value::I64(i), Format::Json) => json_number(i as f64),
+        (Value::U64(u), Format::Json) => json_number(u as f64),
+        (Value::Bytes(_), Format::Json) => Value::Null,
+        (Value::Array(arr), format) => Value::Array(
+            arr.into_iter()
+                .map(|v| normalize(v, format))
+                .collect::<Vec<_>>()
+                .into(),
+        ),
+        (Value::Map(map), format) => Value::Map(
+            map.into_iter()
+                .map(|(k, v)| (k, normalize(v, format)))
+                .collect::<Map>(),
+        ),
+        (v, _) => v,
+    }
+}
+
+/// Same as `JsonDeserializer`
+fn json_number<'s>(f: f64) -> Value<'s> {
+    if f.fract() == 0.0 && f >= i64::MIN as f64 && f <= i64::MAX as f64 {
+        Value::I64(f as i64)
+    } else {
+        Value::Float(f.into())
+    }
+}
@@ -0,0 +1,184 @@
+//! Building a custom value tree (here, the value type of a small templating
+//! language) straight from the events of any format, without going through
+//! `merde::Value`.
+
+use merde::{
+    CowStr, Deserialize, DynDeserializer, DynDeserializerExt, Event, EventBuffer, EventType,
+    MerdeError, MetastackExt,
+};
+
+/// A value, as seen by templates.
+#[derive(Debug)]
+enum TplValue<'s> {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    Text(CowStr<'s>),
+    List(Vec<TplValue<'s>>),
+    // unlike `merde::Map`, keeps keys in document order, so templates can
+    // iterate over them predictably
+    Dict(Vec<(CowStr<'s>, TplValue<'s>)>),
+    // `{"$filter": ...}` maps are kept as raw events, and only deserialized
+    // into a concrete type when (and if) the template engine needs them
+    Filter(EventBuffer<'s>),
+}
+
+impl<'s> Deserialize<'s> for TplValue<'s> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        match de.next().await? {
+            Event::Null => Ok(TplValue::Nil),
+            Event::Bool(b) => Ok(TplValue::Bool(b)),
+            Event::I64(i) => Ok(TplValue::Number(i as f64)),
+            Event::U64(u) => Ok(TplValue::Number(u as f64)),
+            Event::F64(f) => Ok(TplValue::Number(f)),
+            Event::Str(s) => Ok(TplValue::Text(s)),
+            Event::Bytes(b) => Ok(TplValue::Text(
+                String::from_utf8_lossy(&b).into_owned().into(),
+            )),
+            Event::ArrayStart(_) => {
+                let mut items = Vec::new();
+                loop {
+                    match de.next().await? {
+                        Event::ArrayEnd => break,
+                        ev => {
+                            // we needed to look at the event to know whether the
+                            // array was over: hand it back before recursing.
+                            de.put_back(ev)?;
+                            // this is what makes deeply nested documents safe: if
+                            // we're running out of stack, the rest of the work is
+                            // resumed from the `run_sync_with_metastack` landing pad.
+                            let item = TplValue::deserialize(de)
+                                .with_metastack_resume_point()
+                                .await?;
+                            items.push(item);
+                        }
+                    }
+                }
+                Ok(TplValue::List(items))
+            }
+            Event::MapStart(_) => {
+                let mut entries = Vec::new();
+                loop {
+                    match de.next().await? {
+                        Event::MapEnd => break,
+                        Event::Str(key) if entries.is_empty() && key == "$filter" => {
+                            // record the filter's value, whatever it is, for later
+                            let filter = EventBuffer::record(de).await?;
+                            match de.next().await? {
+                                Event::MapEnd => return Ok(TplValue::Filter(filter)),
+                                ev => {
+                                    return Err(MerdeError::UnexpectedEvent {
+                                        got: EventType::from(&ev),
+                                        expected: &[EventType::MapEnd],
+                                        help: Some("$filter maps have a single key".into()),
+                                    })
+                                }
+                            }
+                        }
+                        Event::Str(key) => {
+                            let value = TplValue::deserialize(de)
+                                .with_metastack_resume_point()
+                                .await?;
+                            entries.push((key, value));
+                        }
+                        ev => {
+                            return Err(MerdeError::UnexpectedEvent {
+                                got: EventType::from(&ev),
+                                expected: &[EventType::Str, EventType::MapEnd],
+                                help: None,
+                            })
+                        }
+                    }
+                }
+                Ok(TplValue::Dict(entries))
+            }
+            ev => Err(MerdeError::UnexpectedEvent {
+                got: EventType::from(&ev),
+                expected: &[],
+                help: None,
+            }),
+        }
+    }
+}
+
+impl TplValue<'_> {
+    /// How the value looks when interpolated in a template
+    fn render(&self, out: &mut String) {
+        match self {
+            TplValue::Nil => {}
+            TplValue::Bool(b) => out.push_str(if *b { "yes" } else { "no" }),
+            TplValue::Number(n) => out.push_str(&n.to_string()),
+            TplValue::Text(s) => out.push_str(s),
+            TplValue::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    item.render(out);
+                }
+            }
+            TplValue::Dict(entries) => {
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(k);
+                    out.push_str(": ");
+                    v.render(out);
+                }
+            }
+            TplValue::Filter(_) => out.push_str("<filter>"),
+        }
+    }
+}
+
+/// What a `$filter` deserializes to, once the template engine gets to it.
+#[derive(Debug)]
+struct Filter {
+    name: String,
+    args: Vec<f64>,
+}
+
+merde::derive! {
+    impl (Deserialize) for struct Filter { name, args }
+}
+
+fn main() {
+    let json = r#"
+        {
+            "title": "Hello",
+            "tags": ["a", "b"],
+            "price": { "$filter": { "name": "round", "args": [2] } }
+        }
+    "#;
+    let from_json: TplValue = merde::json::from_str(json).unwrap();
+    println!("from JSON: {from_json:#?}");
+
+    let yaml = r#"
+        title: Hello
+        tags: [a, b]
+        price:
+          $filter:
+            name: round
+            args: [2]
+    "#;
+    let from_yaml: TplValue = merde::yaml::from_str(yaml).unwrap();
+    println!("from YAML: {from_yaml:#?}");
+
+    let mut rendered = String::new();
+    from_yaml.render(&mut rendered);
+    assert_eq!(rendered, "title: Hello, tags: a, b, price: <filter>");
+
+    // the filter was never parsed: do it now, and only now
+    let TplValue::Dict(entries) = from_yaml else {
+        unreachable!()
+    };
+    let (_, price) = entries.into_iter().find(|(k, _)| *k == "price").unwrap();
+    let TplValue::Filter(mut events) = price else {
+        unreachable!()
+    };
+    let filter: Filter = events.deserialize().unwrap();
+    println!("filter: {filter:?}");
+    assert_eq!(filter.name, "round");
+    assert_eq!(filter.args, [2.0]);
+}
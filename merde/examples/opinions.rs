@@ -29,6 +29,13 @@ fn main() {
     let o: Owned = merde_json::from_str(input_missing_field).unwrap();
     assert_eq!(o.foo_bar, "(default)");
     eprintln!("{o:#?}");
+
+    let input_untrimmed = r#"
+        { "foo_bar": "  hello\n" }
+    "#;
+    let o: Owned = merde_json::from_str(input_untrimmed).unwrap();
+    assert_eq!(o.foo_bar, "hello");
+    eprintln!("{o:#?}");
 }
 
 #[derive(Debug)]
@@ -57,6 +64,18 @@ impl DeserOpinions for OwnedOpinions {
             key
         }
     }
+
+    fn map_string_value<'s>(&self, _field: &str, value: CowStr<'s>) -> CowStr<'s> {
+        // trimming borrowed strings doesn't need to allocate
+        match value {
+            CowStr::Borrowed(s) => CowStr::Borrowed(s.trim()),
+            CowStr::Owned(s) => CowStr::from(s.trim().to_string()),
+        }
+    }
+
+    fn maps_string_values(&self) -> bool {
+        true
+    }
 }
 
 merde::derive! {
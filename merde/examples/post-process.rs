@@ -0,0 +1,128 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+    thread::Thread,
+    time::{Duration, Instant},
+};
+
+use merde::{CowStr, PostProcess};
+
+#[derive(Debug)]
+struct Attachment<'s> {
+    blob_id: CowStr<'s>,
+    // filled in after deserialization
+    contents: Option<String>,
+}
+
+merde::derive! {
+    impl (Deserialize) for struct Attachment<'s> { blob_id, contents }
+}
+
+#[derive(Debug)]
+struct Message<'s> {
+    author_id: u64,
+    author_name: Option<String>,
+    attachments: Vec<Attachment<'s>>,
+}
+
+merde::derive! {
+    impl (Deserialize) for struct Message<'s> { author_id, author_name, attachments }
+}
+
+fn main() {
+    let input = r#"
+        {
+            "author_id": 42,
+            "attachments": [
+                { "blob_id": "a1" },
+                { "blob_id": "b2" },
+                { "blob_id": "c3" },
+                { "blob_id": "d4" }
+            ]
+        }
+    "#;
+    let mut message: Message = merde_json::from_str(input).unwrap();
+
+    // every lookup takes 100ms: with two of them in flight at a time, the five
+    // of them take 300ms instead of 500ms.
+    let before = Instant::now();
+    let (author_id, author_name) = (message.author_id, &mut message.author_name);
+    let res: Result<(), String> = block_on(
+        PostProcess::new(2)
+            .with(async {
+                *author_name = Some(slow_lookup(format!("user #{author_id}")).await);
+                Ok(())
+            })
+            .extend(message.attachments.iter_mut().map(|attachment| async move {
+                if attachment.blob_id.is_empty() {
+                    return Err("empty blob id".to_string());
+                }
+                let contents = slow_lookup(format!("contents of {}", attachment.blob_id)).await;
+                attachment.contents = Some(contents);
+                Ok(())
+            }))
+            .run(),
+    );
+    res.unwrap();
+    eprintln!("post-processed in {:?}: {message:#?}", before.elapsed());
+
+    assert_eq!(message.author_name.as_deref(), Some("user #42"));
+    assert!(message.attachments.iter().all(|a| a.contents.is_some()));
+    assert!(before.elapsed() < Duration::from_millis(450));
+}
+
+/// Stands in for a database query or an HTTP request: the result is produced
+/// by another thread, which wakes the task when it's done.
+fn slow_lookup(result: String) -> impl Future<Output = String> {
+    struct Lookup {
+        result: Option<String>,
+        shared: Arc<Mutex<Option<String>>>,
+    }
+
+    impl Future for Lookup {
+        type Output = String;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<String> {
+            if let Some(result) = self.result.take() {
+                let (shared, waker) = (self.shared.clone(), cx.waker().clone());
+                std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_millis(100));
+                    *shared.lock().unwrap() = Some(result);
+                    waker.wake();
+                });
+            }
+            match self.shared.lock().unwrap().take() {
+                Some(result) => Poll::Ready(result),
+                None => Poll::Pending,
+            }
+        }
+    }
+
+    Lookup {
+        result: Some(result),
+        shared: Default::default(),
+    }
+}
+
+/// A minimal executor, use tokio or smol's in real code.
+fn block_on<F: Future>(fut: F) -> F::Output {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = std::pin::pin!(fut);
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(out) => return out,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
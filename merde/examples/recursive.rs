@@ -0,0 +1,104 @@
+//! Recursive types: `Vec<Self>`, `Box<Self>`, `Option<Box<Self>>`, etc. work
+//! with `derive!` out of the box. The impls for those containers box their
+//! futures (otherwise the futures of recursive types would be infinitely
+//! sized), and put a metastack resume point there, so that serializing and
+//! deserializing very deep values doesn't overflow the stack either.
+
+#[derive(Debug)]
+struct Tree {
+    name: String,
+    children: Vec<Tree>,
+}
+
+merde::derive! {
+    impl (Serialize, Deserialize) for struct Tree { name, children }
+}
+
+// dropping is recursive too, and merde can't help with that: very deep values
+// need to be taken apart one level at a time. (a `Drop` impl would do that
+// automatically, but then `derive!` couldn't move fields out of the struct)
+fn drop_tree(tree: Tree) {
+    let mut stack = vec![tree];
+    while let Some(mut tree) = stack.pop() {
+        stack.append(&mut tree.children);
+    }
+}
+
+#[derive(Debug)]
+struct List {
+    value: u64,
+    next: Option<Box<List>>,
+}
+
+merde::derive! {
+    impl (Serialize, Deserialize) for struct List { value, next }
+}
+
+fn drop_list(mut next: Option<Box<List>>) {
+    while let Some(mut list) = next {
+        next = list.next.take();
+    }
+}
+
+fn main() {
+    let input = r#"{
+        "name": "root",
+        "children": [
+            { "name": "left", "children": [] },
+            { "name": "right", "children": [{ "name": "leaf", "children": [] }] }
+        ]
+    }"#;
+    let tree: Tree = merde::json::from_str(input).unwrap();
+    println!("{}", merde::json::to_string(&tree).unwrap());
+
+    // a small stack, to show off: without the metastack, a few thousand levels
+    // would be enough to overflow it.
+    std::thread::Builder::new()
+        .stack_size(128 * 1024)
+        .spawn(|| {
+            let depth = 100_000;
+
+            let mut tree = Tree {
+                name: "leaf".into(),
+                children: vec![],
+            };
+            for _ in 0..depth {
+                tree = Tree {
+                    name: "node".into(),
+                    children: vec![tree],
+                };
+            }
+            let json = merde::json::to_string(&tree).unwrap();
+            drop_tree(tree);
+            let tree: Tree = merde::json::from_str(&json).unwrap();
+            let mut count = 0;
+            let mut current = &tree;
+            while let Some(child) = current.children.first() {
+                current = child;
+                count += 1;
+            }
+            assert_eq!(count, depth);
+            println!("tree of depth {count}: {} bytes of JSON", json.len());
+            drop_tree(tree);
+
+            let mut list = None;
+            for value in (0..depth).rev() {
+                list = Some(Box::new(List { value, next: list }));
+            }
+            let json = merde::json::to_string(&list).unwrap();
+            drop_list(list);
+            let list: Option<Box<List>> = merde::json::from_str(&json).unwrap();
+            let (mut count, mut current) = (0, list.as_deref());
+            while let Some(node) = current {
+                assert_eq!(node.value, count);
+                current = node.next.as_deref();
+                count += 1;
+            }
+            assert_eq!(count, depth);
+            println!("list of length {count}: {} bytes of JSON", json.len());
+            drop_list(list);
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
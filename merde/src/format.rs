@@ -0,0 +1,131 @@
+//! Picking a format at runtime, e.g. from an HTTP `Content-Type` header.
+
+use merde_core::{Deserialize, DynSerialize, MerdeError};
+
+/// A serialization format, among those enabled via cargo features.
+///
+/// YAML isn't part of it (yet): merde_yaml can only deserialize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Format {
+    /// JSON, via merde_json (`application/json`)
+    #[cfg(feature = "json")]
+    Json,
+
+    /// MessagePack, via merde_msgpack (`application/msgpack`)
+    #[cfg(feature = "msgpack")]
+    Msgpack,
+}
+
+impl Format {
+    /// Returns the canonical media type for this format, e.g. for a
+    /// `Content-Type` response header.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            #[cfg(feature = "json")]
+            Format::Json => "application/json",
+            #[cfg(feature = "msgpack")]
+            Format::Msgpack => "application/msgpack",
+        }
+    }
+}
+
+/// Returns the format for a media type, like the value of a `Content-Type`
+/// header. Parameters (`; charset=utf-8`) are ignored, the comparison is
+/// case-insensitive, and structured syntax suffixes (`application/problem+json`)
+/// are recognized.
+///
+/// Returns `None` if the media type is unknown, or if support for the
+/// corresponding format isn't enabled.
+///
+/// ```rust
+/// assert_eq!(
+///     merde::negotiate("application/json; charset=utf-8"),
+///     Some(merde::Format::Json)
+/// );
+/// assert_eq!(merde::negotiate("text/html"), None);
+/// ```
+pub fn negotiate(content_type: &str) -> Option<Format> {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let (kind, subtype) = essence.split_once('/')?;
+    let suffix = subtype.rsplit_once('+').map(|(_, suffix)| suffix);
+
+    match (kind, subtype, suffix) {
+        #[cfg(feature = "json")]
+        ("application" | "text", "json", _) | (_, _, Some("json")) => Some(Format::Json),
+        #[cfg(feature = "msgpack")]
+        ("application", "msgpack" | "x-msgpack" | "vnd.msgpack", _) | (_, _, Some("msgpack")) => {
+            Some(Format::Msgpack)
+        }
+        _ => None,
+    }
+}
+
+/// Deserializes an instance of `T` from `bytes`, in the given format.
+pub fn from_bytes_any<'s, T>(format: Format, bytes: &'s [u8]) -> Result<T, MerdeError<'s>>
+where
+    T: Deserialize<'s>,
+{
+    match format {
+        #[cfg(feature = "json")]
+        Format::Json => merde_json::from_bytes(bytes),
+        #[cfg(feature = "msgpack")]
+        Format::Msgpack => merde_msgpack::from_slice(bytes),
+    }
+}
+
+/// Serializes `value` to a `Vec<u8>`, in the given format.
+pub fn to_vec_any(
+    format: Format,
+    value: &dyn DynSerialize,
+) -> Result<Vec<u8>, MerdeError<'static>> {
+    match format {
+        #[cfg(feature = "json")]
+        Format::Json => merde_json::to_vec(value),
+        #[cfg(feature = "msgpack")]
+        Format::Msgpack => merde_msgpack::to_vec(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        #[cfg(feature = "json")]
+        {
+            assert_eq!(negotiate("application/json"), Some(Format::Json));
+            assert_eq!(
+                negotiate(" Application/JSON ; charset=UTF-8"),
+                Some(Format::Json)
+            );
+            assert_eq!(negotiate("application/problem+json"), Some(Format::Json));
+        }
+        #[cfg(feature = "msgpack")]
+        {
+            assert_eq!(negotiate("application/msgpack"), Some(Format::Msgpack));
+            assert_eq!(negotiate("application/x-msgpack"), Some(Format::Msgpack));
+            assert_eq!(negotiate("application/vnd.msgpack"), Some(Format::Msgpack));
+        }
+        assert_eq!(negotiate("text/html"), None);
+        assert_eq!(negotiate("json"), None);
+        assert_eq!(negotiate(""), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "json", feature = "msgpack"))]
+    fn test_roundtrip_any() {
+        let value: Vec<Option<String>> = vec![Some("a".into()), None];
+        for format in [Format::Json, Format::Msgpack] {
+            let bytes = to_vec_any(format, &value).unwrap();
+            let back: Vec<Option<String>> = from_bytes_any(format, &bytes).unwrap();
+            assert_eq!(back, value, "{format:?}");
+        }
+    }
+}
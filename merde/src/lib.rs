@@ -8,13 +8,45 @@ pub use merde_json as json;
 #[cfg(feature = "yaml")]
 pub use merde_yaml as yaml;
 
+#[cfg(feature = "msgpack")]
+pub use merde_msgpack as msgpack;
+
+#[cfg(feature = "urlencoded")]
+pub use merde_urlencoded as urlencoded;
+
+#[cfg(feature = "env")]
+pub use merde_env as env;
+
+#[cfg(feature = "config")]
+pub use merde_config as config;
+
 #[cfg(feature = "core")]
 pub use merde_core::*;
 
+#[cfg(all(feature = "core", any(feature = "json", feature = "msgpack")))]
+mod format;
+#[cfg(all(feature = "core", any(feature = "json", feature = "msgpack")))]
+pub use format::{from_bytes_any, negotiate, to_vec_any, Format};
+
+#[cfg(feature = "core")]
+pub mod testing;
+
 #[doc(hidden)]
 #[cfg(feature = "deserialize")]
 #[macro_export]
 macro_rules! impl_deserialize {
+    // unit struct
+    (struct $struct_name:ident) => {
+        #[automatically_derived]
+        impl<'s> $crate::Deserialize<'s> for $struct_name {
+            #[inline(always)]
+            async fn deserialize(__de: &mut dyn $crate::DynDeserializer<'s>) -> Result<Self, $crate::MerdeError<'s>> {
+                __de.next().await?.into_null()?;
+                Ok($struct_name)
+            }
+        }
+    };
+
     // owned tuple struct (transparent)
     (struct $struct_name:ident transparent) => {
         #[automatically_derived]
@@ -42,12 +74,12 @@ macro_rules! impl_deserialize {
     };
 
     // owned struct
-    (struct $struct_name:ident { $($field:ident),* }) => {
+    (struct $struct_name:ident { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* }) => {
         $crate::impl_deserialize! {
-            struct $struct_name { $($field),* } via $crate::DefaultDeserOpinions
+            struct $struct_name { $($field $(: aliases [$($alias),*])?),* } via $crate::DefaultDeserOpinions
         }
     };
-    (struct $struct_name:ident { $($field:ident),* } via $opinions:expr) => {
+    (struct $struct_name:ident { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } via $opinions:expr) => {
         #[automatically_derived]
         impl<'s> $crate::Deserialize<'s> for $struct_name {
             #[inline(always)]
@@ -57,6 +89,7 @@ macro_rules! impl_deserialize {
 
                 let __opinions = $opinions;
                 __de.next().await?.into_map_start()?;
+                let mut __errors = $crate::FieldErrors::new(__de);
 
                 $(
                     let mut $field = $crate::none_of(|i: $struct_name| i.$field);
@@ -68,12 +101,47 @@ macro_rules! impl_deserialize {
                         $crate::Event::Str(__key) => {
                             let __key = __opinions.map_key_name(__key);
                             match __key.as_ref() {
-                                $(stringify!($field) => {
-                                    $field = Some(__de.t().await?);
+                                // aliases go through the same mapping as keys
+                                $(__k if __k == stringify!($field) $($(|| *__k == *__opinions.map_key_name($crate::CowStr::Borrowed($alias)))*)? => {
+                                    if __opinions.maps_string_values() {
+                                        match __de.next().await? {
+                                            $crate::Event::Str(__s) => {
+                                                let __s = __opinions.map_string_value(stringify!($field), __s);
+                                                __de.put_back($crate::Event::Str(__s))?;
+                                            }
+                                            __ev => __de.put_back(__ev)?,
+                                        }
+                                    }
+                                    let __value = if __opinions.recover_from_field_errors() {
+                                        match $crate::deserialize_or_skip(__de).await? {
+                                            Ok(__value) => Ok(Some(__value)),
+                                            Err(__err) => {
+                                                $field = None;
+                                                let __slot = $crate::FieldSlot::new(&mut $field);
+                                                __opinions
+                                                    .on_field_error(stringify!($field), __err, __slot)
+                                                    .map(|()| $field.take())
+                                            }
+                                        }
+                                    } else {
+                                        __de.t().await.map(Some)
+                                    };
+                                    $field = match __value {
+                                        Ok(__value) => __value,
+                                        Err(__err) => {
+                                            __errors.field(__de, stringify!($field), __err).await?;
+                                            None
+                                        }
+                                    };
                                 })*
                                 _ => {
                                     if __opinions.deny_unknown_fields() {
-                                        return Err($crate::MerdeError::UnknownProperty(__key).into());
+                                        let __field = __key.to_string();
+                                        let __err = $crate::MerdeError::UnknownProperty(__key);
+                                        __errors.field(__de, &__field, __err).await?;
+                                    } else {
+                                        // skip the value, nested values included
+                                        __de.skip_value().await?;
                                     }
                                 }
                             }
@@ -89,26 +157,35 @@ macro_rules! impl_deserialize {
                     }
                 }
 
-                Ok($struct_name {
-                    $($field: {
-                        if $field.is_none() {
-                            let __slot = $crate::FieldSlot::new(&mut $field);
-                            __opinions.default_field_value(stringify!($field), __slot);
+                $(
+                    if $field.is_none() {
+                        let __slot = $crate::FieldSlot::new(&mut $field);
+                        __opinions.default_field_value(stringify!($field), __slot);
+                    }
+                    let $field = match $crate::Deserialize::from_option($field, stringify!($field).into()) {
+                        Ok(__value) => Some(__value),
+                        Err(__err) => {
+                            __errors.missing(__de, stringify!($field), __err)?;
+                            None
                         }
-                        $crate::Deserialize::from_option($field, stringify!($field).into())?
-                    },)+
-                })
+                    };
+                )+
+
+                match ($($field,)+) {
+                    ($(Some($field),)+) if !__errors.failed() => Ok($struct_name { $($field,)+ }),
+                    _ => Err(__errors.finish(__de)),
+                }
             }
         }
     };
 
     // lifetimed struct
-    (struct $struct_name:ident <$lifetime:lifetime> { $($field:ident),* }) => {
+    (struct $struct_name:ident <$lifetime:lifetime> { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* }) => {
         $crate::impl_deserialize! {
-            struct $struct_name <$lifetime> { $($field),* } via $crate::DefaultDeserOpinions
+            struct $struct_name <$lifetime> { $($field $(: aliases [$($alias),*])?),* } via $crate::DefaultDeserOpinions
         }
     };
-    (struct $struct_name:ident <$s:lifetime> { $($field:ident),* } via $opinions:expr) => {
+    (struct $struct_name:ident <$s:lifetime> { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } via $opinions:expr) => {
         #[automatically_derived]
         impl<$s> $crate::Deserialize<$s> for $struct_name<$s> {
             #[inline(always)]
@@ -118,6 +195,7 @@ macro_rules! impl_deserialize {
 
                 let __opinions = $opinions;
                 __de.next().await?.into_map_start()?;
+                let mut __errors = $crate::FieldErrors::new(__de);
 
                 $(
                     let mut $field = $crate::none_of(|i: $struct_name<$s>| i.$field);
@@ -129,12 +207,47 @@ macro_rules! impl_deserialize {
                         $crate::Event::Str(__key) => {
                             let __key = __opinions.map_key_name(__key);
                             match __key.as_ref() {
-                                $(stringify!($field) => {
-                                    $field = Some(__de.t().await?);
+                                // aliases go through the same mapping as keys
+                                $(__k if __k == stringify!($field) $($(|| *__k == *__opinions.map_key_name($crate::CowStr::Borrowed($alias)))*)? => {
+                                    if __opinions.maps_string_values() {
+                                        match __de.next().await? {
+                                            $crate::Event::Str(__s) => {
+                                                let __s = __opinions.map_string_value(stringify!($field), __s);
+                                                __de.put_back($crate::Event::Str(__s))?;
+                                            }
+                                            __ev => __de.put_back(__ev)?,
+                                        }
+                                    }
+                                    let __value = if __opinions.recover_from_field_errors() {
+                                        match $crate::deserialize_or_skip(__de).await? {
+                                            Ok(__value) => Ok(Some(__value)),
+                                            Err(__err) => {
+                                                $field = None;
+                                                let __slot = $crate::FieldSlot::new(&mut $field);
+                                                __opinions
+                                                    .on_field_error(stringify!($field), __err, __slot)
+                                                    .map(|()| $field.take())
+                                            }
+                                        }
+                                    } else {
+                                        __de.t().await.map(Some)
+                                    };
+                                    $field = match __value {
+                                        Ok(__value) => __value,
+                                        Err(__err) => {
+                                            __errors.field(__de, stringify!($field), __err).await?;
+                                            None
+                                        }
+                                    };
                                 })*
                                 _ => {
                                     if __opinions.deny_unknown_fields() {
-                                        return Err($crate::MerdeError::UnknownProperty(__key).into());
+                                        let __field = __key.to_string();
+                                        let __err = $crate::MerdeError::UnknownProperty(__key);
+                                        __errors.field(__de, &__field, __err).await?;
+                                    } else {
+                                        // skip the value, nested values included
+                                        __de.skip_value().await?;
                                     }
                                 }
                             }
@@ -150,15 +263,24 @@ macro_rules! impl_deserialize {
                     }
                 }
 
-                Ok($struct_name {
-                    $($field: {
-                        if $field.is_none() {
-                            let __slot = $crate::FieldSlot::new(&mut $field);
-                            __opinions.default_field_value(stringify!($field), __slot);
+                $(
+                    if $field.is_none() {
+                        let __slot = $crate::FieldSlot::new(&mut $field);
+                        __opinions.default_field_value(stringify!($field), __slot);
+                    }
+                    let $field = match $crate::Deserialize::from_option($field, stringify!($field).into()) {
+                        Ok(__value) => Some(__value),
+                        Err(__err) => {
+                            __errors.missing(__de, stringify!($field), __err)?;
+                            None
                         }
-                        $crate::Deserialize::from_option($field, stringify!($field).into())?
-                    },)+
-                })
+                    };
+                )+
+
+                match ($($field,)+) {
+                    ($(Some($field),)+) if !__errors.failed() => Ok($struct_name { $($field,)+ }),
+                    _ => Err(__errors.finish(__de)),
+                }
             }
         }
     };
@@ -221,6 +343,7 @@ macro_rules! impl_deserialize {
             async fn deserialize(__de: &mut dyn $crate::DynDeserializer<'s>) -> Result<Self, $crate::MerdeError<'s>> {
                 #[allow(unused_imports)]
                 use $crate::MerdeError;
+                #[allow(unused_imports)]
                 use $crate::DynDeserializerExt;
 
                 let s = __de.next().await?.into_str()?;
@@ -231,6 +354,111 @@ macro_rules! impl_deserialize {
             }
         }
     };
+
+    // owned enum (string-like, with a fallback variant)
+    (enum $enum_name:ident string_like {
+        $($variant_str:literal => $variant:ident),* , _ => $fallback:ident $(($fallback_ty:ty))? $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<'s> $crate::Deserialize<'s> for $enum_name {
+            async fn deserialize(__de: &mut dyn $crate::DynDeserializer<'s>) -> Result<Self, $crate::MerdeError<'s>> {
+                let s = __de.next().await?.into_str()?;
+                Ok(match s.as_ref() {
+                    $($variant_str => $enum_name::$variant,)*
+                    _ => $enum_name::$fallback(::std::string::String::from(s).into()),
+                })
+            }
+        }
+    };
+
+    // lifetimed enum (string-like, with a fallback variant)
+    (enum $enum_name:ident <$lifetime:lifetime> string_like {
+        $($variant_str:literal => $variant:ident),* , _ => $fallback:ident $(($fallback_ty:ty))? $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<$lifetime> $crate::Deserialize<$lifetime> for $enum_name<$lifetime> {
+            async fn deserialize(__de: &mut dyn $crate::DynDeserializer<$lifetime>) -> Result<Self, $crate::MerdeError<$lifetime>> {
+                let s = __de.next().await?.into_str()?;
+                Ok(match s.as_ref() {
+                    $($variant_str => $enum_name::$variant,)*
+                    _ => $enum_name::$fallback(s.into()),
+                })
+            }
+        }
+    };
+
+    // owned enum (externally tagged, with integer tags)
+    (enum $enum_name:ident externally_tagged_int {
+        $($tag:literal => $variant:ident $(($payload:ty))?),* $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<'s> $crate::Deserialize<'s> for $enum_name {
+            async fn deserialize(__de: &mut dyn $crate::DynDeserializer<'s>) -> Result<Self, $crate::MerdeError<'s>> {
+                $crate::impl_deserialize!(@int_tagged __de, $enum_name { $($tag => $variant $(($payload))?),* })
+            }
+        }
+    };
+
+    // lifetimed enum (externally tagged, with integer tags)
+    (enum $enum_name:ident <$lifetime:lifetime> externally_tagged_int {
+        $($tag:literal => $variant:ident $(($payload:ty))?),* $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<$lifetime> $crate::Deserialize<$lifetime> for $enum_name<$lifetime> {
+            async fn deserialize(__de: &mut dyn $crate::DynDeserializer<$lifetime>) -> Result<Self, $crate::MerdeError<$lifetime>> {
+                $crate::impl_deserialize!(@int_tagged __de, $enum_name { $($tag => $variant $(($payload))?),* })
+            }
+        }
+    };
+
+    // variants with a payload are `[tag, payload]`, unit variants are a bare `tag`
+    (@int_tagged $de:ident, $enum_name:ident { $($tag:literal => $variant:ident $(($payload:ty))?),* }) => {{
+        #[allow(unused_imports)]
+        use $crate::{MerdeError, DynDeserializerExt};
+
+        match $de.next().await? {
+            $crate::Event::ArrayStart(_) => {
+                let __tag: u64 = $de.t().await?;
+                let __value = match __tag {
+                    $($tag => $crate::impl_deserialize!(@int_payload $de, $enum_name::$variant $(($payload))?),)*
+                    _ => return Err(MerdeError::UnknownProperty(__tag.to_string().into())),
+                };
+                $de.next().await?.into_array_end()?;
+                Ok(__value)
+            }
+            __ev => {
+                let __got = $crate::EventType::from(&__ev);
+                $de.put_back(__ev)?;
+                let __tag: u64 = $de.t().await?;
+                match __tag {
+                    $($tag => $crate::impl_deserialize!(@int_bare __got, $enum_name::$variant $(($payload))?),)*
+                    _ => Err(MerdeError::UnknownProperty(__tag.to_string().into())),
+                }
+            }
+        }
+    }};
+    (@int_payload $de:ident, $enum_name:ident :: $variant:ident ($payload:ty)) => {{
+        let __payload: $payload = $de.t().await?;
+        $enum_name::$variant(__payload)
+    }};
+    // `[tag]` is fine for unit variants too
+    (@int_payload $de:ident, $enum_name:ident :: $variant:ident) => {
+        $enum_name::$variant
+    };
+    (@int_bare $got:ident, $enum_name:ident :: $variant:ident ($payload:ty)) => {
+        Err($crate::MerdeError::UnexpectedEvent {
+            got: $got,
+            expected: &[$crate::EventType::ArrayStart],
+            help: Some(format!(
+                "({}::{} has a payload, it's written as [tag, payload])",
+                stringify!($enum_name),
+                stringify!($variant)
+            )),
+        })
+    };
+    (@int_bare $got:ident, $enum_name:ident :: $variant:ident) => {
+        Ok($enum_name::$variant)
+    };
 }
 
 #[doc(hidden)]
@@ -240,10 +468,212 @@ macro_rules! impl_deserialize {
     ($($tt:tt)*) => {};
 }
 
+#[doc(hidden)]
+#[cfg(feature = "deserialize")]
+#[macro_export]
+macro_rules! impl_deserialize_in_place {
+    // unit struct
+    (struct $struct_name:ident) => {
+        #[automatically_derived]
+        impl<'s> $crate::DeserializeInPlace<'s> for $struct_name {}
+    };
+
+    // owned tuple struct (transparent)
+    (struct $struct_name:ident transparent) => {
+        #[automatically_derived]
+        impl<'s> $crate::DeserializeInPlace<'s> for $struct_name {
+            #[inline(always)]
+            async fn deserialize_in_place<'de>(&'de mut self, __de: &'de mut dyn $crate::DynDeserializer<'s>) -> Result<(), $crate::MerdeError<'s>> {
+                $crate::DeserializeInPlace::deserialize_in_place(&mut self.0, __de).await
+            }
+        }
+    };
+
+    // lifetimed tuple struct (transparent)
+    (struct $struct_name:ident <$s:lifetime> transparent) => {
+        #[automatically_derived]
+        impl<$s> $crate::DeserializeInPlace<$s> for $struct_name<$s> {
+            #[inline(always)]
+            async fn deserialize_in_place<'de>(&'de mut self, __de: &'de mut dyn $crate::DynDeserializer<$s>) -> Result<(), $crate::MerdeError<$s>> {
+                $crate::DeserializeInPlace::deserialize_in_place(&mut self.0, __de).await
+            }
+        }
+    };
+
+    // owned struct
+    (struct $struct_name:ident { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* }) => {
+        $crate::impl_deserialize_in_place! {
+            struct $struct_name { $($field $(: aliases [$($alias),*])?),* } via $crate::DefaultDeserOpinions
+        }
+    };
+    (struct $struct_name:ident { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } via $opinions:expr) => {
+        #[automatically_derived]
+        impl<'s> $crate::DeserializeInPlace<'s> for $struct_name {
+            #[inline(always)]
+            async fn deserialize_in_place<'de>(&'de mut self, __de: &'de mut dyn $crate::DynDeserializer<'s>) -> Result<(), $crate::MerdeError<'s>> {
+                $crate::impl_deserialize_in_place!(@fields self, __de, $struct_name { $($field $(: aliases [$($alias),*])?),* } via $opinions)
+            }
+        }
+    };
+
+    // lifetimed struct
+    (struct $struct_name:ident <$lifetime:lifetime> { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* }) => {
+        $crate::impl_deserialize_in_place! {
+            struct $struct_name <$lifetime> { $($field $(: aliases [$($alias),*])?),* } via $crate::DefaultDeserOpinions
+        }
+    };
+    (struct $struct_name:ident <$s:lifetime> { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } via $opinions:expr) => {
+        #[automatically_derived]
+        impl<$s> $crate::DeserializeInPlace<$s> for $struct_name<$s> {
+            #[inline(always)]
+            async fn deserialize_in_place<'de>(&'de mut self, __de: &'de mut dyn $crate::DynDeserializer<$s>) -> Result<(), $crate::MerdeError<$s>> {
+                $crate::impl_deserialize_in_place!(@fields self, __de, $struct_name { $($field $(: aliases [$($alias),*])?),* } via $opinions)
+            }
+        }
+    };
+
+    // the body of the struct impls: fields that are present are deserialized
+    // in place, missing ones are filled like `impl_deserialize!` does
+    (@fields $self:ident, $de:ident, $struct_name:ident { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } via $opinions:expr) => {{
+        #![allow(unreachable_code)]
+        use $crate::DeserOpinions;
+
+        let __opinions = $opinions;
+        $de.next().await?.into_map_start()?;
+        let mut __errors = $crate::FieldErrors::new($de);
+
+        $(
+            let mut $field = false;
+        )+
+
+        loop {
+            match $de.next().await? {
+                $crate::Event::MapEnd => break,
+                $crate::Event::Str(__key) => {
+                    let __key = __opinions.map_key_name(__key);
+                    match __key.as_ref() {
+                        // aliases go through the same mapping as keys
+                        $(__k if __k == stringify!($field) $($(|| *__k == *__opinions.map_key_name($crate::CowStr::Borrowed($alias)))*)? => {
+                            if __opinions.maps_string_values() {
+                                match $de.next().await? {
+                                    $crate::Event::Str(__s) => {
+                                        let __s = __opinions.map_string_value(stringify!($field), __s);
+                                        $de.put_back($crate::Event::Str(__s))?;
+                                    }
+                                    __ev => $de.put_back(__ev)?,
+                                }
+                            }
+                            let __res = if __opinions.recover_from_field_errors() {
+                                match $crate::deserialize_in_place_or_skip(&mut $self.$field, $de).await? {
+                                    Ok(()) => Ok(true),
+                                    Err(__err) => {
+                                        // the substitute, if any, replaces what's there
+                                        let mut __value = $crate::none_of(|i: $struct_name| i.$field);
+                                        let __slot = $crate::FieldSlot::new(&mut __value);
+                                        __opinions
+                                            .on_field_error(stringify!($field), __err, __slot)
+                                            .map(|()| match __value {
+                                                Some(__value) => {
+                                                    $self.$field = __value;
+                                                    true
+                                                }
+                                                None => false,
+                                            })
+                                    }
+                                }
+                            } else {
+                                $crate::DeserializeInPlace::deserialize_in_place(&mut $self.$field, $de)
+                                    .await
+                                    .map(|()| true)
+                            };
+                            $field = match __res {
+                                Ok(__present) => __present,
+                                Err(__err) => {
+                                    __errors.field($de, stringify!($field), __err).await?;
+                                    false
+                                }
+                            };
+                        })*
+                        _ => {
+                            if __opinions.deny_unknown_fields() {
+                                let __field = __key.to_string();
+                                let __err = $crate::MerdeError::UnknownProperty(__key);
+                                __errors.field($de, &__field, __err).await?;
+                            } else {
+                                // skip the value, nested values included
+                                $de.skip_value().await?;
+                            }
+                        }
+                    }
+                }
+                ev => {
+                    return Err($crate::MerdeError::UnexpectedEvent {
+                        got: $crate::EventType::from(&ev),
+                        expected: &[$crate::EventType::Str, $crate::EventType::MapEnd],
+                        help: Some(format!("While deserializing {}", stringify!($struct_name))),
+                    }
+                    .into())
+                }
+            }
+        }
+
+        let mut __missing = false;
+        $(
+            if !$field {
+                let mut __value = $crate::none_of(|i: $struct_name| i.$field);
+                let __slot = $crate::FieldSlot::new(&mut __value);
+                __opinions.default_field_value(stringify!($field), __slot);
+                match $crate::Deserialize::from_option(__value, stringify!($field).into()) {
+                    Ok(__value) => $self.$field = __value,
+                    Err(__err) => {
+                        __errors.missing($de, stringify!($field), __err)?;
+                        __missing = true;
+                    }
+                }
+            }
+        )+
+
+        if __missing || __errors.failed() {
+            return Err(__errors.finish($de));
+        }
+        Ok(())
+    }};
+
+    // enums have nothing to reuse: they're replaced
+    (enum $enum_name:ident <$lifetime:lifetime> $($rest:tt)*) => {
+        #[automatically_derived]
+        impl<$lifetime> $crate::DeserializeInPlace<$lifetime> for $enum_name<$lifetime> {}
+    };
+    (enum $enum_name:ident $($rest:tt)*) => {
+        #[automatically_derived]
+        impl<'s> $crate::DeserializeInPlace<'s> for $enum_name {}
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "deserialize"))]
+#[macro_export]
+macro_rules! impl_deserialize_in_place {
+    ($($tt:tt)*) => {};
+}
+
 #[doc(hidden)]
 #[macro_export]
 #[cfg(feature = "core")]
 macro_rules! impl_into_static {
+    // unit struct
+    (struct $struct_name:ident) => {
+        #[automatically_derived]
+        impl $crate::IntoStatic for $struct_name {
+            type Output = $struct_name;
+
+            #[inline(always)]
+            fn into_static(self) -> Self::Output {
+                self
+            }
+        }
+    };
+
     // owned tuple struct (transparent)
     (struct $struct_name:ident transparent) => {
         #[automatically_derived]
@@ -271,7 +701,7 @@ macro_rules! impl_into_static {
     };
 
     // owned struct
-    (struct $struct_name:ident { $($field:ident),* } $($rest:tt)*) => {
+    (struct $struct_name:ident { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } $($rest:tt)*) => {
         #[automatically_derived]
         impl $crate::IntoStatic for $struct_name {
             type Output = $struct_name;
@@ -284,7 +714,7 @@ macro_rules! impl_into_static {
     };
 
     // lifetimed struct
-    (struct $struct_name:ident <$lifetime:lifetime> { $($field:ident),* } $($rest:tt)*) => {
+    (struct $struct_name:ident <$lifetime:lifetime> { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } $($rest:tt)*) => {
         #[automatically_derived]
         impl<$lifetime> $crate::IntoStatic for $struct_name<$lifetime> {
             type Output = $struct_name<'static>;
@@ -348,6 +778,87 @@ macro_rules! impl_into_static {
             }
         }
     };
+
+    // owned enum (string-like, with a fallback variant)
+    (enum $enum_name:ident string_like {
+        $($variant_str:literal => $variant:ident),* , _ => $fallback:ident $(($fallback_ty:ty))? $(,)?
+    }) => {
+        #[automatically_derived]
+        impl $crate::IntoStatic for $enum_name {
+            type Output = $enum_name;
+
+            #[inline(always)]
+            fn into_static(self) -> Self::Output {
+                self
+            }
+        }
+    };
+
+    // lifetimed enum (string-like, with a fallback variant)
+    (enum $enum_name:ident <$lifetime:lifetime> string_like {
+        $($variant_str:literal => $variant:ident),* , _ => $fallback:ident $(($fallback_ty:ty))? $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<$lifetime> $crate::IntoStatic for $enum_name<$lifetime> {
+            type Output = $enum_name<'static>;
+
+            #[inline(always)]
+            fn into_static(self) -> Self::Output {
+                match self {
+                    $(Self::$variant => $enum_name::$variant,)*
+                    Self::$fallback(value) => $enum_name::$fallback($crate::IntoStatic::into_static(value)),
+                }
+            }
+        }
+    };
+
+    // owned enum (externally tagged, with integer tags)
+    (enum $enum_name:ident externally_tagged_int {
+        $($tag:literal => $variant:ident $(($payload:ty))?),* $(,)?
+    }) => {
+        #[automatically_derived]
+        impl $crate::IntoStatic for $enum_name {
+            type Output = $enum_name;
+
+            #[inline(always)]
+            fn into_static(self) -> Self::Output {
+                self
+            }
+        }
+    };
+
+    // lifetimed enum (externally tagged, with integer tags)
+    (enum $enum_name:ident <$lifetime:lifetime> externally_tagged_int {
+        $($tag:literal => $variant:ident $(($payload:ty))?),* $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<$lifetime> $crate::IntoStatic for $enum_name<$lifetime> {
+            type Output = $enum_name<'static>;
+
+            #[inline(always)]
+            fn into_static(self) -> Self::Output {
+                match self {
+                    $(
+                        $crate::impl_into_static!(@int_pat __value, $variant $(($payload))?) => {
+                            $crate::impl_into_static!(@int_variant __value, $enum_name::$variant $(($payload))?)
+                        }
+                    )+
+                }
+            }
+        }
+    };
+    (@int_pat $value:ident, $variant:ident ($payload:ty)) => {
+        Self::$variant($value)
+    };
+    (@int_pat $value:ident, $variant:ident) => {
+        Self::$variant
+    };
+    (@int_variant $value:ident, $enum_name:ident :: $variant:ident ($payload:ty)) => {
+        $enum_name::$variant($crate::IntoStatic::into_static($value))
+    };
+    (@int_variant $value:ident, $enum_name:ident :: $variant:ident) => {
+        $enum_name::$variant
+    };
 }
 
 #[doc(hidden)]
@@ -361,6 +872,14 @@ macro_rules! impl_into_static {
 #[macro_export]
 #[cfg(feature = "core")]
 macro_rules! impl_with_lifetime {
+    // unit struct
+    (struct $struct_name:ident) => {
+        #[automatically_derived]
+        impl<'s> $crate::WithLifetime<'s> for $struct_name {
+            type Lifetimed = $struct_name;
+        }
+    };
+
     // owned tuple struct (transparent)
     (struct $struct_name:ident transparent) => {
         #[automatically_derived]
@@ -378,7 +897,7 @@ macro_rules! impl_with_lifetime {
     };
 
     // owned struct
-    (struct $struct_name:ident { $($field:ident),* } $($rest:tt)*) => {
+    (struct $struct_name:ident { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } $($rest:tt)*) => {
         #[automatically_derived]
         impl<'s> $crate::WithLifetime<'s> for $struct_name {
             type Lifetimed = $struct_name;
@@ -386,7 +905,7 @@ macro_rules! impl_with_lifetime {
     };
 
     // lifetimed struct
-    (struct $struct_name:ident <$lifetime:lifetime> { $($field:ident),* } $($rest:tt)*) => {
+    (struct $struct_name:ident <$lifetime:lifetime> { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } $($rest:tt)*) => {
         #[automatically_derived]
         impl<$lifetime, 'instantiated_lifetime> $crate::WithLifetime<'instantiated_lifetime>
             for $struct_name<$lifetime>
@@ -426,6 +945,50 @@ macro_rules! impl_with_lifetime {
             type Lifetimed = $enum_name;
         }
     };
+
+    // owned enum (string-like, with a fallback variant)
+    (enum $enum_name:ident string_like {
+        $($variant_str:literal => $variant:ident),* , _ => $fallback:ident $(($fallback_ty:ty))? $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<'s> $crate::WithLifetime<'s> for $enum_name {
+            type Lifetimed = $enum_name;
+        }
+    };
+
+    // lifetimed enum (string-like, with a fallback variant)
+    (enum $enum_name:ident <$lifetime:lifetime> string_like {
+        $($variant_str:literal => $variant:ident),* , _ => $fallback:ident $(($fallback_ty:ty))? $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<$lifetime, 'instantiated_lifetime> $crate::WithLifetime<'instantiated_lifetime>
+            for $enum_name<$lifetime>
+        {
+            type Lifetimed = $enum_name<'instantiated_lifetime>;
+        }
+    };
+
+    // owned enum (externally tagged, with integer tags)
+    (enum $enum_name:ident externally_tagged_int {
+        $($tag:literal => $variant:ident $(($payload:ty))?),* $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<'s> $crate::WithLifetime<'s> for $enum_name {
+            type Lifetimed = $enum_name;
+        }
+    };
+
+    // lifetimed enum (externally tagged, with integer tags)
+    (enum $enum_name:ident <$lifetime:lifetime> externally_tagged_int {
+        $($tag:literal => $variant:ident $(($payload:ty))?),* $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<$lifetime, 'instantiated_lifetime> $crate::WithLifetime<'instantiated_lifetime>
+            for $enum_name<$lifetime>
+        {
+            type Lifetimed = $enum_name<'instantiated_lifetime>;
+        }
+    };
 }
 
 #[doc(hidden)]
@@ -439,6 +1002,20 @@ macro_rules! impl_with_lifetime {
 #[macro_export]
 #[cfg(feature = "core")]
 macro_rules! impl_serialize {
+    // unit struct
+    (struct $struct_name:ident) => {
+        #[automatically_derived]
+        impl $crate::Serialize for $struct_name {
+            #[allow(clippy::manual_async_fn)]
+            fn serialize<'fut>(
+                &'fut self,
+                serializer: &'fut mut dyn $crate::DynSerializer,
+            ) -> impl ::std::future::Future<Output = Result<(), $crate::MerdeError<'static>>> + 'fut {
+                serializer.write($crate::Event::Null)
+            }
+        }
+    };
+
     // owned tuple struct (transparent)
     (struct $struct_name:ident transparent) => {
         #[automatically_derived]
@@ -471,8 +1048,8 @@ macro_rules! impl_serialize {
         }
     };
 
-    // lifetimed struct
-    (struct $struct_name:ident < $lifetime:lifetime > { $($field:ident),* }) => {
+    // lifetimed struct (deserialization opinions don't matter here)
+    (struct $struct_name:ident < $lifetime:lifetime > { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } $(via $opinions:expr)?) => {
         #[automatically_derived]
         impl<$lifetime> $crate::Serialize for $struct_name<$lifetime> {
             #[allow(clippy::manual_async_fn)]
@@ -481,14 +1058,18 @@ macro_rules! impl_serialize {
                 serializer: &'fut mut dyn $crate::DynSerializer,
             ) -> impl ::std::future::Future<Output = Result<(), $crate::MerdeError<'static>>> + 'fut {
                 async move {
+                    // fields that are absent (see `Tri`) are left out
+                    let __absent = 0 $(+ $crate::Serialize::is_absent(&self.$field) as usize)+;
                     serializer
                         .write($crate::Event::MapStart($crate::MapStart {
-                            size_hint: Some($crate::count_ident_tokens!($($field)*)),
+                            size_hint: Some($crate::count_ident_tokens!($($field)*) - __absent),
                         }))
                         .await?;
                     $(
-                        serializer.write($crate::Event::Str($crate::CowStr::Borrowed(stringify!($field)))).await?;
-                        self.$field.serialize(serializer).await?;
+                        if !$crate::Serialize::is_absent(&self.$field) {
+                            serializer.write($crate::Event::Str($crate::CowStr::Borrowed(stringify!($field)))).await?;
+                            self.$field.serialize(serializer).await?;
+                        }
                     )+
                     serializer.write($crate::Event::MapEnd).await
                 }
@@ -497,7 +1078,7 @@ macro_rules! impl_serialize {
     };
 
     // owned struct
-    (struct $struct_name:ident { $($field:ident),* }) => {
+    (struct $struct_name:ident { $($field:ident $(: aliases [$($alias:literal),* $(,)?])?),* } $(via $opinions:expr)?) => {
         #[automatically_derived]
         impl $crate::Serialize for $struct_name {
             #[allow(clippy::manual_async_fn)]
@@ -506,14 +1087,18 @@ macro_rules! impl_serialize {
                 serializer: &'fut mut dyn $crate::DynSerializer,
             ) -> impl ::std::future::Future<Output = Result<(), $crate::MerdeError<'static>>> + 'fut {
                 async move {
+                    // fields that are absent (see `Tri`) are left out
+                    let __absent = 0 $(+ $crate::Serialize::is_absent(&self.$field) as usize)+;
                     serializer
                         .write($crate::Event::MapStart($crate::MapStart {
-                            size_hint: Some($crate::count_ident_tokens!($($field)*)),
+                            size_hint: Some($crate::count_ident_tokens!($($field)*) - __absent),
                         }))
                         .await?;
                     $(
-                        serializer.write($crate::Event::Str($crate::CowStr::Borrowed(stringify!($field)))).await?;
-                        self.$field.serialize(serializer).await?;
+                        if !$crate::Serialize::is_absent(&self.$field) {
+                            serializer.write($crate::Event::Str($crate::CowStr::Borrowed(stringify!($field)))).await?;
+                            self.$field.serialize(serializer).await?;
+                        }
                     )+
                     serializer.write($crate::Event::MapEnd).await
                 }
@@ -610,6 +1195,115 @@ macro_rules! impl_serialize {
             }
         }
     };
+
+    // owned enum (string-like, with a fallback variant)
+    (enum $enum_name:ident string_like {
+        $($variant_str:literal => $variant:ident),* , _ => $fallback:ident $(($fallback_ty:ty))? $(,)?
+    }) => {
+        #[automatically_derived]
+        impl $crate::Serialize for $enum_name {
+            #[allow(clippy::manual_async_fn)]
+            fn serialize<'fut>(
+                &'fut self,
+                serializer: &'fut mut dyn $crate::DynSerializer,
+            ) -> impl ::std::future::Future<Output = Result<(), $crate::MerdeError<'static>>> + 'fut {
+                async move {
+                    let s: &str = match self {
+                        $(Self::$variant => $variant_str,)*
+                        Self::$fallback(value) => value,
+                    };
+                    serializer.write($crate::Event::Str($crate::CowStr::Borrowed(s))).await
+                }
+            }
+        }
+    };
+
+    // lifetimed enum (string-like, with a fallback variant)
+    (enum $enum_name:ident <$lifetime:lifetime> string_like {
+        $($variant_str:literal => $variant:ident),* , _ => $fallback:ident $(($fallback_ty:ty))? $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<$lifetime> $crate::Serialize for $enum_name<$lifetime> {
+            #[allow(clippy::manual_async_fn)]
+            fn serialize<'fut>(
+                &'fut self,
+                serializer: &'fut mut dyn $crate::DynSerializer,
+            ) -> impl ::std::future::Future<Output = Result<(), $crate::MerdeError<'static>>> + 'fut {
+                async move {
+                    let s: &str = match self {
+                        $(Self::$variant => $variant_str,)*
+                        Self::$fallback(value) => value,
+                    };
+                    serializer.write($crate::Event::Str($crate::CowStr::Borrowed(s))).await
+                }
+            }
+        }
+    };
+
+    // owned enum (externally tagged, with integer tags)
+    (enum $enum_name:ident externally_tagged_int {
+        $($tag:literal => $variant:ident $(($payload:ty))?),* $(,)?
+    }) => {
+        #[automatically_derived]
+        impl $crate::Serialize for $enum_name {
+            #[allow(clippy::manual_async_fn)]
+            fn serialize<'fut>(
+                &'fut self,
+                serializer: &'fut mut dyn $crate::DynSerializer,
+            ) -> impl ::std::future::Future<Output = Result<(), $crate::MerdeError<'static>>> + 'fut {
+                async move {
+                    $crate::impl_serialize!(@int_tagged self, serializer, { $($tag => $variant $(($payload))?),* })
+                }
+            }
+        }
+    };
+
+    // lifetimed enum (externally tagged, with integer tags)
+    (enum $enum_name:ident <$lifetime:lifetime> externally_tagged_int {
+        $($tag:literal => $variant:ident $(($payload:ty))?),* $(,)?
+    }) => {
+        #[automatically_derived]
+        impl<$lifetime> $crate::Serialize for $enum_name<$lifetime> {
+            #[allow(clippy::manual_async_fn)]
+            fn serialize<'fut>(
+                &'fut self,
+                serializer: &'fut mut dyn $crate::DynSerializer,
+            ) -> impl ::std::future::Future<Output = Result<(), $crate::MerdeError<'static>>> + 'fut {
+                async move {
+                    $crate::impl_serialize!(@int_tagged self, serializer, { $($tag => $variant $(($payload))?),* })
+                }
+            }
+        }
+    };
+
+    (@int_tagged $self:ident, $serializer:ident, { $($tag:literal => $variant:ident $(($payload:ty))?),* }) => {
+        match $self {
+            $(
+                $crate::impl_serialize!(@int_pat __value, $variant $(($payload))?) => {
+                    $crate::impl_serialize!(@int_write $serializer, __value, $tag $(($payload))?)
+                }
+            )+
+        }
+    };
+    (@int_pat $value:ident, $variant:ident ($payload:ty)) => {
+        Self::$variant($value)
+    };
+    (@int_pat $value:ident, $variant:ident) => {
+        Self::$variant
+    };
+    (@int_write $serializer:ident, $value:ident, $tag:literal ($payload:ty)) => {{
+        $serializer
+            .write($crate::Event::ArrayStart($crate::ArrayStart {
+                size_hint: Some(2),
+            }))
+            .await?;
+        $serializer.write($crate::Event::U64($tag)).await?;
+        $crate::Serialize::serialize($value, $serializer).await?;
+        $serializer.write($crate::Event::ArrayEnd).await
+    }};
+    (@int_write $serializer:ident, $value:ident, $tag:literal) => {
+        $serializer.write($crate::Event::U64($tag)).await
+    };
 }
 
 #[doc(hidden)]
@@ -619,6 +1313,73 @@ macro_rules! impl_serialize {
     ($($rest:tt)*) => {};
 }
 
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "core")]
+macro_rules! impl_partial {
+    (struct $struct_name:ident { $($field:ident : $ty:ty),* $(,)? } as $(#[$attr:meta])* $vis:vis struct $partial:ident) => {
+        #[doc = concat!("A [`", stringify!($struct_name), "`] where every field is optional.")]
+        $(#[$attr])*
+        #[derive(Default)]
+        $vis struct $partial {
+            $($vis $field: ::std::option::Option<$ty>,)*
+        }
+
+        impl $partial {
+            #[doc = concat!("Overwrites the fields of `target` that are set in `self`, see [`", stringify!($struct_name), "`]'s `merge`.")]
+            #[allow(dead_code)]
+            $vis fn merge_into(self, target: &mut $struct_name) {
+                $crate::HasPartial::merge(target, self)
+            }
+        }
+
+        #[automatically_derived]
+        impl $crate::HasPartial for $struct_name {
+            type Partial = $partial;
+
+            fn merge(&mut self, partial: $partial) {
+                $(
+                    if let Some(__value) = partial.$field {
+                        self.$field = __value;
+                    }
+                )*
+            }
+        }
+
+        $crate::impl_trait!(Deserialize for struct $partial { $($field),* });
+
+        // fields that aren't set are left out, rather than written as `null`
+        #[automatically_derived]
+        impl $crate::Serialize for $partial {
+            #[allow(clippy::manual_async_fn)]
+            fn serialize<'fut>(
+                &'fut self,
+                serializer: &'fut mut dyn $crate::DynSerializer,
+            ) -> impl ::std::future::Future<Output = Result<(), $crate::MerdeError<'static>>> + 'fut {
+                async move {
+                    serializer
+                        .write($crate::Event::MapStart($crate::MapStart { size_hint: None }))
+                        .await?;
+                    $(
+                        if let Some(__value) = &self.$field {
+                            serializer.write($crate::Event::Str($crate::CowStr::Borrowed(stringify!($field)))).await?;
+                            $crate::Serialize::serialize(__value, serializer).await?;
+                        }
+                    )*
+                    serializer.write($crate::Event::MapEnd).await
+                }
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "core"))]
+macro_rules! impl_partial {
+    ($($rest:tt)*) => {};
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! impl_trait {
@@ -632,6 +1393,14 @@ macro_rules! impl_trait {
     (Serialize for $($rest:tt)*) => {
         $crate::impl_serialize!($($rest)*);
     };
+
+    (DeserializeInPlace for $($rest:tt)*) => {
+        $crate::impl_deserialize_in_place!($($rest)*);
+    };
+
+    (Partial for $($rest:tt)*) => {
+        $crate::impl_partial!($($rest)*);
+    };
 }
 
 /// Derives the specified traits for a struct.
@@ -688,6 +1457,21 @@ macro_rules! impl_trait {
 /// );
 /// ```
 ///
+/// Unit structs are (de)serialized as `null`, like `()`, which comes in handy for
+/// messages that don't have a body:
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// struct Heartbeat;
+///
+/// merde::derive! {
+///     impl (Serialize, Deserialize) for struct Heartbeat
+/// }
+///
+/// assert_eq!(merde::json::to_string(&Heartbeat).unwrap(), "null");
+/// # }
+/// ```
+///
 /// Externally tagged enums are also supported. For both owned and lifetimed variants:
 ///
 /// ```rust
@@ -720,8 +1504,226 @@ macro_rules! impl_trait {
 ///
 /// This will serialize `MyEnum::Variant1("hello".into())` as `{"variant1":"hello"}`,
 /// and `MyEnum::Variant2(42)` as `{"variant2":42}`.
+///
+/// Enums made of unit variants can be (de)serialized as plain strings with `string_like`.
+/// A catch-all `_` variant, holding a `CowStr` (or a `String`, for owned enums), keeps
+/// strings that aren't known yet instead of failing, so that adding a variant upstream
+/// doesn't break older consumers. Its type can be spelled out (`_ => Other(String)`) or
+/// left out (`_ => Other`), it's only there for readability:
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// #[derive(Debug, PartialEq)]
+/// enum Region {
+///     Eu,
+///     Us,
+///     Other(String),
+/// }
+///
+/// merde::derive! {
+///     impl (Serialize, Deserialize) for enum Region string_like {
+///         "eu" => Eu,
+///         "us" => Us,
+///         _ => Other(String),
+///     }
+/// }
+///
+/// let region: Region = merde::json::from_str(r#""ap""#).unwrap();
+/// assert_eq!(region, Region::Other("ap".into()));
+/// assert_eq!(merde::json::to_string(&region).unwrap(), r#""ap""#);
+/// # }
+/// ```
+///
+/// The catch-all variant is written out as-is, without checking it against the known
+/// strings: `Region::Other("eu".into())` is serialized as `"eu"`, and comes back as
+/// `Region::Eu`.
+///
+/// Binary protocols tend to tag variants with small integers instead: with
+/// `externally_tagged_int`, variants with a payload (whose type is spelled out) are
+/// written as a `[tag, payload]` array, and unit variants as their bare tag.
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// enum Frame {
+///     Ping,
+///     Data(Vec<u8>),
+/// }
+///
+/// merde::derive! {
+///     impl (Serialize, Deserialize) for enum Frame
+///     externally_tagged_int {
+///         0 => Ping,
+///         1 => Data(Vec<u8>),
+///     }
+/// }
+///
+/// let frames = vec![Frame::Ping, Frame::Data(vec![4, 2])];
+/// assert_eq!(merde::json::to_string(&frames).unwrap(), "[0,[1,[4,2]]]");
+/// # }
+/// ```
+///
+/// Types from other crates can't implement merde's traits (because of the orphan rule),
+/// but as long as all their fields are public, you can declare a local mirror struct with
+/// the same fields, and derive the traits for it with the `as` syntax. On top of the usual
+/// impls for the mirror, this generates `From` conversions between the two types, both
+/// ways, whichever traits are derived:
+///
+/// ```rust
+/// mod other_crate {
+///     pub struct Point {
+///         pub x: i32,
+///         pub y: i32,
+///     }
+/// }
+///
+/// struct PointDef {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// merde::derive! {
+///     impl (Serialize, Deserialize) for struct other_crate::Point as PointDef { x, y }
+/// }
+///
+/// let point = other_crate::Point { x: 1, y: 2 };
+/// let json = merde::json::to_string(&PointDef::from(point)).unwrap();
+/// assert_eq!(json, r#"{"x":1,"y":2}"#);
+///
+/// let point: other_crate::Point = merde::json::from_str::<PointDef>(&json).unwrap().into();
+/// assert_eq!((point.x, point.y), (1, 2));
+/// ```
+///
+/// `DeserializeInPlace` can be derived for structs on top of `Deserialize`: deserializing
+/// into an existing value then reuses its allocations (string buffers, vec capacities),
+/// which helps when reading many records of the same shape:
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// use merde::DynDeserializerExt;
+///
+/// struct Record {
+///     name: String,
+///     tags: Vec<String>,
+/// }
+///
+/// merde::derive! {
+///     impl (Deserialize, DeserializeInPlace) for struct Record { name, tags }
+/// }
+///
+/// let mut record = Record { name: String::new(), tags: Vec::new() };
+/// for line in [r#"{"name":"a","tags":["x"]}"#, r#"{"name":"b","tags":[]}"#] {
+///     merde::json::JsonDeserializer::new(line).deserialize_into(&mut record).unwrap();
+/// }
+/// assert_eq!(record.name, "b");
+/// # }
+/// ```
+///
+/// For PATCH-style APIs and layered configuration, `Partial` generates a companion struct
+/// where every field is an `Option`, which implements `Serialize` and `Deserialize` (fields
+/// that aren't set are left out), and can be merged into a complete value. Since the
+/// companion struct is declared by the macro, the field types have to be spelled out (and
+/// `Partial` can't be combined with other traits in the same invocation):
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// #[derive(Debug, PartialEq)]
+/// struct Config {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// merde::derive! {
+///     impl (Partial) for struct Config { host: String, port: u16 }
+///     as #[derive(Debug)] pub struct ConfigPatch
+/// }
+///
+/// let mut config = Config { host: "localhost".into(), port: 8080 };
+/// let patch: ConfigPatch = merde::json::from_str(r#"{"port": 9090}"#).unwrap();
+/// patch.merge_into(&mut config);
+/// assert_eq!(config, Config { host: "localhost".into(), port: 9090 });
+/// # }
+/// ```
+///
+/// Generic code can refer to it as [`Partial<Config>`](Partial), see [`HasPartial`].
+///
+/// How structs are deserialized (unknown fields, missing fields, key names, malformed
+/// fields) can be tweaked by passing [`DeserOpinions`] with `via`. For example, to match
+/// keys to fields regardless of their case:
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// use merde::{CaseInsensitiveOpinions, DefaultDeserOpinions};
+///
+/// struct Account {
+///     id: u64,
+/// }
+///
+/// merde::derive! {
+///     impl (Serialize, Deserialize) for struct Account { id }
+///     via CaseInsensitiveOpinions(DefaultDeserOpinions)
+/// }
+///
+/// let account: Account = merde::json::from_str(r#"{"ID": 7}"#).unwrap();
+/// assert_eq!(account.id, 7);
+/// # }
+/// ```
+///
+/// Fields can have aliases: other keys they're deserialized from, which helps when an
+/// API renames things between versions. Aliases go through the opinions'
+/// `map_key_name` just like keys do, so `"userId"` also matches `"USERID"` with
+/// [`CaseInsensitiveOpinions`]. Fields are always serialized with their name:
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// struct Session {
+///     user_id: u64,
+///     token: String,
+/// }
+///
+/// merde::derive! {
+///     impl (Serialize, Deserialize) for struct Session {
+///         user_id: aliases ["userId", "uid"],
+///         token
+///     }
+/// }
+///
+/// let session: Session = merde::json::from_str(r#"{"uid": 7, "token": "abc"}"#).unwrap();
+/// assert_eq!(
+///     merde::json::to_string(&session).unwrap(),
+///     r#"{"user_id":7,"token":"abc"}"#
+/// );
+/// # }
+/// ```
+///
+/// Like any macro generating items, `derive!` can also be invoked inside a function body.
 #[macro_export]
 macro_rules! derive {
+    // remote struct (via a local mirror): the traits are implemented for the
+    // mirror, which converts to and from the remote type field by field
+    (impl ($($traits:ident),*) for struct $remote:path as $mirror:ident { $($field:ident),* }) => {
+        $crate::derive!(impl ($($traits),*) for struct $mirror { $($field),* });
+
+        #[automatically_derived]
+        impl ::std::convert::From<$mirror> for $remote {
+            #[inline(always)]
+            fn from(mirror: $mirror) -> Self {
+                Self {
+                    $($field: mirror.$field,)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl ::std::convert::From<$remote> for $mirror {
+            #[inline(always)]
+            fn from(remote: $remote) -> Self {
+                Self {
+                    $($field: remote.$field,)*
+                }
+            }
+        }
+    };
+
     // generic
     (impl ($first_trait:ident, $($rest_traits:ident),*) for $($rest:tt)*) => {
         $crate::impl_trait!($first_trait for $($rest)*);
@@ -733,23 +1735,186 @@ macro_rules! derive {
     (impl () for $($rest:tt)*) => {};
 }
 
-/// Returns an `Option<T>` from a closure that returns a `T` (which
-/// is never called) — this is a type inference trick used when deserializing
-/// struct fields
-#[doc(hidden)]
-pub fn none_of<I, T>(_f: impl FnOnce(I) -> T) -> Option<T> {
-    None
+/// Implements [`Display`](std::fmt::Display) (as compact JSON) and
+/// [`FromStr`](std::str::FromStr) (from JSON) for types that already implement
+/// [`Serialize`] and [`Deserialize`] — handy for CLI arguments and log lines.
+///
+/// `FromStr` can't borrow from its input, so this is for types that don't
+/// borrow either. Parsing errors are [`MerdeError<'static>`]s.
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// #[derive(Debug, PartialEq)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// merde::derive! {
+///     impl (Serialize, Deserialize) for struct Point { x, y }
+/// }
+///
+/// merde::impl_display_via_json!(Point);
+///
+/// let point: Point = r#"{"x": 1, "y": 2}"#.parse().unwrap();
+/// assert_eq!(point, Point { x: 1, y: 2 });
+/// assert_eq!(point.to_string(), r#"{"x":1,"y":2}"#);
+/// # }
+/// ```
+#[cfg(all(feature = "core", feature = "json"))]
+#[macro_export]
+macro_rules! impl_display_via_json {
+    ($ty:ty) => {
+        impl ::std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let mut ser = $crate::json::JsonSerializer::from_fmt_writer(f);
+                $crate::DynSerializerExt::serialize(&mut ser, self).map_err(|_| ::std::fmt::Error)
+            }
+        }
+
+        impl ::std::str::FromStr for $ty {
+            type Err = $crate::MerdeError<'static>;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                $crate::json::from_str(s).map_err($crate::IntoStatic::into_static)
+            }
+        }
+    };
 }
 
-#[doc(hidden)]
+/// Asserts that two values serialize to the same thing, ignoring the order of map
+/// keys and how numbers are represented (`1u8` is the same as `1.0`). On failure,
+/// the panic message lists the paths that differ:
+///
+/// ```rust,should_panic
+/// let tags = vec!["a", "b"];
+/// // panics with:
+/// //
+/// // values differ (- left, + right):
+/// // /1
+/// //   - "b"
+/// //   + "c"
+/// merde::assert_same_value!(tags, vec!["a", "c"]);
+/// ```
+///
+/// See the [`testing`] module.
+#[cfg(feature = "core")]
 #[macro_export]
-macro_rules! count_ident_tokens {
-    () => { 0 };
-    ($first:ident) => { 1 };
-    ($first:ident $($rest:ident)*) => {
-        1 + $crate::count_ident_tokens!($($rest)*)
+macro_rules! assert_same_value {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::testing::assert_same_value(&$left, &$right)
     };
 }
+
+/// Asserts that two strings are the same JSON document, ignoring whitespace and
+/// the order of object keys, like [`assert_same_value!`] does for values:
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// merde::assert_same_json!(r#"{"a": 1, "b": [true]}"#, r#"{"b":[true],"a":1}"#);
+/// # }
+/// ```
+#[cfg(all(feature = "core", feature = "json"))]
+#[macro_export]
+macro_rules! assert_same_json {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::testing::assert_same_json(
+            ::std::convert::AsRef::as_ref(&$left),
+            ::std::convert::AsRef::as_ref(&$right),
+        )
+    };
+}
+
+/// Returns an `Option<T>` from a closure that returns a `T` (which
+/// is never called) — this is a type inference trick used when deserializing
+/// struct fields
+#[doc(hidden)]
+pub fn none_of<I, T>(_f: impl FnOnce(I) -> T) -> Option<T> {
+    None
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! count_ident_tokens {
+    () => { 0 };
+    ($first:ident) => { 1 };
+    ($first:ident $($rest:ident)*) => {
+        1 + $crate::count_ident_tokens!($($rest)*)
+    };
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "serialize", feature = "deserialize"))]
+mod convert_tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct UserDto<'s> {
+        user_name: CowStr<'s>,
+        age: u8,
+        tags: Vec<CowStr<'s>>,
+    }
+
+    derive! {
+        impl (Serialize) for struct UserDto<'s> { user_name, age, tags }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct User {
+        name: String,
+        age: u32,
+    }
+
+    struct UserOpinions;
+
+    impl DeserOpinions for UserOpinions {
+        fn deny_unknown_fields(&self) -> bool {
+            false
+        }
+
+        #[allow(clippy::needless_lifetimes)]
+        fn default_field_value<'s, 'borrow>(
+            &self,
+            _key: &'borrow str,
+            _slot: FieldSlot<'s, 'borrow>,
+        ) {
+        }
+
+        fn map_key_name<'s>(&self, key: CowStr<'s>) -> CowStr<'s> {
+            if key == "user_name" {
+                CowStr::Borrowed("name")
+            } else {
+                key
+            }
+        }
+    }
+
+    derive! {
+        impl (Deserialize) for struct User { name, age } via UserOpinions
+    }
+
+    #[test]
+    fn test_convert() {
+        let dto = UserDto {
+            user_name: "jane".into(),
+            age: 42,
+            tags: vec!["admin".into()],
+        };
+        let user: User = convert(&dto).unwrap();
+        assert_eq!(
+            user,
+            User {
+                name: "jane".into(),
+                age: 42
+            }
+        );
+
+        let too_old = UserDto { age: 255, ..dto };
+        assert_eq!(convert::<_, User>(&too_old).unwrap().age, 255);
+        assert!(convert::<_, Vec<u8>>(&too_old).is_err());
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "json")]
 mod json_tests {
@@ -974,6 +2139,1812 @@ mod json_tests {
         let deserialized: bool = from_str(&serialized).unwrap();
         assert_eq!(original, deserialized);
     }
+
+    #[test]
+    fn test_nonzero() {
+        use std::num::{NonZeroI32, NonZeroU64};
+
+        let original = NonZeroU64::new(42).unwrap();
+        let serialized = crate::json::to_string(&original).unwrap();
+        assert_eq!(serialized, "42");
+        let deserialized: NonZeroU64 = from_str(&serialized).unwrap();
+        assert_eq!(original, deserialized);
+
+        assert!(from_str::<NonZeroU64>("0").is_err());
+        assert!(from_str::<NonZeroI32>("0").is_err());
+        assert_eq!(from_str::<NonZeroI32>("-7").unwrap().get(), -7);
+    }
+
+    #[test]
+    #[cfg(feature = "preserve_order")]
+    fn test_map_preserves_order() {
+        let input = r#"{"zebra":1,"apple":{"y":2,"x":3},"mango":[]}"#;
+        let value: Value = from_str(input).unwrap();
+        assert_eq!(crate::json::to_string(&value).unwrap(), input);
+    }
+
+    /// Splits the top-level JSON object `input` into its entries (key + value events).
+    fn record_entries(input: &str) -> Vec<Vec<Event<'_>>> {
+        let mut de = crate::json::JsonDeserializer::new(input);
+        let mut events = EventBuffer::record(&mut de)
+            .run_sync_with_metastack()
+            .unwrap()
+            .into_events();
+        assert!(matches!(events.pop_front(), Some(Event::MapStart(_))));
+        assert!(matches!(events.pop_back(), Some(Event::MapEnd)));
+
+        let mut entries: Vec<Vec<Event>> = Vec::new();
+        let mut depth = 0;
+        let mut expecting_key = true;
+        for ev in events {
+            match ev {
+                Event::MapStart(_) | Event::ArrayStart(_) => depth += 1,
+                Event::MapEnd | Event::ArrayEnd => depth -= 1,
+                _ => {}
+            }
+            if expecting_key {
+                entries.push(Vec::new());
+            }
+            entries.last_mut().unwrap().push(ev);
+            if depth == 0 {
+                expecting_key = !expecting_key;
+            }
+        }
+        entries
+    }
+
+    /// Deserializes the JSON object `input` as a `T` with its fields in every rotation
+    /// of their original order (and reversed), checking that we always get the same result.
+    fn assert_field_order_independent<T>(input: &str)
+    where
+        T: for<'s> Deserialize<'s> + PartialEq + std::fmt::Debug,
+    {
+        let n = record_entries(input).len();
+        let mut orders: Vec<Vec<usize>> = (0..n)
+            .map(|r| (0..n).map(|i| (i + r) % n).collect())
+            .collect();
+        orders.push((0..n).rev().collect());
+
+        let expected: T = from_str(input).unwrap();
+        for order in orders {
+            let mut entries: Vec<Option<Vec<Event>>> =
+                record_entries(input).into_iter().map(Some).collect();
+            let mut buf = EventBuffer::new();
+            buf.push(Event::MapStart(MapStart { size_hint: None }));
+            for i in &order {
+                for ev in entries[*i].take().unwrap() {
+                    buf.push(ev);
+                }
+            }
+            buf.push(Event::MapEnd);
+            let actual: T = buf.deserialize().unwrap();
+            assert_eq!(actual, expected, "with field order {order:?}");
+        }
+    }
+
+    #[test]
+    fn test_field_order_independence() {
+        #[derive(Debug, PartialEq)]
+        struct Inner {
+            x: i64,
+            y: Vec<String>,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Inner { x, y }
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Outer {
+            name: String,
+            inner: Inner,
+            flag: Option<bool>,
+            count: u32,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Outer { name, inner, flag, count }
+        }
+
+        assert_field_order_independent::<Outer>(
+            r#"{"name":"a","inner":{"x":1,"y":["b","c"]},"flag":null,"count":4}"#,
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        #[derive(Debug, PartialEq)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        derive! {
+            impl (Deserialize, DeserializeInPlace) for struct Point { x, y }
+        }
+
+        // unknown fields' values are skipped whole: a string value isn't
+        // mistaken for the next key, and maps and arrays can look like anything
+        let input = r#"{
+            "label": "y",
+            "x": 1,
+            "meta": {"y": 5, "nested": [{"x": 9}, []]},
+            "tags": ["x", "y", {}],
+            "y": 2,
+            "none": null
+        }"#;
+        assert_eq!(from_str::<Point>(input).unwrap(), Point { x: 1, y: 2 });
+
+        let mut point = Point { x: 0, y: 0 };
+        crate::json::JsonDeserializer::new(input)
+            .deserialize_into(&mut point)
+            .unwrap();
+        assert_eq!(point, Point { x: 1, y: 2 });
+    }
+
+    #[test]
+    fn test_remote_derive() {
+        mod other_crate {
+            #[derive(Debug, PartialEq)]
+            pub struct Config {
+                pub name: String,
+                pub retries: u8,
+                pub tags: Vec<String>,
+            }
+        }
+
+        struct ConfigDef {
+            name: String,
+            retries: u8,
+            tags: Vec<String>,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for struct other_crate::Config as ConfigDef {
+                name,
+                retries,
+                tags
+            }
+        }
+
+        let original = other_crate::Config {
+            name: "db".to_string(),
+            retries: 3,
+            tags: vec!["a".to_string()],
+        };
+        let serialized = crate::json::to_string(&ConfigDef::from(original)).unwrap();
+        assert_eq!(serialized, r#"{"name":"db","retries":3,"tags":["a"]}"#);
+
+        let deserialized: other_crate::Config =
+            crate::json::from_str_owned::<ConfigDef>(&serialized)
+                .unwrap()
+                .into();
+        assert_eq!(
+            deserialized,
+            other_crate::Config {
+                name: "db".to_string(),
+                retries: 3,
+                tags: vec!["a".to_string()],
+            }
+        );
+
+        // both conversions are there, even with a single trait
+        struct ConfigDeserDef {
+            name: String,
+            retries: u8,
+            tags: Vec<String>,
+        }
+
+        derive! {
+            impl (Deserialize) for struct other_crate::Config as ConfigDeserDef { name, retries, tags }
+        }
+
+        let mirror = ConfigDeserDef::from(deserialized);
+        assert_eq!(mirror.tags, ["a"]);
+        let remote: other_crate::Config = mirror.into();
+        assert_eq!(remote.retries, 3);
+    }
+
+    #[test]
+    fn test_result() {
+        let original: Vec<Result<i32, String>> = vec![Ok(42), Err("nope".into())];
+        let serialized = crate::json::to_string(&original).unwrap();
+        assert_eq!(serialized, r#"[{"Ok":42},{"Err":"nope"}]"#);
+        let deserialized: Vec<Result<i32, String>> = from_str(&serialized).unwrap();
+        assert_eq!(original, deserialized);
+
+        let lowercase: Vec<Result<i32, String>> =
+            from_str(r#"[{"ok":42},{"err":"nope"}]"#).unwrap();
+        assert_eq!(original, lowercase);
+
+        assert!(from_str::<Result<i32, String>>(r#"{"Maybe":1}"#).is_err());
+        assert!(from_str::<Result<i32, String>>(r#"{"OK":1}"#).is_err());
+    }
+
+    #[test]
+    fn test_control_flow() {
+        use std::ops::ControlFlow;
+
+        let original: Vec<ControlFlow<String, u64>> =
+            vec![ControlFlow::Continue(3), ControlFlow::Break("done".into())];
+        let serialized = crate::json::to_string(&original).unwrap();
+        assert_eq!(serialized, r#"[{"Continue":3},{"Break":"done"}]"#);
+        let deserialized: Vec<ControlFlow<String, u64>> = from_str(&serialized).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn test_shared_pointers() {
+        use std::{rc::Rc, sync::Arc};
+
+        #[derive(Debug, PartialEq, Clone)]
+        struct Inner {
+            n: u32,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for struct Inner { n }
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Shared {
+            name: Arc<str>,
+            label: Box<str>,
+            inner: Arc<Inner>,
+            local: Rc<Inner>,
+            ids: Box<[u32]>,
+            tags: Arc<[String]>,
+            more: Rc<[Inner]>,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for struct Shared {
+                name, label, inner, local, ids, tags, more
+            }
+        }
+
+        let input = r#"{"name":"a","label":"b","inner":{"n":1},"local":{"n":2},"ids":[1,2],"tags":["x"],"more":[{"n":3}]}"#;
+        let shared: Shared = from_str(input).unwrap();
+        assert_eq!(&*shared.name, "a");
+        assert_eq!(shared.inner.n, 1);
+        assert_eq!(&*shared.ids, &[1, 2]);
+        assert_eq!(&*shared.tags, &["x".to_string()]);
+        assert_eq!(crate::json::to_string(&shared).unwrap(), input);
+
+        let cow: std::borrow::Cow<[u32]> = from_str("[4,2]").unwrap();
+        assert_eq!(&*cow, &[4, 2]);
+        assert_eq!(crate::json::to_string(&cow).unwrap(), "[4,2]");
+    }
+
+    #[test]
+    fn test_deserialize_in_place() {
+        use crate::json::JsonDeserializer;
+
+        #[derive(Debug, PartialEq)]
+        struct Position {
+            x: i32,
+            y: i32,
+        }
+
+        derive! {
+            impl (Deserialize, DeserializeInPlace) for struct Position { x, y }
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Record {
+            name: String,
+            tags: Vec<String>,
+            position: Position,
+            score: Option<u32>,
+        }
+
+        derive! {
+            impl (Deserialize, DeserializeInPlace) for struct Record { name, tags, position, score }
+        }
+
+        let mut record: Record =
+            from_str(r#"{"name":"first","tags":["a","b"],"position":{"x":1,"y":2},"score":3}"#)
+                .unwrap();
+        let name_ptr = record.name.as_ptr();
+        let tags_ptr = record.tags.as_ptr();
+
+        let input = r#"{"name":"2nd","tags":["c"],"position":{"x":3,"y":4},"extra":[1]}"#;
+        JsonDeserializer::new(input)
+            .deserialize_into(&mut record)
+            .unwrap();
+        assert_eq!(
+            record,
+            Record {
+                name: "2nd".into(),
+                tags: vec!["c".into()],
+                position: Position { x: 3, y: 4 },
+                // missing fields aren't left over from the previous record
+                score: None,
+            }
+        );
+        assert_eq!(record.name.as_ptr(), name_ptr);
+        assert_eq!(record.tags.as_ptr(), tags_ptr);
+
+        let err = JsonDeserializer::new(r#"{"name":"3rd","tags":[]}"#)
+            .deserialize_into(&mut record)
+            .unwrap_err();
+        assert!(matches!(err, MerdeError::MissingProperty(p) if p == "position"));
+    }
+
+    #[cfg(feature = "either")]
+    #[test]
+    fn test_either() {
+        use crate::Either;
+
+        #[derive(Debug, PartialEq)]
+        struct Author {
+            name: String,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for struct Author { name }
+        }
+
+        // "string or object"
+        let input = r#"["amos", {"name": "fasterthanlime"}]"#;
+        let authors: Vec<Either<String, Author>> = from_str(input).unwrap();
+        assert_eq!(
+            authors,
+            [
+                Either::Left("amos".to_string()),
+                Either::Right(Author {
+                    name: "fasterthanlime".to_string()
+                })
+            ]
+        );
+        assert_eq!(
+            crate::json::to_string(&authors).unwrap(),
+            r#"["amos",{"name":"fasterthanlime"}]"#
+        );
+
+        // tried in order: an integer is a valid f64 too, so this never
+        // picks the right side
+        let n: Either<f64, u64> = from_str("1").unwrap();
+        assert_eq!(n, Either::Left(1.0));
+        let n: Either<u64, String> = from_str(r#""many""#).unwrap();
+        assert_eq!(n, Either::Right("many".to_string()));
+        assert!(from_str::<Either<u64, String>>("true").is_err());
+    }
+
+    #[test]
+    fn test_char() {
+        let original = 'é';
+        let serialized = crate::json::to_string(&original).unwrap();
+        assert_eq!(serialized, r#""é""#);
+        let deserialized: char = from_str(&serialized).unwrap();
+        assert_eq!(original, deserialized);
+
+        assert!(from_str::<char>(r#""""#).is_err());
+        assert!(from_str::<char>(r#""ab""#).is_err());
+    }
+
+    #[test]
+    fn test_net_addrs() {
+        use std::net::{IpAddr, SocketAddr};
+
+        let original: (IpAddr, SocketAddr) =
+            ("::1".parse().unwrap(), "127.0.0.1:8080".parse().unwrap());
+        let serialized = crate::json::to_string(&original).unwrap();
+        assert_eq!(serialized, r#"["::1","127.0.0.1:8080"]"#);
+        let deserialized: (IpAddr, SocketAddr) = from_str(&serialized).unwrap();
+        assert_eq!(original, deserialized);
+
+        assert!(from_str::<IpAddr>(r#""not an ip""#).is_err());
+    }
+
+    #[test]
+    fn test_pathbuf() {
+        use std::path::PathBuf;
+
+        let original = PathBuf::from("/etc/merde/config.yaml");
+        let serialized = crate::json::to_string(&original).unwrap();
+        assert_eq!(serialized, r#""/etc/merde/config.yaml""#);
+        let deserialized: PathBuf = from_str(&serialized).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn test_wrapping_saturating() {
+        use std::num::{Saturating, Wrapping};
+
+        let original = (Wrapping(u8::MAX), Saturating(-3i16));
+        let serialized = crate::json::to_string(&original).unwrap();
+        assert_eq!(serialized, "[255,-3]");
+        let deserialized: (Wrapping<u8>, Saturating<i16>) = from_str(&serialized).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn test_shared_map() {
+        use crate::SharedMap;
+
+        let input = String::from(r#"{"name":"merde","tags":["a","b"]}"#);
+        // the map doesn't borrow from `input`, so it can outlive it
+        let map: SharedMap = from_str(&input).unwrap();
+        drop(input);
+
+        assert_eq!(map.get("name"), Some(&Value::Str("merde".into())));
+        let serialized = crate::json::to_string(&map).unwrap();
+        let roundtripped: SharedMap = from_str(&serialized).unwrap();
+        assert_eq!(map, roundtripped);
+    }
+
+    #[test]
+    fn test_serialize_error() {
+        let err = from_str::<HashMap<String, u32>>(r#"{"a": "nope"}"#).unwrap_err();
+        let json = crate::json::to_string(&err).unwrap();
+        let map: HashMap<String, Value> = from_str(&json).unwrap();
+        assert_eq!(map.get("code"), Some(&Value::from("unexpected_event")));
+        assert_eq!(map.get("got"), Some(&Value::from("Str")));
+        assert!(map.contains_key("message"));
+
+        let err = from_str::<Value>("[1, 2").unwrap_err().into_static();
+        let json = crate::json::to_string(&err).unwrap();
+        let map: HashMap<String, Value> = from_str(&json).unwrap();
+        assert_eq!(map.get("code"), Some(&Value::from("string_parsing")));
+        assert_eq!(map.get("format"), Some(&Value::from("JSON")));
+        assert!(matches!(map.get("offset"), Some(Value::I64(_))));
+        // no terminal escape codes
+        assert!(!json.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_lenient_map() {
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        enum Plan {
+            Free,
+            Pro,
+        }
+
+        derive! {
+            impl (Deserialize, Serialize) for enum Plan string_like {
+                "free" => Free,
+                "pro" => Pro,
+            }
+        }
+
+        let input = r#"{"free": 1, "enterprise": {"seats": [1, 2]}, "pro": 10, "gold": 3}"#;
+        assert!(from_str::<HashMap<Plan, u32>>(input).is_err());
+
+        let quotas: LenientMap<Plan, u32> = from_str(input).unwrap();
+        assert_eq!(quotas.len(), 2);
+        assert_eq!(quotas[&Plan::Free], 1);
+        assert_eq!(quotas[&Plan::Pro], 10);
+        assert_eq!(quotas.skipped(), 2);
+
+        // values of known keys still have to be valid
+        assert!(from_str::<LenientMap<Plan, u32>>(r#"{"pro": "ten"}"#).is_err());
+
+        // and only keys that aren't a `Plan` are skipped, not broken input
+        assert!(matches!(
+            from_str::<LenientMap<Plan, u32>>(r#"{"free": 1, "gold"#),
+            Err(MerdeError::StringParsingError { .. })
+        ));
+
+        // (a key type that runs out of input while reading itself)
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        struct Truncated;
+
+        impl<'s> Deserialize<'s> for Truncated {
+            async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+                match de.next().await? {
+                    Event::Str(s) if s == "ok" => Ok(Truncated),
+                    Event::Str(_) => Err(MerdeError::eof()),
+                    ev => Err(MerdeError::UnexpectedEvent {
+                        got: EventType::from(&ev),
+                        expected: &[EventType::Str],
+                        help: None,
+                    }),
+                }
+            }
+        }
+
+        let input = r#"{"ok": 1, "2": 2}"#;
+        assert!(matches!(
+            from_str::<LenientMap<Truncated, u32>>(input),
+            Err(MerdeError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn test_unit_structs() {
+        #[derive(Debug, PartialEq)]
+        struct Ping;
+
+        derive! {
+            impl (Serialize, Deserialize, DeserializeInPlace) for struct Ping
+        }
+
+        #[derive(Debug, PartialEq)]
+        enum Message {
+            Ping(Ping),
+            Close(()),
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for enum Message externally_tagged {
+                "ping" => Ping,
+                "close" => Close,
+            }
+        }
+
+        let messages = vec![Message::Ping(Ping), Message::Close(())];
+        let json = json::to_string(&messages).unwrap();
+        assert_eq!(json, r#"[{"ping":null},{"close":null}]"#);
+        assert_eq!(from_str::<Vec<Message>>(&json).unwrap(), messages);
+        assert!(from_str::<Ping>("{}").is_err());
+
+        #[cfg(feature = "msgpack")]
+        {
+            let bytes = crate::msgpack::to_vec(&Ping).unwrap();
+            assert_eq!(bytes, [0xc0]);
+            crate::msgpack::from_slice::<()>(&bytes).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_display_from_str_fields() {
+        use crate::with::{DisplayFromStr, With};
+        use std::net::Ipv4Addr;
+
+        // a third-party type that only offers `Display` and `FromStr`
+        #[derive(Debug, Clone, PartialEq)]
+        struct Ipv4Network {
+            addr: Ipv4Addr,
+            prefix: u8,
+        }
+
+        impl std::fmt::Display for Ipv4Network {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}/{}", self.addr, self.prefix)
+            }
+        }
+
+        impl std::str::FromStr for Ipv4Network {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let (addr, prefix) = s.split_once('/').ok_or("missing prefix")?;
+                Ok(Self {
+                    addr: addr.parse().map_err(|e| format!("{e}"))?,
+                    prefix: prefix.parse().map_err(|e| format!("{e}"))?,
+                })
+            }
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Rule {
+            network: With<Ipv4Network, DisplayFromStr>,
+            allowed: Vec<With<Ipv4Network, DisplayFromStr>>,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize, DeserializeInPlace) for struct Rule { network, allowed }
+        }
+
+        let input = r#"{"network":"10.0.0.0/8","allowed":["10.1.0.0/16"]}"#;
+        let rule: Rule = from_str(input).unwrap();
+        assert_eq!(rule.network.prefix, 8);
+        assert_eq!(rule.allowed[0].addr, Ipv4Addr::new(10, 1, 0, 0));
+        assert_eq!(json::to_string(&rule).unwrap(), input);
+
+        let err = from_str::<Rule>(r#"{"network":"10.0.0.0","allowed":[]}"#).unwrap_err();
+        assert!(
+            matches!(&err, MerdeError::StringParsingError { message, .. } if message == "missing prefix"),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_partial_structs() {
+        #[derive(Debug, PartialEq)]
+        struct Settings {
+            name: String,
+            retries: u8,
+            tags: Vec<String>,
+        }
+
+        derive! {
+            impl (Partial) for struct Settings { name: String, retries: u8, tags: Vec<String> }
+            as #[derive(Debug, PartialEq)] struct SettingsPatch
+        }
+
+        let mut settings = Settings {
+            name: "default".into(),
+            retries: 3,
+            tags: vec![],
+        };
+
+        // layers are applied in order, later ones win
+        let layers = [
+            r#"{"retries": 5, "tags": ["a"]}"#,
+            r#"{"retries": 7}"#,
+            "{}",
+        ];
+        for layer in layers {
+            let patch: Partial<Settings> = from_str(layer).unwrap();
+            settings.merge(patch);
+        }
+        assert_eq!(
+            settings,
+            Settings {
+                name: "default".into(),
+                retries: 7,
+                tags: vec!["a".into()],
+            }
+        );
+
+        let patch = SettingsPatch {
+            name: Some("custom".into()),
+            ..Default::default()
+        };
+        assert_eq!(json::to_string(&patch).unwrap(), r#"{"name":"custom"}"#);
+        assert_eq!(
+            from_str::<SettingsPatch>(&json::to_string(&patch).unwrap()).unwrap(),
+            patch
+        );
+        patch.merge_into(&mut settings);
+        assert_eq!(settings.name, "custom");
+    }
+
+    #[test]
+    fn test_display_via_json() {
+        #[derive(Debug, PartialEq)]
+        struct Filter {
+            name: String,
+            tags: Vec<String>,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for struct Filter { name, tags }
+        }
+
+        impl_display_via_json!(Filter);
+
+        let filter: Filter = r#"{"name": "recent", "tags": ["a\"b"]}"#.parse().unwrap();
+        assert_eq!(filter.tags, ["a\"b"]);
+        assert_eq!(
+            format!("filter={filter}"),
+            r#"filter={"name":"recent","tags":["a\"b"]}"#
+        );
+        assert!("{\"name\": 1}".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_string_like_fallback() {
+        #[derive(Debug, PartialEq)]
+        enum Status<'s> {
+            Active,
+            Suspended,
+            Unknown(CowStr<'s>),
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for enum Status<'s> string_like {
+                "active" => Active,
+                "suspended" => Suspended,
+                _ => Unknown(CowStr<'s>),
+            }
+        }
+
+        let statuses: Vec<Status> = from_str(r#"["active","archived","suspended"]"#).unwrap();
+        assert_eq!(
+            statuses,
+            [
+                Status::Active,
+                Status::Unknown("archived".into()),
+                Status::Suspended
+            ]
+        );
+        assert_eq!(
+            json::to_string(&statuses).unwrap(),
+            r#"["active","archived","suspended"]"#
+        );
+
+        // known strings in the catch-all variant come back as their own variant
+        let json = json::to_string(&Status::Unknown("active".into())).unwrap();
+        assert_eq!(from_str::<Status>(&json).unwrap(), Status::Active);
+
+        #[derive(Debug, PartialEq)]
+        enum Plan {
+            Free,
+            Other(String),
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for enum Plan string_like {
+                "free" => Free,
+                _ => Other
+            }
+        }
+
+        let plan: Plan = from_str(r#""enterprise""#).unwrap();
+        assert_eq!(plan, Plan::Other("enterprise".into()));
+        assert_eq!(from_str::<Plan>(r#""free""#).unwrap(), Plan::Free);
+        assert!(from_str::<Plan>("1").is_err());
+    }
+
+    #[test]
+    fn test_map_string_value() {
+        #[derive(Debug, PartialEq)]
+        struct Signup {
+            email: String,
+            name: String,
+            tags: Vec<String>,
+            age: u32,
+        }
+
+        struct SignupOpinions;
+
+        impl DeserOpinions for SignupOpinions {
+            fn deny_unknown_fields(&self) -> bool {
+                false
+            }
+
+            #[allow(clippy::needless_lifetimes)]
+            fn default_field_value<'s, 'borrow>(
+                &self,
+                _key: &'borrow str,
+                _slot: FieldSlot<'s, 'borrow>,
+            ) {
+            }
+
+            fn map_key_name<'s>(&self, key: CowStr<'s>) -> CowStr<'s> {
+                key
+            }
+
+            fn map_string_value<'s>(&self, field: &str, value: CowStr<'s>) -> CowStr<'s> {
+                match field {
+                    "email" => value.trim().to_lowercase().into(),
+                    _ => value.trim().to_string().into(),
+                }
+            }
+
+            fn maps_string_values(&self) -> bool {
+                true
+            }
+        }
+
+        derive! {
+            impl (Deserialize) for struct Signup { email, name, tags, age } via SignupOpinions
+        }
+
+        let signup: Signup =
+            from_str(r#"{"email":" Amos@Example.COM ","name":"  Amos\t","tags":[" a "],"age":37}"#)
+                .unwrap();
+        assert_eq!(
+            signup,
+            Signup {
+                email: "amos@example.com".into(),
+                name: "Amos".into(),
+                // only direct string values are mapped
+                tags: vec![" a ".into()],
+                age: 37,
+            }
+        );
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_field_type_hints() {
+        #[derive(Debug, PartialEq)]
+        struct Blob<'s> {
+            data: CowBytes<'s>,
+        }
+
+        derive! {
+            impl (Deserialize, DeserializeInPlace) for struct Blob<'s> { data }
+        }
+
+        // `{"data": <str8 ff fe>}`: fields ask for bytes, so this isn't
+        // validated as UTF-8
+        let input = [0x81, 0xa4, b'd', b'a', b't', b'a', 0xd9, 0x02, 0xff, 0xfe];
+        let blob: Blob = crate::msgpack::from_slice(&input).unwrap();
+        assert_eq!(&blob.data[..], &[0xff, 0xfe]);
+
+        let mut blob = Blob {
+            data: CowBytes::from(&b""[..]),
+        };
+        crate::msgpack::MsgpackDeserializer::new(&input)
+            .deserialize_into(&mut blob)
+            .unwrap();
+        assert_eq!(&blob.data[..], &[0xff, 0xfe]);
+    }
+
+    #[test]
+    fn test_field_aliases() {
+        #[derive(Debug, PartialEq)]
+        struct Session<'s> {
+            user_id: u64,
+            token: CowStr<'s>,
+            scopes: Vec<String>,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize, DeserializeInPlace) for struct Session<'s> {
+                user_id: aliases ["userId", "uid"],
+                token,
+                scopes: aliases ["scope"]
+            }
+        }
+
+        for input in [
+            r#"{"user_id":7,"token":"t","scopes":[]}"#,
+            r#"{"userId":7,"token":"t","scope":[]}"#,
+            r#"{"uid":7,"token":"t","scopes":[]}"#,
+        ] {
+            let session: Session = from_str(input).unwrap();
+            assert_eq!(session.user_id, 7);
+            assert_eq!(
+                json::to_string(&session).unwrap(),
+                r#"{"user_id":7,"token":"t","scopes":[]}"#
+            );
+        }
+
+        let mut session: Session = from_str(r#"{"uid":7,"token":"t","scopes":[]}"#).unwrap();
+        json::JsonDeserializer::new(r#"{"userId":8,"token":"u","scope":["read"]}"#)
+            .deserialize_into(&mut session)
+            .unwrap();
+        assert_eq!(session.user_id, 8);
+        assert_eq!(session.scopes, ["read"]);
+    }
+
+    #[test]
+    fn test_case_insensitive_keys() {
+        #[derive(Debug, PartialEq)]
+        struct Account<'s> {
+            id: u64,
+            display_name: CowStr<'s>,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for struct Account<'s> { id, display_name }
+            via CaseInsensitiveOpinions(DefaultDeserOpinions)
+        }
+
+        for input in [
+            r#"{"ID":7,"DISPLAY_NAME":"amos"}"#,
+            r#"{"Id":7,"Display_Name":"amos"}"#,
+            r#"{"id":7,"display_name":"amos"}"#,
+        ] {
+            let account: Account = from_str(input).unwrap();
+            assert_eq!(account.id, 7);
+            assert_eq!(account.display_name, "amos");
+        }
+
+        // always serialized with the field names
+        let account: Account = from_str(r#"{"ID":7,"Display_Name":"amos"}"#).unwrap();
+        assert_eq!(
+            json::to_string(&account).unwrap(),
+            r#"{"id":7,"display_name":"amos"}"#
+        );
+
+        // keys that are already lowercase aren't copied
+        let key = CaseInsensitiveOpinions(DefaultDeserOpinions).map_key_name(CowStr::from("id"));
+        assert!(matches!(key, CowStr::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_case_insensitive_aliases() {
+        #[derive(Debug, PartialEq)]
+        struct Session<'s> {
+            user_id: u64,
+            token: CowStr<'s>,
+        }
+
+        derive! {
+            impl (Deserialize, DeserializeInPlace) for struct Session<'s> {
+                user_id: aliases ["userId"],
+                token
+            }
+            via CaseInsensitiveOpinions(DefaultDeserOpinions)
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Owned {
+            user_id: u64,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Owned { user_id: aliases ["userId"] }
+            via CaseInsensitiveOpinions(DefaultDeserOpinions)
+        }
+
+        // keys are lowercased, and so are the aliases they're compared to
+        for input in [r#"{"userId":7,"token":"t"}"#, r#"{"USERID":7,"token":"t"}"#] {
+            let session: Session = from_str(input).unwrap();
+            assert_eq!(session.user_id, 7);
+            let owned: Owned = from_str(input).unwrap();
+            assert_eq!(owned, Owned { user_id: 7 });
+        }
+
+        let mut session: Session = from_str(r#"{"user_id":7,"token":"t"}"#).unwrap();
+        json::JsonDeserializer::new(r#"{"UserId":8,"token":"u"}"#)
+            .deserialize_into(&mut session)
+            .unwrap();
+        assert_eq!(session.user_id, 8);
+    }
+
+    #[test]
+    fn test_int_tagged_enum() {
+        #[derive(Debug, PartialEq)]
+        enum Message<'s> {
+            Ping,
+            Pong(CowStr<'s>),
+            Ack(u64),
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for enum Message<'s>
+            externally_tagged_int {
+                0 => Ping,
+                1 => Pong(CowStr<'s>),
+                7 => Ack(u64),
+            }
+        }
+
+        let messages = vec![Message::Ping, Message::Pong("hi".into()), Message::Ack(3)];
+        let json = json::to_string(&messages).unwrap();
+        assert_eq!(json, r#"[0,[1,"hi"],[7,3]]"#);
+        assert_eq!(from_str::<Vec<Message>>(&json).unwrap(), messages);
+
+        // unit variants can be written as `[tag]` too
+        assert_eq!(from_str::<Message>("[0]").unwrap(), Message::Ping);
+        assert!(matches!(
+            from_str::<Message>("2"),
+            Err(MerdeError::UnknownProperty(tag)) if tag == "2"
+        ));
+        assert!(matches!(
+            from_str::<Message>("7"),
+            Err(MerdeError::UnexpectedEvent { .. })
+        ));
+
+        #[cfg(feature = "msgpack")]
+        {
+            let bytes = crate::msgpack::to_vec(&messages).unwrap();
+            assert_eq!(bytes[1], 0x00, "the tag is a positive fixint");
+            let roundtrip: Vec<Message> = crate::msgpack::from_slice(&bytes).unwrap();
+            assert_eq!(roundtrip, messages);
+        }
+    }
+
+    #[test]
+    fn test_tagged_any() {
+        use std::sync::OnceLock;
+
+        trait Shape {
+            fn area(&self) -> f64;
+        }
+
+        #[derive(Debug)]
+        struct Square {
+            side: f64,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Square { side }
+        }
+
+        impl Shape for Square {
+            fn area(&self) -> f64 {
+                self.side * self.side
+            }
+        }
+
+        #[derive(Debug)]
+        struct Rect {
+            size: Vec<f64>,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Rect { size }
+        }
+
+        impl Shape for Rect {
+            fn area(&self) -> f64 {
+                self.size.iter().product()
+            }
+        }
+
+        impl Registered for dyn Shape {
+            fn registry() -> &'static Registry<Self> {
+                static REGISTRY: OnceLock<Registry<dyn Shape>> = OnceLock::new();
+                REGISTRY.get_or_init(|| {
+                    Registry::<dyn Shape>::new("kind")
+                        .with::<Square>("square", |s| Box::new(s))
+                        .with::<Rect>("rect", |r| Box::new(r))
+                })
+            }
+        }
+
+        let input = r#"[
+            {"kind": "square", "side": 2},
+            {"size": [2, {"not": "a number"}], "kind": "rect"},
+            {"size": [2, 3], "kind": "rect"}
+        ]"#;
+        assert!(from_str::<Vec<TaggedAny<dyn Shape>>>(input).is_err());
+
+        let input = r#"[{"kind": "square", "side": 2}, {"size": [2, 3], "kind": "rect"}]"#;
+        let shapes: Vec<TaggedAny<dyn Shape>> = from_str(input).unwrap();
+        let areas: Vec<f64> = shapes.iter().map(|s| s.area()).collect();
+        assert_eq!(areas, [4.0, 6.0]);
+
+        assert!(matches!(
+            from_str::<TaggedAny<dyn Shape>>(r#"{"kind": "circle", "radius": 1}"#),
+            Err(MerdeError::UnknownProperty(tag)) if tag == "circle"
+        ));
+        assert!(matches!(
+            from_str::<TaggedAny<dyn Shape>>(r#"{"side": 1}"#),
+            Err(MerdeError::MissingProperty(key)) if key == "kind"
+        ));
+        assert!(from_str::<TaggedAny<dyn Shape>>(r#"["square"]"#).is_err());
+    }
+
+    #[test]
+    fn test_long_tuples_and_arrays() {
+        type Sixteen = (
+            u8,
+            i16,
+            u32,
+            i64,
+            bool,
+            String,
+            f64,
+            Option<u8>,
+            char,
+            Vec<u8>,
+            (u8,),
+            [u8; 2],
+            u16,
+            i8,
+            u64,
+            String,
+        );
+        let value: Sixteen = (
+            1,
+            -2,
+            3,
+            -4,
+            true,
+            "six".into(),
+            7.5,
+            None,
+            '9',
+            vec![10],
+            (11,),
+            [12, 12],
+            13,
+            -14,
+            15,
+            "sixteen".into(),
+        );
+        let json = crate::json::to_string(&value).unwrap();
+        assert_eq!(
+            json,
+            r#"[1,-2,3,-4,true,"six",7.5,null,"9",[10],[11],[12,12],13,-14,15,"sixteen"]"#
+        );
+        // (std only has `PartialEq` for tuples of up to 12 elements)
+        let back: Sixteen = from_str(&json).unwrap();
+        assert_eq!(back.15, "sixteen");
+        assert_eq!(crate::json::to_string(&back).unwrap(), json);
+        let back = crate::json::from_str_owned::<Sixteen>(&json).unwrap();
+        assert_eq!(crate::json::to_string(&back).unwrap(), json);
+
+        let key: [u8; 4] = from_str("[222, 173, 190, 239]").unwrap();
+        assert_eq!(key, [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(crate::json::to_string(&key).unwrap(), "[222,173,190,239]");
+        let names: [CowStr; 2] = from_str(r#"["a", "b"]"#).unwrap();
+        assert_eq!(names.into_static(), ["a", "b"]);
+        let empty: [u8; 0] = from_str("[]").unwrap();
+        assert_eq!(empty, [0u8; 0]);
+
+        // the length must match
+        for input in ["[1, 2, 3]", "[1, 2, 3, 4, 5]", "[]"] {
+            let err = from_str::<[u8; 4]>(input).unwrap_err();
+            assert!(
+                err.to_string().contains("Expected an array of 4 elements"),
+                "{input}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tri() {
+        #[derive(Debug, PartialEq)]
+        struct UserPatch {
+            name: Tri<String>,
+            email: Tri<String>,
+            age: Tri<u8>,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for struct UserPatch { name, email, age }
+        }
+
+        let patch: UserPatch = from_str(r#"{"name": "Amos", "email": null}"#).unwrap();
+        assert_eq!(
+            patch,
+            UserPatch {
+                name: Tri::Value("Amos".into()),
+                email: Tri::Null,
+                age: Tri::Absent,
+            }
+        );
+        assert_eq!(patch.email.clone().into_option(), Some(None));
+        assert_eq!(patch.age.into_option(), None);
+
+        // absent fields are left out, `null` ones aren't
+        let json = crate::json::to_string(&patch).unwrap();
+        assert_eq!(json, r#"{"name":"Amos","email":null}"#);
+        assert_eq!(from_str::<UserPatch>(&json).unwrap(), patch);
+
+        // the map's size accounts for the fields left out
+        let bytes = crate::msgpack::to_vec(&patch).unwrap();
+        assert_eq!(bytes[0], 0x82, "a fixmap with 2 entries");
+        let back: UserPatch = crate::msgpack::from_slice(&bytes).unwrap();
+        assert_eq!(back, patch);
+
+        // outside of structs, absent values are `null`
+        let values = vec![Tri::Absent, Tri::Null, Tri::Value(1u8)];
+        assert_eq!(crate::json::to_string(&values).unwrap(), "[null,null,1]");
+    }
+
+    #[test]
+    fn test_collect_errors() {
+        #[derive(Debug, PartialEq)]
+        struct Server {
+            host: String,
+            port: u16,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Server { host, port }
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Config {
+            name: String,
+            servers: Vec<Server>,
+            primary: Server,
+            retries: u8,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Config { name, servers, primary, retries }
+        }
+
+        fn collect(input: &str) -> Result<Config, MerdeError<'_>> {
+            CollectErrors::new(crate::json::JsonDeserializer::new(input)).deserialize()
+        }
+
+        let input = r#"{
+            "servers": [{"host": "a", "port": 80}, {"host": "b", "port": "eighty"}],
+            "primary": {"port": 70000, "extra": [1, {"deep": true}]},
+            "retries": [3],
+            "name": "prod"
+        }"#;
+
+        // without collecting, only the first error is reported
+        assert!(matches!(
+            from_str::<Config>(input),
+            Err(MerdeError::UnexpectedEvent { .. })
+        ));
+
+        let Err(MerdeError::Multiple(errors)) = collect(input) else {
+            panic!("expected several errors");
+        };
+        let errors = errors
+            .iter()
+            .map(|(path, err)| (path.as_str(), err.code()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                ("servers[1].port", "unexpected_event"),
+                ("primary.port", "out_of_range"),
+                ("primary.host", "missing_property"),
+                ("retries", "unexpected_event"),
+            ]
+        );
+
+        // all good: collecting doesn't change anything
+        let input =
+            r#"{"name": "dev", "servers": [], "primary": {"host": "c", "port": 1}, "retries": 0}"#;
+        assert_eq!(collect(input).unwrap(), from_str::<Config>(input).unwrap());
+
+        // syntax errors still stop everything
+        let input = r#"{"name": 1, "servers": [}"#;
+        assert!(matches!(
+            collect(input),
+            Err(MerdeError::StringParsingError { .. })
+        ));
+
+        // values skipped or captured verbatim by the inner deserializer
+        // still move the paths along
+        #[derive(Debug)]
+        struct Plugin<'s> {
+            raw: crate::json::RawValue<'s>,
+            port: u16,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Plugin<'s> { raw, port }
+        }
+
+        #[derive(Debug)]
+        struct Plugins<'s> {
+            first: Plugin<'s>,
+            second: Plugin<'s>,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Plugins<'s> { first, second }
+        }
+
+        let input = r#"{
+            "first": {"unknown": {"a": [1]}, "raw": {"b": 2}, "port": "x"},
+            "second": {"raw": [3], "port": -1}
+        }"#;
+        let res: Result<Plugins, _> =
+            CollectErrors::new(crate::json::JsonDeserializer::new(input)).deserialize();
+        let Err(MerdeError::Multiple(errors)) = res else {
+            panic!("expected several errors");
+        };
+        let paths = errors
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["first.port", "second.port"]);
+    }
+
+    #[test]
+    fn test_on_field_error() {
+        #[derive(Debug, PartialEq)]
+        struct Sample<'s> {
+            host: CowStr<'s>,
+            cpu: f64,
+            tags: Vec<String>,
+            pid: u32,
+        }
+
+        struct LenientOpinions;
+
+        impl DeserOpinions for LenientOpinions {
+            fn deny_unknown_fields(&self) -> bool {
+                false
+            }
+
+            #[allow(clippy::needless_lifetimes)]
+            fn default_field_value<'s, 'borrow>(
+                &self,
+                key: &'borrow str,
+                slot: FieldSlot<'s, 'borrow>,
+            ) {
+                if key == "tags" {
+                    slot.fill::<Vec<String>>(vec![]);
+                }
+            }
+
+            fn map_key_name<'s>(&self, key: CowStr<'s>) -> CowStr<'s> {
+                key
+            }
+
+            fn recover_from_field_errors(&self) -> bool {
+                true
+            }
+
+            #[allow(clippy::needless_lifetimes)]
+            fn on_field_error<'e, 's, 'borrow>(
+                &self,
+                key: &'borrow str,
+                error: MerdeError<'e>,
+                slot: FieldSlot<'s, 'borrow>,
+            ) -> Result<(), MerdeError<'e>> {
+                match key {
+                    "cpu" => slot.fill::<f64>(f64::NAN),
+                    // treated as missing
+                    "tags" => {}
+                    _ => return Err(error),
+                }
+                Ok(())
+            }
+        }
+
+        derive! {
+            impl (Deserialize) for struct Sample<'s> { host, cpu, tags, pid } via LenientOpinions
+        }
+
+        let sample: Sample = from_str(
+            r#"{"cpu": {"user": "?", "sys": [1, 2]}, "tags": ["a", 3], "host": "web1", "pid": 42}"#,
+        )
+        .unwrap();
+        assert_eq!(sample.host, "web1");
+        assert!(sample.cpu.is_nan());
+        assert!(sample.tags.is_empty());
+        assert_eq!(sample.pid, 42);
+
+        // other fields still fail
+        assert!(matches!(
+            from_str::<Sample>(r#"{"cpu": 1, "host": "web1", "pid": -1}"#),
+            Err(MerdeError::OutOfRange)
+        ));
+    }
+
+    #[test]
+    fn test_deserialize_in_place_field_errors() {
+        use crate::json::JsonDeserializer;
+
+        #[derive(Debug, PartialEq)]
+        struct Server {
+            host: String,
+            cpu: f64,
+            tags: Vec<String>,
+        }
+
+        struct LenientOpinions;
+
+        impl DeserOpinions for LenientOpinions {
+            fn deny_unknown_fields(&self) -> bool {
+                false
+            }
+
+            #[allow(clippy::needless_lifetimes)]
+            fn default_field_value<'s, 'borrow>(
+                &self,
+                key: &'borrow str,
+                slot: FieldSlot<'s, 'borrow>,
+            ) {
+                if key == "tags" {
+                    slot.fill::<Vec<String>>(vec!["default".into()]);
+                }
+            }
+
+            fn map_key_name<'s>(&self, key: CowStr<'s>) -> CowStr<'s> {
+                key
+            }
+
+            fn recover_from_field_errors(&self) -> bool {
+                true
+            }
+
+            #[allow(clippy::needless_lifetimes)]
+            fn on_field_error<'e, 's, 'borrow>(
+                &self,
+                key: &'borrow str,
+                error: MerdeError<'e>,
+                slot: FieldSlot<'s, 'borrow>,
+            ) -> Result<(), MerdeError<'e>> {
+                match key {
+                    "cpu" => slot.fill::<f64>(f64::NAN),
+                    // treated as missing
+                    "tags" => {}
+                    _ => return Err(error),
+                }
+                Ok(())
+            }
+        }
+
+        derive! {
+            impl (Deserialize, DeserializeInPlace) for struct Server { host, cpu, tags } via LenientOpinions
+        }
+
+        let mut server = Server {
+            host: "web0".into(),
+            cpu: 0.5,
+            tags: vec!["old".into()],
+        };
+        JsonDeserializer::new(r#"{"cpu": "?", "tags": ["a", 3], "host": "web1"}"#)
+            .deserialize_into(&mut server)
+            .unwrap();
+        assert_eq!(server.host, "web1");
+        assert!(server.cpu.is_nan());
+        assert_eq!(server.tags, ["default"]);
+
+        // errors are collected too
+        #[derive(Debug, PartialEq)]
+        struct Endpoint {
+            host: String,
+            port: u16,
+        }
+
+        derive! {
+            impl (Deserialize, DeserializeInPlace) for struct Endpoint { host, port }
+        }
+
+        let mut endpoint = Endpoint {
+            host: "a".into(),
+            port: 80,
+        };
+        let res = CollectErrors::new(JsonDeserializer::new(r#"{"port": "eighty", "extra": 1}"#))
+            .deserialize_into(&mut endpoint);
+        let Err(MerdeError::Multiple(errors)) = res else {
+            panic!("expected several errors, got {res:?}");
+        };
+        let errors = errors
+            .iter()
+            .map(|(path, err)| (path.as_str(), err.code()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [("port", "unexpected_event"), ("host", "missing_property")]
+        );
+    }
+
+    #[test]
+    fn test_custom_errors() {
+        #[derive(Debug, PartialEq)]
+        struct Version(u64, u64, u64);
+
+        impl<'s> Deserialize<'s> for Version {
+            async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+                let s = String::deserialize(de).await?;
+                let parts = s
+                    .split('.')
+                    .map(|part| part.parse::<u64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| MerdeError::custom(format!("{s:?} isn't a version: {e}")))?;
+                match parts[..] {
+                    [major, minor, patch] => Ok(Version(major, minor, patch)),
+                    _ => Err(MerdeError::custom("expected a semver string, like `1.2.3`")
+                        .context(format!("while parsing {s:?}"))),
+                }
+            }
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Package {
+            name: String,
+            version: Version,
+        }
+
+        // (a hand-written impl that says which field failed)
+        impl<'s> Deserialize<'s> for Package {
+            async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+                de.next().await?.into_map_start()?;
+                let (mut name, mut version) = (None, None);
+                loop {
+                    match de.next().await? {
+                        Event::MapEnd => break,
+                        Event::Str(key) => match key.as_ref() {
+                            "name" => {
+                                let value = String::deserialize(de).await;
+                                name = Some(value.map_err(|e| e.with_field("name"))?);
+                            }
+                            "version" => {
+                                let value = Version::deserialize(de).await;
+                                version = Some(value.map_err(|e| e.with_field("version"))?);
+                            }
+                            _ => de.skip_value().await?,
+                        },
+                        ev => return Err(MerdeError::custom(format!("unexpected {ev:?}"))),
+                    }
+                }
+                Ok(Package {
+                    name: name.ok_or_else(|| MerdeError::MissingProperty("name".into()))?,
+                    version: version
+                        .ok_or_else(|| MerdeError::MissingProperty("version".into()))?,
+                })
+            }
+        }
+
+        let package: Package = from_str(r#"{"name": "merde", "version": "10.0.1"}"#).unwrap();
+        assert_eq!(package.version, Version(10, 0, 1));
+
+        let err = from_str::<Package>(r#"{"name": "merde", "version": "10.x"}"#).unwrap_err();
+        assert!(matches!(err.root(), MerdeError::Custom(_)));
+        assert_eq!(
+            err.to_string(),
+            r#"version: "10.x" isn't a version: invalid digit found in string"#
+        );
+
+        let err = from_str::<Vec<Package>>(r#"[{"name": "merde", "version": "10"}]"#)
+            .unwrap_err()
+            .with_field("dependencies");
+        assert_eq!(
+            err.to_string(),
+            "dependencies.version: while parsing \"10\": expected a semver string, like `1.2.3`"
+        );
+        assert_eq!(err.code(), "custom");
+
+        let json = crate::json::to_string(&err).unwrap();
+        let map: HashMap<String, Value> = from_str(&json).unwrap();
+        assert_eq!(map.get("code"), Some(&Value::from("custom")));
+        assert_eq!(map.get("path"), Some(&Value::from("dependencies.version")));
+        assert_eq!(
+            map.get("context"),
+            Some(&Value::from("while parsing \"10\""))
+        );
+
+        // wrapped parsing errors still don't show the source in their message
+        let err = from_str::<Value>("[1, 2")
+            .unwrap_err()
+            .context("in the lockfile");
+        let json = crate::json::to_string(&err).unwrap();
+        assert!(!json.contains('\x1b'));
+        assert_eq!(err.code(), "string_parsing");
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "urlencoded")]
+mod urlencoded_tests {
+    use super::*;
+
+    #[test]
+    fn test_form_struct() {
+        #[derive(Debug, PartialEq)]
+        struct SearchForm<'s> {
+            q: CowStr<'s>,
+            page: u32,
+            exact: bool,
+            tags: Vec<String>,
+            lang: Option<String>,
+        }
+
+        derive! {
+            impl (Deserialize) for struct SearchForm<'s> { q, page, exact, tags, lang }
+        }
+
+        let form: SearchForm = crate::urlencoded::from_str(
+            "q=merde+%26+friends&page=2&exact=false&tags=rust&tags=serde",
+        )
+        .unwrap();
+        assert_eq!(
+            form,
+            SearchForm {
+                q: "merde & friends".into(),
+                page: 2,
+                exact: false,
+                tags: vec!["rust".into(), "serde".into()],
+                lang: None,
+            }
+        );
+
+        // a single tag still needs to be an array
+        let form: SearchForm =
+            crate::urlencoded::from_str("q=x&page=1&exact=true&tags[]=rust&lang=fr").unwrap();
+        assert_eq!(form.tags, vec!["rust".to_string()]);
+        assert_eq!(form.lang.as_deref(), Some("fr"));
+    }
+
+    #[test]
+    fn test_form_round_trip() {
+        #[derive(Debug, PartialEq)]
+        struct Filters {
+            tags: Vec<String>,
+            ids: Vec<u32>,
+        }
+
+        derive! {
+            impl (Serialize, Deserialize) for struct Filters { tags, ids }
+        }
+
+        for (tags, ids) in [
+            (vec![], vec![]),
+            (vec!["rust".to_string()], vec![7]),
+            (vec!["a b".to_string(), "c".to_string()], vec![1, 2]),
+        ] {
+            let filters = Filters { tags, ids };
+            let form = crate::urlencoded::to_string(&filters).unwrap();
+            let back: Filters = crate::urlencoded::from_str(&form).unwrap();
+            assert_eq!(back, filters, "{form}");
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "env")]
+mod env_tests {
+    use super::*;
+
+    #[test]
+    fn test_env_config() {
+        #[derive(Debug, PartialEq)]
+        struct DbConfig {
+            host: String,
+            port: u16,
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Config {
+            debug: bool,
+            db: DbConfig,
+        }
+
+        derive! {
+            impl (Deserialize) for struct DbConfig { host, port }
+        }
+
+        derive! {
+            impl (Deserialize) for struct Config { debug, db }
+        }
+
+        let vars = [
+            ("MYSVC_DEBUG", "false"),
+            ("MYSVC_DB__HOST", "db.internal"),
+            ("MYSVC_DB__PORT", "5432"),
+            ("MYSVC_UNUSED", "whatever"),
+        ];
+        let config: Config = crate::env::from_vars(
+            crate::env::EnvDeserializer::new(
+                vars.iter().map(|(k, v)| (k.to_string(), v.to_string())),
+            )
+            .with_prefix("MYSVC_"),
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                debug: false,
+                db: DbConfig {
+                    host: "db.internal".into(),
+                    port: 5432,
+                },
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "config", feature = "env"))]
+mod config_tests {
+    use super::*;
+
+    #[test]
+    fn test_layered_config() {
+        #[derive(Debug, PartialEq)]
+        struct Config {
+            port: u16,
+            debug: bool,
+        }
+
+        derive! {
+            impl (Deserialize) for struct Config { port, debug }
+        }
+
+        let config: Config = crate::config::ConfigLoader::new()
+            .with_defaults(
+                Map::new()
+                    .with("port", Value::I64(8080))
+                    .with("debug", Value::Bool(false)),
+            )
+            .with_env_vars(
+                crate::env::EnvDeserializer::new([("MYSVC_PORT".to_string(), "9090".to_string())])
+                    .with_prefix("MYSVC_"),
+            )
+            .load()
+            .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                port: 9090,
+                debug: false,
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "json", feature = "msgpack", feature = "yaml"))]
+mod recursive_tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Tree {
+        name: String,
+        children: Vec<Tree>,
+    }
+
+    derive! {
+        impl (Serialize, Deserialize) for struct Tree { name, children }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct List {
+        value: u64,
+        next: Option<Box<List>>,
+    }
+
+    derive! {
+        impl (Serialize, Deserialize) for struct List { value, next }
+    }
+
+    fn leaf(name: &str) -> Tree {
+        Tree {
+            name: name.into(),
+            children: vec![],
+        }
+    }
+
+    #[test]
+    fn test_recursive_roundtrip() {
+        let tree = Tree {
+            name: "root".into(),
+            children: vec![
+                leaf("a"),
+                Tree {
+                    name: "b".into(),
+                    children: vec![leaf("c")],
+                },
+            ],
+        };
+        let json = crate::json::to_string(&tree).unwrap();
+        assert_eq!(tree, crate::json::from_str_owned::<Tree>(&json).unwrap());
+        let msgpack = crate::msgpack::to_vec(&tree).unwrap();
+        assert_eq!(
+            tree,
+            crate::msgpack::from_slice_owned::<Tree>(&msgpack).unwrap()
+        );
+
+        let list: List = crate::yaml::from_str("{value: 1, next: {value: 2, next: null}}").unwrap();
+        assert_eq!(list.next.as_ref().unwrap().value, 2);
+        let json = crate::json::to_string(&list).unwrap();
+        assert_eq!(json, r#"{"value":1,"next":{"value":2,"next":null}}"#);
+        assert_eq!(list, crate::json::from_str_owned::<List>(&json).unwrap());
+    }
+
+    #[test]
+    fn test_deeply_recursive() {
+        // deep enough to overflow this stack many times over without the
+        // metastack, on both the serialize and deserialize paths
+        const DEPTH: usize = 10_000;
+
+        std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(|| {
+                let mut tree = leaf("leaf");
+                for _ in 0..DEPTH {
+                    tree = Tree {
+                        name: "node".into(),
+                        children: vec![tree],
+                    };
+                }
+                let json = crate::json::to_string(&tree).unwrap();
+                let msgpack = crate::msgpack::to_vec(&tree).unwrap();
+                for tree in [
+                    tree,
+                    crate::json::from_str_owned::<Tree>(&json).unwrap(),
+                    crate::msgpack::from_slice_owned::<Tree>(&msgpack).unwrap(),
+                ] {
+                    // take it apart one level at a time: dropping it is
+                    // recursive, and would overflow the stack
+                    let mut depth = 0;
+                    let mut stack = vec![tree];
+                    while let Some(mut tree) = stack.pop() {
+                        depth += 1;
+                        stack.append(&mut tree.children);
+                    }
+                    assert_eq!(depth, DEPTH + 1);
+                }
+
+                let mut list = None;
+                for value in 0..DEPTH as u64 {
+                    list = Some(Box::new(List { value, next: list }));
+                }
+                let json = crate::json::to_string(&list).unwrap();
+                let msgpack = crate::msgpack::to_vec(&list).unwrap();
+                for list in [
+                    list,
+                    crate::json::from_str_owned::<Option<Box<List>>>(&json).unwrap(),
+                    crate::msgpack::from_slice_owned::<Option<Box<List>>>(&msgpack).unwrap(),
+                ] {
+                    let (mut len, mut next) = (0, list);
+                    while let Some(mut list) = next {
+                        len += 1;
+                        next = list.next.take();
+                    }
+                    assert_eq!(len, DEPTH);
+                }
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
 }
 
 // used to test out doc-tests
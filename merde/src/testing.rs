@@ -0,0 +1,193 @@
+//! Assertions for tests, that compare values by their serialized form, and
+//! show what differs when they fail: see [`assert_same_value!`](crate::assert_same_value)
+//! and [`assert_same_json!`](crate::assert_same_json).
+//!
+//! Values are compared like [`CanonicalValue`] does: maps regardless of their
+//! keys' order, and numbers by value.
+
+use std::fmt::{self, Write};
+
+use merde_core::{convert, CanonicalValue, Serialize, Value};
+
+/// Panics with a diff if `left` and `right` don't serialize to the same value.
+/// Use it through [`assert_same_value!`](crate::assert_same_value).
+#[track_caller]
+pub fn assert_same_value<L: Serialize, R: Serialize>(left: &L, right: &R) {
+    let left: Value<'static> = convert(left).unwrap_or_else(|e| panic!("left side: {e}"));
+    let right: Value<'static> = convert(right).unwrap_or_else(|e| panic!("right side: {e}"));
+    assert_same(&left, &right);
+}
+
+/// Panics with a diff if `left` and `right` aren't the same JSON documents,
+/// whitespace and key order aside. Use it through
+/// [`assert_same_json!`](crate::assert_same_json).
+#[cfg(feature = "json")]
+#[track_caller]
+pub fn assert_same_json(left: &str, right: &str) {
+    let left: Value<'_> = merde_json::from_str(left).unwrap_or_else(|e| panic!("left side: {e}"));
+    let right: Value<'_> =
+        merde_json::from_str(right).unwrap_or_else(|e| panic!("right side: {e}"));
+    assert_same(&left, &right);
+}
+
+#[track_caller]
+fn assert_same(left: &Value<'_>, right: &Value<'_>) {
+    let diff = diff(left, right, std::env::var_os("NO_COLOR").is_none());
+    if !diff.is_empty() {
+        panic!("values differ (- left, + right):\n{diff}");
+    }
+}
+
+/// Lists what differs between `left` and `right`, one path per entry, like:
+///
+/// ```text
+/// /user/age
+///   - 36
+///   + 37
+/// ```
+///
+/// Returns an empty string if they're the same.
+pub(crate) fn diff(left: &Value<'_>, right: &Value<'_>, colors: bool) -> String {
+    let mut out = String::new();
+    let mut path = String::new();
+    diff_at(&mut out, &mut path, Some(left), Some(right), colors);
+    out
+}
+
+fn diff_at(
+    out: &mut String,
+    path: &mut String,
+    left: Option<&Value<'_>>,
+    right: Option<&Value<'_>>,
+    colors: bool,
+) {
+    match (left, right) {
+        (Some(Value::Map(l)), Some(Value::Map(r))) => {
+            let mut keys: Vec<&str> = l.keys().chain(r.keys()).map(|k| k.as_ref()).collect();
+            keys.sort_unstable();
+            keys.dedup();
+            for key in keys {
+                let len = path.len();
+                // escaped like JSON Pointers
+                write!(path, "/{}", key.replace('~', "~0").replace('/', "~1")).unwrap();
+                diff_at(out, path, l.get(key), r.get(key), colors);
+                path.truncate(len);
+            }
+        }
+        (Some(Value::Array(l)), Some(Value::Array(r))) => {
+            for i in 0..l.len().max(r.len()) {
+                let len = path.len();
+                write!(path, "/{i}").unwrap();
+                diff_at(out, path, l.get(i), r.get(i), colors);
+                path.truncate(len);
+            }
+        }
+        (Some(l), Some(r)) if CanonicalValue(l.clone()) == CanonicalValue(r.clone()) => {}
+        _ => {
+            let path = if path.is_empty() { "(root)" } else { path };
+            writeln!(out, "{path}").unwrap();
+            if let Some(l) = left {
+                line(out, '-', l, colors);
+            }
+            if let Some(r) = right {
+                line(out, '+', r, colors);
+            }
+        }
+    }
+}
+
+fn line(out: &mut String, sign: char, value: &Value<'_>, colors: bool) {
+    let color = if sign == '-' { "\x1b[31m" } else { "\x1b[32m" };
+    let (start, end) = if colors { (color, "\x1b[0m") } else { ("", "") };
+    writeln!(out, "  {start}{sign} {}{end}", Compact(value)).unwrap();
+}
+
+// Formats a value like compact JSON (bytes aside), so that diffs don't
+// depend on the json feature
+struct Compact<'a, 's>(&'a Value<'s>);
+
+impl fmt::Display for Compact<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::I64(i) => write!(f, "{i}"),
+            Value::U64(u) => write!(f, "{u}"),
+            Value::Float(x) => write!(f, "{:?}", x.into_inner()),
+            Value::Str(s) => write!(f, "{:?}", s.as_ref()),
+            Value::Bytes(b) => write!(f, "{:?}", b.as_ref()),
+            Value::Array(arr) => {
+                f.write_char('[')?;
+                for (i, v) in arr.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", Compact(v))?;
+                }
+                f.write_char(']')
+            }
+            Value::Map(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_unstable_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+                f.write_char('{')?;
+                for (i, (k, v)) in entries.into_iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{:?}:{}", k.as_ref(), Compact(v))?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use merde_core::{Array, Map};
+
+    #[test]
+    fn test_diff() {
+        let left = Value::from(
+            Map::new()
+                .with("name", "merde")
+                .with("tags", Array::new().with("a").with("b"))
+                .with("meta", Map::new().with("stars", 1u64).with("a/b", true)),
+        );
+        let right = Value::from(
+            Map::new()
+                .with("meta", Map::new().with("stars", 1.0).with("a/b", false))
+                .with("tags", Array::new().with("a"))
+                .with("name", "merde")
+                .with("extra", ()),
+        );
+        assert_eq!(diff(&left, &left.clone(), false), "");
+        assert_eq!(
+            diff(&left, &right, false),
+            "/extra\n  + null\n/meta/a~1b\n  - true\n  + false\n/tags/1\n  - \"b\"\n"
+        );
+        assert_eq!(
+            diff(&Value::from(1u64), &Value::from("1"), true),
+            "(root)\n  \x1b[31m- 1\x1b[0m\n  \x1b[32m+ \"1\"\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_assert_same_value() {
+        crate::assert_same_value!(vec![1u8, 2], vec![1.0, 2.0]);
+
+        let res = std::panic::catch_unwind(|| crate::assert_same_value!(Some(1), None::<i32>));
+        let message = res.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("values differ"), "{message}");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_assert_same_json() {
+        crate::assert_same_json!(r#"{"a": [1, 2], "b": null}"#, r#"{ "b":null,"a":[1,2] }"#);
+        let res = std::panic::catch_unwind(|| crate::assert_same_json!("[1]", "[1, 2]"));
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,443 @@
+#![deny(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+use std::path::{Path, PathBuf};
+
+use merde_core::{
+    ArrayStart, Deserialize, Deserializer, DynDeserializerExt, Event, IntoStatic, MapStart,
+    MerdeError, TextEvents, TypeHint, Value,
+};
+
+#[derive(Debug)]
+enum Layer {
+    Value(Value<'static>),
+    File {
+        path: PathBuf,
+        required: bool,
+    },
+    #[cfg(feature = "env")]
+    Env(merde_env::EnvDeserializer),
+}
+
+/// Builds a configuration out of layers (defaults, files, environment
+/// variables), merged in the order they were added: later layers take
+/// precedence over earlier ones.
+///
+/// Layers are only read when calling [`ConfigLoader::load`] (or
+/// [`ConfigLoader::load_value`]), and merged with [`merge`].
+///
+/// ```rust
+/// use merde_config::ConfigLoader;
+/// use merde_core::{Map, Value};
+///
+/// let value = ConfigLoader::new()
+///     .with_defaults(Map::new().with("port", Value::I64(8080)).with("debug", Value::Bool(false)))
+///     .with_defaults(Map::new().with("debug", Value::Bool(true)))
+///     .load_value()
+///     .unwrap();
+/// assert_eq!(
+///     value,
+///     Value::Map(Map::new().with("port", Value::I64(8080)).with("debug", Value::Bool(true)))
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct ConfigLoader {
+    layers: Vec<Layer>,
+}
+
+impl ConfigLoader {
+    /// Construct a loader with no layers: loading it gives an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer from a [`Value`]. This is typically called first, with the
+    /// defaults, but can also be used to apply overrides (e.g. from command-line
+    /// arguments) last.
+    pub fn with_defaults(mut self, value: impl Into<Value<'static>>) -> Self {
+        self.layers.push(Layer::Value(value.into()));
+        self
+    }
+
+    /// Adds a layer from a file, whose format is picked from its extension
+    /// (`.json`, `.yaml` or `.yml`). Loading fails if the file doesn't exist.
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.layers.push(Layer::File {
+            path: path.into(),
+            required: true,
+        });
+        self
+    }
+
+    /// Like [`ConfigLoader::with_file`], except the layer is skipped if the
+    /// file doesn't exist.
+    pub fn with_optional_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.layers.push(Layer::File {
+            path: path.into(),
+            required: false,
+        });
+        self
+    }
+
+    /// Adds a layer from the environment variables of the current process
+    /// that start with `prefix` (e.g. `APP_`), see [`merde_env::EnvDeserializer`].
+    #[cfg(feature = "env")]
+    pub fn with_env(self, prefix: &str) -> Self {
+        self.with_env_vars(merde_env::EnvDeserializer::from_env().with_prefix(prefix))
+    }
+
+    /// Adds a layer from an [`merde_env::EnvDeserializer`], e.g. one with a
+    /// custom separator, or with a given set of variables.
+    #[cfg(feature = "env")]
+    pub fn with_env_vars(mut self, vars: merde_env::EnvDeserializer) -> Self {
+        self.layers.push(Layer::Env(vars));
+        self
+    }
+
+    /// Reads and merges all layers, returning the merged tree.
+    ///
+    /// Environment variables that look like booleans and numbers are typed as
+    /// such in the tree. [`ConfigLoader::load`] still gives them as written to
+    /// fields that want a string.
+    pub fn load_value(self) -> Result<Value<'static>, MerdeError<'static>> {
+        Ok(self.load_trees()?.0)
+    }
+
+    /// Reads and merges all layers, then deserializes the merged tree into a `T`.
+    pub fn load<T>(self) -> Result<T, MerdeError<'static>>
+    where
+        T: Deserialize<'static>,
+    {
+        let (typed, raw) = self.load_trees()?;
+        let mut deser = LayeredDeserializer::default();
+        push_events(&typed, Some(&raw), &mut deser.events);
+        deser.deserialize()
+    }
+
+    /// Returns the merged tree, along with the same tree where environment
+    /// variables are left as written.
+    fn load_trees(self) -> Result<(Value<'static>, Value<'static>), MerdeError<'static>> {
+        let mut typed = Value::Map(Default::default());
+        let mut raw = Value::Map(Default::default());
+        for layer in self.layers {
+            let value = match layer {
+                Layer::Value(value) => value,
+                Layer::File { path, required } => match read_file(&path)? {
+                    Some(value) => value,
+                    None if required => {
+                        return Err(MerdeError::Io(std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("config file not found: {}", path.display()),
+                        )))
+                    }
+                    None => continue,
+                },
+                #[cfg(feature = "env")]
+                Layer::Env(vars) => {
+                    let value: Value<'static> =
+                        merde_env::from_vars(vars.with_type_inference(false))?;
+                    merge(&mut typed, infer(value.clone()));
+                    merge(&mut raw, value);
+                    continue;
+                }
+            };
+            merge(&mut typed, value.clone());
+            merge(&mut raw, value);
+        }
+        Ok((typed, raw))
+    }
+}
+
+/// Types strings by their looks, like [`merde_env::EnvDeserializer`] does.
+#[cfg(feature = "env")]
+fn infer(value: Value<'static>) -> Value<'static> {
+    match value {
+        Value::Str(s) => match merde_core::infer_scalar(s.clone()) {
+            Event::I64(i) => Value::I64(i),
+            Event::U64(u) => Value::U64(u),
+            Event::F64(f) => Value::Float(f.into()),
+            Event::Bool(b) => Value::Bool(b),
+            // including integers too wide for a `Value`
+            _ => Value::Str(s),
+        },
+        Value::Array(items) => {
+            Value::Array(merde_core::Array(items.into_iter().map(infer).collect()))
+        }
+        Value::Map(entries) => {
+            Value::Map(entries.into_iter().map(|(k, v)| (k, infer(v))).collect())
+        }
+        value => value,
+    }
+}
+
+/// Queues the events for `typed`: values that are strings in `raw` but not in
+/// `typed` were typed by their looks, and are given as written when asked for
+/// a string.
+fn push_events(
+    typed: &Value<'static>,
+    raw: Option<&Value<'static>>,
+    events: &mut TextEvents<'static>,
+) {
+    match typed {
+        Value::Map(entries) => {
+            events.push(Event::MapStart(MapStart {
+                size_hint: Some(entries.len()),
+            }));
+            let raw = match raw {
+                Some(Value::Map(raw)) => Some(raw),
+                _ => None,
+            };
+            for (key, value) in entries.iter() {
+                events.push(Event::Str(key.clone()));
+                push_events(value, raw.and_then(|raw| raw.get(key)), events);
+            }
+            events.push(Event::MapEnd);
+        }
+        Value::Array(items) => {
+            events.push(Event::ArrayStart(ArrayStart {
+                size_hint: Some(items.len()),
+            }));
+            let raw = match raw {
+                Some(Value::Array(raw)) => Some(raw),
+                _ => None,
+            };
+            for (i, item) in items.iter().enumerate() {
+                push_events(item, raw.and_then(|raw| raw.get(i)), events);
+            }
+            events.push(Event::ArrayEnd);
+        }
+        Value::Str(s) => events.push(Event::Str(s.clone())),
+        _ => match raw {
+            Some(Value::Str(text)) => events.push_scalar(text.clone()),
+            _ => events.push(match typed {
+                Value::I64(i) => Event::I64(*i),
+                Value::U64(u) => Event::U64(*u),
+                Value::Float(f) => Event::F64(f.into_inner()),
+                Value::Bytes(b) => Event::Bytes(b.clone()),
+                Value::Bool(b) => Event::Bool(*b),
+                _ => Event::Null,
+            }),
+        },
+    }
+}
+
+/// Deserializes the events queued by [push_events].
+#[derive(Debug, Default)]
+struct LayeredDeserializer {
+    events: TextEvents<'static>,
+}
+
+impl Deserializer<'static> for LayeredDeserializer {
+    async fn next(&mut self) -> Result<Event<'static>, MerdeError<'static>> {
+        self.events.next(None).ok_or_else(MerdeError::eof)
+    }
+
+    async fn next_event_with_hint(
+        &mut self,
+        hint: TypeHint,
+    ) -> Result<Event<'static>, MerdeError<'static>> {
+        self.events.next(Some(hint)).ok_or_else(MerdeError::eof)
+    }
+
+    fn put_back(&mut self, ev: Event<'static>) -> Result<(), MerdeError<'static>> {
+        self.events.put_back(ev);
+        Ok(())
+    }
+}
+
+/// Merges `overlay` on top of `base`: if both are maps, their entries are
+/// merged key by key (recursively), otherwise `overlay` replaces `base`
+/// (arrays are not concatenated).
+pub fn merge<'s>(base: &mut Value<'s>, overlay: Value<'s>) {
+    match (base, overlay) {
+        (Value::Map(base), Value::Map(overlay)) => {
+            for (key, value) in overlay.0 {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Returns `None` if the file doesn't exist.
+#[cfg_attr(
+    not(any(feature = "json", feature = "yaml")),
+    allow(unused_variables, unreachable_code)
+)]
+fn read_file(path: &Path) -> Result<Option<Value<'static>>, MerdeError<'static>> {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(MerdeError::Io(std::io::Error::new(
+                e.kind(),
+                format!("reading config file {}: {e}", path.display()),
+            )))
+        }
+    };
+
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    let parsed: Result<Value<'_>, MerdeError<'_>> = match extension {
+        #[cfg(feature = "json")]
+        "json" => merde_json::from_str(&source),
+        #[cfg(feature = "yaml")]
+        "yaml" | "yml" => merde_yaml::from_str(&source),
+        _ => {
+            return Err(MerdeError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "unsupported config file format (is the feature for it enabled?): {}",
+                    path.display()
+                ),
+            )))
+        }
+    };
+    match parsed {
+        Ok(value) => Ok(Some(value.into_static())),
+        Err(e) => Err(e.into_static()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use merde_core::{Array, Map};
+
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut base = Value::Map(
+            Map::new()
+                .with("name", Value::from("app"))
+                .with(
+                    "db",
+                    Map::new()
+                        .with("host", Value::from("localhost"))
+                        .with("port", Value::I64(5432)),
+                )
+                .with("tags", Array::new().with(Value::from("a"))),
+        );
+        merge(
+            &mut base,
+            Value::Map(
+                Map::new()
+                    .with("db", Map::new().with("port", Value::I64(5433)))
+                    .with("tags", Array::new().with(Value::from("b")))
+                    .with("debug", Value::Bool(true)),
+            ),
+        );
+        assert_eq!(
+            base,
+            Value::Map(
+                Map::new()
+                    .with("name", Value::from("app"))
+                    .with(
+                        "db",
+                        Map::new()
+                            .with("host", Value::from("localhost"))
+                            .with("port", Value::I64(5433)),
+                    )
+                    .with("tags", Array::new().with(Value::from("b")))
+                    .with("debug", Value::Bool(true))
+            )
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "json", feature = "yaml", feature = "env"))]
+    fn test_precedence() {
+        let dir = std::env::temp_dir().join(format!("merde_config_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let yaml = dir.join("config.yaml");
+        std::fs::write(&yaml, "db:\n  host: db.internal\n  port: 5433\n").unwrap();
+        let json = dir.join("local.json");
+        std::fs::write(&json, r#"{"db": {"port": 6000}, "debug": true}"#).unwrap();
+
+        let value = ConfigLoader::new()
+            .with_defaults(
+                Map::new()
+                    .with("debug", Value::Bool(false))
+                    .with("db", Map::new().with("host", Value::from("localhost"))),
+            )
+            .with_file(&yaml)
+            .with_optional_file(dir.join("missing.yaml"))
+            .with_file(&json)
+            .with_env_vars(
+                merde_env::EnvDeserializer::new([(
+                    "APP_DB__HOST".to_string(),
+                    "override".to_string(),
+                )])
+                .with_prefix("APP_"),
+            )
+            .load_value();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            value.unwrap(),
+            Value::Map(
+                Map::new().with("debug", Value::Bool(true)).with(
+                    "db",
+                    Map::new()
+                        .with("host", Value::from("override"))
+                        .with("port", Value::I64(6000))
+                )
+            )
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "env")]
+    fn test_env_strings() {
+        use std::collections::HashMap;
+
+        let loader = || {
+            ConfigLoader::new()
+                .with_defaults(Map::new().with("port", Value::I64(80)))
+                .with_env_vars(
+                    merde_env::EnvDeserializer::new(
+                        [
+                            ("APP_PIN", "1234"),
+                            ("APP_ZIP", "007"),
+                            ("APP_PORT", "8080"),
+                        ]
+                        .map(|(k, v)| (k.to_string(), v.to_string())),
+                    )
+                    .with_prefix("APP_"),
+                )
+        };
+
+        let strings: HashMap<String, String> = loader().load().unwrap();
+        assert_eq!(strings["pin"], "1234");
+        assert_eq!(strings["zip"], "007");
+        assert_eq!(strings["port"], "8080");
+
+        let value = loader().load_value().unwrap();
+        assert_eq!(value.as_map().unwrap().get("pin"), Some(&Value::I64(1234)));
+        let numbers: Result<HashMap<String, u16>, _> = loader().load();
+        assert!(numbers.is_err(), "zip isn't a number");
+
+        // values from other layers aren't turned into strings
+        let res: Result<HashMap<String, String>, _> = ConfigLoader::new()
+            .with_defaults(Map::new().with("port", Value::I64(80)))
+            .load();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let res = ConfigLoader::new()
+            .with_file("/nonexistent/merde_config.yaml")
+            .load_value();
+        assert!(matches!(res, Err(MerdeError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound));
+
+        let res = ConfigLoader::new()
+            .with_optional_file("/nonexistent/merde_config.toml")
+            .load_value();
+        assert_eq!(res.unwrap(), Value::Map(Map::new()));
+    }
+}
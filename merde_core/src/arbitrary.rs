@@ -0,0 +1,406 @@
+//! Deterministic generation and shrinking of [Value] trees, for fuzz harnesses
+//! and property tests, without pulling in `proptest` or `arbitrary`.
+//!
+//! A typical harness generates a value, serializes it with one backend, reads it
+//! back with another, and when that fails, uses [minimize] to find the smallest
+//! value that still fails:
+//!
+//! ```rust
+//! use merde_core::{arbitrary::{minimize, Rng}, Value};
+//!
+//! let mut rng = Rng::new(42);
+//! let value = Value::arbitrary_with(&mut rng, 3, 4);
+//!
+//! // the same seed always generates the same value
+//! assert_eq!(value, Value::arbitrary_with(&mut Rng::new(42), 3, 4));
+//!
+//! // pretend our backend chokes on `true`, wherever it is
+//! fn has_true(v: &Value) -> bool {
+//!     match v {
+//!         Value::Bool(true) => true,
+//!         Value::Array(arr) => arr.iter().any(has_true),
+//!         Value::Map(map) => map.values().any(has_true),
+//!         _ => false,
+//!     }
+//! }
+//! let failing = Value::Array(vec![Value::I64(7), Value::Str("a".into()), Value::Bool(true)].into());
+//! assert_eq!(minimize(failing, has_true), Value::Bool(true));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use ordered_float::OrderedFloat;
+
+use crate::{CowBytes, CowStr, Map, Value};
+
+/// A small, seedable pseudo-random number generator (splitmix64).
+///
+/// It is not cryptographically secure, and doesn't need to be: it only needs
+/// to be fast, and to give the same sequence for the same seed on every
+/// platform.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a generator from arbitrary bytes, typically a fuzzer's input.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        // FNV-1a
+        let mut seed: u64 = 0xcbf29ce484222325;
+        for &b in bytes {
+            seed ^= b as u64;
+            seed = seed.wrapping_mul(0x100000001b3);
+        }
+        Self::new(seed)
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`, or 0 if `n` is 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        (self.next_u64() % n as u64) as usize
+    }
+
+    fn chance(&mut self, one_in: usize) -> bool {
+        self.below(one_in) == 0
+    }
+}
+
+// characters that tend to trip up escaping and encoding code
+const TRICKY_CHARS: &[char] = &[
+    '"', '\\', '/', '\n', '\r', '\t', '\u{0}', '\u{1f}', '\u{7f}', 'é', 'ß', '\u{2028}', '€', '日',
+    '🦀',
+];
+
+const TRICKY_I64: &[i64] = &[
+    0,
+    1,
+    -1,
+    i8::MIN as i64,
+    i8::MAX as i64,
+    u8::MAX as i64,
+    i16::MIN as i64,
+    u16::MAX as i64,
+    i32::MIN as i64,
+    u32::MAX as i64,
+    1 << 53,
+    -(1 << 53),
+    i64::MIN,
+    i64::MAX,
+];
+
+const TRICKY_F64: &[f64] = &[
+    0.5,
+    -0.5,
+    0.1,
+    1.5e300,
+    -2.5e-300,
+    f64::MIN_POSITIVE,
+    f64::EPSILON,
+];
+
+impl Value<'static> {
+    /// Generates a random value, at most `depth` containers deep, with at most
+    /// `width` items in each array and map.
+    ///
+    /// To keep values comparable across formats, integers are generated in the
+    /// form decoders produce them (`U64` only for values that don't fit in an
+    /// `i64`), and floats are always finite and never integral. Formats that
+    /// can't represent everything (like JSON and bytes) still need to normalize
+    /// values before comparing.
+    pub fn arbitrary_with(rng: &mut Rng, depth: usize, width: usize) -> Self {
+        let kinds = if depth == 0 { 7 } else { 9 };
+        match rng.below(kinds) {
+            0 => Value::Null,
+            1 => Value::Bool(rng.chance(2)),
+            2 => Value::I64(if rng.chance(2) {
+                TRICKY_I64[rng.below(TRICKY_I64.len())]
+            } else {
+                rng.next_u64() as i64
+            }),
+            3 => Value::U64(if rng.chance(2) {
+                u64::MAX
+            } else {
+                rng.next_u64() | (1 << 63)
+            }),
+            4 => Value::Float(OrderedFloat(if rng.chance(2) {
+                TRICKY_F64[rng.below(TRICKY_F64.len())]
+            } else {
+                (rng.next_u64() >> 11) as f64 / (1u64 << 20) as f64 + 0.25
+            })),
+            5 => Value::Str(arbitrary_str(rng, width * 2)),
+            6 => {
+                let len = rng.below(width * 2 + 1);
+                let bytes: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+                Value::Bytes(CowBytes::from(bytes))
+            }
+            7 => {
+                let len = rng.below(width + 1);
+                Value::Array(
+                    (0..len)
+                        .map(|_| Value::arbitrary_with(rng, depth - 1, width))
+                        .collect::<Vec<_>>()
+                        .into(),
+                )
+            }
+            _ => {
+                let len = rng.below(width + 1);
+                let mut map = Map::with_capacity(len);
+                for _ in 0..len {
+                    // duplicate keys just make for a smaller map
+                    map.insert(
+                        arbitrary_str(rng, 8),
+                        Value::arbitrary_with(rng, depth - 1, width),
+                    );
+                }
+                Value::Map(map)
+            }
+        }
+    }
+
+    /// Returns values that are "simpler" than this one, simplest first.
+    ///
+    /// Repeatedly picking a candidate that still triggers a bug eventually
+    /// reaches [Value::Null] (or a value with no candidates), so shrinking
+    /// always terminates. See [minimize].
+    pub fn shrink(&self) -> Vec<Value<'static>> {
+        let mut out = Vec::new();
+        match self {
+            Value::Null => return out,
+            Value::Bool(b) => {
+                out.push(Value::Null);
+                if *b {
+                    out.push(Value::Bool(false));
+                }
+            }
+            Value::I64(i) => {
+                out.push(Value::Null);
+                if *i != 0 {
+                    out.push(Value::I64(0));
+                    if *i / 2 != 0 {
+                        out.push(Value::I64(*i / 2));
+                    }
+                }
+            }
+            Value::U64(u) => {
+                out.push(Value::Null);
+                out.push(Value::I64(0));
+                // always fits, since `u` is at most `u64::MAX`
+                out.push(Value::I64((*u / 2) as i64));
+            }
+            Value::Float(f) => {
+                out.push(Value::Null);
+                if f.0 != 0.0 {
+                    out.push(Value::Float(OrderedFloat(0.0)));
+                    let truncated = f.0.trunc();
+                    if truncated != f.0 && truncated != 0.0 {
+                        out.push(Value::Float(OrderedFloat(truncated)));
+                    }
+                }
+            }
+            Value::Str(s) => {
+                out.push(Value::Null);
+                let chars: Vec<char> = s.chars().collect();
+                for len in shorter_lengths(chars.len()) {
+                    out.push(Value::Str(CowStr::from(
+                        chars[..len].iter().collect::<String>(),
+                    )));
+                }
+            }
+            Value::Bytes(b) => {
+                out.push(Value::Null);
+                for len in shorter_lengths(b.len()) {
+                    out.push(Value::Bytes(CowBytes::from(b[..len].to_vec())));
+                }
+            }
+            Value::Array(arr) => {
+                out.push(Value::Null);
+                // hoist each item in place of the whole array
+                out.extend(arr.iter().cloned());
+                // drop one item
+                for i in 0..arr.len() {
+                    let mut smaller = arr.clone();
+                    smaller.remove(i);
+                    out.push(Value::Array(smaller));
+                }
+                // shrink one item
+                for (i, item) in arr.iter().enumerate() {
+                    for candidate in item.shrink() {
+                        let mut smaller = arr.clone();
+                        smaller[i] = candidate;
+                        out.push(Value::Array(smaller));
+                    }
+                }
+            }
+            Value::Map(map) => {
+                out.push(Value::Null);
+                out.extend(map.values().cloned());
+                let keys: Vec<&CowStr<'static>> = map.keys().collect();
+                for key in &keys {
+                    out.push(Value::Map(
+                        map.iter()
+                            .filter(|(k, _)| k != key)
+                            .map(|(k, v)| (k.clone(), v.clone()))
+                            .collect(),
+                    ));
+                }
+                for key in &keys {
+                    for candidate in map[*key].shrink() {
+                        let mut smaller = map.clone();
+                        smaller.insert((*key).clone(), candidate);
+                        out.push(Value::Map(smaller));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Shrinks `value` as long as `still_fails` keeps returning true, and returns
+/// the smallest failing value found.
+///
+/// `still_fails` is assumed to return true for `value` itself.
+pub fn minimize(
+    value: Value<'static>,
+    mut still_fails: impl FnMut(&Value<'static>) -> bool,
+) -> Value<'static> {
+    let mut current = value;
+    'outer: loop {
+        for candidate in current.shrink() {
+            if still_fails(&candidate) {
+                current = candidate;
+                continue 'outer;
+            }
+        }
+        return current;
+    }
+}
+
+fn arbitrary_str(rng: &mut Rng, max_len: usize) -> CowStr<'static> {
+    let len = rng.below(max_len + 1);
+    let s: String = (0..len)
+        .map(|_| {
+            if rng.chance(4) {
+                TRICKY_CHARS[rng.below(TRICKY_CHARS.len())]
+            } else {
+                (b'a' + rng.below(26) as u8) as char
+            }
+        })
+        .collect();
+    CowStr::from(s)
+}
+
+// empty, then half, then one less
+fn shorter_lengths(len: usize) -> Vec<usize> {
+    let mut lens = Vec::new();
+    for candidate in [0, len / 2, len.saturating_sub(1)] {
+        if candidate < len && !lens.contains(&candidate) {
+            lens.push(candidate);
+        }
+    }
+    lens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(v: &Value) -> usize {
+        match v {
+            Value::Array(arr) => 1 + arr.iter().map(size).sum::<usize>(),
+            Value::Map(map) => 1 + map.values().map(size).sum::<usize>(),
+            _ => 1,
+        }
+    }
+
+    fn depth(v: &Value) -> usize {
+        match v {
+            Value::Array(arr) => 1 + arr.iter().map(depth).max().unwrap_or(0),
+            Value::Map(map) => 1 + map.values().map(depth).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn test_arbitrary_is_deterministic_and_bounded() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..200 {
+            let v = Value::arbitrary_with(&mut a, 3, 5);
+            assert_eq!(v, Value::arbitrary_with(&mut b, 3, 5));
+            assert!(depth(&v) <= 3);
+            if let Value::Array(arr) = &v {
+                assert!(arr.len() <= 5);
+            }
+            if let Value::U64(u) = v {
+                assert!(u > i64::MAX as u64);
+            }
+            if let Value::Float(f) = v {
+                assert!(f.0.is_finite() && f.0.fract() != 0.0);
+            }
+        }
+
+        assert_ne!(
+            Value::arbitrary_with(&mut Rng::from_bytes(b"abc"), 4, 4),
+            Value::arbitrary_with(&mut Rng::from_bytes(b"abd"), 4, 4),
+        );
+    }
+
+    #[test]
+    fn test_shrink_terminates() {
+        let mut rng = Rng::new(1);
+        for _ in 0..50 {
+            let v = Value::arbitrary_with(&mut rng, 3, 4);
+            for candidate in v.shrink() {
+                assert_ne!(candidate, v);
+            }
+            // "everything fails" must still bottom out
+            assert_eq!(minimize(v, |_| true), Value::Null);
+        }
+    }
+
+    #[test]
+    fn test_minimize() {
+        fn has_long_str(v: &Value) -> bool {
+            match v {
+                Value::Str(s) => s.chars().count() >= 3,
+                Value::Array(arr) => arr.iter().any(has_long_str),
+                Value::Map(map) => map.values().any(has_long_str),
+                _ => false,
+            }
+        }
+
+        let mut rng = Rng::new(3);
+        let mut found = 0;
+        for _ in 0..500 {
+            let v = Value::arbitrary_with(&mut rng, 3, 4);
+            if !has_long_str(&v) {
+                continue;
+            }
+            found += 1;
+            let minimal = minimize(v.clone(), has_long_str);
+            assert!(size(&minimal) <= size(&v));
+            let Value::Str(s) = minimal else {
+                panic!("expected a string, got {minimal:?}")
+            };
+            assert_eq!(s.chars().count(), 3);
+        }
+        assert!(found > 0);
+    }
+}
@@ -0,0 +1,288 @@
+//! Arena-backed [Value](crate::Value)-like trees, for parse-inspect-drop
+//! workflows: every node, array and map of an [ArenaValue] lives in a
+//! [`Bump`] arena, so building one barely touches the global allocator, and
+//! dropping it is as cheap as resetting (or dropping) the arena.
+//!
+//! ```rust
+//! use merde_core::{
+//!     arena::{ArenaValue, Bump},
+//!     Event, EventBuffer, MapStart,
+//! };
+//!
+//! let bump = Bump::new();
+//! let mut events = EventBuffer::from_events([
+//!     Event::MapStart(MapStart { size_hint: None }),
+//!     Event::Str("name".into()),
+//!     Event::Str("merde".into()),
+//!     Event::MapEnd,
+//! ]);
+//! let value = ArenaValue::deserialize_in(&bump, &mut events).unwrap();
+//! assert_eq!(value.get("name").and_then(|v| v.as_str()), Some("merde"));
+//! ```
+
+pub use bumpalo::Bump;
+
+use alloc::{string::ToString, vec::Vec};
+use bumpalo::collections::Vec as BumpVec;
+
+use crate::{
+    Array, CowBytes, CowStr, DynDeserializer, DynSerializer, Event, EventType, Map, MapStart,
+    MerdeError, MetastackExt, Serialize, Value,
+};
+
+/// Like [Value], but with nodes allocated in a [Bump] arena. It's `Copy`,
+/// since it's only made of references into the arena (and into the input,
+/// for strings and bytes that didn't need copying).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaValue<'a> {
+    I64(i64),
+    U64(u64),
+    Float(f64),
+    Str(&'a str),
+    Bytes(&'a [u8]),
+    Null,
+    Bool(bool),
+    Array(&'a [ArenaValue<'a>]),
+    /// Entries, in the order they were deserialized. Duplicate keys are kept,
+    /// [ArenaValue::get] returns the last one.
+    Map(&'a [(&'a str, ArenaValue<'a>)]),
+}
+
+impl<'a> ArenaValue<'a> {
+    /// Deserializes a value into `bump`. Strings and bytes that the deserializer
+    /// borrowed from its input are borrowed as-is, the others are copied into
+    /// the arena.
+    pub fn deserialize_in<'s: 'a>(
+        bump: &'a Bump,
+        de: &mut dyn DynDeserializer<'s>,
+    ) -> Result<Self, MerdeError<'s>> {
+        build(bump, de).run_sync_with_metastack()
+    }
+
+    /// Returns the value for `key` if this is a map.
+    pub fn get(&self, key: &str) -> Option<&ArenaValue<'a>> {
+        match self {
+            ArenaValue::Map(entries) => entries
+                .iter()
+                .rev()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            ArenaValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&'a [ArenaValue<'a>]> {
+        match self {
+            ArenaValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&'a [(&'a str, ArenaValue<'a>)]> {
+        match self {
+            ArenaValue::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Copies this tree out of the arena, into a regular [Value] (strings
+    /// still borrow from the arena, or from the input).
+    pub fn to_value(&self) -> Value<'a> {
+        match *self {
+            ArenaValue::I64(i) => Value::I64(i),
+            ArenaValue::U64(u) => Value::U64(u),
+            ArenaValue::Float(f) => Value::Float(f.into()),
+            ArenaValue::Str(s) => Value::Str(CowStr::Borrowed(s)),
+            ArenaValue::Bytes(b) => Value::Bytes(CowBytes::Borrowed(b)),
+            ArenaValue::Null => Value::Null,
+            ArenaValue::Bool(b) => Value::Bool(b),
+            ArenaValue::Array(items) => Value::Array(Array::from(
+                items.iter().map(|v| v.to_value()).collect::<Vec<_>>(),
+            )),
+            ArenaValue::Map(entries) => Value::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| (CowStr::Borrowed(k), v.to_value()))
+                    .collect::<Map>(),
+            ),
+        }
+    }
+}
+
+fn str_in<'a, 's: 'a>(bump: &'a Bump, s: CowStr<'s>) -> &'a str {
+    match s {
+        CowStr::Borrowed(s) => s,
+        CowStr::Owned(s) => bump.alloc_str(&s),
+    }
+}
+
+async fn build<'a, 's: 'a>(
+    bump: &'a Bump,
+    de: &mut dyn DynDeserializer<'s>,
+) -> Result<ArenaValue<'a>, MerdeError<'s>> {
+    Ok(match de.next().await? {
+        Event::I64(i) => ArenaValue::I64(i),
+        Event::U64(u) => ArenaValue::U64(u),
+        // like `Value`, these have to fit in 64 bits
+        Event::I128(i) => match i64::try_from(i) {
+            Ok(i) => ArenaValue::I64(i),
+            Err(_) => ArenaValue::U64(u64::try_from(i).map_err(|_| MerdeError::OutOfRange)?),
+        },
+        Event::U128(u) => ArenaValue::U64(u64::try_from(u).map_err(|_| MerdeError::OutOfRange)?),
+        Event::F64(f) => ArenaValue::Float(f),
+        Event::Str(s) => ArenaValue::Str(str_in(bump, s)),
+        Event::Bytes(CowBytes::Borrowed(b)) => ArenaValue::Bytes(b),
+        Event::Bytes(CowBytes::Owned(b)) => ArenaValue::Bytes(bump.alloc_slice_copy(&b)),
+        Event::Bool(b) => ArenaValue::Bool(b),
+        Event::Null => ArenaValue::Null,
+        Event::MapStart(ms) => {
+            let mut entries = BumpVec::with_capacity_in(ms.size_hint.unwrap_or(0), bump);
+            loop {
+                match de.next().await? {
+                    Event::MapEnd => break,
+                    Event::Str(key) => {
+                        let key = str_in(bump, key);
+                        // see `Vec<T>`'s `Deserialize` impl
+                        let value = build(bump, de).with_metastack_resume_point().await?;
+                        entries.push((key, value));
+                    }
+                    ev => {
+                        return Err(MerdeError::UnexpectedEvent {
+                            got: EventType::from(&ev),
+                            expected: &[EventType::Str, EventType::MapEnd],
+                            help: None,
+                        })
+                    }
+                }
+            }
+            ArenaValue::Map(entries.into_bump_slice())
+        }
+        Event::ArrayStart(arr) => {
+            let mut items = BumpVec::with_capacity_in(arr.size_hint.unwrap_or(0), bump);
+            loop {
+                match de.next().await? {
+                    Event::ArrayEnd => break,
+                    ev => {
+                        de.put_back(ev)?;
+                        items.push(build(bump, de).with_metastack_resume_point().await?);
+                    }
+                }
+            }
+            ArenaValue::Array(items.into_bump_slice())
+        }
+        ev => {
+            return Err(MerdeError::UnexpectedEvent {
+                got: EventType::from(&ev),
+                expected: &[
+                    EventType::I64,
+                    EventType::U64,
+                    EventType::Float,
+                    EventType::Str,
+                    EventType::Bytes,
+                    EventType::Bool,
+                    EventType::Null,
+                    EventType::MapStart,
+                    EventType::ArrayStart,
+                ],
+                help: Some("(While trying to deserialize a merde ArenaValue)".to_string()),
+            })
+        }
+    })
+}
+
+impl Serialize for ArenaValue<'_> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        match *self {
+            ArenaValue::I64(i) => serializer.write(Event::I64(i)).await,
+            ArenaValue::U64(u) => serializer.write(Event::U64(u)).await,
+            ArenaValue::Float(f) => serializer.write(Event::F64(f)).await,
+            ArenaValue::Str(s) => serializer.write(Event::Str(s.into())).await,
+            ArenaValue::Bytes(b) => serializer.write(Event::Bytes(b.into())).await,
+            ArenaValue::Null => serializer.write(Event::Null).await,
+            ArenaValue::Bool(b) => serializer.write(Event::Bool(b)).await,
+            ArenaValue::Array(items) => {
+                items
+                    .serialize(serializer)
+                    .with_metastack_resume_point()
+                    .await
+            }
+            ArenaValue::Map(entries) => {
+                async move {
+                    serializer
+                        .write(Event::MapStart(MapStart {
+                            size_hint: Some(entries.len()),
+                        }))
+                        .await?;
+                    for (key, value) in entries {
+                        serializer.write(Event::Str((*key).into())).await?;
+                        value.serialize(serializer).await?;
+                    }
+                    serializer.write(Event::MapEnd).await
+                }
+                .with_metastack_resume_point()
+                .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStart, EventBuffer};
+
+    #[test]
+    fn test_arena_value() {
+        let bump = Bump::new();
+        let owned = String::from("copied into the arena, it's too long to be inline");
+        let mut events = EventBuffer::from_events([
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("items".into()),
+            Event::ArrayStart(ArrayStart { size_hint: Some(3) }),
+            Event::I64(-1),
+            Event::Str(CowStr::copy_from_str(&owned)),
+            Event::Null,
+            Event::ArrayEnd,
+            Event::Str("items".into()),
+            Event::Bool(true),
+            Event::MapEnd,
+        ]);
+        let value = ArenaValue::deserialize_in(&bump, &mut events).unwrap();
+        drop(owned);
+
+        let items = value.as_map().unwrap()[0].1.as_array().unwrap();
+        assert_eq!(items[0], ArenaValue::I64(-1));
+        assert!(items[1].as_str().unwrap().starts_with("copied"));
+        assert_eq!(value.get("items"), Some(&ArenaValue::Bool(true)));
+
+        let as_value = value.to_value();
+        assert_eq!(
+            as_value.as_map().unwrap().get(&CowStr::from("items")),
+            Some(&Value::Bool(true))
+        );
+    }
+
+    #[test]
+    fn test_arena_wide_ints() {
+        let bump = Bump::new();
+        let mut events = EventBuffer::from_events([Event::U128(u64::MAX as u128)]);
+        let value = ArenaValue::deserialize_in(&bump, &mut events).unwrap();
+        assert_eq!(value, ArenaValue::U64(u64::MAX));
+
+        let mut events = EventBuffer::from_events([Event::I128(-1 << 100)]);
+        assert!(matches!(
+            ArenaValue::deserialize_in(&bump, &mut events),
+            Err(MerdeError::OutOfRange)
+        ));
+    }
+}
@@ -1,6 +1,10 @@
-use std::ops::{Deref, DerefMut};
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
 
-use crate::{value::Value, IntoStatic};
+use crate::{
+    value::{deserialize_from_value, Value},
+    Deserialize, IntoStatic, MerdeError,
+};
 
 /// An array of [`Value`] items
 #[derive(PartialEq, Eq, Hash, Clone)]
@@ -19,10 +23,24 @@ impl<'s> Array<'s> {
     pub fn into_inner(self) -> Vec<Value<'s>> {
         self.0
     }
+
+    /// Estimates how many bytes this array (and everything in it) holds on
+    /// the heap, see [`Value::estimated_heap_size`].
+    pub fn estimated_heap_size(&self) -> usize {
+        self.0.capacity() * core::mem::size_of::<Value>()
+            + self.0.iter().map(Value::estimated_heap_size).sum::<usize>()
+    }
+
+    /// Releases unused capacity, in this array and everything in it,
+    /// recursively.
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+        self.0.iter_mut().for_each(Value::shrink_to_fit);
+    }
 }
 
-impl std::fmt::Debug for Array<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Array<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.0.fmt(f)
     }
 }
@@ -38,7 +56,7 @@ impl IntoStatic for Array<'_> {
 
 impl<'s> IntoIterator for Array<'s> {
     type Item = Value<'s>;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type IntoIter = alloc::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
@@ -78,3 +96,112 @@ impl<'s> Array<'s> {
         self
     }
 }
+
+impl<'s> Array<'s> {
+    /// Deserializes the item at `index` as a `T`, failing if there's no such item
+    /// or if it can't be converted.
+    ///
+    /// ```
+    /// use merde_core::{Array, CowStr};
+    ///
+    /// let arr = Array::new().with("hello").with(42);
+    /// let s: CowStr = arr.must_get(0).unwrap();
+    /// assert_eq!(s, "hello");
+    /// assert_eq!(arr.must_get::<u8>(1).unwrap(), 42);
+    /// assert!(arr.must_get::<u8>(2).is_err());
+    /// ```
+    pub fn must_get<T>(&self, index: usize) -> Result<T, MerdeError<'s>>
+    where
+        T: Deserialize<'s>,
+    {
+        match self.0.get(index) {
+            Some(v) => deserialize_from_value(v),
+            None => Err(MerdeError::IndexOutOfBounds {
+                index,
+                len: self.0.len(),
+            }),
+        }
+    }
+
+    /// Like [`Array::must_get`], but returns `None` if there's no such item
+    /// or if it can't be converted.
+    pub fn get_as<T>(&self, index: usize) -> Option<T>
+    where
+        T: Deserialize<'s>,
+    {
+        self.must_get(index).ok()
+    }
+
+    /// Iterates over the items, deserializing each of them as a `T`.
+    pub fn iter_as<T>(&self) -> impl Iterator<Item = Result<T, MerdeError<'s>>> + '_
+    where
+        T: Deserialize<'s>,
+    {
+        self.0.iter().map(deserialize_from_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Array, CowStr, Map, MerdeError, Value};
+
+    #[test]
+    fn test_heterogeneous_array() {
+        let arr = Array::new()
+            .with("name")
+            .with(3)
+            .with(Array::new().with(1.5).with(2.5))
+            .with(Map::new().with("k", Value::Null));
+
+        let name: CowStr = arr.must_get(0).unwrap();
+        assert_eq!(name, "name");
+        assert_eq!(arr.must_get::<u32>(1).unwrap(), 3);
+        assert_eq!(arr.must_get::<Vec<f64>>(2).unwrap(), vec![1.5, 2.5]);
+        assert_eq!(
+            arr.must_get::<std::collections::HashMap<String, Option<i64>>>(3)
+                .unwrap()["k"],
+            None
+        );
+
+        assert!(matches!(
+            arr.must_get::<u32>(4),
+            Err(MerdeError::IndexOutOfBounds { index: 4, len: 4 })
+        ));
+        assert_eq!(arr.get_as::<u32>(0), None);
+        assert_eq!(arr.get_as::<u32>(1), Some(3));
+    }
+
+    #[test]
+    fn test_iter_as() {
+        let arr = Array::new().with(1).with(2).with(3);
+        let v: Vec<u8> = arr.iter_as().collect::<Result<_, _>>().unwrap();
+        assert_eq!(v, [1, 2, 3]);
+
+        let arr = arr.with("four");
+        assert!(arr.iter_as::<u8>().collect::<Result<Vec<_>, _>>().is_err());
+    }
+
+    #[test]
+    fn test_heap_size_and_shrink() {
+        let long = "x".repeat(100);
+        let value_size = core::mem::size_of::<Value>();
+
+        // borrowed and inline strings don't count
+        let mut arr = Array::with_capacity(10)
+            .with(Value::from(long.as_str()))
+            .with(Value::from("short".to_string()))
+            .with(Value::from(long.clone()));
+        assert_eq!(
+            Value::Array(arr.clone()).estimated_heap_size(),
+            // the clone only has as much capacity as it needs
+            3 * value_size + long.len()
+        );
+        assert!(arr.estimated_heap_size() >= 10 * value_size + 100);
+
+        arr.shrink_to_fit();
+        assert_eq!(arr.capacity(), 3);
+
+        let map = Map::new().with("nested", arr);
+        assert!(Value::Map(map).estimated_heap_size() >= 3 * value_size + 100);
+    }
+}
@@ -0,0 +1,241 @@
+//! Provides [Base64], a wrapper that lets binary blobs live inside text formats
+//! like JSON, along with the [encode] and [decode] helpers it's built on.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    fmt,
+    future::Future,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{
+    CowBytes, CowStr, Deserialize, DynDeserializer, DynSerializer, Event, EventType, IntoStatic,
+    MerdeError, Serialize, WithLifetime,
+};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as standard base64 (RFC 4648, with padding).
+pub fn encode(input: &[u8]) -> String {
+    let mut out = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes standard base64 (RFC 4648). Padding is optional, but if it's there,
+/// it must be exactly what the length calls for: `Zg==`, not `Zg=` or `Zg===`.
+///
+/// On error, returns the byte index of the first offending character.
+pub fn decode(input: &str) -> Result<Vec<u8>, usize> {
+    let bytes = input.trim_end_matches('=').as_bytes();
+    if bytes.len() % 4 == 1 {
+        return Err(bytes.len() - 1);
+    }
+    let padding = input.len() - bytes.len();
+    let expected = (4 - bytes.len() % 4) % 4;
+    if padding != 0 && padding != expected {
+        return Err(bytes.len() + padding.min(expected));
+    }
+
+    let mut out = Vec::with_capacity(bytes.len() / 4 * 3 + 2);
+    for (chunk_index, chunk) in bytes.chunks(4).enumerate() {
+        let mut n: u32 = 0;
+        for (i, &c) in chunk.iter().enumerate() {
+            let v = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return Err(chunk_index * 4 + i),
+            };
+            n |= (v as u32) << (18 - 6 * i);
+        }
+        out.push((n >> 16) as u8);
+        if chunk.len() > 2 {
+            out.push((n >> 8) as u8);
+        }
+        if chunk.len() > 3 {
+            out.push(n as u8);
+        }
+    }
+    Ok(out)
+}
+
+/// Decodes a base64 string, mapping failures to a [`MerdeError::StringParsingError`].
+pub(crate) fn decode_str(s: CowStr<'_>) -> Result<Vec<u8>, MerdeError<'_>> {
+    decode(&s).map_err(|index| MerdeError::StringParsingError {
+        format: "base64",
+        source: s,
+        index,
+        message: "invalid base64".to_string(),
+    })
+}
+
+/// A wrapper around byte buffers that serializes them as base64 strings, and
+/// deserializes them from either base64 strings or raw bytes (for formats like
+/// msgpack that have a native representation).
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct Base64<T>(pub T);
+
+impl<'s> WithLifetime<'s> for Base64<CowBytes<'_>> {
+    type Lifetimed = Base64<CowBytes<'s>>;
+}
+
+impl WithLifetime<'_> for Base64<Vec<u8>> {
+    type Lifetimed = Self;
+}
+
+impl IntoStatic for Base64<CowBytes<'_>> {
+    type Output = Base64<CowBytes<'static>>;
+
+    fn into_static(self) -> Self::Output {
+        Base64(self.0.into_static())
+    }
+}
+
+impl IntoStatic for Base64<Vec<u8>> {
+    type Output = Base64<Vec<u8>>;
+
+    fn into_static(self) -> Self::Output {
+        self
+    }
+}
+
+impl<T> From<T> for Base64<T> {
+    fn from(t: T) -> Self {
+        Base64(t)
+    }
+}
+
+impl<T> Deref for Base64<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Base64<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> fmt::Debug for Base64<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> fmt::Display for Base64<T>
+where
+    T: AsRef<[u8]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode(self.0.as_ref()))
+    }
+}
+
+// Not `CowBytes::deserialize`: it asks for bytes, and formats that have both
+// (like MessagePack) would then hand over the base64 text itself
+async fn deserialize_base64<'s>(
+    de: &mut dyn DynDeserializer<'s>,
+) -> Result<CowBytes<'s>, MerdeError<'s>> {
+    match de.next().await? {
+        Event::Str(s) => Ok(decode_str(s)?.into()),
+        Event::Bytes(b) => Ok(b),
+        ev => Err(MerdeError::UnexpectedEvent {
+            got: EventType::from(&ev),
+            expected: &[EventType::Str, EventType::Bytes],
+            help: Some("(While trying to deserialize base64 data)".to_string()),
+        }),
+    }
+}
+
+impl<'s> Deserialize<'s> for Base64<CowBytes<'s>> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Base64(deserialize_base64(de).await?))
+    }
+}
+
+impl<'s> Deserialize<'s> for Base64<Vec<u8>> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Base64(deserialize_base64(de).await?.into_owned()))
+    }
+}
+
+impl<T> Serialize for Base64<T>
+where
+    T: AsRef<[u8]>,
+{
+    #[allow(clippy::manual_async_fn)]
+    fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        async move {
+            let s = encode(self.0.as_ref());
+            serializer.write(Event::Str(CowStr::Borrowed(&s))).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        for (raw, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+            (&[0xff, 0xfe, 0x00], "//4A"),
+        ] {
+            assert_eq!(encode(raw), encoded);
+            assert_eq!(decode(encoded).unwrap(), raw);
+            assert_eq!(decode(encoded.trim_end_matches('=')).unwrap(), raw);
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(decode("Zm9v!mFy"), Err(4));
+        assert_eq!(decode("Zm9vY"), Err(4));
+    }
+
+    #[test]
+    fn test_decode_invalid_padding() {
+        assert_eq!(decode("QQ=="), Ok(b"A".to_vec()));
+        assert_eq!(decode("QQ======"), Err(4));
+        assert_eq!(decode("QQ="), Err(3));
+        assert_eq!(decode("QUJD="), Err(4));
+        assert_eq!(decode("="), Err(0));
+    }
+}
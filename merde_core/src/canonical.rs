@@ -0,0 +1,254 @@
+use alloc::vec::Vec;
+use core::{
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+use ordered_float::OrderedFloat;
+
+use crate::{
+    Deserialize, DynDeserializer, DynSerializer, IntoStatic, MerdeError, Serialize, Value,
+    WithLifetime,
+};
+
+/// A [Value] that's compared and hashed by what it means rather than how it's
+/// represented, for deduplicating dynamically-typed payloads, or using them
+/// as map keys:
+///
+///   * numbers are compared by value: `1` (an `I64`), `1` (a `U64`) and `1.0`
+///     are all equal. So are `0.0` and `-0.0`, and all the NaNs;
+///   * maps are compared (and hashed) regardless of their keys' order.
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use merde_core::{CanonicalValue, Map, Value};
+///
+/// let payloads = [
+///     Value::from(Map::new().with("id", 1u64).with("ratio", 0.5)),
+///     Value::from(Map::new().with("ratio", 0.5).with("id", 1.0)),
+/// ];
+/// let unique: HashSet<CanonicalValue> = payloads.into_iter().map(CanonicalValue).collect();
+/// assert_eq!(unique.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct CanonicalValue<'s>(pub Value<'s>);
+
+impl<'s> CanonicalValue<'s> {
+    pub fn into_inner(self) -> Value<'s> {
+        self.0
+    }
+}
+
+impl<'s> Deref for CanonicalValue<'s> {
+    type Target = Value<'s>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'s> From<Value<'s>> for CanonicalValue<'s> {
+    fn from(value: Value<'s>) -> Self {
+        Self(value)
+    }
+}
+
+impl PartialEq for CanonicalValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        canonical_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CanonicalValue<'_> {}
+
+impl Hash for CanonicalValue<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        canonical_hash(&self.0, state)
+    }
+}
+
+enum Number {
+    Int(i128),
+    Float(OrderedFloat<f64>),
+}
+
+// Floats with an integer value are integers: that way, equal numbers are
+// hashed the same
+fn number(value: &Value<'_>) -> Option<Number> {
+    const LIMIT: f64 = 170141183460469231731687303715884105728.0; // 2^127
+    match value {
+        Value::I64(i) => Some(Number::Int(*i as i128)),
+        Value::U64(u) => Some(Number::Int(*u as i128)),
+        Value::Float(f) => {
+            let f = f.into_inner();
+            if (-LIMIT..LIMIT).contains(&f) && f == (f as i128) as f64 {
+                Some(Number::Int(f as i128))
+            } else {
+                Some(Number::Float(OrderedFloat(f)))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Compares values like [CanonicalValue] does.
+pub(crate) fn canonical_eq(a: &Value<'_>, b: &Value<'_>) -> bool {
+    match (a, b) {
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| canonical_eq(a, b))
+        }
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, v)| b.get(k).is_some_and(|w| canonical_eq(v, w)))
+        }
+        _ => match (number(a), number(b)) {
+            (Some(Number::Int(a)), Some(Number::Int(b))) => a == b,
+            // `OrderedFloat` considers all NaNs equal
+            (Some(Number::Float(a)), Some(Number::Float(b))) => a == b,
+            (None, None) => a == b,
+            _ => false,
+        },
+    }
+}
+
+fn canonical_hash<H: Hasher>(value: &Value<'_>, state: &mut H) {
+    match value {
+        Value::Null => state.write_u8(0),
+        Value::Bool(b) => {
+            state.write_u8(1);
+            b.hash(state);
+        }
+        Value::I64(_) | Value::U64(_) | Value::Float(_) => match number(value) {
+            Some(Number::Int(i)) => {
+                state.write_u8(2);
+                i.hash(state);
+            }
+            Some(Number::Float(f)) => {
+                state.write_u8(3);
+                f.hash(state);
+            }
+            None => unreachable!(),
+        },
+        Value::Str(s) => {
+            state.write_u8(4);
+            s.hash(state);
+        }
+        Value::Bytes(b) => {
+            state.write_u8(5);
+            b.hash(state);
+        }
+        Value::Array(arr) => {
+            state.write_u8(6);
+            state.write_usize(arr.len());
+            for v in arr.iter() {
+                canonical_hash(v, state);
+            }
+        }
+        Value::Map(map) => {
+            state.write_u8(7);
+            state.write_usize(map.len());
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+            for (k, v) in entries {
+                k.hash(state);
+                canonical_hash(v, state);
+            }
+        }
+    }
+}
+
+impl<'s> Deserialize<'s> for CanonicalValue<'s> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Self(Value::deserialize(de).await?))
+    }
+}
+
+impl Serialize for CanonicalValue<'_> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        self.0.serialize(serializer).await
+    }
+}
+
+impl IntoStatic for CanonicalValue<'_> {
+    type Output = CanonicalValue<'static>;
+
+    fn into_static(self) -> Self::Output {
+        CanonicalValue(self.0.into_static())
+    }
+}
+
+impl<'s> WithLifetime<'s> for CanonicalValue<'_> {
+    type Lifetimed = CanonicalValue<'s>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Array, Map};
+    use std::hash::{BuildHasher, RandomState};
+
+    fn hash(v: &CanonicalValue<'_>) -> u64 {
+        // the same keys for every call
+        static STATE: std::sync::OnceLock<RandomState> = std::sync::OnceLock::new();
+        STATE.get_or_init(RandomState::new).hash_one(v)
+    }
+
+    fn assert_same(a: impl Into<Value<'static>>, b: impl Into<Value<'static>>) {
+        let (a, b) = (CanonicalValue(a.into()), CanonicalValue(b.into()));
+        assert_eq!(a, b);
+        assert_eq!(hash(&a), hash(&b), "{a:?} and {b:?}");
+    }
+
+    #[test]
+    fn test_canonical_numbers() {
+        assert_same(1i64, 1u64);
+        assert_same(1u64, 1.0);
+        assert_same(-3i64, -3.0);
+        assert_same(0.0, -0.0);
+        assert_same(f64::NAN, -f64::NAN);
+        assert_same(1e30, 1e30);
+
+        let c = |v: Value<'static>| CanonicalValue(v);
+        assert_ne!(c(1i64.into()), c(1.5.into()));
+        assert_ne!(c(u64::MAX.into()), c((u64::MAX as f64).into()));
+        assert_ne!(c(0i64.into()), c(false.into()));
+        assert_ne!(c(0i64.into()), c(Value::Null));
+        assert_ne!(c("1".into()), c(1i64.into()));
+    }
+
+    #[test]
+    fn test_canonical_maps() {
+        let mut a = Map::new();
+        let mut b = Map::new();
+        for i in 0..32u64 {
+            a.insert(format!("key{i}").into(), Value::from(i));
+            b.insert(
+                format!("key{}", 31 - i).into(),
+                Value::from((31 - i) as f64),
+            );
+        }
+        assert_same(
+            Array::new().with(a.clone()).with(1u8),
+            Array::new().with(b.clone()).with(1i8),
+        );
+
+        // plain `Value`s hash maps regardless of their keys' order too
+        let b_ints: Map = b
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::from(v.as_f64().unwrap() as u64)))
+            .collect();
+        let state = RandomState::new();
+        assert_eq!(
+            state.hash_one(Value::from(a.clone())),
+            state.hash_one(Value::from(b_ints))
+        );
+
+        b.insert("key0".into(), Value::from(1u64));
+        assert_ne!(CanonicalValue(a.into()), CanonicalValue(b.into()));
+    }
+}
@@ -0,0 +1,455 @@
+use alloc::{
+    boxed::Box,
+    collections::VecDeque,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, future::Future, ops::Range, pin::Pin};
+
+use crate::{
+    CowStr, Deserialize, DeserializeInPlace, Deserializer, DynDeserializer, Event, EventPath,
+    EventType, IntoStatic, MerdeError, NumberPolicy, TypeHint,
+};
+
+/// A [Deserializer] that keeps going when the fields of `derive!`d structs
+/// fail to deserialize (because they're missing, have the wrong type, etc.),
+/// so that all of those errors are reported at once, instead of just the
+/// first one. That's what a config file's author wants to hear about.
+///
+/// If there were any such errors, deserializing returns
+/// [MerdeError::Multiple], with every error and the path of the field it's
+/// about, like `servers[1].port`.
+///
+/// ```text
+/// merde::derive! {
+///     impl (Deserialize) for struct Server { host, port }
+/// }
+///
+/// let input = r#"{"port": "eighty"}"#;
+/// let res: Result<Server, _> = CollectErrors::new(JsonDeserializer::new(input)).deserialize();
+/// // Err(Multiple([("port", UnexpectedEvent { .. }), ("host", MissingProperty("host"))]))
+/// ```
+///
+/// Only `derive!`d structs collect errors: other types, like maps and arrays,
+/// stop at their first one (if it's in a struct's field, the struct moves on
+/// to its next field).
+///
+/// Errors that leave the input in an unknown state, like syntax errors or
+/// the input ending early, still stop deserialization right away.
+pub struct CollectErrors<'s, D> {
+    inner: D,
+    collector: ErrorCollector<'s>,
+}
+
+impl<'s, D> CollectErrors<'s, D>
+where
+    D: Deserializer<'s>,
+{
+    /// Wraps `inner`, collecting errors instead of stopping at the first one.
+    pub fn new(inner: D) -> Self {
+        Self {
+            inner,
+            collector: ErrorCollector::default(),
+        }
+    }
+
+    /// Returns the wrapped deserializer.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<D: fmt::Debug> fmt::Debug for CollectErrors<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CollectErrors")
+            .field("inner", &self.inner)
+            .field("collector", &self.collector)
+            .finish()
+    }
+}
+
+impl<'s, D> Deserializer<'s> for CollectErrors<'s, D>
+where
+    D: Deserializer<'s>,
+{
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.collector.starters.pop_front() {
+            return Ok(ev);
+        }
+        let ev = self.inner.next().await?;
+        self.collector.path.advance_owned(&ev);
+        Ok(ev)
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.collector.starters.push_front(ev);
+        Ok(())
+    }
+
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.collector.starters.pop_front() {
+            return Ok(ev);
+        }
+        let ev = self.inner.next_event_with_hint(hint).await?;
+        self.collector.path.advance_owned(&ev);
+        Ok(ev)
+    }
+
+    async fn peek(&mut self) -> Result<EventType, MerdeError<'s>> {
+        match self.collector.starters.front() {
+            Some(ev) => Ok(ev.kind()),
+            None => self.inner.peek().await,
+        }
+    }
+
+    fn number_policy(&self) -> NumberPolicy {
+        self.inner.number_policy()
+    }
+
+    async fn skip_value(&mut self) -> Result<(), MerdeError<'s>> {
+        if !self.collector.starters.is_empty() || self.collector.path.is_key() {
+            // the value's already started, or it's a whole map entry:
+            // go through its events so the path stays right
+            let mut depth = 0usize;
+            loop {
+                match Deserializer::next(self).await? {
+                    Event::MapStart(_) | Event::ArrayStart(_) => depth += 1,
+                    Event::MapEnd | Event::ArrayEnd => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+        }
+        self.inner.skip_value().await?;
+        self.collector.path.finish_value();
+        Ok(())
+    }
+
+    async fn next_raw(
+        &mut self,
+        format: &'static str,
+    ) -> Result<Option<CowStr<'s>>, MerdeError<'s>> {
+        if !self.collector.starters.is_empty() {
+            // the inner deserializer is past the value's start
+            return Ok(None);
+        }
+        let raw = self.inner.next_raw(format).await?;
+        if raw.is_some() {
+            self.collector.path.finish_value();
+        }
+        Ok(raw)
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        self.inner.span()
+    }
+
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>> {
+        Some(&mut self.collector)
+    }
+}
+
+/// Where [CollectErrors] keeps the errors collected so far, and keeps track
+/// of where in the input they are. See [Deserializer::error_collector].
+#[derive(Debug, Default)]
+pub struct ErrorCollector<'s> {
+    path: EventPath<'static>,
+    // events put back: they're already accounted for in `path`
+    starters: VecDeque<Event<'s>>,
+    errors: Vec<(String, MerdeError<'static>)>,
+}
+
+impl ErrorCollector<'_> {
+    /// The errors collected so far, with the path of the field each is about.
+    pub fn errors(&self) -> &[(String, MerdeError<'static>)] {
+        &self.errors
+    }
+}
+
+type BoxFut<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Used by `derive!`d [Deserialize](crate::Deserialize) impls for structs:
+/// reports errors about their fields to the [ErrorCollector], if there's one.
+#[doc(hidden)]
+pub struct FieldErrors {
+    mark: Option<Box<Mark>>,
+}
+
+struct Mark {
+    // the depth of the struct's map, and its path
+    depth: usize,
+    path: String,
+    // how many errors there were before the struct
+    errors: usize,
+    // fields that failed, that aren't missing as far as the user is concerned
+    failed: Vec<String>,
+}
+
+impl FieldErrors {
+    /// To be called right after the struct's `MapStart`.
+    pub fn new(de: &mut dyn DynDeserializer<'_>) -> Self {
+        let mark = de.error_collector().map(|c| {
+            Box::new(Mark {
+                depth: c.path.depth(),
+                path: c.path.to_string(),
+                errors: c.errors.len(),
+                failed: Vec::new(),
+            })
+        });
+        Self { mark }
+    }
+
+    /// Reports an error about `field`'s value (or about `field` itself, for
+    /// unknown fields): returns it if errors aren't collected, otherwise skips
+    /// what's left of the value.
+    pub fn field<'a, 's: 'a>(
+        &'a mut self,
+        de: &'a mut dyn DynDeserializer<'s>,
+        field: &'a str,
+        err: MerdeError<'s>,
+    ) -> BoxFut<'a, Result<(), MerdeError<'s>>> {
+        // boxed, so it doesn't weigh on every struct's future
+        Box::pin(async move {
+            let Some(mark) = self.mark.as_mut() else {
+                return Err(err);
+            };
+            if !is_recoverable(&err) {
+                return Err(err);
+            }
+
+            let Some(collector) = de.error_collector() else {
+                return Err(err);
+            };
+            collector.starters.clear();
+            match err {
+                // collected by a nested struct, paths and all
+                MerdeError::Multiple(errors) => collector.errors.extend(errors),
+                err => collector
+                    .errors
+                    .push((join(&mark.path, field), err.into_static())),
+            }
+            mark.failed.push(field.to_string());
+
+            // skip until the struct's map is waiting for its next key
+            while let Some(collector) = de.error_collector() {
+                let depth = collector.path.depth();
+                if depth < mark.depth || (depth == mark.depth && collector.path.is_key()) {
+                    break;
+                }
+                de.next().await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Reports that `field` is missing: returns the error if errors aren't
+    /// collected. If its value failed to deserialize, that was reported already.
+    pub fn missing<'s>(
+        &mut self,
+        de: &mut dyn DynDeserializer<'s>,
+        field: &'static str,
+        err: MerdeError<'s>,
+    ) -> Result<(), MerdeError<'s>> {
+        let (Some(mark), Some(collector)) = (self.mark.as_mut(), de.error_collector()) else {
+            return Err(err);
+        };
+        if !mark.failed.iter().any(|f| f == field) {
+            collector
+                .errors
+                .push((join(&mark.path, field), err.into_static()));
+        }
+        Ok(())
+    }
+
+    /// Returns true if errors were collected about the struct's fields.
+    pub fn failed(&self) -> bool {
+        self.mark
+            .as_ref()
+            .is_some_and(|mark| !mark.failed.is_empty())
+    }
+
+    /// The error to return when some fields couldn't be deserialized: all the
+    /// errors collected since the struct started.
+    pub fn finish<'s>(self, de: &mut dyn DynDeserializer<'s>) -> MerdeError<'s> {
+        match (self.mark, de.error_collector()) {
+            (Some(mark), Some(collector)) => {
+                MerdeError::Multiple(collector.errors.split_off(mark.errors))
+            }
+            _ => MerdeError::MissingValue,
+        }
+    }
+}
+
+/// Used by `derive!`d [Deserialize] impls for structs whose opinions
+/// [recover from field errors](crate::DeserOpinions::recover_from_field_errors):
+/// deserializes a field's value, and if that fails, skips what's left of it
+/// and returns the error in `Ok(Err(_))`. Errors the input can't recover from
+/// are returned as-is.
+#[doc(hidden)]
+pub fn deserialize_or_skip<'de, 's: 'de, T: Deserialize<'s> + 'de>(
+    de: &'de mut dyn DynDeserializer<'s>,
+) -> BoxFut<'de, Result<Result<T, MerdeError<'s>>, MerdeError<'s>>> {
+    Box::pin(async move {
+        let mut guard = SkipGuard::new(de);
+        let res = T::deserialize(&mut guard).await;
+        guard.recover(res).await
+    })
+}
+
+/// Like [deserialize_or_skip], for `derive!`d
+/// [DeserializeInPlace](crate::DeserializeInPlace) impls: if deserializing
+/// into `dst` fails, it may have been partially updated.
+#[doc(hidden)]
+pub fn deserialize_in_place_or_skip<'de, 's: 'de, T: DeserializeInPlace<'s> + 'de>(
+    dst: &'de mut T,
+    de: &'de mut dyn DynDeserializer<'s>,
+) -> BoxFut<'de, Result<Result<(), MerdeError<'s>>, MerdeError<'s>>> {
+    Box::pin(async move {
+        let mut guard = SkipGuard::new(de);
+        let res = dst.deserialize_in_place(&mut guard).await;
+        guard.recover(res).await
+    })
+}
+
+// Keeps track of how much of a value has been read
+struct SkipGuard<'de, 's> {
+    inner: &'de mut (dyn DynDeserializer<'s> + 'de),
+    // how many maps and arrays are open
+    depth: usize,
+    // whether the value's first event has been read
+    started: bool,
+}
+
+impl fmt::Debug for SkipGuard<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SkipGuard")
+            .field("depth", &self.depth)
+            .field("started", &self.started)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de, 's> SkipGuard<'de, 's> {
+    fn new(inner: &'de mut (dyn DynDeserializer<'s> + 'de)) -> Self {
+        Self {
+            inner,
+            depth: 0,
+            started: false,
+        }
+    }
+
+    // Skips what's left of the value if it failed to deserialize, and the
+    // input can be recovered from.
+    async fn recover<T>(
+        &mut self,
+        res: Result<T, MerdeError<'s>>,
+    ) -> Result<Result<T, MerdeError<'s>>, MerdeError<'s>> {
+        match res {
+            Ok(value) => Ok(Ok(value)),
+            Err(err) if is_recoverable(&err) => {
+                while !self.started || self.depth > 0 {
+                    Deserializer::next(self).await?;
+                }
+                Ok(Err(err))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn advance(&mut self, ev: &Event<'s>) {
+        match ev {
+            Event::MapStart(_) | Event::ArrayStart(_) => self.depth += 1,
+            Event::MapEnd | Event::ArrayEnd => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        self.started = true;
+    }
+}
+
+impl<'s> Deserializer<'s> for SkipGuard<'_, 's> {
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        let ev = self.inner.next().await?;
+        self.advance(&ev);
+        Ok(ev)
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        match &ev {
+            Event::MapStart(_) | Event::ArrayStart(_) => self.depth = self.depth.saturating_sub(1),
+            Event::MapEnd | Event::ArrayEnd => self.depth += 1,
+            _ => {}
+        }
+        // at the top level, only the value's first event can be put back
+        if self.depth == 0 && !matches!(ev, Event::MapEnd | Event::ArrayEnd) {
+            self.started = false;
+        }
+        self.inner.put_back(ev)
+    }
+
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        let ev = self.inner.next_event_with_hint(hint).await?;
+        self.advance(&ev);
+        Ok(ev)
+    }
+
+    async fn peek(&mut self) -> Result<EventType, MerdeError<'s>> {
+        self.inner.peek().await
+    }
+
+    fn number_policy(&self) -> NumberPolicy {
+        self.inner.number_policy()
+    }
+
+    async fn skip_value(&mut self) -> Result<(), MerdeError<'s>> {
+        self.inner.skip_value().await?;
+        self.started = true;
+        Ok(())
+    }
+
+    async fn next_raw(
+        &mut self,
+        format: &'static str,
+    ) -> Result<Option<CowStr<'s>>, MerdeError<'s>> {
+        let raw = self.inner.next_raw(format).await?;
+        if raw.is_some() {
+            self.started = true;
+        }
+        Ok(raw)
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        self.inner.span()
+    }
+
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>> {
+        self.inner.error_collector()
+    }
+}
+
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        alloc::format!("{path}.{field}")
+    }
+}
+
+// Errors after which the input can still be read
+fn is_recoverable(err: &MerdeError<'_>) -> bool {
+    match err {
+        MerdeError::UnexpectedEof
+        | MerdeError::Utf8Error(_)
+        | MerdeError::StringParsingError { .. }
+        | MerdeError::DisallowedSyntax { .. }
+        | MerdeError::BinaryParsingError { .. }
+        | MerdeError::PutBackCalledTwice => false,
+        #[cfg(feature = "std")]
+        MerdeError::Io(_) => false,
+        MerdeError::Context { source, .. } | MerdeError::Field { source, .. } => {
+            is_recoverable(source)
+        }
+        _ => true,
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{
+    Deserialize, DynDeserializerExt, DynSerializerExt, EventCollector, MerdeError, Serialize,
+};
+
+/// Converts a value of one type into another, by serializing it and feeding the
+/// events to the other type's [Deserialize] impl — as if it went through JSON,
+/// except there's no JSON: no formatting, no parsing.
+///
+/// This makes for cheap "DTO to domain model" mappings: both types only need
+/// to agree on the shape of the data, and field renames are handled by their
+/// respective `derive!` opinions.
+///
+/// Strings are copied (except small ones), so the result doesn't borrow from
+/// `src`.
+///
+/// ```rust
+/// use merde_core::{convert, Map, Value};
+///
+/// let src: Value = Map::new().with("a", Value::from(1)).with("b", Value::from(2)).into();
+/// let dst: std::collections::HashMap<String, u8> = convert(&src).unwrap();
+/// assert_eq!(dst["b"], 2);
+/// ```
+pub fn convert<Src, Dst>(src: &Src) -> Result<Dst, MerdeError<'static>>
+where
+    Src: Serialize,
+    Dst: Deserialize<'static>,
+{
+    let mut events = EventCollector::new();
+    events.serialize(src)?;
+    events.into_buffer().deserialize()
+}
@@ -1,6 +1,6 @@
+use alloc::{borrow::Cow, vec::Vec};
 use compact_bytes::CompactBytes;
-use std::{
-    borrow::Cow,
+use core::{
     fmt,
     hash::{Hash, Hasher},
     ops::Deref,
@@ -27,6 +27,22 @@ impl<'a> CowBytes<'a> {
             CowBytes::Owned(b) => b.to_vec(),
         }
     }
+
+    /// How many bytes this holds on the heap: zero if it's borrowed, or if
+    /// it's short enough to be stored inline.
+    pub fn estimated_heap_size(&self) -> usize {
+        match self {
+            CowBytes::Owned(b) if b.spilled() => b.capacity(),
+            _ => 0,
+        }
+    }
+
+    /// Releases unused capacity, if the bytes are owned.
+    pub fn shrink_to_fit(&mut self) {
+        if let CowBytes::Owned(b) = self {
+            b.shrink_to_fit();
+        }
+    }
 }
 
 impl AsRef<[u8]> for CowBytes<'_> {
@@ -1,8 +1,9 @@
-use std::{
-    borrow::Cow,
+use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+use core::{
     fmt,
     hash::{Hash, Hasher},
     ops::Deref,
+    slice::SliceIndex,
 };
 
 use compact_str::CompactString;
@@ -31,12 +32,12 @@ impl CowStr<'static> {
 
 impl<'s> CowStr<'s> {
     #[inline]
-    pub fn from_utf8(s: &'s [u8]) -> Result<Self, std::str::Utf8Error> {
-        Ok(Self::Borrowed(std::str::from_utf8(s)?))
+    pub fn from_utf8(s: &'s [u8]) -> Result<Self, core::str::Utf8Error> {
+        Ok(Self::Borrowed(core::str::from_utf8(s)?))
     }
 
     #[inline]
-    pub fn from_utf8_owned(s: Vec<u8>) -> Result<Self, std::str::Utf8Error> {
+    pub fn from_utf8_owned(s: Vec<u8>) -> Result<Self, core::str::Utf8Error> {
         Ok(Self::Owned(CompactString::from_utf8(s)?))
     }
 
@@ -52,6 +53,70 @@ impl<'s> CowStr<'s> {
     pub unsafe fn from_utf8_unchecked(s: &'s [u8]) -> Self {
         Self::Owned(CompactString::from_utf8_unchecked(s))
     }
+
+    /// Returns a substring, borrowing from the same source if this string is
+    /// borrowed (so no copy is made), and copying it otherwise.
+    ///
+    /// Panics if `range` is out of bounds or not on a `char` boundary, like
+    /// indexing a `str` would.
+    pub fn slice<R>(&self, range: R) -> CowStr<'s>
+    where
+        R: SliceIndex<str, Output = str>,
+    {
+        match self {
+            CowStr::Borrowed(s) => CowStr::Borrowed(&s[range]),
+            CowStr::Owned(s) => CowStr::Owned(CompactString::from(&s.as_str()[range])),
+        }
+    }
+
+    /// Like [`str::split_once`], but returns substrings that borrow from the
+    /// same source as `this` when it's borrowed, see [`CowStr::slice`].
+    ///
+    /// This is an associated function rather than a method, so that
+    /// `s.split_once(..)` still calls the `str` method.
+    ///
+    /// ```rust
+    /// use merde_core::CowStr;
+    ///
+    /// let id = CowStr::Borrowed("user:42");
+    /// let (kind, rest) = CowStr::split_once(&id, ":").unwrap();
+    /// assert!(matches!(kind, CowStr::Borrowed("user")));
+    /// assert!(matches!(rest, CowStr::Borrowed("42")));
+    /// ```
+    pub fn split_once(this: &Self, delimiter: &str) -> Option<(CowStr<'s>, CowStr<'s>)> {
+        let i = this.find(delimiter)?;
+        Some((this.slice(..i), this.slice(i + delimiter.len()..)))
+    }
+
+    /// Like [`str::rsplit_once`], see [`CowStr::split_once`].
+    pub fn rsplit_once(this: &Self, delimiter: &str) -> Option<(CowStr<'s>, CowStr<'s>)> {
+        let i = this.rfind(delimiter)?;
+        Some((this.slice(..i), this.slice(i + delimiter.len()..)))
+    }
+
+    /// Like [`str::split`], see [`CowStr::split_once`].
+    pub fn split<'a>(this: &'a Self, delimiter: &'a str) -> impl Iterator<Item = CowStr<'s>> + 'a {
+        str::split(this, delimiter).map(move |piece| {
+            let start = piece.as_ptr() as usize - this.as_ptr() as usize;
+            this.slice(start..start + piece.len())
+        })
+    }
+
+    /// How many bytes this string holds on the heap: zero if it's borrowed,
+    /// or if it's short enough to be stored inline.
+    pub fn estimated_heap_size(&self) -> usize {
+        match self {
+            CowStr::Owned(s) if s.is_heap_allocated() => s.capacity(),
+            _ => 0,
+        }
+    }
+
+    /// Releases unused capacity, if the string is owned.
+    pub fn shrink_to_fit(&mut self) {
+        if let CowStr::Owned(s) = self {
+            s.shrink_to_fit();
+        }
+    }
 }
 
 impl AsRef<str> for CowStr<'_> {
@@ -64,6 +129,15 @@ impl AsRef<str> for CowStr<'_> {
     }
 }
 
+// `Hash` and `Eq` agree with `str`'s, so maps keyed by `CowStr` can be
+// looked up with a `&str`
+impl core::borrow::Borrow<str> for CowStr<'_> {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_ref()
+    }
+}
+
 impl Deref for CowStr<'_> {
     type Target = str;
 
@@ -312,9 +386,38 @@ mod tests {
         assert_ne!(cow_str1, cow_str3);
     }
 
+    #[test]
+    fn test_slice_keeps_borrowedness() {
+        let source = String::from("tenant:user:42");
+        let borrowed = CowStr::from(source.as_str());
+        let owned = CowStr::from(source.clone());
+
+        for s in [&borrowed, &owned] {
+            let piece = s.slice(7..11);
+            assert_eq!(piece, "user");
+            assert_eq!(
+                matches!(piece, CowStr::Borrowed(_)),
+                matches!(s, CowStr::Borrowed(_))
+            );
+
+            let (head, tail) = CowStr::rsplit_once(s, ":").unwrap();
+            assert_eq!((head.as_ref(), tail.as_ref()), ("tenant:user", "42"));
+            assert_eq!(CowStr::split_once(s, "::"), None);
+            let parts: Vec<CowStr> = CowStr::split(s, ":").collect();
+            assert_eq!(parts, ["tenant", "user", "42"]);
+        }
+
+        // borrowed substrings point into the source
+        let (head, _) = CowStr::split_once(&borrowed, ":").unwrap();
+        assert_eq!(head.as_ptr(), source.as_ptr());
+
+        // the `str` method is still reachable
+        assert_eq!(borrowed.split_once(':'), Some(("tenant", "user:42")));
+    }
+
     #[cfg(feature = "rusqlite")]
     #[test]
-    fn test_rusqlite_integration() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_rusqlite_integration() -> Result<(), Box<dyn core::error::Error>> {
         use rusqlite::Connection;
 
         // Create an in-memory database
@@ -1,32 +1,228 @@
-use std::{
-    any::TypeId,
+use alloc::{
     borrow::Cow,
-    collections::HashMap,
+    boxed::Box,
+    format,
+    rc::Rc,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    any::TypeId,
     future::Future,
     hash::{BuildHasher, Hash},
     marker::PhantomData,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+    },
+    ops::{ControlFlow, Range},
     pin::Pin,
 };
+#[cfg(feature = "std")]
+use std::path::PathBuf;
 
 use crate::{
-    metastack::MetastackExt, Array, CowStr, Event, EventType, IntoStatic, Map, MerdeError, Value,
+    deserialize_in_place::DeserializeInPlace, instrument, metastack::MetastackExt, Array, CowBytes,
+    CowStr, ErrorCollector, Event, EventType, HashMap, IntoStatic, Map, MerdeError, Value,
     WithLifetime,
 };
 
-pub trait Deserializer<'s>: std::fmt::Debug {
+/// A source of [Event]s: that's what format crates implement (`JsonDeserializer`,
+/// `YamlDeserializer`, etc.), and what [Deserialize] impls consume.
+///
+/// Events form a tree: maps are `MapStart`, then alternating keys and values,
+/// then `MapEnd`. Arrays are `ArrayStart`, values, then `ArrayEnd`. Any value
+/// can itself be a map or an array.
+///
+/// Hand-written [Deserialize] impls (like the one for [Value]) usually call `next`,
+/// match on the event, and recurse for nested values. See the `custom-ast` example
+/// in the merde crate for a complete walkthrough.
+pub trait Deserializer<'s>: core::fmt::Debug {
     /// Get the next event from the deserializer.
     fn next(&mut self) -> impl Future<Output = Result<Event<'s>, MerdeError<'s>>> + '_;
 
-    /// Put back an event into the deserializer.
+    /// Put back an event into the deserializer, so that it's returned by the next
+    /// call to `next`. This lets a [Deserialize] impl peek at an event before
+    /// handing the rest of the work to another impl.
+    ///
+    /// Any number of events can be put back: they're returned last-in, first-out,
+    /// so to undo reading `a` then `b`, put back `b`, then `a`. To look further
+    /// ahead than a few events, record them into an [EventBuffer](crate::EventBuffer).
     fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>>;
+
+    /// Get the next event from the deserializer, knowing what type the caller
+    /// is about to deserialize.
+    ///
+    /// Formats that have several encodings for the same kind of value can use
+    /// the hint to pick one: MessagePack, for example, returns `str` payloads
+    /// as bytes when asked for [TypeHint::Bytes], and `bin` payloads as
+    /// strings (if they're valid UTF-8) when asked for [TypeHint::Str]. YAML
+    /// returns plain scalars as written (`1.10`, `012345`) rather than typed
+    /// by their looks when asked for [TypeHint::Str].
+    ///
+    /// The hint is only a hint: callers must still handle any event. By default,
+    /// it's ignored and this is the same as `next`.
+    fn next_event_with_hint(
+        &mut self,
+        hint: TypeHint,
+    ) -> impl Future<Output = Result<Event<'s>, MerdeError<'s>>> + '_ {
+        let _ = hint;
+        self.next()
+    }
+
+    /// Returns the type of the next event, without consuming it, for impls
+    /// that only need to branch on it (see `Option<T>`'s [Deserialize] impl).
+    ///
+    /// By default, this reads the next event and puts it back.
+    fn peek(&mut self) -> impl Future<Output = Result<EventType, MerdeError<'s>>> + '_ {
+        async move {
+            let ev = self.next().await?;
+            let ty = ev.kind();
+            self.put_back(ev)?;
+            Ok(ty)
+        }
+    }
+
+    /// How the [Deserialize] impls for numbers should handle values that
+    /// don't fit the requested type exactly, see [NumberPolicy].
+    ///
+    /// Defaults to [NumberPolicy::Lenient]. Wrap any deserializer in a
+    /// [StrictNumbers](crate::StrictNumbers) to make it strict.
+    fn number_policy(&self) -> NumberPolicy {
+        NumberPolicy::Lenient
+    }
+
+    /// Skips the next value, nested values included: that's what [Deserialize]
+    /// impls do with unknown fields.
+    ///
+    /// By default, this reads the value's events and drops them. Formats can do
+    /// better: merde_json skips over the text without decoding anything, and
+    /// merde_msgpack jumps over strings, maps and arrays using their lengths.
+    fn skip_value(&mut self) -> impl Future<Output = Result<(), MerdeError<'s>>> + '_ {
+        async move {
+            let mut depth = 0usize;
+            loop {
+                match self.next().await? {
+                    Event::MapStart(_) | Event::ArrayStart(_) => depth += 1,
+                    Event::MapEnd | Event::ArrayEnd if depth > 0 => depth -= 1,
+                    ev @ (Event::MapEnd | Event::ArrayEnd) => {
+                        return Err(MerdeError::UnexpectedEvent {
+                            got: EventType::from(&ev),
+                            expected: &[],
+                            help: Some("(While skipping a value)".to_string()),
+                        })
+                    }
+                    _ => {}
+                }
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Skips the next value, and returns it as it's written in the input, if
+    /// the input is in `format` (like `"json"`) and the deserializer can do
+    /// that. This is how types like merde_json's `RawValue` capture values
+    /// verbatim.
+    ///
+    /// By default, this returns `None` without consuming anything, and callers
+    /// fall back to reading events.
+    fn next_raw(
+        &mut self,
+        format: &'static str,
+    ) -> impl Future<Output = Result<Option<CowStr<'s>>, MerdeError<'s>>> + '_ {
+        let _ = format;
+        async { Ok(None) }
+    }
+
+    /// Where the last event returned is in the input, in bytes: for a map
+    /// or an array, that's just its opening (or closing) delimiter. See
+    /// [Spanned](crate::Spanned) for the span of a whole value.
+    ///
+    /// By default, this returns `None`: not all formats (or inputs) have
+    /// positions to report.
+    fn span(&self) -> Option<Range<usize>> {
+        None
+    }
+
+    /// Where errors about struct fields go when they shouldn't stop
+    /// deserialization, see [CollectErrors](crate::CollectErrors).
+    ///
+    /// By default, this returns `None`: the first error is returned.
+    /// Deserializers that wrap another one should forward this.
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>> {
+        None
+    }
+}
+
+/// What type a [Deserialize] impl is about to deserialize, see
+/// [Deserializer::next_event_with_hint].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TypeHint {
+    /// A string, like [CowStr] or [String]
+    Str,
+    /// A byte string, like [CowBytes]
+    Bytes,
+    /// A number, as written in the input, see [RawNumber](crate::RawNumber).
+    /// Text formats can return it as an [Event::Str].
+    RawNumber,
+}
+
+/// How the [Deserialize] impls for numbers handle values that don't fit the
+/// requested type exactly, see [Deserializer::number_policy].
+///
+/// Integers that don't fit the requested integer type (`300` for a `u8`, `-1`
+/// for a `u64`) are rejected with [MerdeError::OutOfRange] either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum NumberPolicy {
+    /// Convert as `as` casts would: `1.5` deserializes as `1u32`, `-1.0` as
+    /// `0u32`, `NaN` as `0`, and integers above 2^53 are rounded when
+    /// deserialized as `f64`.
+    #[default]
+    Lenient,
+
+    /// Reject conversions that lose information with [MerdeError::LossyNumber]:
+    /// floats with a fractional part, `NaN`, infinities and floats out of range
+    /// for integer types, and integers that `f64` can't represent exactly.
+    ///
+    /// `f32` only rejects values that are out of its range, since most
+    /// decimal inputs (like `0.1`) aren't exact `f32`s anyway.
+    Strict,
 }
 
 type BoxFut<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
 
+/// The object-safe version of [Deserializer], implemented for every [Deserializer]:
+/// this is what [Deserialize] impls are handed.
 pub trait DynDeserializer<'s> {
     fn next<'de>(&'de mut self) -> BoxFut<'de, Result<Event<'s>, MerdeError<'s>>>;
 
     fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>>;
+
+    fn next_event_with_hint<'de>(
+        &'de mut self,
+        hint: TypeHint,
+    ) -> BoxFut<'de, Result<Event<'s>, MerdeError<'s>>>;
+
+    fn peek<'de>(&'de mut self) -> BoxFut<'de, Result<EventType, MerdeError<'s>>>;
+
+    fn number_policy(&self) -> NumberPolicy;
+
+    fn skip_value<'de>(&'de mut self) -> BoxFut<'de, Result<(), MerdeError<'s>>>;
+
+    fn next_raw<'de>(
+        &'de mut self,
+        format: &'static str,
+    ) -> BoxFut<'de, Result<Option<CowStr<'s>>, MerdeError<'s>>>;
+
+    fn span(&self) -> Option<Range<usize>>;
+
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>>;
 }
 
 impl dyn DynDeserializer<'_> {
@@ -44,6 +240,40 @@ where
     fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
         Deserializer::put_back(self, ev)
     }
+
+    fn next_event_with_hint(
+        &mut self,
+        hint: TypeHint,
+    ) -> BoxFut<'_, Result<Event<'s>, MerdeError<'s>>> {
+        Box::pin(Deserializer::next_event_with_hint(self, hint))
+    }
+
+    fn peek(&mut self) -> BoxFut<'_, Result<EventType, MerdeError<'s>>> {
+        Box::pin(Deserializer::peek(self))
+    }
+
+    fn number_policy(&self) -> NumberPolicy {
+        Deserializer::number_policy(self)
+    }
+
+    fn skip_value(&mut self) -> BoxFut<'_, Result<(), MerdeError<'s>>> {
+        Box::pin(Deserializer::skip_value(self))
+    }
+
+    fn next_raw(
+        &mut self,
+        format: &'static str,
+    ) -> BoxFut<'_, Result<Option<CowStr<'s>>, MerdeError<'s>>> {
+        Box::pin(Deserializer::next_raw(self, format))
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        Deserializer::span(self)
+    }
+
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>> {
+        Deserializer::error_collector(self)
+    }
 }
 
 pub trait DynDeserializerExt<'s> {
@@ -56,6 +286,18 @@ pub trait DynDeserializerExt<'s> {
     fn deserialize<T: Deserialize<'s>>(&mut self) -> Result<T, MerdeError<'s>>;
 
     fn deserialize_owned<T: DeserializeOwned>(&mut self) -> Result<T, MerdeError<'s>>;
+
+    /// Like [deserialize](DynDeserializerExt::deserialize), but resume points
+    /// don't check the stack, spill onto an emptier one, or allocate: see
+    /// [MetastackExt::run_sync_flat]. Only for inputs whose depth is known to
+    /// be bounded, since deeply nested ones overflow the stack.
+    fn deserialize_sync_flat<T: Deserialize<'s>>(&mut self) -> Result<T, MerdeError<'s>>;
+
+    /// Deserializes into an existing value, see [DeserializeInPlace].
+    fn deserialize_into<T: DeserializeInPlace<'s>>(
+        &mut self,
+        dst: &mut T,
+    ) -> Result<(), MerdeError<'s>>;
 }
 
 impl<'s, D> DynDeserializerExt<'s> for D
@@ -72,11 +314,30 @@ where
     }
 
     fn deserialize<T: Deserialize<'s>>(&mut self) -> Result<T, MerdeError<'s>> {
-        T::deserialize(self).run_sync_with_metastack()
+        instrument::deserialize(self, core::any::type_name::<T>(), |de| {
+            T::deserialize(de).run_sync_with_metastack()
+        })
     }
 
     fn deserialize_owned<T: DeserializeOwned>(&mut self) -> Result<T, MerdeError<'s>> {
-        T::deserialize_owned(self).run_sync_with_metastack()
+        instrument::deserialize(self, core::any::type_name::<T>(), |de| {
+            T::deserialize_owned(de).run_sync_with_metastack()
+        })
+    }
+
+    fn deserialize_sync_flat<T: Deserialize<'s>>(&mut self) -> Result<T, MerdeError<'s>> {
+        instrument::deserialize(self, core::any::type_name::<T>(), |de| {
+            T::deserialize(de).run_sync_flat()
+        })
+    }
+
+    fn deserialize_into<T: DeserializeInPlace<'s>>(
+        &mut self,
+        dst: &mut T,
+    ) -> Result<(), MerdeError<'s>> {
+        instrument::deserialize(self, core::any::type_name::<T>(), |de| {
+            dst.deserialize_in_place(de).run_sync_with_metastack()
+        })
     }
 }
 
@@ -91,11 +352,30 @@ impl<'s> DynDeserializerExt<'s> for dyn DynDeserializer<'s> + '_ {
     }
 
     fn deserialize<T: Deserialize<'s>>(&mut self) -> Result<T, MerdeError<'s>> {
-        T::deserialize(self).run_sync_with_metastack()
+        instrument::deserialize(self, core::any::type_name::<T>(), |de| {
+            T::deserialize(de).run_sync_with_metastack()
+        })
     }
 
     fn deserialize_owned<T: DeserializeOwned>(&mut self) -> Result<T, MerdeError<'s>> {
-        T::deserialize_owned(self).run_sync_with_metastack()
+        instrument::deserialize(self, core::any::type_name::<T>(), |de| {
+            T::deserialize_owned(de).run_sync_with_metastack()
+        })
+    }
+
+    fn deserialize_sync_flat<T: Deserialize<'s>>(&mut self) -> Result<T, MerdeError<'s>> {
+        instrument::deserialize(self, core::any::type_name::<T>(), |de| {
+            T::deserialize(de).run_sync_flat()
+        })
+    }
+
+    fn deserialize_into<T: DeserializeInPlace<'s>>(
+        &mut self,
+        dst: &mut T,
+    ) -> Result<(), MerdeError<'s>> {
+        instrument::deserialize(self, core::any::type_name::<T>(), |de| {
+            dst.deserialize_in_place(de).run_sync_with_metastack()
+        })
     }
 }
 
@@ -106,7 +386,7 @@ mod mini_typeid {
     // We don't really need const type_id construction or older rustc support, so this is a minimal
     // take on it.
 
-    use std::{any::TypeId, marker::PhantomData};
+    use core::{any::TypeId, marker::PhantomData};
 
     #[must_use]
     #[inline(always)]
@@ -132,7 +412,7 @@ mod mini_typeid {
 
         let phantom_data = PhantomData::<T>;
         NonStaticAny::get_type_id(unsafe {
-            std::mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&phantom_data)
+            core::mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&phantom_data)
         })
     }
 }
@@ -152,10 +432,10 @@ impl<'s, 'borrow: 's> FieldSlot<'s, 'borrow> {
     pub fn new<T: 's>(option: &'borrow mut Option<T>) -> Self {
         Self {
             option: unsafe {
-                std::mem::transmute::<*mut Option<T>, *mut Option<()>>(option as *mut _)
+                core::mem::transmute::<*mut Option<T>, *mut Option<()>>(option as *mut _)
             },
             type_id_of_field: mini_typeid::of::<T>(),
-            type_name_of_field: std::any::type_name::<T>(),
+            type_name_of_field: core::any::type_name::<T>(),
             _phantom: PhantomData,
         }
     }
@@ -167,12 +447,12 @@ impl<'s, 'borrow: 's> FieldSlot<'s, 'borrow> {
             self.type_id_of_field,
             type_id_of_value,
             "tried to assign a \x1b[33m{}\x1b[0m to a slot of type \x1b[34m{}\x1b[0m",
-            std::any::type_name::<T>(),
+            core::any::type_name::<T>(),
             self.type_name_of_field,
         );
 
         unsafe {
-            let option_ptr: *mut Option<T> = std::mem::transmute(self.option);
+            let option_ptr: *mut Option<T> = core::mem::transmute(self.option);
             (*option_ptr).replace(value);
         }
     }
@@ -203,6 +483,59 @@ pub trait DeserOpinions {
     /// called with "jazz_band".
     #[allow(clippy::needless_lifetimes)]
     fn default_field_value<'s, 'borrow>(&self, key: &'borrow str, slot: FieldSlot<'s, 'borrow>);
+
+    /// If we encounter `{ "name": "  Amos " }`, `map_string_value` is called with
+    /// `field = "name"` and `value = "  Amos "`, and can return a cleaned-up version
+    /// (trimmed, normalized, etc.) before it's deserialized into the field.
+    ///
+    /// This is only called when the field's value is a string: strings nested in
+    /// arrays or maps are left alone. Like [DeserOpinions::default_field_value], it's
+    /// called with the field name, after `map_key_name`.
+    ///
+    /// It's only called at all if [DeserOpinions::maps_string_values] returns true.
+    fn map_string_value<'s>(&self, field: &str, value: CowStr<'s>) -> CowStr<'s> {
+        let _ = field;
+        value
+    }
+
+    /// Should [DeserOpinions::map_string_value] be called for every field?
+    ///
+    /// This is off by default, since it means reading each field's value
+    /// before knowing what type it's for: formats that pick an encoding based
+    /// on the target type (see [TypeHint]) can't do that for these fields.
+    fn maps_string_values(&self) -> bool {
+        false
+    }
+
+    /// Should a field whose value can't be deserialized (`{ "age": "old" }` for
+    /// an `age: u8`) be skipped and handed to [DeserOpinions::on_field_error],
+    /// instead of failing the whole struct?
+    ///
+    /// This is off by default, since it means keeping track of where every
+    /// field's value ends, in case it has to be skipped.
+    fn recover_from_field_errors(&self) -> bool {
+        false
+    }
+
+    /// If [DeserOpinions::recover_from_field_errors] returns true, this is
+    /// called with `key = "age"` when `{ "age": "old" }` can't be deserialized
+    /// into an `age: u8`, after the value has been skipped.
+    ///
+    /// Returning `Ok(())` carries on with the next field: `slot` can be filled
+    /// with a substitute, or left empty, in which case the field is treated
+    /// as missing (see [DeserOpinions::default_field_value]). By default, this
+    /// returns the error. Errors the input can't recover from, like syntax
+    /// errors, are returned without calling this.
+    #[allow(clippy::needless_lifetimes)]
+    fn on_field_error<'e, 's, 'borrow>(
+        &self,
+        key: &'borrow str,
+        error: MerdeError<'e>,
+        slot: FieldSlot<'s, 'borrow>,
+    ) -> Result<(), MerdeError<'e>> {
+        let _ = (key, slot);
+        Err(error)
+    }
 }
 
 /// merde's default opinions for deserialization: allow unknown fields, don't fill in default values
@@ -230,6 +563,79 @@ impl DeserOpinions for DefaultDeserOpinions {
     }
 }
 
+/// Opinions that match keys to fields regardless of case: `ID`, `Id` and `id`
+/// all go to the `id` field. Everything else is left to the wrapped opinions.
+///
+/// Keys are lowercased after the wrapped opinions' [DeserOpinions::map_key_name],
+/// so this only works for fields whose names are lowercase (as is customary).
+/// Keys that are already lowercase aren't copied.
+///
+/// With `merde::derive!`, pass it (wrapping other opinions, or
+/// [DefaultDeserOpinions]) with `via`:
+///
+/// ```text
+/// merde::derive! {
+///     impl (Serialize, Deserialize) for struct Account { id, display_name }
+///     via CaseInsensitiveOpinions(DefaultDeserOpinions)
+/// }
+/// ```
+///
+/// ```rust
+/// use merde_core::{CaseInsensitiveOpinions, CowStr, DefaultDeserOpinions, DeserOpinions};
+///
+/// let opinions = CaseInsensitiveOpinions(DefaultDeserOpinions);
+/// assert_eq!(opinions.map_key_name(CowStr::from("Display_Name")), "display_name");
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaseInsensitiveOpinions<O = DefaultDeserOpinions>(pub O);
+
+impl<O: DeserOpinions> DeserOpinions for CaseInsensitiveOpinions<O> {
+    fn deny_unknown_fields(&self) -> bool {
+        self.0.deny_unknown_fields()
+    }
+
+    #[allow(clippy::needless_lifetimes)]
+    fn default_field_value<'s, 'borrow>(&self, key: &'borrow str, slot: FieldSlot<'s, 'borrow>) {
+        self.0.default_field_value(key, slot)
+    }
+
+    fn map_key_name<'s>(&self, key: CowStr<'s>) -> CowStr<'s> {
+        let key = self.0.map_key_name(key);
+        if key.chars().any(char::is_uppercase) {
+            CowStr::from(key.to_lowercase())
+        } else {
+            key
+        }
+    }
+
+    fn map_string_value<'s>(&self, field: &str, value: CowStr<'s>) -> CowStr<'s> {
+        self.0.map_string_value(field, value)
+    }
+
+    fn maps_string_values(&self) -> bool {
+        self.0.maps_string_values()
+    }
+
+    fn recover_from_field_errors(&self) -> bool {
+        self.0.recover_from_field_errors()
+    }
+
+    #[allow(clippy::needless_lifetimes)]
+    fn on_field_error<'e, 's, 'borrow>(
+        &self,
+        key: &'borrow str,
+        error: MerdeError<'e>,
+        slot: FieldSlot<'s, 'borrow>,
+    ) -> Result<(), MerdeError<'e>> {
+        self.0.on_field_error(key, error, slot)
+    }
+}
+
+/// Types that can be built from a stream of [Event]s, from any format.
+///
+/// Impls that recurse into nested values should wrap the recursive call with
+/// [`with_metastack_resume_point`](crate::MetastackExt::with_metastack_resume_point),
+/// so that deeply nested input can't overflow the stack.
 pub trait Deserialize<'s>: Sized + 's {
     fn deserialize<'de>(
         de: &'de mut dyn DynDeserializer<'s>,
@@ -293,7 +699,18 @@ impl<'s> Deserialize<'s> for i64 {
         let v: i64 = match de.next().await? {
             Event::I64(i) => i,
             Event::U64(u) => u.try_into().map_err(|_| MerdeError::OutOfRange)?,
-            Event::F64(f) => f as _,
+            Event::I128(i) => i.try_into().map_err(|_| MerdeError::OutOfRange)?,
+            Event::U128(u) => u.try_into().map_err(|_| MerdeError::OutOfRange)?,
+            Event::F64(f) => {
+                // the upper bound is 2^63: `i64::MAX as f64` rounds up to it
+                if de.number_policy() == NumberPolicy::Strict
+                    && !((-9223372036854775808.0..9223372036854775808.0).contains(&f)
+                        && f as i64 as f64 == f)
+                {
+                    return Err(MerdeError::LossyNumber { target: "i64" });
+                }
+                f as _
+            }
             ev => {
                 return Err(MerdeError::UnexpectedEvent {
                     got: EventType::from(&ev),
@@ -311,7 +728,16 @@ impl<'s> Deserialize<'s> for u64 {
         let v: u64 = match de.next().await? {
             Event::U64(u) => u,
             Event::I64(i) => i.try_into().map_err(|_| MerdeError::OutOfRange)?,
-            Event::F64(f) => f as u64,
+            Event::I128(i) => i.try_into().map_err(|_| MerdeError::OutOfRange)?,
+            Event::U128(u) => u.try_into().map_err(|_| MerdeError::OutOfRange)?,
+            Event::F64(f) => {
+                if de.number_policy() == NumberPolicy::Strict
+                    && !((0.0..18446744073709551616.0).contains(&f) && f as u64 as f64 == f)
+                {
+                    return Err(MerdeError::LossyNumber { target: "u64" });
+                }
+                f as u64
+            }
             ev => {
                 return Err(MerdeError::UnexpectedEvent {
                     got: EventType::from(&ev),
@@ -324,6 +750,112 @@ impl<'s> Deserialize<'s> for u64 {
     }
 }
 
+/// Besides numbers, 128-bit integers can be deserialized from strings (for
+/// formats or consumers that can't represent them as numbers, see
+/// `JsonSerializer::with_wide_ints_as_strings`), and from 16 big-endian bytes
+/// (which is how MessagePack encodes them).
+impl<'s> Deserialize<'s> for i128 {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        let v: i128 = match de.next().await? {
+            Event::I64(i) => i.into(),
+            Event::U64(u) => u.into(),
+            Event::I128(i) => i,
+            Event::U128(u) => u.try_into().map_err(|_| MerdeError::OutOfRange)?,
+            Event::F64(f) => {
+                // the upper bound is 2^127: `i128::MAX as f64` rounds up to it
+                if de.number_policy() == NumberPolicy::Strict
+                    && !((i128::MIN as f64..i128::MAX as f64).contains(&f) && f as i128 as f64 == f)
+                {
+                    return Err(MerdeError::LossyNumber { target: "i128" });
+                }
+                f as i128
+            }
+            Event::Str(s) => match s.parse() {
+                Ok(i) => i,
+                Err(e) => return Err(wide_int_parsing_error("i128", s, e)),
+            },
+            Event::Bytes(b) => i128::from_be_bytes(wide_int_bytes("i128", &b)?),
+            ev => {
+                return Err(MerdeError::UnexpectedEvent {
+                    got: EventType::from(&ev),
+                    expected: &[
+                        EventType::I128,
+                        EventType::I64,
+                        EventType::U64,
+                        EventType::U128,
+                        EventType::Float,
+                        EventType::Str,
+                        EventType::Bytes,
+                    ],
+                    help: None,
+                })
+            }
+        };
+        Ok(v)
+    }
+}
+
+/// See `i128`'s [Deserialize] impl.
+impl<'s> Deserialize<'s> for u128 {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        let v: u128 = match de.next().await? {
+            Event::U64(u) => u.into(),
+            Event::I64(i) => i.try_into().map_err(|_| MerdeError::OutOfRange)?,
+            Event::U128(u) => u,
+            Event::I128(i) => i.try_into().map_err(|_| MerdeError::OutOfRange)?,
+            Event::F64(f) => {
+                if de.number_policy() == NumberPolicy::Strict
+                    && !((0.0..u128::MAX as f64).contains(&f) && f as u128 as f64 == f)
+                {
+                    return Err(MerdeError::LossyNumber { target: "u128" });
+                }
+                f as u128
+            }
+            Event::Str(s) => match s.parse() {
+                Ok(u) => u,
+                Err(e) => return Err(wide_int_parsing_error("u128", s, e)),
+            },
+            Event::Bytes(b) => u128::from_be_bytes(wide_int_bytes("u128", &b)?),
+            ev => {
+                return Err(MerdeError::UnexpectedEvent {
+                    got: EventType::from(&ev),
+                    expected: &[
+                        EventType::U128,
+                        EventType::U64,
+                        EventType::I64,
+                        EventType::I128,
+                        EventType::Float,
+                        EventType::Str,
+                        EventType::Bytes,
+                    ],
+                    help: None,
+                })
+            }
+        };
+        Ok(v)
+    }
+}
+
+fn wide_int_parsing_error<'s>(
+    format: &'static str,
+    source: CowStr<'s>,
+    e: core::num::ParseIntError,
+) -> MerdeError<'s> {
+    MerdeError::StringParsingError {
+        format,
+        source,
+        index: 0,
+        message: e.to_string(),
+    }
+}
+
+fn wide_int_bytes<'s>(format: &'static str, b: &[u8]) -> Result<[u8; 16], MerdeError<'s>> {
+    b.try_into().map_err(|_| MerdeError::BinaryParsingError {
+        format,
+        message: format!("expected 16 big-endian bytes, got {}", b.len()),
+    })
+}
+
 impl<'s> Deserialize<'s> for i32 {
     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
         let v: i64 = i64::deserialize(de).await?;
@@ -380,18 +912,81 @@ impl<'s> Deserialize<'s> for usize {
     }
 }
 
+macro_rules! impl_nonzero_deserialize {
+    ($($ty:ty => $inner:ty),* $(,)?) => {
+        $(
+            impl<'s> Deserialize<'s> for $ty {
+                async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+                    let v: $inner = <$inner>::deserialize(de).await?;
+                    <$ty>::new(v).ok_or(MerdeError::OutOfRange)
+                }
+            }
+        )*
+    };
+}
+
+impl_nonzero_deserialize! {
+    NonZeroI8 => i8,
+    NonZeroI16 => i16,
+    NonZeroI32 => i32,
+    NonZeroI64 => i64,
+    NonZeroI128 => i128,
+    NonZeroIsize => isize,
+    NonZeroU8 => u8,
+    NonZeroU16 => u16,
+    NonZeroU32 => u32,
+    NonZeroU64 => u64,
+    NonZeroU128 => u128,
+    NonZeroUsize => usize,
+}
+
+impl<'s, T: Deserialize<'s>> Deserialize<'s> for Wrapping<T> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Wrapping(T::deserialize(de).await?))
+    }
+}
+
+impl<'s, T: Deserialize<'s>> Deserialize<'s> for Saturating<T> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Saturating(T::deserialize(de).await?))
+    }
+}
+
 impl<'s> Deserialize<'s> for bool {
     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
         de.next().await?.into_bool()
     }
 }
 
+impl<'s> Deserialize<'s> for () {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        de.next().await?.into_null()
+    }
+}
+
 impl<'s> Deserialize<'s> for f64 {
     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        let strict = de.number_policy() == NumberPolicy::Strict;
         let v: f64 = match de.next().await? {
             Event::F64(f) => f,
+            // going through i128 so that the round-trip can't saturate
+            Event::I64(i) if strict && i as f64 as i128 != i as i128 => {
+                return Err(MerdeError::LossyNumber { target: "f64" })
+            }
+            Event::U64(u) if strict && u as f64 as i128 != u as i128 => {
+                return Err(MerdeError::LossyNumber { target: "f64" })
+            }
             Event::I64(i) => i as f64,
             Event::U64(u) => u as f64,
+            // `MAX as f64` rounds up to a value that doesn't round-trip
+            Event::I128(i) if strict && (i as f64 == i128::MAX as f64 || i as f64 as i128 != i) => {
+                return Err(MerdeError::LossyNumber { target: "f64" })
+            }
+            Event::U128(u) if strict && (u as f64 == u128::MAX as f64 || u as f64 as u128 != u) => {
+                return Err(MerdeError::LossyNumber { target: "f64" })
+            }
+            Event::I128(i) => i as f64,
+            Event::U128(u) => u as f64,
             ev => {
                 return Err(MerdeError::UnexpectedEvent {
                     got: EventType::from(&ev),
@@ -407,6 +1002,9 @@ impl<'s> Deserialize<'s> for f64 {
 impl<'s> Deserialize<'s> for f32 {
     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
         let v: f64 = f64::deserialize(de).await?;
+        if de.number_policy() == NumberPolicy::Strict && v.is_finite() && (v as f32).is_infinite() {
+            return Err(MerdeError::LossyNumber { target: "f32" });
+        }
         Ok(v as f32)
     }
 }
@@ -418,9 +1016,60 @@ impl<'s> Deserialize<'s> for String {
     }
 }
 
+impl<'s> Deserialize<'s> for char {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        let s: CowStr<'s> = CowStr::deserialize(de).await?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(MerdeError::StringParsingError {
+                format: "char",
+                source: s,
+                index: 0,
+                message: "expected a string of exactly one character".to_string(),
+            }),
+        }
+    }
+}
+
+macro_rules! impl_from_str_deserialize {
+    ($($ty:ty => $format:literal),* $(,)?) => {
+        $(
+            impl<'s> Deserialize<'s> for $ty {
+                async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+                    let s: CowStr<'s> = CowStr::deserialize(de).await?;
+                    s.parse().map_err(|e: core::net::AddrParseError| MerdeError::StringParsingError {
+                        format: $format,
+                        message: e.to_string(),
+                        source: s,
+                        index: 0,
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_from_str_deserialize! {
+    IpAddr => "IP address",
+    Ipv4Addr => "IPv4 address",
+    Ipv6Addr => "IPv6 address",
+    SocketAddr => "socket address",
+    SocketAddrV4 => "IPv4 socket address",
+    SocketAddrV6 => "IPv6 socket address",
+}
+
+#[cfg(feature = "std")]
+impl<'s> Deserialize<'s> for PathBuf {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        let s: CowStr<'s> = CowStr::deserialize(de).await?;
+        Ok(PathBuf::from(s.as_ref()))
+    }
+}
+
 impl<'s> Deserialize<'s> for CowStr<'s> {
     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_str()
+        de.next_event_with_hint(TypeHint::Str).await?.into_str()
     }
 }
 
@@ -434,23 +1083,90 @@ impl<'s> Deserialize<'s> for Cow<'s, str> {
     }
 }
 
+// there's no way to borrow a slice of `T` from the input, so this is always owned
+impl<'s, T: Deserialize<'s> + Clone> Deserialize<'s> for Cow<'s, [T]> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Cow::Owned(Vec::<T>::deserialize(de).await?))
+    }
+}
+
+impl<'s> Deserialize<'s> for CowBytes<'s> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        match de.next_event_with_hint(TypeHint::Bytes).await? {
+            Event::Bytes(b) => Ok(b),
+            // text formats like JSON have no bytes type, so accept base64 there
+            Event::Str(s) => Ok(crate::base64::decode_str(s)?.into()),
+            ev => Err(MerdeError::UnexpectedEvent {
+                got: EventType::from(&ev),
+                expected: &[EventType::Bytes, EventType::Str],
+                help: None,
+            }),
+        }
+    }
+}
+
 impl<'s, T: Deserialize<'s>> Deserialize<'s> for Box<T> {
     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        let value: T = T::deserialize(de).await?;
+        // see `Vec<T>`
+        let value: T = T::deserialize(de).with_metastack_resume_point().await?;
         Ok(Box::new(value))
     }
 }
 
-impl<'s, T: Deserialize<'s>> Deserialize<'s> for Option<T> {
+impl<'s> Deserialize<'s> for Box<str> {
     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        match de.next().await? {
-            Event::Null => Ok(None),
-            ev => {
-                de.put_back(ev)?;
-                let value = T::deserialize(de).await?;
-                Ok(Some(value))
+        Ok(String::deserialize(de).await?.into_boxed_str())
+    }
+}
+
+impl<'s, T: Deserialize<'s>> Deserialize<'s> for Box<[T]> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Vec::<T>::deserialize(de).await?.into_boxed_slice())
+    }
+}
+
+macro_rules! impl_deserialize_for_shared {
+    ($($ptr:ident),*) => {
+        $(
+            impl<'s, T: Deserialize<'s>> Deserialize<'s> for $ptr<T> {
+                async fn deserialize(
+                    de: &mut dyn DynDeserializer<'s>,
+                ) -> Result<Self, MerdeError<'s>> {
+                    // see `Vec<T>`
+                    let value: T = T::deserialize(de).with_metastack_resume_point().await?;
+                    Ok($ptr::new(value))
+                }
+            }
+
+            impl<'s> Deserialize<'s> for $ptr<str> {
+                async fn deserialize(
+                    de: &mut dyn DynDeserializer<'s>,
+                ) -> Result<Self, MerdeError<'s>> {
+                    let s = CowStr::deserialize(de).await?;
+                    Ok($ptr::from(&*s))
+                }
             }
+
+            impl<'s, T: Deserialize<'s>> Deserialize<'s> for $ptr<[T]> {
+                async fn deserialize(
+                    de: &mut dyn DynDeserializer<'s>,
+                ) -> Result<Self, MerdeError<'s>> {
+                    Ok($ptr::from(Vec::<T>::deserialize(de).await?))
+                }
+            }
+        )*
+    };
+}
+
+impl_deserialize_for_shared!(Arc, Rc);
+
+impl<'s, T: Deserialize<'s>> Deserialize<'s> for Option<T> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        if de.peek().await? == EventType::Null {
+            de.next().await?;
+            return Ok(None);
         }
+        Ok(Some(T::deserialize(de).await?))
     }
 
     fn from_option(value: Option<Self>, _field_name: CowStr<'s>) -> Result<Self, MerdeError<'s>> {
@@ -461,28 +1177,67 @@ impl<'s, T: Deserialize<'s>> Deserialize<'s> for Option<T> {
     }
 }
 
-impl<'s, T: Deserialize<'s>> Deserialize<'s> for Vec<T> {
+/// Accepts what the [Serialize](crate::Serialize) impl writes, `{"Ok": value}` or
+/// `{"Err": error}`, and also the lowercase `{"ok": value}` and `{"err": error}`
+/// that other languages' libraries (and many HTTP APIs) use.
+impl<'s, T: Deserialize<'s>, E: Deserialize<'s>> Deserialize<'s> for Result<T, E> {
     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        let array_start = de.next().await?.into_array_start()?;
-        let mut vec = if let Some(size) = array_start.size_hint {
-            Vec::with_capacity(size)
-        } else {
-            Vec::new()
+        de.next().await?.into_map_start()?;
+        let key = de.next().await?.into_str()?;
+        let res = match key.as_ref() {
+            "Ok" | "ok" => Ok(T::deserialize(de).await?),
+            "Err" | "err" => Err(E::deserialize(de).await?),
+            _ => return Err(MerdeError::UnknownProperty(key)),
+        };
+        de.next().await?.into_map_end()?;
+        Ok(res)
+    }
+}
+
+impl<'s, B: Deserialize<'s>, C: Deserialize<'s>> Deserialize<'s> for ControlFlow<B, C> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        de.next().await?.into_map_start()?;
+        let key = de.next().await?.into_str()?;
+        let res = match key.as_ref() {
+            "Continue" => ControlFlow::Continue(C::deserialize(de).await?),
+            "Break" => ControlFlow::Break(B::deserialize(de).await?),
+            _ => return Err(MerdeError::UnknownProperty(key)),
         };
+        de.next().await?.into_map_end()?;
+        Ok(res)
+    }
+}
 
-        loop {
-            match de.next().await? {
-                Event::ArrayEnd => {
-                    break;
-                }
-                ev => {
-                    de.put_back(ev)?;
-                    vec.push(T::deserialize(de).await?);
+impl<'s, T: Deserialize<'s>> Deserialize<'s> for Vec<T> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        // Boxing the whole thing (which `with_metastack_resume_point` does)
+        // is what allows recursive types like `struct Tree { children: Vec<Tree> }`,
+        // whose futures would otherwise be infinitely sized: it also lets the
+        // metastack move on to a fresh stack when deserializing very deep trees.
+        async move {
+            let array_start = de.next().await?.into_array_start()?;
+            let mut vec = if let Some(size) = array_start.size_hint {
+                Vec::with_capacity(size)
+            } else {
+                Vec::new()
+            };
+
+            loop {
+                match de.next().await? {
+                    Event::ArrayEnd => {
+                        break;
+                    }
+                    ev => {
+                        de.put_back(ev)?;
+                        vec.push(T::deserialize(de).await?);
+                    }
                 }
             }
-        }
 
-        Ok(vec)
+            Ok(vec)
+        }
+        .with_metastack_resume_point()
+        .await
     }
 }
 
@@ -493,22 +1248,30 @@ where
     S: Default + BuildHasher + 's,
 {
     async fn deserialize<'d>(de: &'d mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_map_start()?;
-        let mut map = HashMap::<K, V, S>::default();
-
-        loop {
-            match de.next().await? {
-                Event::MapEnd => break,
-                ev => {
-                    de.put_back(ev)?;
-                    let key: K = K::deserialize(de).await?;
-                    let value: V = V::deserialize(de).await?;
-                    map.insert(key, value);
+        // see `Vec<T>`
+        async move {
+            let map_start = de.next().await?.into_map_start()?;
+            let mut map = match map_start.size_hint {
+                Some(size) => HashMap::<K, V, S>::with_capacity_and_hasher(size, S::default()),
+                None => HashMap::<K, V, S>::default(),
+            };
+
+            loop {
+                match de.next().await? {
+                    Event::MapEnd => break,
+                    ev => {
+                        de.put_back(ev)?;
+                        let key: K = K::deserialize(de).await?;
+                        let value: V = V::deserialize(de).await?;
+                        map.insert(key, value);
+                    }
                 }
             }
-        }
 
-        Ok(map)
+            Ok(map)
+        }
+        .with_metastack_resume_point()
+        .await
     }
 }
 
@@ -516,8 +1279,11 @@ impl<'s> Deserialize<'s> for Map<'s> {
     async fn deserialize<'de>(
         de: &'de mut dyn DynDeserializer<'s>,
     ) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_map_start()?;
-        let mut map = Map::new();
+        let map_start = de.next().await?.into_map_start()?;
+        let mut map = match map_start.size_hint {
+            Some(size) => Map::with_capacity(size),
+            None => Map::new(),
+        };
 
         loop {
             match de.next().await? {
@@ -569,6 +1335,15 @@ impl<'s> Deserialize<'s> for Value<'s> {
         match de.next().await? {
             Event::I64(i) => Ok(Value::I64(i)),
             Event::U64(u) => Ok(Value::U64(u)),
+            // values have no 128-bit integers: those that don't fit in 64 bits
+            // are an error rather than a float that's been rounded
+            Event::I128(i) => i64::try_from(i)
+                .map(Value::I64)
+                .or_else(|_| u64::try_from(i).map(Value::U64))
+                .map_err(|_| MerdeError::OutOfRange),
+            Event::U128(u) => u64::try_from(u)
+                .map(Value::U64)
+                .map_err(|_| MerdeError::OutOfRange),
             Event::F64(f) => Ok(Value::Float(f.into())),
             Event::Str(s) => Ok(Value::Str(s)),
             Event::Bytes(b) => Ok(Value::Bytes(b)),
@@ -633,155 +1408,73 @@ impl<'s> Deserialize<'s> for Value<'s> {
     }
 }
 
-impl<'s, T1> Deserialize<'s> for (T1,)
-where
-    T1: Deserialize<'s>,
-{
-    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_array_start()?;
-        let t1 = de.t().await?;
-        de.next().await?.into_array_end()?;
-        Ok((t1,))
-    }
-}
-
-impl<'s, T1, T2> Deserialize<'s> for (T1, T2)
-where
-    T1: Deserialize<'s>,
-    T2: Deserialize<'s>,
-{
-    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_array_start()?;
-        let t1 = de.t().await?;
-        let t2 = de.t().await?;
-        de.next().await?.into_array_end()?;
-        Ok((t1, t2))
-    }
-}
-
-impl<'s, T1, T2, T3> Deserialize<'s> for (T1, T2, T3)
-where
-    T1: Deserialize<'s>,
-    T2: Deserialize<'s>,
-    T3: Deserialize<'s>,
-{
-    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_array_start()?;
-        let t1 = de.t().await?;
-        let t2 = de.t().await?;
-        let t3 = de.t().await?;
-        de.next().await?.into_array_end()?;
-        Ok((t1, t2, t3))
-    }
-}
-
-impl<'s, T1, T2, T3, T4> Deserialize<'s> for (T1, T2, T3, T4)
-where
-    T1: Deserialize<'s>,
-    T2: Deserialize<'s>,
-    T3: Deserialize<'s>,
-    T4: Deserialize<'s>,
-{
-    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_array_start()?;
-        let t1 = de.t().await?;
-        let t2 = de.t().await?;
-        let t3 = de.t().await?;
-        let t4 = de.t().await?;
-        de.next().await?.into_array_end()?;
-        Ok((t1, t2, t3, t4))
-    }
-}
-
-impl<'s, T1, T2, T3, T4, T5> Deserialize<'s> for (T1, T2, T3, T4, T5)
-where
-    T1: Deserialize<'s>,
-    T2: Deserialize<'s>,
-    T3: Deserialize<'s>,
-    T4: Deserialize<'s>,
-    T5: Deserialize<'s>,
-{
-    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_array_start()?;
-        let t1 = de.t().await?;
-        let t2 = de.t().await?;
-        let t3 = de.t().await?;
-        let t4 = de.t().await?;
-        let t5 = de.t().await?;
-        de.next().await?.into_array_end()?;
-        Ok((t1, t2, t3, t4, t5))
-    }
-}
-
-impl<'s, T1, T2, T3, T4, T5, T6> Deserialize<'s> for (T1, T2, T3, T4, T5, T6)
-where
-    T1: Deserialize<'s>,
-    T2: Deserialize<'s>,
-    T3: Deserialize<'s>,
-    T4: Deserialize<'s>,
-    T5: Deserialize<'s>,
-    T6: Deserialize<'s>,
-{
-    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_array_start()?;
-        let t1 = de.t().await?;
-        let t2 = de.t().await?;
-        let t3 = de.t().await?;
-        let t4 = de.t().await?;
-        let t5 = de.t().await?;
-        let t6 = de.t().await?;
-        de.next().await?.into_array_end()?;
-        Ok((t1, t2, t3, t4, t5, t6))
-    }
-}
-
-impl<'s, T1, T2, T3, T4, T5, T6, T7> Deserialize<'s> for (T1, T2, T3, T4, T5, T6, T7)
-where
-    T1: Deserialize<'s>,
-    T2: Deserialize<'s>,
-    T3: Deserialize<'s>,
-    T4: Deserialize<'s>,
-    T5: Deserialize<'s>,
-    T6: Deserialize<'s>,
-    T7: Deserialize<'s>,
-{
-    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_array_start()?;
-        let t1 = de.t().await?;
-        let t2 = de.t().await?;
-        let t3 = de.t().await?;
-        let t4 = de.t().await?;
-        let t5 = de.t().await?;
-        let t6 = de.t().await?;
-        let t7 = de.t().await?;
-        de.next().await?.into_array_end()?;
-        Ok((t1, t2, t3, t4, t5, t6, t7))
-    }
+macro_rules! impl_deserialize_for_tuple {
+    ($($type_arg:ident),+) => {
+        impl<'s, $($type_arg: Deserialize<'s>),+> Deserialize<'s> for ($($type_arg,)+) {
+            async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+                de.next().await?.into_array_start()?;
+                // (tuple fields are evaluated in order)
+                let tuple = ($($type_arg::deserialize(de).await?,)+);
+                de.next().await?.into_array_end()?;
+                Ok(tuple)
+            }
+        }
+    };
 }
 
-impl<'s, T1, T2, T3, T4, T5, T6, T7, T8> Deserialize<'s> for (T1, T2, T3, T4, T5, T6, T7, T8)
-where
-    T1: Deserialize<'s>,
-    T2: Deserialize<'s>,
-    T3: Deserialize<'s>,
-    T4: Deserialize<'s>,
-    T5: Deserialize<'s>,
-    T6: Deserialize<'s>,
-    T7: Deserialize<'s>,
-    T8: Deserialize<'s>,
-{
+impl_deserialize_for_tuple!(T1);
+impl_deserialize_for_tuple!(T1, T2);
+impl_deserialize_for_tuple!(T1, T2, T3);
+impl_deserialize_for_tuple!(T1, T2, T3, T4);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
+impl_deserialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
+
+/// Arrays must have exactly `N` elements.
+impl<'s, T: Deserialize<'s>, const N: usize> Deserialize<'s> for [T; N] {
     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
-        de.next().await?.into_array_start()?;
-        let t1 = de.t().await?;
-        let t2 = de.t().await?;
-        let t3 = de.t().await?;
-        let t4 = de.t().await?;
-        let t5 = de.t().await?;
-        let t6 = de.t().await?;
-        let t7 = de.t().await?;
-        let t8 = de.t().await?;
-        de.next().await?.into_array_end()?;
-        Ok((t1, t2, t3, t4, t5, t6, t7, t8))
+        // see `Vec<T>`
+        async move {
+            de.next().await?.into_array_start()?;
+            let mut items = Vec::with_capacity(N);
+            loop {
+                match de.next().await? {
+                    Event::ArrayEnd => break,
+                    ev if items.len() == N => {
+                        return Err(MerdeError::UnexpectedEvent {
+                            got: EventType::from(&ev),
+                            expected: &[EventType::ArrayEnd],
+                            help: Some(format!("(Expected an array of {N} elements, got more)")),
+                        })
+                    }
+                    ev => {
+                        de.put_back(ev)?;
+                        items.push(T::deserialize(de).await?);
+                    }
+                }
+            }
+            items
+                .try_into()
+                .map_err(|items: Vec<T>| MerdeError::UnexpectedEvent {
+                    got: EventType::ArrayEnd,
+                    expected: &[],
+                    help: Some(format!(
+                        "(Expected an array of {N} elements, got {})",
+                        items.len()
+                    )),
+                })
+        }
+        .with_metastack_resume_point()
+        .await
     }
 }
 
@@ -39,3 +39,69 @@ fn ui() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/ui/*.rs");
 }
+
+// Records the hints `Deserialize` impls pass along (`None` for plain `next` calls)
+#[derive(Debug)]
+struct HintRecorder<'s> {
+    events: crate::EventBuffer<'s>,
+    hints: Vec<Option<super::TypeHint>>,
+}
+
+impl<'s> super::Deserializer<'s> for HintRecorder<'s> {
+    async fn next(&mut self) -> Result<crate::Event<'s>, crate::MerdeError<'s>> {
+        self.hints.push(None);
+        self.events.next().await
+    }
+
+    fn put_back(&mut self, ev: crate::Event<'s>) -> Result<(), crate::MerdeError<'s>> {
+        self.events.put_back(ev)
+    }
+
+    async fn next_event_with_hint(
+        &mut self,
+        hint: super::TypeHint,
+    ) -> Result<crate::Event<'s>, crate::MerdeError<'s>> {
+        self.hints.push(Some(hint));
+        self.events.next().await
+    }
+}
+
+fn hints<T: super::Deserialize<'static>>(
+    ev: crate::Event<'static>,
+) -> Vec<Option<super::TypeHint>> {
+    use crate::DynDeserializerExt;
+
+    let mut de = HintRecorder {
+        events: crate::EventBuffer::from_events([ev]),
+        hints: Vec::new(),
+    };
+    de.deserialize::<T>().unwrap();
+    de.hints
+}
+
+#[test]
+fn test_type_hints() {
+    use super::TypeHint::*;
+    use crate::{base64::Base64, CowBytes, CowStr, Event, RawNumber};
+
+    let s = || Event::Str("a".into());
+    assert_eq!(hints::<CowStr>(s()), [Some(Str)]);
+    assert_eq!(hints::<String>(s()), [Some(Str)]);
+    assert_eq!(hints::<Box<str>>(s()), [Some(Str)]);
+    assert_eq!(hints::<std::borrow::Cow<str>>(s()), [Some(Str)]);
+    assert_eq!(hints::<char>(s()), [Some(Str)]);
+    assert_eq!(hints::<std::path::PathBuf>(s()), [Some(Str)]);
+    // peeked at first
+    assert_eq!(hints::<Option<String>>(s()), [None, Some(Str)]);
+
+    assert_eq!(
+        hints::<CowBytes>(Event::Bytes(vec![1].into())),
+        [Some(Bytes)]
+    );
+    // base64 takes strings and bytes alike, and decodes strings: it must
+    // not ask for bytes
+    assert_eq!(hints::<Base64<Vec<u8>>>(Event::Str("AQID".into())), [None]);
+    assert_eq!(hints::<Base64<CowBytes>>(Event::Str("AQID".into())), [None]);
+
+    assert_eq!(hints::<RawNumber>(Event::I64(1)), [Some(RawNumber)]);
+}
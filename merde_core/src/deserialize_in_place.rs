@@ -0,0 +1,212 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{
+    future::Future,
+    hash::{BuildHasher, Hash},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+    },
+};
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+use crate::{
+    Array, CowBytes, CowStr, Deserialize, DynDeserializer, Event, HashMap, Map, MerdeError,
+    MetastackExt, Value,
+};
+
+/// Types that can be deserialized into an existing value, reusing its
+/// allocations: when reading many records of the same shape, a `String` keeps
+/// its buffer and a `Vec` keeps its capacity (and its elements) from one record
+/// to the next.
+///
+/// The default implementation simply replaces the value, so types with nothing
+/// worth reusing can opt in with an empty impl. For structs, use
+/// `derive!(impl (Deserialize, DeserializeInPlace) for struct ...)`.
+///
+/// If deserialization fails, the value is left in a valid but unspecified state
+/// (some fields may already have been overwritten).
+pub trait DeserializeInPlace<'s>: Deserialize<'s> {
+    fn deserialize_in_place<'de>(
+        &'de mut self,
+        de: &'de mut dyn DynDeserializer<'s>,
+    ) -> impl Future<Output = Result<(), MerdeError<'s>>> + 'de {
+        async move {
+            *self = Self::deserialize(de).await?;
+            Ok(())
+        }
+    }
+}
+
+macro_rules! impl_deserialize_in_place_by_replacing {
+    ($($ty:ty),* $(,)?) => {
+        $(impl<'s> DeserializeInPlace<'s> for $ty {})*
+    };
+}
+
+impl_deserialize_in_place_by_replacing! {
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, bool, char, (),
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize,
+    NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize,
+    CowStr<'s>, CowBytes<'s>, Value<'s>, Map<'s>, Array<'s>,
+}
+
+#[cfg(feature = "std")]
+impl_deserialize_in_place_by_replacing!(PathBuf);
+
+impl<'s> DeserializeInPlace<'s> for String {
+    async fn deserialize_in_place<'de>(
+        &'de mut self,
+        de: &'de mut dyn DynDeserializer<'s>,
+    ) -> Result<(), MerdeError<'s>> {
+        let s = CowStr::deserialize(de).await?;
+        self.clear();
+        self.push_str(&s);
+        Ok(())
+    }
+}
+
+impl<'s, T: DeserializeInPlace<'s>> DeserializeInPlace<'s> for Option<T> {
+    async fn deserialize_in_place<'de>(
+        &'de mut self,
+        de: &'de mut dyn DynDeserializer<'s>,
+    ) -> Result<(), MerdeError<'s>> {
+        match de.next().await? {
+            Event::Null => *self = None,
+            ev => {
+                de.put_back(ev)?;
+                match self {
+                    Some(value) => value.deserialize_in_place(de).await?,
+                    None => *self = Some(T::deserialize(de).await?),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'s, T: DeserializeInPlace<'s>> DeserializeInPlace<'s> for Box<T> {
+    async fn deserialize_in_place<'de>(
+        &'de mut self,
+        de: &'de mut dyn DynDeserializer<'s>,
+    ) -> Result<(), MerdeError<'s>> {
+        // see `Vec<T>`'s `Deserialize` impl
+        (**self)
+            .deserialize_in_place(de)
+            .with_metastack_resume_point()
+            .await
+    }
+}
+
+impl<'s, T: DeserializeInPlace<'s>> DeserializeInPlace<'s> for Vec<T> {
+    async fn deserialize_in_place<'de>(
+        &'de mut self,
+        de: &'de mut dyn DynDeserializer<'s>,
+    ) -> Result<(), MerdeError<'s>> {
+        // see `Vec<T>`'s `Deserialize` impl
+        async move {
+            de.next().await?.into_array_start()?;
+
+            // existing elements are deserialized in place, extra elements
+            // are pushed, and leftover ones are dropped at the end.
+            let mut len = 0;
+            loop {
+                match de.next().await? {
+                    Event::ArrayEnd => break,
+                    ev => {
+                        de.put_back(ev)?;
+                        match self.get_mut(len) {
+                            Some(item) => item.deserialize_in_place(de).await?,
+                            None => self.push(T::deserialize(de).await?),
+                        }
+                        len += 1;
+                    }
+                }
+            }
+            self.truncate(len);
+
+            Ok(())
+        }
+        .with_metastack_resume_point()
+        .await
+    }
+}
+
+/// Keeps the map's capacity, but not its entries: keys and values are
+/// deserialized from scratch.
+impl<'s, K, V, S> DeserializeInPlace<'s> for HashMap<K, V, S>
+where
+    K: Deserialize<'s> + Eq + Hash,
+    V: Deserialize<'s>,
+    S: Default + BuildHasher + 's,
+{
+    async fn deserialize_in_place<'de>(
+        &'de mut self,
+        de: &'de mut dyn DynDeserializer<'s>,
+    ) -> Result<(), MerdeError<'s>> {
+        // see `Vec<T>`'s `Deserialize` impl
+        async move {
+            let map_start = de.next().await?.into_map_start()?;
+            self.clear();
+            if let Some(size) = map_start.size_hint {
+                self.reserve(size);
+            }
+
+            loop {
+                match de.next().await? {
+                    Event::MapEnd => break,
+                    ev => {
+                        de.put_back(ev)?;
+                        let key: K = K::deserialize(de).await?;
+                        let value: V = V::deserialize(de).await?;
+                        self.insert(key, value);
+                    }
+                }
+            }
+
+            Ok(())
+        }
+        .with_metastack_resume_point()
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStart, DynDeserializerExt, EventBuffer};
+
+    fn strings(items: &[&'static str]) -> EventBuffer<'static> {
+        let mut events = EventBuffer::new();
+        events.push(Event::ArrayStart(ArrayStart { size_hint: None }));
+        for item in items {
+            events.push(Event::Str((*item).into()));
+        }
+        events.push(Event::ArrayEnd);
+        events
+    }
+
+    #[test]
+    fn test_vec_of_strings() {
+        let mut value: Vec<String> = Vec::with_capacity(16);
+        strings(&["first", "second", "third"])
+            .deserialize_into(&mut value)
+            .unwrap();
+        assert_eq!(value, ["first", "second", "third"]);
+
+        let vec_ptr = value.as_ptr();
+        let first_ptr = value[0].as_ptr();
+        strings(&["1st", "2nd"])
+            .deserialize_into(&mut value)
+            .unwrap();
+        assert_eq!(value, ["1st", "2nd"]);
+        assert_eq!(value.as_ptr(), vec_ptr);
+        assert_eq!(value[0].as_ptr(), first_ptr);
+
+        strings(&["a", "b", "c", "d"])
+            .deserialize_into(&mut value)
+            .unwrap();
+        assert_eq!(value, ["a", "b", "c", "d"]);
+        assert_eq!(value[0].as_ptr(), first_ptr);
+    }
+}
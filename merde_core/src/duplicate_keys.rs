@@ -0,0 +1,209 @@
+//! Detecting (and dealing with) maps that have the same key more than once,
+//! like `{"a":1,"a":2}` in JSON.
+
+use alloc::vec::Vec;
+
+use crate::{CowStr, Event, HashSet, MerdeError};
+
+/// What a deserializer should do when a map has the same key more than once.
+///
+/// Duplicate keys are a classic way of smuggling data past validation: if two
+/// parsers disagree on which value wins, one of them checks one value and the
+/// other one uses another.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// Pass duplicate keys through. With merde's own `Deserialize` impls (maps,
+    /// derived structs), the last value wins. This is the default, and costs nothing.
+    #[default]
+    LastWins,
+
+    /// Drop any later occurrence of a key (and its value), so the first value wins.
+    FirstWins,
+
+    /// Return a [`MerdeError::DuplicateKey`] error.
+    Error,
+}
+
+#[derive(Debug)]
+enum Frame<'s> {
+    Map {
+        seen: HashSet<CowStr<'s>>,
+        expecting_key: bool,
+    },
+    Array,
+    // dropping the value of a duplicate key
+    Skip {
+        depth: usize,
+    },
+}
+
+/// Applies a [`DuplicateKeyPolicy`] to a stream of events, for use by
+/// [`Deserializer`](crate::Deserializer) implementations: every event read from
+/// the input goes through [`DuplicateKeyFilter::filter`].
+///
+/// Only string keys are tracked.
+#[derive(Debug, Default)]
+pub struct DuplicateKeyFilter<'s> {
+    policy: DuplicateKeyPolicy,
+    stack: Vec<Frame<'s>>,
+}
+
+impl<'s> DuplicateKeyFilter<'s> {
+    /// Creates a filter enforcing `policy`.
+    pub fn new(policy: DuplicateKeyPolicy) -> Self {
+        Self {
+            policy,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Returns the policy this filter enforces.
+    pub fn policy(&self) -> DuplicateKeyPolicy {
+        self.policy
+    }
+
+    /// Feeds the next event read from the input. Returns `Some(ev)` if the
+    /// event should be passed on, and `None` if it should be dropped (in which
+    /// case the caller should just move on to the next event).
+    pub fn filter(&mut self, ev: Event<'s>) -> Result<Option<Event<'s>>, MerdeError<'s>> {
+        if self.policy == DuplicateKeyPolicy::LastWins {
+            return Ok(Some(ev));
+        }
+
+        match self.stack.last_mut() {
+            Some(Frame::Skip { depth }) => {
+                match ev {
+                    Event::MapStart(_) | Event::ArrayStart(_) => *depth += 1,
+                    Event::MapEnd | Event::ArrayEnd => *depth -= 1,
+                    _ => {}
+                }
+                if *depth == 0 {
+                    self.stack.pop();
+                }
+                return Ok(None);
+            }
+            Some(Frame::Map {
+                seen,
+                expecting_key,
+            }) => {
+                if *expecting_key {
+                    match ev {
+                        Event::MapEnd => {
+                            self.stack.pop();
+                            return Ok(Some(ev));
+                        }
+                        Event::Str(ref key) => {
+                            if seen.contains(key) {
+                                match self.policy {
+                                    DuplicateKeyPolicy::Error => {
+                                        let Event::Str(key) = ev else { unreachable!() };
+                                        return Err(MerdeError::DuplicateKey(key));
+                                    }
+                                    _ => {
+                                        self.stack.push(Frame::Skip { depth: 0 });
+                                        return Ok(None);
+                                    }
+                                }
+                            }
+                            seen.insert(key.clone());
+                            *expecting_key = false;
+                            return Ok(Some(ev));
+                        }
+                        // non-string keys aren't tracked, but they might be maps
+                        // or arrays, which would push a frame: treat them like values
+                        _ => {}
+                    }
+                }
+                *expecting_key = !*expecting_key;
+            }
+            Some(Frame::Array) => {
+                if matches!(ev, Event::ArrayEnd) {
+                    self.stack.pop();
+                    return Ok(Some(ev));
+                }
+            }
+            None => {}
+        }
+
+        match ev {
+            Event::MapStart(_) => self.stack.push(Frame::Map {
+                seen: HashSet::new(),
+                expecting_key: true,
+            }),
+            Event::ArrayStart(_) => self.stack.push(Frame::Array),
+            _ => {}
+        }
+        Ok(Some(ev))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStart, MapStart};
+
+    fn run(
+        policy: DuplicateKeyPolicy,
+        events: Vec<Event<'static>>,
+    ) -> Result<Vec<Event<'static>>, MerdeError<'static>> {
+        let mut filter = DuplicateKeyFilter::new(policy);
+        let mut out = Vec::new();
+        for ev in events {
+            out.extend(filter.filter(ev)?);
+        }
+        Ok(out)
+    }
+
+    // {"a":1,"b":{"a":[2]},"a":{"c":[3]},"b":4}
+    fn events() -> Vec<Event<'static>> {
+        vec![
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("a".into()),
+            Event::I64(1),
+            Event::Str("b".into()),
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("a".into()),
+            Event::ArrayStart(ArrayStart { size_hint: None }),
+            Event::I64(2),
+            Event::ArrayEnd,
+            Event::MapEnd,
+            Event::Str("a".into()),
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("c".into()),
+            Event::ArrayStart(ArrayStart { size_hint: None }),
+            Event::I64(3),
+            Event::ArrayEnd,
+            Event::MapEnd,
+            Event::Str("b".into()),
+            Event::I64(4),
+            Event::MapEnd,
+        ]
+    }
+
+    #[test]
+    fn test_last_wins() {
+        assert_eq!(
+            run(DuplicateKeyPolicy::LastWins, events()).unwrap().len(),
+            events().len()
+        );
+    }
+
+    #[test]
+    fn test_first_wins() {
+        // the second "a" and the second "b" are dropped, along with their values
+        let mut expected = events();
+        expected.truncate(10);
+        expected.push(Event::MapEnd);
+
+        let out = run(DuplicateKeyPolicy::FirstWins, events()).unwrap();
+        assert_eq!(format!("{out:?}"), format!("{expected:?}"));
+    }
+
+    #[test]
+    fn test_error() {
+        assert!(matches!(
+            run(DuplicateKeyPolicy::Error, events()),
+            Err(MerdeError::DuplicateKey(k)) if k == "a"
+        ));
+    }
+}
@@ -2,7 +2,15 @@
 // Error Handling and Field Type
 // -------------------------------------------------------------------------
 
-use crate::{CowStr, EventType, IntoStatic, Value};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::{CowStr, DynSerializer, Event, EventType, IntoStatic, Map, MapStart, Serialize, Value};
 
 /// A content-less variant of the [`Value`] enum, used for reporting errors, see [`MerdeError::MismatchedType`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -67,10 +75,18 @@ pub enum MerdeError<'s> {
     /// For example, we had a `u8` field but the JSON value was bigger than `u8::MAX`.
     OutOfRange,
 
+    /// A number couldn't be converted to the requested type without losing
+    /// information, like `1.5` for a `u32`. Only returned with
+    /// [`NumberPolicy::Strict`](crate::NumberPolicy::Strict).
+    LossyNumber {
+        /// The requested type, like `"u64"`
+        target: &'static str,
+    },
+
     /// A field was missing (but we don't know its name)
     MissingValue,
 
-    /// While calling out to [`FromStr::from_str`](std::str::FromStr::from_str) to build a [`HashMap`](std::collections::HashMap), we got an error.
+    /// While calling out to [`FromStr::from_str`](core::str::FromStr::from_str) to build a [`HashMap`](std::collections::HashMap), we got an error.
     InvalidKey {
         key: CowStr<'s>,
         type_name: &'static str,
@@ -86,10 +102,14 @@ pub enum MerdeError<'s> {
     },
 
     /// An I/O error occurred.
+    #[cfg(feature = "std")]
     Io(std::io::Error),
 
+    /// The input ended in the middle of a value.
+    UnexpectedEof,
+
     /// An Utf8 error
-    Utf8Error(std::str::Utf8Error),
+    Utf8Error(core::str::Utf8Error),
 
     /// Error occured while parsing a string, we can format
     /// a nice error message with the source string, highlighted etc.
@@ -107,16 +127,186 @@ pub enum MerdeError<'s> {
         message: String,
     },
 
-    /// `.put_back()` was called more than once
+    /// `.put_back()` was called more than once. merde's deserializers support
+    /// putting back any number of events, but third-party ones might not.
     PutBackCalledTwice,
+
+    /// A map had the same key more than once, see [`DuplicateKeyPolicy`](crate::DuplicateKeyPolicy).
+    DuplicateKey(CowStr<'s>),
+
+    /// A [`Versioned`](crate::Versioned) value had a version that's newer than
+    /// the type, or older than any version it knows about.
+    UnknownVersion(u32),
+
+    /// An operation of a [`Patch`](crate::Patch) couldn't be applied: its
+    /// path didn't exist, its `test` failed, etc.
+    PatchFailed {
+        /// The index of the operation in the patch.
+        index: usize,
+        /// What went wrong.
+        message: String,
+    },
+
+    /// The input has syntax that's common in hand-written documents, but that
+    /// the format doesn't allow, like comments or trailing commas in JSON.
+    DisallowedSyntax {
+        format: &'static str,
+        /// What was found, like `"trailing commas"`
+        syntax: &'static str,
+        /// Where it was, in bytes
+        index: usize,
+        /// Where it was, as a line and a column (both starting at 1)
+        line: usize,
+        column: usize,
+        /// What to do about it
+        help: Option<&'static str>,
+    },
+
+    /// Several errors, collected by [CollectErrors](crate::CollectErrors), each
+    /// with the path of the field it's about, like `servers[1].port`.
+    Multiple(Vec<(String, MerdeError<'static>)>),
+
+    /// An error from a hand-written impl, like "expected a semver string",
+    /// see [MerdeError::custom].
+    Custom(CowStr<'s>),
+
+    /// Another error, with a word on what was going on, see [MerdeError::context].
+    Context {
+        /// Like `"while reading the lockfile"`
+        context: CowStr<'s>,
+        source: Box<MerdeError<'s>>,
+    },
+
+    /// Another error, about a field, see [MerdeError::with_field].
+    Field {
+        field: CowStr<'s>,
+        source: Box<MerdeError<'s>>,
+    },
 }
 
-impl MerdeError<'_> {
+impl<'s> MerdeError<'s> {
     pub fn eof() -> Self {
-        MerdeError::Io(std::io::Error::new(
-            std::io::ErrorKind::UnexpectedEof,
-            "eof",
-        ))
+        MerdeError::UnexpectedEof
+    }
+
+    /// An error with just a message, for hand-written [Deserialize](crate::Deserialize)
+    /// impls that reject values for their own reasons:
+    ///
+    /// ```rust
+    /// use merde_core::MerdeError;
+    ///
+    /// let err = MerdeError::custom("expected a semver string, like `1.2.3`");
+    /// assert_eq!(err.to_string(), "expected a semver string, like `1.2.3`");
+    /// ```
+    pub fn custom(message: impl Into<CowStr<'s>>) -> Self {
+        MerdeError::Custom(message.into())
+    }
+
+    /// Wraps this error with a word on what was going on when it happened.
+    ///
+    /// ```rust
+    /// use merde_core::MerdeError;
+    ///
+    /// let err = MerdeError::custom("bad version").context("while reading the lockfile");
+    /// assert_eq!(err.to_string(), "while reading the lockfile: bad version");
+    /// ```
+    pub fn context(self, context: impl Into<CowStr<'s>>) -> Self {
+        MerdeError::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Wraps this error with the name of the field it's about. Wrapping it
+    /// again, from the outer structs, builds up a path:
+    ///
+    /// ```rust
+    /// use merde_core::MerdeError;
+    ///
+    /// let err = MerdeError::custom("bad version")
+    ///     .with_field("version")
+    ///     .with_field("package");
+    /// assert_eq!(err.to_string(), "package.version: bad version");
+    /// ```
+    pub fn with_field(self, field: impl Into<CowStr<'s>>) -> Self {
+        MerdeError::Field {
+            field: field.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// The error that [MerdeError::context] and [MerdeError::with_field]
+    /// wrapped, if any, or this error.
+    pub fn root(&self) -> &MerdeError<'s> {
+        match self {
+            MerdeError::Context { source, .. } | MerdeError::Field { source, .. } => source.root(),
+            error => error,
+        }
+    }
+
+    // Like `Display`, without the source document or terminal escape codes
+    fn plain_message(&self) -> String {
+        match self {
+            MerdeError::StringParsingError {
+                format, message, ..
+            } => format!("{format} parsing error: {message}"),
+            MerdeError::Context { context, source } => {
+                format!("{context}: {}", source.plain_message())
+            }
+            MerdeError::Field { .. } => {
+                let (path, source) = self.field_path();
+                format!("{path}: {}", source.plain_message())
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    // For nested `Field`s: their path, and what they wrap
+    fn field_path(&self) -> (String, &MerdeError<'s>) {
+        let mut path = String::new();
+        let mut error = self;
+        while let MerdeError::Field { field, source } = error {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(field);
+            error = source;
+        }
+        (path, error)
+    }
+
+    /// A stable, machine-readable name for the kind of error, like
+    /// `"missing_property"`. This is the `code` field of the serialized error.
+    ///
+    /// Errors wrapped with [MerdeError::context] or [MerdeError::with_field]
+    /// have the code of the error they wrap.
+    pub fn code(&self) -> &'static str {
+        match self {
+            MerdeError::MismatchedType { .. } => "mismatched_type",
+            MerdeError::MissingProperty(_) => "missing_property",
+            MerdeError::IndexOutOfBounds { .. } => "index_out_of_bounds",
+            MerdeError::UnknownProperty(_) => "unknown_property",
+            MerdeError::OutOfRange => "out_of_range",
+            MerdeError::LossyNumber { .. } => "lossy_number",
+            MerdeError::MissingValue => "missing_value",
+            MerdeError::InvalidKey { .. } => "invalid_key",
+            MerdeError::InvalidDateTimeValue => "invalid_date_time_value",
+            MerdeError::UnexpectedEvent { .. } => "unexpected_event",
+            #[cfg(feature = "std")]
+            MerdeError::Io(_) => "io",
+            MerdeError::UnexpectedEof => "unexpected_eof",
+            MerdeError::Utf8Error(_) => "utf8",
+            MerdeError::StringParsingError { .. } => "string_parsing",
+            MerdeError::BinaryParsingError { .. } => "binary_parsing",
+            MerdeError::PutBackCalledTwice => "put_back_called_twice",
+            MerdeError::DuplicateKey(_) => "duplicate_key",
+            MerdeError::UnknownVersion(_) => "unknown_version",
+            MerdeError::PatchFailed { .. } => "patch_failed",
+            MerdeError::DisallowedSyntax { .. } => "disallowed_syntax",
+            MerdeError::Multiple(_) => "multiple",
+            MerdeError::Custom(_) => "custom",
+            MerdeError::Context { source, .. } | MerdeError::Field { source, .. } => source.code(),
+        }
     }
 }
 
@@ -134,13 +324,16 @@ impl IntoStatic for MerdeError<'_> {
             }
             MerdeError::UnknownProperty(prop) => MerdeError::UnknownProperty(prop.into_static()),
             MerdeError::OutOfRange => MerdeError::OutOfRange,
+            MerdeError::LossyNumber { target } => MerdeError::LossyNumber { target },
             MerdeError::MissingValue => MerdeError::MissingValue,
             MerdeError::InvalidKey { key, type_name } => MerdeError::InvalidKey {
                 key: key.into_static(),
                 type_name,
             },
             MerdeError::InvalidDateTimeValue => MerdeError::InvalidDateTimeValue,
+            #[cfg(feature = "std")]
             MerdeError::Io(e) => MerdeError::Io(e),
+            MerdeError::UnexpectedEof => MerdeError::UnexpectedEof,
             MerdeError::UnexpectedEvent {
                 got,
                 expected,
@@ -163,27 +356,58 @@ impl IntoStatic for MerdeError<'_> {
                 message,
             },
             MerdeError::PutBackCalledTwice => MerdeError::PutBackCalledTwice,
+            MerdeError::DuplicateKey(key) => MerdeError::DuplicateKey(key.into_static()),
+            MerdeError::UnknownVersion(version) => MerdeError::UnknownVersion(version),
+            MerdeError::PatchFailed { index, message } => {
+                MerdeError::PatchFailed { index, message }
+            }
             MerdeError::BinaryParsingError { format, message } => {
                 MerdeError::BinaryParsingError { format, message }
             }
+            MerdeError::DisallowedSyntax {
+                format,
+                syntax,
+                index,
+                line,
+                column,
+                help,
+            } => MerdeError::DisallowedSyntax {
+                format,
+                syntax,
+                index,
+                line,
+                column,
+                help,
+            },
+            MerdeError::Multiple(errors) => MerdeError::Multiple(errors),
+            MerdeError::Custom(message) => MerdeError::Custom(message.into_static()),
+            MerdeError::Context { context, source } => MerdeError::Context {
+                context: context.into_static(),
+                source: source.into_static(),
+            },
+            MerdeError::Field { field, source } => MerdeError::Field {
+                field: field.into_static(),
+                source: source.into_static(),
+            },
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for MerdeError<'_> {
     fn from(e: std::io::Error) -> Self {
         MerdeError::Io(e)
     }
 }
 
-impl From<std::str::Utf8Error> for MerdeError<'_> {
-    fn from(e: std::str::Utf8Error) -> Self {
+impl From<core::str::Utf8Error> for MerdeError<'_> {
+    fn from(e: core::str::Utf8Error) -> Self {
         MerdeError::Utf8Error(e)
     }
 }
 
-impl std::fmt::Display for MerdeError<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for MerdeError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             MerdeError::MismatchedType { expected, found } => {
                 write!(f, "Expected {:?}, found {:?}", expected, found)
@@ -204,6 +428,9 @@ impl std::fmt::Display for MerdeError<'_> {
             MerdeError::OutOfRange => {
                 write!(f, "Value is out of range")
             }
+            MerdeError::LossyNumber { target } => {
+                write!(f, "Number can't be converted to {target} exactly")
+            }
             MerdeError::MissingValue => {
                 write!(f, "Missing value")
             }
@@ -217,9 +444,13 @@ impl std::fmt::Display for MerdeError<'_> {
             MerdeError::InvalidDateTimeValue => {
                 write!(f, "Invalid date/time value")
             }
+            #[cfg(feature = "std")]
             MerdeError::Io(e) => {
                 write!(f, "I/O error: {}", e)
             }
+            MerdeError::UnexpectedEof => {
+                write!(f, "Unexpected end of input")
+            }
             MerdeError::UnexpectedEvent {
                 got,
                 expected,
@@ -267,11 +498,199 @@ impl std::fmt::Display for MerdeError<'_> {
             MerdeError::BinaryParsingError { format, message } => {
                 write!(f, "{format} parsing error: {message}")
             }
+            MerdeError::DuplicateKey(key) => {
+                write!(f, "Duplicate key: {}", key)
+            }
+            MerdeError::UnknownVersion(version) => {
+                write!(f, "Unknown version: {}", version)
+            }
+            MerdeError::PatchFailed { index, message } => {
+                write!(f, "Patch operation #{index} failed: {message}")
+            }
+            MerdeError::DisallowedSyntax {
+                format,
+                syntax,
+                line,
+                column,
+                help,
+                ..
+            } => {
+                write!(
+                    f,
+                    "{format} does not allow {syntax}; found at line {line}, column {column}"
+                )?;
+                if let Some(help) = help {
+                    write!(f, " ({help})")?;
+                }
+                Ok(())
+            }
+            MerdeError::Multiple(errors) => {
+                write!(f, "{} errors:", errors.len())?;
+                for (path, error) in errors {
+                    write!(f, "\n  {path}: {error}")?;
+                }
+                Ok(())
+            }
+            MerdeError::Custom(message) => f.write_str(message),
+            MerdeError::Context { context, source } => {
+                write!(f, "{context}: {source}")
+            }
+            MerdeError::Field { .. } => {
+                let (path, source) = self.field_path();
+                write!(f, "{path}: {source}")
+            }
         }
     }
 }
 
-impl std::error::Error for MerdeError<'_> {}
+impl core::error::Error for MerdeError<'_> {}
+
+/// Errors serialize as a map with a `code` (see [MerdeError::code]), a
+/// human-readable `message`, and the details of the error, if any: `offset` for
+/// parsing errors, `property` for missing and unknown properties, etc. Errors
+/// wrapped with [MerdeError::with_field] have a `path`, and those wrapped with
+/// [MerdeError::context] have a `context` (the outermost one).
+///
+/// Unlike its [Display](core::fmt::Display) implementation, the message never
+/// includes the source document, or terminal escape codes.
+impl Serialize for MerdeError<'_> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let mut fields: Vec<(&'static str, Value<'_>)> = vec![
+            ("code", Value::from(self.code())),
+            ("message", Value::from(self.plain_message())),
+        ];
+        let mut path = Vec::new();
+        let mut error = self;
+        loop {
+            match error {
+                MerdeError::Context { context, source } => {
+                    if !fields.iter().any(|(key, _)| *key == "context") {
+                        fields.push(("context", Value::Str(context.clone())));
+                    }
+                    error = source;
+                }
+                MerdeError::Field { field, source } => {
+                    path.push(field.as_ref());
+                    error = source;
+                }
+                _ => break,
+            }
+        }
+        if !path.is_empty() {
+            fields.push(("path", Value::from(path.join("."))));
+        }
+        match error {
+            MerdeError::MismatchedType { expected, found } => {
+                fields.push(("expected", Value::from(format!("{expected:?}"))));
+                fields.push(("found", Value::from(format!("{found:?}"))));
+            }
+            MerdeError::MissingProperty(prop) | MerdeError::UnknownProperty(prop) => {
+                fields.push(("property", Value::Str(prop.clone())));
+            }
+            MerdeError::IndexOutOfBounds { index, len } => {
+                fields.push(("index", Value::from(*index as u64)));
+                fields.push(("len", Value::from(*len as u64)));
+            }
+            MerdeError::InvalidKey { key, type_name } => {
+                fields.push(("key", Value::Str(key.clone())));
+                fields.push(("type_name", Value::from(*type_name)));
+            }
+            MerdeError::UnexpectedEvent {
+                got,
+                expected,
+                help,
+            } => {
+                fields.push(("got", Value::from(format!("{got:?}"))));
+                fields.push((
+                    "expected",
+                    Value::from(
+                        expected
+                            .iter()
+                            .map(|ty| Value::from(format!("{ty:?}")))
+                            .collect::<Vec<_>>(),
+                    ),
+                ));
+                if let Some(help) = help {
+                    fields.push(("help", Value::from(help.as_str())));
+                }
+            }
+            MerdeError::Utf8Error(e) => {
+                fields.push(("offset", Value::from(e.valid_up_to() as u64)));
+            }
+            MerdeError::StringParsingError { format, index, .. } => {
+                fields.push(("format", Value::from(*format)));
+                fields.push(("offset", Value::from(*index as u64)));
+            }
+            MerdeError::BinaryParsingError { format, .. } => {
+                fields.push(("format", Value::from(*format)));
+            }
+            MerdeError::DuplicateKey(key) => {
+                fields.push(("key", Value::Str(key.clone())));
+            }
+            MerdeError::LossyNumber { target } => {
+                fields.push(("target", Value::from(*target)));
+            }
+            MerdeError::UnknownVersion(version) => {
+                fields.push(("version", Value::from(*version as u64)));
+            }
+            MerdeError::PatchFailed { index, .. } => {
+                fields.push(("index", Value::from(*index as u64)));
+            }
+            MerdeError::DisallowedSyntax {
+                format,
+                syntax,
+                index,
+                line,
+                column,
+                ..
+            } => {
+                fields.push(("format", Value::from(*format)));
+                fields.push(("syntax", Value::from(*syntax)));
+                fields.push(("offset", Value::from(*index as u64)));
+                fields.push(("line", Value::from(*line as u64)));
+                fields.push(("column", Value::from(*column as u64)));
+            }
+            MerdeError::Multiple(errors) => {
+                let errors = errors
+                    .iter()
+                    .map(|(path, error)| {
+                        Value::from(
+                            Map::new()
+                                .with("path", path.as_str())
+                                .with("code", error.code())
+                                .with("message", error.to_string()),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                fields.push(("errors", Value::from(errors)));
+            }
+            MerdeError::OutOfRange
+            | MerdeError::MissingValue
+            | MerdeError::InvalidDateTimeValue
+            | MerdeError::UnexpectedEof
+            | MerdeError::PutBackCalledTwice
+            | MerdeError::Custom(_)
+            | MerdeError::Context { .. }
+            | MerdeError::Field { .. } => {}
+            #[cfg(feature = "std")]
+            MerdeError::Io(_) => {}
+        }
+
+        serializer
+            .write(Event::MapStart(MapStart {
+                size_hint: Some(fields.len()),
+            }))
+            .await?;
+        for (key, value) in &fields {
+            serializer.write(Event::Str(CowStr::Borrowed(key))).await?;
+            value.serialize(serializer).await?;
+        }
+        serializer.write(Event::MapEnd).await
+    }
+}
 
 impl Value<'_> {
     /// Returns the [ValueType] for a given [Value].
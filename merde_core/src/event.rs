@@ -1,11 +1,18 @@
-use std::borrow::Cow;
+use alloc::{borrow::Cow, collections::VecDeque, string::String, vec::Vec};
 
-use crate::{CowBytes, CowStr, MerdeError};
+use crate::{CowBytes, CowStr, MerdeError, TypeHint};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Event<'s> {
     I64(i64),
     U64(u64),
+    /// An integer that doesn't fit in an `i64` (or a `u64`): formats and
+    /// [Serialize](crate::Serialize) impls only use it for values that need it,
+    /// so serializers that can't represent 128-bit integers only see it for
+    /// those.
+    I128(i128),
+    /// See [Event::I128]
+    U128(u128),
     F64(f64),
     Str(CowStr<'s>),
     Bytes(CowBytes<'s>),
@@ -46,6 +53,9 @@ impl_from_for_event! {
     u16 => U64,
     u32 => U64,
     u64 => U64,
+    // wide
+    i128 => I128,
+    u128 => U128,
     // floats
     f32 => F64,
     f64 => F64,
@@ -105,6 +115,8 @@ impl<'s> From<CowBytes<'s>> for Event<'s> {
 pub enum EventType {
     I64,
     U64,
+    I128,
+    U128,
     Float,
     Str,
     Bytes,
@@ -121,6 +133,8 @@ impl From<&Event<'_>> for EventType {
         match event {
             Event::I64(_) => EventType::I64,
             Event::U64(_) => EventType::U64,
+            Event::I128(_) => EventType::I128,
+            Event::U128(_) => EventType::U128,
             Event::F64(_) => EventType::Float,
             Event::Str(_) => EventType::Str,
             Event::Bytes(_) => EventType::Bytes,
@@ -134,16 +148,115 @@ impl From<&Event<'_>> for EventType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ArrayStart {
     pub size_hint: Option<usize>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MapStart {
     pub size_hint: Option<usize>,
 }
 
+/// Borrowing accessors, for branching on an event without consuming it (the
+/// `into_*` methods below consume it, and return an error on mismatch).
+impl Event<'_> {
+    /// The type of this event, without its payload.
+    pub fn kind(&self) -> EventType {
+        EventType::from(self)
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Event::I64(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Event::U64(u) => Some(*u),
+            _ => None,
+        }
+    }
+
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Event::I128(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_u128(&self) -> Option<u128> {
+        match self {
+            Event::U128(u) => Some(*u),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Event::F64(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Event::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Event::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Event::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_map_start(&self) -> Option<&MapStart> {
+        match self {
+            Event::MapStart(ms) => Some(ms),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_start(&self) -> Option<&ArrayStart> {
+        match self {
+            Event::ArrayStart(array_start) => Some(array_start),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Event::Null)
+    }
+
+    pub fn is_map_start(&self) -> bool {
+        matches!(self, Event::MapStart(_))
+    }
+
+    pub fn is_map_end(&self) -> bool {
+        matches!(self, Event::MapEnd)
+    }
+
+    pub fn is_array_start(&self) -> bool {
+        matches!(self, Event::ArrayStart(_))
+    }
+
+    pub fn is_array_end(&self) -> bool {
+        matches!(self, Event::ArrayEnd)
+    }
+}
+
 impl<'s> Event<'s> {
     pub fn into_i64(self) -> Result<i64, MerdeError<'s>> {
         match self {
@@ -266,3 +379,126 @@ impl<'s> Event<'s> {
         }
     }
 }
+
+/// Used by formats whose values are all text, like environment variables and
+/// query strings: `true`, `false` and numbers written the way JSON would
+/// (`-?(0|[1-9][0-9]*)(\.[0-9]+)?`, so that `007` or `+1` stay strings) become
+/// booleans and numbers, anything else is a string.
+#[doc(hidden)]
+pub fn infer_scalar(s: CowStr<'_>) -> Event<'_> {
+    match &s[..] {
+        "true" => return Event::Bool(true),
+        "false" => return Event::Bool(false),
+        num if is_canonical_number(num) => {
+            if let Ok(i) = num.parse::<i64>() {
+                return Event::I64(i);
+            }
+            if let Ok(u) = num.parse::<u64>() {
+                return Event::U64(u);
+            }
+            if let Ok(i) = num.parse::<i128>() {
+                return Event::I128(i);
+            }
+            if let Ok(u) = num.parse::<u128>() {
+                return Event::U128(u);
+            }
+            if let Ok(f) = num.parse::<f64>() {
+                return Event::F64(f);
+            }
+        }
+        _ => {}
+    }
+    Event::Str(s)
+}
+
+/// A queue of events for formats whose values are all text: values pushed with
+/// [TextEvents::push_scalar] are typed with [infer_scalar], except when the
+/// caller asks for a string ([TypeHint::Str]), in which case they're returned
+/// as written, so that `zip=007` or `PASSWORD=1234` still fit in a `String`.
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct TextEvents<'s> {
+    // values that were typed by their looks come with their text
+    events: VecDeque<(Event<'s>, Option<CowStr<'s>>)>,
+    // the last event returned, if it's a value that wasn't typed as a string:
+    // it may be put back, then asked for again as a string
+    last_inferred: Option<(Event<'s>, CowStr<'s>)>,
+}
+
+impl<'s> TextEvents<'s> {
+    /// Queues an event as-is.
+    pub fn push(&mut self, ev: Event<'s>) {
+        self.events.push_back((ev, None));
+    }
+
+    /// Queues a value, typed by its looks.
+    pub fn push_scalar(&mut self, s: CowStr<'s>) {
+        match infer_scalar(s.clone()) {
+            Event::Str(s) => self.events.push_back((Event::Str(s), None)),
+            ev => self.events.push_back((ev, Some(s))),
+        }
+    }
+
+    /// Returns the next event, as written if it's a value and `hint` asks for
+    /// a string.
+    pub fn next(&mut self, hint: Option<TypeHint>) -> Option<Event<'s>> {
+        let (ev, text) = self.events.pop_front()?;
+        self.last_inferred = None;
+        match text {
+            Some(text) if hint == Some(TypeHint::Str) => Some(Event::Str(text)),
+            Some(text) => {
+                self.last_inferred = Some((ev.clone(), text));
+                Some(ev)
+            }
+            None => Some(ev),
+        }
+    }
+
+    /// Puts an event back, keeping its text if it's the last one returned.
+    pub fn put_back(&mut self, ev: Event<'s>) {
+        let text = match self.last_inferred.take() {
+            Some((last, text)) if last == ev => Some(text),
+            _ => None,
+        };
+        self.events.push_front((ev, text));
+    }
+}
+
+// `-?(0|[1-9][0-9]*)(\.[0-9]+)?`
+fn is_canonical_number(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    let (int, frac) = match s.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (s, None),
+    };
+    let int_ok = int == "0"
+        || (!int.is_empty() && !int.starts_with('0') && int.bytes().all(|b| b.is_ascii_digit()));
+    let frac_ok = match frac {
+        Some(frac) => !frac.is_empty() && frac.bytes().all(|b| b.is_ascii_digit()),
+        None => true,
+    };
+    int_ok && frac_ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_accessors() {
+        let ev = Event::from("hello");
+        assert_eq!(ev.kind(), EventType::Str);
+        assert_eq!(ev.as_str(), Some("hello"));
+        assert_eq!(ev.as_i64(), None);
+        assert!(!ev.is_null());
+        assert_eq!(ev, Event::Str(CowStr::from(String::from("hello"))));
+
+        let ev = Event::MapStart(MapStart { size_hint: Some(2) });
+        assert!(ev.is_map_start());
+        assert_eq!(ev.as_map_start().and_then(|ms| ms.size_hint), Some(2));
+        assert_ne!(ev, Event::MapStart(MapStart { size_hint: None }));
+
+        assert_eq!(Event::from(3u8).as_u64(), Some(3));
+        assert_ne!(Event::I64(3), Event::U64(3));
+    }
+}
@@ -0,0 +1,154 @@
+use alloc::{collections::VecDeque, string::ToString};
+
+use crate::{Deserializer, DynDeserializer, Event, EventType, MerdeError};
+
+/// A recorded sequence of events, that can be replayed as a [`Deserializer`].
+///
+/// This is the building block for anything that needs to look ahead before
+/// deciding how to deserialize a value: untagged enums (try each variant against
+/// the same events), internally-tagged enums whose tag isn't the first field,
+/// flattening, etc.
+///
+/// ```
+/// use merde_core::{DynDeserializerExt, Event, EventBuffer, MetastackExt};
+///
+/// let mut source = EventBuffer::from_events([Event::I64(1), Event::I64(2)]);
+/// // record exactly one value from the source...
+/// let mut recorded = EventBuffer::record(&mut source).run_sync_with_metastack().unwrap();
+/// assert_eq!(recorded.len(), 1);
+/// // ...and replay it
+/// assert_eq!(recorded.deserialize::<i64>().unwrap(), 1);
+/// assert_eq!(source.deserialize::<i64>().unwrap(), 2);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct EventBuffer<'s> {
+    events: VecDeque<Event<'s>>,
+}
+
+impl<'s> EventBuffer<'s> {
+    /// Creates an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a buffer that will replay `events`, in order.
+    pub fn from_events(events: impl IntoIterator<Item = Event<'s>>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+
+    /// Reads exactly one value from `de` (a single scalar, or a whole map or array,
+    /// nested values included) and records its events.
+    pub async fn record(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        let mut events = VecDeque::new();
+        let mut depth: usize = 0;
+        loop {
+            let ev = de.next().await?;
+            match ev {
+                Event::MapStart(_) | Event::ArrayStart(_) => depth += 1,
+                Event::MapEnd | Event::ArrayEnd => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or_else(|| MerdeError::UnexpectedEvent {
+                            got: EventType::from(&ev),
+                            expected: &[],
+                            help: Some("(While recording a value into an EventBuffer)".to_string()),
+                        })?
+                }
+                _ => {}
+            }
+            events.push_back(ev);
+            if depth == 0 {
+                return Ok(Self { events });
+            }
+        }
+    }
+
+    /// Appends an event to be replayed after all the others.
+    pub fn push(&mut self, ev: Event<'s>) {
+        self.events.push_back(ev);
+    }
+
+    /// Returns the number of events left to replay.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns true if there are no events left to replay.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the events left to replay.
+    pub fn into_events(self) -> VecDeque<Event<'s>> {
+        self.events
+    }
+}
+
+impl<'s> Deserializer<'s> for EventBuffer<'s> {
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        self.events.pop_front().ok_or_else(MerdeError::eof)
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.events.push_front(ev);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStart, DynDeserializerExt, MapStart, MetastackExt};
+
+    #[test]
+    fn test_record_subtree() {
+        let mut source = EventBuffer::from_events([
+            Event::ArrayStart(ArrayStart { size_hint: None }),
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("a".into()),
+            Event::ArrayStart(ArrayStart { size_hint: None }),
+            Event::ArrayEnd,
+            Event::MapEnd,
+            Event::Bool(true),
+            Event::ArrayEnd,
+        ]);
+
+        assert!(matches!(
+            Deserializer::next(&mut source).run_sync_with_metastack(),
+            Ok(Event::ArrayStart(_))
+        ));
+        let recorded = EventBuffer::record(&mut source)
+            .run_sync_with_metastack()
+            .unwrap();
+        assert_eq!(recorded.len(), 5);
+        assert_eq!(source.len(), 2);
+
+        let mut recorded = recorded;
+        let map: std::collections::HashMap<String, Vec<i64>> = recorded.deserialize().unwrap();
+        assert_eq!(map["a"], Vec::<i64>::new());
+        assert!(recorded.is_empty());
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut source = EventBuffer::from_events([Event::Null, Event::I64(1)]);
+        let mut peek = || Deserializer::peek(&mut source).run_sync_with_metastack();
+        assert_eq!(peek().unwrap(), EventType::Null);
+        assert_eq!(peek().unwrap(), EventType::Null);
+
+        let values: (Option<i64>, Option<i64>) =
+            (source.deserialize().unwrap(), source.deserialize().unwrap());
+        assert_eq!(values, (None, Some(1)));
+        assert!(source.is_empty());
+    }
+
+    #[test]
+    fn test_record_unbalanced() {
+        let mut source = EventBuffer::from_events([Event::MapEnd]);
+        assert!(EventBuffer::record(&mut source)
+            .run_sync_with_metastack()
+            .is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use alloc::vec::Vec;
+use core::future::Future;
+
+use crate::{Event, EventBuffer, IntoStatic, MerdeError, Serializer};
+
+/// A [Serializer] that just records the events it's given, for testing
+/// [Serialize](crate::Serialize) impls, or transcoding. Strings and bytes
+/// are copied (except small ones), so the events don't borrow from what was
+/// serialized.
+///
+/// ```
+/// use merde_core::{DynDeserializerExt, DynSerializerExt, Event, EventCollector};
+///
+/// let mut collector = EventCollector::new();
+/// collector.serialize(&vec![1u8, 2]).unwrap();
+/// assert_eq!(collector.events().len(), 4);
+/// assert_eq!(collector.events()[1], Event::U64(1));
+///
+/// // and back
+/// let v: Vec<u8> = collector.into_buffer().deserialize().unwrap();
+/// assert_eq!(v, [1, 2]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct EventCollector {
+    events: Vec<Event<'static>>,
+}
+
+impl EventCollector {
+    /// Creates a collector with no events.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the events collected so far.
+    pub fn events(&self) -> &[Event<'static>] {
+        &self.events
+    }
+
+    /// Returns the events collected, in order.
+    pub fn into_events(self) -> Vec<Event<'static>> {
+        self.events
+    }
+
+    /// Returns a buffer that replays the events collected, to deserialize them.
+    pub fn into_buffer(self) -> EventBuffer<'static> {
+        EventBuffer::from_events(self.events)
+    }
+}
+
+impl Serializer for EventCollector {
+    fn write<'fut>(
+        &'fut mut self,
+        ev: Event<'fut>,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        self.events.push(ev.into_static());
+        core::future::ready(Ok(()))
+    }
+}
@@ -0,0 +1,444 @@
+use alloc::{collections::VecDeque, vec::Vec};
+use core::{fmt, ops::Range};
+
+use crate::{
+    CowStr, Deserializer, ErrorCollector, Event, IntoStatic, MerdeError, NumberPolicy, TypeHint,
+};
+
+/// Where an event sits in a document, as seen by [MapEvents] and [FilterEvents]:
+/// which map key or array index it's under, at every level.
+///
+/// Its `Display` impl formats it like `users[2].password`.
+#[derive(Debug, Default, Clone)]
+pub struct EventPath<'s> {
+    frames: Vec<Frame<'s>>,
+}
+
+#[derive(Debug, Clone)]
+enum Frame<'s> {
+    /// `key` is `None` when the next event is a key (or the end of the map)
+    Map {
+        key: Option<CowStr<'s>>,
+    },
+    Array {
+        index: usize,
+    },
+}
+
+impl<'s> EventPath<'s> {
+    /// How many maps and arrays the event is nested in (0 for the top-level value)
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns true if the event is a map key.
+    pub fn is_key(&self) -> bool {
+        matches!(self.frames.last(), Some(Frame::Map { key: None }))
+    }
+
+    /// If the event is (the start of) a map value, the key it's under.
+    pub fn key(&self) -> Option<&str> {
+        match self.frames.last() {
+            Some(Frame::Map { key: Some(key) }) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// If the event is (the start of) an array item, its index in the input.
+    pub fn index(&self) -> Option<usize> {
+        match self.frames.last() {
+            Some(Frame::Array { index }) => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// The keys of all the maps the event is in, from the outermost one.
+    /// Array indices are skipped, and so is the key itself if the event is a key.
+    pub fn keys(&self) -> impl Iterator<Item = &str> + '_ {
+        self.frames.iter().filter_map(|frame| match frame {
+            Frame::Map { key: Some(key) } => Some(key.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// Moves past `ev`, which was at this position.
+    fn advance(&mut self, ev: &Event<'s>) {
+        match ev {
+            Event::MapStart(_) => self.frames.push(Frame::Map { key: None }),
+            Event::ArrayStart(_) => self.frames.push(Frame::Array { index: 0 }),
+            Event::MapEnd | Event::ArrayEnd => {
+                self.frames.pop();
+                self.finish_value();
+            }
+            Event::Str(key) if self.is_key() => {
+                self.frames.pop();
+                self.frames.push(Frame::Map {
+                    key: Some(key.clone()),
+                });
+            }
+            _ => self.finish_value(),
+        }
+    }
+
+    /// Moves past a whole value (or a whole map entry, if at a key).
+    pub(crate) fn finish_value(&mut self) {
+        match self.frames.last_mut() {
+            Some(Frame::Map { key }) => *key = None,
+            Some(Frame::Array { index }) => *index += 1,
+            None => {}
+        }
+    }
+}
+
+impl EventPath<'static> {
+    /// Like `advance`, for events that don't live as long as the path.
+    pub(crate) fn advance_owned(&mut self, ev: &Event<'_>) {
+        let ev = match ev {
+            Event::Str(key) if self.is_key() => Event::Str(key.clone().into_static()),
+            Event::MapStart(ms) => Event::MapStart(ms.clone()),
+            Event::ArrayStart(ars) => Event::ArrayStart(ars.clone()),
+            Event::MapEnd => Event::MapEnd,
+            Event::ArrayEnd => Event::ArrayEnd,
+            // only the shape matters
+            _ => Event::Null,
+        };
+        self.advance(&ev);
+    }
+}
+
+impl fmt::Display for EventPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for frame in &self.frames {
+            match frame {
+                Frame::Map { key: Some(key) } => {
+                    if !first {
+                        f.write_str(".")?;
+                    }
+                    f.write_str(key)?;
+                }
+                Frame::Map { key: None } => {}
+                Frame::Array { index } => write!(f, "[{index}]")?,
+            }
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+/// A [Deserializer] that passes every event of another deserializer through a
+/// function, e.g. to rename keys, or to redact values before they're logged.
+///
+/// The function gets each event along with its [EventPath]. It may change
+/// scalars (including turning a string into a number, etc.), but must leave
+/// the structure alone: it must return `MapStart`, `MapEnd`, `ArrayStart` and
+/// `ArrayEnd` events as they are, and map keys must stay strings.
+///
+/// ```rust
+/// use merde_core::{DynDeserializerExt, Event, EventBuffer, MapEvents, MapStart, Value};
+///
+/// let source = EventBuffer::from_events([
+///     Event::MapStart(MapStart { size_hint: None }),
+///     Event::Str("password".into()),
+///     Event::Str("hunter2".into()),
+///     Event::MapEnd,
+/// ]);
+/// let mut de = MapEvents::new(source, |ev, path| match (ev, path.key()) {
+///     (Event::Str(_), Some("password")) => Event::Str("[redacted]".into()),
+///     (ev, _) => ev,
+/// });
+/// let value: Value = de.deserialize().unwrap();
+/// assert_eq!(value.as_map().unwrap().values().next(), Some(&Value::from("[redacted]")));
+/// ```
+pub struct MapEvents<'s, D, F> {
+    inner: D,
+    f: F,
+    path: EventPath<'s>,
+    starters: VecDeque<Event<'s>>,
+}
+
+impl<'s, D, F> MapEvents<'s, D, F>
+where
+    D: Deserializer<'s>,
+    F: FnMut(Event<'s>, &EventPath<'s>) -> Event<'s>,
+{
+    /// Wraps `inner`, passing all its events through `f`.
+    pub fn new(inner: D, f: F) -> Self {
+        Self {
+            inner,
+            f,
+            path: EventPath::default(),
+            starters: VecDeque::new(),
+        }
+    }
+
+    /// Returns the wrapped deserializer.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+
+    fn map(&mut self, ev: Event<'s>) -> Event<'s> {
+        let ev = (self.f)(ev, &self.path);
+        self.path.advance(&ev);
+        ev
+    }
+}
+
+impl<D: fmt::Debug, F> fmt::Debug for MapEvents<'_, D, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapEvents")
+            .field("inner", &self.inner)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'s, D, F> Deserializer<'s> for MapEvents<'s, D, F>
+where
+    D: Deserializer<'s>,
+    F: FnMut(Event<'s>, &EventPath<'s>) -> Event<'s>,
+{
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
+            return Ok(ev);
+        }
+        let ev = self.inner.next().await?;
+        Ok(self.map(ev))
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.starters.push_front(ev);
+        Ok(())
+    }
+
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
+            return Ok(ev);
+        }
+        let ev = self.inner.next_event_with_hint(hint).await?;
+        Ok(self.map(ev))
+    }
+
+    fn number_policy(&self) -> NumberPolicy {
+        self.inner.number_policy()
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        self.inner.span()
+    }
+
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>> {
+        self.inner.error_collector()
+    }
+}
+
+/// A [Deserializer] that drops parts of another deserializer's event stream,
+/// e.g. fields nobody should see, or subtrees that are too expensive to
+/// deserialize.
+///
+/// The function gets the first event of every value (a scalar, or the start of
+/// a map or an array) along with its [EventPath], and returns `false` to drop
+/// the value, nested values included. It's also called for map keys, in which
+/// case returning `false` drops the whole entry. It's _not_ called for map
+/// values: since their key has already been passed on by then, it's too late
+/// to drop them.
+///
+/// ```rust
+/// use merde_core::{DynDeserializerExt, Event, EventBuffer, FilterEvents, MapStart, Value};
+///
+/// let source = EventBuffer::from_events([
+///     Event::MapStart(MapStart { size_hint: None }),
+///     Event::Str("name".into()),
+///     Event::Str("Jane".into()),
+///     Event::Str("secret".into()),
+///     Event::MapStart(MapStart { size_hint: None }),
+///     Event::Str("token".into()),
+///     Event::Str("abc".into()),
+///     Event::MapEnd,
+///     Event::MapEnd,
+/// ]);
+/// let mut de = FilterEvents::new(source, |ev, path| {
+///     !(path.is_key() && matches!(ev, Event::Str(k) if *k == "secret"))
+/// });
+/// let value: Value = de.deserialize().unwrap();
+/// assert_eq!(value.as_map().unwrap().len(), 1);
+/// ```
+pub struct FilterEvents<'s, D, F> {
+    inner: D,
+    f: F,
+    path: EventPath<'s>,
+    starters: VecDeque<Event<'s>>,
+}
+
+impl<'s, D, F> FilterEvents<'s, D, F>
+where
+    D: Deserializer<'s>,
+    F: FnMut(&Event<'s>, &EventPath<'s>) -> bool,
+{
+    /// Wraps `inner`, dropping the values for which `f` returns `false`.
+    pub fn new(inner: D, f: F) -> Self {
+        Self {
+            inner,
+            f,
+            path: EventPath::default(),
+            starters: VecDeque::new(),
+        }
+    }
+
+    /// Returns the wrapped deserializer.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+
+    async fn next_filtered(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
+            return Ok(ev);
+        }
+        loop {
+            let ev = match hint {
+                Some(hint) => self.inner.next_event_with_hint(hint).await?,
+                None => self.inner.next().await?,
+            };
+            let consulted =
+                !matches!(ev, Event::MapEnd | Event::ArrayEnd) && self.path.key().is_none();
+            if !consulted || (self.f)(&ev, &self.path) {
+                self.path.advance(&ev);
+                return Ok(ev);
+            }
+
+            if self.path.is_key() {
+                // drop the key and its value: the path stays at the next key
+                self.inner.skip_value().await?;
+            } else {
+                self.inner.put_back(ev)?;
+                self.inner.skip_value().await?;
+                self.path.finish_value();
+            }
+        }
+    }
+}
+
+impl<D: fmt::Debug, F> fmt::Debug for FilterEvents<'_, D, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterEvents")
+            .field("inner", &self.inner)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'s, D, F> Deserializer<'s> for FilterEvents<'s, D, F>
+where
+    D: Deserializer<'s>,
+    F: FnMut(&Event<'s>, &EventPath<'s>) -> bool,
+{
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        self.next_filtered(None).await
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.starters.push_front(ev);
+        Ok(())
+    }
+
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        self.next_filtered(Some(hint)).await
+    }
+
+    fn number_policy(&self) -> NumberPolicy {
+        self.inner.number_policy()
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        self.inner.span()
+    }
+
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>> {
+        self.inner.error_collector()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStart, DynDeserializerExt, EventBuffer, MapStart, Value};
+
+    fn events() -> EventBuffer<'static> {
+        // {"users": [{"name": "a", "password": "x"}, {"name": "b", "tags": [1, 2]}], "n": 2}
+        EventBuffer::from_events([
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("users".into()),
+            Event::ArrayStart(ArrayStart { size_hint: None }),
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("name".into()),
+            Event::Str("a".into()),
+            Event::Str("password".into()),
+            Event::Str("x".into()),
+            Event::MapEnd,
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("name".into()),
+            Event::Str("b".into()),
+            Event::Str("tags".into()),
+            Event::ArrayStart(ArrayStart { size_hint: None }),
+            Event::I64(1),
+            Event::I64(2),
+            Event::ArrayEnd,
+            Event::MapEnd,
+            Event::ArrayEnd,
+            Event::Str("n".into()),
+            Event::I64(2),
+            Event::MapEnd,
+        ])
+    }
+
+    #[test]
+    fn test_map_events_paths() {
+        let mut paths = vec![];
+        let mut de = MapEvents::new(events(), |ev, path| {
+            if !path.is_key() && !matches!(ev, Event::MapEnd | Event::ArrayEnd) {
+                paths.push(path.to_string());
+            }
+            match ev {
+                Event::Str(k) if path.is_key() => Event::Str(k.to_uppercase().into()),
+                ev => ev,
+            }
+        });
+        let value: Value = de.deserialize().unwrap();
+        assert!(value.as_map().unwrap().contains_key(&CowStr::from("USERS")));
+        assert_eq!(
+            paths,
+            [
+                "",
+                "USERS",
+                "USERS[0]",
+                "USERS[0].NAME",
+                "USERS[0].PASSWORD",
+                "USERS[1]",
+                "USERS[1].NAME",
+                "USERS[1].TAGS",
+                "USERS[1].TAGS[0]",
+                "USERS[1].TAGS[1]",
+                "N",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_events() {
+        let mut de = FilterEvents::new(events(), |ev, path| {
+            let is_secret = path.is_key() && matches!(ev, Event::Str(k) if *k == "password");
+            // drop the second user
+            let is_dropped = path.index() == Some(1) && path.depth() == 2;
+            !is_secret && !is_dropped
+        });
+        let value: Value = de.deserialize().unwrap();
+
+        let map = value.as_map().unwrap();
+        assert_eq!(map.get(&CowStr::from("n")), Some(&Value::I64(2)));
+        let users = map.get(&CowStr::from("users")).unwrap().as_array().unwrap();
+        assert_eq!(users.len(), 1);
+        let user = users[0].as_map().unwrap();
+        assert_eq!(user.len(), 1);
+        assert_eq!(user.get(&CowStr::from("name")), Some(&Value::from("a")));
+    }
+}
@@ -0,0 +1,210 @@
+//! Adapters that turn an existing source of [Event]s into a [Deserializer], so
+//! that anything producing events (a database driver, a custom binary reader, a
+//! test harness) can feed merde's [Deserialize](crate::Deserialize) machinery directly.
+
+use alloc::collections::VecDeque;
+use core::future::Future;
+
+use crate::{Deserializer, Event, MerdeError};
+
+/// A [Deserializer] reading events from any [Iterator] of [Event]s.
+///
+/// For producers that can fail, or that have to wait for their events (like
+/// a decoder reading from a socket), implement [EventSource] and use an
+/// [EventSourceDeserializer] instead.
+///
+/// ```
+/// use merde_core::{DynDeserializerExt, Event, EventIterDeserializer};
+///
+/// let mut de = EventIterDeserializer::new([Event::I64(42)]);
+/// let n: i64 = de.deserialize().unwrap();
+/// assert_eq!(n, 42);
+/// ```
+pub struct EventIterDeserializer<'s, I>
+where
+    I: Iterator<Item = Event<'s>>,
+{
+    iter: I,
+    starters: VecDeque<Event<'s>>,
+}
+
+impl<'s, I> EventIterDeserializer<'s, I>
+where
+    I: Iterator<Item = Event<'s>>,
+{
+    /// Creates a deserializer that yields the events of `iter`, then reports EOF.
+    pub fn new(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            iter: iter.into_iter(),
+            starters: VecDeque::new(),
+        }
+    }
+
+    /// Returns the underlying iterator, dropping any put-back event.
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<'s, I> core::fmt::Debug for EventIterDeserializer<'s, I>
+where
+    I: Iterator<Item = Event<'s>>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EventIterDeserializer")
+            .field("starters", &self.starters)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'s, I> Deserializer<'s> for EventIterDeserializer<'s, I>
+where
+    I: Iterator<Item = Event<'s>>,
+{
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
+            return Ok(ev);
+        }
+        self.iter.next().ok_or_else(MerdeError::eof)
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.starters.push_front(ev);
+        Ok(())
+    }
+}
+
+/// An asynchronous source of events, for use with [EventSourceDeserializer].
+///
+/// This is the async counterpart of `Iterator<Item = Result<Event, MerdeError>>`:
+/// returning `None` signals the end of the events.
+pub trait EventSource<'s> {
+    /// Produces the next event, or `None` if there are no more events.
+    fn next_event(
+        &mut self,
+    ) -> impl Future<Output = Option<Result<Event<'s>, MerdeError<'s>>>> + '_;
+}
+
+/// A [Deserializer] reading events from an [EventSource].
+pub struct EventSourceDeserializer<'s, S>
+where
+    S: EventSource<'s>,
+{
+    source: S,
+    starters: VecDeque<Event<'s>>,
+}
+
+impl<'s, S> EventSourceDeserializer<'s, S>
+where
+    S: EventSource<'s>,
+{
+    /// Creates a deserializer that yields the events of `source`, then reports EOF.
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            starters: VecDeque::new(),
+        }
+    }
+
+    /// Returns the underlying source, dropping any put-back event.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<'s, S> core::fmt::Debug for EventSourceDeserializer<'s, S>
+where
+    S: EventSource<'s>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EventSourceDeserializer")
+            .field("starters", &self.starters)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'s, S> Deserializer<'s> for EventSourceDeserializer<'s, S>
+where
+    S: EventSource<'s>,
+{
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
+            return Ok(ev);
+        }
+        match self.source.next_event().await {
+            Some(res) => res,
+            None => Err(MerdeError::eof()),
+        }
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.starters.push_front(ev);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStart, CowStr, DynDeserializerExt, MetastackExt};
+
+    fn events() -> Vec<Event<'static>> {
+        vec![
+            Event::ArrayStart(ArrayStart { size_hint: None }),
+            Event::Str(CowStr::Borrowed("a")),
+            Event::Str(CowStr::Borrowed("b")),
+            Event::ArrayEnd,
+        ]
+    }
+
+    #[test]
+    fn test_event_iter_deserializer() {
+        let mut de = EventIterDeserializer::new(events());
+        let v: Vec<String> = de.deserialize().unwrap();
+        assert_eq!(v, ["a", "b"]);
+
+        assert!(de.deserialize::<i64>().is_err());
+
+        // events that are put back come first, and the rest of the iterator
+        // is left alone
+        let mut de = EventIterDeserializer::new(events());
+        let first = de.next().run_sync_flat().unwrap();
+        de.put_back(first).unwrap();
+        assert!(matches!(
+            de.next().run_sync_flat().unwrap(),
+            Event::ArrayStart(_)
+        ));
+        assert_eq!(de.into_inner().count(), 3);
+    }
+
+    struct Queue(VecDeque<Result<Event<'static>, MerdeError<'static>>>);
+
+    impl<'s> EventSource<'s> for Queue {
+        // FIXME: that's a workaround for <https://github.com/rust-lang/rust/issues/133676>
+        #[allow(clippy::manual_async_fn)]
+        fn next_event(
+            &mut self,
+        ) -> impl Future<Output = Option<Result<Event<'s>, MerdeError<'s>>>> + '_ {
+            async { self.0.pop_front() }
+        }
+    }
+
+    #[test]
+    fn test_event_source_deserializer() {
+        let mut de = EventSourceDeserializer::new(Queue(events().into_iter().map(Ok).collect()));
+        let v: Vec<String> = de.deserialize().unwrap();
+        assert_eq!(v, ["a", "b"]);
+
+        let mut de = EventSourceDeserializer::new(Queue(
+            [
+                Ok(Event::ArrayStart(ArrayStart { size_hint: None })),
+                Err(MerdeError::OutOfRange),
+            ]
+            .into(),
+        ));
+        assert!(matches!(
+            de.deserialize::<Vec<String>>(),
+            Err(MerdeError::OutOfRange)
+        ));
+    }
+}
@@ -0,0 +1,276 @@
+//! Spans around the sync entry points (`deserialize`, `serialize`...), with
+//! the `tracing` feature. Without it, these are plain function calls.
+
+use crate::{DynDeserializer, DynSerializer, MerdeError};
+
+#[cfg(feature = "tracing")]
+use crate::{
+    CowStr, Deserializer, ErrorCollector, Event, EventType, NumberPolicy, Serializer, TypeHint,
+};
+#[cfg(feature = "tracing")]
+use core::{future::Future, ops::Range};
+
+/// Opens a `debug` span that lasts until the end of the enclosing block,
+/// when merde_core's `tracing` feature is enabled. For the entry points of
+/// format crates, like `merde_json::from_str`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "tracing")]
+macro_rules! entry_span {
+    ($($args:tt)*) => {
+        let _span = $crate::__tracing::debug_span!($($args)*).entered();
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "tracing"))]
+macro_rules! entry_span {
+    ($($args:tt)*) => {};
+}
+
+/// Runs `f`, which drives the deserialization of a `type_name` to completion,
+/// in a span that records how many events it took.
+pub(crate) fn deserialize<'s, R>(
+    de: &mut (dyn DynDeserializer<'s> + '_),
+    type_name: &'static str,
+    f: impl FnOnce(&mut (dyn DynDeserializer<'s> + '_)) -> Result<R, MerdeError<'s>>,
+) -> Result<R, MerdeError<'s>> {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::debug_span!(
+            "deserialize",
+            r#type = type_name,
+            events = tracing::field::Empty
+        );
+        let _guard = span.enter();
+        let mut counting = CountingDeserializer {
+            inner: de,
+            events: 0,
+        };
+        let res = f(&mut counting);
+        span.record("events", counting.events);
+        if let Err(e) = &res {
+            tracing::debug!(error = %e, "deserialization failed");
+        }
+        res
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = type_name;
+        f(de)
+    }
+}
+
+/// Runs `f`, which serializes a `type_name`, in a span that records how many
+/// events were written.
+pub(crate) fn serialize(
+    ser: &mut (dyn DynSerializer + '_),
+    type_name: &'static str,
+    f: impl FnOnce(&mut (dyn DynSerializer + '_)) -> Result<(), MerdeError<'static>>,
+) -> Result<(), MerdeError<'static>> {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::debug_span!(
+            "serialize",
+            r#type = type_name,
+            events = tracing::field::Empty
+        );
+        let _guard = span.enter();
+        let mut counting = CountingSerializer {
+            inner: ser,
+            events: 0,
+        };
+        let res = f(&mut counting);
+        span.record("events", counting.events);
+        if let Err(e) = &res {
+            tracing::debug!(error = %e, "serialization failed");
+        }
+        res
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = type_name;
+        f(ser)
+    }
+}
+
+// Counts the events handed over to the `Deserialize` impl (events that are
+// put back are only counted once)
+#[cfg(feature = "tracing")]
+struct CountingDeserializer<'d, 's> {
+    inner: &'d mut (dyn DynDeserializer<'s> + 'd),
+    events: usize,
+}
+
+#[cfg(feature = "tracing")]
+impl core::fmt::Debug for CountingDeserializer<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CountingDeserializer")
+            .field("events", &self.events)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<'s> Deserializer<'s> for CountingDeserializer<'_, 's> {
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        let ev = self.inner.next().await?;
+        self.events += 1;
+        Ok(ev)
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.inner.put_back(ev)?;
+        self.events = self.events.saturating_sub(1);
+        Ok(())
+    }
+
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        let ev = self.inner.next_event_with_hint(hint).await?;
+        self.events += 1;
+        Ok(ev)
+    }
+
+    fn peek(&mut self) -> impl Future<Output = Result<EventType, MerdeError<'s>>> + '_ {
+        self.inner.peek()
+    }
+
+    fn number_policy(&self) -> NumberPolicy {
+        self.inner.number_policy()
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        self.inner.span()
+    }
+
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>> {
+        self.inner.error_collector()
+    }
+
+    fn skip_value(&mut self) -> impl Future<Output = Result<(), MerdeError<'s>>> + '_ {
+        self.inner.skip_value()
+    }
+
+    fn next_raw(
+        &mut self,
+        format: &'static str,
+    ) -> impl Future<Output = Result<Option<CowStr<'s>>, MerdeError<'s>>> + '_ {
+        self.inner.next_raw(format)
+    }
+}
+
+#[cfg(feature = "tracing")]
+struct CountingSerializer<'s> {
+    inner: &'s mut (dyn DynSerializer + 's),
+    events: usize,
+}
+
+#[cfg(feature = "tracing")]
+impl Serializer for CountingSerializer<'_> {
+    fn write<'fut>(
+        &'fut mut self,
+        ev: Event<'fut>,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        self.events += 1;
+        self.inner.write(ev)
+    }
+
+    fn write_raw<'fut>(
+        &'fut mut self,
+        format: &'static str,
+        raw: &'fut str,
+    ) -> impl Future<Output = Result<bool, MerdeError<'static>>> + 'fut {
+        self.inner.write_raw(format, raw)
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event as TracingEvent, Metadata, Subscriber,
+    };
+
+    use crate::{
+        size_counting::{EncodedSize, SizeCountingSerializer},
+        DynDeserializerExt, DynSerializerExt, Event, EventBuffer, MerdeError,
+    };
+
+    // Collects the `events` recorded on spans, by span name
+    #[derive(Default, Clone)]
+    struct Recorder(Arc<Mutex<Vec<(&'static str, u64)>>>);
+
+    struct EventsVisitor<'a>(&'a mut Option<u64>);
+
+    impl Visit for EventsVisitor<'_> {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "events" {
+                *self.0 = Some(value);
+            }
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn core::fmt::Debug) {}
+    }
+
+    struct OneBytePerEvent;
+
+    impl EncodedSize for OneBytePerEvent {
+        fn event_size(&mut self, _ev: &Event<'_>) -> Result<usize, MerdeError<'static>> {
+            Ok(1)
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut spans = self.0.lock().unwrap();
+            spans.push((span.metadata().name(), 0));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut events = None;
+            values.record(&mut EventsVisitor(&mut events));
+            if let Some(events) = events {
+                self.0.lock().unwrap()[span.into_u64() as usize - 1].1 = events;
+            }
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+        fn event(&self, _event: &TracingEvent<'_>) {}
+        fn enter(&self, _span: &Id) {}
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn test_spans() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let v: Vec<u8> = EventBuffer::from_events([
+                Event::ArrayStart(crate::ArrayStart { size_hint: None }),
+                Event::U64(1),
+                Event::U64(2),
+                Event::ArrayEnd,
+            ])
+            .deserialize()
+            .unwrap();
+
+            let mut ser = SizeCountingSerializer::new(OneBytePerEvent);
+            ser.serialize(&v).unwrap();
+            assert_eq!(ser.size(), 4);
+        });
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [("deserialize", 4), ("serialize", 4)]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use alloc::{boxed::Box, collections::VecDeque};
+use core::{cell::RefCell, fmt, marker::PhantomData, ops::Range};
+
+use crate::{CowStr, Deserializer, Event, HashSet, IntoStatic, MerdeError, NumberPolicy, TypeHint};
+
+/// A set of strings, handed out as `&str` so that identical strings share a
+/// single allocation.
+///
+/// This is mostly useful with an [InterningDeserializer], when a document
+/// repeats the same keys and values (`"status": "active"`) many times over.
+#[derive(Default)]
+pub struct StringInterner {
+    strings: RefCell<HashSet<Box<str>>>,
+}
+
+impl StringInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the interned copy of `s`, copying it into the interner if it's
+    /// the first time it's seen.
+    pub fn intern(&self, s: &str) -> &str {
+        let mut strings = self.strings.borrow_mut();
+        let ptr: *const str = match strings.get(s) {
+            Some(interned) => &**interned,
+            None => {
+                let interned = Box::<str>::from(s);
+                let ptr: *const str = &*interned;
+                strings.insert(interned);
+                ptr
+            }
+        };
+        // SAFETY: strings are boxed, so their contents don't move when the set
+        // grows, and they're only ever dropped along with the set, which needs
+        // a mutable borrow of `self`.
+        unsafe { &*ptr }
+    }
+
+    /// The number of distinct strings interned so far.
+    pub fn len(&self) -> usize {
+        self.strings.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.borrow().is_empty()
+    }
+
+    /// Forgets all strings, keeping the allocated capacity for the set.
+    pub fn clear(&mut self) {
+        self.strings.get_mut().clear();
+    }
+}
+
+impl fmt::Debug for StringInterner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StringInterner")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// A [Deserializer] that passes strings (map keys included) from another
+/// deserializer through a [StringInterner].
+///
+/// Strings in the resulting values borrow from the interner rather than from
+/// the input, so the input can be dropped as soon as deserialization is done,
+/// and identical strings share a single allocation — where making values
+/// owned with [IntoStatic] would copy each of them.
+///
+/// ```rust
+/// use merde_core::{
+///     DynDeserializerExt, Event, EventBuffer, InterningDeserializer, MapStart, StringInterner,
+///     Value,
+/// };
+///
+/// let interner = StringInterner::new();
+/// let value: Value = {
+///     let input = EventBuffer::from_events([
+///         Event::MapStart(MapStart { size_hint: None }),
+///         Event::Str("status".into()),
+///         Event::Str("active".into()),
+///         Event::MapEnd,
+///     ]);
+///     InterningDeserializer::new(input, &interner).deserialize().unwrap()
+/// };
+/// assert_eq!(interner.len(), 2);
+/// # let _ = value;
+/// ```
+pub struct InterningDeserializer<'i, 's, D> {
+    inner: D,
+    interner: &'s StringInterner,
+    starters: VecDeque<Event<'s>>,
+    _input: PhantomData<&'i ()>,
+}
+
+impl<'i, 's, D> InterningDeserializer<'i, 's, D>
+where
+    D: Deserializer<'i>,
+{
+    /// Wraps `inner`, interning its strings into `interner`.
+    pub fn new(inner: D, interner: &'s StringInterner) -> Self {
+        Self {
+            inner,
+            interner,
+            starters: VecDeque::new(),
+            _input: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped deserializer.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+
+    fn intern(&self, ev: Event<'i>) -> Event<'s> {
+        match ev {
+            Event::Str(s) => Event::Str(CowStr::Borrowed(self.interner.intern(&s))),
+            // bytes are rare enough that they're just copied
+            ev => ev.into_static(),
+        }
+    }
+}
+
+impl<D: fmt::Debug> fmt::Debug for InterningDeserializer<'_, '_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InterningDeserializer")
+            .field("inner", &self.inner)
+            .field("interner", &self.interner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'i, 's, D> Deserializer<'s> for InterningDeserializer<'i, 's, D>
+where
+    D: Deserializer<'i>,
+{
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
+            return Ok(ev);
+        }
+        let ev = self.inner.next().await.map_err(IntoStatic::into_static)?;
+        Ok(self.intern(ev))
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.starters.push_front(ev);
+        Ok(())
+    }
+
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
+            return Ok(ev);
+        }
+        let ev = self
+            .inner
+            .next_event_with_hint(hint)
+            .await
+            .map_err(IntoStatic::into_static)?;
+        Ok(self.intern(ev))
+    }
+
+    fn number_policy(&self) -> NumberPolicy {
+        self.inner.number_policy()
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        self.inner.span()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStart, DynDeserializerExt, EventBuffer};
+
+    #[test]
+    fn test_interning() {
+        let interner = StringInterner::new();
+        let status = "a status that's too long to be stored inline";
+
+        let value: Vec<CowStr> = {
+            let input: Vec<String> = vec![status.into(), "other".into(), status.into()];
+            let mut events = EventBuffer::new();
+            events.push(Event::ArrayStart(ArrayStart { size_hint: None }));
+            for s in &input {
+                events.push(Event::Str(s.as_str().into()));
+            }
+            events.push(Event::ArrayEnd);
+            InterningDeserializer::new(events, &interner)
+                .deserialize()
+                .unwrap()
+        };
+
+        assert_eq!(value, [status, "other", status]);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(value[0].as_ptr(), value[2].as_ptr());
+    }
+}
@@ -1,11 +1,22 @@
-use std::borrow::Cow;
-use std::collections::HashMap;
-use std::collections::HashSet;
-use std::collections::VecDeque;
-use std::hash::BuildHasher;
-use std::hash::Hash;
-
-use crate::Event;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::hash::BuildHasher;
+use core::hash::Hash;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+};
+use core::ops::ControlFlow;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+use crate::{Event, HashMap, HashSet};
 
 /// Allow turning a value into an "owned" variant, which can then be
 /// returned, moved, etc.
@@ -36,6 +47,21 @@ where
     }
 }
 
+impl<B, C> IntoStatic for ControlFlow<B, C>
+where
+    B: IntoStatic,
+    C: IntoStatic,
+{
+    type Output = ControlFlow<B::Output, C::Output>;
+
+    fn into_static(self) -> Self::Output {
+        match self {
+            ControlFlow::Continue(v) => ControlFlow::Continue(v.into_static()),
+            ControlFlow::Break(v) => ControlFlow::Break(v.into_static()),
+        }
+    }
+}
+
 impl<T> IntoStatic for Cow<'_, T>
 where
     T: ToOwned + ?Sized + 'static,
@@ -58,6 +84,8 @@ impl IntoStatic for Event<'_> {
         match self {
             Event::I64(v) => Event::I64(v),
             Event::U64(v) => Event::U64(v),
+            Event::I128(v) => Event::I128(v),
+            Event::U128(v) => Event::U128(v),
             Event::F64(v) => Event::F64(v),
             Event::Str(v) => Event::Str(v.into_static()),
             Event::Bytes(v) => Event::Bytes(v.into_static()),
@@ -87,9 +115,69 @@ macro_rules! impl_into_static_passthru {
 }
 
 impl_into_static_passthru!(
-    String, u128, u64, u32, u16, u8, i128, i64, i32, i16, i8, bool, char, usize, isize, f32, f64
+    String,
+    u128,
+    u64,
+    u32,
+    u16,
+    u8,
+    i128,
+    i64,
+    i32,
+    i16,
+    i8,
+    bool,
+    char,
+    usize,
+    isize,
+    f32,
+    f64,
+    ()
+);
+
+impl_into_static_passthru!(
+    NonZeroU128,
+    NonZeroU64,
+    NonZeroU32,
+    NonZeroU16,
+    NonZeroU8,
+    NonZeroUsize,
+    NonZeroI128,
+    NonZeroI64,
+    NonZeroI32,
+    NonZeroI16,
+    NonZeroI8,
+    NonZeroIsize
 );
 
+impl_into_static_passthru!(
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6
+);
+
+#[cfg(feature = "std")]
+impl_into_static_passthru!(PathBuf);
+
+impl<T: IntoStatic> IntoStatic for Wrapping<T> {
+    type Output = Wrapping<T::Output>;
+
+    fn into_static(self) -> Self::Output {
+        Wrapping(self.0.into_static())
+    }
+}
+
+impl<T: IntoStatic> IntoStatic for Saturating<T> {
+    type Output = Saturating<T::Output>;
+
+    fn into_static(self) -> Self::Output {
+        Saturating(self.0.into_static())
+    }
+}
+
 impl<T: IntoStatic> IntoStatic for Box<T> {
     type Output = Box<T::Output>;
 
@@ -98,6 +186,55 @@ impl<T: IntoStatic> IntoStatic for Box<T> {
     }
 }
 
+impl IntoStatic for Box<str> {
+    type Output = Box<str>;
+
+    fn into_static(self) -> Self::Output {
+        self
+    }
+}
+
+impl<T: IntoStatic> IntoStatic for Box<[T]> {
+    type Output = Box<[T::Output]>;
+
+    fn into_static(self) -> Self::Output {
+        self.into_vec().into_static().into_boxed_slice()
+    }
+}
+
+// Shared values are cloned out of the pointer unless it's the only one left.
+macro_rules! impl_into_static_for_shared {
+    ($($ptr:ident),*) => {
+        $(
+            impl<T: IntoStatic + Clone> IntoStatic for $ptr<T> {
+                type Output = $ptr<T::Output>;
+
+                fn into_static(self) -> Self::Output {
+                    $ptr::new($ptr::unwrap_or_clone(self).into_static())
+                }
+            }
+
+            impl IntoStatic for $ptr<str> {
+                type Output = $ptr<str>;
+
+                fn into_static(self) -> Self::Output {
+                    self
+                }
+            }
+
+            impl<T: IntoStatic + Clone> IntoStatic for $ptr<[T]> {
+                type Output = $ptr<[T::Output]>;
+
+                fn into_static(self) -> Self::Output {
+                    self.iter().cloned().map(IntoStatic::into_static).collect()
+                }
+            }
+        )*
+    };
+}
+
+impl_into_static_for_shared!(Arc, Rc);
+
 impl<T: IntoStatic> IntoStatic for Option<T> {
     type Output = Option<T::Output>;
 
@@ -149,160 +286,41 @@ impl<T: IntoStatic> IntoStatic for VecDeque<T> {
     }
 }
 
-impl<T1: IntoStatic> IntoStatic for (T1,) {
-    type Output = (T1::Output,);
-
-    fn into_static(self) -> Self::Output {
-        (self.0.into_static(),)
-    }
-}
-
-impl<T1: IntoStatic, T2: IntoStatic> IntoStatic for (T1, T2) {
-    type Output = (T1::Output, T2::Output);
-
-    fn into_static(self) -> Self::Output {
-        (self.0.into_static(), self.1.into_static())
-    }
-}
-
-impl<T1: IntoStatic, T2: IntoStatic, T3: IntoStatic> IntoStatic for (T1, T2, T3) {
-    type Output = (T1::Output, T2::Output, T3::Output);
-
-    fn into_static(self) -> Self::Output {
-        (
-            self.0.into_static(),
-            self.1.into_static(),
-            self.2.into_static(),
-        )
-    }
-}
-
-impl<T1: IntoStatic, T2: IntoStatic, T3: IntoStatic, T4: IntoStatic> IntoStatic
-    for (T1, T2, T3, T4)
-{
-    type Output = (T1::Output, T2::Output, T3::Output, T4::Output);
-
-    fn into_static(self) -> Self::Output {
-        (
-            self.0.into_static(),
-            self.1.into_static(),
-            self.2.into_static(),
-            self.3.into_static(),
-        )
-    }
-}
-
-impl<T1: IntoStatic, T2: IntoStatic, T3: IntoStatic, T4: IntoStatic, T5: IntoStatic> IntoStatic
-    for (T1, T2, T3, T4, T5)
-{
-    type Output = (T1::Output, T2::Output, T3::Output, T4::Output, T5::Output);
-
-    fn into_static(self) -> Self::Output {
-        (
-            self.0.into_static(),
-            self.1.into_static(),
-            self.2.into_static(),
-            self.3.into_static(),
-            self.4.into_static(),
-        )
-    }
-}
-
-impl<
-        T1: IntoStatic,
-        T2: IntoStatic,
-        T3: IntoStatic,
-        T4: IntoStatic,
-        T5: IntoStatic,
-        T6: IntoStatic,
-    > IntoStatic for (T1, T2, T3, T4, T5, T6)
-{
-    type Output = (
-        T1::Output,
-        T2::Output,
-        T3::Output,
-        T4::Output,
-        T5::Output,
-        T6::Output,
-    );
-
-    fn into_static(self) -> Self::Output {
-        (
-            self.0.into_static(),
-            self.1.into_static(),
-            self.2.into_static(),
-            self.3.into_static(),
-            self.4.into_static(),
-            self.5.into_static(),
-        )
-    }
-}
-
-impl<
-        T1: IntoStatic,
-        T2: IntoStatic,
-        T3: IntoStatic,
-        T4: IntoStatic,
-        T5: IntoStatic,
-        T6: IntoStatic,
-        T7: IntoStatic,
-    > IntoStatic for (T1, T2, T3, T4, T5, T6, T7)
-{
-    type Output = (
-        T1::Output,
-        T2::Output,
-        T3::Output,
-        T4::Output,
-        T5::Output,
-        T6::Output,
-        T7::Output,
-    );
+macro_rules! impl_into_static_for_tuple {
+    ($($type_arg:ident),+) => {
+        impl<$($type_arg: IntoStatic),+> IntoStatic for ($($type_arg,)+) {
+            type Output = ($($type_arg::Output,)+);
 
-    fn into_static(self) -> Self::Output {
-        (
-            self.0.into_static(),
-            self.1.into_static(),
-            self.2.into_static(),
-            self.3.into_static(),
-            self.4.into_static(),
-            self.5.into_static(),
-            self.6.into_static(),
-        )
-    }
+            #[allow(non_snake_case)]
+            fn into_static(self) -> Self::Output {
+                let ($($type_arg,)+) = self;
+                ($($type_arg.into_static(),)+)
+            }
+        }
+    };
 }
 
-impl<
-        T1: IntoStatic,
-        T2: IntoStatic,
-        T3: IntoStatic,
-        T4: IntoStatic,
-        T5: IntoStatic,
-        T6: IntoStatic,
-        T7: IntoStatic,
-        T8: IntoStatic,
-    > IntoStatic for (T1, T2, T3, T4, T5, T6, T7, T8)
-{
-    type Output = (
-        T1::Output,
-        T2::Output,
-        T3::Output,
-        T4::Output,
-        T5::Output,
-        T6::Output,
-        T7::Output,
-        T8::Output,
-    );
+impl_into_static_for_tuple!(T1);
+impl_into_static_for_tuple!(T1, T2);
+impl_into_static_for_tuple!(T1, T2, T3);
+impl_into_static_for_tuple!(T1, T2, T3, T4);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
+impl_into_static_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
+
+impl<T: IntoStatic, const N: usize> IntoStatic for [T; N] {
+    type Output = [T::Output; N];
 
     fn into_static(self) -> Self::Output {
-        (
-            self.0.into_static(),
-            self.1.into_static(),
-            self.2.into_static(),
-            self.3.into_static(),
-            self.4.into_static(),
-            self.5.into_static(),
-            self.6.into_static(),
-            self.7.into_static(),
-        )
+        self.map(IntoStatic::into_static)
     }
 }
@@ -0,0 +1,108 @@
+//! Conversions between [Value] and `serde_json::Value`, for crates that
+//! speak the latter.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{CowStr, Map, Value};
+
+/// Numbers become `I64` if they fit, `U64` if they don't but are
+/// positive integers, and `Float` otherwise.
+impl From<serde_json::Value> for Value<'static> {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    Value::I64(i)
+                } else if let Some(u) = n.as_u64() {
+                    Value::U64(u)
+                } else {
+                    // without `arbitrary_precision`, numbers are one of the three
+                    Value::from(n.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            serde_json::Value::String(s) => Value::Str(CowStr::from(s)),
+            serde_json::Value::Array(arr) => {
+                Value::from(arr.into_iter().map(Value::from).collect::<Vec<_>>())
+            }
+            serde_json::Value::Object(obj) => Value::Map(
+                obj.into_iter()
+                    .map(|(k, v)| (CowStr::from(k), Value::from(v)))
+                    .collect::<Map<'static>>(),
+            ),
+        }
+    }
+}
+
+/// Like `merde_json` would write it, except for:
+///
+///   * floats that aren't finite (`NaN`, infinities), which become `null`,
+///     like `serde_json` does;
+///   * bytes, which become an array of numbers.
+impl From<Value<'_>> for serde_json::Value {
+    fn from(value: Value<'_>) -> Self {
+        match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::Value::Bool(b),
+            Value::I64(i) => serde_json::Value::from(i),
+            Value::U64(u) => serde_json::Value::from(u),
+            Value::Float(f) => serde_json::Value::from(f.into_inner()),
+            Value::Str(s) => serde_json::Value::String(String::from(s.as_ref())),
+            Value::Bytes(b) => serde_json::Value::from(b.as_ref()),
+            Value::Array(arr) => {
+                serde_json::Value::Array(arr.into_iter().map(serde_json::Value::from).collect())
+            }
+            Value::Map(map) => serde_json::Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| (String::from(k.as_ref()), serde_json::Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Array;
+
+    #[test]
+    fn test_serde_json_roundtrip() {
+        let json = serde_json::json!({
+            "name": "merde",
+            "stars": 42,
+            "big": u64::MAX,
+            "negative": -7,
+            "ratio": 0.5,
+            "tags": ["a", null, true],
+            "nested": {"empty": {}}
+        });
+
+        let value = Value::from(json.clone());
+        let expected = Value::from(
+            Map::new()
+                .with("name", "merde")
+                .with("stars", 42i64)
+                .with("big", u64::MAX)
+                .with("negative", -7i64)
+                .with("ratio", 0.5)
+                .with("tags", Array::new().with("a").with(()).with(true))
+                .with("nested", Map::new().with("empty", Map::new())),
+        );
+        assert_eq!(value, expected);
+        assert_eq!(serde_json::Value::from(value), json);
+    }
+
+    #[test]
+    fn test_serde_json_lossy() {
+        assert_eq!(
+            serde_json::Value::from(Value::from(f64::NAN)),
+            serde_json::Value::Null
+        );
+        assert_eq!(
+            serde_json::Value::from(Value::Bytes(crate::CowBytes::from(&[1u8, 2][..]))),
+            serde_json::json!([1, 2])
+        );
+    }
+}
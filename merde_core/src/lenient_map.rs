@@ -0,0 +1,171 @@
+use core::{
+    future::Future,
+    hash::{BuildHasher, Hash},
+    ops::{Deref, DerefMut},
+};
+
+use crate::{
+    Deserialize, DynDeserializer, DynSerializer, Event, HashMap, IntoStatic, MerdeError,
+    RandomState, Serialize, WithLifetime,
+};
+
+/// A [HashMap] that skips entries whose key can't be deserialized, instead of
+/// failing the whole document, and counts them.
+///
+/// This is meant for maps keyed by enums that are defined by someone else (a
+/// server, a newer version of a schema): when they add a variant, older
+/// clients keep working and simply ignore the entries they don't know about.
+///
+/// Only entries whose key is a scalar (a string, a number, etc.) are skipped:
+/// if a key is a map or an array and fails to deserialize, so does the map.
+/// Errors that aren't about the key's value, like syntax errors or the input
+/// ending early, aren't skipped either.
+///
+/// After deserializing, [LenientMap::skipped] tells how many entries were
+/// skipped, so they can be logged or reported.
+#[derive(Debug, Clone)]
+pub struct LenientMap<K, V, S = RandomState> {
+    map: HashMap<K, V, S>,
+    skipped: usize,
+}
+
+impl<K, V, S> Default for LenientMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self {
+            map: HashMap::default(),
+            skipped: 0,
+        }
+    }
+}
+
+impl<K, V, S> LenientMap<K, V, S> {
+    /// How many entries were skipped, because their key couldn't be deserialized.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Returns the underlying map.
+    pub fn into_inner(self) -> HashMap<K, V, S> {
+        self.map
+    }
+}
+
+impl<K, V, S> From<HashMap<K, V, S>> for LenientMap<K, V, S> {
+    fn from(map: HashMap<K, V, S>) -> Self {
+        Self { map, skipped: 0 }
+    }
+}
+
+impl<K, V, S> Deref for LenientMap<K, V, S> {
+    type Target = HashMap<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<K, V, S> DerefMut for LenientMap<K, V, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.map
+    }
+}
+
+impl<'s, K, V, S> WithLifetime<'s> for LenientMap<K, V, S>
+where
+    S: 's,
+    K: WithLifetime<'s>,
+    V: WithLifetime<'s>,
+{
+    type Lifetimed = LenientMap<K::Lifetimed, V::Lifetimed, S>;
+}
+
+impl<K, V, S> IntoStatic for LenientMap<K, V, S>
+where
+    S: BuildHasher + Default + 'static,
+    K: IntoStatic + Eq + Hash,
+    V: IntoStatic,
+    K::Output: Eq + Hash,
+{
+    type Output = LenientMap<K::Output, V::Output, S>;
+
+    fn into_static(self) -> Self::Output {
+        LenientMap {
+            map: self.map.into_static(),
+            skipped: self.skipped,
+        }
+    }
+}
+
+impl<'s, K, V, S> Deserialize<'s> for LenientMap<K, V, S>
+where
+    K: Deserialize<'s> + Eq + Hash,
+    V: Deserialize<'s>,
+    S: Default + BuildHasher + 's,
+{
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        de.next().await?.into_map_start()?;
+        let mut out = Self::default();
+
+        loop {
+            match de.next().await? {
+                Event::MapEnd => break,
+                ev => {
+                    let scalar_key = !matches!(ev, Event::MapStart(_) | Event::ArrayStart(_));
+                    de.put_back(ev)?;
+                    match K::deserialize(de).await {
+                        Ok(key) => {
+                            let value: V = V::deserialize(de).await?;
+                            out.map.insert(key, value);
+                        }
+                        // the key was a single event, and it's been consumed: skip
+                        // over the value, whatever it is
+                        Err(e) if scalar_key && is_key_error(&e) => {
+                            de.skip_value().await?;
+                            out.skipped += 1;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+// Errors about a key that was read fine, but isn't one of `K`'s values: the
+// others (syntax errors, the input ending early, etc.) are returned as-is.
+fn is_key_error(err: &MerdeError<'_>) -> bool {
+    match err {
+        MerdeError::MismatchedType { .. }
+        | MerdeError::UnknownProperty(_)
+        | MerdeError::OutOfRange
+        | MerdeError::LossyNumber { .. }
+        | MerdeError::InvalidKey { .. }
+        | MerdeError::InvalidDateTimeValue
+        | MerdeError::UnexpectedEvent { .. }
+        | MerdeError::Custom(_) => true,
+        MerdeError::Context { source, .. } | MerdeError::Field { source, .. } => {
+            is_key_error(source)
+        }
+        _ => false,
+    }
+}
+
+impl<K, V, S> Serialize for LenientMap<K, V, S>
+where
+    K: Serialize,
+    V: Serialize,
+    S: BuildHasher,
+{
+    #[allow(clippy::manual_async_fn)]
+    fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        self.map.serialize(serializer)
+    }
+}
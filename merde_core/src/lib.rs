@@ -1,3 +1,13 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// without `std`, maps (and sets) are `hashbrown`'s
+#[cfg(not(feature = "std"))]
+use hashbrown::{DefaultHashBuilder as RandomState, HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::collections::{hash_map::RandomState, HashMap, HashSet};
+
 mod cowstr;
 
 pub use cowstr::CowStr;
@@ -13,6 +23,12 @@ pub use array::Array;
 mod map;
 pub use map::Map;
 
+mod shared_map;
+pub use shared_map::SharedMap;
+
+mod lenient_map;
+pub use lenient_map::LenientMap;
+
 mod error;
 pub use error::MerdeError;
 pub use error::ValueType;
@@ -26,23 +42,62 @@ pub use with_lifetime::WithLifetime;
 mod value;
 pub use value::Value;
 
+mod canonical;
+pub use canonical::CanonicalValue;
+
+#[cfg(feature = "serde_json")]
+mod json_value;
+
+mod spanned;
+pub use spanned::Spanned;
+
+mod tri;
+pub use tri::Tri;
+
+mod instrument;
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing as __tracing;
+
 mod metastack;
-pub use metastack::{with_metastack_resume_point, MetastackExt};
+#[cfg(feature = "std")]
+pub use metastack::{metastack_spills, metastack_threshold, set_metastack_threshold};
+pub use metastack::{with_metastack_resume_point, MetastackDriver, MetastackExt, ResumePoint};
+
+mod patch;
+pub use patch::merge_patch;
+pub use patch::Patch;
+pub use patch::PatchOp;
+
+mod partial;
+pub use partial::HasPartial;
+pub use partial::Partial;
+
+mod post_process;
+pub use post_process::PostProcess;
 
 mod event;
+#[doc(hidden)]
+pub use event::infer_scalar;
 pub use event::ArrayStart;
 pub use event::Event;
 pub use event::EventType;
 pub use event::MapStart;
+#[doc(hidden)]
+pub use event::TextEvents;
 
 mod serialize;
 pub use serialize::DynSerialize;
 pub use serialize::DynSerializer;
 pub use serialize::DynSerializerExt;
+pub use serialize::InfallibleSerializer;
+pub use serialize::NonStringKeyPolicy;
 pub use serialize::Serialize;
 pub use serialize::Serializer;
+pub use serialize::Sorted;
 
 mod deserialize;
+pub use deserialize::CaseInsensitiveOpinions;
 pub use deserialize::DefaultDeserOpinions;
 pub use deserialize::DeserOpinions;
 pub use deserialize::Deserialize;
@@ -52,5 +107,79 @@ pub use deserialize::DynDeserialize;
 pub use deserialize::DynDeserializer;
 pub use deserialize::DynDeserializerExt;
 pub use deserialize::FieldSlot;
+pub use deserialize::NumberPolicy;
+pub use deserialize::TypeHint;
+
+mod deserialize_in_place;
+pub use deserialize_in_place::DeserializeInPlace;
+
+mod duplicate_keys;
+pub use duplicate_keys::DuplicateKeyFilter;
+pub use duplicate_keys::DuplicateKeyPolicy;
+
+mod transparent_enum;
+#[cfg(feature = "either")]
+pub use either::Either;
+pub use transparent_enum::Transparent;
+pub use transparent_enum::TransparentEnum;
+
+mod collect_errors;
+pub use collect_errors::CollectErrors;
+pub use collect_errors::ErrorCollector;
+#[doc(hidden)]
+pub use collect_errors::{deserialize_in_place_or_skip, deserialize_or_skip, FieldErrors};
+
+mod event_middleware;
+pub use event_middleware::EventPath;
+pub use event_middleware::FilterEvents;
+pub use event_middleware::MapEvents;
+
+mod interner;
+pub use interner::InterningDeserializer;
+pub use interner::StringInterner;
+
+mod raw_number;
+pub use raw_number::RawNumber;
+
+mod strict_numbers;
+pub use strict_numbers::StrictNumbers;
+
+mod redact;
+pub use redact::RedactingSerializer;
+pub use redact::REDACTED;
+
+mod convert;
+pub use convert::convert;
+
+mod event_buffer;
+pub use event_buffer::EventBuffer;
+
+mod event_collector;
+pub use event_collector::EventCollector;
+
+mod registry;
+pub use registry::Registered;
+pub use registry::Registry;
+pub use registry::TaggedAny;
+
+mod event_source;
+pub use event_source::EventIterDeserializer;
+pub use event_source::EventSource;
+pub use event_source::EventSourceDeserializer;
+
+mod versioned;
+pub use versioned::Migrate;
+pub use versioned::Versioned;
+
+mod size_counting;
+pub use size_counting::EncodedSize;
+pub use size_counting::SizeCountingSerializer;
 
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod base64;
 pub mod time;
+pub mod units;
+pub mod with;
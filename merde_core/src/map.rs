@@ -1,38 +1,62 @@
-use std::{
-    collections::HashMap,
+use alloc::vec::Vec;
+use core::{
     hash::{Hash, Hasher},
     ops::{Deref, DerefMut},
 };
 
-use crate::{value::Value, CowStr, IntoStatic};
+use crate::{value::Value, CowStr, HashMap, IntoStatic};
+
+#[cfg(not(feature = "preserve_order"))]
+type MapInner<'s> = HashMap<CowStr<'s>, Value<'s>>;
+
+#[cfg(feature = "preserve_order")]
+type MapInner<'s> = indexmap::IndexMap<CowStr<'s>, Value<'s>>;
 
 /// A map, dictionary, object, whatever — with string keys.
+///
+/// By default, this is backed by a [HashMap], so iteration order is unspecified.
+/// With the `preserve_order` feature, it's backed by an `IndexMap` instead, so
+/// that keys keep their insertion order (which makes deserialize-then-serialize
+/// round-trips stable).
+///
+/// That feature isn't additive: the inner map is public (and `Map` derefs to
+/// it), so its type changes for every crate in the build when any of them
+/// enables it. Libraries should stick to the methods both maps have, and leave
+/// the feature for applications to turn on.
 #[derive(PartialEq, Eq, Clone)]
 #[repr(transparent)]
-pub struct Map<'s>(pub HashMap<CowStr<'s>, Value<'s>>);
+pub struct Map<'s>(pub MapInner<'s>);
 
 impl Hash for Map<'_> {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        for (k, v) in self.iter() {
+        // equal maps don't necessarily iterate in the same order, so entries
+        // are hashed sorted by key
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+        state.write_usize(entries.len());
+        for (k, v) in entries {
             k.hash(state);
             v.hash(state);
         }
     }
 }
 
-impl std::fmt::Debug for Map<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Map<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.0.fmt(f)
     }
 }
 
 impl<'s> Map<'s> {
     pub fn new() -> Self {
-        Map(HashMap::new())
+        Map(MapInner::default())
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
-        Map(HashMap::with_capacity(capacity))
+        Map(MapInner::with_capacity_and_hasher(
+            capacity,
+            Default::default(),
+        ))
     }
 
     pub fn with(mut self, key: impl Into<CowStr<'s>>, value: impl Into<Value<'s>>) -> Self {
@@ -40,9 +64,28 @@ impl<'s> Map<'s> {
         self
     }
 
-    pub fn into_inner(self) -> HashMap<CowStr<'s>, Value<'s>> {
+    pub fn into_inner(self) -> MapInner<'s> {
         self.0
     }
+
+    /// Estimates how many bytes this map (and everything in it) holds on the
+    /// heap, see [`Value::estimated_heap_size`]. The hash table's own
+    /// bookkeeping isn't counted.
+    pub fn estimated_heap_size(&self) -> usize {
+        self.0.capacity() * core::mem::size_of::<(CowStr, Value)>()
+            + self
+                .0
+                .iter()
+                .map(|(k, v)| k.estimated_heap_size() + v.estimated_heap_size())
+                .sum::<usize>()
+    }
+
+    /// Releases unused capacity, in this map and in its values, recursively.
+    /// Keys can't be modified in place, so they're left as-is.
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+        self.0.values_mut().for_each(Value::shrink_to_fit);
+    }
 }
 
 impl IntoStatic for Map<'_> {
@@ -59,7 +102,7 @@ impl IntoStatic for Map<'_> {
 
 impl<'s> IntoIterator for Map<'s> {
     type Item = (CowStr<'s>, Value<'s>);
-    type IntoIter = std::collections::hash_map::IntoIter<CowStr<'s>, Value<'s>>;
+    type IntoIter = <MapInner<'s> as IntoIterator>::IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
@@ -74,12 +117,32 @@ impl Default for Map<'_> {
 
 impl<'s> From<HashMap<CowStr<'s>, Value<'s>>> for Map<'s> {
     fn from(v: HashMap<CowStr<'s>, Value<'s>>) -> Self {
+        #[cfg(not(feature = "preserve_order"))]
+        {
+            Map(v)
+        }
+        #[cfg(feature = "preserve_order")]
+        {
+            Map(v.into_iter().collect())
+        }
+    }
+}
+
+#[cfg(feature = "preserve_order")]
+impl<'s> From<indexmap::IndexMap<CowStr<'s>, Value<'s>>> for Map<'s> {
+    fn from(v: indexmap::IndexMap<CowStr<'s>, Value<'s>>) -> Self {
         Map(v)
     }
 }
 
+impl<'s> FromIterator<(CowStr<'s>, Value<'s>)> for Map<'s> {
+    fn from_iter<T: IntoIterator<Item = (CowStr<'s>, Value<'s>)>>(iter: T) -> Self {
+        Map(iter.into_iter().collect())
+    }
+}
+
 impl<'s> Deref for Map<'s> {
-    type Target = HashMap<CowStr<'s>, Value<'s>>;
+    type Target = MapInner<'s>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -8,27 +8,105 @@
 //!
 //! This unwinds the stack all the way to the metastack landing pad, which then polls the "next future"
 //! from an empty stack. That future is in turn free to schedule another "next future", and so on.
-use std::{
-    cell::RefCell,
+//!
+//! Without the `std` feature, there are no thread-locals and no way to tell how much stack is
+//! left, so resume points are just boxed futures, and deep recursion uses the regular stack.
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{
     future::Future,
     pin::Pin,
-    sync::LazyLock,
     task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
 };
+#[cfg(feature = "std")]
+use std::{
+    cell::{Cell, RefCell},
+    sync::LazyLock,
+};
 
 type NextFuture = Pin<Box<dyn Future<Output = ()>>>;
 
-// TODO: make this configurable? make this depend on the
-// future size? 8K is not one-size-fits-all
-const MINIMUM_VIABLE_FREE_STACK_SPACE: u64 = 8 * 1024;
+// 32K is not one-size-fits-all, hence `set_metastack_threshold`. (8K
+// wasn't enough to serialize one level of a recursive struct to JSON in
+// debug builds.)
+#[cfg(feature = "std")]
+const DEFAULT_MINIMUM_VIABLE_FREE_STACK_SPACE: u64 = 32 * 1024;
 
 const DUMMY_VTABLE: RawWakerVTable = RawWakerVTable::new(|_| todo!(), |_| {}, |_| {}, |_| {});
 const DUMMY_WAKER: &Waker =
-    unsafe { &Waker::from_raw(RawWaker::new(std::ptr::null(), &DUMMY_VTABLE)) };
+    unsafe { &Waker::from_raw(RawWaker::new(core::ptr::null(), &DUMMY_VTABLE)) };
 
+#[cfg(feature = "std")]
 std::thread_local! {
     pub static NEXT_FUTURE: RefCell<Option<NextFuture>> = const { RefCell::new(None) };
     pub static STACK_INFO: LazyLock<StackInfo> = LazyLock::new(StackInfo::get);
+    static MINIMUM_VIABLE_FREE_STACK_SPACE: Cell<u64> =
+        const { Cell::new(DEFAULT_MINIMUM_VIABLE_FREE_STACK_SPACE) };
+    static SPILLS: Cell<u64> = const { Cell::new(0) };
+    // set by `run_sync_flat`: resume points run their future right away
+    static FLAT: Cell<bool> = const { Cell::new(false) };
+}
+
+// Sets `FLAT` for as long as it's alive
+#[cfg(feature = "std")]
+struct FlatGuard {
+    previous: bool,
+}
+
+#[cfg(feature = "std")]
+impl FlatGuard {
+    fn enter() -> Self {
+        Self {
+            previous: FLAT.replace(true),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for FlatGuard {
+    fn drop(&mut self) {
+        FLAT.set(self.previous);
+    }
+}
+
+/// Sets how many bytes of stack must be left, on the current thread, for a
+/// resume point to keep going on the current stack rather than spill the rest
+/// of its work onto an emptier one. Returns the previous threshold.
+///
+/// The default, 32KiB, fits most futures. Raise it if deeply nested values
+/// still overflow the stack (e.g. with large hand-written futures, or in debug
+/// builds), lower it to spill less often on small thread stacks.
+///
+/// This is per-thread: for a thread pool, like tokio's, set it when each
+/// worker starts (see `tokio::runtime::Builder::on_thread_start`).
+#[cfg(feature = "std")]
+pub fn set_metastack_threshold(bytes: u64) -> u64 {
+    MINIMUM_VIABLE_FREE_STACK_SPACE.replace(bytes)
+}
+
+/// Returns the current thread's threshold, see [set_metastack_threshold].
+#[cfg(feature = "std")]
+pub fn metastack_threshold() -> u64 {
+    MINIMUM_VIABLE_FREE_STACK_SPACE.get()
+}
+
+/// Returns how many times, so far, a resume point ran out of stack on the
+/// current thread, and spilled the rest of its work onto an emptier stack.
+///
+/// Each spill costs an allocation and a trip back to the landing pad: compare
+/// the count before and after deserializing a typical payload to see whether
+/// [set_metastack_threshold] is worth tuning.
+#[cfg(feature = "std")]
+pub fn metastack_spills() -> u64 {
+    SPILLS.get()
+}
+
+/// Takes the future scheduled by a resume point that ran out of stack, if any.
+fn take_next_future() -> Option<NextFuture> {
+    #[cfg(feature = "std")]
+    return NEXT_FUTURE.with_borrow_mut(|next_fut| next_fut.take());
+
+    #[cfg(not(feature = "std"))]
+    None
 }
 
 pub trait MetastackExt<'s>: Sized {
@@ -36,11 +114,24 @@ pub trait MetastackExt<'s>: Sized {
 
     /// Transforms a future into a future that will return `Poll::Pending` if there
     /// is not enough stack space to execute the future.
-    fn with_metastack_resume_point(self) -> Pin<Box<dyn Future<Output = Self::Output> + 's>>;
+    fn with_metastack_resume_point(self) -> ResumePoint<'s, Self::Output>;
 
     /// Sets up a landing pad to catch `Poll::Pending` returns and run the next
     /// scheduled future on a slightly emptier stack.
     fn run_sync_with_metastack(self) -> Self::Output;
+
+    /// Like [`run_sync_with_metastack`](MetastackExt::run_sync_with_metastack),
+    /// but resume points don't check how much stack is left, and never move
+    /// the rest of the work onto an emptier stack: it all runs on the current
+    /// one. Resume points don't box their future either: they poll it to
+    /// completion as soon as they're created, which saves an allocation per
+    /// resume point (that is, per nested array, map, or `Value`).
+    ///
+    /// Only use this when the depth of the input is known to be bounded:
+    /// deeply nested values overflow the stack, which aborts the process.
+    ///
+    /// Panics if the future returns `Poll::Pending` on its own.
+    fn run_sync_flat(self) -> Self::Output;
 }
 
 impl<'s, F> MetastackExt<'s> for F
@@ -49,13 +140,13 @@ where
 {
     type Output = F::Output;
 
-    fn with_metastack_resume_point(self) -> Pin<Box<dyn Future<Output = Self::Output> + 's>> {
+    fn with_metastack_resume_point(self) -> ResumePoint<'s, Self::Output> {
         with_metastack_resume_point(self)
     }
 
     fn run_sync_with_metastack(self) -> Self::Output {
         let mut cx = Context::from_waker(DUMMY_WAKER);
-        let mut first_fut = std::pin::pin!(self);
+        let mut first_fut = core::pin::pin!(self);
 
         match first_fut.as_mut().poll(&mut cx) {
             Poll::Ready(res) => res,
@@ -64,8 +155,7 @@ where
                 let mut metastack = vec![];
 
                 'crimes: loop {
-                    let mut fut = NEXT_FUTURE
-                        .with_borrow_mut(|next_fut| next_fut.take())
+                    let mut fut = take_next_future()
                         .expect("NEXT_FUTURE must've been set before returning Poll::Pending");
                     match Pin::new(&mut fut).poll(&mut cx) {
                         Poll::Ready(_) => break 'crimes,
@@ -95,18 +185,179 @@ where
             }
         }
     }
+
+    fn run_sync_flat(self) -> Self::Output {
+        #[cfg(feature = "std")]
+        let _flat = FlatGuard::enter();
+
+        let mut cx = Context::from_waker(DUMMY_WAKER);
+        match core::pin::pin!(self).poll(&mut cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => panic!("futures run with `run_sync_flat` can't suspend themselves"),
+        }
+    }
+}
+
+/// Like [`MetastackExt::run_sync_with_metastack`], but for futures that also
+/// return `Poll::Pending` on their own, to hand control back to the caller
+/// (e.g. a serializer whose output buffer is full), and that must be resumed
+/// later, possibly deep into a recursive value.
+///
+/// Call [`MetastackDriver::resume`] until it returns `Poll::Ready`: each time
+/// it returns `Poll::Pending`, the future has suspended itself, and is kept
+/// (along with the futures it scheduled to run on an emptier stack) until the
+/// next call.
+pub struct MetastackDriver<'s, T> {
+    root: Option<Pin<Box<dyn Future<Output = T> + 's>>>,
+    // futures scheduled via `NEXT_FUTURE`: each one borrows from the one
+    // before it (or from `root`), so they must be dropped last-to-first
+    frames: Vec<NextFuture>,
+}
+
+impl<'s, T> MetastackDriver<'s, T> {
+    /// Wraps a future, without polling it yet.
+    pub fn new(fut: impl Future<Output = T> + 's) -> Self {
+        Self {
+            root: Some(Box::pin(fut)),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Polls the future until it completes, or suspends itself.
+    ///
+    /// Panics if called again after returning `Poll::Ready`.
+    pub fn resume(&mut self) -> Poll<T> {
+        let mut cx = Context::from_waker(DUMMY_WAKER);
+        loop {
+            match self.frames.last_mut() {
+                Some(frame) => {
+                    if frame.as_mut().poll(&mut cx).is_ready() {
+                        // the frame below it can now pick up the result
+                        self.frames.pop();
+                        continue;
+                    }
+                }
+                None => {
+                    let root = self
+                        .root
+                        .as_mut()
+                        .expect("MetastackDriver::resume called after completion");
+                    if let Poll::Ready(res) = root.as_mut().poll(&mut cx) {
+                        self.root = None;
+                        return Poll::Ready(res);
+                    }
+                }
+            }
+
+            match take_next_future() {
+                // out of stack: run the rest on an emptier one
+                Some(next) => self.frames.push(next),
+                // the future suspended itself
+                None => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<T> Drop for MetastackDriver<'_, T> {
+    fn drop(&mut self) {
+        while let Some(frame) = self.frames.pop() {
+            drop(frame);
+        }
+        self.root = None;
+    }
+}
+
+impl<T> core::fmt::Debug for MetastackDriver<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MetastackDriver")
+            .field("done", &self.root.is_none())
+            .field("frames", &self.frames.len())
+            .finish()
+    }
+}
+
+/// The future returned by [`with_metastack_resume_point`].
+///
+/// It's usually a boxed future, which is what gives recursive impls (like
+/// `Value`'s) futures of a finite size. Under [`MetastackExt::run_sync_flat`],
+/// it's the output of a future that already ran to completion instead.
+pub struct ResumePoint<'s, T> {
+    inner: ResumePointInner<'s, T>,
+}
+
+enum ResumePointInner<'s, T> {
+    Boxed(Pin<Box<dyn Future<Output = T> + 's>>),
+    // (`None` once taken; only under `run_sync_flat`, which needs `std`)
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    Ready(Option<T>),
+}
+
+// the output is moved out, never pinned
+impl<T> Unpin for ResumePoint<'_, T> {}
+
+impl<T> Future for ResumePoint<'_, T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        match &mut self.get_mut().inner {
+            ResumePointInner::Boxed(fut) => fut.as_mut().poll(cx),
+            ResumePointInner::Ready(res) => {
+                Poll::Ready(res.take().expect("ResumePoint polled after completion"))
+            }
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for ResumePoint<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ResumePoint")
+            .field(
+                "ready",
+                &matches!(self.inner, ResumePointInner::Ready(Some(_))),
+            )
+            .finish()
+    }
+}
+
+/// Transforms a future into a future that will return `Poll::Pending` if there
+/// is not enough stack space to execute the future.
+///
+/// This relies on the current async stack being invoked via `run_with_infinite_stack`
+#[cfg(not(feature = "std"))]
+pub fn with_metastack_resume_point<'s, F>(fut: F) -> ResumePoint<'s, F::Output>
+where
+    F: Future + 's,
+{
+    ResumePoint {
+        inner: ResumePointInner::Boxed(Box::pin(fut)),
+    }
 }
 
 /// Transforms a future into a future that will return `Poll::Pending` if there
 /// is not enough stack space to execute the future.
 ///
 /// This relies on the current async stack being invoked via `run_with_infinite_stack`
-pub fn with_metastack_resume_point<'s, F>(fut: F) -> Pin<Box<dyn Future<Output = F::Output> + 's>>
+///
+/// Under [`MetastackExt::run_sync_flat`], the future is polled to completion
+/// right away, on the current stack, rather than when the resume point is.
+#[cfg(feature = "std")]
+pub fn with_metastack_resume_point<'s, F>(fut: F) -> ResumePoint<'s, F::Output>
 where
     F: Future + 's,
 {
-    Box::pin(async move {
-        if STACK_INFO.with(|si| si.left()) >= MINIMUM_VIABLE_FREE_STACK_SPACE {
+    if FLAT.get() {
+        let mut cx = Context::from_waker(DUMMY_WAKER);
+        return match core::pin::pin!(fut).poll(&mut cx) {
+            Poll::Ready(res) => ResumePoint {
+                inner: ResumePointInner::Ready(Some(res)),
+            },
+            Poll::Pending => panic!("futures run with `run_sync_flat` can't suspend themselves"),
+        };
+    }
+
+    let fut = Box::pin(async move {
+        if STACK_INFO.with(|si| si.left()) >= MINIMUM_VIABLE_FREE_STACK_SPACE.get() {
             // no need for any special handling
             return fut.await;
         }
@@ -126,25 +377,32 @@ where
         // todo: make sure that this is actually the case by handling panics and
         // clearing thread-locals.
         let assign_fut: Pin<Box<dyn Future<Output = ()> + 'static>> =
-            unsafe { std::mem::transmute(assign_fut) };
+            unsafe { core::mem::transmute(assign_fut) };
 
         NEXT_FUTURE.with_borrow_mut(|next_future| *next_future = Some(assign_fut));
+        SPILLS.set(SPILLS.get() + 1);
         ReturnPendingOnce::new().await;
         result.unwrap()
-    })
+    });
+    ResumePoint {
+        inner: ResumePointInner::Boxed(fut),
+    }
 }
 
 /// A future that returns `Poll::Pending` once, and then `Poll::Ready`
+#[cfg(feature = "std")]
 struct ReturnPendingOnce {
     polled: bool,
 }
 
+#[cfg(feature = "std")]
 impl ReturnPendingOnce {
     fn new() -> Self {
         Self { polled: false }
     }
 }
 
+#[cfg(feature = "std")]
 impl Future for ReturnPendingOnce {
     type Output = ();
 
@@ -162,6 +420,7 @@ impl Future for ReturnPendingOnce {
 
 /// Stack information — we always assume the stack grows down (e.g. the more we
 /// allocate, the "lower" the address).
+#[cfg(feature = "std")]
 pub struct StackInfo {
     /// The highest possible address of the stack
     highest_address: u64,
@@ -170,6 +429,7 @@ pub struct StackInfo {
     size: u64,
 }
 
+#[cfg(feature = "std")]
 impl StackInfo {
     pub fn get() -> Self {
         #[cfg(target_os = "macos")]
@@ -195,7 +455,7 @@ impl StackInfo {
         #[cfg(target_os = "linux")]
         {
             unsafe {
-                use std::mem;
+                use core::mem;
                 use std::os::raw::c_void;
 
                 extern "C" {
@@ -220,7 +480,7 @@ impl StackInfo {
                 type pthread_t = usize;
 
                 let mut attr: pthread_attr_t = mem::zeroed();
-                let mut lowest_address: *mut c_void = std::ptr::null_mut();
+                let mut lowest_address: *mut c_void = core::ptr::null_mut();
                 let mut size: usize = 0;
 
                 pthread_attr_init(&mut attr);
@@ -241,8 +501,8 @@ impl StackInfo {
         #[cfg(target_os = "windows")]
         {
             unsafe {
-                use std::mem;
-                use std::ptr;
+                use core::mem;
+                use core::ptr;
 
                 #[repr(C)]
                 struct MEMORY_BASIC_INFORMATION {
@@ -297,3 +557,115 @@ impl StackInfo {
             .expect("we assume we haven't exhausted the whole stack")
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{ArrayStart, DynDeserializerExt, Event, EventBuffer, Value};
+
+    // `[[[...]]]`, `depth` arrays deep
+    fn nested_arrays(depth: usize) -> EventBuffer<'static> {
+        let start = Event::ArrayStart(ArrayStart { size_hint: None });
+        EventBuffer::from_events(
+            core::iter::repeat_n(start, depth).chain(core::iter::repeat_n(Event::ArrayEnd, depth)),
+        )
+    }
+
+    fn runtime(threshold: u64) -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_stack_size(256 * 1024)
+            .on_thread_start(move || {
+                set_metastack_threshold(threshold);
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_spills_under_tokio() {
+        const DEPTH: usize = 2_000;
+
+        let rt = runtime(DEFAULT_MINIMUM_VIABLE_FREE_STACK_SPACE);
+        let spills = rt.block_on(async {
+            tokio::spawn(async {
+                let before = metastack_spills();
+                let value: Value = nested_arrays(DEPTH).deserialize().unwrap();
+                let spills = metastack_spills() - before;
+
+                // dropping it is recursive too: take it apart one level at a time
+                let mut stack = vec![value];
+                while let Some(value) = stack.pop() {
+                    if let Value::Array(arr) = value {
+                        stack.extend(arr);
+                    }
+                }
+                spills
+            })
+            .await
+            .unwrap()
+        });
+        // a worker's stack can't fit that many levels
+        assert!(spills > 0);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let rt = runtime(u64::MAX);
+        let (threshold, spills) = rt.block_on(async {
+            tokio::spawn(async {
+                let before = metastack_spills();
+                let _: Value = nested_arrays(8).deserialize().unwrap();
+                (metastack_threshold(), metastack_spills() - before)
+            })
+            .await
+            .unwrap()
+        });
+        assert_eq!(threshold, u64::MAX);
+        // never enough stack left: every resume point spills, and there's
+        // one per element (the innermost array is empty)
+        assert_eq!(spills, 7);
+
+        let previous = set_metastack_threshold(0);
+        assert_eq!(previous, DEFAULT_MINIMUM_VIABLE_FREE_STACK_SPACE);
+        let before = metastack_spills();
+        let _: Value = nested_arrays(8).deserialize().unwrap();
+        assert_eq!(metastack_spills(), before);
+        set_metastack_threshold(previous);
+    }
+
+    #[test]
+    fn test_flat() {
+        let previous = set_metastack_threshold(u64::MAX);
+        let before = metastack_spills();
+        let value: Value = nested_arrays(8).deserialize_sync_flat().unwrap();
+        assert_eq!(metastack_spills(), before);
+        assert!(!FLAT.get());
+
+        // resume points don't box their future
+        async {
+            let point = async { 1 }.with_metastack_resume_point();
+            assert!(matches!(point.inner, ResumePointInner::Ready(Some(1))));
+            assert_eq!(point.await, 1);
+        }
+        .run_sync_flat();
+        let point = async { 1 }.with_metastack_resume_point();
+        assert!(matches!(point.inner, ResumePointInner::Boxed(_)));
+
+        // back to spilling afterwards
+        let _: Value = nested_arrays(8).deserialize().unwrap();
+        assert_eq!(metastack_spills(), before + 7);
+        set_metastack_threshold(previous);
+
+        let mut depth = 0;
+        let mut value = &value;
+        while let Value::Array(arr) = value {
+            depth += 1;
+            match arr.first() {
+                Some(v) => value = v,
+                None => break,
+            }
+        }
+        assert_eq!(depth, 8);
+    }
+}
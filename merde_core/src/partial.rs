@@ -0,0 +1,27 @@
+/// Types that have a companion "partial" type, where every field is optional:
+/// the body of a PATCH request, or one layer of a layered configuration.
+///
+/// merde's `derive!` generates both the partial type and this impl with its
+/// `Partial` mode. Partial values can then be merged, in order, into a
+/// complete one:
+///
+/// ```rust
+/// use merde_core::{HasPartial, Partial};
+///
+/// fn apply_layers<T: HasPartial>(base: &mut T, layers: Vec<Partial<T>>) {
+///     for layer in layers {
+///         base.merge(layer);
+///     }
+/// }
+/// ```
+pub trait HasPartial {
+    /// A version of `Self` where every field is an `Option`.
+    type Partial: Default;
+
+    /// Overwrites the fields of `self` that are set in `partial`, and leaves
+    /// the others alone.
+    fn merge(&mut self, partial: Self::Partial);
+}
+
+/// The partial type of `T`, see [HasPartial].
+pub type Partial<T> = <T as HasPartial>::Partial;
@@ -0,0 +1,584 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::{Deref, DerefMut};
+
+use crate::{
+    canonical::canonical_eq, CowStr, Deserialize, DynDeserializer, DynSerializer, Event, EventType,
+    IntoStatic, Map, MapStart, MerdeError, Serialize, Value, WithLifetime,
+};
+
+/// Applies a JSON Merge Patch ([RFC 7386](https://www.rfc-editor.org/rfc/rfc7386))
+/// to `target`: maps in `patch` are merged into maps in `target` recursively,
+/// `null` removes a key, and anything else replaces the target value.
+///
+/// ```rust
+/// use merde_core::{merge_patch, Map, Value};
+///
+/// let mut doc = Value::from(Map::new().with("title", "Hello").with("draft", true));
+/// merge_patch(&mut doc, &Value::from(Map::new().with("title", "Hi").with("draft", ())));
+/// assert_eq!(doc, Value::from(Map::new().with("title", "Hi")));
+/// ```
+pub fn merge_patch<'s>(target: &mut Value<'s>, patch: &Value<'s>) {
+    let Value::Map(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !matches!(target, Value::Map(_)) {
+        *target = Value::Map(Map::new());
+    }
+    let Value::Map(target) = target else {
+        unreachable!()
+    };
+    for (key, value) in patch.iter() {
+        if matches!(value, Value::Null) {
+            remove_key(target, key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+// keeps the order of the other keys with `preserve_order`
+fn remove_key<'s>(map: &mut Map<'s>, key: &str) -> Option<Value<'s>> {
+    #[cfg(feature = "preserve_order")]
+    {
+        map.shift_remove(key)
+    }
+    #[cfg(not(feature = "preserve_order"))]
+    {
+        map.remove(key)
+    }
+}
+
+/// A JSON Patch ([RFC 6902](https://www.rfc-editor.org/rfc/rfc6902)): a list
+/// of operations, applied in order, to values pointed at by JSON Pointers
+/// ([RFC 6901](https://www.rfc-editor.org/rfc/rfc6901)) like `/items/0/name`.
+///
+/// It (de)serializes as the usual array of `{"op": "add", "path": ...}` maps.
+///
+/// ```rust
+/// use merde_core::{Array, Map, Patch, PatchOp, Value};
+///
+/// let mut doc = Value::from(Map::new().with("tags", Array::new().with("a")));
+/// let patch = Patch(vec![
+///     PatchOp::Add { path: "/tags/-".into(), value: "b".into() },
+///     PatchOp::Test { path: "/tags/0".into(), value: "a".into() },
+///     PatchOp::Move { from: "/tags".into(), path: "/labels".into() },
+/// ]);
+/// patch.apply(&mut doc).unwrap();
+/// assert_eq!(doc, Value::from(Map::new().with("labels", Array::new().with("a").with("b"))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Patch<'s>(pub Vec<PatchOp<'s>>);
+
+/// One operation of a [Patch].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOp<'s> {
+    /// Adds `value` to a map (replacing the existing value, if any), or inserts
+    /// it in an array: the last path segment is then an index, or `-` to append.
+    Add { path: CowStr<'s>, value: Value<'s> },
+    /// Removes the value at `path`, which must exist.
+    Remove { path: CowStr<'s> },
+    /// Replaces the value at `path`, which must exist.
+    Replace { path: CowStr<'s>, value: Value<'s> },
+    /// Removes the value at `from`, and adds it at `path`.
+    Move { from: CowStr<'s>, path: CowStr<'s> },
+    /// Adds a copy of the value at `from` at `path`.
+    Copy { from: CowStr<'s>, path: CowStr<'s> },
+    /// Checks that the value at `path` is equal to `value`, as compared by
+    /// [CanonicalValue](crate::CanonicalValue): `1` is equal to `1.0`.
+    Test { path: CowStr<'s>, value: Value<'s> },
+}
+
+impl<'s> Deref for Patch<'s> {
+    type Target = Vec<PatchOp<'s>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Patch<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'s> Patch<'s> {
+    /// Applies all operations in order. If one of them fails, `target` is
+    /// left untouched, and the error is [MerdeError::PatchFailed].
+    pub fn apply(&self, target: &mut Value<'s>) -> Result<(), MerdeError<'static>> {
+        let mut doc = target.clone();
+        for (index, op) in self.0.iter().enumerate() {
+            op.apply(&mut doc)
+                .map_err(|message| MerdeError::PatchFailed { index, message })?;
+        }
+        *target = doc;
+        Ok(())
+    }
+}
+
+impl<'s> PatchOp<'s> {
+    fn name(&self) -> &'static str {
+        match self {
+            PatchOp::Add { .. } => "add",
+            PatchOp::Remove { .. } => "remove",
+            PatchOp::Replace { .. } => "replace",
+            PatchOp::Move { .. } => "move",
+            PatchOp::Copy { .. } => "copy",
+            PatchOp::Test { .. } => "test",
+        }
+    }
+
+    fn apply(&self, doc: &mut Value<'s>) -> Result<(), String> {
+        match self {
+            PatchOp::Add { path, value } => add(doc, path, value.clone()),
+            PatchOp::Remove { path } => remove(doc, path).map(drop),
+            PatchOp::Replace { path, value } => {
+                *pointer_mut(doc, path)? = value.clone();
+                Ok(())
+            }
+            PatchOp::Move { from, path } => {
+                if from == path {
+                    return pointer_mut(doc, path).map(drop);
+                }
+                if path.starts_with(from.as_ref()) && path[from.len()..].starts_with('/') {
+                    return Err(format!("can't move {from} into one of its children"));
+                }
+                let value = remove(doc, from)?;
+                add(doc, path, value)
+            }
+            PatchOp::Copy { from, path } => {
+                let value = pointer_mut(doc, from)?.clone();
+                add(doc, path, value)
+            }
+            PatchOp::Test { path, value } => {
+                if canonical_eq(pointer_mut(doc, path)?, value) {
+                    Ok(())
+                } else {
+                    Err(format!("value at {path} isn't the expected one"))
+                }
+            }
+        }
+    }
+}
+
+// Splits a JSON Pointer into its unescaped segments
+fn segments(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!(
+            "invalid pointer {pointer:?}, should start with '/'"
+        ));
+    };
+    Ok(rest
+        .split('/')
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn array_index(segment: &str, len: usize) -> Result<usize, String> {
+    let valid = !segment.is_empty()
+        && segment.bytes().all(|b| b.is_ascii_digit())
+        && (segment == "0" || !segment.starts_with('0'));
+    match segment.parse::<usize>() {
+        Ok(index) if valid && index < len => Ok(index),
+        _ => Err(format!(
+            "invalid index {segment:?} for an array of length {len}"
+        )),
+    }
+}
+
+fn child_mut<'v, 's>(value: &'v mut Value<'s>, segment: &str) -> Result<&'v mut Value<'s>, String> {
+    match value {
+        Value::Map(map) => map
+            .get_mut(segment)
+            .ok_or_else(|| format!("missing key {segment:?}")),
+        Value::Array(arr) => {
+            let index = array_index(segment, arr.len())?;
+            Ok(&mut arr[index])
+        }
+        other => Err(format!(
+            "can't look up {segment:?} in a {:?}",
+            other.value_type()
+        )),
+    }
+}
+
+fn pointer_mut<'v, 's>(doc: &'v mut Value<'s>, pointer: &str) -> Result<&'v mut Value<'s>, String> {
+    let mut value = doc;
+    for segment in segments(pointer)? {
+        value = child_mut(value, &segment)?;
+    }
+    Ok(value)
+}
+
+// Returns the parent of the value at `pointer`, and the last segment, or
+// `None` for the whole document.
+fn parent_mut<'v, 's>(
+    doc: &'v mut Value<'s>,
+    pointer: &str,
+) -> Result<Option<(&'v mut Value<'s>, String)>, String> {
+    let mut segments = segments(pointer)?;
+    let Some(last) = segments.pop() else {
+        return Ok(None);
+    };
+    let mut parent = doc;
+    for segment in segments {
+        parent = child_mut(parent, &segment)?;
+    }
+    Ok(Some((parent, last)))
+}
+
+fn add<'s>(doc: &mut Value<'s>, path: &str, value: Value<'s>) -> Result<(), String> {
+    let Some((parent, last)) = parent_mut(doc, path)? else {
+        *doc = value;
+        return Ok(());
+    };
+    match parent {
+        Value::Map(map) => {
+            map.insert(CowStr::from(last), value);
+        }
+        Value::Array(arr) if last == "-" => arr.push(value),
+        Value::Array(arr) => {
+            // inserting right after the last element is allowed
+            let index = array_index(&last, arr.len() + 1)?;
+            arr.insert(index, value);
+        }
+        other => return Err(format!("can't add {last:?} to a {:?}", other.value_type())),
+    }
+    Ok(())
+}
+
+fn remove<'s>(doc: &mut Value<'s>, path: &str) -> Result<Value<'s>, String> {
+    let Some((parent, last)) = parent_mut(doc, path)? else {
+        return Err("can't remove the whole document".to_string());
+    };
+    match parent {
+        Value::Map(map) => remove_key(map, &last).ok_or_else(|| format!("missing key {last:?}")),
+        Value::Array(arr) => {
+            let index = array_index(&last, arr.len())?;
+            Ok(arr.remove(index))
+        }
+        other => Err(format!(
+            "can't remove {last:?} from a {:?}",
+            other.value_type()
+        )),
+    }
+}
+
+impl<'s> Deserialize<'s> for PatchOp<'s> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        de.next().await?.into_map_start()?;
+
+        let (mut op, mut path, mut from, mut value) = (None, None, None, None);
+        loop {
+            match de.next().await? {
+                Event::MapEnd => break,
+                Event::Str(key) => match key.as_ref() {
+                    "op" => op = Some(CowStr::deserialize(de).await?),
+                    "path" => path = Some(CowStr::deserialize(de).await?),
+                    "from" => from = Some(CowStr::deserialize(de).await?),
+                    "value" => value = Some(Value::deserialize(de).await?),
+                    // other members are ignored, as per the RFC
+                    _ => de.skip_value().await?,
+                },
+                ev => {
+                    return Err(MerdeError::UnexpectedEvent {
+                        got: EventType::from(&ev),
+                        expected: &[EventType::Str, EventType::MapEnd],
+                        help: Some("(While deserializing a PatchOp)".to_string()),
+                    })
+                }
+            }
+        }
+
+        let op = op.ok_or(MerdeError::MissingProperty("op".into()))?;
+        let path = path.ok_or(MerdeError::MissingProperty("path".into()))?;
+        let from = || from.ok_or(MerdeError::MissingProperty("from".into()));
+        let value = || value.ok_or(MerdeError::MissingProperty("value".into()));
+        Ok(match op.as_ref() {
+            "add" => PatchOp::Add {
+                path,
+                value: value()?,
+            },
+            "remove" => PatchOp::Remove { path },
+            "replace" => PatchOp::Replace {
+                path,
+                value: value()?,
+            },
+            "move" => PatchOp::Move {
+                from: from()?,
+                path,
+            },
+            "copy" => PatchOp::Copy {
+                from: from()?,
+                path,
+            },
+            "test" => PatchOp::Test {
+                path,
+                value: value()?,
+            },
+            _ => return Err(MerdeError::UnknownProperty(op)),
+        })
+    }
+}
+
+impl Serialize for PatchOp<'_> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let (path, from, value) = match self {
+            PatchOp::Add { path, value }
+            | PatchOp::Replace { path, value }
+            | PatchOp::Test { path, value } => (path, None, Some(value)),
+            PatchOp::Remove { path } => (path, None, None),
+            PatchOp::Move { from, path } | PatchOp::Copy { from, path } => (path, Some(from), None),
+        };
+
+        serializer
+            .write(Event::MapStart(MapStart { size_hint: Some(3) }))
+            .await?;
+        serializer.write(Event::Str("op".into())).await?;
+        serializer.write(Event::Str(self.name().into())).await?;
+        if let Some(from) = from {
+            serializer.write(Event::Str("from".into())).await?;
+            serializer.write(Event::Str(from.clone())).await?;
+        }
+        serializer.write(Event::Str("path".into())).await?;
+        serializer.write(Event::Str(path.clone())).await?;
+        if let Some(value) = value {
+            serializer.write(Event::Str("value".into())).await?;
+            value.serialize(serializer).await?;
+        }
+        serializer.write(Event::MapEnd).await
+    }
+}
+
+impl<'s> Deserialize<'s> for Patch<'s> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Patch(Vec::deserialize(de).await?))
+    }
+}
+
+impl Serialize for Patch<'_> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        self.0.serialize(serializer).await
+    }
+}
+
+impl IntoStatic for PatchOp<'_> {
+    type Output = PatchOp<'static>;
+
+    fn into_static(self) -> Self::Output {
+        match self {
+            PatchOp::Add { path, value } => PatchOp::Add {
+                path: path.into_static(),
+                value: value.into_static(),
+            },
+            PatchOp::Remove { path } => PatchOp::Remove {
+                path: path.into_static(),
+            },
+            PatchOp::Replace { path, value } => PatchOp::Replace {
+                path: path.into_static(),
+                value: value.into_static(),
+            },
+            PatchOp::Move { from, path } => PatchOp::Move {
+                from: from.into_static(),
+                path: path.into_static(),
+            },
+            PatchOp::Copy { from, path } => PatchOp::Copy {
+                from: from.into_static(),
+                path: path.into_static(),
+            },
+            PatchOp::Test { path, value } => PatchOp::Test {
+                path: path.into_static(),
+                value: value.into_static(),
+            },
+        }
+    }
+}
+
+impl IntoStatic for Patch<'_> {
+    type Output = Patch<'static>;
+
+    fn into_static(self) -> Self::Output {
+        Patch(self.0.into_iter().map(IntoStatic::into_static).collect())
+    }
+}
+
+impl<'s> WithLifetime<'s> for PatchOp<'_> {
+    type Lifetimed = PatchOp<'s>;
+}
+
+impl<'s> WithLifetime<'s> for Patch<'_> {
+    type Lifetimed = Patch<'s>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Array, DynDeserializerExt, EventBuffer};
+
+    fn map() -> Map<'static> {
+        Map::new()
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        // from the RFC's appendix
+        let mut doc = Value::from(
+            map()
+                .with("title", "Goodbye!")
+                .with(
+                    "author",
+                    map().with("givenName", "John").with("familyName", "Doe"),
+                )
+                .with("tags", Array::new().with("example").with("sample"))
+                .with("content", "This will be unchanged"),
+        );
+        let patch = Value::from(
+            map()
+                .with("title", "Hello!")
+                .with("phoneNumber", "+01-123-456-7890")
+                .with("author", map().with("familyName", ()))
+                .with("tags", Array::new().with("example")),
+        );
+        merge_patch(&mut doc, &patch);
+        assert_eq!(
+            doc,
+            Value::from(
+                map()
+                    .with("title", "Hello!")
+                    .with("author", map().with("givenName", "John"))
+                    .with("tags", Array::new().with("example"))
+                    .with("content", "This will be unchanged")
+                    .with("phoneNumber", "+01-123-456-7890"),
+            )
+        );
+
+        let mut doc = Value::from("not a map");
+        merge_patch(&mut doc, &Value::from(map().with("a", map().with("b", ()))));
+        assert_eq!(doc, Value::from(map().with("a", map())));
+    }
+
+    #[test]
+    fn test_patch() {
+        let mut doc = Value::from(
+            map()
+                .with("foo", Array::new().with("bar").with("baz"))
+                .with("a/b", 1),
+        );
+        let patch = Patch(vec![
+            PatchOp::Add {
+                path: "/foo/1".into(),
+                value: "qux".into(),
+            },
+            PatchOp::Test {
+                path: "/a~1b".into(),
+                value: Value::from(1.0),
+            },
+            PatchOp::Copy {
+                from: "/foo/0".into(),
+                path: "/first".into(),
+            },
+            PatchOp::Remove {
+                path: "/foo/2".into(),
+            },
+            PatchOp::Replace {
+                path: "/a~1b".into(),
+                value: Value::from(2),
+            },
+            PatchOp::Move {
+                from: "/first".into(),
+                path: "/foo/-".into(),
+            },
+        ]);
+        patch.apply(&mut doc).unwrap();
+        assert_eq!(
+            doc,
+            Value::from(
+                map()
+                    .with("foo", Array::new().with("bar").with("qux").with("bar"))
+                    .with("a/b", 2),
+            )
+        );
+
+        // failed patches leave the document untouched
+        let before = doc.clone();
+        let patch = Patch(vec![
+            PatchOp::Remove {
+                path: "/foo/0".into(),
+            },
+            PatchOp::Test {
+                path: "/a~1b".into(),
+                value: Value::from(3),
+            },
+        ]);
+        assert!(matches!(
+            patch.apply(&mut doc),
+            Err(MerdeError::PatchFailed { index: 1, .. })
+        ));
+        assert_eq!(doc, before);
+
+        for path in ["/foo/3", "/foo/01", "/missing/0", "foo"] {
+            let patch = Patch(vec![PatchOp::Remove { path: path.into() }]);
+            assert!(patch.apply(&mut doc).is_err(), "{path}");
+        }
+        let patch = Patch(vec![PatchOp::Move {
+            from: "/foo".into(),
+            path: "/foo/0".into(),
+        }]);
+        assert!(patch.apply(&mut doc).is_err());
+    }
+
+    #[test]
+    fn test_patch_roundtrip() {
+        let patch = Patch(vec![
+            PatchOp::Add {
+                path: "/a".into(),
+                value: Value::Null,
+            },
+            PatchOp::Move {
+                from: "/a".into(),
+                path: "/b".into(),
+            },
+        ]);
+        let back: Patch = crate::convert(&patch).unwrap();
+        assert_eq!(back, patch);
+
+        let mut events = EventBuffer::from_events([
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("path".into()),
+            Event::Str("/x".into()),
+            Event::Str("op".into()),
+            Event::Str("remove".into()),
+            Event::Str("comment".into()),
+            Event::Str("ignored".into()),
+            Event::MapEnd,
+        ]);
+        let op: PatchOp = events.deserialize().unwrap();
+        assert_eq!(op, PatchOp::Remove { path: "/x".into() });
+
+        let res = EventBuffer::from_events([
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("op".into()),
+            Event::Str("add".into()),
+            Event::Str("path".into()),
+            Event::Str("/x".into()),
+            Event::MapEnd,
+        ])
+        .deserialize::<PatchOp>();
+        assert!(matches!(res, Err(MerdeError::MissingProperty(p)) if p == "value"));
+    }
+}
@@ -0,0 +1,217 @@
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+type Task<'a, E> = Pin<Box<dyn Future<Output = Result<(), E>> + 'a>>;
+
+/// Runs async post-processing steps (resolving references, fetching blobs,
+/// etc.) on a freshly-deserialized value, concurrently, but with at most
+/// `limit` of them in flight at any given time.
+///
+/// Each step is a future that usually borrows one field mutably: since they
+/// borrow different fields, the borrow checker is happy to let them run
+/// concurrently. `run` returns once all steps are done, or as soon as one
+/// of them fails — in which case the others are dropped (cancelled), so no
+/// step outlives the call.
+///
+/// This doesn't depend on any runtime: it's a future like any other, and can
+/// be awaited from tokio, smol, etc.
+///
+/// ```rust
+/// use merde_core::{MetastackExt, PostProcess};
+///
+/// struct Post { author: String, tags: Vec<String> }
+///
+/// async fn resolve_user(id: &mut String) -> Result<(), String> {
+///     *id = format!("user #{id}");
+///     Ok(())
+/// }
+///
+/// let mut post = Post { author: "42".into(), tags: vec!["a".into(), "b".into()] };
+/// PostProcess::new(4)
+///     .with(resolve_user(&mut post.author))
+///     .extend(post.tags.iter_mut().map(|tag| async move {
+///         tag.make_ascii_uppercase();
+///         Ok(())
+///     }))
+///     .run()
+///     .run_sync_with_metastack()
+///     .unwrap();
+/// assert_eq!(post.author, "user #42");
+/// assert_eq!(post.tags, ["A", "B"]);
+/// ```
+pub struct PostProcess<'a, E> {
+    limit: usize,
+    queued: VecDeque<Task<'a, E>>,
+}
+
+impl<E> core::fmt::Debug for PostProcess<'_, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PostProcess")
+            .field("limit", &self.limit)
+            .field("queued", &self.queued.len())
+            .finish()
+    }
+}
+
+impl<'a, E> PostProcess<'a, E> {
+    /// Creates an empty set of steps, that will be run at most `limit` at a
+    /// time (a limit of 0 is treated as 1).
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Adds a step. Steps are started in the order they were added.
+    pub fn with(mut self, step: impl Future<Output = Result<(), E>> + 'a) -> Self {
+        self.queued.push_back(Box::pin(step));
+        self
+    }
+
+    /// Adds several steps, e.g. one per element of a `Vec` field.
+    pub fn extend<F>(mut self, steps: impl IntoIterator<Item = F>) -> Self
+    where
+        F: Future<Output = Result<(), E>> + 'a,
+    {
+        for step in steps {
+            self.queued.push_back(Box::pin(step));
+        }
+        self
+    }
+
+    /// Runs all steps, returning the first error, if any.
+    pub fn run(self) -> impl Future<Output = Result<(), E>> + 'a
+    where
+        E: 'a,
+    {
+        let mut queued = self.queued;
+        let mut running: Vec<Task<'a, E>> = Vec::with_capacity(self.limit.min(queued.len()));
+        let limit = self.limit;
+
+        core::future::poll_fn(move |cx: &mut Context<'_>| loop {
+            while running.len() < limit {
+                match queued.pop_front() {
+                    Some(task) => running.push(task),
+                    None => break,
+                }
+            }
+            if running.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut finished = false;
+            let mut i = 0;
+            while i < running.len() {
+                match running[i].as_mut().poll(cx) {
+                    Poll::Ready(Ok(())) => {
+                        drop(running.swap_remove(i));
+                        finished = true;
+                    }
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                    Poll::Pending => i += 1,
+                }
+            }
+            // if nothing finished, there's no room to start anything else
+            if !finished {
+                return Poll::Pending;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        sync::Arc,
+        task::{Wake, Waker},
+    };
+
+    use super::*;
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = core::pin::pin!(fut);
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    /// Returns `Pending` a few times before completing, like I/O would
+    async fn yield_times(n: usize) {
+        let mut left = n;
+        core::future::poll_fn(|_| {
+            if left == 0 {
+                Poll::Ready(())
+            } else {
+                left -= 1;
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn test_bounded() {
+        let in_flight = Cell::new(0);
+        let max_in_flight = Cell::new(0);
+        let mut values = vec![0u32; 10];
+
+        let res: Result<(), ()> = block_on(
+            PostProcess::new(3)
+                .extend(values.iter_mut().enumerate().map(|(i, v)| {
+                    let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+                    async move {
+                        in_flight.set(in_flight.get() + 1);
+                        max_in_flight.set(max_in_flight.get().max(in_flight.get()));
+                        yield_times(i % 4).await;
+                        *v = i as u32 * 2;
+                        in_flight.set(in_flight.get() - 1);
+                        Ok(())
+                    }
+                }))
+                .run(),
+        );
+        res.unwrap();
+        assert_eq!(max_in_flight.get(), 3);
+        assert_eq!(values, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_first_error_cancels() {
+        let started = Cell::new(0);
+        let res = block_on(
+            PostProcess::new(2)
+                .extend((0..5).map(|i| {
+                    let started = &started;
+                    async move {
+                        started.set(started.get() + 1);
+                        yield_times(1).await;
+                        if i == 1 {
+                            Err(format!("step {i} failed"))
+                        } else {
+                            yield_times(10).await;
+                            Ok(())
+                        }
+                    }
+                }))
+                .run(),
+        );
+        assert_eq!(res, Err("step 1 failed".to_string()));
+        assert_eq!(started.get(), 2);
+    }
+}
@@ -0,0 +1,199 @@
+use alloc::string::ToString;
+
+use crate::{
+    deserialize_in_place::DeserializeInPlace, CowStr, Deserialize, DynDeserializer, DynSerializer,
+    Event, EventType, IntoStatic, MerdeError, NumberPolicy, Serialize, TypeHint,
+};
+
+/// A number, as it was written in the input, for when `f64` (or `i64`, etc.)
+/// isn't precise enough: amounts of money, measurements with many significant
+/// digits, etc. Parse it with whatever decimal or big number type you like.
+///
+/// Deserializers that support it (like merde_json's) hand over the number's
+/// text untouched. Others hand over a parsed number, which is then formatted
+/// back: that's still exact for integers, but not for floats.
+///
+/// Strings that look like numbers (`"12.50"`) are accepted too, since APIs
+/// often send decimals that way to avoid exactly this problem.
+///
+/// ```rust
+/// use merde_core::{DynDeserializerExt, Event, EventBuffer, RawNumber};
+///
+/// let n: RawNumber = EventBuffer::from_events([Event::Str("12.50".into())])
+///     .deserialize()
+///     .unwrap();
+/// assert_eq!(n.as_str(), "12.50");
+/// assert_eq!(n.parse::<f64>().unwrap(), 12.5);
+/// ```
+///
+/// When serialized, it's written as the closest integer or float.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawNumber<'s>(CowStr<'s>);
+
+impl<'s> RawNumber<'s> {
+    /// Returns `None` if `s` isn't a number, as JSON defines it: `-12`,
+    /// `3.14`, `6.02e23`, but not `+1`, `.5`, `0x10` or `NaN`.
+    pub fn new(s: impl Into<CowStr<'s>>) -> Option<Self> {
+        let s = s.into();
+        is_number(&s).then_some(Self(s))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> CowStr<'s> {
+        self.0
+    }
+
+    /// Parses the number, e.g. as a decimal type.
+    pub fn parse<T: core::str::FromStr>(&self) -> Result<T, T::Err> {
+        self.0.parse()
+    }
+}
+
+impl core::fmt::Display for RawNumber<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`
+fn is_number(s: &str) -> bool {
+    fn digits(s: &[u8]) -> usize {
+        s.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+
+    let mut s = s.strip_prefix('-').unwrap_or(s).as_bytes();
+    match digits(s) {
+        0 => return false,
+        n if n > 1 && s[0] == b'0' => return false,
+        n => s = &s[n..],
+    }
+    if let Some(rest) = s.strip_prefix(b".") {
+        match digits(rest) {
+            0 => return false,
+            n => s = &rest[n..],
+        }
+    }
+    if let Some(rest) = s.strip_prefix(b"e").or_else(|| s.strip_prefix(b"E")) {
+        let rest = rest
+            .strip_prefix(b"+")
+            .or_else(|| rest.strip_prefix(b"-"))
+            .unwrap_or(rest);
+        match digits(rest) {
+            0 => return false,
+            n => s = &rest[n..],
+        }
+    }
+    s.is_empty()
+}
+
+impl<'s> Deserialize<'s> for RawNumber<'s> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        let formatted = |n: &dyn ToString| Ok(Self(CowStr::from(n.to_string())));
+        match de.next_event_with_hint(TypeHint::RawNumber).await? {
+            Event::Str(s) => match Self::new(s.clone()) {
+                Some(n) => Ok(n),
+                None => Err(MerdeError::StringParsingError {
+                    format: "number",
+                    source: s,
+                    index: 0,
+                    message: "expected a number".to_string(),
+                }),
+            },
+            Event::I64(i) => formatted(&i),
+            Event::U64(u) => formatted(&u),
+            Event::I128(i) => formatted(&i),
+            Event::U128(u) => formatted(&u),
+            Event::F64(f) if f.is_finite() => {
+                if de.number_policy() == NumberPolicy::Strict {
+                    return Err(MerdeError::LossyNumber {
+                        target: "RawNumber",
+                    });
+                }
+                formatted(&f)
+            }
+            ev => Err(MerdeError::UnexpectedEvent {
+                got: EventType::from(&ev),
+                expected: &[
+                    EventType::Str,
+                    EventType::I64,
+                    EventType::U64,
+                    EventType::I128,
+                    EventType::U128,
+                    EventType::Float,
+                ],
+                help: Some("(While trying to deserialize a RawNumber)".to_string()),
+            }),
+        }
+    }
+}
+
+impl<'s> DeserializeInPlace<'s> for RawNumber<'s> {}
+
+impl Serialize for RawNumber<'_> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let ev = if let Ok(i) = self.parse() {
+            Event::I64(i)
+        } else if let Ok(u) = self.parse() {
+            Event::U64(u)
+        } else if let Ok(i) = self.parse() {
+            Event::I128(i)
+        } else if let Ok(u) = self.parse() {
+            Event::U128(u)
+        } else {
+            // always succeeds for JSON numbers
+            Event::F64(self.parse().map_err(|_| MerdeError::OutOfRange)?)
+        };
+        serializer.write(ev).await
+    }
+}
+
+impl IntoStatic for RawNumber<'_> {
+    type Output = RawNumber<'static>;
+
+    fn into_static(self) -> Self::Output {
+        RawNumber(self.0.into_static())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DynDeserializerExt, EventBuffer};
+
+    #[test]
+    fn test_is_number() {
+        for s in ["0", "-0", "12", "3.14", "-0.5", "6.02e23", "1E-7", "1e+2"] {
+            assert!(is_number(s), "{s}");
+        }
+        for s in [
+            "", "-", "+1", ".5", "1.", "01", "0x10", "NaN", "1e", "1.5.2", "1 ",
+        ] {
+            assert!(!is_number(s), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_raw_number() {
+        let n: RawNumber = EventBuffer::from_events([Event::U64(u64::MAX)])
+            .deserialize()
+            .unwrap();
+        assert_eq!(n.as_str(), "18446744073709551615");
+
+        let res =
+            EventBuffer::from_events([Event::Str("twelve".into())]).deserialize::<RawNumber>();
+        assert!(res.is_err());
+
+        let f: f64 = crate::convert(&RawNumber::new("0.1").unwrap()).unwrap();
+        assert_eq!(f, 0.1);
+        let i: i128 =
+            crate::convert(&RawNumber::new("-170141183460469231731687303715884105728").unwrap())
+                .unwrap();
+        assert_eq!(i, i128::MIN);
+    }
+}
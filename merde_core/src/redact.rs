@@ -0,0 +1,186 @@
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::future::Future;
+
+use crate::{Event, EventPath, MerdeError, Serializer};
+
+/// What [RedactingSerializer] writes instead of redacted values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A [Serializer] that replaces the values of some map keys with `"[REDACTED]"`
+/// before handing events to another serializer, e.g. to log request and response
+/// bodies without leaking credentials.
+///
+/// Patterns are lists of keys separated by dots:
+///
+///   * a single key, like `password`, matches that key in any map, at any depth
+///   * several keys, like `auth.token`, match that path from the top-level map;
+///     `*` matches any one key, so `*.token` matches the `token` key of any map
+///     that's a value of the top-level map.
+///
+/// Array indices are skipped when matching: `users.password` also matches the
+/// `password` key of the maps in a `users` array. Keys are compared exactly
+/// (and case-sensitively). Redacted values are replaced whole, even maps and
+/// arrays.
+///
+/// ```rust
+/// use merde_core::{DynSerializerExt, RedactingSerializer, Serializer};
+/// # use merde_core::{Event, MerdeError};
+/// # #[derive(Default)]
+/// # struct Printer(String);
+/// # impl Serializer for Printer {
+/// #     fn write<'fut>(
+/// #         &'fut mut self,
+/// #         ev: Event<'fut>,
+/// #     ) -> impl core::future::Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+/// #         self.0.push_str(&format!("{ev:?} "));
+/// #         async { Ok(()) }
+/// #     }
+/// # }
+/// use std::collections::HashMap;
+///
+/// let body: HashMap<&str, &str> = [("password", "hunter2")].into();
+/// let mut s = RedactingSerializer::new(Printer::default(), ["password", "*.token"]);
+/// s.serialize(&body).unwrap();
+/// assert!(!s.into_inner().0.contains("hunter2"));
+/// ```
+#[derive(Debug)]
+pub struct RedactingSerializer<S>
+where
+    S: Serializer,
+{
+    inner: S,
+    patterns: Vec<Vec<String>>,
+    path: EventPath<'static>,
+    // > 0 while swallowing a redacted map or array
+    skip_depth: usize,
+}
+
+impl<S> RedactingSerializer<S>
+where
+    S: Serializer,
+{
+    /// Wraps `inner`, redacting the values of keys that match any of `patterns`.
+    pub fn new(inner: S, patterns: impl IntoIterator<Item: AsRef<str>>) -> Self {
+        Self {
+            inner,
+            patterns: patterns
+                .into_iter()
+                .map(|p| p.as_ref().split('.').map(str::to_owned).collect())
+                .collect(),
+            path: EventPath::default(),
+            skip_depth: 0,
+        }
+    }
+
+    /// Returns the wrapped serializer.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn is_redacted(&self) -> bool {
+        let Some(key) = self.path.key() else {
+            return false;
+        };
+        self.patterns.iter().any(|pattern| match &pattern[..] {
+            [single] => single == key,
+            pattern => {
+                let mut keys = self.path.keys();
+                pattern
+                    .iter()
+                    .all(|p| keys.next().is_some_and(|k| p == "*" || p == k))
+                    && keys.next().is_none()
+            }
+        })
+    }
+}
+
+impl<S> Serializer for RedactingSerializer<S>
+where
+    S: Serializer,
+{
+    #[allow(clippy::manual_async_fn)]
+    fn write<'fut>(
+        &'fut mut self,
+        ev: Event<'fut>,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        async move {
+            if self.skip_depth > 0 {
+                match ev {
+                    Event::MapStart(_) | Event::ArrayStart(_) => self.skip_depth += 1,
+                    Event::MapEnd | Event::ArrayEnd => self.skip_depth -= 1,
+                    _ => {}
+                }
+                return Ok(());
+            }
+
+            if self.is_redacted() {
+                if matches!(ev, Event::MapStart(_) | Event::ArrayStart(_)) {
+                    self.skip_depth = 1;
+                }
+                // the whole value is replaced by one string
+                self.path.advance_owned(&Event::Null);
+                return self.inner.write(Event::Str(REDACTED.into())).await;
+            }
+
+            self.path.advance_owned(&ev);
+            self.inner.write(ev).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CowStr, DynDeserializerExt, DynSerializerExt, EventCollector, Value};
+
+    fn redact(value: &Value<'_>, patterns: &[&str]) -> Value<'static> {
+        let mut s = RedactingSerializer::new(EventCollector::new(), patterns);
+        s.serialize(value).unwrap();
+        s.into_inner().into_buffer().deserialize().unwrap()
+    }
+
+    fn get<'a>(v: &'a Value<'static>, path: &[&'a str]) -> &'a Value<'static> {
+        path.iter().fold(v, |v, k| match k.parse::<usize>() {
+            Ok(i) => &v.as_array().unwrap()[i],
+            Err(_) => v.as_map().unwrap().get(&CowStr::from(*k)).unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_redact() {
+        let input: Value = crate::Map::new()
+            .with("password", Value::from("hunter2"))
+            .with(
+                "users",
+                Value::from(vec![Value::from(
+                    crate::Map::new()
+                        .with("password", Value::from("x"))
+                        .with("name", Value::from("a")),
+                )]),
+            )
+            .with(
+                "session",
+                crate::Map::new()
+                    .with("token", Value::from(vec![Value::from(1), Value::from(2)]))
+                    .with("deep", crate::Map::new().with("token", Value::from("kept"))),
+            )
+            .with("token", Value::from("kept"))
+            .into();
+
+        let out = redact(&input, &["password", "*.token"]);
+        let redacted = Value::from(REDACTED);
+        assert_eq!(get(&out, &["password"]), &redacted);
+        assert_eq!(get(&out, &["users", "0", "password"]), &redacted);
+        assert_eq!(get(&out, &["users", "0", "name"]), &Value::from("a"));
+        assert_eq!(get(&out, &["session", "token"]), &redacted);
+        assert_eq!(
+            get(&out, &["session", "deep", "token"]),
+            &Value::from("kept")
+        );
+        assert_eq!(get(&out, &["token"]), &Value::from("kept"));
+
+        let out = redact(&input, &["session"]);
+        assert_eq!(get(&out, &["session"]), &redacted);
+        assert_eq!(get(&out, &["token"]), &Value::from("kept"));
+    }
+}
@@ -0,0 +1,264 @@
+//! Deserializing trait objects, like `Box<dyn Message>`, from values that
+//! say what they are with a tag: see [Registry] and [TaggedAny].
+
+use alloc::{boxed::Box, collections::VecDeque};
+use core::{fmt, future::Future, ops::Deref, pin::Pin};
+
+use crate::{
+    CowStr, Deserialize, DeserializeOwned, DynDeserializer, Event, EventBuffer, EventType, HashMap,
+    IntoStatic, MerdeError, WithLifetime,
+};
+
+type BoxFut<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+type Entry<T> = Box<
+    dyn for<'s, 'de> Fn(
+            &'de mut dyn DynDeserializer<'s>,
+        ) -> BoxFut<'de, Result<Box<T>, MerdeError<'s>>>
+        + Send
+        + Sync,
+>;
+
+// pins down the closure's signature, which can't be inferred
+fn entry<T: ?Sized, F>(f: F) -> F
+where
+    F: for<'s, 'de> Fn(
+        &'de mut dyn DynDeserializer<'s>,
+    ) -> BoxFut<'de, Result<Box<T>, MerdeError<'s>>>,
+{
+    f
+}
+
+/// Maps tags to the concrete types that implement a trait, for
+/// deserializing `Box<dyn Trait>` from maps like `{"type": "ping", "seq": 1}`:
+/// the tag is looked up, and the rest of the map is deserialized as the type
+/// registered for it.
+///
+/// ```rust
+/// use std::{collections::HashMap, sync::OnceLock};
+/// use merde_core::{
+///     Deserialize, DynDeserializer, DynDeserializerExt, Event, EventBuffer, MapStart,
+///     MerdeError, Registered, Registry, TaggedAny,
+/// };
+///
+/// trait Message {
+///     fn describe(&self) -> String;
+/// }
+///
+/// struct Ping(u64);
+///
+/// impl Message for Ping {
+///     fn describe(&self) -> String {
+///         format!("ping #{}", self.0)
+///     }
+/// }
+///
+/// // (this would usually be derived, for a struct with a `seq` field)
+/// impl<'s> Deserialize<'s> for Ping {
+///     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+///         let fields = HashMap::<String, u64>::deserialize(de).await?;
+///         Ok(Ping(fields["seq"]))
+///     }
+/// }
+/// # impl merde_core::IntoStatic for Ping {
+/// #     type Output = Ping;
+/// #     fn into_static(self) -> Ping { self }
+/// # }
+/// # impl merde_core::WithLifetime<'_> for Ping {
+/// #     type Lifetimed = Ping;
+/// # }
+///
+/// impl Registered for dyn Message {
+///     fn registry() -> &'static Registry<Self> {
+///         static REGISTRY: OnceLock<Registry<dyn Message>> = OnceLock::new();
+///         REGISTRY.get_or_init(|| Registry::new("type").with::<Ping>("ping", |m| Box::new(m)))
+///     }
+/// }
+///
+/// let mut de = EventBuffer::from_events([
+///     Event::MapStart(MapStart { size_hint: None }),
+///     Event::Str("seq".into()),
+///     Event::U64(3),
+///     Event::Str("type".into()),
+///     Event::Str("ping".into()),
+///     Event::MapEnd,
+/// ]);
+/// let message: TaggedAny<dyn Message> = de.deserialize().unwrap();
+/// assert_eq!(message.describe(), "ping #3");
+/// ```
+///
+/// The tag can be anywhere in the map, so the whole map is buffered first.
+pub struct Registry<T: ?Sized> {
+    tag_key: &'static str,
+    entries: HashMap<&'static str, Entry<T>>,
+}
+
+impl<T: ?Sized> fmt::Debug for Registry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field("tag_key", &self.tag_key)
+            .field("tags", &self.entries.keys())
+            .finish()
+    }
+}
+
+impl<T: ?Sized + 'static> Registry<T> {
+    /// Creates an empty registry, for maps whose tag is under `tag_key`. With
+    /// several types to register, `T` usually needs spelling out, as in
+    /// `Registry::<dyn Message>::new("type")`.
+    pub fn new(tag_key: &'static str) -> Self {
+        Self {
+            tag_key,
+            entries: HashMap::default(),
+        }
+    }
+
+    /// Registers `M` for `tag`: `into` turns it into a `Box<T>` (it's usually
+    /// just `|m| Box::new(m)`). A type registered again for the same tag
+    /// replaces the previous one.
+    pub fn register<M>(&mut self, tag: &'static str, into: fn(M) -> Box<T>) -> &mut Self
+    where
+        M: DeserializeOwned + 'static,
+    {
+        let entry = entry(move |de| {
+            Box::pin(async move {
+                let value = M::deserialize_owned(de).await?;
+                Ok(into(value))
+            })
+        });
+        self.entries.insert(tag, Box::new(entry));
+        self
+    }
+
+    /// Like [register](Self::register), but by value.
+    pub fn with<M>(mut self, tag: &'static str, into: fn(M) -> Box<T>) -> Self
+    where
+        M: DeserializeOwned + 'static,
+    {
+        self.register(tag, into);
+        self
+    }
+
+    /// Deserializes a map with a tag, as the type registered for that tag. The
+    /// tag is taken out of the map before it's handed over to that type.
+    pub async fn deserialize<'s>(
+        &self,
+        de: &mut dyn DynDeserializer<'s>,
+    ) -> Result<Box<T>, MerdeError<'s>> {
+        let mut events = EventBuffer::record(de).await?.into_events();
+        if !matches!(events.front(), Some(Event::MapStart(_))) {
+            let got = events
+                .front()
+                .map(EventType::from)
+                .unwrap_or(EventType::Null);
+            return Err(MerdeError::UnexpectedEvent {
+                got,
+                expected: &[EventType::MapStart],
+                help: Some(alloc::format!(
+                    "(A tagged value is a map, with its tag under {:?})",
+                    self.tag_key
+                )),
+            });
+        }
+
+        let tag = take_tag(&mut events, self.tag_key)?;
+        let Some(entry) = self.entries.get(tag.as_ref()) else {
+            return Err(MerdeError::UnknownProperty(tag));
+        };
+        entry(&mut EventBuffer::from_events(events)).await
+    }
+}
+
+// Removes the `tag_key` entry from the recorded map, and returns its value
+fn take_tag<'s>(
+    events: &mut VecDeque<Event<'s>>,
+    tag_key: &'static str,
+) -> Result<CowStr<'s>, MerdeError<'s>> {
+    // skips the `MapStart`
+    let mut index = 1;
+    while let Some(Event::Str(key)) = events.get(index) {
+        if key.as_ref() == tag_key {
+            return match events.remove(index + 1) {
+                Some(Event::Str(tag)) => {
+                    events.remove(index);
+                    Ok(tag)
+                }
+                Some(ev) => Err(MerdeError::UnexpectedEvent {
+                    got: EventType::from(&ev),
+                    expected: &[EventType::Str],
+                    help: Some(alloc::format!("(For the {tag_key:?} tag)")),
+                }),
+                None => Err(MerdeError::eof()),
+            };
+        }
+
+        // skips the key, then the value
+        index += 1;
+        let mut depth = 0usize;
+        loop {
+            match events.get(index) {
+                Some(Event::MapStart(_) | Event::ArrayStart(_)) => depth += 1,
+                Some(Event::MapEnd | Event::ArrayEnd) => depth -= 1,
+                Some(_) => {}
+                None => return Err(MerdeError::eof()),
+            }
+            index += 1;
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+    Err(MerdeError::MissingProperty(tag_key.into()))
+}
+
+/// Types (usually trait objects, like `dyn Message`) that have a [Registry],
+/// so that [TaggedAny] can deserialize them.
+pub trait Registered: 'static {
+    /// The registry for this type. It's usually built once, in a `static`.
+    fn registry() -> &'static Registry<Self>;
+}
+
+/// A `Box<T>` that deserializes as whatever type is registered in `T`'s
+/// [Registry] for the tag it finds: see [Registry] for an example.
+pub struct TaggedAny<T: ?Sized>(pub Box<T>);
+
+impl<T: ?Sized> TaggedAny<T> {
+    pub fn into_inner(self) -> Box<T> {
+        self.0
+    }
+}
+
+impl<T: ?Sized> Deref for TaggedAny<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for TaggedAny<T>
+where
+    Box<T>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TaggedAny").field(&self.0).finish()
+    }
+}
+
+impl<'s, T: ?Sized + Registered> Deserialize<'s> for TaggedAny<T> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Self(T::registry().deserialize(de).await?))
+    }
+}
+
+impl<T: ?Sized + 'static> IntoStatic for TaggedAny<T> {
+    type Output = Self;
+
+    fn into_static(self) -> Self::Output {
+        self
+    }
+}
+
+impl<T: ?Sized + 'static> WithLifetime<'_> for TaggedAny<T> {
+    type Lifetimed = Self;
+}
@@ -1,8 +1,28 @@
-use std::{borrow::Cow, collections::HashMap, future::Future, hash::BuildHasher, pin::Pin};
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    rc::Rc,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    future::Future,
+    hash::BuildHasher,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+    },
+    ops::ControlFlow,
+    pin::Pin,
+};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
 
 use crate::{
-    metastack::MetastackExt, Array, ArrayStart, CowBytes, CowStr, Event, Map, MapStart, MerdeError,
-    Value,
+    instrument, metastack::MetastackExt, Array, ArrayStart, CowBytes, CowStr, Event, HashMap, Map,
+    MapStart, MerdeError, Value,
 };
 
 pub trait Serializer {
@@ -10,6 +30,21 @@ pub trait Serializer {
         &'fut mut self,
         ev: Event<'fut>,
     ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut;
+
+    /// Writes `raw`, a complete value that's already encoded in `format` (like
+    /// `"json"`), as-is. Returns `false` if this serializer doesn't write
+    /// `format`, or can't write raw values where it's at: nothing was written
+    /// then, and the caller should write events instead.
+    ///
+    /// By default, this always returns `false`.
+    fn write_raw<'fut>(
+        &'fut mut self,
+        format: &'static str,
+        raw: &'fut str,
+    ) -> impl Future<Output = Result<bool, MerdeError<'static>>> + 'fut {
+        let _ = (format, raw);
+        core::future::ready(Ok(false))
+    }
 }
 
 type BoxFut<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
@@ -19,6 +54,12 @@ pub trait DynSerializer {
         &'fut mut self,
         ev: Event<'fut>,
     ) -> BoxFut<'fut, Result<(), MerdeError<'static>>>;
+
+    fn write_raw<'fut>(
+        &'fut mut self,
+        format: &'static str,
+        raw: &'fut str,
+    ) -> BoxFut<'fut, Result<bool, MerdeError<'static>>>;
 }
 
 impl dyn DynSerializer {
@@ -35,6 +76,14 @@ where
     ) -> BoxFut<'fut, Result<(), MerdeError<'static>>> {
         Box::pin(Serializer::write(self, ev))
     }
+
+    fn write_raw<'fut>(
+        &'fut mut self,
+        format: &'static str,
+        raw: &'fut str,
+    ) -> BoxFut<'fut, Result<bool, MerdeError<'static>>> {
+        Box::pin(Serializer::write_raw(self, format, raw))
+    }
 }
 
 pub trait DynSerializerExt {
@@ -47,19 +96,66 @@ where
     S: DynSerializer,
 {
     fn serialize<T: Serialize>(&mut self, t: &T) -> Result<(), MerdeError<'static>> {
-        T::serialize(t, self).run_sync_with_metastack()
+        instrument::serialize(self, core::any::type_name::<T>(), |ser| {
+            T::serialize(t, ser).run_sync_with_metastack()
+        })
     }
 
     fn dyn_serialize(&mut self, t: &dyn DynSerialize) -> Result<(), MerdeError<'static>> {
-        DynSerialize::dyn_serialize(t, self).run_sync_with_metastack()
+        instrument::serialize(self, "dyn DynSerialize", |ser| {
+            DynSerialize::dyn_serialize(t, ser).run_sync_with_metastack()
+        })
+    }
+}
+
+/// A [`Serializer`] that writes to memory (e.g. a JSON serializer targeting a `Vec<u8>`),
+/// and whose `write` therefore never fails on its own.
+///
+/// Implementing this lets callers skip the `?` dance where errors can't be
+/// propagated anyway, like in `fmt::Display` impls.
+pub trait InfallibleSerializer: Serializer + Sized {
+    /// Like [`DynSerializerExt::serialize`], but panics instead of returning an error.
+    ///
+    /// The serializer itself can't fail on I/O, and merde's own `Serialize` impls only
+    /// ever forward serializer errors, so this only panics if a hand-written `Serialize`
+    /// impl makes up an error of its own, or if the value can't be represented in the
+    /// target format (e.g. a non-string map key in JSON, see [`NonStringKeyPolicy`]).
+    fn serialize_infallible<T: Serialize>(&mut self, t: &T) {
+        if let Err(e) = DynSerializerExt::serialize(self, t) {
+            panic!("in-memory serializer returned an error, which should be impossible: {e}")
+        }
     }
 }
 
+/// What a serializer for a format that only allows string map keys (like JSON)
+/// should do when a [`Serialize`] impl writes a map key that isn't a string,
+/// e.g. when serializing a `HashMap<i32, T>`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NonStringKeyPolicy {
+    /// Return an error (this is the default).
+    #[default]
+    Error,
+
+    /// Write booleans and numbers as strings (`1` becomes `"1"`), and return
+    /// an error for anything else (null, bytes, arrays, maps).
+    Stringify,
+
+    /// Silently leave out the entry (both key and value).
+    Skip,
+}
+
 pub trait Serialize {
     fn serialize<'fut>(
         &'fut self,
         serializer: &'fut mut dyn DynSerializer,
     ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut;
+
+    /// Returns true if this value, as a struct field, should be left out
+    /// entirely: `derive!` skips those fields. Only [Tri::Absent](crate::Tri)
+    /// is, by default.
+    fn is_absent(&self) -> bool {
+        false
+    }
 }
 
 /// Dynamic dispatch version of [`Serialize`].
@@ -114,6 +210,138 @@ impl_trivial_serialize! {
     bool,
 }
 
+impl Serialize for () {
+    async fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        serializer.write(Event::Null).await
+    }
+}
+
+// 128-bit integers are written as 64-bit ones when they fit, so that only
+// values that actually need them reach serializers as `Event::I128` / `Event::U128`.
+impl Serialize for i128 {
+    async fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let ev = if let Ok(i) = i64::try_from(*self) {
+            Event::I64(i)
+        } else if let Ok(u) = u64::try_from(*self) {
+            Event::U64(u)
+        } else {
+            Event::I128(*self)
+        };
+        serializer.write(ev).await
+    }
+}
+
+impl Serialize for u128 {
+    async fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let ev = match u64::try_from(*self) {
+            Ok(u) => Event::U64(u),
+            Err(_) => Event::U128(*self),
+        };
+        serializer.write(ev).await
+    }
+}
+
+macro_rules! impl_nonzero_serialize {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                async fn serialize<'fut>(
+                    &'fut self,
+                    serializer: &'fut mut dyn DynSerializer,
+                ) -> Result<(), MerdeError<'static>> {
+                    serializer.write(Event::from(self.get())).await
+                }
+            }
+        )*
+    };
+}
+
+impl_nonzero_serialize! {
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize,
+    NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize,
+}
+
+impl<T: Serialize> Serialize for Wrapping<T> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        self.0.serialize(serializer).await
+    }
+}
+
+impl<T: Serialize> Serialize for Saturating<T> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        self.0.serialize(serializer).await
+    }
+}
+
+impl Serialize for char {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let mut buf = [0u8; 4];
+        serializer
+            .write(Event::Str(CowStr::Borrowed(self.encode_utf8(&mut buf))))
+            .await
+    }
+}
+
+macro_rules! impl_display_serialize {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                async fn serialize<'se>(
+                    &'se self,
+                    serializer: &'se mut dyn DynSerializer,
+                ) -> Result<(), MerdeError<'static>> {
+                    let s = self.to_string();
+                    serializer.write(Event::Str(CowStr::Borrowed(&s))).await
+                }
+            }
+        )*
+    };
+}
+
+impl_display_serialize! {
+    IpAddr, Ipv4Addr, Ipv6Addr,
+    SocketAddr, SocketAddrV4, SocketAddrV6,
+}
+
+#[cfg(feature = "std")]
+impl Serialize for Path {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let s = core::str::from_utf8(self.as_os_str().as_encoded_bytes())?;
+        serializer.write(Event::Str(CowStr::Borrowed(s))).await
+    }
+}
+
+#[cfg(feature = "std")]
+impl Serialize for PathBuf {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        self.as_path().serialize(serializer).await
+    }
+}
+
 impl Serialize for String {
     async fn serialize<'se>(
         &'se self,
@@ -154,6 +382,16 @@ impl<'s> Serialize for Cow<'s, str> {
     }
 }
 
+impl<T: Serialize + Clone> Serialize for Cow<'_, [T]> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let slice: &[T] = self;
+        slice.serialize(serializer).await
+    }
+}
+
 impl<'s> Serialize for CowBytes<'s> {
     async fn serialize<'se>(
         &'se self,
@@ -177,37 +415,186 @@ impl<T: Serialize> Serialize for Option<T> {
     }
 }
 
-impl<T: Serialize> Serialize for &[T] {
+/// Externally tagged, like enums produced by `merde::derive!`: `{"Ok": value}`
+/// or `{"Err": error}`. For other keys, wrap it in a [With](crate::with::With)
+/// with a [TaggedResult](crate::with::TaggedResult) adapter; for an entirely
+/// different encoding, declare your own enum and derive its impls instead.
+impl<T: Serialize, E: Serialize> Serialize for Result<T, E> {
     async fn serialize<'se>(
         &'se self,
         serializer: &'se mut dyn DynSerializer,
     ) -> Result<(), MerdeError<'static>> {
         serializer
-            .write(Event::ArrayStart(ArrayStart {
-                size_hint: Some(self.len()),
-            }))
+            .write(Event::MapStart(MapStart { size_hint: Some(1) }))
             .await?;
-        for item in *self {
-            item.serialize(serializer).await?;
+        match self {
+            Ok(value) => {
+                serializer.write(Event::Str(CowStr::Borrowed("Ok"))).await?;
+                value.serialize(serializer).await?;
+            }
+            Err(error) => {
+                serializer
+                    .write(Event::Str(CowStr::Borrowed("Err")))
+                    .await?;
+                error.serialize(serializer).await?;
+            }
         }
-        serializer.write(Event::ArrayEnd).await
+        serializer.write(Event::MapEnd).await
     }
 }
 
-impl<T: Serialize> Serialize for Vec<T> {
+impl<T: Serialize> Serialize for Box<T> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        // see `Vec<T>`
+        (**self)
+            .serialize(serializer)
+            .with_metastack_resume_point()
+            .await
+    }
+}
+
+impl Serialize for Box<str> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        serializer.write(Event::Str(CowStr::Borrowed(self))).await
+    }
+}
+
+impl<T: Serialize> Serialize for Box<[T]> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let slice: &[T] = self;
+        slice.serialize(serializer).await
+    }
+}
+
+macro_rules! impl_serialize_for_shared {
+    ($($ptr:ident),*) => {
+        $(
+            impl<T: Serialize> Serialize for $ptr<T> {
+                async fn serialize<'se>(
+                    &'se self,
+                    serializer: &'se mut dyn DynSerializer,
+                ) -> Result<(), MerdeError<'static>> {
+                    // see `Vec<T>`
+                    (**self)
+                        .serialize(serializer)
+                        .with_metastack_resume_point()
+                        .await
+                }
+            }
+
+            impl Serialize for $ptr<str> {
+                async fn serialize<'se>(
+                    &'se self,
+                    serializer: &'se mut dyn DynSerializer,
+                ) -> Result<(), MerdeError<'static>> {
+                    serializer.write(Event::Str(CowStr::Borrowed(self))).await
+                }
+            }
+
+            impl<T: Serialize> Serialize for $ptr<[T]> {
+                async fn serialize<'se>(
+                    &'se self,
+                    serializer: &'se mut dyn DynSerializer,
+                ) -> Result<(), MerdeError<'static>> {
+                    let slice: &[T] = self;
+                    slice.serialize(serializer).await
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_for_shared!(Arc, Rc);
+
+/// Externally tagged, like [`Result`]: `{"Continue": value}` or `{"Break": value}`.
+impl<B: Serialize, C: Serialize> Serialize for ControlFlow<B, C> {
     async fn serialize<'se>(
         &'se self,
         serializer: &'se mut dyn DynSerializer,
     ) -> Result<(), MerdeError<'static>> {
         serializer
-            .write(Event::ArrayStart(ArrayStart {
-                size_hint: Some(self.len()),
-            }))
+            .write(Event::MapStart(MapStart { size_hint: Some(1) }))
             .await?;
-        for item in self {
-            item.serialize(serializer).await?;
+        match self {
+            ControlFlow::Continue(value) => {
+                serializer
+                    .write(Event::Str(CowStr::Borrowed("Continue")))
+                    .await?;
+                value.serialize(serializer).await?;
+            }
+            ControlFlow::Break(value) => {
+                serializer
+                    .write(Event::Str(CowStr::Borrowed("Break")))
+                    .await?;
+                value.serialize(serializer).await?;
+            }
         }
-        serializer.write(Event::ArrayEnd).await
+        serializer.write(Event::MapEnd).await
+    }
+}
+
+impl<T: Serialize, const N: usize> Serialize for [T; N] {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        self.as_slice().serialize(serializer).await
+    }
+}
+
+impl<T: Serialize> Serialize for &[T] {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        // see `Vec<T>`
+        async move {
+            serializer
+                .write(Event::ArrayStart(ArrayStart {
+                    size_hint: Some(self.len()),
+                }))
+                .await?;
+            for item in *self {
+                item.serialize(serializer).await?;
+            }
+            serializer.write(Event::ArrayEnd).await
+        }
+        .with_metastack_resume_point()
+        .await
+    }
+}
+
+impl<T: Serialize> Serialize for Vec<T> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        // Boxing the whole thing (which `with_metastack_resume_point` does)
+        // is what allows recursive types like `struct Tree { children: Vec<Tree> }`,
+        // whose futures would otherwise be infinitely sized: it also lets the
+        // metastack move on to a fresh stack when serializing very deep trees.
+        async move {
+            serializer
+                .write(Event::ArrayStart(ArrayStart {
+                    size_hint: Some(self.len()),
+                }))
+                .await?;
+            for item in self {
+                item.serialize(serializer).await?;
+            }
+            serializer.write(Event::ArrayEnd).await
+        }
+        .with_metastack_resume_point()
+        .await
     }
 }
 
@@ -216,12 +603,55 @@ impl<K: Serialize, V: Serialize, BH: BuildHasher> Serialize for HashMap<K, V, BH
         &'fut self,
         serializer: &'fut mut dyn DynSerializer,
     ) -> Result<(), MerdeError<'static>> {
+        // see `Vec<T>`
+        async move {
+            serializer
+                .write(Event::MapStart(MapStart {
+                    size_hint: Some(self.len()),
+                }))
+                .await?;
+            for (key, value) in self {
+                key.serialize(serializer).await?;
+                value.serialize(serializer).await?;
+            }
+            serializer.write(Event::MapEnd).await
+        }
+        .with_metastack_resume_point()
+        .await
+    }
+}
+
+/// Serializes a map with its entries in key order, e.g. for snapshot tests or
+/// canonical output, without having to switch the map itself to a `BTreeMap`.
+///
+/// Only the outer map is sorted: maps nested in its values are serialized
+/// in their own iteration order.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use merde_core::{Serialize, Sorted};
+///
+/// fn serialize_stats(stats: &HashMap<String, u64>) -> impl Serialize + '_ {
+///     Sorted(stats)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Sorted<T>(pub T);
+
+impl<K: Serialize + Ord, V: Serialize, BH: BuildHasher> Serialize for Sorted<&HashMap<K, V, BH>> {
+    async fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
         serializer
             .write(Event::MapStart(MapStart {
-                size_hint: Some(self.len()),
+                size_hint: Some(entries.len()),
             }))
             .await?;
-        for (key, value) in self {
+        for (key, value) in entries {
             key.serialize(serializer).await?;
             value.serialize(serializer).await?;
         }
@@ -229,6 +659,27 @@ impl<K: Serialize, V: Serialize, BH: BuildHasher> Serialize for HashMap<K, V, BH
     }
 }
 
+impl Serialize for Sorted<&Map<'_>> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        serializer
+            .write(Event::MapStart(MapStart {
+                size_hint: Some(entries.len()),
+            }))
+            .await?;
+        for (key, value) in entries {
+            serializer.write(Event::Str(CowStr::Borrowed(key))).await?;
+            value.serialize(serializer).await?;
+        }
+        serializer.write(Event::MapEnd).await
+    }
+}
+
 impl Serialize for Map<'_> {
     async fn serialize<'se>(
         &'se self,
@@ -335,6 +786,10 @@ impl_serialize_for_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8);
 impl_serialize_for_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9);
 impl_serialize_for_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
 impl_serialize_for_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_serialize_for_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_serialize_for_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_serialize_for_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_serialize_for_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
 
 #[cfg(test)]
 mod tests;
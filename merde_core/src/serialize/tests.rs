@@ -1,26 +1,63 @@
-use crate::{DynSerializerExt, Event, IntoStatic, Map, MerdeError, Serializer, Value};
+use std::collections::HashMap;
+
+use crate::{DynSerializerExt, Event, IntoStatic, Map, MerdeError, Serializer, Sorted, Value};
 use insta::assert_debug_snapshot;
 
-#[test]
-fn test_serialize() {
-    #[derive(Default, Debug)]
-    struct ToySerializer {
-        events: Vec<Event<'static>>,
-    }
+#[derive(Default, Debug)]
+struct ToySerializer {
+    events: Vec<Event<'static>>,
+}
 
-    impl Serializer for ToySerializer {
-        fn write<'fut>(
-            &'fut mut self,
-            ev: Event<'fut>,
-        ) -> impl std::future::Future<Output = Result<(), MerdeError<'static>>> + 'fut {
-            self.events.push(ev.into_static());
-            async { Ok(()) }
-        }
+impl Serializer for ToySerializer {
+    fn write<'fut>(
+        &'fut mut self,
+        ev: Event<'fut>,
+    ) -> impl std::future::Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        self.events.push(ev.into_static());
+        async { Ok(()) }
     }
+}
 
+#[test]
+fn test_serialize() {
     let mut s = ToySerializer::default();
     let value: Value = Map::new().with("foo", Value::from(42)).into();
     s.serialize(&value).unwrap();
 
     assert_debug_snapshot!(s.events);
 }
+
+#[test]
+fn test_sorted() {
+    let map: HashMap<u64, &str> = (0..32).map(|i| ((i * 7) % 32, "v")).collect();
+    let mut s = ToySerializer::default();
+    s.serialize(&Sorted(&map)).unwrap();
+    let keys: Vec<u64> = s
+        .events
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter_map(|ev| match ev {
+            Event::U64(k) => Some(*k),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(keys, (0..32).collect::<Vec<_>>());
+
+    let map = Map::new()
+        .with("b", Value::from(2))
+        .with("c", Value::from(3))
+        .with("a", Value::from(1));
+    let mut s = ToySerializer::default();
+    s.serialize(&Sorted(&map)).unwrap();
+    let keys: Vec<&str> = s
+        .events
+        .iter()
+        .filter_map(|ev| match ev {
+            Event::Str(k) => Some(k.as_ref()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(keys, ["a", "b", "c"]);
+    assert!(matches!(s.events[2], Event::I64(1)));
+}
@@ -0,0 +1,166 @@
+use alloc::sync::Arc;
+use core::{
+    future::Future,
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(not(feature = "preserve_order"))]
+use crate::HashMap;
+use crate::{
+    CowStr, Deserialize, DynDeserializer, DynSerializer, Event, HashSet, IntoStatic, Map, MapStart,
+    MerdeError, Serialize, Value, WithLifetime,
+};
+
+#[cfg(not(feature = "preserve_order"))]
+type SharedMapInner = HashMap<Arc<str>, Value<'static>>;
+
+#[cfg(feature = "preserve_order")]
+type SharedMapInner = indexmap::IndexMap<Arc<str>, Value<'static>>;
+
+/// Like [Map], but with `Arc<str>` keys, and values that don't borrow from anything.
+///
+/// This is meant for long-lived caches of parsed documents: unlike a [Map], it never
+/// keeps the source buffer alive (through `CowStr::Borrowed`), and thousands of
+/// documents with the same shape can share the same key allocations, by converting
+/// them with [SharedMap::from_map_interned].
+///
+/// Only top-level keys are shared: maps nested in values are regular [Map]s.
+#[derive(PartialEq, Eq, Clone, Default)]
+#[repr(transparent)]
+pub struct SharedMap(pub SharedMapInner);
+
+impl core::fmt::Debug for SharedMap {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl SharedMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converts a [Map] in a single pass, reusing keys from `interner` where
+    /// possible, and adding the others to it.
+    ///
+    /// ```rust
+    /// use std::{collections::HashSet, sync::Arc};
+    /// use merde_core::{Map, SharedMap, Value};
+    ///
+    /// let mut interner = HashSet::new();
+    /// let a = SharedMap::from_map_interned(Map::new().with("id", Value::I64(1)), &mut interner);
+    /// let b = SharedMap::from_map_interned(Map::new().with("id", Value::I64(2)), &mut interner);
+    ///
+    /// let (key_a, _) = a.get_key_value("id").unwrap();
+    /// let (key_b, _) = b.get_key_value("id").unwrap();
+    /// assert!(Arc::ptr_eq(key_a, key_b));
+    /// ```
+    pub fn from_map_interned(map: Map<'_>, interner: &mut HashSet<Arc<str>>) -> Self {
+        SharedMap(
+            map.into_iter()
+                .map(|(k, v)| {
+                    let key = match interner.get(&*k) {
+                        Some(key) => key.clone(),
+                        None => {
+                            let key: Arc<str> = Arc::from(&*k);
+                            interner.insert(key.clone());
+                            key
+                        }
+                    };
+                    (key, v.into_static())
+                })
+                .collect(),
+        )
+    }
+
+    pub fn into_inner(self) -> SharedMapInner {
+        self.0
+    }
+}
+
+impl From<Map<'_>> for SharedMap {
+    fn from(map: Map<'_>) -> Self {
+        SharedMap(
+            map.into_iter()
+                .map(|(k, v)| (Arc::from(&*k), v.into_static()))
+                .collect(),
+        )
+    }
+}
+
+impl From<SharedMap> for Map<'static> {
+    fn from(map: SharedMap) -> Self {
+        map.into_iter()
+            .map(|(k, v)| (CowStr::from(&*k).into_static(), v))
+            .collect()
+    }
+}
+
+impl FromIterator<(Arc<str>, Value<'static>)> for SharedMap {
+    fn from_iter<T: IntoIterator<Item = (Arc<str>, Value<'static>)>>(iter: T) -> Self {
+        SharedMap(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for SharedMap {
+    type Item = (Arc<str>, Value<'static>);
+    type IntoIter = <SharedMapInner as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Deref for SharedMap {
+    type Target = SharedMapInner;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SharedMap {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl WithLifetime<'_> for SharedMap {
+    type Lifetimed = Self;
+}
+
+impl IntoStatic for SharedMap {
+    type Output = SharedMap;
+
+    #[inline(always)]
+    fn into_static(self) -> Self::Output {
+        self
+    }
+}
+
+impl<'s> Deserialize<'s> for SharedMap {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Map::deserialize(de).await?.into())
+    }
+}
+
+impl Serialize for SharedMap {
+    #[allow(clippy::manual_async_fn)]
+    fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        async move {
+            serializer
+                .write(Event::MapStart(MapStart {
+                    size_hint: Some(self.len()),
+                }))
+                .await?;
+            for (key, value) in self.iter() {
+                serializer.write(Event::Str(CowStr::Borrowed(key))).await?;
+                value.serialize(serializer).await?;
+            }
+            serializer.write(Event::MapEnd).await
+        }
+    }
+}
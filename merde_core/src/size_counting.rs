@@ -0,0 +1,94 @@
+//! Computing how many bytes a value will take once encoded, without encoding it,
+//! e.g. to write the length prefix of a frame before the frame itself.
+
+use core::future::Future;
+
+use crate::{Event, MerdeError, Serializer};
+
+/// Knows how many bytes a given format encodes each event to.
+///
+/// Implementations are stateful, since the size of an event can depend on what
+/// came before it (separators, container headers that include an element count, etc.).
+/// Bytes that can only be accounted for once a container is complete (like a
+/// msgpack map header) may be reported on its `MapEnd` / `ArrayEnd` event.
+pub trait EncodedSize {
+    /// Returns the number of bytes `ev` adds to the output.
+    fn event_size(&mut self, ev: &Event<'_>) -> Result<usize, MerdeError<'static>>;
+}
+
+/// A [`Serializer`] that doesn't write anything, but adds up the encoded size of
+/// every event it's given, according to a format's [`EncodedSize`] implementation.
+///
+/// Format crates that support it provide the [`EncodedSize`] implementation, and
+/// usually a helper function that wraps this serializer.
+#[derive(Debug, Default)]
+pub struct SizeCountingSerializer<E>
+where
+    E: EncodedSize,
+{
+    encoder: E,
+    size: usize,
+}
+
+impl<E> SizeCountingSerializer<E>
+where
+    E: EncodedSize,
+{
+    /// Creates a serializer counting bytes as `encoder` would emit them.
+    pub fn new(encoder: E) -> Self {
+        Self { encoder, size: 0 }
+    }
+
+    /// Returns the number of bytes counted so far.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the underlying encoder.
+    pub fn into_inner(self) -> E {
+        self.encoder
+    }
+}
+
+impl<E> Serializer for SizeCountingSerializer<E>
+where
+    E: EncodedSize,
+{
+    #[allow(clippy::manual_async_fn)]
+    fn write<'fut>(
+        &'fut mut self,
+        ev: Event<'fut>,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        async move {
+            self.size += self.encoder.event_size(&ev)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynSerializerExt;
+
+    // counts one byte per scalar, two per container
+    #[derive(Default)]
+    struct Toy;
+
+    impl EncodedSize for Toy {
+        fn event_size(&mut self, ev: &Event<'_>) -> Result<usize, MerdeError<'static>> {
+            Ok(match ev {
+                Event::MapStart(_) | Event::ArrayStart(_) => 2,
+                Event::MapEnd | Event::ArrayEnd => 0,
+                _ => 1,
+            })
+        }
+    }
+
+    #[test]
+    fn test_size_counting_serializer() {
+        let mut ser = SizeCountingSerializer::new(Toy);
+        ser.serialize(&vec![vec![1, 2], vec![3]]).unwrap();
+        assert_eq!(ser.size(), 2 + 2 + 2 + 2 + 1);
+    }
+}
@@ -0,0 +1,96 @@
+use core::ops::{Deref, DerefMut, Range};
+
+use crate::{
+    Deserialize, DynDeserializer, DynSerializer, IntoStatic, MerdeError, Serialize, WithLifetime,
+};
+
+/// A value, and where it was in the input it was deserialized from, for
+/// pointing at it in error messages and the like.
+///
+/// The span comes from [Deserializer::span](crate::Deserializer::span): it's
+/// `None` for formats that don't keep track of positions. It's left out when
+/// serializing, where `Spanned<T>` is just a `T`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Spanned<T> {
+    /// The value itself
+    pub value: T,
+    /// Where the value was, in bytes: from the first character of its text to
+    /// the last, delimiters included.
+    pub span: Option<Range<usize>>,
+}
+
+impl<T> Spanned<T> {
+    /// A value that doesn't come from anywhere in particular.
+    pub fn new(value: T) -> Self {
+        Self { value, span: None }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> From<T> for Spanned<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Spanned<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<'s, T: Deserialize<'s>> Deserialize<'s> for Spanned<T> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        // peek at the first event for where the value starts
+        let ev = de.next().await?;
+        let start = de.span();
+        de.put_back(ev)?;
+
+        let value = T::deserialize(de).await?;
+        // the value ends with the last event it consumed (unless it put it
+        // back, in which case the deserializer may not know anymore)
+        let span = match (start, de.span()) {
+            (Some(start), Some(end)) if end.end >= start.start => Some(start.start..end.end),
+            (start, _) => start,
+        };
+        Ok(Self { value, span })
+    }
+}
+
+impl<T: Serialize> Serialize for Spanned<T> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        self.value.serialize(serializer).await
+    }
+}
+
+impl<T: IntoStatic> IntoStatic for Spanned<T> {
+    type Output = Spanned<T::Output>;
+
+    fn into_static(self) -> Self::Output {
+        Spanned {
+            value: self.value.into_static(),
+            span: self.span,
+        }
+    }
+}
+
+impl<'s, T> WithLifetime<'s> for Spanned<T>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = Spanned<T::Lifetimed>;
+}
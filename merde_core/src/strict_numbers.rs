@@ -0,0 +1,136 @@
+use core::{fmt, future::Future, ops::Range};
+
+use crate::{
+    CowStr, Deserializer, ErrorCollector, Event, EventType, MerdeError, NumberPolicy, TypeHint,
+};
+
+/// A [Deserializer] that passes another deserializer's events through as-is,
+/// but asks for [NumberPolicy::Strict]: numbers that don't fit the requested
+/// type exactly are rejected instead of being rounded, truncated or saturated.
+///
+/// ```rust
+/// use merde_core::{DynDeserializerExt, Event, EventBuffer, MerdeError, StrictNumbers};
+///
+/// let lenient: u32 = EventBuffer::from_events([Event::F64(1.5)]).deserialize().unwrap();
+/// assert_eq!(lenient, 1);
+///
+/// let strict = StrictNumbers::new(EventBuffer::from_events([Event::F64(1.5)])).deserialize::<u32>();
+/// assert!(matches!(strict, Err(MerdeError::LossyNumber { .. })));
+/// ```
+pub struct StrictNumbers<D> {
+    inner: D,
+}
+
+impl<D> StrictNumbers<D> {
+    /// Wraps `inner`, making its numbers strict.
+    pub fn new(inner: D) -> Self {
+        Self { inner }
+    }
+
+    /// Returns the wrapped deserializer.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<D: fmt::Debug> fmt::Debug for StrictNumbers<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StrictNumbers")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<'s, D> Deserializer<'s> for StrictNumbers<D>
+where
+    D: Deserializer<'s>,
+{
+    fn next(&mut self) -> impl Future<Output = Result<Event<'s>, MerdeError<'s>>> + '_ {
+        self.inner.next()
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.inner.put_back(ev)
+    }
+
+    fn next_event_with_hint(
+        &mut self,
+        hint: TypeHint,
+    ) -> impl Future<Output = Result<Event<'s>, MerdeError<'s>>> + '_ {
+        self.inner.next_event_with_hint(hint)
+    }
+
+    fn peek(&mut self) -> impl Future<Output = Result<EventType, MerdeError<'s>>> + '_ {
+        self.inner.peek()
+    }
+
+    fn number_policy(&self) -> NumberPolicy {
+        NumberPolicy::Strict
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        self.inner.span()
+    }
+
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>> {
+        self.inner.error_collector()
+    }
+
+    fn skip_value(&mut self) -> impl Future<Output = Result<(), MerdeError<'s>>> + '_ {
+        self.inner.skip_value()
+    }
+
+    fn next_raw(
+        &mut self,
+        format: &'static str,
+    ) -> impl Future<Output = Result<Option<CowStr<'s>>, MerdeError<'s>>> + '_ {
+        self.inner.next_raw(format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Deserialize, DynDeserializerExt, EventBuffer};
+
+    fn strict<'s, T: Deserialize<'s>>(ev: Event<'s>) -> Result<T, MerdeError<'s>> {
+        StrictNumbers::new(EventBuffer::from_events([ev])).deserialize()
+    }
+
+    fn is_lossy<T>(res: Result<T, MerdeError<'_>>) -> bool {
+        matches!(res, Err(MerdeError::LossyNumber { .. }))
+    }
+
+    #[test]
+    fn test_strict_numbers() {
+        assert_eq!(strict::<u32>(Event::F64(3.0)).unwrap(), 3);
+        assert_eq!(strict::<i64>(Event::F64(-3.0)).unwrap(), -3);
+        assert!(is_lossy(strict::<u32>(Event::F64(1.5))));
+        assert!(is_lossy(strict::<u64>(Event::F64(-1.0))));
+        assert!(is_lossy(strict::<i64>(Event::F64(f64::NAN))));
+        assert!(is_lossy(strict::<i64>(Event::F64(f64::INFINITY))));
+        assert!(is_lossy(strict::<i64>(Event::F64(9223372036854775808.0))));
+        assert!(is_lossy(strict::<u64>(Event::F64(18446744073709551616.0))));
+
+        // still out of range, not lossy
+        assert!(matches!(
+            strict::<u8>(Event::F64(300.0)),
+            Err(MerdeError::OutOfRange)
+        ));
+
+        assert_eq!(
+            strict::<f64>(Event::I64(1 << 53)).unwrap(),
+            9007199254740992.0
+        );
+        assert!(is_lossy(strict::<f64>(Event::I64((1 << 53) + 1))));
+        assert!(is_lossy(strict::<f64>(Event::U64(u64::MAX))));
+        assert_eq!(strict::<f32>(Event::F64(0.1)).unwrap(), 0.1);
+        assert!(is_lossy(strict::<f32>(Event::F64(1e300))));
+
+        // lenient by default
+        let lenient: u64 = EventBuffer::from_events([Event::F64(-1.5)])
+            .deserialize()
+            .unwrap();
+        assert_eq!(lenient, 0);
+    }
+}
@@ -1,13 +1,21 @@
 //! Provides [Rfc3339], a wrapper around [time::OffsetDateTime] that implements
 //! [Serialize] and [Deserialize] when the right
 //! cargo features are enabled.
+//!
+//! Also provides [MonotonicMillis], for shipping [std::time::Instant] deltas around.
 
-use std::{
+use core::{
     fmt,
+    future::Future,
     ops::{Deref, DerefMut},
 };
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
-use crate::WithLifetime;
+use crate::{
+    Deserialize, DynDeserializer, DynSerializer, Event, IntoStatic, MerdeError, Serialize,
+    WithLifetime,
+};
 
 /// A wrapper around date-time types that implements `Serialize` and `Deserialize`
 /// when the right cargo features are enabled.
@@ -60,12 +68,93 @@ where
     }
 }
 
+/// A number of milliseconds elapsed since some epoch [`Instant`], serialized
+/// as a signed integer.
+///
+/// [`Instant`] is opaque and only meaningful within the process that created it,
+/// so it deliberately doesn't implement `Serialize` or `Deserialize`. This is _not_
+/// a wall-clock time either: the sender picks an epoch (e.g. process start), and
+/// whoever reads the payload back has to know which epoch that was.
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use merde_core::time::MonotonicMillis;
+///
+/// let epoch = Instant::now();
+/// let later = epoch + Duration::from_millis(1500);
+/// let mm = MonotonicMillis::since(epoch, later);
+/// assert_eq!(mm.0, 1500);
+/// assert_eq!(mm.to_instant(epoch), Some(later));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct MonotonicMillis(pub i64);
+
+#[cfg(feature = "std")]
+impl MonotonicMillis {
+    /// Measures `instant` relative to `epoch`. Instants that come before the epoch
+    /// give negative values. Saturates at `i64::MIN` / `i64::MAX`.
+    pub fn since(epoch: Instant, instant: Instant) -> Self {
+        match instant.checked_duration_since(epoch) {
+            Some(d) => MonotonicMillis(d.as_millis().try_into().unwrap_or(i64::MAX)),
+            None => {
+                let d = epoch.duration_since(instant);
+                MonotonicMillis(
+                    i64::try_from(d.as_millis())
+                        .map(|ms| -ms)
+                        .unwrap_or(i64::MIN),
+                )
+            }
+        }
+    }
+
+    /// Resolves this back to an [`Instant`], given the same epoch that was used
+    /// to produce it. Returns `None` if the result can't be represented.
+    pub fn to_instant(self, epoch: Instant) -> Option<Instant> {
+        let d = Duration::from_millis(self.0.unsigned_abs());
+        if self.0 >= 0 {
+            epoch.checked_add(d)
+        } else {
+            epoch.checked_sub(d)
+        }
+    }
+}
+
+impl WithLifetime<'_> for MonotonicMillis {
+    type Lifetimed = Self;
+}
+
+impl IntoStatic for MonotonicMillis {
+    type Output = MonotonicMillis;
+
+    #[inline(always)]
+    fn into_static(self) -> Self::Output {
+        self
+    }
+}
+
+impl<'s> Deserialize<'s> for MonotonicMillis {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(MonotonicMillis(i64::deserialize(de).await?))
+    }
+}
+
+impl Serialize for MonotonicMillis {
+    #[allow(clippy::manual_async_fn)]
+    fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        async move { serializer.write(Event::I64(self.0)).await }
+    }
+}
+
 #[cfg(feature = "time")]
 pub use time::OffsetDateTime;
 
 #[cfg(feature = "time")]
 mod time_impls {
-    use std::future::Future;
+    use core::future::Future;
 
     use super::*;
 
@@ -162,6 +251,26 @@ mod tests {
         assert_eq!(original, deserialized);
     }
 
+    #[test]
+    fn test_monotonic_millis_roundtrip() {
+        use crate::DynDeserializerExt;
+
+        let epoch = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        for instant in [
+            epoch,
+            epoch + std::time::Duration::from_millis(1234),
+            epoch - std::time::Duration::from_millis(5678),
+        ] {
+            let original = MonotonicMillis::since(epoch, instant);
+            let mut journal: Journal = Default::default();
+            journal.serialize(&original).unwrap();
+            let deserialized = journal.deserialize_owned::<MonotonicMillis>().unwrap();
+
+            assert_eq!(original, deserialized);
+            assert_eq!(deserialized.to_instant(epoch), Some(instant));
+        }
+    }
+
     // #[test]
     // fn test_rfc3339_offset_date_time_serialization() {
     //     let dt = Rfc3339(datetime!(2023-05-15 14:30:00 UTC));
@@ -0,0 +1,245 @@
+use crate::{
+    Deserialize, DynDeserializer, DynSerializer, EventBuffer, MerdeError, Serialize, WithLifetime,
+};
+
+/// A two-variant enum whose variants each wrap a value, and that is
+/// (de)serialized "untagged": as the value it holds, without saying which
+/// variant it is.
+///
+/// When deserializing, the `Left` variant is tried first, then the `Right`
+/// one, against the same input. That's what API payloads that are "either a
+/// string or an object" need.
+///
+/// Implementing this trait doesn't implement [`Serialize`] or [`Deserialize`]
+/// directly: wrap the enum in [`Transparent`], or delegate to it.
+///
+/// ```rust
+/// use merde_core::{Deserialize, DynDeserializer, MerdeError, Transparent, TransparentEnum};
+///
+/// enum Author {
+///     Name(String),
+///     Id(u64),
+/// }
+///
+/// impl TransparentEnum for Author {
+///     type Left = String;
+///     type Right = u64;
+///
+///     fn from_left(left: String) -> Self {
+///         Author::Name(left)
+///     }
+///
+///     fn from_right(right: u64) -> Self {
+///         Author::Id(right)
+///     }
+///
+///     fn as_result(&self) -> Result<&String, &u64> {
+///         match self {
+///             Author::Name(name) => Ok(name),
+///             Author::Id(id) => Err(id),
+///         }
+///     }
+/// }
+///
+/// impl<'s> Deserialize<'s> for Author {
+///     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+///         Ok(Transparent::<Author>::deserialize(de).await?.0)
+///     }
+/// }
+/// ```
+pub trait TransparentEnum: Sized {
+    /// The value held by the first variant, which is tried first
+    type Left;
+
+    /// The value held by the second variant
+    type Right;
+
+    /// Builds the first variant
+    fn from_left(left: Self::Left) -> Self;
+
+    /// Builds the second variant
+    fn from_right(right: Self::Right) -> Self;
+
+    /// Returns the value held by the first variant as `Ok`, and the value held
+    /// by the second variant as `Err`.
+    fn as_result(&self) -> Result<&Self::Left, &Self::Right>;
+}
+
+/// Implements [`Serialize`] and [`Deserialize`] for any [`TransparentEnum`].
+///
+/// If neither variant can be deserialized from the input, this returns the
+/// error from the `Right` variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Transparent<T>(pub T);
+
+impl<'s, T> Deserialize<'s> for Transparent<T>
+where
+    T: TransparentEnum + 's,
+    T::Left: Deserialize<'s>,
+    T::Right: Deserialize<'s>,
+{
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        let recorded = EventBuffer::record(de).await?;
+
+        let mut left = recorded.clone();
+        if let Ok(value) = T::Left::deserialize(&mut left).await {
+            return Ok(Transparent(T::from_left(value)));
+        }
+
+        let mut right = recorded;
+        let value = T::Right::deserialize(&mut right).await?;
+        Ok(Transparent(T::from_right(value)))
+    }
+}
+
+impl<T> Serialize for Transparent<T>
+where
+    T: TransparentEnum,
+    T::Left: Serialize,
+    T::Right: Serialize,
+{
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        match self.0.as_result() {
+            Ok(left) => left.serialize(serializer).await,
+            Err(right) => right.serialize(serializer).await,
+        }
+    }
+}
+
+impl<'s, T> WithLifetime<'s> for Transparent<T>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = Transparent<T::Lifetimed>;
+}
+
+#[cfg(feature = "either")]
+mod either_impls {
+    use either::Either;
+
+    use super::*;
+    use crate::IntoStatic;
+
+    impl<L, R> TransparentEnum for Either<L, R> {
+        type Left = L;
+        type Right = R;
+
+        fn from_left(left: L) -> Self {
+            Either::Left(left)
+        }
+
+        fn from_right(right: R) -> Self {
+            Either::Right(right)
+        }
+
+        fn as_result(&self) -> Result<&L, &R> {
+            match self {
+                Either::Left(left) => Ok(left),
+                Either::Right(right) => Err(right),
+            }
+        }
+    }
+
+    /// Untagged, see [`TransparentEnum`]: `L` is tried first, then `R`. (Note that the
+    /// `either` crate's own serde impls are externally tagged, unless you opt into
+    /// `either::serde_untagged`.)
+    impl<'s, L, R> Deserialize<'s> for Either<L, R>
+    where
+        L: Deserialize<'s>,
+        R: Deserialize<'s>,
+    {
+        async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+            Ok(Transparent::<Self>::deserialize(de).await?.0)
+        }
+    }
+
+    impl<L: Serialize, R: Serialize> Serialize for Either<L, R> {
+        async fn serialize<'se>(
+            &'se self,
+            serializer: &'se mut dyn DynSerializer,
+        ) -> Result<(), MerdeError<'static>> {
+            match self {
+                Either::Left(left) => left.serialize(serializer).await,
+                Either::Right(right) => right.serialize(serializer).await,
+            }
+        }
+    }
+
+    impl<'s, L, R> WithLifetime<'s> for Either<L, R>
+    where
+        L: WithLifetime<'s>,
+        R: WithLifetime<'s>,
+    {
+        type Lifetimed = Either<L::Lifetimed, R::Lifetimed>;
+    }
+
+    impl<L: IntoStatic, R: IntoStatic> IntoStatic for Either<L, R> {
+        type Output = Either<L::Output, R::Output>;
+
+        fn into_static(self) -> Self::Output {
+            self.map_either(IntoStatic::into_static, IntoStatic::into_static)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStart, CowStr, DynDeserializerExt, Event, Map, MapStart, Value};
+
+    enum StrOrMap<'s> {
+        Str(CowStr<'s>),
+        Map(Map<'s>),
+    }
+
+    impl<'s> TransparentEnum for StrOrMap<'s> {
+        type Left = CowStr<'s>;
+        type Right = Map<'s>;
+
+        fn from_left(left: CowStr<'s>) -> Self {
+            StrOrMap::Str(left)
+        }
+
+        fn from_right(right: Map<'s>) -> Self {
+            StrOrMap::Map(right)
+        }
+
+        fn as_result(&self) -> Result<&CowStr<'s>, &Map<'s>> {
+            match self {
+                StrOrMap::Str(s) => Ok(s),
+                StrOrMap::Map(m) => Err(m),
+            }
+        }
+    }
+
+    #[test]
+    fn test_transparent_try_in_order() {
+        let mut de = EventBuffer::from_events([Event::Str("hi".into())]);
+        let v: Transparent<StrOrMap> = de.deserialize().unwrap();
+        assert!(matches!(v.0, StrOrMap::Str(s) if s == "hi"));
+
+        let mut de = EventBuffer::from_events([
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("k".into()),
+            Event::I64(1),
+            Event::MapEnd,
+            // only one value is consumed
+            Event::Null,
+        ]);
+        let v: Transparent<StrOrMap> = de.deserialize().unwrap();
+        assert!(
+            matches!(&v.0, StrOrMap::Map(m) if m.get(&CowStr::from("k")) == Some(&Value::I64(1)))
+        );
+        assert_eq!(de.len(), 1);
+
+        let mut de = EventBuffer::from_events([
+            Event::ArrayStart(ArrayStart { size_hint: None }),
+            Event::ArrayEnd,
+        ]);
+        assert!(de.deserialize::<Transparent<StrOrMap>>().is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    CowStr, Deserialize, DynDeserializer, DynSerializer, Event, IntoStatic, MerdeError, Serialize,
+    WithLifetime,
+};
+
+/// A field that can be left out, set to `null`, or set to a value: like
+/// `Option<Option<T>>`, except that those three cases survive a round-trip.
+/// That's what the body of a PATCH request needs, where a missing field is
+/// left alone and a `null` one is cleared.
+///
+/// In a struct that `derive!`s `Deserialize`, a missing field is [Tri::Absent].
+/// One that `derive!`s `Serialize` leaves [Tri::Absent] fields out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tri<T> {
+    /// The field wasn't there
+    #[default]
+    Absent,
+    /// The field was `null`
+    Null,
+    /// The field had a value
+    Value(T),
+}
+
+impl<T> Tri<T> {
+    /// Returns true if the field wasn't there.
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Tri::Absent)
+    }
+
+    /// Returns true if the field was `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Tri::Null)
+    }
+
+    /// Returns the value, if there's one.
+    pub fn as_value(&self) -> Option<&T> {
+        match self {
+            Tri::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// `None` if the field wasn't there, `Some(None)` if it was `null`.
+    pub fn into_option(self) -> Option<Option<T>> {
+        match self {
+            Tri::Absent => None,
+            Tri::Null => Some(None),
+            Tri::Value(value) => Some(Some(value)),
+        }
+    }
+}
+
+impl<T> From<Option<Option<T>>> for Tri<T> {
+    fn from(value: Option<Option<T>>) -> Self {
+        match value {
+            None => Tri::Absent,
+            Some(None) => Tri::Null,
+            Some(Some(value)) => Tri::Value(value),
+        }
+    }
+}
+
+impl<'s, T: Deserialize<'s>> Deserialize<'s> for Tri<T> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        match de.next().await? {
+            Event::Null => Ok(Tri::Null),
+            ev => {
+                de.put_back(ev)?;
+                Ok(Tri::Value(T::deserialize(de).await?))
+            }
+        }
+    }
+
+    fn from_option(value: Option<Self>, _field_name: CowStr<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(value.unwrap_or(Tri::Absent))
+    }
+}
+
+/// [Tri::Absent] is written as `null` when it can't be left out, like in
+/// an array.
+impl<T: Serialize> Serialize for Tri<T> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        match self {
+            Tri::Absent | Tri::Null => serializer.write(Event::Null).await,
+            Tri::Value(value) => value.serialize(serializer).await,
+        }
+    }
+
+    fn is_absent(&self) -> bool {
+        Tri::is_absent(self)
+    }
+}
+
+impl<T: IntoStatic> IntoStatic for Tri<T> {
+    type Output = Tri<T::Output>;
+
+    fn into_static(self) -> Self::Output {
+        match self {
+            Tri::Absent => Tri::Absent,
+            Tri::Null => Tri::Null,
+            Tri::Value(value) => Tri::Value(value.into_static()),
+        }
+    }
+}
+
+impl<'s, T> WithLifetime<'s> for Tri<T>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = Tri<T::Lifetimed>;
+}
@@ -0,0 +1,443 @@
+//! Provides [ByteSize] and [HumanDuration], wrappers that deserialize from the
+//! human-friendly strings people like to put in config files (`"10MiB"`, `"1h30m"`),
+//! and serialize back to them.
+
+use alloc::{borrow::ToOwned, string::ToString};
+use core::{fmt, future::Future, str::FromStr, time::Duration};
+
+use crate::{
+    CowStr, Deserialize, DynDeserializer, DynSerializer, Event, EventType, IntoStatic, MerdeError,
+    Serialize, WithLifetime,
+};
+
+/// A number of bytes, written as an integer, or as a string with an optional unit.
+///
+/// Units are case-insensitive, may be separated from the number by spaces, and the
+/// number may have a fractional part (the result is truncated to a whole number of bytes):
+///
+///   * `B` (or no unit): bytes
+///   * `k`/`kB`, `M`/`MB`, `G`/`GB`, `T`/`TB`, `P`/`PB`: powers of 1000
+///   * `Ki`/`KiB`, `Mi`/`MiB`, `Gi`/`GiB`, `Ti`/`TiB`, `Pi`/`PiB`: powers of 1024
+///
+/// It serializes as a string, using the largest unit that represents the size exactly:
+///
+/// ```rust
+/// use merde_core::units::ByteSize;
+///
+/// let size: ByteSize = "1.5 KiB".parse().unwrap();
+/// assert_eq!(size.0, 1536);
+/// assert_eq!(size.to_string(), "1536B");
+/// assert_eq!(ByteSize(10 * 1024 * 1024).to_string(), "10MiB");
+/// assert_eq!("512k".parse::<ByteSize>().unwrap().0, 512_000);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct ByteSize(pub u64);
+
+// by decreasing size, which is what `Display` relies on
+const BYTE_UNITS: &[(&str, u64)] = &[
+    ("PiB", 1 << 50),
+    ("PB", 1_000_000_000_000_000),
+    ("TiB", 1 << 40),
+    ("TB", 1_000_000_000_000),
+    ("GiB", 1 << 30),
+    ("GB", 1_000_000_000),
+    ("MiB", 1 << 20),
+    ("MB", 1_000_000),
+    ("KiB", 1 << 10),
+    ("kB", 1_000),
+];
+
+fn byte_unit(unit: &str) -> Option<u64> {
+    if unit.is_empty() || unit.eq_ignore_ascii_case("b") {
+        return Some(1);
+    }
+    BYTE_UNITS.iter().find_map(|&(name, mult)| {
+        // "KiB" is also accepted as "Ki", and "kB" as "k"
+        let short = &name[..name.len() - 1];
+        (unit.eq_ignore_ascii_case(name) || unit.eq_ignore_ascii_case(short)).then_some(mult)
+    })
+}
+
+impl FromStr for ByteSize {
+    type Err = MerdeError<'static>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_byte_size(s).map_err(|(index, message)| {
+            parsing_error("byte size", s.to_owned().into(), index, message)
+        })
+    }
+}
+
+fn parse_byte_size(s: &str) -> Result<ByteSize, (usize, &'static str)> {
+    let (number, number_start, rest) = split_number(s, 0)?;
+    let unit = rest.trim();
+    let unit_start = s.len() - rest.trim_start().len();
+    let mult = byte_unit(unit).ok_or((unit_start, "unknown unit"))?;
+    let bytes = scale_decimal(number, mult as u128).ok_or((number_start, "invalid number"))?;
+    u64::try_from(bytes)
+        .map(ByteSize)
+        .map_err(|_| (number_start, "byte size too large"))
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 != 0 {
+            for &(name, mult) in BYTE_UNITS {
+                if self.0 % mult == 0 {
+                    return write!(f, "{}{name}", self.0 / mult);
+                }
+            }
+        }
+        write!(f, "{}B", self.0)
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        ByteSize(bytes)
+    }
+}
+
+impl WithLifetime<'_> for ByteSize {
+    type Lifetimed = Self;
+}
+
+impl IntoStatic for ByteSize {
+    type Output = ByteSize;
+
+    #[inline(always)]
+    fn into_static(self) -> Self::Output {
+        self
+    }
+}
+
+impl<'s> Deserialize<'s> for ByteSize {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        match de.next().await? {
+            Event::U64(u) => Ok(ByteSize(u)),
+            Event::I64(i) => u64::try_from(i)
+                .map(ByteSize)
+                .map_err(|_| MerdeError::OutOfRange),
+            Event::Str(s) => parse_byte_size(&s)
+                .map_err(|(index, message)| parsing_error("byte size", s, index, message)),
+            ev => Err(MerdeError::UnexpectedEvent {
+                got: EventType::from(&ev),
+                expected: &[EventType::Str, EventType::U64, EventType::I64],
+                help: Some("(While trying to deserialize a byte size)".to_string()),
+            }),
+        }
+    }
+}
+
+impl Serialize for ByteSize {
+    #[allow(clippy::manual_async_fn)]
+    fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        async move {
+            let s = self.to_string();
+            serializer.write(Event::Str(CowStr::Borrowed(&s))).await
+        }
+    }
+}
+
+/// A [Duration], written as a number of seconds, or as a string made of one or
+/// more `<number><unit>` parts (`"500ms"`, `"1h30m"`, `"2 days 12 hours"`).
+///
+/// Numbers may have a fractional part (`"1.5s"`), and units are:
+///
+///   * `ns`, `us` (or `µs`), `ms`
+///   * `s`, `sec`, `secs`, `second`, `seconds`
+///   * `m`, `min`, `mins`, `minute`, `minutes`
+///   * `h`, `hr`, `hrs`, `hour`, `hours`
+///   * `d`, `day`, `days`
+///   * `w`, `week`, `weeks`
+///
+/// It serializes as a string, in the compact form:
+///
+/// ```rust
+/// use std::time::Duration;
+/// use merde_core::units::HumanDuration;
+///
+/// let d: HumanDuration = "1h 30m".parse().unwrap();
+/// assert_eq!(d.0, Duration::from_secs(90 * 60));
+/// assert_eq!(d.to_string(), "1h30m");
+/// assert_eq!(HumanDuration(Duration::from_millis(1500)).to_string(), "1s500ms");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct HumanDuration(pub Duration);
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+// by decreasing size, which is what `Display` relies on
+const DURATION_UNITS: &[(&[&str], u128)] = &[
+    (&["w", "week", "weeks"], 7 * 24 * 3600 * NANOS_PER_SEC),
+    (&["d", "day", "days"], 24 * 3600 * NANOS_PER_SEC),
+    (&["h", "hr", "hrs", "hour", "hours"], 3600 * NANOS_PER_SEC),
+    (
+        &["m", "min", "mins", "minute", "minutes"],
+        60 * NANOS_PER_SEC,
+    ),
+    (&["s", "sec", "secs", "second", "seconds"], NANOS_PER_SEC),
+    (&["ms"], 1_000_000),
+    (&["us", "µs"], 1_000),
+    (&["ns"], 1),
+];
+
+impl FromStr for HumanDuration {
+    type Err = MerdeError<'static>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s).map_err(|(index, message)| {
+            parsing_error("duration", s.to_owned().into(), index, message)
+        })
+    }
+}
+
+fn parse_duration(s: &str) -> Result<HumanDuration, (usize, &'static str)> {
+    let mut nanos: u128 = 0;
+    let mut offset = 0;
+    loop {
+        let (number, number_start, rest) = split_number(s, offset)?;
+        let trimmed = rest.trim_start();
+        let unit_start = s.len() - trimmed.len();
+        let unit_len = trimmed
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(trimmed.len());
+        let unit = &trimmed[..unit_len];
+
+        let mult = if unit.is_empty() && number_start == s.len() - s.trim_start().len() {
+            // a lone number is a number of seconds
+            if !trimmed.is_empty() {
+                return Err((unit_start, "expected a unit"));
+            }
+            NANOS_PER_SEC
+        } else {
+            DURATION_UNITS
+                .iter()
+                .find_map(|&(names, mult)| names.contains(&unit).then_some(mult))
+                .ok_or((unit_start, "unknown unit"))?
+        };
+        let part = scale_decimal(number, mult).ok_or((number_start, "invalid number"))?;
+        nanos = nanos
+            .checked_add(part)
+            .ok_or((number_start, "duration too large"))?;
+
+        offset = unit_start + unit_len;
+        if s[offset..].trim().is_empty() {
+            break;
+        }
+    }
+
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).map_err(|_| (0, "duration too large"))?;
+    Ok(HumanDuration(Duration::new(
+        secs,
+        (nanos % NANOS_PER_SEC) as u32,
+    )))
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut nanos = self.0.as_nanos();
+        if nanos == 0 {
+            return f.write_str("0s");
+        }
+        // weeks aren't that readable, skip them
+        for &(names, mult) in &DURATION_UNITS[1..] {
+            if nanos >= mult {
+                write!(f, "{}{}", nanos / mult, names[0])?;
+                nanos %= mult;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(d: Duration) -> Self {
+        HumanDuration(d)
+    }
+}
+
+impl WithLifetime<'_> for HumanDuration {
+    type Lifetimed = Self;
+}
+
+impl IntoStatic for HumanDuration {
+    type Output = HumanDuration;
+
+    #[inline(always)]
+    fn into_static(self) -> Self::Output {
+        self
+    }
+}
+
+impl<'s> Deserialize<'s> for HumanDuration {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        match de.next().await? {
+            Event::U64(u) => Ok(HumanDuration(Duration::from_secs(u))),
+            Event::I64(i) => u64::try_from(i)
+                .map(|secs| HumanDuration(Duration::from_secs(secs)))
+                .map_err(|_| MerdeError::OutOfRange),
+            Event::F64(f) => Duration::try_from_secs_f64(f)
+                .map(HumanDuration)
+                .map_err(|_| MerdeError::OutOfRange),
+            Event::Str(s) => parse_duration(&s)
+                .map_err(|(index, message)| parsing_error("duration", s, index, message)),
+            ev => Err(MerdeError::UnexpectedEvent {
+                got: EventType::from(&ev),
+                expected: &[
+                    EventType::Str,
+                    EventType::U64,
+                    EventType::I64,
+                    EventType::Float,
+                ],
+                help: Some("(While trying to deserialize a duration)".to_string()),
+            }),
+        }
+    }
+}
+
+impl Serialize for HumanDuration {
+    #[allow(clippy::manual_async_fn)]
+    fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        async move {
+            let s = self.to_string();
+            serializer.write(Event::Str(CowStr::Borrowed(&s))).await
+        }
+    }
+}
+
+/// Splits off a decimal number (digits, optionally followed by a `.` and more
+/// digits) at `offset`, after skipping whitespace. Returns the number, where it
+/// starts, and the rest of the string.
+fn split_number(s: &str, offset: usize) -> Result<(&str, usize, &str), (usize, &'static str)> {
+    let tail = &s[offset..];
+    let start = offset + (tail.len() - tail.trim_start().len());
+    let tail = &s[start..];
+    let len = tail
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(tail.len());
+    if len == 0 {
+        return Err((start, "expected a number"));
+    }
+    Ok((&tail[..len], start, &tail[len..]))
+}
+
+/// Computes `number * mult`, where `number` is a decimal like `1.25`, truncating
+/// the result. Returns `None` on malformed numbers or overflow.
+fn scale_decimal(number: &str, mult: u128) -> Option<u128> {
+    let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+    if int.is_empty() || (number.contains('.') && frac.is_empty()) || frac.contains('.') {
+        return None;
+    }
+    let mut res = int.parse::<u128>().ok()?.checked_mul(mult)?;
+    // further digits can't make a difference for any of our units, and with
+    // at most 20 of them, `frac_value * mult` fits in an u128
+    let frac = &frac[..frac.len().min(20)];
+    if !frac.is_empty() {
+        let frac_value = frac.parse::<u128>().ok()?;
+        res = res.checked_add(frac_value * mult / 10u128.pow(frac.len() as u32))?;
+    }
+    Some(res)
+}
+
+fn parsing_error<'s>(
+    format: &'static str,
+    source: CowStr<'s>,
+    index: usize,
+    message: &str,
+) -> MerdeError<'s> {
+    MerdeError::StringParsingError {
+        format,
+        source,
+        index,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_size() {
+        for (input, bytes) in [
+            ("0", 0),
+            ("123", 123),
+            ("123B", 123),
+            ("512k", 512_000),
+            ("512 kB", 512_000),
+            ("10MiB", 10 << 20),
+            ("10mib", 10 << 20),
+            ("1.5Ki", 1536),
+            ("2 GB", 2_000_000_000),
+            ("1TiB", 1 << 40),
+            ("0.5B", 0),
+        ] {
+            assert_eq!(
+                input.parse::<ByteSize>().unwrap(),
+                ByteSize(bytes),
+                "{input}"
+            );
+        }
+
+        for input in ["", "MiB", "10 bananas", "1.2.3k", "1.k", "100000PiB"] {
+            assert!(input.parse::<ByteSize>().is_err(), "{input}");
+        }
+
+        for (bytes, output) in [
+            (0, "0B"),
+            (123, "123B"),
+            (1000, "1kB"),
+            (1024, "1KiB"),
+            (1536, "1536B"),
+            (3 << 30, "3GiB"),
+            (5_000_000, "5MB"),
+        ] {
+            assert_eq!(ByteSize(bytes).to_string(), output);
+            assert_eq!(output.parse::<ByteSize>().unwrap(), ByteSize(bytes));
+        }
+    }
+
+    #[test]
+    fn test_human_duration() {
+        for (input, expected) in [
+            ("0", Duration::ZERO),
+            ("30", Duration::from_secs(30)),
+            ("30s", Duration::from_secs(30)),
+            ("500ms", Duration::from_millis(500)),
+            ("1h30m", Duration::from_secs(5400)),
+            ("2 days 12 hours", Duration::from_secs(60 * 3600)),
+            ("1.5s", Duration::from_millis(1500)),
+            ("1w", Duration::from_secs(7 * 24 * 3600)),
+            ("10µs 3ns", Duration::from_nanos(10_003)),
+        ] {
+            assert_eq!(
+                input.parse::<HumanDuration>().unwrap(),
+                HumanDuration(expected),
+                "{input}"
+            );
+        }
+
+        for input in ["", "s", "10 parsecs", "1h 30", "1.s"] {
+            assert!(input.parse::<HumanDuration>().is_err(), "{input}");
+        }
+
+        for (d, output) in [
+            (Duration::ZERO, "0s"),
+            (Duration::from_secs(5400), "1h30m"),
+            (Duration::from_secs(8 * 24 * 3600), "8d"),
+            (Duration::new(1, 2), "1s2ns"),
+        ] {
+            assert_eq!(HumanDuration(d).to_string(), output);
+            assert_eq!(output.parse::<HumanDuration>().unwrap(), HumanDuration(d));
+        }
+    }
+}
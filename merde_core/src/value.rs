@@ -1,8 +1,11 @@
-use std::collections::HashMap;
+use alloc::{boxed::Box, string::String, vec::Vec};
 
 use ordered_float::OrderedFloat;
 
-use crate::{array::Array, map::Map, CowBytes, CowStr, IntoStatic, MerdeError, ValueType};
+use crate::{
+    array::Array, map::Map, ArrayStart, CowBytes, CowStr, Deserialize, DynDeserializerExt, Event,
+    EventIterDeserializer, HashMap, IntoStatic, MapStart, MerdeError, ValueType,
+};
 
 /// Think [`serde_json::Value`](https://docs.rs/serde_json/1.0.128/serde_json/enum.Value.html), but with a small string optimization,
 /// copy-on-write strings, etc. Might include other value types later.
@@ -134,11 +137,49 @@ impl<'s> From<Vec<Value<'s>>> for Value<'s> {
 
 impl<'s> From<HashMap<CowStr<'s>, Value<'s>>> for Value<'s> {
     fn from(v: HashMap<CowStr<'s>, Value<'s>>) -> Self {
-        Value::Map(Map(v))
+        Value::Map(Map::from(v))
     }
 }
 
 impl<'s> Value<'s> {
+    /// Estimates how many bytes this value holds on the heap, recursively:
+    /// owned strings and bytes that aren't stored inline, and the capacity
+    /// of arrays and maps. Borrowed data isn't counted, since it belongs to
+    /// the source document. Allocator overhead isn't counted either, so this
+    /// is a lower bound.
+    ///
+    /// This doesn't include `size_of::<Value>()` for the value itself.
+    pub fn estimated_heap_size(&self) -> usize {
+        match self {
+            Value::Str(s) => s.estimated_heap_size(),
+            Value::Bytes(b) => b.estimated_heap_size(),
+            Value::Array(a) => a.estimated_heap_size(),
+            Value::Map(m) => m.estimated_heap_size(),
+            Value::I64(_) | Value::U64(_) | Value::Float(_) | Value::Null | Value::Bool(_) => 0,
+        }
+    }
+
+    /// Releases unused capacity, recursively, e.g. before storing a parsed
+    /// document in a long-lived cache.
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            Value::Str(s) => s.shrink_to_fit(),
+            Value::Bytes(b) => b.shrink_to_fit(),
+            Value::Array(a) => a.shrink_to_fit(),
+            Value::Map(m) => m.shrink_to_fit(),
+            Value::I64(_) | Value::U64(_) | Value::Float(_) | Value::Null | Value::Bool(_) => {}
+        }
+    }
+
+    /// Deserializes a `T` out of this value, as if it had been read from a
+    /// document with the same contents.
+    pub fn deserialize_as<T>(&self) -> Result<T, MerdeError<'s>>
+    where
+        T: Deserialize<'s>,
+    {
+        deserialize_from_value(self)
+    }
+
     #[inline(always)]
     pub fn as_map(&self) -> Result<&Map<'s>, MerdeError<'static>> {
         match self {
@@ -262,3 +303,81 @@ impl<'s> Value<'s> {
         }
     }
 }
+
+/// Walks a [`Value`] tree, yielding the events a deserializer would for the same document.
+struct ValueEvents<'a, 's> {
+    pending: Option<&'a Value<'s>>,
+    stack: Vec<ValueEventsFrame<'a, 's>>,
+}
+
+enum ValueEventsFrame<'a, 's> {
+    Array(core::slice::Iter<'a, Value<'s>>),
+    Map(Box<dyn Iterator<Item = (&'a CowStr<'s>, &'a Value<'s>)> + 'a>),
+}
+
+impl<'a, 's> ValueEvents<'a, 's> {
+    fn start(&mut self, v: &'a Value<'s>) -> Event<'s> {
+        match v {
+            Value::I64(i) => Event::I64(*i),
+            Value::U64(u) => Event::U64(*u),
+            Value::Float(f) => Event::F64(f.into_inner()),
+            Value::Str(s) => Event::Str(s.clone()),
+            Value::Bytes(b) => Event::Bytes(b.clone()),
+            Value::Null => Event::Null,
+            Value::Bool(b) => Event::Bool(*b),
+            Value::Array(a) => {
+                self.stack.push(ValueEventsFrame::Array(a.iter()));
+                Event::ArrayStart(ArrayStart {
+                    size_hint: Some(a.len()),
+                })
+            }
+            Value::Map(m) => {
+                self.stack.push(ValueEventsFrame::Map(Box::new(m.iter())));
+                Event::MapStart(MapStart {
+                    size_hint: Some(m.len()),
+                })
+            }
+        }
+    }
+}
+
+impl<'s> Iterator for ValueEvents<'_, 's> {
+    type Item = Event<'s>;
+
+    fn next(&mut self) -> Option<Event<'s>> {
+        if let Some(v) = self.pending.take() {
+            return Some(self.start(v));
+        }
+        match self.stack.last_mut()? {
+            ValueEventsFrame::Array(items) => match items.next() {
+                Some(v) => Some(self.start(v)),
+                None => {
+                    self.stack.pop();
+                    Some(Event::ArrayEnd)
+                }
+            },
+            ValueEventsFrame::Map(entries) => match entries.next() {
+                Some((k, v)) => {
+                    self.pending = Some(v);
+                    Some(Event::Str(k.clone()))
+                }
+                None => {
+                    self.stack.pop();
+                    Some(Event::MapEnd)
+                }
+            },
+        }
+    }
+}
+
+/// Deserializes a `T` out of a [`Value`] tree, without going through any format.
+pub(crate) fn deserialize_from_value<'s, T>(v: &Value<'s>) -> Result<T, MerdeError<'s>>
+where
+    T: Deserialize<'s>,
+{
+    EventIterDeserializer::new(ValueEvents {
+        pending: Some(v),
+        stack: Vec::new(),
+    })
+    .deserialize()
+}
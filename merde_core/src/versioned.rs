@@ -0,0 +1,297 @@
+use alloc::string::ToString;
+use core::{
+    future::Future,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{
+    ArrayStart, Deserialize, DynDeserializer, DynSerializer, Event, EventType, IntoStatic,
+    MerdeError, Serialize, WithLifetime,
+};
+
+/// Types that are persisted along with a version number (see [Versioned]),
+/// and that know how to read the payloads written by their older versions.
+///
+/// Each layout a type went through is kept around as its own type, with its
+/// own version number. The current one deserializes older payloads by asking
+/// the previous one for them (which may ask the one before it, and so on),
+/// then upgrading the result:
+///
+/// ```rust
+/// use merde_core::{Deserialize, DynDeserializer, MerdeError, Migrate};
+///
+/// /// Version 1 had the timeout in seconds
+/// struct TimeoutV1(u64);
+///
+/// /// Version 2 has it in milliseconds
+/// struct Timeout(u64);
+///
+/// impl Migrate for TimeoutV1 {
+///     const VERSION: u32 = 1;
+/// }
+///
+/// impl Migrate for Timeout {
+///     const VERSION: u32 = 2;
+///
+///     async fn deserialize_older<'s>(
+///         version: u32,
+///         de: &mut dyn DynDeserializer<'s>,
+///     ) -> Result<Self, MerdeError<'s>> {
+///         let TimeoutV1(secs) = TimeoutV1::deserialize_version(version, de).await?;
+///         Ok(Timeout(secs * 1000))
+///     }
+/// }
+/// # impl<'s> Deserialize<'s> for TimeoutV1 {
+/// #     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+/// #         Ok(Self(u64::deserialize(de).await?))
+/// #     }
+/// # }
+/// # impl<'s> Deserialize<'s> for Timeout {
+/// #     async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+/// #         Ok(Self(u64::deserialize(de).await?))
+/// #     }
+/// # }
+/// ```
+///
+/// Persisted types don't borrow from their input, hence the `for<'s>` bound.
+pub trait Migrate: for<'s> Deserialize<'s> {
+    /// The version written along with this type. Bump it (and keep the
+    /// previous layout as its own type) whenever the layout changes.
+    const VERSION: u32;
+
+    /// Deserializes a payload written with an older `version`, and upgrades
+    /// it. The default implementation knows no older versions.
+    fn deserialize_older<'s, 'de>(
+        version: u32,
+        de: &'de mut dyn DynDeserializer<'s>,
+    ) -> impl Future<Output = Result<Self, MerdeError<'s>>> + 'de {
+        let _ = de;
+        async move { Err(MerdeError::UnknownVersion(version)) }
+    }
+
+    /// Deserializes a payload written with `version`: this type's own version,
+    /// or an older one. Payloads from newer versions are rejected.
+    fn deserialize_version<'s, 'de>(
+        version: u32,
+        de: &'de mut dyn DynDeserializer<'s>,
+    ) -> impl Future<Output = Result<Self, MerdeError<'s>>> + 'de {
+        async move {
+            match version.cmp(&Self::VERSION) {
+                core::cmp::Ordering::Equal => Self::deserialize(de).await,
+                core::cmp::Ordering::Less => Self::deserialize_older(version, de).await,
+                core::cmp::Ordering::Greater => Err(MerdeError::UnknownVersion(version)),
+            }
+        }
+    }
+}
+
+/// Implements [`Serialize`] and [`Deserialize`] for any type that implements
+/// [`Migrate`], as a two-element array: the version, then the payload.
+/// That's `[2, 1500]` for the `Timeout` from [Migrate]'s example.
+///
+/// Deserializing accepts payloads from the type's own version and from all
+/// the older versions it knows about, and fails with
+/// [MerdeError::UnknownVersion] for the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct Versioned<T>(pub T);
+
+impl<T> Deref for Versioned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Versioned<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'s, T> Deserialize<'s> for Versioned<T>
+where
+    T: Migrate,
+{
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        de.next().await?.into_array_start()?;
+        let version = u32::deserialize(de).await?;
+        let value = T::deserialize_version(version, de).await?;
+        match de.next().await? {
+            Event::ArrayEnd => Ok(Versioned(value)),
+            ev => Err(MerdeError::UnexpectedEvent {
+                got: EventType::from(&ev),
+                expected: &[EventType::ArrayEnd],
+                help: Some("(While trying to deserialize a Versioned value)".to_string()),
+            }),
+        }
+    }
+}
+
+impl<T> Serialize for Versioned<T>
+where
+    T: Migrate + Serialize,
+{
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        serializer
+            .write(Event::ArrayStart(ArrayStart { size_hint: Some(2) }))
+            .await?;
+        serializer.write(Event::U64(T::VERSION as u64)).await?;
+        self.0.serialize(serializer).await?;
+        serializer.write(Event::ArrayEnd).await
+    }
+}
+
+impl<T: IntoStatic> IntoStatic for Versioned<T> {
+    type Output = Versioned<T::Output>;
+
+    fn into_static(self) -> Self::Output {
+        Versioned(self.0.into_static())
+    }
+}
+
+impl<'s, T> WithLifetime<'s> for Versioned<T>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = Versioned<T::Lifetimed>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DynDeserializerExt, EventBuffer};
+
+    /// Version 1: just a name
+    struct UserV1(String);
+
+    /// Version 2: a name and an age, which version 1 didn't have
+    struct UserV2(String, Option<u8>);
+
+    /// Version 3: the name is split
+    #[derive(Debug, Clone, PartialEq)]
+    struct User {
+        first: String,
+        last: String,
+        age: Option<u8>,
+    }
+
+    impl<'s> Deserialize<'s> for UserV1 {
+        async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+            Ok(Self(String::deserialize(de).await?))
+        }
+    }
+
+    impl Migrate for UserV1 {
+        const VERSION: u32 = 1;
+    }
+
+    impl<'s> Deserialize<'s> for UserV2 {
+        async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+            let (name, age) = <(String, Option<u8>)>::deserialize(de).await?;
+            Ok(Self(name, age))
+        }
+    }
+
+    impl Migrate for UserV2 {
+        const VERSION: u32 = 2;
+
+        async fn deserialize_older<'s>(
+            version: u32,
+            de: &mut dyn DynDeserializer<'s>,
+        ) -> Result<Self, MerdeError<'s>> {
+            let UserV1(name) = UserV1::deserialize_version(version, de).await?;
+            Ok(Self(name, None))
+        }
+    }
+
+    impl<'s> Deserialize<'s> for User {
+        async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+            let (first, last, age) = <(String, String, Option<u8>)>::deserialize(de).await?;
+            Ok(Self { first, last, age })
+        }
+    }
+
+    impl Serialize for User {
+        async fn serialize<'se>(
+            &'se self,
+            serializer: &'se mut dyn DynSerializer,
+        ) -> Result<(), MerdeError<'static>> {
+            (self.first.as_str(), self.last.as_str(), self.age)
+                .serialize(serializer)
+                .await
+        }
+    }
+
+    impl Migrate for User {
+        const VERSION: u32 = 3;
+
+        async fn deserialize_older<'s>(
+            version: u32,
+            de: &mut dyn DynDeserializer<'s>,
+        ) -> Result<Self, MerdeError<'s>> {
+            let UserV2(name, age) = UserV2::deserialize_version(version, de).await?;
+            let (first, last) = name.split_once(' ').unwrap_or((&name, ""));
+            Ok(Self {
+                first: first.to_string(),
+                last: last.to_string(),
+                age,
+            })
+        }
+    }
+
+    fn versioned(version: u64, payload: &[Event<'static>]) -> EventBuffer<'static> {
+        let mut events = EventBuffer::new();
+        events.push(Event::ArrayStart(ArrayStart { size_hint: Some(2) }));
+        events.push(Event::U64(version));
+        for ev in payload {
+            events.push(ev.clone());
+        }
+        events.push(Event::ArrayEnd);
+        events
+    }
+
+    #[test]
+    fn test_versioned() {
+        let expected = User {
+            first: "Ada".into(),
+            last: "Lovelace".into(),
+            age: None,
+        };
+
+        let mut v1 = versioned(1, &[Event::Str("Ada Lovelace".into())]);
+        let user: Versioned<User> = v1.deserialize().unwrap();
+        assert_eq!(user.0, expected);
+
+        let mut v2 = versioned(
+            2,
+            &[
+                Event::ArrayStart(ArrayStart { size_hint: None }),
+                Event::Str("Ada Lovelace".into()),
+                Event::U64(36),
+                Event::ArrayEnd,
+            ],
+        );
+        let user: Versioned<User> = v2.deserialize().unwrap();
+        assert_eq!(user.age, Some(36));
+
+        // written at the current version
+        let (version, _): (u32, crate::Value) =
+            crate::convert(&Versioned(expected.clone())).unwrap();
+        assert_eq!(version, 3);
+        let user: Versioned<User> = crate::convert(&Versioned(expected)).unwrap();
+        assert_eq!(user.last, "Lovelace");
+
+        for version in [0, 4] {
+            let res = versioned(version, &[Event::Null]).deserialize::<Versioned<User>>();
+            assert!(
+                matches!(res, Err(MerdeError::UnknownVersion(v)) if v as u64 == version),
+                "{version}"
+            );
+        }
+    }
+}
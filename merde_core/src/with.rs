@@ -0,0 +1,426 @@
+//! Provides [With], which (de)serializes a value through an [Adapter] instead
+//! of its own impls (or lack thereof), along with a few adapters:
+//! [DisplayFromStr], [Base64], [TaggedResult] and, with `std`, [SecondsSinceEpoch].
+//!
+//! ```rust
+//! use std::net::Ipv4Addr;
+//! use merde_core::{
+//!     with::{DisplayFromStr, With},
+//!     DynDeserializerExt, Event, EventBuffer,
+//! };
+//!
+//! let addr: With<Ipv4Addr, DisplayFromStr> =
+//!     EventBuffer::from_events([Event::Str("127.0.0.1".into())])
+//!         .deserialize()
+//!         .unwrap();
+//! assert_eq!(*addr, Ipv4Addr::LOCALHOST);
+//! ```
+
+use alloc::{string::ToString, vec::Vec};
+use core::{
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+use crate::{
+    deserialize_in_place::DeserializeInPlace, CowStr, Deserialize, DeserializeOwned,
+    DynDeserializer, DynSerializer, Event, IntoStatic, MapStart, MerdeError, Serialize,
+    WithLifetime,
+};
+
+/// Serializes and deserializes `T` on its behalf, usually by converting it
+/// to and from a proxy type that implements [Serialize] and [Deserialize].
+///
+/// ```rust
+/// use merde_core::{with::Adapter, Deserialize, DynDeserializer, DynSerializer, MerdeError, Serialize};
+///
+/// /// Booleans written as `0` or `1`
+/// struct BoolAsInt;
+///
+/// impl Adapter<bool> for BoolAsInt {
+///     async fn serialize_as<'fut>(
+///         value: &'fut bool,
+///         serializer: &'fut mut dyn DynSerializer,
+///     ) -> Result<(), MerdeError<'static>> {
+///         (*value as u8).serialize(serializer).await
+///     }
+///
+///     async fn deserialize_as<'s>(
+///         de: &mut dyn DynDeserializer<'s>,
+///     ) -> Result<bool, MerdeError<'s>> {
+///         Ok(u8::deserialize(de).await? != 0)
+///     }
+/// }
+/// ```
+pub trait Adapter<T> {
+    fn serialize_as<'fut>(
+        value: &'fut T,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut;
+
+    fn deserialize_as<'s>(
+        de: &mut dyn DynDeserializer<'s>,
+    ) -> impl Future<Output = Result<T, MerdeError<'s>>>;
+}
+
+/// A `T` that's (de)serialized with the adapter `A`, e.g.
+/// `With<SystemTime, SecondsSinceEpoch>`. It derefs to `T`.
+pub struct With<T, A> {
+    value: T,
+    _adapter: PhantomData<A>,
+}
+
+impl<T, A> With<T, A> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            _adapter: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, A> From<T> for With<T, A> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, A> Deref for With<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, A> DerefMut for With<T, A> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+// not derived, so that the adapter doesn't need to implement these
+impl<T: fmt::Debug, A> fmt::Debug for With<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: Clone, A> Clone for With<T, A> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T: PartialEq, A> PartialEq for With<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, A> Eq for With<T, A> {}
+
+impl<T: Default, A> Default for With<T, A> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<'s, T, A> Deserialize<'s> for With<T, A>
+where
+    T: 's,
+    A: Adapter<T> + 's,
+{
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        Ok(Self::new(A::deserialize_as(de).await?))
+    }
+}
+
+impl<'s, T, A> DeserializeInPlace<'s> for With<T, A>
+where
+    T: 's,
+    A: Adapter<T> + 's,
+{
+}
+
+impl<T, A> Serialize for With<T, A>
+where
+    A: Adapter<T>,
+{
+    fn serialize<'fut>(
+        &'fut self,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        A::serialize_as(&self.value, serializer)
+    }
+}
+
+impl<T: IntoStatic, A: 'static> IntoStatic for With<T, A> {
+    type Output = With<T::Output, A>;
+
+    fn into_static(self) -> Self::Output {
+        With::new(self.value.into_static())
+    }
+}
+
+impl<'s, T: WithLifetime<'s>, A: 's> WithLifetime<'s> for With<T, A> {
+    type Lifetimed = With<T::Lifetimed, A>;
+}
+
+/// Writes values as strings using their [Display](fmt::Display) impl, and
+/// reads them back with their [FromStr] impl: that's all many third-party
+/// types (IP networks, URLs, semver versions...) offer.
+///
+/// Parsing errors are reported as [MerdeError::StringParsingError], with the
+/// type's name as the format, and its `FromStr` error as the message.
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayFromStr;
+
+impl<T> Adapter<T> for DisplayFromStr
+where
+    T: fmt::Display + FromStr,
+    T::Err: fmt::Display,
+{
+    async fn serialize_as<'fut>(
+        value: &'fut T,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let s = value.to_string();
+        serializer.write(Event::Str(CowStr::Borrowed(&s))).await
+    }
+
+    async fn deserialize_as<'s>(de: &mut dyn DynDeserializer<'s>) -> Result<T, MerdeError<'s>> {
+        let s = CowStr::deserialize(de).await?;
+        s.parse()
+            .map_err(|e: T::Err| MerdeError::StringParsingError {
+                format: core::any::type_name::<T>(),
+                message: e.to_string(),
+                source: s,
+                index: 0,
+            })
+    }
+}
+
+/// Writes byte buffers as base64 strings, like [base64::Base64](crate::base64::Base64)
+/// does, for types that can't be wrapped in it (like `Box<[u8]>`).
+#[derive(Debug, Clone, Copy)]
+pub struct Base64;
+
+impl<T> Adapter<T> for Base64
+where
+    T: AsRef<[u8]> + From<Vec<u8>>,
+{
+    async fn serialize_as<'fut>(
+        value: &'fut T,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        crate::base64::Base64(value.as_ref())
+            .serialize(serializer)
+            .await
+    }
+
+    async fn deserialize_as<'s>(de: &mut dyn DynDeserializer<'s>) -> Result<T, MerdeError<'s>> {
+        let bytes = crate::base64::Base64::<Vec<u8>>::deserialize(de).await?;
+        Ok(T::from(bytes.0))
+    }
+}
+
+/// The keys [TaggedResult] writes `Result`s under, and reads them from.
+pub trait ResultTags {
+    const OK: &'static str;
+    const ERR: &'static str;
+}
+
+/// `{"ok": value}` and `{"err": error}`
+#[derive(Debug, Clone, Copy)]
+pub struct LowercaseTags;
+
+impl ResultTags for LowercaseTags {
+    const OK: &'static str = "ok";
+    const ERR: &'static str = "err";
+}
+
+/// Writes `Result`s as maps with a single key, like their own impls do
+/// (`{"Ok": value}` or `{"Err": error}`), but with the keys of `Tags`: for
+/// APIs that want, say, `{"data": ...}` or `{"error": ...}`. Only those keys
+/// are accepted when reading them back.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use merde_core::{
+///     convert,
+///     with::{LowercaseTags, TaggedResult, With},
+/// };
+///
+/// let res: With<Result<u32, String>, TaggedResult<LowercaseTags>> = With::new(Ok(42));
+/// let map: HashMap<String, u32> = convert(&res).unwrap();
+/// assert_eq!(map["ok"], 42);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TaggedResult<Tags>(PhantomData<Tags>);
+
+impl<T, E, Tags> Adapter<Result<T, E>> for TaggedResult<Tags>
+where
+    T: Serialize + DeserializeOwned,
+    E: Serialize + DeserializeOwned,
+    Tags: ResultTags + 'static,
+{
+    async fn serialize_as<'fut>(
+        value: &'fut Result<T, E>,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        serializer
+            .write(Event::MapStart(MapStart { size_hint: Some(1) }))
+            .await?;
+        match value {
+            Ok(value) => {
+                serializer
+                    .write(Event::Str(CowStr::Borrowed(Tags::OK)))
+                    .await?;
+                value.serialize(serializer).await?;
+            }
+            Err(error) => {
+                serializer
+                    .write(Event::Str(CowStr::Borrowed(Tags::ERR)))
+                    .await?;
+                error.serialize(serializer).await?;
+            }
+        }
+        serializer.write(Event::MapEnd).await
+    }
+
+    async fn deserialize_as<'s>(
+        de: &mut dyn DynDeserializer<'s>,
+    ) -> Result<Result<T, E>, MerdeError<'s>> {
+        de.next().await?.into_map_start()?;
+        let key = de.next().await?.into_str()?;
+        let res = if key == Tags::OK {
+            Ok(T::deserialize_owned(de).await?)
+        } else if key == Tags::ERR {
+            Err(E::deserialize_owned(de).await?)
+        } else {
+            return Err(MerdeError::UnknownProperty(key));
+        };
+        de.next().await?.into_map_end()?;
+        Ok(res)
+    }
+}
+
+/// Writes [SystemTime](std::time::SystemTime)s as a number of whole seconds
+/// since the Unix epoch (rounded down, negative before 1970).
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct SecondsSinceEpoch;
+
+#[cfg(feature = "std")]
+impl Adapter<std::time::SystemTime> for SecondsSinceEpoch {
+    async fn serialize_as<'fut>(
+        value: &'fut std::time::SystemTime,
+        serializer: &'fut mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        let secs = match value.duration_since(std::time::UNIX_EPOCH) {
+            Ok(d) => i64::try_from(d.as_secs()).map_err(|_| MerdeError::OutOfRange)?,
+            Err(e) => {
+                let d = e.duration();
+                let secs = i64::try_from(d.as_secs()).map_err(|_| MerdeError::OutOfRange)?;
+                -secs - (d.subsec_nanos() > 0) as i64
+            }
+        };
+        serializer.write(Event::I64(secs)).await
+    }
+
+    async fn deserialize_as<'s>(
+        de: &mut dyn DynDeserializer<'s>,
+    ) -> Result<std::time::SystemTime, MerdeError<'s>> {
+        let secs = i64::deserialize(de).await?;
+        let d = std::time::Duration::from_secs(secs.unsigned_abs());
+        let time = if secs >= 0 {
+            std::time::UNIX_EPOCH.checked_add(d)
+        } else {
+            std::time::UNIX_EPOCH.checked_sub(d)
+        };
+        time.ok_or(MerdeError::OutOfRange)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{convert, DynDeserializerExt, EventBuffer};
+
+    #[test]
+    fn test_display_from_str() {
+        let n: With<u32, DisplayFromStr> = convert(&"42").unwrap();
+        assert_eq!(*n, 42);
+        let s: String = convert(&n).unwrap();
+        assert_eq!(s, "42");
+
+        let res = EventBuffer::from_events([Event::Str("forty-two".into())])
+            .deserialize::<With<u32, DisplayFromStr>>();
+        assert!(matches!(
+            res,
+            Err(MerdeError::StringParsingError { format: "u32", .. })
+        ));
+    }
+
+    #[test]
+    fn test_base64() {
+        let bytes: With<Box<[u8]>, Base64> = convert(&"AQID").unwrap();
+        assert_eq!(&bytes[..], [1, 2, 3]);
+        let s: String = convert(&bytes).unwrap();
+        assert_eq!(s, "AQID");
+    }
+
+    #[test]
+    fn test_tagged_result() {
+        struct RpcTags;
+
+        impl ResultTags for RpcTags {
+            const OK: &'static str = "data";
+            const ERR: &'static str = "error";
+        }
+
+        type Rpc = With<Result<u32, String>, TaggedResult<RpcTags>>;
+
+        let events = |key: &'static str, value: Event<'static>| {
+            EventBuffer::from_events([
+                Event::MapStart(MapStart { size_hint: None }),
+                Event::Str(key.into()),
+                value,
+                Event::MapEnd,
+            ])
+        };
+
+        let res: Rpc = events("data", Event::U64(7)).deserialize().unwrap();
+        assert_eq!(*res, Ok(7));
+        let res: Rpc = events("error", Event::Str("nope".into()))
+            .deserialize()
+            .unwrap();
+        assert_eq!(*res, Err("nope".to_string()));
+        // the default tags don't apply anymore
+        assert!(events("Ok", Event::U64(7)).deserialize::<Rpc>().is_err());
+
+        let res: Rpc = convert(&Rpc::new(Err("nope".into()))).unwrap();
+        assert_eq!(*res, Err("nope".to_string()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_seconds_since_epoch() {
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        let time: With<SystemTime, SecondsSinceEpoch> = convert(&1_700_000_000).unwrap();
+        assert_eq!(*time, UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+
+        let before = With::<_, SecondsSinceEpoch>::new(UNIX_EPOCH - Duration::from_millis(1500));
+        let secs: i64 = convert(&before).unwrap();
+        assert_eq!(secs, -2);
+    }
+}
@@ -1,9 +1,24 @@
-use std::{
-    borrow::Cow,
-    collections::{HashMap, HashSet, VecDeque},
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    collections::VecDeque,
+    rc::Rc,
+    string::String,
+    sync::Arc,
+    vec::Vec,
 };
+use core::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+    },
+    ops::ControlFlow,
+};
+#[cfg(feature = "std")]
+use std::path::PathBuf;
 
-use crate::{CowStr, Value};
+use crate::{CowBytes, CowStr, HashMap, HashSet, RawNumber, Value};
 
 /// Allow instantiating a type with a lifetime parameter, which in
 /// turn lets us require `Deserialize<'s>` for `CowStr<'s>` for
@@ -46,7 +61,9 @@ impl<'s> WithLifetime<'s> for &str {
 
 impl_with_lifetime!(
     Value<'s>,
+    RawNumber<'s>,
     CowStr<'s>,
+    CowBytes<'s>,
     String,
     u128,
     u64,
@@ -64,8 +81,29 @@ impl_with_lifetime!(
     isize,
     f32,
     f64,
+    NonZeroU128,
+    NonZeroU64,
+    NonZeroU32,
+    NonZeroU16,
+    NonZeroU8,
+    NonZeroUsize,
+    NonZeroI128,
+    NonZeroI64,
+    NonZeroI32,
+    NonZeroI16,
+    NonZeroI8,
+    NonZeroIsize,
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6,
 );
 
+#[cfg(feature = "std")]
+impl_with_lifetime!(PathBuf);
+
 impl WithLifetime<'_> for () {
     type Lifetimed = ();
 }
@@ -77,6 +115,80 @@ where
     type Lifetimed = Option<T::Lifetimed>;
 }
 
+impl<'s, T> WithLifetime<'s> for Box<T>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = Box<T::Lifetimed>;
+}
+
+impl WithLifetime<'_> for Box<str> {
+    type Lifetimed = Box<str>;
+}
+
+impl<'s, T> WithLifetime<'s> for Box<[T]>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = Box<[T::Lifetimed]>;
+}
+
+macro_rules! impl_with_lifetime_for_shared {
+    ($($ptr:ident),*) => {
+        $(
+            impl<'s, T> WithLifetime<'s> for $ptr<T>
+            where
+                T: WithLifetime<'s>,
+            {
+                type Lifetimed = $ptr<T::Lifetimed>;
+            }
+
+            impl WithLifetime<'_> for $ptr<str> {
+                type Lifetimed = $ptr<str>;
+            }
+
+            impl<'s, T> WithLifetime<'s> for $ptr<[T]>
+            where
+                T: WithLifetime<'s>,
+            {
+                type Lifetimed = $ptr<[T::Lifetimed]>;
+            }
+        )*
+    };
+}
+
+impl_with_lifetime_for_shared!(Arc, Rc);
+
+impl<'s, T, E> WithLifetime<'s> for Result<T, E>
+where
+    T: WithLifetime<'s>,
+    E: WithLifetime<'s>,
+{
+    type Lifetimed = Result<T::Lifetimed, E::Lifetimed>;
+}
+
+impl<'s, B, C> WithLifetime<'s> for ControlFlow<B, C>
+where
+    B: WithLifetime<'s>,
+    C: WithLifetime<'s>,
+{
+    type Lifetimed = ControlFlow<B::Lifetimed, C::Lifetimed>;
+}
+
+impl<'s, T> WithLifetime<'s> for Wrapping<T>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = Wrapping<T::Lifetimed>;
+}
+
+impl<'s, T> WithLifetime<'s> for Saturating<T>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = Saturating<T::Lifetimed>;
+}
+
 impl<'s, T> WithLifetime<'s> for Vec<T>
 where
     T: WithLifetime<'s>,
@@ -107,111 +219,36 @@ where
     type Lifetimed = HashMap<K::Lifetimed, V::Lifetimed, S>;
 }
 
-impl<'s, T1: WithLifetime<'s>> WithLifetime<'s> for (T1,) {
-    type Lifetimed = (T1::Lifetimed,);
-}
-
-impl<'s, T1: WithLifetime<'s>, T2: WithLifetime<'s>> WithLifetime<'s> for (T1, T2) {
-    type Lifetimed = (T1::Lifetimed, T2::Lifetimed);
-}
-
-impl<'s, T1: WithLifetime<'s>, T2: WithLifetime<'s>, T3: WithLifetime<'s>> WithLifetime<'s>
-    for (T1, T2, T3)
-{
-    type Lifetimed = (T1::Lifetimed, T2::Lifetimed, T3::Lifetimed);
+macro_rules! impl_with_lifetime_for_tuple {
+    ($($type_arg:ident),+) => {
+        impl<'s, $($type_arg: WithLifetime<'s>),+> WithLifetime<'s> for ($($type_arg,)+) {
+            type Lifetimed = ($($type_arg::Lifetimed,)+);
+        }
+    };
 }
 
-impl<
-        's,
-        T1: WithLifetime<'s>,
-        T2: WithLifetime<'s>,
-        T3: WithLifetime<'s>,
-        T4: WithLifetime<'s>,
-    > WithLifetime<'s> for (T1, T2, T3, T4)
-{
-    type Lifetimed = (T1::Lifetimed, T2::Lifetimed, T3::Lifetimed, T4::Lifetimed);
-}
+impl_with_lifetime_for_tuple!(T1);
+impl_with_lifetime_for_tuple!(T1, T2);
+impl_with_lifetime_for_tuple!(T1, T2, T3);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_with_lifetime_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
+impl_with_lifetime_for_tuple!(
+    T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16
+);
 
-impl<
-        's,
-        T1: WithLifetime<'s>,
-        T2: WithLifetime<'s>,
-        T3: WithLifetime<'s>,
-        T4: WithLifetime<'s>,
-        T5: WithLifetime<'s>,
-    > WithLifetime<'s> for (T1, T2, T3, T4, T5)
-{
-    type Lifetimed = (
-        T1::Lifetimed,
-        T2::Lifetimed,
-        T3::Lifetimed,
-        T4::Lifetimed,
-        T5::Lifetimed,
-    );
-}
-
-impl<
-        's,
-        T1: WithLifetime<'s>,
-        T2: WithLifetime<'s>,
-        T3: WithLifetime<'s>,
-        T4: WithLifetime<'s>,
-        T5: WithLifetime<'s>,
-        T6: WithLifetime<'s>,
-    > WithLifetime<'s> for (T1, T2, T3, T4, T5, T6)
-{
-    type Lifetimed = (
-        T1::Lifetimed,
-        T2::Lifetimed,
-        T3::Lifetimed,
-        T4::Lifetimed,
-        T5::Lifetimed,
-        T6::Lifetimed,
-    );
-}
-
-impl<
-        's,
-        T1: WithLifetime<'s>,
-        T2: WithLifetime<'s>,
-        T3: WithLifetime<'s>,
-        T4: WithLifetime<'s>,
-        T5: WithLifetime<'s>,
-        T6: WithLifetime<'s>,
-        T7: WithLifetime<'s>,
-    > WithLifetime<'s> for (T1, T2, T3, T4, T5, T6, T7)
-{
-    type Lifetimed = (
-        T1::Lifetimed,
-        T2::Lifetimed,
-        T3::Lifetimed,
-        T4::Lifetimed,
-        T5::Lifetimed,
-        T6::Lifetimed,
-        T7::Lifetimed,
-    );
-}
-
-impl<
-        's,
-        T1: WithLifetime<'s>,
-        T2: WithLifetime<'s>,
-        T3: WithLifetime<'s>,
-        T4: WithLifetime<'s>,
-        T5: WithLifetime<'s>,
-        T6: WithLifetime<'s>,
-        T7: WithLifetime<'s>,
-        T8: WithLifetime<'s>,
-    > WithLifetime<'s> for (T1, T2, T3, T4, T5, T6, T7, T8)
+impl<'s, T, const N: usize> WithLifetime<'s> for [T; N]
+where
+    T: WithLifetime<'s>,
 {
-    type Lifetimed = (
-        T1::Lifetimed,
-        T2::Lifetimed,
-        T3::Lifetimed,
-        T4::Lifetimed,
-        T5::Lifetimed,
-        T6::Lifetimed,
-        T7::Lifetimed,
-        T8::Lifetimed,
-    );
+    type Lifetimed = [T::Lifetimed; N];
 }
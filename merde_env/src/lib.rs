@@ -0,0 +1,294 @@
+#![deny(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+use std::collections::BTreeMap;
+
+use merde_core::{
+    CowStr, DeserializeOwned, Deserializer, Event, MapStart, MerdeError, MetastackExt, TextEvents,
+    TypeHint,
+};
+
+enum Node {
+    Leaf(String),
+    Branch(BTreeMap<String, Node>),
+}
+
+/// A deserializer that reads a set of environment variables as a (possibly
+/// nested) map, that implements [`merde_core::Deserializer`].
+///
+/// Like forms, environment variables are untyped, so values that look like
+/// booleans (`true`, `false`) and numbers (`42`, `-1.5`) are read as such, and
+/// everything else is read as a string. When the target type asks for a string
+/// (see [`TypeHint::Str`]), it gets the value as written instead, so
+/// `APP_DB__PASSWORD=1234` deserializes into a `String` just fine. See
+/// [`EnvDeserializer::with_type_inference`] to read everything as strings.
+pub struct EnvDeserializer {
+    vars: Vec<(String, String)>,
+    prefix: String,
+    separator: String,
+    infer_types: bool,
+    // the variables are parsed on the first call to `next`
+    parsed: bool,
+    events: TextEvents<'static>,
+}
+
+impl std::fmt::Debug for EnvDeserializer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // values may be secrets: don't print them
+        f.debug_struct("EnvDeserializer")
+            .field("vars_len", &self.vars.len())
+            .field("prefix", &self.prefix)
+            .field("separator", &self.separator)
+            .finish()
+    }
+}
+
+impl EnvDeserializer {
+    /// Reads the environment of the current process. Variables whose name or
+    /// value isn't valid UTF-8 are ignored.
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::vars_os()
+                .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?))),
+        )
+    }
+
+    /// Reads the given variables, e.g. for tests.
+    pub fn new(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            vars: vars.into_iter().collect(),
+            prefix: String::new(),
+            separator: "__".to_string(),
+            infer_types: true,
+            parsed: false,
+            events: TextEvents::default(),
+        }
+    }
+
+    /// Only read variables whose name starts with `prefix` (e.g. `APP_`), and
+    /// strip it from the keys. Defaults to no prefix (all variables are read).
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets the separator between nesting levels. Defaults to `__`, so that
+    /// `DB__HOST` is read as `{"db": {"host": ...}}`.
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Sets whether values that look like booleans and numbers are read as
+    /// such (the default), or whether all values are read as strings.
+    pub fn with_type_inference(mut self, infer_types: bool) -> Self {
+        self.infer_types = infer_types;
+        self
+    }
+
+    fn parse(&self, events: &mut TextEvents<'static>) -> Result<(), MerdeError<'static>> {
+        let mut root = BTreeMap::new();
+        for (name, value) in &self.vars {
+            let Some(path) = name.strip_prefix(&self.prefix) else {
+                continue;
+            };
+            if path.is_empty() {
+                continue;
+            }
+            let segments: Vec<String> = path
+                .split(self.separator.as_str())
+                .map(|segment| segment.to_lowercase())
+                .collect();
+            if segments.iter().any(|segment| segment.is_empty()) {
+                return Err(self.error(name, "empty segment in variable name"));
+            }
+
+            let (last, parents) = segments.split_last().unwrap();
+            let mut map = &mut root;
+            for segment in parents {
+                let node = map
+                    .entry(segment.clone())
+                    .or_insert_with(|| Node::Branch(BTreeMap::new()));
+                map = match node {
+                    Node::Branch(map) => map,
+                    Node::Leaf(_) => {
+                        return Err(self.error(name, "conflicts with a variable for a parent key"))
+                    }
+                };
+            }
+            if map.contains_key(last) {
+                return Err(self.error(
+                    name,
+                    "conflicts with another variable (names are case-insensitive)",
+                ));
+            }
+            map.insert(last.clone(), Node::Leaf(value.clone()));
+        }
+
+        self.push_map(root, events);
+        Ok(())
+    }
+
+    fn push_map(&self, map: BTreeMap<String, Node>, events: &mut TextEvents<'static>) {
+        events.push(Event::MapStart(MapStart {
+            size_hint: Some(map.len()),
+        }));
+        for (key, node) in map {
+            events.push(Event::Str(CowStr::from(key)));
+            match node {
+                Node::Leaf(value) if self.infer_types => events.push_scalar(CowStr::from(value)),
+                Node::Leaf(value) => events.push(Event::Str(CowStr::from(value))),
+                Node::Branch(map) => self.push_map(map, events),
+            }
+        }
+        events.push(Event::MapEnd);
+    }
+
+    fn next_hinted(
+        &mut self,
+        hint: Option<TypeHint>,
+    ) -> Result<Event<'static>, MerdeError<'static>> {
+        if !self.parsed {
+            self.parsed = true;
+            // (after whatever was put back)
+            let mut events = std::mem::take(&mut self.events);
+            self.parse(&mut events)?;
+            self.events = events;
+        }
+        self.events.next(hint).ok_or_else(MerdeError::eof)
+    }
+
+    fn error(&self, name: &str, message: &str) -> MerdeError<'static> {
+        MerdeError::StringParsingError {
+            format: "env",
+            source: CowStr::from(name.to_string()),
+            index: 0,
+            message: message.to_string(),
+        }
+    }
+}
+
+// everything is owned, so events never borrow from the deserializer
+impl Deserializer<'static> for EnvDeserializer {
+    async fn next(&mut self) -> Result<Event<'static>, MerdeError<'static>> {
+        self.next_hinted(None)
+    }
+
+    async fn next_event_with_hint(
+        &mut self,
+        hint: TypeHint,
+    ) -> Result<Event<'static>, MerdeError<'static>> {
+        self.next_hinted(Some(hint))
+    }
+
+    fn put_back(&mut self, ev: Event<'static>) -> Result<(), MerdeError<'static>> {
+        self.events.put_back(ev);
+        Ok(())
+    }
+}
+
+/// Deserialize an instance of type `T` from the environment variables of the
+/// current process that start with `prefix` (e.g. `APP_`).
+pub fn from_env<T>(prefix: &str) -> Result<T, MerdeError<'static>>
+where
+    T: DeserializeOwned,
+{
+    from_vars(EnvDeserializer::from_env().with_prefix(prefix))
+}
+
+/// Deserialize an instance of type `T` from an [`EnvDeserializer`], e.g. one
+/// with a custom separator, or with a given set of variables.
+pub fn from_vars<T>(mut deser: EnvDeserializer) -> Result<T, MerdeError<'static>>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize_owned(&mut deser).run_sync_with_metastack()
+}
+
+#[cfg(test)]
+mod tests {
+    use merde_core::{Map, Value};
+
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> EnvDeserializer {
+        EnvDeserializer::new(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())))
+    }
+
+    #[test]
+    fn test_nesting() {
+        let value: Value = from_vars(
+            vars(&[
+                ("APP_PORT", "8080"),
+                ("APP_DB__HOST", "localhost"),
+                ("APP_DB__TLS", "true"),
+                ("APP_DB__POOL__SIZE", "007"),
+                ("HOME", "/root"),
+            ])
+            .with_prefix("APP_"),
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            Value::Map(
+                Map::new().with("port", Value::I64(8080)).with(
+                    "db",
+                    Map::new()
+                        .with("host", Value::from("localhost"))
+                        .with("tls", Value::Bool(true))
+                        .with("pool", Map::new().with("size", Value::from("007")))
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn test_string_hint() {
+        use std::collections::HashMap;
+
+        let map: HashMap<String, HashMap<String, String>> = from_vars(
+            vars(&[("APP_DB__PASSWORD", "1234"), ("APP_DB__TLS", "true")]).with_prefix("APP_"),
+        )
+        .unwrap();
+        assert_eq!(map["db"]["password"], "1234");
+        assert_eq!(map["db"]["tls"], "true");
+
+        let map: HashMap<String, Value> =
+            from_vars(vars(&[("APP_PORT", "8080")]).with_prefix("APP_")).unwrap();
+        assert_eq!(map["port"], Value::I64(8080));
+        let map: HashMap<String, Value> = from_vars(
+            vars(&[("APP_PORT", "8080")])
+                .with_prefix("APP_")
+                .with_type_inference(false),
+        )
+        .unwrap();
+        assert_eq!(map["port"], Value::from("8080"));
+    }
+
+    #[test]
+    fn test_separator() {
+        let value: Value = from_vars(
+            vars(&[("APP.DB.HOST", "localhost")])
+                .with_prefix("APP.")
+                .with_separator("."),
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            Value::Map(Map::new().with("db", Map::new().with("host", Value::from("localhost"))))
+        );
+    }
+
+    #[test]
+    fn test_conflicts() {
+        for pairs in [
+            &[("APP_DB", "x"), ("APP_DB__HOST", "y")][..],
+            &[("APP_DB__HOST", "y"), ("APP_DB", "x")][..],
+            &[("APP_HOST", "x"), ("APP_host", "y")][..],
+            &[("APP_DB____HOST", "x")][..],
+        ] {
+            let res: Result<Value, _> = from_vars(vars(pairs).with_prefix("APP_"));
+            assert!(res.is_err(), "{pairs:?}");
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Deserializes the same object into maps with owned and borrowed keys,
+//! counting allocations and timing it. Run with:
+//!
+//! ```text
+//! cargo bench -p merde_json --bench borrowed_keys
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    borrow::Cow,
+    collections::HashMap,
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use merde_core::{CowStr, Deserialize, DynDeserializerExt as _, Map};
+use merde_json::JsonDeserializer;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const KEYS: usize = 1000;
+const ITERATIONS: u32 = 200;
+
+struct Measurement {
+    allocations: usize,
+    per_iteration: Duration,
+}
+
+fn measure<'s, T>(input: &'s str) -> Measurement
+where
+    T: Deserialize<'s>,
+{
+    let parse = || {
+        JsonDeserializer::new(black_box(input))
+            .deserialize::<T>()
+            .unwrap()
+    };
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let value = parse();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    drop(value);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(parse());
+    }
+
+    Measurement {
+        allocations,
+        per_iteration: start.elapsed() / ITERATIONS,
+    }
+}
+
+fn main() {
+    let input = format!(
+        "{{{}}}",
+        (0..KEYS)
+            .map(|i| format!(r#""a_reasonably_long_key_{i:04}":{i}"#))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+
+    let owned = measure::<HashMap<String, u64>>(&input);
+    let results = [
+        ("HashMap<String, u64>", &owned),
+        (
+            "HashMap<CowStr, u64>",
+            &measure::<HashMap<CowStr, u64>>(&input),
+        ),
+        (
+            "HashMap<Cow<str>, u64>",
+            &measure::<HashMap<Cow<str>, u64>>(&input),
+        ),
+        ("Map", &measure::<Map>(&input)),
+    ];
+
+    println!("{KEYS} keys, {ITERATIONS} iterations");
+    for (name, m) in results {
+        println!(
+            "{name:>24}: {:>5} allocations, {:>10.2?} per iteration",
+            m.allocations, m.per_iteration
+        );
+        if name != "HashMap<String, u64>" {
+            // the owned map needs one allocation per key, the borrowed ones
+            // none at all: what's left is the table itself, and the futures
+            // boxed by `DynDeserializer`, which both kinds of maps pay for.
+            assert!(
+                m.allocations + KEYS <= owned.allocations,
+                "{name} should not allocate for keys"
+            );
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::io::Write;
+
+use merde_core::InfallibleSerializer;
+
+use crate::{JsonSerializer, JsonSerializerWriter};
+
+/// A single-byte character set that JSON can be transcoded to, for systems
+/// that can't deal with UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Charset {
+    /// ISO-8859-1: U+0000 to U+00FF map to the byte of the same value.
+    Latin1,
+    /// Windows-1252: like Latin-1, except 0x80 to 0x9F hold typographic
+    /// characters (`€`, `‘`, `—`, etc.) instead of C1 control codes.
+    Windows1252,
+}
+
+// what 0x80..=0x9F stand for in windows-1252 (0x81, 0x8D, 0x8F, 0x90 and 0x9D
+// are unassigned)
+const WINDOWS_1252_HIGH: [Option<char>; 32] = [
+    Some('€'),
+    None,
+    Some('‚'),
+    Some('ƒ'),
+    Some('„'),
+    Some('…'),
+    Some('†'),
+    Some('‡'),
+    Some('ˆ'),
+    Some('‰'),
+    Some('Š'),
+    Some('‹'),
+    Some('Œ'),
+    None,
+    Some('Ž'),
+    None,
+    None,
+    Some('‘'),
+    Some('’'),
+    Some('“'),
+    Some('”'),
+    Some('•'),
+    Some('–'),
+    Some('—'),
+    Some('˜'),
+    Some('™'),
+    Some('š'),
+    Some('›'),
+    Some('œ'),
+    None,
+    Some('ž'),
+    Some('Ÿ'),
+];
+
+impl Charset {
+    /// Returns the byte `c` is encoded as, if it can be represented.
+    pub fn encode_char(self, c: char) -> Option<u8> {
+        match (self, c as u32) {
+            (_, 0..=0x7f) => Some(c as u8),
+            (Charset::Latin1, 0x80..=0xff) => Some(c as u8),
+            (Charset::Windows1252, 0xa0..=0xff) => Some(c as u8),
+            (Charset::Windows1252, _) => WINDOWS_1252_HIGH
+                .iter()
+                .position(|&high| high == Some(c))
+                .map(|i| 0x80 + i as u8),
+            (Charset::Latin1, _) => None,
+        }
+    }
+}
+
+/// Wraps a [JsonSerializerWriter], and transcodes the (UTF-8) JSON written to
+/// it to a single-byte [Charset].
+///
+/// Characters that can't be represented are written as `\u` escapes (with
+/// surrogate pairs outside of the Basic Multilingual Plane), so the output is
+/// still valid JSON that decodes to the same strings.
+///
+/// ```rust
+/// use merde_json::{Charset, JsonSerializer, TranscodingWriter};
+/// use merde_core::InfallibleSerializer;
+///
+/// let mut buf = Vec::new();
+/// JsonSerializer::new(TranscodingWriter::new(&mut buf, Charset::Latin1))
+///     .serialize_infallible(&"café €5");
+/// assert_eq!(buf, b"\"caf\xe9 \\u20ac5\"");
+/// ```
+pub struct TranscodingWriter<W> {
+    w: W,
+    charset: Charset,
+    // an UTF-8 sequence split across two writes
+    partial: Vec<u8>,
+}
+
+impl<W> TranscodingWriter<W>
+where
+    W: JsonSerializerWriter,
+{
+    /// Wraps `w`, so that everything written through it is encoded as `charset`
+    pub fn new(w: W, charset: Charset) -> Self {
+        Self {
+            w,
+            charset,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Returns the wrapped writer
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+
+    fn transcode_char(&self, c: char, out: &mut Vec<u8>) {
+        match self.charset.encode_char(c) {
+            Some(b) => out.push(b),
+            None => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(out, "\\u{:04x}", unit).unwrap();
+                }
+            }
+        }
+    }
+}
+
+impl<W> JsonSerializerWriter for TranscodingWriter<W>
+where
+    W: JsonSerializerWriter,
+{
+    async fn extend_from_slice(&mut self, mut slice: &[u8]) -> Result<(), std::io::Error> {
+        let mut out = Vec::with_capacity(slice.len());
+
+        if !self.partial.is_empty() {
+            let missing = utf8_len(self.partial[0]) - self.partial.len();
+            let take = missing.min(slice.len());
+            self.partial.extend_from_slice(&slice[..take]);
+            slice = &slice[take..];
+            if take < missing {
+                return Ok(());
+            }
+            let c = decode_char(&self.partial)?;
+            self.partial.clear();
+            self.transcode_char(c, &mut out);
+        }
+
+        let mut i = 0;
+        while i < slice.len() {
+            let b = slice[i];
+            if b.is_ascii() {
+                out.push(b);
+                i += 1;
+                continue;
+            }
+            let len = utf8_len(b);
+            if i + len > slice.len() {
+                self.partial.extend_from_slice(&slice[i..]);
+                break;
+            }
+            let c = decode_char(&slice[i..i + len])?;
+            self.transcode_char(c, &mut out);
+            i += len;
+        }
+
+        self.w.extend_from_slice(&out).await
+    }
+}
+
+impl InfallibleSerializer for JsonSerializer<TranscodingWriter<&mut Vec<u8>>> {}
+
+fn utf8_len(first: u8) -> usize {
+    match first {
+        0xf0.. => 4,
+        0xe0.. => 3,
+        _ => 2,
+    }
+}
+
+fn decode_char(bytes: &[u8]) -> Result<char, std::io::Error> {
+    std::str::from_utf8(bytes)
+        .ok()
+        .and_then(|s| s.chars().next())
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use merde_core::{MetastackExt, Value};
+
+    use super::*;
+
+    fn transcode(value: &Value, charset: Charset) -> Vec<u8> {
+        let mut buf = Vec::new();
+        JsonSerializer::new(TranscodingWriter::new(&mut buf, charset)).serialize_infallible(value);
+        buf
+    }
+
+    #[test]
+    fn test_transcode() {
+        let value = Value::from("Zoë, 5€, 🦀");
+        let latin1 = transcode(&value, Charset::Latin1);
+        assert_eq!(latin1, b"\"Zo\xeb, 5\\u20ac, \\ud83e\\udd80\"");
+        assert_eq!(
+            transcode(&value, Charset::Windows1252),
+            b"\"Zo\xeb, 5\x80, \\ud83e\\udd80\""
+        );
+
+        // decoding as latin-1 gives back JSON for the same string
+        let decoded: String = latin1.iter().map(|&b| b as char).collect();
+        let back: String = crate::from_str(&decoded).unwrap();
+        assert_eq!(back, "Zoë, 5€, 🦀");
+    }
+
+    #[test]
+    fn test_split_sequences() {
+        let mut buf = Vec::new();
+        let mut w = TranscodingWriter::new(&mut buf, Charset::Windows1252);
+        for chunk in "a—é".as_bytes().chunks(1) {
+            w.extend_from_slice(chunk)
+                .run_sync_with_metastack()
+                .unwrap();
+        }
+        assert_eq!(buf, b"a\x97\xe9");
+    }
+}
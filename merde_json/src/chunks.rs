@@ -0,0 +1,171 @@
+use std::{cell::RefCell, rc::Rc, task::Poll};
+
+use merde_core::{DynSerialize, DynSerializer, MerdeError, MetastackDriver};
+
+use crate::{JsonSerializer, JsonSerializerWriter};
+
+/// Serializes a value as JSON, a fixed number of bytes at a time, see [crate::to_chunks].
+///
+/// Serialization is suspended whenever a chunk is full, and only picks up
+/// where it left off when the next chunk is asked for: the bytes that haven't
+/// been produced yet only exist as the serializer's state, so memory use is
+/// bounded by the chunk size (and the nesting depth of the value), not by the
+/// size of the output.
+///
+/// Every chunk is exactly `chunk_size` bytes long, except the last one. Chunk
+/// boundaries don't care about JSON syntax or UTF-8: they can fall in the middle
+/// of a string, or of a multi-byte character.
+pub struct JsonChunks<'a> {
+    driver: Option<MetastackDriver<'a, Result<(), MerdeError<'static>>>>,
+    chunk: Rc<RefCell<Vec<u8>>>,
+    chunk_size: usize,
+}
+
+impl<'a> JsonChunks<'a> {
+    pub(crate) fn new(value: &'a dyn DynSerialize, chunk_size: usize) -> Self {
+        let chunk_size = chunk_size.max(1);
+        let chunk = Rc::new(RefCell::new(Vec::with_capacity(chunk_size)));
+        let w = ChunkWriter {
+            chunk: chunk.clone(),
+            chunk_size,
+        };
+        let driver = MetastackDriver::new(async move {
+            let mut s = JsonSerializer::new(w);
+            let s: &mut dyn DynSerializer = &mut s;
+            value.dyn_serialize(s).await
+        });
+        Self {
+            driver: Some(driver),
+            chunk,
+            chunk_size,
+        }
+    }
+
+    /// Returns the next chunk, `None` once everything has been written, or the
+    /// error serialization stopped with.
+    pub fn next_chunk(&mut self) -> Option<Result<Vec<u8>, MerdeError<'static>>> {
+        let driver = self.driver.as_mut()?;
+        let take_chunk = || self.chunk.replace(Vec::with_capacity(self.chunk_size));
+
+        match driver.resume() {
+            Poll::Pending => Some(Ok(take_chunk())),
+            Poll::Ready(res) => {
+                self.driver = None;
+                match res {
+                    Ok(()) => Some(take_chunk()).filter(|c| !c.is_empty()).map(Ok),
+                    Err(e) => Some(Err(e)),
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for JsonChunks<'_> {
+    type Item = Result<Vec<u8>, MerdeError<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk()
+    }
+}
+
+impl std::fmt::Debug for JsonChunks<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonChunks")
+            .field("done", &self.driver.is_none())
+            .field("buffered", &self.chunk.borrow().len())
+            .finish()
+    }
+}
+
+struct ChunkWriter {
+    chunk: Rc<RefCell<Vec<u8>>>,
+    chunk_size: usize,
+}
+
+impl JsonSerializerWriter for ChunkWriter {
+    async fn extend_from_slice(&mut self, mut slice: &[u8]) -> Result<(), std::io::Error> {
+        loop {
+            {
+                let mut chunk = self.chunk.borrow_mut();
+                let n = (self.chunk_size - chunk.len()).min(slice.len());
+                chunk.extend_from_slice(&slice[..n]);
+                slice = &slice[n..];
+            }
+            if slice.is_empty() {
+                return Ok(());
+            }
+
+            // the chunk is full: suspend until `next_chunk` has taken it
+            let mut suspended = false;
+            std::future::poll_fn(|_| {
+                if suspended {
+                    Poll::Ready(())
+                } else {
+                    suspended = true;
+                    Poll::Pending
+                }
+            })
+            .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use merde_core::{Array, Value};
+
+    use crate::to_vec;
+
+    use super::*;
+
+    #[test]
+    fn test_chunks() {
+        let value: Vec<String> = (0..100).map(|i| format!("item \"{i}\" ✨")).collect();
+        let whole = to_vec(&value).unwrap();
+
+        for chunk_size in [1, 7, 64, whole.len(), whole.len() + 1] {
+            let chunks = JsonChunks::new(&value, chunk_size)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let (last, full) = chunks.split_last().unwrap();
+            assert!(full.iter().all(|c| c.len() == chunk_size));
+            assert!(!last.is_empty() && last.len() <= chunk_size);
+            assert_eq!(chunks.concat(), whole);
+        }
+    }
+
+    #[test]
+    fn test_chunks_deep() {
+        // suspends inside values that were moved to an emptier stack
+        std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(|| {
+                let mut value = Value::Null;
+                for _ in 0..10_000 {
+                    value = Value::Array(Array(vec![value]));
+                }
+                let mut out = Vec::new();
+                for chunk in JsonChunks::new(&value, 1000) {
+                    out.extend(chunk.unwrap());
+                }
+                assert_eq!(out, to_vec(&value).unwrap());
+
+                // dropping a suspended serialization is fine too
+                let mut chunks = JsonChunks::new(&value, 1000);
+                chunks.next_chunk().unwrap().unwrap();
+                drop(chunks);
+
+                drop_nested(value);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    // dropping 10k levels of nesting recursively would overflow the stack
+    fn drop_nested(mut value: Value) {
+        while let Value::Array(mut a) = value {
+            value = a.pop().unwrap_or(Value::Null);
+        }
+    }
+}
@@ -1,16 +1,28 @@
 //! An experimental JSON deserializer implementation
 
-use merde_core::{ArrayStart, CowStr, Deserializer, Event, MapStart, MerdeError};
+use alloc::{collections::VecDeque, string::ToString, vec::Vec};
+use core::ops::Range;
+use merde_core::{
+    ArrayStart, CowStr, Deserializer, DuplicateKeyFilter, DuplicateKeyPolicy, Event, EventType,
+    MapStart, MerdeError, TypeHint,
+};
 
-use crate::jiter_lite::{errors::JiterError, jiter::Jiter, parse::Peek};
+use crate::jiter_lite::{
+    errors::{JiterError, JiterErrorType, JsonErrorType, LinePosition},
+    jiter::Jiter,
+    parse::Peek,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum StackItem<'s> {
-    ObjectKey(Option<CowStr<'s>>),
+    // the first key is read along with the `{`, and so is where it is
+    ObjectKey(Option<(CowStr<'s>, Range<usize>)>),
     ObjectValue,
-    ObjectEnd,
+    // the `}` read along with the `{` ends there
+    ObjectEnd(usize),
     Array(Option<Peek>),
-    ArrayEnd,
+    // the `]` read along with the `[` ends there
+    ArrayEnd(usize),
 }
 
 /// A JSON deserializer
@@ -18,11 +30,21 @@ pub struct JsonDeserializer<'s> {
     source: &'s str,
     jiter: Jiter<'s>,
     stack: Vec<StackItem<'s>>,
-    starter: Option<Event<'s>>,
+    // events that were put back, and their spans
+    starters: VecDeque<(Event<'s>, Option<Range<usize>>)>,
+    keys: DuplicateKeyFilter<'s>,
+    // the last number event returned, and its text, in case it's put back and
+    // asked for again with `TypeHint::RawNumber`
+    last_number: Option<(Event<'s>, &'s str)>,
+    // where the value behind the last event returned starts, and how deep the
+    // stack was before it, in case it's put back and then skipped
+    last_value: Option<(usize, usize)>,
+    // where the last event returned is in the source
+    span: Option<Range<usize>>,
 }
 
-impl std::fmt::Debug for JsonDeserializer<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for JsonDeserializer<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("JsonDeserializer")
             .field("source_len", &self.source)
             .field("stack_size", &self.stack.len())
@@ -38,12 +60,74 @@ impl<'s> JsonDeserializer<'s> {
             source,
             jiter,
             stack: Default::default(),
-            starter: None,
+            starters: VecDeque::new(),
+            keys: Default::default(),
+            last_number: None,
+            last_value: None,
+            span: None,
         }
     }
+
+    /// Sets what to do when an object has the same key more than once,
+    /// like `{"a":1,"a":2}`. Defaults to [`DuplicateKeyPolicy::LastWins`].
+    pub fn with_duplicate_key_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.keys = DuplicateKeyFilter::new(policy);
+        self
+    }
+
+    /// Moves to the value at `path` (object keys, or indices for arrays),
+    /// skipping over everything before it without decoding it. Returns false
+    /// if there's no such value. Must be called before anything's been read.
+    pub(crate) fn seek(&mut self, path: &[&str]) -> Result<bool, MerdeError<'s>> {
+        let err = |e| jiter_error(self.source, e);
+        for segment in path {
+            let peek = self.jiter.peek().map_err(err)?;
+            if peek == Peek::Object {
+                // the first key that matches wins
+                let mut found = self
+                    .jiter
+                    .known_object()
+                    .map_err(err)?
+                    .map(|k| k == *segment);
+                loop {
+                    match found {
+                        None => return Ok(false),
+                        Some(true) => break,
+                        Some(false) => {
+                            let peek = self.jiter.peek().map_err(err)?;
+                            self.jiter.known_skip(peek).map_err(err)?;
+                            found = self.jiter.next_key().map_err(err)?.map(|k| k == *segment);
+                        }
+                    }
+                }
+            } else if peek == Peek::Array {
+                let Ok(index) = segment.parse::<usize>() else {
+                    return Ok(false);
+                };
+                let mut next = self.jiter.known_array().map_err(err)?;
+                for _ in 0..index {
+                    let Some(peek) = next else {
+                        return Ok(false);
+                    };
+                    self.jiter.known_skip(peek).map_err(err)?;
+                    next = self.jiter.array_step().map_err(err)?;
+                }
+                if next.is_none() {
+                    return Ok(false);
+                }
+            } else {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
 }
 
-fn jiter_error(source: &str, err: JiterError) -> MerdeError<'_> {
+pub(crate) fn jiter_error(source: &str, err: JiterError) -> MerdeError<'_> {
+    if let Some(err) = disallowed_syntax(source, &err) {
+        return err;
+    }
+
     MerdeError::StringParsingError {
         format: "JSON",
         index: err.index,
@@ -52,16 +136,163 @@ fn jiter_error(source: &str, err: JiterError) -> MerdeError<'_> {
     }
 }
 
+// Comments and trailing commas are common in hand-written JSON (and allowed
+// by JSON5, JSONC...), so they get an error that says what's wrong
+fn disallowed_syntax(source: &str, err: &JiterError) -> Option<MerdeError<'static>> {
+    let bytes = source.as_bytes();
+    let (syntax, index, help) = match err.error_type {
+        JiterErrorType::JsonError(JsonErrorType::TrailingComma) => (
+            "trailing commas",
+            source.get(..err.index)?.rfind(',')?,
+            "remove the comma after the last item",
+        ),
+        _ if bytes.get(err.index) == Some(&b'/')
+            && matches!(bytes.get(err.index + 1), Some(b'/' | b'*')) =>
+        {
+            (
+                "comments",
+                err.index,
+                "remove it, or parse the document with `JsoncDocument`",
+            )
+        }
+        _ => return None,
+    };
+
+    let LinePosition { line, column } = LinePosition::find(bytes, index);
+    Some(MerdeError::DisallowedSyntax {
+        format: "JSON",
+        syntax,
+        index,
+        line,
+        column,
+        help: Some(help),
+    })
+}
+
 impl<'s> Deserializer<'s> for JsonDeserializer<'s> {
     async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
-        if let Some(ev) = self.starter.take() {
+        self.next_filtered(false)
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.starters.push_front((ev, self.span.take()));
+        Ok(())
+    }
+
+    // numbers are returned as they're written when asked for a `RawNumber`
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        if hint != TypeHint::RawNumber {
+            return self.next_filtered(false);
+        }
+        if let Some(ev) = self.pop_starter() {
+            // the number was read without the hint (e.g. by `Option<T>` peeking)
+            return Ok(match self.last_number.take() {
+                Some((last, raw)) if last == ev => Event::Str(CowStr::Borrowed(raw)),
+                _ => ev,
+            });
+        }
+        self.next_filtered(true)
+    }
+
+    // the value is skipped over by the jiter
+    async fn skip_value(&mut self) -> Result<(), MerdeError<'s>> {
+        if self.skip_raw()?.is_some() {
+            return Ok(());
+        }
+        let mut depth = 0usize;
+        loop {
+            match self.next_filtered(false)? {
+                Event::MapStart(_) | Event::ArrayStart(_) => depth += 1,
+                Event::MapEnd | Event::ArrayEnd if depth > 0 => depth -= 1,
+                ev @ (Event::MapEnd | Event::ArrayEnd) => {
+                    return Err(MerdeError::UnexpectedEvent {
+                        got: EventType::from(&ev),
+                        expected: &[],
+                        help: Some("(While skipping a value)".to_string()),
+                    })
+                }
+                _ => {}
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn next_raw(
+        &mut self,
+        format: &'static str,
+    ) -> Result<Option<CowStr<'s>>, MerdeError<'s>> {
+        if format != "json" {
+            return Ok(None);
+        }
+        Ok(self.skip_raw()?.map(CowStr::Borrowed))
+    }
+
+    // keys and scalars span their text (quotes included), maps and arrays
+    // their opening and closing delimiters
+    fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+}
+
+impl<'s> JsonDeserializer<'s> {
+    /// Skips the next value with the jiter, and returns its text. Returns
+    /// `None` without consuming anything if the next event isn't the start of
+    /// a value (like `ArrayEnd`), or if it can't be skipped that way.
+    fn skip_raw(&mut self) -> Result<Option<&'s str>, MerdeError<'s>> {
+        // skipped values would get around duplicate key filtering
+        if self.keys.policy() != DuplicateKeyPolicy::LastWins || self.starters.len() > 1 {
+            return Ok(None);
+        }
+
+        let ev = match self.pop_starter() {
+            Some(ev) => ev,
+            None => self.next_event(false)?,
+        };
+        let Some((start, depth)) = self.last_value.take() else {
+            self.starters.push_front((ev, self.span.take()));
+            return Ok(None);
+        };
+
+        self.jiter.rewind(start);
+        self.stack.truncate(depth);
+        let peek = self.jiter.peek().map_err(|e| jiter_error(self.source, e))?;
+        self.jiter
+            .known_skip(peek)
+            .map_err(|e| jiter_error(self.source, e))?;
+        self.span = Some(start..self.jiter.current_index());
+        Ok(Some(&self.source[start..self.jiter.current_index()]))
+    }
+
+    fn pop_starter(&mut self) -> Option<Event<'s>> {
+        let (ev, span) = self.starters.pop_front()?;
+        self.span = span;
+        Some(ev)
+    }
+
+    fn next_filtered(&mut self, raw_numbers: bool) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.pop_starter() {
             return Ok(ev);
         }
 
+        loop {
+            let ev = self.next_event(raw_numbers)?;
+            if let Some(ev) = self.keys.filter(ev)? {
+                return Ok(ev);
+            }
+        }
+    }
+
+    fn next_event(&mut self, raw_numbers: bool) -> Result<Event<'s>, MerdeError<'s>> {
+        self.last_value = None;
+        // the end of a map or an array is the character just read
+        let end_span = |jiter: &Jiter<'s>| Some(jiter.current_index() - 1..jiter.current_index());
         let peek: Option<Peek> = match self.stack.pop() {
             Some(StackItem::ObjectKey(maybe_key)) => match maybe_key {
-                Some(key) => {
+                Some((key, span)) => {
                     self.stack.push(StackItem::ObjectValue);
+                    self.span = Some(span);
                     return Ok(Event::Str(key));
                 }
                 None => match self
@@ -72,9 +303,11 @@ impl<'s> Deserializer<'s> for JsonDeserializer<'s> {
                     Some(key) => {
                         self.stack.push(StackItem::ObjectValue);
                         let key = cowify(self.source.as_bytes(), key);
+                        self.span = Some(self.jiter.last_key_span());
                         return Ok(Event::Str(key));
                     }
                     None => {
+                        self.span = end_span(&self.jiter);
                         return Ok(Event::MapEnd);
                     }
                 },
@@ -83,7 +316,8 @@ impl<'s> Deserializer<'s> for JsonDeserializer<'s> {
                 self.stack.push(StackItem::ObjectKey(None));
                 None
             }
-            Some(StackItem::ObjectEnd) => {
+            Some(StackItem::ObjectEnd(end)) => {
+                self.span = Some(end - 1..end);
                 return Ok(Event::MapEnd);
             }
             Some(StackItem::Array(maybe_peek)) => match maybe_peek {
@@ -102,12 +336,14 @@ impl<'s> Deserializer<'s> for JsonDeserializer<'s> {
                             Some(peek)
                         }
                         None => {
+                            self.span = end_span(&self.jiter);
                             return Ok(Event::ArrayEnd);
                         }
                     }
                 }
             },
-            Some(StackItem::ArrayEnd) => {
+            Some(StackItem::ArrayEnd(end)) => {
+                self.span = Some(end - 1..end);
                 return Ok(Event::ArrayEnd);
             }
             None => None,
@@ -117,6 +353,8 @@ impl<'s> Deserializer<'s> for JsonDeserializer<'s> {
             Some(ev) => ev,
             None => self.jiter.peek().map_err(|e| jiter_error(self.source, e))?,
         };
+        let start = self.jiter.current_index();
+        self.last_value = Some((start, self.stack.len()));
 
         let ev = if peek == Peek::Null {
             self.jiter
@@ -134,10 +372,13 @@ impl<'s> Deserializer<'s> for JsonDeserializer<'s> {
                 .jiter
                 .known_float(peek)
                 .map_err(|err| jiter_error(self.source, err))?;
-            if num.fract() == 0.0 && num >= i64::MIN as f64 && num <= i64::MAX as f64 {
-                Event::I64(num as i64)
+            let raw = &self.source[start..self.jiter.current_index()];
+            if raw_numbers {
+                Event::Str(CowStr::Borrowed(raw))
             } else {
-                Event::F64(num)
+                let ev = number_event(num, raw);
+                self.last_number = Some((ev.clone(), raw));
+                ev
             }
         } else if peek == Peek::String {
             let s = self
@@ -154,9 +395,11 @@ impl<'s> Deserializer<'s> for JsonDeserializer<'s> {
             if let Some(peek) = peek {
                 self.stack.push(StackItem::Array(Some(peek)));
             } else {
-                self.stack.push(StackItem::ArrayEnd);
+                self.stack
+                    .push(StackItem::ArrayEnd(self.jiter.current_index()));
             }
-            Event::ArrayStart(ArrayStart { size_hint: None })
+            self.span = Some(start..start + 1);
+            return Ok(Event::ArrayStart(ArrayStart { size_hint: None }));
         } else if peek == Peek::Object {
             let key = self
                 .jiter
@@ -164,23 +407,45 @@ impl<'s> Deserializer<'s> for JsonDeserializer<'s> {
                 .map_err(|err| jiter_error(self.source, err))?;
             if let Some(key) = key {
                 let key = cowify(self.source.as_bytes(), key);
-                self.stack.push(StackItem::ObjectKey(Some(key)));
+                let span = self.jiter.last_key_span();
+                self.stack.push(StackItem::ObjectKey(Some((key, span))));
             } else {
-                self.stack.push(StackItem::ObjectEnd);
+                self.stack
+                    .push(StackItem::ObjectEnd(self.jiter.current_index()));
             }
-            Event::MapStart(MapStart { size_hint: None })
+            self.span = Some(start..start + 1);
+            return Ok(Event::MapStart(MapStart { size_hint: None }));
         } else {
-            panic!("Unknown peek: {:?}", peek);
+            return Err(jiter_error(self.source, self.jiter.expected_value_error()));
         };
+        self.span = Some(start..self.jiter.current_index());
         Ok(ev)
     }
+}
 
-    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
-        if self.starter.is_some() {
-            return Err(MerdeError::PutBackCalledTwice);
+fn number_event(num: f64, raw: &str) -> Event<'static> {
+    if num.fract() == 0.0 && (-9007199254740992.0..9007199254740992.0).contains(&num) {
+        // below 2^53, the float is exact
+        Event::I64(num as i64)
+    } else if num.fract() == 0.0 {
+        // the float may have been rounded: if the number is written as an
+        // integer, read it again, as the smallest type that fits.
+        if let Ok(i) = raw.parse() {
+            Event::I64(i)
+        } else if let Ok(u) = raw.parse() {
+            Event::U64(u)
+        } else if let Ok(i) = raw.parse() {
+            Event::I128(i)
+        } else if let Ok(u) = raw.parse() {
+            Event::U128(u)
+        } else if num >= i64::MIN as f64 && num <= i64::MAX as f64 {
+            // like `1e18`
+            Event::I64(num as i64)
+        } else {
+            Event::F64(num)
         }
-        self.starter = Some(ev);
-        Ok(())
+    } else {
+        Event::F64(num)
     }
 }
 
@@ -197,7 +462,7 @@ fn _assert_dyn_deser_ext() {
 pub(crate) fn cowify<'j>(src: &'j [u8], s: &str) -> CowStr<'j> {
     if src.as_ptr_range().contains(&s.as_ptr()) {
         CowStr::Borrowed(unsafe {
-            std::str::from_utf8_unchecked(std::slice::from_raw_parts(s.as_ptr(), s.len()))
+            core::str::from_utf8_unchecked(core::slice::from_raw_parts(s.as_ptr(), s.len()))
         })
     } else {
         CowStr::Owned(s.into())
@@ -210,8 +475,8 @@ mod tests {
 
     use super::JsonDeserializer;
     use merde_core::{
-        Array, CowStr, Deserialize, DynDeserializer, DynDeserializerExt as _, Event, EventType,
-        Map, MerdeError,
+        Array, CowStr, Deserialize, Deserializer, DynDeserializer, DynDeserializerExt as _, Event,
+        EventType, Map, MerdeError, MetastackExt as _, Spanned, Value,
     };
     use merde_loggingserializer::LoggingDeserializer;
 
@@ -332,6 +597,101 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_put_back_several_events() {
+        let input = r#"{"height": 100, "kind": true}"#;
+        let mut deser = JsonDeserializer::new(input);
+
+        let map_start = Deserializer::next(&mut deser)
+            .run_sync_with_metastack()
+            .unwrap();
+        let key = Deserializer::next(&mut deser)
+            .run_sync_with_metastack()
+            .unwrap();
+        assert_eq!(key, Event::Str("height".into()));
+
+        // last in, first out
+        Deserializer::put_back(&mut deser, key).unwrap();
+        Deserializer::put_back(&mut deser, map_start).unwrap();
+        let sample: Sample = deser.deserialize().unwrap();
+        assert_eq!(
+            sample,
+            Sample {
+                height: 100,
+                kind: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        use merde_core::DuplicateKeyPolicy;
+        use std::collections::HashMap;
+
+        let input = r#"{"a":1,"b":[{"a":2}],"a":3}"#;
+
+        let mut de = JsonDeserializer::new(input);
+        let map = de
+            .deserialize::<HashMap<String, merde_core::Value>>()
+            .unwrap();
+        assert_eq!(map["a"], merde_core::Value::I64(3));
+
+        let mut de =
+            JsonDeserializer::new(input).with_duplicate_key_policy(DuplicateKeyPolicy::FirstWins);
+        let map = de
+            .deserialize::<HashMap<String, merde_core::Value>>()
+            .unwrap();
+        assert_eq!(map["a"], merde_core::Value::I64(1));
+        assert_eq!(map.len(), 2);
+
+        let mut de =
+            JsonDeserializer::new(input).with_duplicate_key_policy(DuplicateKeyPolicy::Error);
+        assert!(matches!(
+            de.deserialize::<merde_core::Value>(),
+            Err(MerdeError::DuplicateKey(k)) if k == "a"
+        ));
+    }
+
+    #[test]
+    fn test_borrowed_keys() {
+        use merde_core::{DuplicateKeyPolicy, Value};
+        use std::{borrow::Cow, collections::HashMap};
+
+        let input = r#"{"plain":{"nested":1},"esc\u0061ped":2}"#;
+        let in_source = |k: &str| input.as_bytes().as_ptr_range().contains(&k.as_ptr());
+
+        let map = JsonDeserializer::new(input)
+            .deserialize::<HashMap<CowStr, Value>>()
+            .unwrap();
+        for (k, v) in &map {
+            match k.as_ref() {
+                "plain" => {
+                    assert!(matches!(k, CowStr::Borrowed(_)) && in_source(k));
+                    let (nested, _) = v.as_map().unwrap().iter().next().unwrap();
+                    assert!(matches!(nested, CowStr::Borrowed(_)) && in_source(nested));
+                }
+                // escaped keys have to be unescaped somewhere
+                "escaped" => assert!(matches!(k, CowStr::Owned(_))),
+                _ => unreachable!(),
+            }
+        }
+
+        let map = JsonDeserializer::new(input)
+            .deserialize::<HashMap<Cow<str>, Value>>()
+            .unwrap();
+        let (k, _) = map.get_key_value("plain").unwrap();
+        assert!(matches!(k, Cow::Borrowed(_)) && in_source(k));
+
+        // the duplicate key filter keeps track of keys it has seen, but must
+        // not copy them in the process
+        let map = JsonDeserializer::new(input)
+            .with_duplicate_key_policy(DuplicateKeyPolicy::Error)
+            .deserialize::<Map>()
+            .unwrap();
+        let (k, _) = map.get_key_value(&CowStr::from("plain")).unwrap();
+        assert!(matches!(k, CowStr::Borrowed(_)) && in_source(k));
+    }
+
     #[test]
     fn test_cowify() {
         let src = "That's a subset!";
@@ -342,4 +702,273 @@ mod tests {
         let s = "indeed not";
         assert_eq!(cowify(src.as_bytes(), s), CowStr::Owned(s.into()));
     }
+
+    #[test]
+    fn test_invalid_value_start() {
+        use merde_core::Value;
+
+        for input in ["\u{1c}", "[1, @]", r#"{"a": #}"#] {
+            assert!(crate::from_str::<Value>(input).is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_wide_ints() {
+        use merde_core::Value;
+
+        // above 2^53, integers used to be rounded through an f64
+        assert_eq!(
+            crate::from_str::<i64>("9007199254740993").unwrap(),
+            9007199254740993
+        );
+        assert_eq!(
+            crate::from_str::<u64>("18446744073709551615").unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            crate::from_str::<Value>("18446744073709551615").unwrap(),
+            Value::U64(u64::MAX)
+        );
+        assert_eq!(
+            crate::from_str::<i64>("1e18").unwrap(),
+            1_000_000_000_000_000_000
+        );
+
+        for i in [i128::MIN, i128::MAX, -1, 1 << 64] {
+            let json = crate::to_string(&i).unwrap();
+            assert_eq!(json, i.to_string());
+            assert_eq!(crate::from_str::<i128>(&json).unwrap(), i);
+        }
+        let json = crate::to_string(&u128::MAX).unwrap();
+        assert_eq!(crate::from_str::<u128>(&json).unwrap(), u128::MAX);
+        assert!(crate::from_str::<u64>(&json).is_err());
+        // values only have 64-bit integers, and won't round the others
+        assert!(matches!(
+            crate::from_str::<Value>(&json),
+            Err(MerdeError::OutOfRange)
+        ));
+
+        // wider than 128 bits, this is a float again
+        assert_eq!(
+            crate::from_str::<f64>("1000000000000000000000000000000000000000").unwrap(),
+            1e39
+        );
+    }
+
+    #[test]
+    fn test_raw_numbers() {
+        use merde_core::RawNumber;
+
+        let input =
+            r#"[0.30000000000000000001, 12.50, null, "1e3", 123456789012345678901234567890]"#;
+        let numbers: Vec<Option<RawNumber>> = crate::from_str(input).unwrap();
+        let numbers: Vec<_> = numbers
+            .iter()
+            .map(|n| n.as_ref().map(|n| n.as_str()))
+            .collect();
+        assert_eq!(
+            numbers,
+            [
+                Some("0.30000000000000000001"),
+                Some("12.50"),
+                None,
+                Some("1e3"),
+                Some("123456789012345678901234567890"),
+            ]
+        );
+
+        assert!(crate::from_str::<RawNumber>(r#""twelve""#).is_err());
+    }
+
+    #[test]
+    fn test_skip_value() {
+        let input = r#"[{"a": [1, {"b": "\u0032"}]}, "x", 3, {}]"#;
+        let mut deser = JsonDeserializer::new(input);
+        let de: &mut dyn DynDeserializer = &mut deser;
+        async {
+            de.next().await?.into_array_start()?;
+            de.skip_value().await?;
+            // put back, like `Option<T>` does when peeking
+            let ev = de.next().await?;
+            assert_eq!(ev, Event::Str("x".into()));
+            de.put_back(ev)?;
+            de.skip_value().await?;
+            assert_eq!(de.next().await?, Event::I64(3));
+            // started, then skipped
+            de.next().await?.into_map_start()?;
+            assert_eq!(de.next().await?, Event::MapEnd);
+            assert!(de.skip_value().await.is_err());
+            Ok::<_, MerdeError>(())
+        }
+        .run_sync_with_metastack()
+        .unwrap();
+
+        // same thing, without the fast path
+        let mut deser = JsonDeserializer::new(input)
+            .with_duplicate_key_policy(merde_core::DuplicateKeyPolicy::FirstWins);
+        let de: &mut dyn DynDeserializer = &mut deser;
+        async {
+            de.next().await?.into_array_start()?;
+            de.skip_value().await?;
+            assert_eq!(de.next().await?, Event::Str("x".into()));
+            Ok::<_, MerdeError>(())
+        }
+        .run_sync_with_metastack()
+        .unwrap();
+
+        assert!(crate::from_str::<Vec<u8>>(r#"[1, {"a": tru}]"#).is_err());
+    }
+
+    #[test]
+    fn test_get_path() {
+        let input = r#"{
+            "items": [{"tags": ["x", {"deep": [1, 2.5, "three"]}]}, {"id": 7}],
+            "meta\u0064ata": {"id": "first"},
+            "metadata": {"id": "second"}
+        } this isn't JSON"#;
+
+        let id: Option<CowStr> = crate::get_path(input, &["metadata", "id"]).unwrap();
+        assert_eq!(id.as_deref(), Some("first"));
+        let id: Option<u32> = crate::get_path(input, &["items", "1", "id"]).unwrap();
+        assert_eq!(id, Some(7));
+        let items: Option<Vec<Map>> = crate::get_path(input, &["items"]).unwrap();
+        assert_eq!(items.unwrap().len(), 2);
+
+        for missing in [
+            &["nope"][..],
+            &["items", "2"],
+            &["items", "first"],
+            &["metadata", "id", "more"],
+        ] {
+            assert!(crate::get_path::<Map>(input, missing).unwrap().is_none());
+        }
+
+        // syntax errors before the value are still caught
+        assert!(crate::get_path::<u32>(r#"{"a": [1, 2}, "b": 3}"#, &["b"]).is_err());
+    }
+
+    #[test]
+    fn test_spans() {
+        let input = r#" {"a": [1, -2.5e-3, "x\"y"], "bé" : {}, "c": [], "d": {"e": null}} "#;
+        let mut de = JsonDeserializer::new(input);
+        let mut spans = vec![];
+        for _ in 0..17 {
+            let ev = Deserializer::next(&mut de).run_sync_flat().unwrap();
+            let span = Deserializer::span(&de).unwrap();
+            spans.push((EventType::from(&ev), &input[span]));
+        }
+        assert_eq!(
+            spans,
+            vec![
+                (EventType::MapStart, "{"),
+                (EventType::Str, r#""a""#),
+                (EventType::ArrayStart, "["),
+                (EventType::I64, "1"),
+                (EventType::Float, "-2.5e-3"),
+                (EventType::Str, r#""x\"y""#),
+                (EventType::ArrayEnd, "]"),
+                (EventType::Str, r#""bé""#),
+                (EventType::MapStart, "{"),
+                (EventType::MapEnd, "}"),
+                (EventType::Str, r#""c""#),
+                (EventType::ArrayStart, "["),
+                (EventType::ArrayEnd, "]"),
+                (EventType::Str, r#""d""#),
+                (EventType::MapStart, "{"),
+                (EventType::Str, r#""e""#),
+                (EventType::Null, "null"),
+            ]
+        );
+
+        // events that are put back keep their span
+        let ev = Deserializer::next(&mut de).run_sync_flat().unwrap();
+        Deserializer::put_back(&mut de, ev).unwrap();
+        assert_eq!(Deserializer::span(&de), None);
+        Deserializer::next(&mut de).run_sync_flat().unwrap();
+        assert_eq!(&input[Deserializer::span(&de).unwrap()], "}");
+    }
+
+    #[test]
+    fn test_spanned() {
+        let input = r#"[1, {"a": [true]}, "x\"", [], null, 42, {"b": 3}]"#;
+        let values: Vec<Spanned<Value>> = crate::from_str(input).unwrap();
+        let texts: Vec<&str> = values
+            .iter()
+            .map(|v| &input[v.span.clone().unwrap()])
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                "1",
+                r#"{"a": [true]}"#,
+                r#""x\"""#,
+                "[]",
+                "null",
+                "42",
+                r#"{"b": 3}"#
+            ]
+        );
+        assert_eq!(*values[0], Value::I64(1));
+
+        // `Option<T>` peeks at its first event, then puts it back
+        let values: Vec<Spanned<Option<u32>>> = crate::from_str("[null, 42 ]").unwrap();
+        assert_eq!(values[0].span, Some(1..5));
+        assert_eq!(values[1].span, Some(7..9));
+        assert_eq!(values[1].value, Some(42));
+
+        // skipped values span their whole text
+        let raw: Spanned<crate::RawValue> = crate::from_str(r#" {"a": [1, 2]} "#).unwrap();
+        assert_eq!(raw.span, Some(1..14));
+
+        // spans aren't serialized
+        assert_eq!(crate::to_string(&values).unwrap(), "[null,42]");
+    }
+
+    #[test]
+    fn test_disallowed_syntax() {
+        fn syntax(input: &str) -> (&'static str, usize, usize, usize) {
+            match crate::from_str::<Value>(input) {
+                Err(MerdeError::DisallowedSyntax {
+                    format: "JSON",
+                    syntax,
+                    index,
+                    line,
+                    column,
+                    ..
+                }) => (syntax, index, line, column),
+                res => panic!("{input:?}: expected DisallowedSyntax, got {res:?}"),
+            }
+        }
+
+        assert_eq!(syntax("[1, 2,]"), ("trailing commas", 5, 1, 6));
+        assert_eq!(syntax("{\n  \"a\": 1,\n}"), ("trailing commas", 10, 2, 9));
+        assert_eq!(
+            syntax("{\"a\": [1],\n \"b\": [\n  1 ,\n  ]}"),
+            ("trailing commas", 23, 3, 5)
+        );
+
+        assert_eq!(
+            syntax("{\n  // the answer\n  \"a\": 42\n}"),
+            ("comments", 4, 2, 3)
+        );
+        assert_eq!(syntax("[1 /* one */]"), ("comments", 3, 1, 4));
+        assert_eq!(syntax("{\"a\": /* tbd */ 1}"), ("comments", 6, 1, 7));
+
+        let err = crate::from_str::<Value>("[1,\n2,\n]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "JSON does not allow trailing commas; found at line 2, column 2 \
+             (remove the comma after the last item)"
+        );
+
+        // other syntax errors are left alone
+        assert!(matches!(
+            crate::from_str::<Value>("[1 2]"),
+            Err(MerdeError::StringParsingError { .. })
+        ));
+        assert!(matches!(
+            crate::from_str::<Value>("[1, /]"),
+            Err(MerdeError::StringParsingError { .. })
+        ));
+    }
 }
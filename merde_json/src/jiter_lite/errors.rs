@@ -1,3 +1,5 @@
+use alloc::{format, string::String};
+
 /// Enum representing all possible errors in JSON syntax.
 ///
 /// Almost all of `JsonErrorType` is copied from [serde_json](https://github.com/serde-rs) so errors match
@@ -78,8 +80,8 @@ pub enum JsonErrorType {
     RecursionLimitExceeded,
 }
 
-impl std::fmt::Display for JsonErrorType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for JsonErrorType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // Messages for enum members copied from serde_json are unchanged
         match self {
             Self::FloatExpectingInt => {
@@ -141,8 +143,8 @@ impl JsonError {
     }
 }
 
-impl std::fmt::Display for JsonError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for JsonError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{} at index {}", self.error_type, self.index)
     }
 }
@@ -178,8 +180,8 @@ pub enum JsonType {
     Object,
 }
 
-impl std::fmt::Display for JsonType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for JsonType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Null => f.write_str("null"),
             Self::Bool => f.write_str("bool"),
@@ -202,8 +204,8 @@ pub enum JiterErrorType {
     },
 }
 
-impl std::fmt::Display for JiterErrorType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for JiterErrorType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::JsonError(error_type) => write!(f, "{error_type}"),
             Self::WrongType { expected, actual } => {
@@ -220,8 +222,8 @@ pub struct JiterError {
     pub index: usize,
 }
 
-impl std::fmt::Display for JiterError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for JiterError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{} at index {}", self.error_type, self.index)
     }
 }
@@ -254,8 +256,8 @@ pub struct LinePosition {
     pub column: usize,
 }
 
-impl std::fmt::Display for LinePosition {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for LinePosition {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "line {} column {}", self.line, self.column)
     }
 }
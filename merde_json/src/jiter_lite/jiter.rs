@@ -1,9 +1,11 @@
+use alloc::vec::Vec;
+
 use crate::jiter_lite as jiter;
 
 use jiter::errors::{json_error, JiterError, JsonError, JsonType};
-use jiter::number_decoder::{NumberAny, NumberFloat};
+use jiter::number_decoder::{NumberAny, NumberFloat, NumberRange};
 use jiter::parse::{Parser, Peek};
-use jiter::string_decoder::{StringDecoder, Tape};
+use jiter::string_decoder::{StringDecoder, StringDecoderRange, Tape};
 
 pub type JiterResult<T> = Result<T, JiterError>;
 
@@ -79,6 +81,85 @@ impl<'j> Jiter<'j> {
             .map_err(|e| self.maybe_number_error(e, JsonType::Float, peek))
     }
 
+    /// The position of the parser in the JSON data: right after [Jiter::peek],
+    /// that's where the next value starts.
+    pub fn current_index(&self) -> usize {
+        self.parser.index
+    }
+
+    /// Moves the parser back (or forward) to `index`, which must be the start
+    /// of a value, or right after one.
+    pub fn rewind(&mut self, index: usize) {
+        self.parser.index = index;
+    }
+
+    /// Knowing the next value starts with `peek`, skip over it (and over
+    /// everything it contains), checking its syntax without decoding it.
+    pub fn known_skip(&mut self, peek: Peek) -> JiterResult<()> {
+        // one entry per array (`false`) or object (`true`) we're in
+        let mut stack: Vec<bool> = Vec::new();
+        let mut next = Some(peek);
+        loop {
+            if let Some(peek) = next {
+                match peek {
+                    Peek::Null => self.parser.consume_null()?,
+                    Peek::True => self.parser.consume_true()?,
+                    Peek::False => self.parser.consume_false()?,
+                    Peek::String => {
+                        self.parser
+                            .consume_string::<StringDecoderRange>(&mut self.tape, false)?;
+                    }
+                    Peek::Array => {
+                        if let Some(peek) = self.parser.array_first()? {
+                            stack.push(false);
+                            next = Some(peek);
+                            continue;
+                        }
+                    }
+                    Peek::Object => {
+                        if self
+                            .parser
+                            .object_first::<StringDecoderRange>(&mut self.tape)?
+                            .is_some()
+                        {
+                            stack.push(true);
+                            next = Some(self.parser.peek()?);
+                            continue;
+                        }
+                    }
+                    _ if peek.is_num() => {
+                        self.parser
+                            .consume_number::<NumberRange>(peek.into_inner(), self.allow_inf_nan)?;
+                    }
+                    _ => return Err(self.expected_value_error()),
+                }
+            }
+
+            // a value just ended: move on to the next one in its container
+            next = match stack.last() {
+                None => return Ok(()),
+                Some(false) => self.parser.array_step()?,
+                Some(true) => match self
+                    .parser
+                    .object_step::<StringDecoderRange>(&mut self.tape)?
+                {
+                    Some(_) => Some(self.parser.peek()?),
+                    None => None,
+                },
+            };
+            if next.is_none() {
+                stack.pop();
+            }
+        }
+    }
+
+    /// Checks that there's only whitespace left in the JSON data.
+    // only used by `RawValue`, which needs std
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub fn finish(&mut self) -> JiterResult<()> {
+        self.parser.finish().map_err(Into::into)
+    }
+
     /// Knowing the next value is a string, parse it.
     pub fn known_str(&mut self) -> JiterResult<&str> {
         match self
@@ -112,6 +193,17 @@ impl<'j> Jiter<'j> {
         Ok(strs.map(|s| s.as_str()))
     }
 
+    /// Where the last key returned by `known_object` or `next_key` starts and
+    /// ends in the data, quotes included.
+    pub fn last_key_span(&self) -> core::ops::Range<usize> {
+        self.parser.key_span.clone()
+    }
+
+    /// The error for a value that starts with a character no JSON value starts with.
+    pub fn expected_value_error(&self) -> JiterError {
+        json_error!(ExpectedSomeValue, self.parser.index).into()
+    }
+
     fn wrong_type(&self, expected: JsonType, peek: Peek) -> JiterError {
         match peek {
             Peek::True | Peek::False => {
@@ -8,4 +8,6 @@ pub(crate) mod number_decoder;
 pub(crate) mod parse;
 #[cfg(target_arch = "aarch64")]
 pub(crate) mod simd_aarch64;
+#[cfg(target_arch = "x86_64")]
+pub(crate) mod simd_x86_64;
 pub(crate) mod string_decoder;
@@ -1,4 +1,4 @@
-use std::ops::Range;
+use core::ops::Range;
 
 use lexical_parse_float::{
     format as lexical_format, FromLexicalWithOptions, Options as ParseFloatOptions,
@@ -275,13 +275,15 @@ impl IntChunk {
 
     #[inline(always)]
     fn parse_big(data: &[u8], index: usize) -> (Self, usize) {
-        // TODO x86_64: use simd
-
         #[cfg(target_arch = "aarch64")]
         {
             crate::jiter_lite::simd_aarch64::decode_int_chunk(data, index)
         }
-        #[cfg(not(target_arch = "aarch64"))]
+        #[cfg(target_arch = "x86_64")]
+        {
+            crate::jiter_lite::simd_x86_64::decode_int_chunk(data, index)
+        }
+        #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
         {
             decode_int_chunk_fallback(data, index, 0)
         }
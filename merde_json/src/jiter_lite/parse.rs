@@ -1,5 +1,5 @@
-use std::fmt;
-use std::ops::Range;
+use core::fmt;
+use core::ops::Range;
 
 use crate::jiter_lite as jiter;
 
@@ -64,11 +64,17 @@ static INFINITY_REST: [u8; 7] = [b'n', b'f', b'i', b'n', b'i', b't', b'y'];
 pub(crate) struct Parser<'j> {
     data: &'j [u8],
     pub index: usize,
+    /// Where the last object key read starts and ends, quotes included
+    pub key_span: Range<usize>,
 }
 
 impl<'j> Parser<'j> {
     pub fn new(data: &'j [u8]) -> Self {
-        Self { data, index: 0 }
+        Self {
+            data,
+            index: 0,
+            key_span: 0..0,
+        }
     }
 
     #[allow(dead_code)]
@@ -84,6 +90,15 @@ impl<'j> Parser<'j> {
         }
     }
 
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub fn finish(&mut self) -> JsonResult<()> {
+        if self.eat_whitespace().is_none() {
+            Ok(())
+        } else {
+            json_err!(TrailingCharacters, self.index)
+        }
+    }
+
     pub fn array_first(&mut self) -> JsonResult<Option<Peek>> {
         self.index += 1;
         if let Some(next) = self.eat_whitespace() {
@@ -218,6 +233,7 @@ impl<'j> Parser<'j> {
         'j: 't,
     {
         let (output, index) = D::decode(self.data, self.index, tape, false)?;
+        self.key_span = self.index..index;
         self.index = index;
         if let Some(next) = self.eat_whitespace() {
             if next == b':' {
@@ -1,8 +1,8 @@
 use crate::jiter_lite as jiter;
 
-use std::mem::transmute;
+use core::mem::transmute;
 #[rustfmt::skip]
-use std::arch::aarch64::{
+use core::arch::aarch64::{
     uint8x16_t,
     uint16x8_t,
     uint32x4_t,
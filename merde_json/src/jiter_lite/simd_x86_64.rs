@@ -0,0 +1,368 @@
+use crate::jiter_lite as jiter;
+
+use core::mem::transmute;
+#[rustfmt::skip]
+use core::arch::x86_64::{
+    __m128i,
+    __m256i,
+    // SSE2 (always available on x86_64)
+    _mm_loadu_si128 as simd_load_16,
+    _mm_set1_epi8 as simd_splat_16,
+    _mm_setzero_si128 as simd_zero_16,
+    _mm_cmpeq_epi8 as simd_eq_16,
+    _mm_cmpgt_epi8 as simd_gt_16,
+    _mm_cmplt_epi8 as simd_lt_16,
+    _mm_or_si128 as simd_or_16,
+    _mm_sub_epi8 as simd_sub_16,
+    _mm_movemask_epi8 as simd_movemask_16,
+    _mm_slli_si128 as simd_shift_in_zeros_16,
+    _mm_madd_epi16 as simd_mul_add_i16_8,
+    _mm_setr_epi8,
+    _mm_setr_epi16,
+    // SSSE3
+    _mm_maddubs_epi16 as simd_mul_add_u8_16,
+    // SSE4.1
+    _mm_packus_epi32 as simd_pack_u32_4,
+    // AVX2
+    _mm256_loadu_si256 as simd_load_32,
+    _mm256_set1_epi8 as simd_splat_32,
+    _mm256_cmpeq_epi8 as simd_eq_32,
+    _mm256_cmpgt_epi8 as simd_gt_32,
+    _mm256_or_si256 as simd_or_32,
+    _mm256_movemask_epi8 as simd_movemask_32,
+};
+use jiter::errors::JsonResult;
+
+use jiter::number_decoder::{decode_int_chunk_fallback, IntChunk};
+use jiter::string_decoder::StringChunk;
+
+type SimdVecu8_16 = __m128i;
+type SimdVecu8_32 = __m256i;
+
+const SIMD_STEP_16: usize = 16;
+const SIMD_STEP_32: usize = 32;
+
+// SSE2 is part of the x86_64 baseline, but the rest has to be detected at
+// runtime (unless it's enabled at compile time, in which case these are
+// constant). std caches the result, so these are just an atomic load.
+// Without std, only what's enabled at compile time is used.
+
+#[cfg(feature = "std")]
+#[inline(always)]
+fn has_avx2() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+#[cfg(feature = "std")]
+#[inline(always)]
+fn has_sse41() -> bool {
+    is_x86_feature_detected!("ssse3") && is_x86_feature_detected!("sse4.1")
+}
+
+#[cfg(not(feature = "std"))]
+#[inline(always)]
+fn has_avx2() -> bool {
+    cfg!(target_feature = "avx2")
+}
+
+#[cfg(not(feature = "std"))]
+#[inline(always)]
+fn has_sse41() -> bool {
+    cfg!(all(target_feature = "ssse3", target_feature = "sse4.1"))
+}
+
+#[inline(always)]
+pub(crate) fn decode_int_chunk(data: &[u8], index: usize) -> (IntChunk, usize) {
+    if has_sse41() {
+        unsafe { decode_int_chunk_sse41(data, index) }
+    } else {
+        decode_int_chunk_fallback(data, index, 0)
+    }
+}
+
+#[target_feature(enable = "ssse3,sse4.1")]
+unsafe fn decode_int_chunk_sse41(data: &[u8], index: usize) -> (IntChunk, usize) {
+    if let Some(byte_chunk) = data.get(index..index + SIMD_STEP_16) {
+        let byte_vec = load_slice_16(byte_chunk);
+        // subtract ascii '0' from every byte to get the digit values
+        let digits = simd_sub_16(byte_vec, simd_splat_16(b'0' as i8));
+
+        // SSE only has signed comparisons: bytes below '0' wrap around to
+        // negative values, and bytes above '9' are either above 9 or
+        // (for non-ascii bytes) negative too.
+        let non_digits = simd_or_16(
+            simd_gt_16(digits, simd_splat_16(9)),
+            simd_lt_16(digits, simd_zero_16()),
+        );
+        let non_digit_mask = simd_movemask_16(non_digits) as u32;
+
+        if non_digit_mask == 0 {
+            // all lanes are digits, parse the full vector
+            let value = calc(digits, 16);
+            (IntChunk::Ongoing(value), index + SIMD_STEP_16)
+        } else {
+            let last_digit = non_digit_mask.trailing_zeros();
+            let index = index + last_digit as usize;
+            if next_is_float(data, index) {
+                (IntChunk::Float, index)
+            } else {
+                let value = calc(digits, last_digit);
+                (IntChunk::Done(value), index)
+            }
+        }
+    } else {
+        // we got near the end of the string, fall back to the slow path
+        decode_int_chunk_fallback(data, index, 0)
+    }
+}
+
+#[inline]
+#[target_feature(enable = "ssse3,sse4.1")]
+unsafe fn calc(digits: SimdVecu8_16, last_digit: u32) -> u64 {
+    // move the first `last_digit` digits to the end of the vector, and fill
+    // the start with zeros, which don't change the value
+    let digits = match last_digit {
+        0 => return 0,
+        1 => simd_shift_in_zeros_16::<15>(digits),
+        2 => simd_shift_in_zeros_16::<14>(digits),
+        3 => simd_shift_in_zeros_16::<13>(digits),
+        4 => simd_shift_in_zeros_16::<12>(digits),
+        5 => simd_shift_in_zeros_16::<11>(digits),
+        6 => simd_shift_in_zeros_16::<10>(digits),
+        7 => simd_shift_in_zeros_16::<9>(digits),
+        8 => simd_shift_in_zeros_16::<8>(digits),
+        9 => simd_shift_in_zeros_16::<7>(digits),
+        10 => simd_shift_in_zeros_16::<6>(digits),
+        11 => simd_shift_in_zeros_16::<5>(digits),
+        12 => simd_shift_in_zeros_16::<4>(digits),
+        13 => simd_shift_in_zeros_16::<3>(digits),
+        14 => simd_shift_in_zeros_16::<2>(digits),
+        15 => simd_shift_in_zeros_16::<1>(digits),
+        16 => digits,
+        _ => unreachable!("last_digit should be at most 16"),
+    };
+    // multiply every other digit by 10, and add pairs together into 8x16-bit lanes
+    let x = simd_mul_add_u8_16(
+        digits,
+        _mm_setr_epi8(10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1),
+    );
+    // multiply every other lane by 100, and add pairs together into 4x32-bit lanes
+    let x = simd_mul_add_i16_8(x, _mm_setr_epi16(100, 1, 100, 1, 100, 1, 100, 1));
+    // narrow back to 16-bit lanes (every value is at most 9999)
+    let x = simd_pack_u32_4(x, x);
+    // multiply every other lane by 10000, and add pairs together into 32-bit lanes
+    let x = simd_mul_add_i16_8(x, _mm_setr_epi16(10000, 1, 10000, 1, 10000, 1, 10000, 1));
+
+    // the first two lanes each hold 8 digits
+    let t: [u32; 4] = transmute(x);
+    (t[0] as u64) * 100_000_000 + t[1] as u64
+}
+
+fn next_is_float(data: &[u8], index: usize) -> bool {
+    let next = unsafe { data.get_unchecked(index) };
+    matches!(next, b'.' | b'e' | b'E')
+}
+
+#[inline(always)]
+pub(crate) fn decode_string_chunk(
+    data: &[u8],
+    index: usize,
+    ascii_only: bool,
+    allow_partial: bool,
+) -> JsonResult<(StringChunk, bool, usize)> {
+    if has_avx2() {
+        unsafe { decode_string_chunk_avx2(data, index, ascii_only, allow_partial) }
+    } else {
+        decode_string_chunk_sse2(data, index, ascii_only, allow_partial)
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn decode_string_chunk_avx2(
+    data: &[u8],
+    mut index: usize,
+    mut ascii_only: bool,
+    allow_partial: bool,
+) -> JsonResult<(StringChunk, bool, usize)> {
+    while let Some(byte_chunk) = data.get(index..index + SIMD_STEP_32) {
+        let mask = string_ascii_mask_32(byte_chunk);
+        if mask == 0 {
+            // this chunk is just ascii, continue to the next chunk
+            index += SIMD_STEP_32;
+        } else {
+            index += mask.trailing_zeros() as usize;
+            match decode_special(data, &mut index, ascii_only) {
+                Some(r) => return r,
+                None => ascii_only = false,
+            }
+        }
+    }
+    // less than 32 bytes left, let SSE2 have a go at the rest
+    decode_string_chunk_sse2(data, index, ascii_only, allow_partial)
+}
+
+#[inline(always)]
+fn decode_string_chunk_sse2(
+    data: &[u8],
+    mut index: usize,
+    mut ascii_only: bool,
+    allow_partial: bool,
+) -> JsonResult<(StringChunk, bool, usize)> {
+    while let Some(byte_chunk) = data.get(index..index + SIMD_STEP_16) {
+        let mask = string_ascii_mask_16(byte_chunk);
+        if mask == 0 {
+            // this chunk is just ascii, continue to the next chunk
+            index += SIMD_STEP_16;
+        } else {
+            index += mask.trailing_zeros() as usize;
+            match decode_special(data, &mut index, ascii_only) {
+                Some(r) => return r,
+                None => ascii_only = false,
+            }
+        }
+    }
+    // we got near the end of the string, fall back to the slow path
+    StringChunk::decode_fallback(data, index, ascii_only, allow_partial)
+}
+
+/// Handles the byte at `index`, which the masks say isn't simple ascii: returns
+/// `None` (and skips past it) if it's just non-ascii.
+#[inline(always)]
+fn decode_special(
+    data: &[u8],
+    index: &mut usize,
+    ascii_only: bool,
+) -> Option<JsonResult<(StringChunk, bool, usize)>> {
+    StringChunk::decode_array([data[*index]], index, ascii_only)
+}
+
+/// returns a bitmask where any set bit means the corresponding byte isn't a simple
+/// ascii character, either quote, backslash, control character, or non-ascii (above 127)
+#[inline(always)]
+fn string_ascii_mask_16(bytes: &[u8]) -> u32 {
+    unsafe {
+        let byte_vec = load_slice_16(bytes);
+        // signed comparison: non-ascii bytes are negative, so they're
+        // "below 32" too
+        let special = simd_or_16(
+            simd_eq_16(byte_vec, simd_splat_16(b'"' as i8)),
+            simd_or_16(
+                simd_eq_16(byte_vec, simd_splat_16(b'\\' as i8)),
+                simd_lt_16(byte_vec, simd_splat_16(32)),
+            ),
+        );
+        simd_movemask_16(special) as u32
+    }
+}
+
+/// Same as [string_ascii_mask_16], 32 bytes at a time
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn string_ascii_mask_32(bytes: &[u8]) -> u32 {
+    debug_assert_eq!(bytes.len(), 32);
+    let byte_vec: SimdVecu8_32 = simd_load_32(bytes.as_ptr().cast());
+    let special = simd_or_32(
+        simd_eq_32(byte_vec, simd_splat_32(b'"' as i8)),
+        simd_or_32(
+            simd_eq_32(byte_vec, simd_splat_32(b'\\' as i8)),
+            simd_gt_32(simd_splat_32(32), byte_vec),
+        ),
+    );
+    simd_movemask_32(special) as u32
+}
+
+#[inline(always)]
+fn load_slice_16(bytes: &[u8]) -> SimdVecu8_16 {
+    debug_assert_eq!(bytes.len(), 16);
+    unsafe { simd_load_16(bytes.as_ptr().cast()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_cases() -> Vec<Vec<u8>> {
+        let mut cases = vec![];
+        for len in [0, 1, 15, 16, 17, 31, 32, 33, 47, 64, 100] {
+            for special in ["\"", "\\", "\n", "é", "\u{7f}"] {
+                for pos in [0, len / 2, len] {
+                    let mut s = "a".repeat(len).into_bytes();
+                    s.splice(pos..pos, special.bytes());
+                    cases.push(s);
+                }
+            }
+            cases.push("b".repeat(len).into_bytes());
+        }
+        cases.push("ééééééééééééééééééééé\"".as_bytes().to_vec());
+        cases
+    }
+
+    fn same_result(
+        a: JsonResult<(StringChunk, bool, usize)>,
+        b: JsonResult<(StringChunk, bool, usize)>,
+    ) -> bool {
+        match (a, b) {
+            (Ok((a, a_ascii, a_index)), Ok((b, b_ascii, b_index))) => {
+                core::mem::discriminant(&a) == core::mem::discriminant(&b)
+                    && a_ascii == b_ascii
+                    && a_index == b_index
+            }
+            (Err(a), Err(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_string_chunks_match_fallback() {
+        for case in string_cases() {
+            for allow_partial in [false, true] {
+                let fallback = || StringChunk::decode_fallback(&case, 0, true, allow_partial);
+                let sse2 = decode_string_chunk_sse2(&case, 0, true, allow_partial);
+                assert!(
+                    same_result(sse2, fallback()),
+                    "SSE2 mismatch for {:?}",
+                    String::from_utf8_lossy(&case)
+                );
+                if has_avx2() {
+                    let avx2 = unsafe { decode_string_chunk_avx2(&case, 0, true, allow_partial) };
+                    assert!(
+                        same_result(avx2, fallback()),
+                        "AVX2 mismatch for {:?}",
+                        String::from_utf8_lossy(&case)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_int_chunks_match_fallback() {
+        if !has_sse41() {
+            return;
+        }
+        const DIGITS: &str = "98765432101234567890";
+        for digits in 0..=20 {
+            for suffix in ["", ",", ".5", "e3", "]  padding padding"] {
+                let input = format!("{}{suffix}", &DIGITS[..digits]);
+                let (chunk, index) = unsafe { decode_int_chunk_sse41(input.as_bytes(), 0) };
+                // the fallback handles at most 18 digits at a time, so compare
+                // against a plain parse instead
+                let end = input.bytes().take_while(u8::is_ascii_digit).count();
+                match chunk {
+                    IntChunk::Ongoing(value) => {
+                        assert_eq!(index, 16);
+                        assert_eq!(value, input[..16].parse::<u64>().unwrap());
+                    }
+                    IntChunk::Done(value) => {
+                        assert_eq!(index, end, "{input:?}");
+                        assert_eq!(value, input[..end].parse::<u64>().unwrap_or(0));
+                    }
+                    IntChunk::Float => {
+                        assert_eq!(index, end, "{input:?}");
+                        assert!(matches!(input.as_bytes()[end], b'.' | b'e'));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,10 @@
-use std::borrow::Cow;
-use std::ops::Range;
-use std::str::{from_utf8, from_utf8_unchecked};
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::String,
+    vec::Vec,
+};
+use core::ops::Range;
+use core::str::{from_utf8, from_utf8_unchecked};
 
 use crate::jiter_lite::errors::{json_err, json_error, JsonResult};
 
@@ -13,7 +17,7 @@ pub trait AbstractStringDecoder<'t, 'j>
 where
     'j: 't,
 {
-    type Output: std::fmt::Debug;
+    type Output: core::fmt::Debug;
 
     fn decode(
         data: &'j [u8],
@@ -152,13 +156,15 @@ pub(crate) fn decode_chunk(
     ascii_only: bool,
     allow_partial: bool,
 ) -> JsonResult<(StringChunk, bool, usize)> {
-    // TODO x86_64: use simd
-
     #[cfg(target_arch = "aarch64")]
     {
         crate::jiter_lite::simd_aarch64::decode_string_chunk(data, index, ascii_only, allow_partial)
     }
-    #[cfg(not(target_arch = "aarch64"))]
+    #[cfg(target_arch = "x86_64")]
+    {
+        crate::jiter_lite::simd_x86_64::decode_string_chunk(data, index, ascii_only, allow_partial)
+    }
+    #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
     {
         StringChunk::decode_fallback(data, index, ascii_only, allow_partial)
     }
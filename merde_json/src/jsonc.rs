@@ -0,0 +1,693 @@
+use std::fmt;
+
+use merde_core::{CowStr, DynSerialize, IntoStatic, Map, MerdeError, Value, ValueType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Colon,
+    Comma,
+    Str,
+    Number,
+    // `true`, `false`, `null`
+    Literal,
+    Whitespace,
+    LineComment,
+    BlockComment,
+}
+
+impl TokenKind {
+    fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+}
+
+/// A value in the document, as a range of tokens (trivia excluded)
+#[derive(Debug)]
+struct Node {
+    start: usize,
+    end: usize,
+    kind: NodeKind,
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    Scalar,
+    Array(Vec<Entry>),
+    Object(Vec<Entry>),
+}
+
+/// A member of an object (with a key) or an element of an array (without)
+#[derive(Debug)]
+struct Entry {
+    key: Option<(String, usize)>,
+    value: Node,
+    comma: Option<usize>,
+}
+
+/// Where to insert tokens for a new entry: the comma separating it from the
+/// previous one goes first, the entry itself goes after that (and after any
+/// same-line comment)
+struct Insertion {
+    comma: Option<usize>,
+    entry: (usize, Vec<Token>),
+}
+
+impl Entry {
+    fn start(&self) -> usize {
+        match &self.key {
+            Some((_, token)) => *token,
+            None => self.value.start,
+        }
+    }
+}
+
+/// A JSON document that keeps everything the source had — comments (`//` and
+/// `/* */`), whitespace, trailing commas, the way numbers and strings were
+/// written — so it can be edited and written back without disturbing the
+/// parts that weren't touched.
+///
+/// This is what config-editing tools need for JSONC files: use [`JsoncDocument::get`]
+/// to read values, [`JsoncDocument::set`] and [`JsoncDocument::remove`] to edit
+/// them, and `to_string()` to get the edited source back.
+///
+/// Paths are lists of object keys and array indices (written as strings, like
+/// in JSON Pointer).
+///
+/// ```rust
+/// use merde_json::JsoncDocument;
+///
+/// let mut doc = JsoncDocument::parse(r#"{
+///   // where to listen
+///   "port": 8080,
+/// }"#).unwrap();
+/// doc.set(&["port"], &9090).unwrap();
+/// doc.set(&["debug"], &true).unwrap();
+/// assert_eq!(doc.to_string(), r#"{
+///   // where to listen
+///   "port": 9090,
+///   "debug": true,
+/// }"#);
+/// ```
+#[derive(Debug)]
+pub struct JsoncDocument {
+    tokens: Vec<Token>,
+    root: Node,
+}
+
+impl JsoncDocument {
+    /// Parses JSON with comments and trailing commas. Objects and arrays can be
+    /// nested 128 levels deep, at most.
+    pub fn parse(source: &str) -> Result<Self, MerdeError<'static>> {
+        let error = |index: usize, message: &str| MerdeError::StringParsingError {
+            format: "JSONC",
+            source: CowStr::from(source.to_string()),
+            index,
+            message: message.to_string(),
+        };
+
+        let tokens = tokenize(source).map_err(|(index, message)| error(index, message))?;
+        let root = parse_tokens(&tokens).map_err(|(token, message)| {
+            let index = tokens[..token].iter().map(|t| t.text.len()).sum();
+            error(index, message)
+        })?;
+        Ok(Self { tokens, root })
+    }
+
+    /// Returns the value at `path`, without any comments, or `None` if there's
+    /// nothing there.
+    pub fn get(&self, path: &[&str]) -> Option<Value<'static>> {
+        self.resolve(path).ok().map(|node| self.to_value(node))
+    }
+
+    /// Sets the value at `path` to `value`, serialized as compact JSON.
+    ///
+    /// If the last segment of the path is a key that the object doesn't have
+    /// (or the length of the array), the value is added at the end of it, with
+    /// the same indentation as its other entries. Everything else in the
+    /// document is left as-is.
+    pub fn set(
+        &mut self,
+        path: &[&str],
+        value: &dyn DynSerialize,
+    ) -> Result<(), MerdeError<'static>> {
+        let mut value_tokens =
+            tokenize(&crate::to_string(value)?).expect("serializer emits valid JSON");
+
+        let Some((last, parents)) = path.split_last() else {
+            let (start, end) = (self.root.start, self.root.end);
+            self.tokens.splice(start..end, value_tokens);
+            return self.reparse();
+        };
+
+        let parent = self.resolve(parents)?;
+        let insertion = match (&parent.kind, self.child(parent, last)) {
+            (_, Ok(node)) => {
+                let (start, end) = (node.start, node.end);
+                self.tokens.splice(start..end, value_tokens);
+                return self.reparse();
+            }
+            (NodeKind::Object(entries), Err(MerdeError::MissingProperty(_))) => {
+                let mut entry = tokenize(&crate::to_string(last)?).expect("valid JSON string");
+                entry.push(token(TokenKind::Colon, ":"));
+                entry.push(token(TokenKind::Whitespace, " "));
+                entry.append(&mut value_tokens);
+                self.plan_insertion(parent.start, entries, entry)
+            }
+            (NodeKind::Array(entries), Err(_)) if last.parse() == Ok(entries.len()) => {
+                self.plan_insertion(parent.start, entries, value_tokens)
+            }
+            (_, Err(e)) => return Err(e),
+        };
+
+        // insert from the end, so indices stay valid
+        let (at, entry) = insertion.entry;
+        self.tokens.splice(at..at, entry);
+        if let Some(at) = insertion.comma {
+            self.tokens.insert(at, token(TokenKind::Comma, ","));
+        }
+        self.reparse()
+    }
+
+    /// Removes the value at `path` (which can't be empty) from its object or
+    /// array, along with its separator and any comment on the same line.
+    pub fn remove(&mut self, path: &[&str]) -> Result<(), MerdeError<'static>> {
+        let Some((last, parents)) = path.split_last() else {
+            return Err(MerdeError::MissingValue);
+        };
+        let parent = self.resolve(parents)?;
+        self.child(parent, last)?;
+        let (index, entries) = match &parent.kind {
+            NodeKind::Object(entries) => (
+                entries
+                    .iter()
+                    .rposition(|e| e.key.as_ref().map(|(k, _)| k.as_str()) == Some(*last))
+                    .expect("the key was just found"),
+                entries,
+            ),
+            NodeKind::Array(entries) => (last.parse().expect("the index was just found"), entries),
+            NodeKind::Scalar => unreachable!("scalars have no children"),
+        };
+
+        let entry = &entries[index];
+        // take the whitespace before the entry with it, so the line goes away
+        let mut start = entry.start();
+        let took_whitespace = start > 0 && self.tokens[start - 1].kind == TokenKind::Whitespace;
+        if took_whitespace {
+            start -= 1;
+        }
+        let (mut end, previous_comma) = match entry.comma {
+            Some(comma) => (comma + 1, None),
+            None => (
+                entry.value.end,
+                index.checked_sub(1).and_then(|i| entries[i].comma),
+            ),
+        };
+        match self.same_line_comment(end) {
+            0 if !took_whitespace && entry.comma.is_some() => {
+                // `[1, 2]` becomes `[2]`, not `[ 2]`
+                if let Some(t) = self.tokens.get(end) {
+                    if t.kind == TokenKind::Whitespace && !t.text.contains('\n') {
+                        end += 1;
+                    }
+                }
+            }
+            len => end += len,
+        }
+
+        self.tokens.drain(start..end);
+        if let Some(comma) = previous_comma {
+            self.tokens.remove(comma);
+        }
+        self.reparse()
+    }
+
+    fn reparse(&mut self) -> Result<(), MerdeError<'static>> {
+        self.root =
+            parse_tokens(&self.tokens).map_err(|(_, message)| MerdeError::StringParsingError {
+                format: "JSONC",
+                source: CowStr::from(self.to_string()),
+                index: 0,
+                message: message.to_string(),
+            })?;
+        Ok(())
+    }
+
+    fn resolve(&self, path: &[&str]) -> Result<&Node, MerdeError<'static>> {
+        let mut node = &self.root;
+        for segment in path {
+            node = self.child(node, segment)?;
+        }
+        Ok(node)
+    }
+
+    fn child<'a>(&self, node: &'a Node, segment: &str) -> Result<&'a Node, MerdeError<'static>> {
+        match &node.kind {
+            // with duplicate keys, the last one wins, like when deserializing
+            NodeKind::Object(entries) => entries
+                .iter()
+                .rev()
+                .find(|e| e.key.as_ref().map(|(k, _)| k.as_str()) == Some(segment))
+                .map(|e| &e.value)
+                .ok_or_else(|| MerdeError::MissingProperty(CowStr::from(segment.to_string()))),
+            NodeKind::Array(entries) => {
+                let index: usize = segment.parse().map_err(|_| MerdeError::MismatchedType {
+                    expected: ValueType::Map,
+                    found: ValueType::Array,
+                })?;
+                entries
+                    .get(index)
+                    .map(|e| &e.value)
+                    .ok_or(MerdeError::IndexOutOfBounds {
+                        index,
+                        len: entries.len(),
+                    })
+            }
+            NodeKind::Scalar => Err(MerdeError::MismatchedType {
+                expected: ValueType::Map,
+                found: self.to_value(node).value_type(),
+            }),
+        }
+    }
+
+    /// Figures out where to insert an entry (tokens for `"key": value` or
+    /// `value`) at the end of an object or array, without modifying anything.
+    fn plan_insertion(
+        &self,
+        container_start: usize,
+        entries: &[Entry],
+        mut entry: Vec<Token>,
+    ) -> Insertion {
+        let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+            return Insertion {
+                comma: None,
+                entry: (container_start + 1, entry),
+            };
+        };
+
+        // indent like the first entry, if entries are on their own lines
+        let separator = match first.start().checked_sub(1).map(|i| &self.tokens[i]) {
+            Some(t) if t.kind == TokenKind::Whitespace && t.text.contains('\n') => {
+                let indent = &t.text[t.text.rfind('\n').unwrap()..];
+                token(TokenKind::Whitespace, indent)
+            }
+            _ => token(TokenKind::Whitespace, " "),
+        };
+        entry.insert(0, separator);
+
+        let (after, comma) = match last.comma {
+            // keep the trailing comma style
+            Some(comma) => {
+                entry.push(token(TokenKind::Comma, ","));
+                (comma + 1, None)
+            }
+            None => (last.value.end, Some(last.value.end)),
+        };
+        // a comment on the same line as the last entry stays with it
+        let at = after + self.same_line_comment(after);
+        Insertion {
+            comma,
+            entry: (at, entry),
+        }
+    }
+
+    /// How many tokens, starting at `at`, make up a line comment that's on
+    /// the same line as what comes before (including the whitespace before it).
+    fn same_line_comment(&self, at: usize) -> usize {
+        match self.tokens.get(at..) {
+            Some([ws, comment, ..])
+                if ws.kind == TokenKind::Whitespace
+                    && !ws.text.contains('\n')
+                    && comment.kind == TokenKind::LineComment =>
+            {
+                2
+            }
+            Some([comment, ..]) if comment.kind == TokenKind::LineComment => 1,
+            _ => 0,
+        }
+    }
+
+    fn to_value(&self, node: &Node) -> Value<'static> {
+        match &node.kind {
+            NodeKind::Scalar => {
+                let value: Value = crate::from_str(&self.tokens[node.start].text)
+                    .expect("scalars are validated when parsing");
+                value.into_static()
+            }
+            NodeKind::Array(entries) => Value::Array(
+                entries
+                    .iter()
+                    .map(|e| self.to_value(&e.value))
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            NodeKind::Object(entries) => {
+                let mut map = Map::new();
+                for e in entries {
+                    let (key, _) = e.key.as_ref().unwrap();
+                    map.insert(CowStr::from(key.clone()), self.to_value(&e.value));
+                }
+                Value::Map(map)
+            }
+        }
+    }
+}
+
+impl fmt::Display for JsoncDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for t in &self.tokens {
+            f.write_str(&t.text)?;
+        }
+        Ok(())
+    }
+}
+
+fn token(kind: TokenKind, text: &str) -> Token {
+    Token {
+        kind,
+        text: text.to_string(),
+    }
+}
+
+/// Splits `source` into tokens, trivia included, so that concatenating them
+/// gives back `source`. Errors are `(byte index, message)`.
+fn tokenize(source: &str) -> Result<Vec<Token>, (usize, &'static str)> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b'{' => TokenKind::LBrace,
+            b'}' => TokenKind::RBrace,
+            b'[' => TokenKind::LBracket,
+            b']' => TokenKind::RBracket,
+            b':' => TokenKind::Colon,
+            b',' => TokenKind::Comma,
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                while i + 1 < bytes.len() && matches!(bytes[i + 1], b' ' | b'\t' | b'\n' | b'\r') {
+                    i += 1;
+                }
+                TokenKind::Whitespace
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i + 1 < bytes.len() && bytes[i + 1] != b'\n' {
+                    i += 1;
+                }
+                TokenKind::LineComment
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => match source[i + 2..].find("*/") {
+                Some(len) => {
+                    i += 2 + len + 1;
+                    TokenKind::BlockComment
+                }
+                None => return Err((start, "unterminated block comment")),
+            },
+            b'"' => {
+                loop {
+                    i += 1;
+                    match bytes.get(i) {
+                        None => return Err((start, "unterminated string")),
+                        Some(b'\\') => i += 1,
+                        Some(b'"') => break,
+                        Some(_) => {}
+                    }
+                }
+                TokenKind::Str
+            }
+            b'-' | b'0'..=b'9' => {
+                while i + 1 < bytes.len()
+                    && matches!(bytes[i + 1], b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-')
+                {
+                    i += 1;
+                }
+                TokenKind::Number
+            }
+            b'a'..=b'z' => {
+                while i + 1 < bytes.len() && bytes[i + 1].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                TokenKind::Literal
+            }
+            _ => return Err((start, "unexpected character")),
+        };
+        i += 1;
+        tokens.push(Token {
+            kind,
+            text: source[start..i].to_string(),
+        });
+    }
+    Ok(tokens)
+}
+
+/// How many objects and arrays can be nested in one another: the parser (and
+/// everything walking the tree) is recursive.
+const MAX_DEPTH: usize = 128;
+
+/// Builds the tree for a whole document. Errors are `(token index, message)`.
+fn parse_tokens(tokens: &[Token]) -> Result<Node, (usize, &'static str)> {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let root = parser.value()?;
+    match parser.peek() {
+        None => Ok(root),
+        Some(_) => Err((parser.pos, "trailing characters after the document")),
+    }
+}
+
+struct Parser<'t> {
+    tokens: &'t [Token],
+    pos: usize,
+    // how many objects and arrays the current value is in
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<TokenKind> {
+        while self.pos < self.tokens.len() && self.tokens[self.pos].kind.is_trivia() {
+            self.pos += 1;
+        }
+        self.tokens.get(self.pos).map(|t| t.kind)
+    }
+
+    fn expect(
+        &mut self,
+        kind: TokenKind,
+        message: &'static str,
+    ) -> Result<usize, (usize, &'static str)> {
+        if self.peek() != Some(kind) {
+            return Err((self.pos, message));
+        }
+        self.pos += 1;
+        Ok(self.pos - 1)
+    }
+
+    fn value(&mut self) -> Result<Node, (usize, &'static str)> {
+        let start = self.pos;
+        let kind = match self.peek() {
+            None => return Err((start, "unexpected end of input")),
+            Some(kind) => kind,
+        };
+        let start = self.pos;
+        match kind {
+            TokenKind::Str | TokenKind::Number | TokenKind::Literal => {
+                if crate::from_str::<Value>(&self.tokens[start].text).is_err() {
+                    return Err((start, "invalid value"));
+                }
+                self.pos += 1;
+                Ok(Node {
+                    start,
+                    end: self.pos,
+                    kind: NodeKind::Scalar,
+                })
+            }
+            TokenKind::LBrace | TokenKind::LBracket => {
+                let object = kind == TokenKind::LBrace;
+                let close = if object {
+                    TokenKind::RBrace
+                } else {
+                    TokenKind::RBracket
+                };
+                if self.depth == MAX_DEPTH {
+                    return Err((start, "too many nested objects and arrays"));
+                }
+                self.depth += 1;
+                self.pos += 1;
+                let mut entries = Vec::new();
+                loop {
+                    if self.peek() == Some(close) {
+                        self.pos += 1;
+                        break;
+                    }
+                    let key = if object {
+                        let token = self.expect(TokenKind::Str, "expected a key or `}`")?;
+                        let key: String = crate::from_str(&self.tokens[token].text)
+                            .map_err(|_| (token, "invalid key"))?;
+                        self.expect(TokenKind::Colon, "expected `:`")?;
+                        Some((key, token))
+                    } else {
+                        None
+                    };
+                    let value = self.value()?;
+                    let comma = match self.peek() {
+                        Some(TokenKind::Comma) => {
+                            self.pos += 1;
+                            Some(self.pos - 1)
+                        }
+                        _ => None,
+                    };
+                    entries.push(Entry { key, value, comma });
+                    if comma.is_none() {
+                        self.expect(
+                            close,
+                            if object {
+                                "expected `,` or `}`"
+                            } else {
+                                "expected `,` or `]`"
+                            },
+                        )?;
+                        break;
+                    }
+                }
+                self.depth -= 1;
+                Ok(Node {
+                    start,
+                    end: self.pos,
+                    kind: if object {
+                        NodeKind::Object(entries)
+                    } else {
+                        NodeKind::Array(entries)
+                    },
+                })
+            }
+            _ => Err((start, "expected a value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"// service config
+{
+  "name": "api", // shown in logs
+  "db": {
+    "host": "localhost",
+    /* default postgres port */
+    "port": 5432
+  },
+  "tags": [1, 2, 3,],
+  "ratio": 1.50
+}
+"#;
+
+    #[test]
+    fn test_lossless() {
+        let doc = JsoncDocument::parse(SOURCE).unwrap();
+        assert_eq!(doc.to_string(), SOURCE);
+        assert_eq!(doc.get(&["db", "port"]), Some(Value::I64(5432)));
+        assert_eq!(doc.get(&["tags", "2"]), Some(Value::I64(3)));
+        assert_eq!(doc.get(&["ratio"]), Some(Value::from(1.5)));
+        assert_eq!(doc.get(&["db", "user"]), None);
+    }
+
+    #[test]
+    fn test_edits() {
+        let mut doc = JsoncDocument::parse(SOURCE).unwrap();
+        doc.set(&["db", "port"], &5433).unwrap();
+        doc.set(&["db", "user"], &"admin").unwrap();
+        doc.set(&["tags", "3"], &4).unwrap();
+        doc.set(&["debug"], &true).unwrap();
+        doc.remove(&["name"]).unwrap();
+        assert_eq!(
+            doc.to_string(),
+            r#"// service config
+{
+  "db": {
+    "host": "localhost",
+    /* default postgres port */
+    "port": 5433,
+    "user": "admin"
+  },
+  "tags": [1, 2, 3, 4,],
+  "ratio": 1.50,
+  "debug": true
+}
+"#
+        );
+
+        doc.remove(&["debug"]).unwrap();
+        doc.remove(&["tags", "0"]).unwrap();
+        assert!(doc
+            .to_string()
+            .ends_with("\"tags\": [2, 3, 4,],\n  \"ratio\": 1.50\n}\n"));
+    }
+
+    #[test]
+    fn test_same_line_comment() {
+        let mut doc = JsoncDocument::parse("{\n  \"a\": 1 // one\n}").unwrap();
+        doc.set(&["b"], &2).unwrap();
+        assert_eq!(doc.to_string(), "{\n  \"a\": 1, // one\n  \"b\": 2\n}");
+
+        let mut doc = JsoncDocument::parse("{}").unwrap();
+        doc.set(&["a"], &Value::Map(Map::new().with("b", Value::Null)))
+            .unwrap();
+        assert_eq!(doc.to_string(), r#"{"a": {"b":null}}"#);
+    }
+
+    #[test]
+    fn test_errors() {
+        for (source, index) in [
+            ("{\"a\": 1,, }", 8),
+            ("[1, /* oops", 4),
+            ("{\"a\": nul}", 6),
+            ("{} {}", 3),
+        ] {
+            match JsoncDocument::parse(source) {
+                Err(MerdeError::StringParsingError { index: i, .. }) => {
+                    assert_eq!(i, index, "{source}")
+                }
+                other => panic!("{source}: expected a parsing error, got {other:?}"),
+            }
+        }
+
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert!(matches!(
+            JsoncDocument::parse(&deep),
+            Err(MerdeError::StringParsingError { index: 128, .. })
+        ));
+        let ok = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert!(JsoncDocument::parse(&ok).is_ok());
+
+        let mut doc = JsoncDocument::parse(SOURCE).unwrap();
+        assert!(matches!(
+            doc.set(&["name", "first"], &"x"),
+            Err(MerdeError::MismatchedType { .. })
+        ));
+        assert!(matches!(
+            doc.set(&["tags", "7"], &1),
+            Err(MerdeError::IndexOutOfBounds { index: 7, len: 3 })
+        ));
+        assert!(matches!(
+            doc.remove(&["nope"]),
+            Err(MerdeError::MissingProperty(_))
+        ));
+        assert_eq!(doc.to_string(), SOURCE);
+    }
+}
@@ -1,36 +1,98 @@
 #![deny(missing_docs)]
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 mod deserialize;
 pub use deserialize::JsonDeserializer;
 
+#[cfg(feature = "std")]
 mod serialize;
+#[cfg(feature = "std")]
 pub use serialize::{JsonSerializer, JsonSerializerWriter};
 
+#[cfg(feature = "std")]
+mod raw_value;
+#[cfg(feature = "std")]
+pub use raw_value::RawValue;
+
+#[cfg(feature = "std")]
+mod chunks;
+#[cfg(feature = "std")]
+pub use chunks::JsonChunks;
+
 mod jiter_lite;
 
-use merde_core::{
-    Deserialize, DeserializeOwned, DynDeserializerExt, DynSerialize, DynSerializerExt, MerdeError,
-    MetastackExt,
-};
+mod tokens;
+pub use tokens::{JsonToken, JsonTokenKind, JsonTokens};
+
+#[cfg(feature = "std")]
+mod charset;
+#[cfg(feature = "std")]
+pub use charset::{Charset, TranscodingWriter};
+
+#[cfg(feature = "std")]
+mod jsonc;
+#[cfg(feature = "std")]
+pub use jsonc::JsoncDocument;
+
+#[cfg(feature = "rusqlite")]
+mod sql;
+#[cfg(feature = "rusqlite")]
+pub use sql::JsonSql;
+
+use merde_core::{Deserialize, DeserializeOwned, DynDeserializerExt, MerdeError};
+#[cfg(feature = "std")]
+use merde_core::{DynSerialize, DynSerializerExt};
 
 /// Deserialize an instance of type `T` from a string of JSON text.
 pub fn from_str<'s, T>(s: &'s str) -> Result<T, MerdeError<'s>>
 where
     T: Deserialize<'s>,
 {
+    merde_core::entry_span!("merde_json::from_str", len = s.len());
     let mut deser = JsonDeserializer::new(s);
     deser.deserialize::<T>()
 }
 
+/// Deserialize only the value at `path` in a string of JSON text: object keys
+/// (or indices, like `"0"`, for arrays), from the root. Everything that comes
+/// before that value is skipped over without being decoded, and everything
+/// after it isn't even looked at, which is much cheaper than deserializing a
+/// whole big document to read a single field.
+///
+/// Returns `None` if there's no value at `path`. If an object has the same
+/// key more than once, the first one is used.
+///
+/// ```rust
+/// let input = r#"{"items": [{"name": "a"}, {"name": "b"}], "metadata": {"id": 42}}"#;
+/// let id: Option<u64> = merde_json::get_path(input, &["metadata", "id"]).unwrap();
+/// assert_eq!(id, Some(42));
+/// let name: Option<String> = merde_json::get_path(input, &["items", "1", "name"]).unwrap();
+/// assert_eq!(name.as_deref(), Some("b"));
+/// ```
+pub fn get_path<'s, T>(s: &'s str, path: &[&str]) -> Result<Option<T>, MerdeError<'s>>
+where
+    T: Deserialize<'s>,
+{
+    merde_core::entry_span!("merde_json::get_path", len = s.len(), path = ?path);
+    let mut deser = JsonDeserializer::new(s);
+    if !deser.seek(path)? {
+        return Ok(None);
+    }
+    deser.deserialize::<T>().map(Some)
+}
+
 /// Deserialize an instance of type `T` from a string of JSON text,
 /// and return its static variant e.g. (CowStr<'static>, etc.)
 pub fn from_str_owned<T>(s: &str) -> Result<T, MerdeError<'_>>
 where
     T: DeserializeOwned,
 {
+    merde_core::entry_span!("merde_json::from_str_owned", len = s.len());
     let mut deser = JsonDeserializer::new(s);
-    T::deserialize_owned(&mut deser).run_sync_with_metastack()
+    deser.deserialize_owned::<T>()
 }
 
 /// Deserialize an instance of type `T` from a byte slice of JSON text.
@@ -38,7 +100,7 @@ pub fn from_bytes<'s, T>(b: &'s [u8]) -> Result<T, MerdeError<'s>>
 where
     T: Deserialize<'s>,
 {
-    let s = std::str::from_utf8(b)?;
+    let s = core::str::from_utf8(b)?;
     from_str(s)
 }
 
@@ -48,11 +110,31 @@ pub fn from_bytes_owned<T>(b: &[u8]) -> Result<T, MerdeError<'_>>
 where
     T: DeserializeOwned,
 {
-    let s = std::str::from_utf8(b)?;
+    let s = core::str::from_utf8(b)?;
     from_str_owned::<T>(s)
 }
 
+/// Parse JSON text into an [ArenaValue](merde_core::arena::ArenaValue) whose nodes
+/// are allocated in `bump`. Strings without escapes borrow from `s`.
+///
+/// ```rust
+/// use merde_core::arena::Bump;
+///
+/// let bump = Bump::new();
+/// let value = merde_json::from_str_in(&bump, r#"{"tags": ["a", "b"]}"#).unwrap();
+/// assert_eq!(value.get("tags").and_then(|v| v.as_array()).map(|a| a.len()), Some(2));
+/// ```
+#[cfg(feature = "arena")]
+pub fn from_str_in<'a>(
+    bump: &'a merde_core::arena::Bump,
+    s: &'a str,
+) -> Result<merde_core::arena::ArenaValue<'a>, MerdeError<'a>> {
+    let mut deser = JsonDeserializer::new(s);
+    merde_core::arena::ArenaValue::deserialize_in(bump, &mut deser)
+}
+
 /// Serialize the given data structure as a String of JSON.
+#[cfg(feature = "std")]
 pub fn to_string(value: &dyn DynSerialize) -> Result<String, MerdeError<'static>> {
     // SAFETY: This is safe because we know that the JSON serialization
     // produced by `to_json_bytes` will always be valid UTF-8.
@@ -61,7 +143,9 @@ pub fn to_string(value: &dyn DynSerialize) -> Result<String, MerdeError<'static>
 }
 
 /// Serialize as JSON to a `Vec<u8>`
+#[cfg(feature = "std")]
 pub fn to_vec(value: &dyn DynSerialize) -> Result<Vec<u8>, MerdeError<'static>> {
+    merde_core::entry_span!("merde_json::to_vec");
     let mut v: Vec<u8> = vec![];
     {
         let mut s = JsonSerializer::new(&mut v);
@@ -70,12 +154,56 @@ pub fn to_vec(value: &dyn DynSerialize) -> Result<Vec<u8>, MerdeError<'static>>
     Ok(v)
 }
 
+/// Serialize as JSON to a `Vec<u8>`, in a single-byte charset instead of UTF-8
+/// (see [TranscodingWriter]).
+#[cfg(feature = "std")]
+pub fn to_vec_with_charset(
+    value: &dyn DynSerialize,
+    charset: Charset,
+) -> Result<Vec<u8>, MerdeError<'static>> {
+    merde_core::entry_span!("merde_json::to_vec_with_charset", charset = ?charset);
+    let mut v: Vec<u8> = vec![];
+    {
+        let mut s = JsonSerializer::new(TranscodingWriter::new(&mut v, charset));
+        s.dyn_serialize(value)?;
+    }
+    Ok(v)
+}
+
+/// Serialize as JSON to a `bytes::Bytes`, without an intermediate `Vec<u8>`
+#[cfg(feature = "bytes")]
+pub fn to_bytes(value: &dyn DynSerialize) -> Result<bytes::Bytes, MerdeError<'static>> {
+    merde_core::entry_span!("merde_json::to_bytes");
+    let mut s = JsonSerializer::from_bytes_mut(bytes::BytesMut::new());
+    s.dyn_serialize(value)?;
+    Ok(s.freeze())
+}
+
 /// Serialize the given data structure as JSON into the I/O stream.
+#[cfg(feature = "std")]
 pub fn to_writer(
     writer: &mut dyn std::io::Write,
     value: &dyn DynSerialize,
 ) -> Result<(), MerdeError<'static>> {
+    merde_core::entry_span!("merde_json::to_writer");
     let mut s = JsonSerializer::from_writer(writer);
     s.dyn_serialize(value)?;
     Ok(())
 }
+
+/// Serialize as JSON, `chunk_size` bytes at a time, e.g. to fill HTTP/2 frames
+/// or the parts of a chunked upload: serialization only progresses when the
+/// next chunk is asked for, so there's never more than one chunk in memory.
+///
+/// ```rust
+/// let value: Vec<u32> = (0..1000).collect();
+/// for chunk in merde_json::to_chunks(&value, 256) {
+///     let chunk = chunk.unwrap();
+///     assert!(chunk.len() <= 256);
+///     // send it somewhere
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn to_chunks(value: &dyn DynSerialize, chunk_size: usize) -> JsonChunks<'_> {
+    JsonChunks::new(value, chunk_size)
+}
@@ -0,0 +1,181 @@
+use merde_core::{
+    CowStr, Deserialize, DeserializeInPlace, DynDeserializer, DynSerializer, EventBuffer,
+    IntoStatic, MerdeError, Serialize, Serializer, WithLifetime,
+};
+
+use crate::{
+    deserialize::jiter_error,
+    jiter_lite::{errors::JiterError, jiter::Jiter},
+    JsonDeserializer, JsonSerializer,
+};
+
+/// Any JSON value, kept as it's written in the input (whitespace included),
+/// and written back verbatim when serialized: for parts of a document that
+/// are only passed along, or whose shape is only known later.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use merde_json::RawValue;
+///
+/// let input = r#"{"kind": "point", "payload": { "x": 1.10, "y": [2, 3] }}"#;
+/// let fields: HashMap<String, RawValue> = merde_json::from_str(input).unwrap();
+/// assert_eq!(fields["payload"].get(), r#"{ "x": 1.10, "y": [2, 3] }"#);
+/// ```
+///
+/// The text borrows from the input when deserializing from a
+/// [JsonDeserializer]. Other deserializers (or a [JsonDeserializer] that
+/// doesn't keep the last duplicate key, see
+/// [JsonDeserializer::with_duplicate_key_policy]) hand over events instead,
+/// which are written as compact JSON.
+///
+/// Likewise, serializers other than [JsonSerializer] (or a [JsonSerializer]
+/// that sorts keys) get the value as events.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawValue<'s>(CowStr<'s>);
+
+impl<'s> RawValue<'s> {
+    /// Checks that `json` is a single valid JSON value (surrounding whitespace
+    /// is allowed, and kept).
+    pub fn new(json: impl Into<CowStr<'s>>) -> Result<Self, MerdeError<'s>> {
+        let json = json.into();
+        if let Err(e) = check_value(&json) {
+            return Err(jiter_error(&json, e).into_static());
+        }
+        Ok(Self(json))
+    }
+
+    /// The JSON text of the value.
+    pub fn get(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the JSON text of the value.
+    pub fn into_inner(self) -> CowStr<'s> {
+        self.0
+    }
+}
+
+fn check_value(json: &str) -> Result<(), JiterError> {
+    let mut jiter = Jiter::new(json.as_bytes());
+    let peek = jiter.peek()?;
+    jiter.known_skip(peek)?;
+    jiter.finish()
+}
+
+impl core::fmt::Display for RawValue<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'s> Deserialize<'s> for RawValue<'s> {
+    async fn deserialize(de: &mut dyn DynDeserializer<'s>) -> Result<Self, MerdeError<'s>> {
+        if let Some(raw) = de.next_raw("json").await? {
+            return Ok(Self(raw));
+        }
+
+        let events = EventBuffer::record(de).await?;
+        let mut json = Vec::new();
+        let mut ser = JsonSerializer::new(&mut json);
+        for ev in events.into_events() {
+            Serializer::write(&mut ser, ev).await?;
+        }
+        // SAFETY: the JSON serializer only writes valid UTF-8
+        let json = unsafe { String::from_utf8_unchecked(json) };
+        Ok(Self(json.into()))
+    }
+}
+
+impl<'s> DeserializeInPlace<'s> for RawValue<'s> {}
+
+impl Serialize for RawValue<'_> {
+    async fn serialize<'se>(
+        &'se self,
+        serializer: &'se mut dyn DynSerializer,
+    ) -> Result<(), MerdeError<'static>> {
+        if serializer.write_raw("json", &self.0).await? {
+            return Ok(());
+        }
+
+        let mut de = JsonDeserializer::new(&self.0);
+        let events = EventBuffer::record(&mut de)
+            .await
+            .map_err(IntoStatic::into_static)?;
+        for ev in events.into_events() {
+            serializer.write(ev).await?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoStatic for RawValue<'_> {
+    type Output = RawValue<'static>;
+
+    fn into_static(self) -> Self::Output {
+        RawValue(self.0.into_static())
+    }
+}
+
+impl<'s> WithLifetime<'s> for RawValue<'_> {
+    type Lifetimed = RawValue<'s>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use merde_core::{DynDeserializerExt, DynSerializerExt, Event, MapStart};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_raw_value() {
+        let input = r#"[{ "x": 1.10, "y": [2, "3"] },null,  -0.0e0 ,[ ]]"#;
+        let values: Vec<Option<RawValue>> = crate::from_str(input).unwrap();
+        let raw: Vec<Option<&str>> = values.iter().map(|v| v.as_ref().map(|v| v.get())).collect();
+        assert_eq!(
+            raw,
+            [
+                Some(r#"{ "x": 1.10, "y": [2, "3"] }"#),
+                None,
+                Some("-0.0e0"),
+                Some("[ ]")
+            ]
+        );
+        assert!(matches!(values[0].as_ref().unwrap().0, CowStr::Borrowed(_)));
+        assert_eq!(
+            crate::to_string(&values).unwrap(),
+            r#"[{ "x": 1.10, "y": [2, "3"] },null,-0.0e0,[ ]]"#
+        );
+
+        let input = r#"{"payload":{"a": [true]}}"#;
+        let map: HashMap<String, RawValue> = crate::from_str(input).unwrap();
+        assert_eq!(crate::to_string(&map).unwrap(), input);
+
+        assert!(RawValue::new(" [1, 2] ").is_ok());
+        for invalid in ["", "[1, 2", "{\"a\"}", "1 2", "tru", "[1,]"] {
+            assert!(RawValue::new(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_raw_value_as_events() {
+        // from events, written as compact JSON
+        let raw: RawValue = EventBuffer::from_events([
+            Event::MapStart(MapStart { size_hint: None }),
+            Event::Str("a".into()),
+            Event::I64(1),
+            Event::MapEnd,
+        ])
+        .deserialize()
+        .unwrap();
+        assert_eq!(raw.get(), r#"{"a":1}"#);
+
+        // to events, when keys are sorted
+        let raw = RawValue::new(r#"{ "b": 1, "a": 2 }"#).unwrap();
+        let mut out = Vec::new();
+        JsonSerializer::new(&mut out)
+            .with_sorted_keys(true)
+            .serialize(&raw)
+            .unwrap();
+        assert_eq!(out, br#"{"a":2,"b":1}"#);
+    }
+}
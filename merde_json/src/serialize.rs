@@ -1,6 +1,9 @@
-use std::{collections::VecDeque, future::Future, io::Write};
+use std::{borrow::Cow, collections::VecDeque, future::Future, iter::Peekable};
 
-use merde_core::{Event, MerdeError, Serializer};
+use merde_core::{
+    CowStr, Event, EventType, InfallibleSerializer, IntoStatic, MerdeError, NonStringKeyPolicy,
+    Serializer,
+};
 
 /// Something the JSON serializer can write to
 pub trait JsonSerializerWriter {
@@ -9,6 +12,13 @@ pub trait JsonSerializerWriter {
         &mut self,
         slice: &[u8],
     ) -> impl Future<Output = Result<(), std::io::Error>>;
+
+    /// Hints that at least `additional` more bytes are about to be written, so
+    /// in-memory buffers can grow once instead of several times. Does nothing
+    /// by default.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
 }
 
 impl JsonSerializerWriter for &mut Vec<u8> {
@@ -16,6 +26,34 @@ impl JsonSerializerWriter for &mut Vec<u8> {
         Vec::extend_from_slice(self, slice);
         Ok(())
     }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl JsonSerializerWriter for bytes::BytesMut {
+    async fn extend_from_slice(&mut self, slice: &[u8]) -> Result<(), std::io::Error> {
+        bytes::BytesMut::extend_from_slice(self, slice);
+        Ok(())
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        bytes::BytesMut::reserve(self, additional);
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl JsonSerializerWriter for &mut bytes::BytesMut {
+    async fn extend_from_slice(&mut self, slice: &[u8]) -> Result<(), std::io::Error> {
+        bytes::BytesMut::extend_from_slice(self, slice);
+        Ok(())
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        bytes::BytesMut::reserve(self, additional);
+    }
 }
 
 /// A wrapper around a `std::io::Write` that implements `JsonSerializerWriter`
@@ -27,6 +65,19 @@ impl<'s> JsonSerializerWriter for SyncWriteWrapper<'s> {
     }
 }
 
+/// A wrapper around a `std::fmt::Write` (a `String`, a `Formatter`...) that
+/// implements `JsonSerializerWriter`
+pub struct FmtWriteWrapper<'s>(&'s mut dyn std::fmt::Write);
+
+impl<'s> JsonSerializerWriter for FmtWriteWrapper<'s> {
+    async fn extend_from_slice(&mut self, slice: &[u8]) -> Result<(), std::io::Error> {
+        // the serializer only ever cuts its output at char boundaries
+        let s = std::str::from_utf8(slice)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        self.0.write_str(s).map_err(std::io::Error::other)
+    }
+}
+
 #[cfg(feature = "tokio")]
 pub mod tokio_io {
     //! Adapter types from `tokio::io::AsyncWrite` to `JsonSerializerWriter`
@@ -45,11 +96,53 @@ pub mod tokio_io {
     }
 }
 
+#[cfg(feature = "futures-io")]
+pub mod futures_io {
+    //! Adapter types from `futures_io::AsyncWrite` to `JsonSerializerWriter`, for
+    //! async runtimes other than tokio (async-std, smol, etc.)
+
+    use std::pin::Pin;
+
+    /// Implements `JsonSerializerWriter` for `futures_io::AsyncWrite`
+    pub struct AsyncWriteWrapper<'s>(pub Pin<&'s mut dyn futures_io::AsyncWrite>);
+
+    impl super::JsonSerializerWriter for AsyncWriteWrapper<'_> {
+        async fn extend_from_slice(&mut self, mut slice: &[u8]) -> Result<(), std::io::Error> {
+            // futures-io doesn't have `write_all`, that's in futures-util
+            while !slice.is_empty() {
+                let n = std::future::poll_fn(|cx| self.0.as_mut().poll_write(cx, slice)).await?;
+                if n == 0 {
+                    return Err(std::io::ErrorKind::WriteZero.into());
+                }
+                slice = &slice[n..];
+            }
+            Ok(())
+        }
+    }
+}
+
 /// Writes JSON to a `Vec<u8>`. None of its methods can fail, since it doesn't target
 /// an `io::Write`. You can provide your own buffer via `JsonSerializer::from_vec`.
 ///
 /// When you're done with the serializer, you can call `JsonSerializer::into_inner` to
 /// get the buffer back.
+///
+/// When targeting a `Vec<u8>`, it implements [`InfallibleSerializer`], which is handy
+/// in places that can't propagate errors:
+///
+/// ```rust
+/// use merde_core::InfallibleSerializer;
+/// use merde_json::JsonSerializer;
+///
+/// let mut buf = Vec::new();
+/// JsonSerializer::new(&mut buf).serialize_infallible(&vec![1, 2, 3]);
+/// assert_eq!(buf, b"[1,2,3]");
+/// ```
+///
+/// JSON only allows string keys in objects: what happens when a `Serialize` impl writes
+/// anything else is controlled by [`JsonSerializer::with_non_string_key_policy`].
+///
+/// For deterministic output (e.g. for hashing or signing), see [`JsonSerializer::with_sorted_keys`].
 #[derive(Default)]
 pub struct JsonSerializer<W>
 where
@@ -57,8 +150,32 @@ where
 {
     w: W,
     stack: VecDeque<StackFrame>,
+    non_string_keys: NonStringKeyPolicy,
+    wide_ints_as_strings: bool,
+
+    sort_keys: bool,
+    // events of the outermost map being buffered for sorting
+    sort_buffer: Vec<Event<'static>>,
+    sort_depth: usize,
 }
 
+// Bytes that can't be copied as-is into a JSON string: quotes, backslashes
+// and control characters, C1 ones included. Those are U+0080..=U+009F, so
+// 0xc2 is flagged too, and `write_str` checks the byte after it.
+const NEEDS_ESCAPE: [bool; 256] = {
+    let mut table = [false; 256];
+    let mut b = 0;
+    while b < 0x20 {
+        table[b] = true;
+        b += 1;
+    }
+    table[b'"' as usize] = true;
+    table[b'\\' as usize] = true;
+    table[0x7f] = true;
+    table[0xc2] = true;
+    table
+};
+
 enum StackFrame {
     // the next item to be written is an array element
     Array { first: bool },
@@ -67,6 +184,94 @@ enum StackFrame {
     // the next item to be written is a map value
     // (and needs a ":" before it)
     MapValue,
+    // we're dropping a map entry with a non-string key: swallow events until
+    // `values` complete values have gone by
+    Skip { values: u8, depth: usize },
+}
+
+// The string a map key sorts by in `with_sorted_keys` mode: non-string keys sort
+// as they'd be written by `NonStringKeyPolicy::Stringify`.
+fn key_sort_string<'a>(ev: &'a Event<'_>) -> Cow<'a, str> {
+    match ev {
+        Event::Str(s) => Cow::Borrowed(s),
+        Event::Bool(b) => Cow::Borrowed(if *b { "true" } else { "false" }),
+        Event::I64(i) => Cow::Owned(itoa::Buffer::new().format(*i).to_owned()),
+        Event::U64(u) => Cow::Owned(itoa::Buffer::new().format(*u).to_owned()),
+        Event::I128(i) => Cow::Owned(itoa::Buffer::new().format(*i).to_owned()),
+        Event::U128(u) => Cow::Owned(itoa::Buffer::new().format(*u).to_owned()),
+        Event::F64(f) => Cow::Owned(ryu::Buffer::new().format(*f).to_owned()),
+        _ => Cow::Borrowed(""),
+    }
+}
+
+// Moves the next value from `events` to `out`, sorting the entries of any map
+// it contains (recursively) by key.
+fn sort_map_keys<I>(events: &mut Peekable<I>, out: &mut Vec<Event<'static>>)
+where
+    I: Iterator<Item = Event<'static>>,
+{
+    let Some(ev) = events.next() else {
+        return;
+    };
+    match ev {
+        Event::MapStart(_) => {
+            out.push(ev);
+            let mut entries: Vec<Vec<Event<'static>>> = Vec::new();
+            while let Some(ev) = events.peek() {
+                if matches!(ev, Event::MapEnd) {
+                    break;
+                }
+                let mut entry = Vec::new();
+                sort_map_keys(events, &mut entry);
+                sort_map_keys(events, &mut entry);
+                entries.push(entry);
+            }
+            // stable, so duplicate keys keep their relative order
+            entries.sort_by(|a, b| key_sort_string(&a[0]).cmp(&key_sort_string(&b[0])));
+            out.extend(entries.into_iter().flatten());
+            out.extend(events.next());
+        }
+        Event::ArrayStart(_) => {
+            out.push(ev);
+            while let Some(ev) = events.peek() {
+                if matches!(ev, Event::ArrayEnd) {
+                    break;
+                }
+                sort_map_keys(events, out);
+            }
+            out.extend(events.next());
+        }
+        ev => out.push(ev),
+    }
+}
+
+fn stringify_key(ev: Event<'_>) -> Result<Event<'_>, MerdeError<'static>> {
+    let s = match ev {
+        Event::Bool(b) => CowStr::Borrowed(if b { "true" } else { "false" }),
+        Event::I64(i) => CowStr::copy_from_str(itoa::Buffer::new().format(i)),
+        Event::U64(u) => CowStr::copy_from_str(itoa::Buffer::new().format(u)),
+        Event::I128(i) => CowStr::copy_from_str(itoa::Buffer::new().format(i)),
+        Event::U128(u) => CowStr::copy_from_str(itoa::Buffer::new().format(u)),
+        Event::F64(f) => CowStr::copy_from_str(ryu::Buffer::new().format(f)),
+        ev => {
+            return Err(MerdeError::UnexpectedEvent {
+                got: EventType::from(&ev),
+                expected: &[
+                    EventType::Str,
+                    EventType::Bool,
+                    EventType::I64,
+                    EventType::U64,
+                    EventType::I128,
+                    EventType::U128,
+                    EventType::Float,
+                ],
+                help: Some(
+                    "JSON map keys must be strings, and this key can't be stringified".to_string(),
+                ),
+            })
+        }
+    };
+    Ok(Event::Str(s))
 }
 
 impl<W> Serializer for JsonSerializer<W>
@@ -79,105 +284,286 @@ where
         ev: Event<'fut>,
     ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
         async move {
-            let stack_top = self.stack.back_mut();
-            if let Some(stack_top) = stack_top {
-                match stack_top {
-                    StackFrame::Array { first } => {
-                        if matches!(ev, merde_core::Event::ArrayEnd) {
-                            self.w.extend_from_slice(b"]").await?;
-                            self.stack.pop_back();
-                            return Ok(());
-                        } else if *first {
-                            *first = false
-                        } else {
-                            self.w.extend_from_slice(b",").await?;
-                        }
-                    }
-                    StackFrame::MapKey { first } => {
-                        if matches!(ev, merde_core::Event::MapEnd) {
-                            self.w.extend_from_slice(b"}").await?;
-                            self.stack.pop_back();
-                            return Ok(());
-                        } else {
-                            if !*first {
-                                self.w.extend_from_slice(b",").await?;
-                            }
-                            *stack_top = StackFrame::MapValue;
-                            // and then let the value write itself
-                        }
+            if self.sort_keys && (self.sort_depth > 0 || matches!(ev, Event::MapStart(_))) {
+                match ev {
+                    Event::MapStart(_) | Event::ArrayStart(_) => self.sort_depth += 1,
+                    Event::MapEnd | Event::ArrayEnd => self.sort_depth -= 1,
+                    _ => {}
+                }
+                self.sort_buffer.push(ev.into_static());
+
+                if self.sort_depth == 0 {
+                    let mut events = std::mem::take(&mut self.sort_buffer).into_iter().peekable();
+                    let mut sorted = Vec::new();
+                    while events.peek().is_some() {
+                        sort_map_keys(&mut events, &mut sorted);
                     }
-                    StackFrame::MapValue => {
-                        self.w.extend_from_slice(b":").await?;
-                        *stack_top = StackFrame::MapKey { first: false };
+                    for ev in sorted {
+                        self.write_event(ev).await?;
                     }
                 }
+                return Ok(());
             }
 
-            match ev {
-                merde_core::Event::Null => {
-                    self.w.extend_from_slice(b"null").await?;
-                }
-                merde_core::Event::Bool(b) => {
-                    self.w
-                        .extend_from_slice(if b { b"true" } else { b"false" })
-                        .await?;
+            self.write_event(ev).await
+        }
+    }
+
+    #[allow(clippy::manual_async_fn)]
+    fn write_raw<'fut>(
+        &'fut mut self,
+        format: &'static str,
+        raw: &'fut str,
+    ) -> impl Future<Output = Result<bool, MerdeError<'static>>> + 'fut {
+        async move {
+            // keys must be sorted (and maybe stringified or skipped) as events
+            if format != "json" || self.sort_keys {
+                return Ok(false);
+            }
+            match self.stack.back_mut() {
+                Some(StackFrame::MapKey { .. } | StackFrame::Skip { .. }) => return Ok(false),
+                Some(StackFrame::Array { first }) => {
+                    let first = std::mem::replace(first, false);
+                    if !first {
+                        self.w.extend_from_slice(b",").await?;
+                    }
                 }
-                merde_core::Event::I64(i) => {
-                    let mut buf = itoa::Buffer::new();
-                    self.w.extend_from_slice(buf.format(i).as_bytes()).await?;
+                Some(frame @ StackFrame::MapValue) => {
+                    *frame = StackFrame::MapKey { first: false };
+                    self.w.extend_from_slice(b":").await?;
                 }
-                merde_core::Event::U64(u) => {
-                    let mut buf = itoa::Buffer::new();
-                    self.w.extend_from_slice(buf.format(u).as_bytes()).await?;
+                None => {}
+            }
+            self.w.extend_from_slice(raw.as_bytes()).await?;
+            Ok(true)
+        }
+    }
+}
+
+impl<W> JsonSerializer<W>
+where
+    W: JsonSerializerWriter,
+{
+    async fn write_event(&mut self, mut ev: Event<'_>) -> Result<(), MerdeError<'static>> {
+        let stack_top = self.stack.back_mut();
+        if let Some(stack_top) = stack_top {
+            match stack_top {
+                StackFrame::Skip { values, depth } => {
+                    match ev {
+                        Event::MapStart(_) | Event::ArrayStart(_) => *depth += 1,
+                        Event::MapEnd | Event::ArrayEnd => *depth -= 1,
+                        _ => {}
+                    }
+                    if *depth == 0 {
+                        *values -= 1;
+                        if *values == 0 {
+                            self.stack.pop_back();
+                        }
+                    }
+                    return Ok(());
                 }
-                merde_core::Event::F64(f) => {
-                    let mut buf = ryu::Buffer::new();
-                    self.w.extend_from_slice(buf.format(f).as_bytes()).await?;
+                StackFrame::Array { first } => {
+                    if matches!(ev, merde_core::Event::ArrayEnd) {
+                        self.w.extend_from_slice(b"]").await?;
+                        self.stack.pop_back();
+                        return Ok(());
+                    } else if *first {
+                        *first = false
+                    } else {
+                        self.w.extend_from_slice(b",").await?;
+                    }
                 }
-                merde_core::Event::Str(s) => {
-                    // slow path
-                    self.w.extend_from_slice(b"\"").await?;
-                    for c in s.chars() {
-                        match c {
-                            '"' => self.w.extend_from_slice(b"\\\"").await?,
-                            '\\' => self.w.extend_from_slice(b"\\\\").await?,
-                            '\n' => self.w.extend_from_slice(b"\\n").await?,
-                            '\r' => self.w.extend_from_slice(b"\\r").await?,
-                            '\t' => self.w.extend_from_slice(b"\\t").await?,
-                            c if c.is_control() => {
-                                let mut buf = [0u8; 6];
-                                write!(&mut buf[..], "\\u{:04x}", c as u32).unwrap();
-                                self.w.extend_from_slice(&buf[..6]).await?;
+                StackFrame::MapKey { first } => {
+                    if matches!(ev, merde_core::Event::MapEnd) {
+                        self.w.extend_from_slice(b"}").await?;
+                        self.stack.pop_back();
+                        return Ok(());
+                    } else {
+                        if !matches!(ev, Event::Str(_)) {
+                            match self.non_string_keys {
+                                NonStringKeyPolicy::Error => {
+                                    return Err(MerdeError::UnexpectedEvent {
+                                            got: EventType::from(&ev),
+                                            expected: &[EventType::Str],
+                                            help: Some(
+                                                "JSON map keys must be strings (see `JsonSerializer::with_non_string_key_policy`)"
+                                                    .to_string(),
+                                            ),
+                                        });
+                                }
+                                NonStringKeyPolicy::Stringify => {
+                                    ev = stringify_key(ev)?;
+                                }
+                                NonStringKeyPolicy::Skip => {
+                                    // skip the rest of the key (if it's a map or an array),
+                                    // then its value
+                                    let frame = match ev {
+                                        Event::MapStart(_) | Event::ArrayStart(_) => {
+                                            StackFrame::Skip {
+                                                values: 2,
+                                                depth: 1,
+                                            }
+                                        }
+                                        _ => StackFrame::Skip {
+                                            values: 1,
+                                            depth: 0,
+                                        },
+                                    };
+                                    self.stack.push_back(frame);
+                                    return Ok(());
+                                }
                             }
-                            c => self.w.extend_from_slice(c.to_string().as_bytes()).await?,
                         }
+                        if !*first {
+                            self.w.extend_from_slice(b",").await?;
+                        }
+                        *stack_top = StackFrame::MapValue;
+                        // and then let the value write itself
                     }
-                    self.w.extend_from_slice(b"\"").await?;
                 }
-                merde_core::Event::MapStart(_) => {
-                    self.w.extend_from_slice(b"{").await?;
-                    self.stack.push_back(StackFrame::MapKey { first: true });
+                StackFrame::MapValue => {
+                    self.w.extend_from_slice(b":").await?;
+                    *stack_top = StackFrame::MapKey { first: false };
                 }
-                merde_core::Event::MapEnd => {
-                    self.w.extend_from_slice(b"}").await?;
-                }
-                merde_core::Event::ArrayStart(_) => {
-                    self.w.extend_from_slice(b"[").await?;
-                    self.stack.push_back(StackFrame::Array { first: true });
-                }
-                merde_core::Event::ArrayEnd => {
-                    panic!("array end without array start");
+            }
+        }
+
+        match ev {
+            merde_core::Event::Null => {
+                self.w.extend_from_slice(b"null").await?;
+            }
+            merde_core::Event::Bool(b) => {
+                self.w
+                    .extend_from_slice(if b { b"true" } else { b"false" })
+                    .await?;
+            }
+            merde_core::Event::I64(i) => {
+                let mut buf = itoa::Buffer::new();
+                self.w.extend_from_slice(buf.format(i).as_bytes()).await?;
+            }
+            merde_core::Event::U64(u) => {
+                let mut buf = itoa::Buffer::new();
+                self.w.extend_from_slice(buf.format(u).as_bytes()).await?;
+            }
+            merde_core::Event::I128(i) => {
+                let mut buf = itoa::Buffer::new();
+                self.write_wide_int(buf.format(i)).await?;
+            }
+            merde_core::Event::U128(u) => {
+                let mut buf = itoa::Buffer::new();
+                self.write_wide_int(buf.format(u)).await?;
+            }
+            merde_core::Event::F64(f) => {
+                let mut buf = ryu::Buffer::new();
+                self.w.extend_from_slice(buf.format(f).as_bytes()).await?;
+            }
+            merde_core::Event::Str(s) => {
+                self.write_str(&s).await?;
+            }
+            merde_core::Event::MapStart(ms) => {
+                // at least `"":0,` per entry
+                self.w.reserve(ms.size_hint.unwrap_or(0).saturating_mul(5));
+                self.w.extend_from_slice(b"{").await?;
+                self.stack.push_back(StackFrame::MapKey { first: true });
+            }
+            merde_core::Event::MapEnd => {
+                self.w.extend_from_slice(b"}").await?;
+            }
+            merde_core::Event::ArrayStart(as_) => {
+                // at least `0,` per element
+                self.w.reserve(as_.size_hint.unwrap_or(0).saturating_mul(2));
+                self.w.extend_from_slice(b"[").await?;
+                self.stack.push_back(StackFrame::Array { first: true });
+            }
+            merde_core::Event::ArrayEnd => {
+                panic!("array end without array start");
+            }
+            merde_core::Event::Bytes(_) => {
+                // figure out what to do with those? maybe base64, maybe an array of
+                // integers? unclear. maybe it should be a serializer setting.
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W> JsonSerializer<W>
+where
+    W: JsonSerializerWriter,
+{
+    // Copies runs of bytes that don't need escaping in one go, rather than
+    // char by char.
+    async fn write_str(&mut self, s: &str) -> Result<(), MerdeError<'static>> {
+        self.w.reserve(s.len() + 2);
+        self.w.extend_from_slice(b"\"").await?;
+
+        let bytes = s.as_bytes();
+        let mut start = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            let b = bytes[i];
+            if !NEEDS_ESCAPE[b as usize] {
+                i += 1;
+                continue;
+            }
+
+            let mut unicode_buf = *b"\\u0000";
+            let (escape, len): (&[u8], usize) = match b {
+                b'"' => (b"\\\"", 1),
+                b'\\' => (b"\\\\", 1),
+                b'\n' => (b"\\n", 1),
+                b'\r' => (b"\\r", 1),
+                b'\t' => (b"\\t", 1),
+                // U+0080..=U+009F are encoded as 0xc2 0x80..=0x9f; other chars
+                // starting with 0xc2 are left alone
+                0xc2 if !(0x80..=0x9f).contains(&bytes[i + 1]) => {
+                    i += 2;
+                    continue;
                 }
-                merde_core::Event::Bytes(_) => {
-                    // figure out what to do with those? maybe base64, maybe an array of
-                    // integers? unclear. maybe it should be a serializer setting.
+                _ => {
+                    let (code, len) = if b == 0xc2 { (bytes[i + 1], 2) } else { (b, 1) };
+                    const HEX: &[u8; 16] = b"0123456789abcdef";
+                    unicode_buf[4] = HEX[(code >> 4) as usize];
+                    unicode_buf[5] = HEX[(code & 0xf) as usize];
+                    (&unicode_buf[..], len)
                 }
+            };
+
+            if start < i {
+                self.w.extend_from_slice(&bytes[start..i]).await?;
             }
-            Ok(())
+            self.w.extend_from_slice(escape).await?;
+            i += len;
+            start = i;
+        }
+        if start < bytes.len() {
+            self.w.extend_from_slice(&bytes[start..]).await?;
         }
+
+        self.w.extend_from_slice(b"\"").await?;
+        Ok(())
+    }
+
+    async fn write_wide_int(&mut self, digits: &str) -> Result<(), MerdeError<'static>> {
+        if self.wide_ints_as_strings {
+            self.w.extend_from_slice(b"\"").await?;
+            self.w.extend_from_slice(digits.as_bytes()).await?;
+            self.w.extend_from_slice(b"\"").await?;
+        } else {
+            self.w.extend_from_slice(digits.as_bytes()).await?;
+        }
+        Ok(())
     }
 }
 
+impl InfallibleSerializer for JsonSerializer<&mut Vec<u8>> {}
+
+#[cfg(feature = "bytes")]
+impl InfallibleSerializer for JsonSerializer<bytes::BytesMut> {}
+
+#[cfg(feature = "bytes")]
+impl InfallibleSerializer for JsonSerializer<&mut bytes::BytesMut> {}
+
 impl<W> JsonSerializer<W>
 where
     W: JsonSerializerWriter,
@@ -187,8 +573,47 @@ where
         JsonSerializer {
             w,
             stack: Default::default(),
+            non_string_keys: Default::default(),
+            wide_ints_as_strings: false,
+            sort_keys: false,
+            sort_buffer: Vec::new(),
+            sort_depth: 0,
         }
     }
+
+    /// Makes the serializer write map entries sorted by key, so that equal values
+    /// always produce the same bytes regardless of map iteration order (e.g. for
+    /// `HashMap` or `Map`), which is handy for content-addressed storage and signing.
+    ///
+    /// Keys are compared as UTF-8 bytes (which is the same as comparing code points).
+    /// Each top-level map is buffered in full before it's written out.
+    pub fn with_sorted_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Makes the serializer write integers that don't fit in 64 bits (`i128`
+    /// and `u128` values, see [`Event::I128`]) as strings, like `"170141183460469231731687303715884105727"`,
+    /// for consumers that would otherwise round them (JavaScript's `JSON.parse`,
+    /// for one). They're written as numbers by default.
+    ///
+    /// Either way, they deserialize back into `i128` and `u128`.
+    pub fn with_wide_ints_as_strings(mut self, wide_ints_as_strings: bool) -> Self {
+        self.wide_ints_as_strings = wide_ints_as_strings;
+        self
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+
+    /// Sets what to do when a map key isn't a string (e.g. when serializing
+    /// a `HashMap<i32, T>`). Defaults to [`NonStringKeyPolicy::Error`].
+    pub fn with_non_string_key_policy(mut self, policy: NonStringKeyPolicy) -> Self {
+        self.non_string_keys = policy;
+        self
+    }
 }
 
 impl<'w> JsonSerializer<SyncWriteWrapper<'w>> {
@@ -198,6 +623,47 @@ impl<'w> JsonSerializer<SyncWriteWrapper<'w>> {
     }
 }
 
+impl<'w> JsonSerializer<FmtWriteWrapper<'w>> {
+    /// Makes a json serializer that writes to a std::fmt::Write, like a
+    /// `String`, or the `Formatter` of a `Display` impl:
+    ///
+    /// ```rust
+    /// use std::fmt;
+    /// use merde_core::DynSerializerExt;
+    /// use merde_json::JsonSerializer;
+    ///
+    /// struct Tags(Vec<&'static str>);
+    ///
+    /// impl fmt::Display for Tags {
+    ///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         JsonSerializer::from_fmt_writer(f)
+    ///             .serialize(&self.0)
+    ///             .map_err(|_| fmt::Error)
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Tags(vec!["a", "b"]).to_string(), r#"["a","b"]"#);
+    /// ```
+    pub fn from_fmt_writer(w: &'w mut dyn std::fmt::Write) -> JsonSerializer<FmtWriteWrapper<'w>> {
+        JsonSerializer::new(FmtWriteWrapper(w))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl JsonSerializer<bytes::BytesMut> {
+    /// Makes a json serializer that writes to a `BytesMut`, e.g. one obtained
+    /// from a network stack's buffer pool.
+    pub fn from_bytes_mut(buf: bytes::BytesMut) -> Self {
+        JsonSerializer::new(buf)
+    }
+
+    /// Consumes the serializer and returns what was written as an immutable
+    /// `Bytes`, without copying.
+    pub fn freeze(self) -> bytes::Bytes {
+        self.w.freeze()
+    }
+}
+
 #[cfg(feature = "tokio")]
 impl<'w> JsonSerializer<tokio_io::AsyncWriteWrapper<'w>> {
     /// Makes a json serializer that writes to a tokio::io::AsyncWrite
@@ -207,3 +673,221 @@ impl<'w> JsonSerializer<tokio_io::AsyncWriteWrapper<'w>> {
         JsonSerializer::new(tokio_io::AsyncWriteWrapper(w))
     }
 }
+
+#[cfg(feature = "futures-io")]
+impl<'w> JsonSerializer<futures_io::AsyncWriteWrapper<'w>> {
+    /// Makes a json serializer that writes to a futures_io::AsyncWrite
+    pub fn from_futures_writer<SW: ::futures_io::AsyncWrite + 'w>(
+        w: std::pin::Pin<&'w mut SW>,
+    ) -> JsonSerializer<futures_io::AsyncWriteWrapper<'w>> {
+        JsonSerializer::new(futures_io::AsyncWriteWrapper(w))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use merde_core::{DynSerializerExt, MapStart, Serialize};
+
+    use super::*;
+
+    fn to_string_with(
+        policy: NonStringKeyPolicy,
+        value: &impl Serialize,
+    ) -> Result<String, MerdeError<'static>> {
+        let mut v = Vec::new();
+        JsonSerializer::new(&mut v)
+            .with_non_string_key_policy(policy)
+            .serialize(value)?;
+        Ok(String::from_utf8(v).unwrap())
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let cases = [
+            ("", r#""""#),
+            ("plain ascii", r#""plain ascii""#),
+            ("héllo, wörld ✨", r#""héllo, wörld ✨""#),
+            ("a\"b\\c", r#""a\"b\\c""#),
+            ("\n\r\t", r#""\n\r\t""#),
+            ("\0\x1f\x7f", r#""\u0000\u001f\u007f""#),
+            // C1 controls are escaped, but not U+00A0 and U+00E9
+            ("\u{85}é\u{9f}\u{a0}", "\"\\u0085é\\u009f\u{a0}\""),
+            ("tail\n", r#""tail\n""#),
+        ];
+        for (input, expected) in cases {
+            let mut v = Vec::new();
+            JsonSerializer::new(&mut v).serialize(&input).unwrap();
+            assert_eq!(String::from_utf8(v).unwrap(), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_fmt_writer() {
+        let mut s = String::from("value: ");
+        JsonSerializer::from_fmt_writer(&mut s)
+            .serialize(&vec!["héllo", "\"wörld\"\n"])
+            .unwrap();
+        assert_eq!(s, r#"value: ["héllo","\"wörld\"\n"]"#);
+    }
+
+    #[test]
+    fn test_non_string_key_error() {
+        let map: HashMap<i32, i32> = [(1, 2)].into();
+        assert!(matches!(
+            to_string_with(NonStringKeyPolicy::Error, &map),
+            Err(MerdeError::UnexpectedEvent {
+                got: EventType::I64,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_non_string_key_stringify() {
+        let map: HashMap<i32, i32> = [(1, 2)].into();
+        assert_eq!(
+            to_string_with(NonStringKeyPolicy::Stringify, &map).unwrap(),
+            r#"{"1":2}"#
+        );
+
+        let map: HashMap<bool, f64> = [(true, 0.5)].into();
+        assert_eq!(
+            to_string_with(NonStringKeyPolicy::Stringify, &map).unwrap(),
+            r#"{"true":0.5}"#
+        );
+
+        let map: HashMap<Vec<i32>, i32> = [(vec![1], 2)].into();
+        assert!(to_string_with(NonStringKeyPolicy::Stringify, &map).is_err());
+    }
+
+    struct MixedKeys;
+
+    impl Serialize for MixedKeys {
+        async fn serialize<'fut>(
+            &'fut self,
+            serializer: &'fut mut dyn merde_core::DynSerializer,
+        ) -> Result<(), MerdeError<'static>> {
+            serializer
+                .write(Event::MapStart(MapStart { size_hint: None }))
+                .await?;
+            serializer.write(Event::I64(1)).await?;
+            vec![1, 2].serialize(serializer).await?;
+            serializer.write(Event::Str("a".into())).await?;
+            serializer.write(Event::I64(1)).await?;
+            vec![3].serialize(serializer).await?;
+            serializer.write(Event::Null).await?;
+            serializer.write(Event::Str("b".into())).await?;
+            serializer.write(Event::I64(2)).await?;
+            serializer.write(Event::MapEnd).await
+        }
+    }
+
+    #[test]
+    fn test_sorted_keys() {
+        let inner: HashMap<&str, i32> = [("b", 1), ("a", 2), ("c", 3)].into();
+        let outer: HashMap<&str, Vec<HashMap<&str, i32>>> = [
+            ("z", vec![inner.clone()]),
+            ("é", vec![]),
+            ("m", vec![inner]),
+        ]
+        .into();
+
+        let mut v = Vec::new();
+        JsonSerializer::new(&mut v)
+            .with_sorted_keys(true)
+            .serialize(&(1, outer))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(v).unwrap(),
+            r#"[1,{"m":[{"a":2,"b":1,"c":3}],"z":[{"a":2,"b":1,"c":3}],"é":[]}]"#
+        );
+
+        let map: HashMap<i32, i32> = [(10, 0), (9, 0), (1, 0)].into();
+        let mut v = Vec::new();
+        JsonSerializer::new(&mut v)
+            .with_sorted_keys(true)
+            .with_non_string_key_policy(NonStringKeyPolicy::Stringify)
+            .serialize(&map)
+            .unwrap();
+        assert_eq!(String::from_utf8(v).unwrap(), r#"{"1":0,"10":0,"9":0}"#);
+    }
+
+    #[test]
+    fn test_non_string_key_skip() {
+        assert_eq!(
+            to_string_with(NonStringKeyPolicy::Skip, &MixedKeys).unwrap(),
+            r#"{"a":1,"b":2}"#
+        );
+
+        let map: HashMap<i32, i32> = [(1, 2)].into();
+        assert_eq!(
+            to_string_with(NonStringKeyPolicy::Skip, &map).unwrap(),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn test_wide_ints_as_strings() {
+        let mut v = Vec::new();
+        JsonSerializer::new(&mut v)
+            .with_wide_ints_as_strings(true)
+            .serialize(&vec![1i128, i128::MAX])
+            .unwrap();
+        let json = String::from_utf8(v).unwrap();
+        assert_eq!(json, r#"[1,"170141183460469231731687303715884105727"]"#);
+        assert_eq!(crate::from_str::<Vec<i128>>(&json).unwrap(), [1, i128::MAX]);
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn test_bytes_mut() {
+        let mut buf = bytes::BytesMut::with_capacity(64);
+        buf.extend_from_slice(b"prefix:");
+        let mut ser = JsonSerializer::from_bytes_mut(buf);
+        ser.serialize(&vec![1, 2]).unwrap();
+        assert_eq!(ser.freeze(), &b"prefix:[1,2]"[..]);
+
+        assert_eq!(crate::to_bytes(&"hi").unwrap(), &b"\"hi\""[..]);
+    }
+
+    #[test]
+    #[cfg(feature = "futures-io")]
+    fn test_futures_writer() {
+        // writes at most 3 bytes at a time, to exercise partial writes
+        struct Trickle(Vec<u8>);
+
+        impl ::futures_io::AsyncWrite for Trickle {
+            fn poll_write(
+                mut self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+                buf: &[u8],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                let n = buf.len().min(3);
+                self.0.extend_from_slice(&buf[..n]);
+                std::task::Poll::Ready(Ok(n))
+            }
+
+            fn poll_flush(
+                self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                std::task::Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(
+                self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                std::task::Poll::Ready(Ok(()))
+            }
+        }
+
+        let mut out = Trickle(Vec::new());
+        JsonSerializer::from_futures_writer(std::pin::Pin::new(&mut out))
+            .serialize(&vec!["hello", "world"])
+            .unwrap();
+        assert_eq!(out.0, br#"["hello","world"]"#);
+    }
+}
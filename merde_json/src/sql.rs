@@ -0,0 +1,129 @@
+//! Storing values as JSON in SQLite `TEXT` columns, via rusqlite.
+
+use std::ops::{Deref, DerefMut};
+
+use merde_core::{DeserializeOwned, IntoStatic, Serialize, WithLifetime};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+
+/// A wrapper that stores any value as JSON text when passed as a rusqlite
+/// parameter, and reads it back from a `TEXT` (or `BLOB`) column.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use merde_json::JsonSql;
+/// use rusqlite::Connection;
+///
+/// let conn = Connection::open_in_memory().unwrap();
+/// conn.execute("CREATE TABLE users (name TEXT, settings TEXT)", []).unwrap();
+///
+/// let settings = HashMap::from([("theme".to_string(), "dark".to_string())]);
+/// conn.execute(
+///     "INSERT INTO users (name, settings) VALUES (?1, ?2)",
+///     rusqlite::params!["amos", JsonSql(settings.clone())],
+/// )
+/// .unwrap();
+///
+/// let JsonSql(read_back): JsonSql<HashMap<String, String>> = conn
+///     .query_row("SELECT settings FROM users", [], |row| row.get(0))
+///     .unwrap();
+/// assert_eq!(read_back, settings);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct JsonSql<T>(pub T);
+
+impl<T> From<T> for JsonSql<T> {
+    fn from(t: T) -> Self {
+        JsonSql(t)
+    }
+}
+
+impl<T> Deref for JsonSql<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for JsonSql<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<'s, T> WithLifetime<'s> for JsonSql<T>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = JsonSql<T::Lifetimed>;
+}
+
+impl<T> IntoStatic for JsonSql<T>
+where
+    T: IntoStatic,
+{
+    type Output = JsonSql<T::Output>;
+
+    fn into_static(self) -> Self::Output {
+        JsonSql(self.0.into_static())
+    }
+}
+
+impl<T> ToSql for JsonSql<T>
+where
+    T: Serialize,
+{
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        let json = crate::to_string(&self.0)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+        Ok(ToSqlOutput::from(json))
+    }
+}
+
+impl<T> FromSql for JsonSql<T>
+where
+    T: DeserializeOwned,
+{
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let bytes = match value {
+            ValueRef::Text(bytes) | ValueRef::Blob(bytes) => bytes,
+            _ => return Err(FromSqlError::InvalidType),
+        };
+        crate::from_bytes_owned(bytes)
+            .map(JsonSql)
+            .map_err(|e| FromSqlError::Other(e.into_static().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::*;
+
+    #[test]
+    fn test_json_column() -> Result<(), Box<dyn std::error::Error>> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, doc TEXT)", [])?;
+
+        let doc = vec![Some(1u32), None, Some(3)];
+        conn.execute("INSERT INTO t (doc) VALUES (?1)", [JsonSql(doc.clone())])?;
+
+        let raw: String = conn.query_row("SELECT doc FROM t", [], |row| row.get(0))?;
+        assert_eq!(raw, "[1,null,3]");
+
+        let read_back: JsonSql<Vec<Option<u32>>> =
+            conn.query_row("SELECT doc FROM t", [], |row| row.get(0))?;
+        assert_eq!(read_back.0, doc);
+
+        // not JSON, or not the right shape
+        for bad in ["'nope'", "'[\"a\"]'", "42"] {
+            let res: rusqlite::Result<JsonSql<Vec<u32>>> =
+                conn.query_row(&format!("SELECT {bad}"), [], |row| row.get(0));
+            assert!(res.is_err(), "{bad}");
+        }
+
+        Ok(())
+    }
+}
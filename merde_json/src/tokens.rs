@@ -0,0 +1,316 @@
+//! A token-level view of JSON text, for tools that need to know where things
+//! are (highlighters, validators...) rather than deserialize them.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use merde_core::{CowStr, MerdeError};
+
+use crate::{
+    deserialize::{cowify, jiter_error},
+    jiter_lite::{jiter::Jiter, parse::Peek},
+};
+
+/// What a [JsonToken] is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonTokenKind<'s> {
+    /// `{`
+    ObjectStart,
+    /// `}`
+    ObjectEnd,
+    /// `[`
+    ArrayStart,
+    /// `]`
+    ArrayEnd,
+    /// An object key, unescaped
+    Key(CowStr<'s>),
+    /// A string value, unescaped
+    Str(CowStr<'s>),
+    /// A number, as written
+    Number(&'s str),
+    /// `true` or `false`
+    Bool(bool),
+    /// `null`
+    Null,
+}
+
+/// A token of JSON text, and where it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonToken<'s> {
+    /// What the token is
+    pub kind: JsonTokenKind<'s>,
+    /// Where the token is in the source, in bytes. Strings and keys include
+    /// their quotes.
+    pub span: Range<usize>,
+}
+
+#[derive(Debug)]
+enum Frame<'s> {
+    // the first key was read along with the `{`
+    ObjectKey(Option<JsonToken<'s>>),
+    ObjectValue,
+    // `}` was read along with the `{`, and ends there
+    ObjectEnd(usize),
+    // the first value's start was peeked at along with the `[`
+    Array(Option<Peek>),
+    // `]` was read along with the `[`, and ends there
+    ArrayEnd(usize),
+}
+
+/// An iterator over the tokens of a JSON document, with their positions, that
+/// checks its syntax as it goes (trailing characters included).
+///
+/// This doesn't involve [Deserialize](merde_core::Deserialize) at all: it's
+/// the parser [JsonDeserializer](crate::JsonDeserializer) is built on.
+///
+/// ```rust
+/// use merde_json::{JsonTokenKind, JsonTokens};
+///
+/// let input = r#"{"id": 42}"#;
+/// let tokens: Vec<_> = JsonTokens::new(input).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(tokens[1].kind, JsonTokenKind::Key("id".into()));
+/// assert_eq!(&input[tokens[1].span.clone()], r#""id""#);
+/// assert_eq!(tokens[2].kind, JsonTokenKind::Number("42"));
+/// assert_eq!(tokens[2].span, 7..9);
+/// ```
+///
+/// Iteration stops after the first error.
+pub struct JsonTokens<'s> {
+    source: &'s str,
+    jiter: Jiter<'s>,
+    stack: Vec<Frame<'s>>,
+    started: bool,
+    done: bool,
+}
+
+impl core::fmt::Debug for JsonTokens<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("JsonTokens")
+            .field("index", &self.jiter.current_index())
+            .field("depth", &self.stack.len())
+            .finish()
+    }
+}
+
+impl<'s> JsonTokens<'s> {
+    /// Tokenizes `source`, which must hold a single JSON value.
+    pub fn new(source: &'s str) -> Self {
+        Self {
+            source,
+            jiter: Jiter::new(source.as_bytes()),
+            stack: Vec::new(),
+            started: false,
+            done: false,
+        }
+    }
+
+    /// How many objects and arrays the next token is in.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn token(&self, kind: JsonTokenKind<'s>, start: usize) -> JsonToken<'s> {
+        JsonToken {
+            kind,
+            span: start..self.jiter.current_index(),
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<JsonToken<'s>>, MerdeError<'s>> {
+        let source = self.source;
+        let err = move |e| jiter_error(source, e);
+        let peek = match self.stack.pop() {
+            Some(Frame::ObjectKey(Some(key))) => {
+                self.stack.push(Frame::ObjectValue);
+                return Ok(Some(key));
+            }
+            Some(Frame::ObjectKey(None)) => {
+                return match self.jiter.next_key().map_err(err)? {
+                    Some(key) => {
+                        let kind = JsonTokenKind::Key(cowify(source.as_bytes(), key));
+                        self.stack.push(Frame::ObjectValue);
+                        Ok(Some(JsonToken {
+                            kind,
+                            span: self.jiter.last_key_span(),
+                        }))
+                    }
+                    None => {
+                        let index = self.jiter.current_index();
+                        Ok(Some(self.token(JsonTokenKind::ObjectEnd, index - 1)))
+                    }
+                };
+            }
+            Some(Frame::ObjectValue) => {
+                self.stack.push(Frame::ObjectKey(None));
+                self.jiter.peek().map_err(err)?
+            }
+            Some(Frame::ObjectEnd(end)) => {
+                return Ok(Some(JsonToken {
+                    kind: JsonTokenKind::ObjectEnd,
+                    span: end - 1..end,
+                }))
+            }
+            Some(Frame::Array(Some(peek))) => {
+                self.stack.push(Frame::Array(None));
+                peek
+            }
+            Some(Frame::Array(None)) => match self.jiter.array_step().map_err(err)? {
+                Some(peek) => {
+                    self.stack.push(Frame::Array(None));
+                    peek
+                }
+                None => {
+                    let index = self.jiter.current_index();
+                    return Ok(Some(self.token(JsonTokenKind::ArrayEnd, index - 1)));
+                }
+            },
+            Some(Frame::ArrayEnd(end)) => {
+                return Ok(Some(JsonToken {
+                    kind: JsonTokenKind::ArrayEnd,
+                    span: end - 1..end,
+                }))
+            }
+            None if self.started => {
+                self.jiter.finish().map_err(err)?;
+                return Ok(None);
+            }
+            None => {
+                self.started = true;
+                self.jiter.peek().map_err(err)?
+            }
+        };
+
+        let start = self.jiter.current_index();
+        let kind = if peek == Peek::Null {
+            self.jiter.known_null().map_err(err)?;
+            JsonTokenKind::Null
+        } else if peek == Peek::True || peek == Peek::False {
+            let b = self.jiter.known_bool(peek).map_err(err)?;
+            JsonTokenKind::Bool(b)
+        } else if peek.is_num() {
+            self.jiter.known_float(peek).map_err(err)?;
+            JsonTokenKind::Number(&source[start..self.jiter.current_index()])
+        } else if peek == Peek::String {
+            let s = self.jiter.known_str().map_err(err)?;
+            JsonTokenKind::Str(cowify(source.as_bytes(), s))
+        } else if peek == Peek::Array {
+            match self.jiter.known_array().map_err(err)? {
+                Some(peek) => self.stack.push(Frame::Array(Some(peek))),
+                None => self.stack.push(Frame::ArrayEnd(self.jiter.current_index())),
+            }
+            return Ok(Some(JsonToken {
+                kind: JsonTokenKind::ArrayStart,
+                span: start..start + 1,
+            }));
+        } else if peek == Peek::Object {
+            let frame = match self.jiter.known_object().map_err(err)? {
+                Some(key) => {
+                    let kind = JsonTokenKind::Key(cowify(source.as_bytes(), key));
+                    Frame::ObjectKey(Some(JsonToken {
+                        kind,
+                        span: self.jiter.last_key_span(),
+                    }))
+                }
+                None => Frame::ObjectEnd(self.jiter.current_index()),
+            };
+            self.stack.push(frame);
+            return Ok(Some(JsonToken {
+                kind: JsonTokenKind::ObjectStart,
+                span: start..start + 1,
+            }));
+        } else {
+            return Err(err(self.jiter.expected_value_error()));
+        };
+        Ok(Some(self.token(kind, start)))
+    }
+}
+
+impl<'s> Iterator for JsonTokens<'s> {
+    type Item = Result<JsonToken<'s>, MerdeError<'s>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_token() {
+            Ok(Some(token)) => Some(Ok(token)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn tokens(input: &str) -> Vec<(JsonTokenKind<'_>, &str)> {
+        JsonTokens::new(input)
+            .map(|t| {
+                let t = t.unwrap();
+                (t.kind, &input[t.span])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tokens() {
+        use JsonTokenKind::*;
+
+        let input =
+            r#" {"a": [1, -2.5e3, "x\"y"], "bé" : {}, "c": [], "d": {"e": null}, "f": true} "#;
+        assert_eq!(
+            tokens(input),
+            vec![
+                (ObjectStart, "{"),
+                (Key("a".into()), r#""a""#),
+                (ArrayStart, "["),
+                (Number("1"), "1"),
+                (Number("-2.5e3"), "-2.5e3"),
+                (Str("x\"y".into()), r#""x\"y""#),
+                (ArrayEnd, "]"),
+                (Key("bé".into()), r#""bé""#),
+                (ObjectStart, "{"),
+                (ObjectEnd, "}"),
+                (Key("c".into()), r#""c""#),
+                (ArrayStart, "["),
+                (ArrayEnd, "]"),
+                (Key("d".into()), r#""d""#),
+                (ObjectStart, "{"),
+                (Key("e".into()), r#""e""#),
+                (Null, "null"),
+                (ObjectEnd, "}"),
+                (Key("f".into()), r#""f""#),
+                (Bool(true), "true"),
+                (ObjectEnd, "}"),
+            ]
+        );
+        assert_eq!(tokens("42"), vec![(Number("42"), "42")]);
+    }
+
+    #[test]
+    fn test_token_errors() {
+        for input in ["[1, 2", "[1,]", "{\"a\" 1}", "[1] 2", "", "[nope]"] {
+            let res: Result<Vec<_>, _> = JsonTokens::new(input).collect();
+            assert!(res.is_err(), "{input:?} should fail");
+        }
+
+        // the tokens before the error come first, then iteration stops
+        let mut tokens = JsonTokens::new("[true, ?]");
+        assert_eq!(
+            tokens.next().unwrap().unwrap().kind,
+            JsonTokenKind::ArrayStart
+        );
+        assert_eq!(tokens.depth(), 1);
+        assert!(tokens.next().unwrap().is_ok());
+        assert!(tokens.next().unwrap().is_err());
+        assert!(tokens.next().is_none());
+    }
+}
@@ -1,11 +1,13 @@
-use merde_core::{Deserializer, Event, MerdeError};
+use std::{collections::VecDeque, ops::Range};
+
+use merde_core::{Deserializer, ErrorCollector, Event, MerdeError, NumberPolicy, TypeHint};
 
 pub struct LoggingDeserializer<'s, I>
 where
     I: Deserializer<'s>,
 {
     inner: I,
-    starter: Option<Event<'s>>,
+    starters: VecDeque<Event<'s>>,
 }
 
 impl<'s, I> std::fmt::Debug for LoggingDeserializer<'s, I>
@@ -26,7 +28,7 @@ where
     pub fn new(inner: I) -> Self {
         Self {
             inner,
-            starter: None,
+            starters: VecDeque::new(),
         }
     }
 }
@@ -36,7 +38,7 @@ where
     I: Deserializer<'s>,
 {
     async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
-        if let Some(ev) = self.starter.take() {
+        if let Some(ev) = self.starters.pop_front() {
             eprintln!("> (from starter) {:?}", ev);
             return Ok(ev);
         }
@@ -47,10 +49,30 @@ where
     }
 
     fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
-        if self.starter.is_some() {
-            return Err(MerdeError::PutBackCalledTwice);
-        }
-        self.starter = Some(ev);
+        self.starters.push_front(ev);
         Ok(())
     }
+
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
+            eprintln!("> (from starter, hint {:?}) {:?}", hint, ev);
+            return Ok(ev);
+        }
+
+        let ev = self.inner.next_event_with_hint(hint).await?;
+        eprintln!("> (from inner.next_event_with_hint({:?})) {:?}", hint, ev);
+        Ok(ev)
+    }
+
+    fn number_policy(&self) -> NumberPolicy {
+        self.inner.number_policy()
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        self.inner.span()
+    }
+
+    fn error_collector(&mut self) -> Option<&mut ErrorCollector<'s>> {
+        self.inner.error_collector()
+    }
 }
@@ -1,23 +1,27 @@
 #![deny(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+use std::collections::VecDeque;
+
 use merde_core::{
-    Deserialize, DeserializeOwned, Deserializer, DynDeserializerExt, Event, MapStart, MerdeError,
-    MetastackExt,
+    CowBytes, CowStr, Deserialize, DeserializeOwned, Deserializer, DuplicateKeyFilter,
+    DuplicateKeyPolicy, DynDeserializerExt, DynSerialize, DynSerializerExt, EncodedSize, Event,
+    EventType, MapStart, MerdeError, SizeCountingSerializer, TypeHint,
 };
 
+mod serialize;
+pub use serialize::MsgpackSerializer;
+
+mod stack;
+use stack::{Stack, StackItem};
+
 /// A MessagePack deserializer, that implements [`merde_core::Deserializer`].
 pub struct MsgpackDeserializer<'s> {
     source: &'s [u8],
     offset: usize,
-    stack: Vec<StackItem>,
-    starter: Option<Event<'s>>,
-}
-
-#[derive(Debug)]
-enum StackItem {
-    Array(usize),
-    Map(usize),
+    stack: Stack,
+    starters: VecDeque<Event<'s>>,
+    keys: DuplicateKeyFilter<'s>,
 }
 
 impl<'s> MsgpackDeserializer<'s> {
@@ -26,10 +30,18 @@ impl<'s> MsgpackDeserializer<'s> {
         Self {
             source,
             offset: 0,
-            stack: Vec::new(),
-            starter: None,
+            stack: Stack::new(),
+            starters: VecDeque::new(),
+            keys: Default::default(),
         }
     }
+
+    /// Sets what to do when a map has the same (string) key more than once.
+    /// Defaults to [`DuplicateKeyPolicy::LastWins`].
+    pub fn with_duplicate_key_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.keys = DuplicateKeyFilter::new(policy);
+        self
+    }
 }
 
 impl std::fmt::Debug for MsgpackDeserializer<'_> {
@@ -43,10 +55,100 @@ impl std::fmt::Debug for MsgpackDeserializer<'_> {
 
 impl<'s> Deserializer<'s> for MsgpackDeserializer<'s> {
     async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
-        if let Some(ev) = self.starter.take() {
+        self.next_filtered(None)
+    }
+
+    fn put_back(&mut self, event: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.starters.push_front(event);
+        Ok(())
+    }
+
+    // `str` and `bin` are often used interchangeably by other implementations
+    // (older ones only have `raw`), so read whichever the caller wants
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
+            // the event was read without a hint (e.g. by `Option<T>` peeking),
+            // convert it after the fact
+            return match (ev, hint) {
+                (Event::Str(s), TypeHint::Bytes) => Ok(Event::Bytes(match s {
+                    CowStr::Borrowed(s) => s.as_bytes().into(),
+                    s => s.as_bytes().to_vec().into(),
+                })),
+                (Event::Bytes(b), TypeHint::Str) => Ok(Event::Str(match b {
+                    CowBytes::Borrowed(b) => std::str::from_utf8(b)?.into(),
+                    b => String::from_utf8(b.into_owned())
+                        .map_err(|e| e.utf8_error())?
+                        .into(),
+                })),
+                (ev, _) => Ok(ev),
+            };
+        }
+        self.next_filtered(Some(hint))
+    }
+
+    // strings, maps and arrays are jumped over using their lengths (strings
+    // aren't checked for UTF-8 then)
+    async fn skip_value(&mut self) -> Result<(), MerdeError<'s>> {
+        // skipped values would get around duplicate key filtering
+        if self.keys.policy() == DuplicateKeyPolicy::LastWins && self.starters.len() <= 1 {
+            match self.starters.pop_front() {
+                // the container's header was read: skip what's left of it
+                Some(Event::MapStart(_) | Event::ArrayStart(_)) => {
+                    if let Some(StackItem::Array(count) | StackItem::Map(count)) = self.stack.pop()
+                    {
+                        return self.skip_items(count);
+                    }
+                }
+                Some(ev @ (Event::MapEnd | Event::ArrayEnd)) => self.starters.push_front(ev),
+                Some(_) => return Ok(()),
+                None => match self.stack.last_mut() {
+                    // the container ends here, there's no value to skip
+                    Some(StackItem::Array(0) | StackItem::Map(0)) => {}
+                    Some(StackItem::Array(count) | StackItem::Map(count)) => {
+                        *count -= 1;
+                        return self.skip_items(1);
+                    }
+                    None => return self.skip_items(1),
+                },
+            }
+        }
+
+        let mut depth = 0usize;
+        loop {
+            match self.next_filtered(None)? {
+                Event::MapStart(_) | Event::ArrayStart(_) => depth += 1,
+                Event::MapEnd | Event::ArrayEnd if depth > 0 => depth -= 1,
+                ev @ (Event::MapEnd | Event::ArrayEnd) => {
+                    return Err(MerdeError::UnexpectedEvent {
+                        got: EventType::from(&ev),
+                        expected: &[],
+                        help: Some("(While skipping a value)".to_string()),
+                    })
+                }
+                _ => {}
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<'s> MsgpackDeserializer<'s> {
+    fn next_filtered(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
+        if let Some(ev) = self.starters.pop_front() {
             return Ok(ev);
         }
 
+        loop {
+            let ev = self.next_event(hint)?;
+            if let Some(ev) = self.keys.filter(ev)? {
+                return Ok(ev);
+            }
+        }
+    }
+
+    fn next_event(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
         if let Some(stack_item) = self.stack.last_mut() {
             match stack_item {
                 StackItem::Array(count) => {
@@ -77,9 +179,9 @@ impl<'s> Deserializer<'s> for MsgpackDeserializer<'s> {
             0xc0 => Ok(Event::Null),
             0xc2 => Ok(Event::Bool(false)),
             0xc3 => Ok(Event::Bool(true)),
-            0xc4 => self.read_bytes_8(),
-            0xc5 => self.read_bytes_16(),
-            0xc6 => self.read_bytes_32(),
+            0xc4 => self.read_bytes_8(hint),
+            0xc5 => self.read_bytes_16(hint),
+            0xc6 => self.read_bytes_32(hint),
             0xcc => self.read_u8().map(|v| Event::U64(v as u64)),
             0xcd => self.read_u16().map(|v| Event::U64(v as u64)),
             0xce => self.read_u32().map(|v| Event::U64(v as u64)),
@@ -92,16 +194,16 @@ impl<'s> Deserializer<'s> for MsgpackDeserializer<'s> {
             0xcb => self.read_f64().map(Event::F64),
             0xa0..=0xbf => {
                 let len = (byte & 0x1f) as usize;
-                self.read_str(len)
+                self.read_str(len, hint)
             }
-            0xd9 => self.read_str_8(),
-            0xda => self.read_str_16(),
-            0xdb => self.read_str_32(),
+            0xd9 => self.read_str_8(hint),
+            0xda => self.read_str_16(hint),
+            0xdb => self.read_str_32(hint),
             0x90..=0x9f => {
                 let len = (byte & 0x0f) as usize;
                 self.stack.push(StackItem::Array(len));
                 Ok(Event::ArrayStart(merde_core::ArrayStart {
-                    size_hint: Some(len),
+                    size_hint: Some(self.size_hint(len, 1)),
                 }))
             }
             0xdc => self.read_array_16(),
@@ -110,43 +212,61 @@ impl<'s> Deserializer<'s> for MsgpackDeserializer<'s> {
                 let len = (byte & 0x0f) as usize;
                 self.stack.push(StackItem::Map(len * 2));
                 Ok(Event::MapStart(MapStart {
-                    size_hint: Some(len as _),
+                    size_hint: Some(self.size_hint(len, 2)),
                 }))
             }
             0xde => {
                 let len = self.read_u16()?;
                 self.stack.push(StackItem::Map(len as usize * 2));
                 Ok(Event::MapStart(MapStart {
-                    size_hint: Some(len as _),
+                    size_hint: Some(self.size_hint(len as usize, 2)),
                 }))
             }
             0xdf => {
                 let len = self.read_u32()?;
                 self.stack.push(StackItem::Map(len as usize * 2));
                 Ok(Event::MapStart(MapStart {
-                    size_hint: Some(len as _),
+                    size_hint: Some(self.size_hint(len as usize, 2)),
                 }))
             }
             0x00..=0x7f => Ok(Event::U64(byte as u64)),
             0xe0..=0xff => Ok(Event::I64((byte as i8) as i64)),
-            0xd4..=0xd8 | 0xc7..=0xc9 => Err(MerdeError::BinaryParsingError {
-                format: "msgpack",
-                message: format!("unsupported extension type 0x{byte:02x}"),
-            }),
-            _ => Err(MerdeError::BinaryParsingError {
-                format: "msgpack",
-                message: format!("unsupported type 0x{byte:02x}"),
-            }),
+            _ => Err(unsupported_type(byte)),
         }
     }
 
-    fn put_back(&mut self, event: Event<'s>) -> Result<(), MerdeError<'s>> {
-        self.starter = Some(event);
+    /// Skips `count` values (keys count as values), without decoding them.
+    fn skip_items(&mut self, mut count: usize) -> Result<(), MerdeError<'s>> {
+        while count > 0 {
+            count -= 1;
+            let byte = self.read_u8()?;
+            let (len, items) = match byte {
+                0x00..=0x7f | 0xe0..=0xff | 0xc0 | 0xc2 | 0xc3 => (0, 0),
+                0xcc | 0xd0 => (1, 0),
+                0xcd | 0xd1 => (2, 0),
+                0xce | 0xd2 | 0xca => (4, 0),
+                0xcf | 0xd3 | 0xcb => (8, 0),
+                0xa0..=0xbf => ((byte & 0x1f) as usize, 0),
+                0xc4 | 0xd9 => (self.read_u8()? as usize, 0),
+                0xc5 | 0xda => (self.read_u16()? as usize, 0),
+                0xc6 | 0xdb => (self.read_u32()? as usize, 0),
+                0x90..=0x9f => (0, (byte & 0x0f) as usize),
+                0xdc => (0, self.read_u16()? as usize),
+                0xdd => (0, self.read_u32()? as usize),
+                0x80..=0x8f => (0, (byte & 0x0f) as usize * 2),
+                0xde => (0, self.read_u16()? as usize * 2),
+                0xdf => (0, (self.read_u32()? as usize).saturating_mul(2)),
+                _ => return Err(unsupported_type(byte)),
+            };
+            if len > self.source.len() - self.offset {
+                return Err(MerdeError::eof());
+            }
+            self.offset += len;
+            count = count.saturating_add(items);
+        }
         Ok(())
     }
-}
 
-impl<'s> MsgpackDeserializer<'s> {
     fn read_u8(&mut self) -> Result<u8, MerdeError<'s>> {
         if self.offset + 1 > self.source.len() {
             return Err(MerdeError::eof());
@@ -221,7 +341,14 @@ impl<'s> MsgpackDeserializer<'s> {
         self.read_u64().map(f64::from_bits)
     }
 
-    fn read_str(&mut self, len: usize) -> Result<Event<'s>, MerdeError<'s>> {
+    fn read_str(
+        &mut self,
+        len: usize,
+        hint: Option<TypeHint>,
+    ) -> Result<Event<'s>, MerdeError<'s>> {
+        if hint == Some(TypeHint::Bytes) {
+            return self.read_bytes(len, None);
+        }
         if self.offset + len > self.source.len() {
             return Err(MerdeError::eof());
         }
@@ -230,22 +357,29 @@ impl<'s> MsgpackDeserializer<'s> {
         Ok(Event::Str(s.into()))
     }
 
-    fn read_str_8(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+    fn read_str_8(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
         let len = self.read_u8()? as usize;
-        self.read_str(len)
+        self.read_str(len, hint)
     }
 
-    fn read_str_16(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+    fn read_str_16(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
         let len = self.read_u16()? as usize;
-        self.read_str(len)
+        self.read_str(len, hint)
     }
 
-    fn read_str_32(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+    fn read_str_32(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
         let len = self.read_u32()? as usize;
-        self.read_str(len)
+        self.read_str(len, hint)
     }
 
-    fn read_bytes(&mut self, len: usize) -> Result<Event<'s>, MerdeError<'s>> {
+    fn read_bytes(
+        &mut self,
+        len: usize,
+        hint: Option<TypeHint>,
+    ) -> Result<Event<'s>, MerdeError<'s>> {
+        if hint == Some(TypeHint::Str) {
+            return self.read_str(len, None);
+        }
         if self.offset + len > self.source.len() {
             return Err(MerdeError::eof());
         }
@@ -254,26 +388,33 @@ impl<'s> MsgpackDeserializer<'s> {
         Ok(Event::Bytes(bytes.into()))
     }
 
-    fn read_bytes_8(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+    fn read_bytes_8(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
         let len = self.read_u8()? as usize;
-        self.read_bytes(len)
+        self.read_bytes(len, hint)
     }
 
-    fn read_bytes_16(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+    fn read_bytes_16(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
         let len = self.read_u16()? as usize;
-        self.read_bytes(len)
+        self.read_bytes(len, hint)
     }
 
-    fn read_bytes_32(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+    fn read_bytes_32(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
         let len = self.read_u32()? as usize;
-        self.read_bytes(len)
+        self.read_bytes(len, hint)
+    }
+
+    /// Length prefixes come from the input, so they can't be trusted as size
+    /// hints (which are used to preallocate): cap them to the number of items
+    /// the rest of the input could possibly hold.
+    fn size_hint(&self, len: usize, min_bytes_per_item: usize) -> usize {
+        len.min((self.source.len() - self.offset) / min_bytes_per_item)
     }
 
     fn read_array_16(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
         let len = self.read_u16()? as usize;
         self.stack.push(StackItem::Array(len));
         Ok(Event::ArrayStart(merde_core::ArrayStart {
-            size_hint: Some(len),
+            size_hint: Some(self.size_hint(len, 1)),
         }))
     }
 
@@ -281,16 +422,172 @@ impl<'s> MsgpackDeserializer<'s> {
         let len = self.read_u32()? as usize;
         self.stack.push(StackItem::Array(len));
         Ok(Event::ArrayStart(merde_core::ArrayStart {
-            size_hint: Some(len),
+            size_hint: Some(self.size_hint(len, 1)),
         }))
     }
 }
 
+fn unsupported_type(byte: u8) -> MerdeError<'static> {
+    let message = match byte {
+        0xd4..=0xd8 | 0xc7..=0xc9 => format!("unsupported extension type 0x{byte:02x}"),
+        _ => format!("unsupported type 0x{byte:02x}"),
+    };
+    MerdeError::BinaryParsingError {
+        format: "msgpack",
+        message,
+    }
+}
+
+/// Computes the size of MessagePack-encoded events, for use with
+/// [`SizeCountingSerializer`] (see [`encoded_size`]).
+///
+/// Sizes are those of the most compact encoding for each value (`fixint`, `fixstr`,
+/// `fixmap`, etc.), which is what `rmp`'s `write_sint` / `write_uint` / `write_str`
+/// family of functions produce. Floats are always counted as `float64`.
+///
+/// Container headers depend on the number of elements, so they're accounted for on
+/// `MapEnd` / `ArrayEnd`. Like [`MsgpackSerializer`], containers without a size hint
+/// are counted with a `map32` / `array32` header.
+#[derive(Debug, Default)]
+pub struct MsgpackEncodedSize {
+    // number of elements seen so far in each open container (keys and values both
+    // count, for maps), and whether its header is the most compact one
+    stack: Vec<(usize, bool)>,
+}
+
+impl MsgpackEncodedSize {
+    /// Creates a size computer for a single top-level value (or several in a row).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn uint_size(v: u64) -> usize {
+        match v {
+            0..=0x7f => 1,
+            0x80..=0xff => 2,
+            0x100..=0xffff => 3,
+            0x1_0000..=0xffff_ffff => 5,
+            _ => 9,
+        }
+    }
+
+    fn sint_size(v: i64) -> usize {
+        if v >= 0 {
+            return Self::uint_size(v as u64);
+        }
+        match v {
+            -32..=-1 => 1,
+            -128..=-33 => 2,
+            -32768..=-129 => 3,
+            -2147483648..=-32769 => 5,
+            _ => 9,
+        }
+    }
+
+    // each of these returns the size of the header that comes before the payload
+
+    fn str_header_size(len: usize) -> Result<usize, MerdeError<'static>> {
+        match len as u64 {
+            0..=31 => Ok(1),
+            32..=0xff => Ok(2),
+            0x100..=0xffff => Ok(3),
+            0x1_0000..=0xffff_ffff => Ok(5),
+            _ => Err(MerdeError::OutOfRange),
+        }
+    }
+
+    fn bin_header_size(len: usize) -> Result<usize, MerdeError<'static>> {
+        match len as u64 {
+            0..=0xff => Ok(2),
+            0x100..=0xffff => Ok(3),
+            0x1_0000..=0xffff_ffff => Ok(5),
+            _ => Err(MerdeError::OutOfRange),
+        }
+    }
+
+    fn container_header_size(
+        &mut self,
+        ev: &Event<'_>,
+        per_element: usize,
+    ) -> Result<usize, MerdeError<'static>> {
+        let (elements, compact) = self
+            .stack
+            .pop()
+            .ok_or_else(|| MerdeError::UnexpectedEvent {
+                got: EventType::from(ev),
+                expected: &[],
+                help: Some("(While computing a MessagePack encoded size)".to_string()),
+            })?;
+        match (elements / per_element) as u64 {
+            0..=0xffff_ffff if !compact => Ok(5),
+            0..=15 => Ok(1),
+            16..=0xffff => Ok(3),
+            0x1_0000..=0xffff_ffff => Ok(5),
+            _ => Err(MerdeError::OutOfRange),
+        }
+    }
+}
+
+impl EncodedSize for MsgpackEncodedSize {
+    fn event_size(&mut self, ev: &Event<'_>) -> Result<usize, MerdeError<'static>> {
+        if !matches!(ev, Event::MapEnd | Event::ArrayEnd) {
+            if let Some((elements, _)) = self.stack.last_mut() {
+                *elements += 1;
+            }
+        }
+
+        Ok(match ev {
+            Event::Null | Event::Bool(_) => 1,
+            Event::I64(v) => Self::sint_size(*v),
+            Event::U64(v) => Self::uint_size(*v),
+            // bin 8 header, then 16 bytes
+            Event::I128(_) | Event::U128(_) => 2 + 16,
+            Event::F64(_) => 9,
+            Event::Str(s) => Self::str_header_size(s.len())? + s.len(),
+            Event::Bytes(b) => Self::bin_header_size(b.len())? + b.len(),
+            Event::MapStart(MapStart { size_hint })
+            | Event::ArrayStart(merde_core::ArrayStart { size_hint }) => {
+                self.stack.push((0, serialize::is_compact(*size_hint)));
+                0
+            }
+            Event::MapEnd => self.container_header_size(ev, 2)?,
+            Event::ArrayEnd => self.container_header_size(ev, 1)?,
+        })
+    }
+}
+
+/// Returns the number of bytes `value` takes once encoded as MessagePack,
+/// without encoding it. Handy to write the length prefix of a frame.
+pub fn encoded_size(value: &dyn DynSerialize) -> Result<usize, MerdeError<'static>> {
+    let mut ser = SizeCountingSerializer::new(MsgpackEncodedSize::new());
+    ser.dyn_serialize(value)?;
+    Ok(ser.size())
+}
+
+/// Serialize as MessagePack to a `Vec<u8>`
+pub fn to_vec(value: &dyn DynSerialize) -> Result<Vec<u8>, MerdeError<'static>> {
+    merde_core::entry_span!("merde_msgpack::to_vec");
+    let mut v: Vec<u8> = vec![];
+    {
+        let mut s = MsgpackSerializer::new(&mut v);
+        s.dyn_serialize(value)?;
+    }
+    Ok(v)
+}
+
+/// Serialize as MessagePack to a `bytes::Bytes`. The buffer the serializer
+/// writes to is handed over to `Bytes` as-is, without copying.
+#[cfg(feature = "bytes")]
+pub fn to_bytes(value: &dyn DynSerialize) -> Result<bytes::Bytes, MerdeError<'static>> {
+    to_vec(value).map(bytes::Bytes::from)
+}
+
 /// Deserialize an instance of type `T` from a byte slice of MessagePack data.
 pub fn from_slice<'s, T>(slice: &'s [u8]) -> Result<T, MerdeError<'s>>
 where
     T: Deserialize<'s>,
 {
+    merde_core::entry_span!("merde_msgpack::from_slice", len = slice.len());
     let mut deser = MsgpackDeserializer::new(slice);
     deser.deserialize::<T>()
 }
@@ -301,13 +598,15 @@ pub fn from_slice_owned<T>(slice: &[u8]) -> Result<T, MerdeError<'_>>
 where
     T: DeserializeOwned,
 {
+    merde_core::entry_span!("merde_msgpack::from_slice_owned", len = slice.len());
     let mut deser = MsgpackDeserializer::new(slice);
-    T::deserialize_owned(&mut deser).run_sync_with_metastack()
+    deser.deserialize_owned::<T>()
 }
 
 #[cfg(test)]
 mod tests {
     use merde_core::Array;
+    use merde_core::CowStr;
     use merde_core::DynDeserializerExt;
     use merde_core::Value;
     use merde_loggingserializer::LoggingDeserializer;
@@ -378,9 +677,180 @@ mod tests {
 
         let map = iter.next().unwrap().as_map().unwrap();
         assert_eq!(map.len(), 2);
-        assert_eq!(map.get(&"key1".into()).unwrap().as_u64().unwrap(), 1);
-        assert!((map.get(&"key2".into()).unwrap().as_f64().unwrap() - 2.7118).abs() < 1e-4);
+        assert_eq!(map.get(&CowStr::from("key1")).unwrap().as_u64().unwrap(), 1);
+        assert!((map.get(&CowStr::from("key2")).unwrap().as_f64().unwrap() - 2.7118).abs() < 1e-4);
 
         assert!(iter.next().unwrap().as_map().unwrap().is_empty());
     }
+
+    #[test]
+    fn test_encoded_size() {
+        use merde_core::{CowBytes, Map};
+        use rmp::encode;
+
+        let value = Value::Array(Array::from(vec![
+            Value::Null,
+            Value::Bool(true),
+            Value::I64(5),
+            Value::I64(-5),
+            Value::I64(-200),
+            Value::U64(200),
+            Value::U64(70_000),
+            Value::I64(i64::MIN),
+            Value::Float(1.5.into()),
+            Value::Str(CowStr::from("short")),
+            Value::Str(CowStr::from("x".repeat(40))),
+            Value::Bytes(CowBytes::from(vec![0u8; 300])),
+            Value::Array(Array::from(vec![Value::I64(0); 20])),
+            Value::Map(Map::new().with("k", Value::Null)),
+        ]));
+
+        let mut buf = Vec::new();
+        encode::write_array_len(&mut buf, 14).unwrap();
+        encode::write_nil(&mut buf).unwrap();
+        encode::write_bool(&mut buf, true).unwrap();
+        encode::write_sint(&mut buf, 5).unwrap();
+        encode::write_sint(&mut buf, -5).unwrap();
+        encode::write_sint(&mut buf, -200).unwrap();
+        encode::write_uint(&mut buf, 200).unwrap();
+        encode::write_uint(&mut buf, 70_000).unwrap();
+        encode::write_sint(&mut buf, i64::MIN).unwrap();
+        encode::write_f64(&mut buf, 1.5).unwrap();
+        encode::write_str(&mut buf, "short").unwrap();
+        encode::write_str(&mut buf, &"x".repeat(40)).unwrap();
+        encode::write_bin(&mut buf, &[0u8; 300]).unwrap();
+        encode::write_array_len(&mut buf, 20).unwrap();
+        for _ in 0..20 {
+            encode::write_sint(&mut buf, 0).unwrap();
+        }
+        encode::write_map_len(&mut buf, 1).unwrap();
+        encode::write_str(&mut buf, "k").unwrap();
+        encode::write_nil(&mut buf).unwrap();
+
+        assert_eq!(super::encoded_size(&value).unwrap(), buf.len());
+    }
+
+    #[test]
+    fn test_str_bin_hints() {
+        use merde_core::{base64::Base64, CowBytes};
+        use rmp::encode;
+
+        // `str` payloads read as bytes aren't validated as UTF-8
+        let mut buf = vec![0xa2, 0xff, 0xfe];
+        let bytes: CowBytes = super::from_slice(&buf).unwrap();
+        assert_eq!(&bytes[..], &[0xff, 0xfe]);
+        let s: Result<String, _> = super::from_slice(&buf);
+        assert!(s.is_err());
+
+        // `bin` payloads read as strings are
+        buf.clear();
+        encode::write_bin(&mut buf, b"hello").unwrap();
+        let s: String = super::from_slice(&buf).unwrap();
+        assert_eq!(s, "hello");
+        let value: Value = super::from_slice(&buf).unwrap();
+        assert_eq!(value, Value::Bytes(CowBytes::from(&b"hello"[..])));
+        buf.clear();
+        encode::write_bin(&mut buf, &[0xff]).unwrap();
+        assert!(super::from_slice::<String>(&buf).is_err());
+
+        // also works when the event was peeked at first
+        buf.clear();
+        encode::write_str(&mut buf, "hi").unwrap();
+        let bytes: Option<CowBytes> = super::from_slice(&buf).unwrap();
+        assert_eq!(bytes.as_deref(), Some(&b"hi"[..]));
+
+        // `Base64` asks for whatever's there: it decodes strings, and takes
+        // `bin` payloads as they are
+        let encoded = super::to_vec(&Base64(CowBytes::from(&[0xffu8, 0x00][..]))).unwrap();
+        let decoded: Base64<CowBytes> = super::from_slice(&encoded).unwrap();
+        assert_eq!(&decoded[..], &[0xff, 0x00]);
+        buf.clear();
+        encode::write_bin(&mut buf, b"/w==").unwrap();
+        let decoded: Base64<Vec<u8>> = super::from_slice(&buf).unwrap();
+        assert_eq!(&decoded[..], b"/w==");
+    }
+
+    #[test]
+    fn test_wide_ints() {
+        use rmp::encode;
+
+        let values = vec![1u128 << 100, 7];
+        let buf = super::to_vec(&values).unwrap();
+        assert_eq!(super::encoded_size(&values).unwrap(), buf.len());
+        assert_eq!(super::from_slice::<Vec<u128>>(&buf).unwrap(), values);
+
+        // like rmp-serde: 16 big-endian bytes
+        let mut expected = Vec::new();
+        encode::write_bin(&mut expected, &(-(1i128 << 100)).to_be_bytes()).unwrap();
+        assert_eq!(super::to_vec(&-(1i128 << 100)).unwrap(), expected);
+        assert_eq!(
+            super::from_slice::<i128>(&expected).unwrap(),
+            -(1i128 << 100)
+        );
+    }
+
+    #[test]
+    fn test_untrusted_length_prefix() {
+        // an array32 and a map32 claiming 4 billion items, with none to follow:
+        // that's an error, not an attempt to preallocate for all of them
+        let array = [0xdd, 0xff, 0xff, 0xff, 0xff];
+        assert!(super::from_slice::<Vec<u64>>(&array).is_err());
+        let map = [0xdf, 0xff, 0xff, 0xff, 0xff, 0xa1, b'a'];
+        assert!(super::from_slice::<Value>(&map).is_err());
+    }
+
+    #[test]
+    fn test_skip_value() {
+        use merde_core::{Deserializer, Event, MerdeError, MetastackExt};
+
+        use rmp::encode;
+
+        // [{"a": [1, "xx", 2.5, {"b": -300}], "long": "..."}, "x", 3, [7]]
+        let mut buf = Vec::new();
+        encode::write_array_len(&mut buf, 4).unwrap();
+        encode::write_map_len(&mut buf, 2).unwrap();
+        encode::write_str(&mut buf, "a").unwrap();
+        encode::write_array_len(&mut buf, 4).unwrap();
+        encode::write_uint(&mut buf, 1).unwrap();
+        encode::write_str(&mut buf, "xx").unwrap();
+        encode::write_f64(&mut buf, 2.5).unwrap();
+        encode::write_map_len(&mut buf, 1).unwrap();
+        encode::write_str(&mut buf, "b").unwrap();
+        encode::write_sint(&mut buf, -300).unwrap();
+        encode::write_str(&mut buf, "long").unwrap();
+        encode::write_str(&mut buf, &"a string that needs a str8 ".repeat(2)).unwrap();
+        encode::write_str(&mut buf, "x").unwrap();
+        encode::write_uint(&mut buf, 3).unwrap();
+        encode::write_array_len(&mut buf, 1).unwrap();
+        encode::write_uint(&mut buf, 7).unwrap();
+
+        let mut deser = super::MsgpackDeserializer::new(&buf);
+        async {
+            deser.next().await?.into_array_start()?;
+            deser.skip_value().await?;
+            let ev = deser.next().await?;
+            assert_eq!(ev, Event::Str("x".into()));
+            deser.put_back(ev)?;
+            deser.skip_value().await?;
+            assert_eq!(deser.next().await?, Event::U64(3));
+            // started, then skipped
+            let ev = deser.next().await?;
+            deser.put_back(ev)?;
+            deser.skip_value().await?;
+            assert!(deser.skip_value().await.is_err());
+            Ok::<_, MerdeError>(())
+        }
+        .run_sync_with_metastack()
+        .unwrap();
+
+        // a string that's cut short
+        let buf = [0x91, 0xa5, b'a'];
+        let mut deser = super::MsgpackDeserializer::new(&buf);
+        let res = async {
+            deser.next().await?.into_array_start()?;
+            deser.skip_value().await
+        }
+        .run_sync_with_metastack();
+        assert!(res.is_err());
+    }
 }
@@ -0,0 +1,259 @@
+use std::future::Future;
+
+use merde_core::{
+    ArrayStart, Event, EventType, InfallibleSerializer, MapStart, MerdeError, Serializer,
+};
+use rmp::{
+    encode::{self, ValueWriteError},
+    Marker,
+};
+
+struct Frame {
+    // where the container's header is in the output, and how long it is
+    header_at: usize,
+    header_len: usize,
+    // whether the header uses the most compact encoding (it was written from
+    // the size hint), or is a `map32` / `array32` header
+    compact: bool,
+    // number of elements written so far (keys and values both count, for maps)
+    elements: usize,
+    is_map: bool,
+}
+
+/// Writes MessagePack to a `Vec<u8>`, picking the most compact encoding for
+/// each value (`fixint`, `fixstr`, etc.)
+///
+/// The header of maps and arrays is written on `MapStart` / `ArrayStart`, from
+/// their size hint, and patched in place on `MapEnd` / `ArrayEnd` if the hint
+/// was off. Containers without a size hint get a `map32` / `array32` header,
+/// since their length is only known once they're complete.
+///
+/// ```rust
+/// use merde_core::InfallibleSerializer;
+/// use merde_msgpack::MsgpackSerializer;
+///
+/// let mut buf = Vec::new();
+/// MsgpackSerializer::new(&mut buf).serialize_infallible(&vec![1, 2, 3]);
+/// assert_eq!(buf, [0x93, 0x01, 0x02, 0x03]);
+/// ```
+pub struct MsgpackSerializer<'w> {
+    out: &'w mut Vec<u8>,
+    stack: Vec<Frame>,
+}
+
+impl<'w> MsgpackSerializer<'w> {
+    /// Uses the provided buffer as the target for serialization.
+    pub fn new(out: &'w mut Vec<u8>) -> Self {
+        Self {
+            out,
+            stack: Vec::new(),
+        }
+    }
+
+    fn write_event(&mut self, ev: Event<'_>) -> Result<(), MerdeError<'static>> {
+        if !matches!(ev, Event::MapEnd | Event::ArrayEnd) {
+            if let Some(frame) = self.stack.last_mut() {
+                frame.elements += 1;
+            }
+        }
+
+        let out = &mut *self.out;
+        match ev {
+            Event::Null => encode::write_nil(out)?,
+            Event::Bool(b) => encode::write_bool(out, b)?,
+            Event::I64(i) => {
+                encode::write_sint(out, i).map_err(io_error)?;
+            }
+            Event::U64(u) => {
+                encode::write_uint(out, u).map_err(io_error)?;
+            }
+            // MessagePack has no 128-bit integers: like rmp-serde, write them
+            // as 16 big-endian bytes
+            Event::I128(i) => encode::write_bin(out, &i.to_be_bytes()).map_err(io_error)?,
+            Event::U128(u) => encode::write_bin(out, &u.to_be_bytes()).map_err(io_error)?,
+            Event::F64(f) => encode::write_f64(out, f).map_err(io_error)?,
+            Event::Str(s) => encode::write_str(out, &s).map_err(io_error)?,
+            Event::Bytes(b) => encode::write_bin(out, &b).map_err(io_error)?,
+            Event::MapStart(MapStart { size_hint })
+            | Event::ArrayStart(ArrayStart { size_hint }) => {
+                let is_map = matches!(ev, Event::MapStart(_));
+                let compact = is_compact(size_hint);
+                let header_at = out.len();
+                write_header(out, is_map, size_hint.unwrap_or(0), compact)?;
+                self.stack.push(Frame {
+                    header_at,
+                    header_len: out.len() - header_at,
+                    compact,
+                    elements: 0,
+                    is_map,
+                });
+            }
+            Event::MapEnd | Event::ArrayEnd => {
+                let is_map = matches!(ev, Event::MapEnd);
+                let frame = match self.stack.pop() {
+                    Some(frame) if frame.is_map == is_map => frame,
+                    _ => {
+                        return Err(MerdeError::UnexpectedEvent {
+                            got: EventType::from(&ev),
+                            expected: &[],
+                            help: Some(
+                                "(While serializing to MessagePack: unbalanced map or array)"
+                                    .to_string(),
+                            ),
+                        })
+                    }
+                };
+
+                let len = if is_map {
+                    frame.elements / 2
+                } else {
+                    frame.elements
+                };
+                let mut header = Vec::with_capacity(5);
+                write_header(&mut header, is_map, len, frame.compact)?;
+                let range = frame.header_at..frame.header_at + frame.header_len;
+                if header.len() == range.len() {
+                    out[range].copy_from_slice(&header);
+                } else {
+                    // only when the size hint was off
+                    out.splice(range, header);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a container with that size hint gets the most compact header for
+/// its length, rather than a `map32` / `array32` one.
+pub(crate) fn is_compact(size_hint: Option<usize>) -> bool {
+    size_hint.is_some_and(|len| u32::try_from(len).is_ok())
+}
+
+fn write_header(
+    out: &mut Vec<u8>,
+    is_map: bool,
+    len: usize,
+    compact: bool,
+) -> Result<(), MerdeError<'static>> {
+    let len = u32::try_from(len).map_err(|_| MerdeError::OutOfRange)?;
+    if compact {
+        if is_map {
+            encode::write_map_len(out, len).map_err(io_error)?;
+        } else {
+            encode::write_array_len(out, len).map_err(io_error)?;
+        }
+    } else {
+        let marker = if is_map {
+            Marker::Map32
+        } else {
+            Marker::Array32
+        };
+        out.push(marker.to_u8());
+        out.extend_from_slice(&len.to_be_bytes());
+    }
+    Ok(())
+}
+
+fn io_error(e: ValueWriteError) -> MerdeError<'static> {
+    MerdeError::Io(e.into())
+}
+
+impl Serializer for MsgpackSerializer<'_> {
+    #[allow(clippy::manual_async_fn)]
+    fn write<'fut>(
+        &'fut mut self,
+        ev: Event<'fut>,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        async move { self.write_event(ev) }
+    }
+}
+
+impl InfallibleSerializer for MsgpackSerializer<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use merde_core::{Array, CowStr, DynSerializerExt, EncodedSize, Map, Value};
+
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let value = Value::Map(
+            Map::new()
+                .with("null", Value::Null)
+                .with("neg", Value::I64(-300))
+                .with("big", Value::U64(u64::MAX))
+                .with("float", Value::Float(0.25.into()))
+                .with("str", Value::Str(CowStr::from("x".repeat(300))))
+                .with(
+                    "nested",
+                    Value::Array(Array::from(vec![Value::Bool(false); 17])),
+                ),
+        );
+
+        let mut buf = Vec::new();
+        MsgpackSerializer::new(&mut buf).serialize(&value).unwrap();
+        assert_eq!(buf.len(), crate::encoded_size(&value).unwrap());
+
+        let back: Value = crate::from_slice(&buf).unwrap();
+        assert_eq!(back, value);
+    }
+
+    #[test]
+    fn test_headers() {
+        fn write(events: Vec<Event<'static>>) -> Vec<u8> {
+            let mut buf = Vec::new();
+            let mut ser = MsgpackSerializer::new(&mut buf);
+            let mut sizes = crate::MsgpackEncodedSize::new();
+            let mut size = 0;
+            for ev in events {
+                size += sizes.event_size(&ev).unwrap();
+                ser.write_event(ev).unwrap();
+            }
+            assert_eq!(size, buf.len());
+            buf
+        }
+        let array = |size_hint| Event::ArrayStart(ArrayStart { size_hint });
+
+        // from the size hint
+        assert_eq!(
+            write(vec![
+                array(Some(2)),
+                Event::Null,
+                Event::Null,
+                Event::ArrayEnd
+            ]),
+            [0x92, 0xc0, 0xc0]
+        );
+        // without one, patched in place
+        assert_eq!(
+            write(vec![
+                Event::MapStart(MapStart { size_hint: None }),
+                Event::Str("k".into()),
+                array(None),
+                Event::Null,
+                Event::ArrayEnd,
+                Event::MapEnd
+            ]),
+            [0xdf, 0, 0, 0, 1, 0xa1, b'k', 0xdd, 0, 0, 0, 1, 0xc0]
+        );
+        // with a wrong one
+        let mut events = vec![array(Some(3))];
+        events.extend(std::iter::repeat(Event::Null).take(16));
+        events.push(Event::ArrayEnd);
+        let buf = write(events);
+        assert_eq!(buf[..3], [0xdc, 0, 16]);
+        assert_eq!(buf.len(), 3 + 16);
+        assert_eq!(write(vec![array(Some(1)), Event::ArrayEnd]), [0x90]);
+    }
+
+    #[test]
+    fn test_unbalanced() {
+        let mut buf = Vec::new();
+        let mut ser = MsgpackSerializer::new(&mut buf);
+        ser.write_event(Event::MapStart(MapStart { size_hint: None }))
+            .unwrap();
+        assert!(ser.write_event(Event::ArrayEnd).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+/// How many items are left in a container that's being read.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum StackItem {
+    Array(usize),
+    Map(usize),
+}
+
+/// How many levels of nesting are kept inline, before spilling to the heap.
+/// Most messages are much shallower than this.
+const INLINE: usize = 16;
+
+/// The containers being read, innermost last. Levels up to [INLINE] live in
+/// the struct itself, so small messages don't allocate.
+pub(crate) struct Stack {
+    inline: [StackItem; INLINE],
+    len: usize,
+    spilled: Vec<StackItem>,
+}
+
+impl Stack {
+    pub(crate) fn new() -> Self {
+        Self {
+            inline: [StackItem::Array(0); INLINE],
+            len: 0,
+            spilled: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, item: StackItem) {
+        if self.len < INLINE {
+            self.inline[self.len] = item;
+        } else {
+            self.spilled.push(item);
+        }
+        self.len += 1;
+    }
+
+    #[inline]
+    pub(crate) fn pop(&mut self) -> Option<StackItem> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        if self.len < INLINE {
+            Some(self.inline[self.len])
+        } else {
+            self.spilled.pop()
+        }
+    }
+
+    #[inline]
+    pub(crate) fn last_mut(&mut self) -> Option<&mut StackItem> {
+        match self.len {
+            0 => None,
+            len if len <= INLINE => Some(&mut self.inline[len - 1]),
+            _ => self.spilled.last_mut(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Stack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(&self.inline[..self.len.min(INLINE)])
+            .entries(&self.spilled)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_spills() {
+        let mut stack = Stack::new();
+        for i in 0..INLINE * 2 {
+            stack.push(StackItem::Array(i));
+        }
+        assert_eq!(stack.spilled.len(), INLINE);
+
+        if let Some(StackItem::Array(count)) = stack.last_mut() {
+            *count = 100;
+        }
+        assert!(matches!(stack.pop(), Some(StackItem::Array(100))));
+        for i in (0..INLINE * 2 - 1).rev() {
+            assert!(matches!(stack.pop(), Some(StackItem::Array(n)) if n == i));
+        }
+        assert!(stack.pop().is_none());
+        assert!(stack.last_mut().is_none());
+    }
+}
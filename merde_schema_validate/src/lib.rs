@@ -0,0 +1,772 @@
+#![deny(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+use std::fmt;
+
+use merde_core::{DynDeserializer, DynDeserializerExt, IntoStatic, Map, MerdeError, Value};
+
+/// A JSON Schema (subset), compiled and ready to validate values.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: Node,
+}
+
+/// Returned by [`Schema::compile`] when the schema itself is malformed, or uses
+/// keywords this crate doesn't support.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// JSON pointer to the offending part of the schema
+    pub path: String,
+    /// What's wrong with it
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid schema at {:?}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// A single way in which a value doesn't match a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// JSON pointer to the offending value, e.g. `/users/3/email` (empty for the root)
+    pub path: String,
+    /// What's wrong with it
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "(root): {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Returned by [`Schema::validate`]: lists every violation found, in document order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The violations, never empty
+    pub violations: Vec<Violation>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} schema violation(s)", self.violations.len())?;
+        for v in &self.violations {
+            write!(f, "\n  {v}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Returned by [`Schema::validate_deserializer`].
+#[derive(Debug)]
+pub enum DeserializeValidationError<'s> {
+    /// The input couldn't be read at all
+    Deserialize(MerdeError<'s>),
+    /// The input was read, but doesn't match the schema
+    Validation(ValidationError),
+}
+
+impl fmt::Display for DeserializeValidationError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeValidationError::Deserialize(e) => fmt::Display::fmt(e, f),
+            DeserializeValidationError::Validation(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeValidationError<'_> {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InstanceType {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl InstanceType {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "null" => Self::Null,
+            "boolean" => Self::Boolean,
+            "integer" => Self::Integer,
+            "number" => Self::Number,
+            "string" => Self::String,
+            "array" => Self::Array,
+            "object" => Self::Object,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Array => "array",
+            Self::Object => "object",
+        }
+    }
+
+    fn matches(self, v: &Value<'_>) -> bool {
+        match (self, v) {
+            (Self::Null, Value::Null) => true,
+            (Self::Boolean, Value::Bool(_)) => true,
+            (Self::Integer, Value::I64(_) | Value::U64(_)) => true,
+            (Self::Integer, Value::Float(f)) => f.fract() == 0.0,
+            (Self::Number, Value::I64(_) | Value::U64(_) | Value::Float(_)) => true,
+            (Self::String, Value::Str(_)) => true,
+            (Self::Array, Value::Array(_)) => true,
+            (Self::Object, Value::Map(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Bool(bool),
+    Keywords(Box<Keywords>),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Keywords {
+    types: Option<Vec<InstanceType>>,
+    enum_: Option<Vec<Value<'static>>>,
+    const_: Option<Value<'static>>,
+
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+    multiple_of: Option<f64>,
+
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+
+    items: Option<Node>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+
+    properties: Vec<(String, Node)>,
+    required: Vec<String>,
+    additional_properties: Option<Node>,
+    min_properties: Option<usize>,
+    max_properties: Option<usize>,
+
+    all_of: Vec<Node>,
+    any_of: Vec<Node>,
+    one_of: Vec<Node>,
+    not: Option<Node>,
+}
+
+const ANNOTATIONS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "format",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+fn push_pointer(path: &str, token: &str) -> String {
+    let mut out = String::with_capacity(path.len() + token.len() + 1);
+    out.push_str(path);
+    out.push('/');
+    for c in token.chars() {
+        match c {
+            '~' => out.push_str("~0"),
+            '/' => out.push_str("~1"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn as_number(v: &Value<'_>) -> Option<f64> {
+    match v {
+        Value::I64(i) => Some(*i as f64),
+        Value::U64(u) => Some(*u as f64),
+        Value::Float(f) => Some(f.into_inner()),
+        _ => None,
+    }
+}
+
+/// How deeply schemas, and the values compared with `enum` and `const`, may be
+/// nested: past that, compiling or validating fails instead of overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Like `==`, except numbers compare by value (`1`, `1u64` and `1.0` are equal).
+/// Returns `None` if both values are nested deeper than [MAX_DEPTH].
+fn json_eq(a: &Value<'_>, b: &Value<'_>, depth: usize) -> Option<bool> {
+    match (a, b) {
+        (Value::Array(a), Value::Array(b)) => {
+            if a.len() != b.len() {
+                return Some(false);
+            }
+            if depth == MAX_DEPTH {
+                return None;
+            }
+            for (a, b) in a.iter().zip(b.iter()) {
+                if !json_eq(a, b, depth + 1)? {
+                    return Some(false);
+                }
+            }
+            Some(true)
+        }
+        (Value::Map(a), Value::Map(b)) => {
+            if a.len() != b.len() {
+                return Some(false);
+            }
+            if depth == MAX_DEPTH {
+                return None;
+            }
+            for (k, v) in a.iter() {
+                let Some(bv) = b.get(k) else {
+                    return Some(false);
+                };
+                if !json_eq(v, bv, depth + 1)? {
+                    return Some(false);
+                }
+            }
+            Some(true)
+        }
+        _ => Some(match (as_number(a), as_number(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => a == b,
+        }),
+    }
+}
+
+struct Compiler {
+    // how many schemas the one being compiled is nested in
+    depth: usize,
+}
+
+impl Compiler {
+    fn err(path: &str, message: impl Into<String>) -> SchemaError {
+        SchemaError {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+
+    fn node(&mut self, v: &Value<'_>, path: &str) -> Result<Node, SchemaError> {
+        let map = match v {
+            Value::Bool(b) => return Ok(Node::Bool(*b)),
+            Value::Map(map) => map,
+            _ => return Err(Self::err(path, "a schema must be an object or a boolean")),
+        };
+        if self.depth == MAX_DEPTH {
+            return Err(Self::err(
+                path,
+                format!("schemas can't be nested more than {MAX_DEPTH} levels deep"),
+            ));
+        }
+
+        self.depth += 1;
+        let kw = self.keywords(map, path);
+        self.depth -= 1;
+        Ok(Node::Keywords(Box::new(kw?)))
+    }
+
+    fn keywords(&mut self, map: &Map<'_>, path: &str) -> Result<Keywords, SchemaError> {
+        let mut kw = Keywords::default();
+        for (key, value) in map.iter() {
+            let path = &push_pointer(path, key);
+            match key.as_ref() {
+                "type" => {
+                    let names: Vec<&Value<'_>> = match value {
+                        Value::Array(a) => a.iter().collect(),
+                        v => vec![v],
+                    };
+                    let mut types = Vec::with_capacity(names.len());
+                    for name in names {
+                        let ty = match name {
+                            Value::Str(s) => InstanceType::parse(s),
+                            _ => None,
+                        };
+                        types.push(ty.ok_or_else(|| Self::err(path, "unknown type"))?);
+                    }
+                    kw.types = Some(types);
+                }
+                "enum" => {
+                    let Value::Array(a) = value else {
+                        return Err(Self::err(path, "expected an array"));
+                    };
+                    kw.enum_ = Some(a.iter().map(|v| v.clone().into_static()).collect());
+                }
+                "const" => kw.const_ = Some(value.clone().into_static()),
+                "minimum" => kw.minimum = Some(self.number(value, path)?),
+                "maximum" => kw.maximum = Some(self.number(value, path)?),
+                "exclusiveMinimum" => kw.exclusive_minimum = Some(self.number(value, path)?),
+                "exclusiveMaximum" => kw.exclusive_maximum = Some(self.number(value, path)?),
+                "multipleOf" => {
+                    let n = self.number(value, path)?;
+                    if n <= 0.0 {
+                        return Err(Self::err(path, "must be strictly positive"));
+                    }
+                    kw.multiple_of = Some(n);
+                }
+                "minLength" => kw.min_length = Some(self.count(value, path)?),
+                "maxLength" => kw.max_length = Some(self.count(value, path)?),
+                "items" => kw.items = Some(self.node(value, path)?),
+                "minItems" => kw.min_items = Some(self.count(value, path)?),
+                "maxItems" => kw.max_items = Some(self.count(value, path)?),
+                "properties" => {
+                    let Value::Map(props) = value else {
+                        return Err(Self::err(path, "expected an object"));
+                    };
+                    for (name, schema) in props.iter() {
+                        kw.properties.push((
+                            name.to_string(),
+                            self.node(schema, &push_pointer(path, name))?,
+                        ));
+                    }
+                    // keeps violations in a stable order regardless of `Map` iteration order
+                    kw.properties.sort_by(|a, b| a.0.cmp(&b.0));
+                }
+                "required" => {
+                    let Value::Array(a) = value else {
+                        return Err(Self::err(path, "expected an array of strings"));
+                    };
+                    for name in a.iter() {
+                        let Value::Str(name) = name else {
+                            return Err(Self::err(path, "expected an array of strings"));
+                        };
+                        kw.required.push(name.to_string());
+                    }
+                }
+                "additionalProperties" => kw.additional_properties = Some(self.node(value, path)?),
+                "minProperties" => kw.min_properties = Some(self.count(value, path)?),
+                "maxProperties" => kw.max_properties = Some(self.count(value, path)?),
+                "allOf" => kw.all_of = self.nodes(value, path)?,
+                "anyOf" => kw.any_of = self.nodes(value, path)?,
+                "oneOf" => kw.one_of = self.nodes(value, path)?,
+                "not" => kw.not = Some(self.node(value, path)?),
+                k if ANNOTATIONS.contains(&k) => {}
+                _ => return Err(Self::err(path, "unsupported keyword")),
+            }
+        }
+        Ok(kw)
+    }
+
+    fn nodes(&mut self, v: &Value<'_>, path: &str) -> Result<Vec<Node>, SchemaError> {
+        let Value::Array(a) = v else {
+            return Err(Self::err(path, "expected an array of schemas"));
+        };
+        if a.is_empty() {
+            return Err(Self::err(path, "expected a non-empty array of schemas"));
+        }
+        a.iter()
+            .enumerate()
+            .map(|(i, v)| self.node(v, &push_pointer(path, &i.to_string())))
+            .collect()
+    }
+
+    fn number(&self, v: &Value<'_>, path: &str) -> Result<f64, SchemaError> {
+        as_number(v).ok_or_else(|| Self::err(path, "expected a number"))
+    }
+
+    fn count(&self, v: &Value<'_>, path: &str) -> Result<usize, SchemaError> {
+        match v {
+            Value::I64(i) if *i >= 0 => Ok(*i as usize),
+            Value::U64(u) => Ok(*u as usize),
+            _ => Err(Self::err(path, "expected a non-negative integer")),
+        }
+    }
+}
+
+struct Validator<'a> {
+    violations: &'a mut Vec<Violation>,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, path: &str, message: impl Into<String>) {
+        self.violations.push(Violation {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    // Whether `v` equals one of `values`, or `None` (after reporting it) if
+    // it's too deeply nested to tell.
+    fn equals_any(&mut self, values: &[Value<'_>], v: &Value<'_>, path: &str) -> Option<bool> {
+        for e in values {
+            match json_eq(e, v, 0) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => {
+                    self.report(
+                        path,
+                        format!("value is nested more than {MAX_DEPTH} levels deep"),
+                    );
+                    return None;
+                }
+            }
+        }
+        Some(false)
+    }
+
+    // Validates against `node` without reporting anything, returns whether it matched.
+    fn matches(node: &Node, v: &Value<'_>, path: &str) -> bool {
+        let mut violations = Vec::new();
+        Validator {
+            violations: &mut violations,
+        }
+        .node(node, v, path);
+        violations.is_empty()
+    }
+
+    fn node(&mut self, node: &Node, v: &Value<'_>, path: &str) {
+        let kw = match node {
+            Node::Bool(true) => return,
+            Node::Bool(false) => return self.report(path, "no value is allowed here"),
+            Node::Keywords(kw) => kw,
+        };
+
+        if let Some(types) = &kw.types {
+            if !types.iter().any(|t| t.matches(v)) {
+                let names: Vec<_> = types.iter().map(|t| t.name()).collect();
+                self.report(path, format!("expected {}", names.join(" or ")));
+                // the other keywords would only pile up confusing violations
+                return;
+            }
+        }
+
+        if let Some(values) = &kw.enum_ {
+            if self.equals_any(values, v, path) == Some(false) {
+                self.report(path, "value is not one of the allowed values");
+            }
+        }
+        if let Some(c) = &kw.const_ {
+            if self.equals_any(std::slice::from_ref(c), v, path) == Some(false) {
+                self.report(path, "value is not the expected constant");
+            }
+        }
+
+        match v {
+            Value::I64(_) | Value::U64(_) | Value::Float(_) => self.number(kw, v, path),
+            Value::Str(s) => {
+                let len = s.chars().count();
+                if let Some(min) = kw.min_length {
+                    if len < min {
+                        self.report(path, format!("string is shorter than {min} characters"));
+                    }
+                }
+                if let Some(max) = kw.max_length {
+                    if len > max {
+                        self.report(path, format!("string is longer than {max} characters"));
+                    }
+                }
+            }
+            Value::Array(a) => {
+                if let Some(min) = kw.min_items {
+                    if a.len() < min {
+                        self.report(path, format!("array has fewer than {min} items"));
+                    }
+                }
+                if let Some(max) = kw.max_items {
+                    if a.len() > max {
+                        self.report(path, format!("array has more than {max} items"));
+                    }
+                }
+                if let Some(items) = &kw.items {
+                    for (i, item) in a.iter().enumerate() {
+                        self.node(items, item, &push_pointer(path, &i.to_string()));
+                    }
+                }
+            }
+            Value::Map(m) => {
+                if let Some(min) = kw.min_properties {
+                    if m.len() < min {
+                        self.report(path, format!("object has fewer than {min} properties"));
+                    }
+                }
+                if let Some(max) = kw.max_properties {
+                    if m.len() > max {
+                        self.report(path, format!("object has more than {max} properties"));
+                    }
+                }
+                for name in &kw.required {
+                    if !m.contains_key(&merde_core::CowStr::from(name.as_str())) {
+                        self.report(path, format!("missing required property {name:?}"));
+                    }
+                }
+                for (name, schema) in &kw.properties {
+                    if let Some(pv) = m.get(&merde_core::CowStr::from(name.as_str())) {
+                        self.node(schema, pv, &push_pointer(path, name));
+                    }
+                }
+                if let Some(additional) = &kw.additional_properties {
+                    let mut extra: Vec<_> = m
+                        .iter()
+                        .filter(|(k, _)| !kw.properties.iter().any(|(name, _)| name == k.as_ref()))
+                        .collect();
+                    extra.sort_by(|a, b| str::cmp(a.0, b.0));
+                    for (k, pv) in extra {
+                        let path = push_pointer(path, k);
+                        if matches!(additional, Node::Bool(false)) {
+                            self.report(&path, "unexpected property");
+                        } else {
+                            self.node(additional, pv, &path);
+                        }
+                    }
+                }
+            }
+            Value::Null | Value::Bool(_) | Value::Bytes(_) => {}
+        }
+
+        for schema in &kw.all_of {
+            self.node(schema, v, path);
+        }
+        if !kw.any_of.is_empty() && !kw.any_of.iter().any(|s| Self::matches(s, v, path)) {
+            self.report(
+                path,
+                "value doesn't match any of the allowed schemas (anyOf)",
+            );
+        }
+        if !kw.one_of.is_empty() {
+            let n = kw
+                .one_of
+                .iter()
+                .filter(|s| Self::matches(s, v, path))
+                .count();
+            if n != 1 {
+                self.report(
+                    path,
+                    format!("value matches {n} schemas, expected exactly one (oneOf)"),
+                );
+            }
+        }
+        if let Some(not) = &kw.not {
+            if Self::matches(not, v, path) {
+                self.report(path, "value matches a disallowed schema (not)");
+            }
+        }
+    }
+
+    fn number(&mut self, kw: &Keywords, v: &Value<'_>, path: &str) {
+        let Some(n) = as_number(v) else { return };
+        if let Some(min) = kw.minimum {
+            if n < min {
+                self.report(path, format!("must be >= {min}"));
+            }
+        }
+        if let Some(max) = kw.maximum {
+            if n > max {
+                self.report(path, format!("must be <= {max}"));
+            }
+        }
+        if let Some(min) = kw.exclusive_minimum {
+            if n <= min {
+                self.report(path, format!("must be > {min}"));
+            }
+        }
+        if let Some(max) = kw.exclusive_maximum {
+            if n >= max {
+                self.report(path, format!("must be < {max}"));
+            }
+        }
+        if let Some(m) = kw.multiple_of {
+            // `0.3 / 0.1` is `2.9999999999999996`: allow for rounding errors
+            let q = n / m;
+            if (q - q.round()).abs() > q.abs().max(1.0) * 1e-9 {
+                self.report(path, format!("must be a multiple of {m}"));
+            }
+        }
+    }
+}
+
+impl Schema {
+    /// Compiles a schema, typically deserialized from JSON.
+    pub fn compile(schema: &Value<'_>) -> Result<Self, SchemaError> {
+        Ok(Self {
+            root: Compiler { depth: 0 }.node(schema, "")?,
+        })
+    }
+
+    /// Validates a value, returning every violation found.
+    pub fn validate(&self, value: &Value<'_>) -> Result<(), ValidationError> {
+        let mut violations = Vec::new();
+        Validator {
+            violations: &mut violations,
+        }
+        .node(&self.root, value, "");
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { violations })
+        }
+    }
+
+    /// Reads a [`Value`] from a deserializer (so, from any format) and validates it.
+    /// Returns the value if it's valid.
+    pub fn validate_deserializer<'s>(
+        &self,
+        de: &mut dyn DynDeserializer<'s>,
+    ) -> Result<Value<'s>, DeserializeValidationError<'s>> {
+        let value: Value<'s> = de
+            .deserialize()
+            .map_err(DeserializeValidationError::Deserialize)?;
+        self.validate(&value)
+            .map_err(DeserializeValidationError::Validation)?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(s: &str) -> Schema {
+        Schema::compile(&merde_json::from_str::<Value>(s).unwrap()).unwrap()
+    }
+
+    fn violations(schema: &Schema, payload: &str) -> Vec<String> {
+        match schema.validate(&merde_json::from_str::<Value>(payload).unwrap()) {
+            Ok(()) => vec![],
+            Err(e) => e.violations.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_types_and_paths() {
+        let s = schema(
+            r#"{
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer", "minimum": 1 },
+                    "name": { "type": "string" },
+                    "a/b": { "type": ["string", "null"] },
+                    "items": { "type": "array", "items": { "type": "number", "exclusiveMaximum": 10 } }
+                },
+                "required": ["id", "name"],
+                "additionalProperties": false
+            }"#,
+        );
+
+        assert!(violations(&s, r#"{"id": 3, "name": "x", "a/b": null}"#).is_empty());
+        assert_eq!(
+            violations(
+                &s,
+                r#"{"id": 0, "a/b": 4, "items": [1, 2.5, 10], "extra": true}"#
+            ),
+            vec![
+                r#"(root): missing required property "name""#,
+                "/a~1b: expected string or null",
+                "/id: must be >= 1",
+                "/items/2: must be < 10",
+                "/extra: unexpected property",
+            ]
+        );
+        assert_eq!(violations(&s, "[]"), vec!["(root): expected object"]);
+    }
+
+    #[test]
+    fn test_combinators() {
+        let s =
+            schema(r#"{ "oneOf": [{ "type": "integer" }, { "type": "number", "minimum": 5 }] }"#);
+        assert!(violations(&s, "1").is_empty());
+        assert!(violations(&s, "5.5").is_empty());
+        assert_eq!(
+            violations(&s, "7"),
+            vec!["(root): value matches 2 schemas, expected exactly one (oneOf)"]
+        );
+
+        let s = schema(r#"{ "enum": ["a", 1], "not": { "const": 1.0 } }"#);
+        assert!(violations(&s, r#""a""#).is_empty());
+        assert_eq!(
+            violations(&s, "1"),
+            vec!["(root): value matches a disallowed schema (not)"]
+        );
+        assert_eq!(
+            violations(&s, "true"),
+            vec!["(root): value is not one of the allowed values"]
+        );
+    }
+
+    #[test]
+    fn test_multiple_of() {
+        let s = schema(r#"{ "multipleOf": 0.1 }"#);
+        assert!(violations(&s, "0.3").is_empty());
+        assert!(violations(&s, "-4.2").is_empty());
+        assert!(violations(&s, "7").is_empty());
+        assert_eq!(
+            violations(&s, "0.35"),
+            vec!["(root): must be a multiple of 0.1"]
+        );
+
+        let s = schema(r#"{ "multipleOf": 2 }"#);
+        assert!(violations(&s, "1e20").is_empty());
+        assert_eq!(violations(&s, "7"), vec!["(root): must be a multiple of 2"]);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let nested = |open: &str, close: &str, n: usize| {
+            format!("{}{}{}", open.repeat(n), "1", close.repeat(n))
+        };
+
+        let deep = nested(r#"{"not":"#, "}", 200);
+        let v = merde_json::from_str::<Value>(&deep).unwrap();
+        assert_eq!(
+            Schema::compile(&v).unwrap_err().message,
+            "schemas can't be nested more than 128 levels deep"
+        );
+
+        let deep = nested("[", "]", 200);
+        let s = schema(&format!(r#"{{ "const": {deep} }}"#));
+        assert_eq!(
+            violations(&s, &deep),
+            vec!["(root): value is nested more than 128 levels deep"]
+        );
+        assert!(violations(&schema(r#"{ "const": [[1]] }"#), "[[1]]").is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_keyword() {
+        let v = merde_json::from_str::<Value>(
+            r#"{ "properties": { "email": { "type": "string", "pattern": ".+@.+" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Schema::compile(&v).unwrap_err(),
+            SchemaError {
+                path: "/properties/email/pattern".to_string(),
+                message: "unsupported keyword".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_deserializer() {
+        let s = schema(r#"{ "type": "array", "maxItems": 1 }"#);
+        let mut de = merde_json::JsonDeserializer::new("[1, 2]");
+        assert!(matches!(
+            s.validate_deserializer(&mut de),
+            Err(DeserializeValidationError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+#![deny(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+use std::ops::{Deref, DerefMut};
+
+use merde_core::{IntoStatic, WithLifetime};
+
+/// A wrapper that stores any value as JSON in a database column, and reads it
+/// back, via sqlx.
+///
+/// With the `postgres` feature, values are sent as `JSONB`, and can be read from
+/// both `JSON` and `JSONB` columns. With the `mysql` and `sqlite` features, values
+/// are sent as text, and can be read from `JSON` and text columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Json<T> {
+    fn from(t: T) -> Self {
+        Json(t)
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<'s, T> WithLifetime<'s> for Json<T>
+where
+    T: WithLifetime<'s>,
+{
+    type Lifetimed = Json<T::Lifetimed>;
+}
+
+impl<T> IntoStatic for Json<T>
+where
+    T: IntoStatic,
+{
+    type Output = Json<T::Output>;
+
+    fn into_static(self) -> Self::Output {
+        Json(self.0.into_static())
+    }
+}
+
+#[cfg(feature = "postgres")]
+mod postgres {
+    use sqlx_core::{
+        decode::Decode,
+        encode::{Encode, IsNull},
+        error::BoxDynError,
+        type_info::TypeInfo,
+        types::Type,
+        value::ValueRef,
+    };
+    use sqlx_postgres::{
+        types::Oid, PgArgumentBuffer, PgTypeInfo, PgValueFormat, PgValueRef, Postgres,
+    };
+
+    use merde_core::{DeserializeOwned, Serialize};
+
+    use super::*;
+
+    // jsonb's OID is the same in every database
+    const JSONB_OID: Oid = Oid(3802);
+
+    // the JSONB binary format is a version byte followed by the JSON text
+    const JSONB_VERSION: u8 = 1;
+
+    impl<T> Type<Postgres> for Json<T> {
+        fn type_info() -> PgTypeInfo {
+            PgTypeInfo::with_oid(JSONB_OID)
+        }
+
+        fn compatible(ty: &PgTypeInfo) -> bool {
+            ty.name().eq_ignore_ascii_case("jsonb") || ty.name().eq_ignore_ascii_case("json")
+        }
+    }
+
+    impl<T> Encode<'_, Postgres> for Json<T>
+    where
+        T: Serialize,
+    {
+        fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+            let json = merde_json::to_vec(&self.0)?;
+            buf.push(JSONB_VERSION);
+            buf.extend_from_slice(&json);
+            Ok(IsNull::No)
+        }
+    }
+
+    impl<T> Decode<'_, Postgres> for Json<T>
+    where
+        T: DeserializeOwned,
+    {
+        fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+            let is_binary_jsonb = value.format() == PgValueFormat::Binary
+                && value.type_info().name().eq_ignore_ascii_case("jsonb");
+            let mut buf = value.as_bytes()?;
+            if is_binary_jsonb {
+                match buf.split_first() {
+                    Some((&JSONB_VERSION, rest)) => buf = rest,
+                    _ => return Err("unsupported JSONB format version".into()),
+                }
+            }
+            Ok(Json(
+                merde_json::from_bytes_owned(buf).map_err(|e| e.into_static())?,
+            ))
+        }
+    }
+}
+
+// MySQL and SQLite both exchange JSON as text
+macro_rules! impl_via_text {
+    ($feature:literal, $mod:ident, $db:path) => {
+        #[cfg(feature = $feature)]
+        mod $mod {
+            use sqlx_core::{
+                database::Database,
+                decode::Decode,
+                encode::{Encode, IsNull},
+                error::BoxDynError,
+                type_info::TypeInfo,
+                types::Type,
+            };
+
+            use merde_core::{DeserializeOwned, Serialize};
+
+            use super::*;
+
+            impl<T> Type<$db> for Json<T> {
+                fn type_info() -> <$db as Database>::TypeInfo {
+                    <str as Type<$db>>::type_info()
+                }
+
+                fn compatible(ty: &<$db as Database>::TypeInfo) -> bool {
+                    ty.name().eq_ignore_ascii_case("json")
+                        || <&str as Type<$db>>::compatible(ty)
+                        || <&[u8] as Type<$db>>::compatible(ty)
+                }
+            }
+
+            impl<'q, T> Encode<'q, $db> for Json<T>
+            where
+                T: Serialize,
+            {
+                fn encode_by_ref(
+                    &self,
+                    buf: &mut <$db as Database>::ArgumentBuffer<'q>,
+                ) -> Result<IsNull, BoxDynError> {
+                    let json = merde_json::to_string(&self.0)?;
+                    <String as Encode<'q, $db>>::encode(json, buf)
+                }
+            }
+
+            impl<'r, T> Decode<'r, $db> for Json<T>
+            where
+                T: DeserializeOwned,
+            {
+                fn decode(value: <$db as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+                    let json = <&str as Decode<'r, $db>>::decode(value)?;
+                    Ok(Json(
+                        merde_json::from_str_owned(json).map_err(|e| e.into_static())?,
+                    ))
+                }
+            }
+        }
+    };
+}
+
+impl_via_text!("mysql", mysql, sqlx_mysql::MySql);
+impl_via_text!("sqlite", sqlite, sqlx_sqlite::Sqlite);
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "postgres")]
+    fn test_postgres_encode() {
+        use sqlx_core::encode::{Encode, IsNull};
+
+        let mut buf = sqlx_postgres::PgArgumentBuffer::default();
+        let is_null =
+            Encode::<sqlx_postgres::Postgres>::encode_by_ref(&Json(vec![1, 2]), &mut buf).unwrap();
+        assert!(matches!(is_null, IsNull::No));
+        assert_eq!(&buf[..], b"\x01[1,2]");
+    }
+
+    #[test]
+    #[cfg(feature = "sqlite")]
+    fn test_sqlite_encode() {
+        use sqlx_core::encode::{Encode, IsNull};
+        use sqlx_sqlite::{Sqlite, SqliteArgumentValue};
+
+        let mut buf = Vec::new();
+        let is_null = Encode::<Sqlite>::encode_by_ref(&Json(Some("hi")), &mut buf).unwrap();
+        assert!(matches!(is_null, IsNull::No));
+        assert!(matches!(&buf[..], [SqliteArgumentValue::Text(s)] if s == r#""hi""#));
+    }
+}
@@ -0,0 +1,505 @@
+#![deny(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+use std::collections::HashMap;
+
+use merde_core::{
+    ArrayStart, CowStr, Deserialize, DeserializeOwned, Deserializer, DynDeserializerExt,
+    DynSerialize, DynSerializerExt, Event, MapStart, MerdeError, MetastackExt, TextEvents,
+    TypeHint,
+};
+
+mod serialize;
+pub use serialize::UrlEncodedSerializer;
+
+/// A deserializer for `application/x-www-form-urlencoded` data, that implements
+/// [`merde_core::Deserializer`].
+///
+/// Forms are untyped, so by default values that look like booleans (`true`,
+/// `false`) and numbers (`42`, `-1.5`) are read as such, and everything else is
+/// read as a string. Numbers are only recognized in their canonical form, so
+/// that values like `007` or `+1` stay strings. When the target type asks for a
+/// string (see [`TypeHint::Str`]), it gets the value as written instead, so
+/// `zip=12345` deserializes into a `String` just fine. See
+/// [`UrlEncodedDeserializer::with_type_inference`] to read everything as strings.
+///
+/// Keys are read with the bracket conventions [`UrlEncodedSerializer`] writes:
+/// `tags[]=x` is an array (even with a single value, and `tags[]` on its own
+/// is an empty one), and `filter[status]=open` is a nested map. Repeated keys
+/// (`tags=x&tags=y`) are read as arrays too.
+pub struct UrlEncodedDeserializer<'s> {
+    source: &'s str,
+    infer_types: bool,
+    // the source is parsed on the first call to `next`
+    parsed: bool,
+    events: TextEvents<'s>,
+}
+
+impl std::fmt::Debug for UrlEncodedDeserializer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UrlEncodedDeserializer")
+            .field("source_len", &self.source.len())
+            .finish()
+    }
+}
+
+impl<'s> UrlEncodedDeserializer<'s> {
+    /// Construct a new deserializer, for a form body or a query string
+    /// (without the leading `?`).
+    pub fn new(source: &'s str) -> Self {
+        Self {
+            source,
+            infer_types: true,
+            parsed: false,
+            events: TextEvents::default(),
+        }
+    }
+
+    /// Sets whether values that look like booleans and numbers are read as
+    /// such (the default), or whether all values are read as strings.
+    pub fn with_type_inference(mut self, infer_types: bool) -> Self {
+        self.infer_types = infer_types;
+        self
+    }
+
+    fn parse(&self, events: &mut TextEvents<'s>) -> Result<(), MerdeError<'s>> {
+        let mut root = Fields::default();
+
+        let mut offset = 0;
+        for pair in self.source.split('&') {
+            let pair_offset = offset;
+            offset += pair.len() + 1;
+            if pair.is_empty() {
+                continue;
+            }
+
+            let (key, value) = match pair.split_once('=') {
+                Some((k, v)) => (k, Some((v, pair_offset + k.len() + 1))),
+                None => (pair, None),
+            };
+            let (base, segments, force_array) = split_key(key);
+            let mut path = Vec::with_capacity(1 + segments.len());
+            path.push(self.decode(base, pair_offset)?);
+            for (segment_offset, segment) in segments {
+                path.push(self.decode(segment, pair_offset + segment_offset)?);
+            }
+            let value = match value {
+                Some((v, value_offset)) => Some(self.decode(v, value_offset)?),
+                // `tags[]` on its own is an empty array
+                None if force_array => None,
+                None => Some(CowStr::Borrowed("")),
+            };
+
+            if !root.insert(path, value, force_array) {
+                return Err(
+                    self.error(pair_offset, "a key can't have both a value and nested keys")
+                );
+            }
+        }
+
+        // (nested maps are walked with a stack, keys can nest arbitrarily deep)
+        events.push(Event::MapStart(MapStart {
+            size_hint: Some(root.entries.len()),
+        }));
+        let mut stack = vec![root.entries.into_iter()];
+        while let Some(entries) = stack.last_mut() {
+            let Some((key, node)) = entries.next() else {
+                stack.pop();
+                events.push(Event::MapEnd);
+                continue;
+            };
+            events.push(Event::Str(key));
+            match node {
+                Node::Values {
+                    mut values,
+                    force_array: false,
+                } if values.len() == 1 => {
+                    self.push_value(events, values.pop().unwrap());
+                }
+                Node::Values { values, .. } => {
+                    events.push(Event::ArrayStart(ArrayStart {
+                        size_hint: Some(values.len()),
+                    }));
+                    for value in values {
+                        self.push_value(events, value);
+                    }
+                    events.push(Event::ArrayEnd);
+                }
+                Node::Map(fields) => {
+                    events.push(Event::MapStart(MapStart {
+                        size_hint: Some(fields.entries.len()),
+                    }));
+                    stack.push(fields.entries.into_iter());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Percent-decodes `s` (found at `offset` in the source), borrowing from
+    /// the source when there's nothing to decode.
+    fn decode(&self, s: &'s str, offset: usize) -> Result<CowStr<'s>, MerdeError<'s>> {
+        if !s.contains(['%', '+']) {
+            return Ok(CowStr::Borrowed(s));
+        }
+
+        let bytes = s.as_bytes();
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'+' => {
+                    out.push(b' ');
+                    i += 1;
+                }
+                b'%' => {
+                    // (`from_str_radix` alone would take a sign, like in `%+f`)
+                    let hex = bytes
+                        .get(i + 1..i + 3)
+                        .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                        .and_then(|hex| std::str::from_utf8(hex).ok())
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                    match hex {
+                        Some(b) => out.push(b),
+                        None => {
+                            return Err(self.error(
+                                offset + i,
+                                "`%` must be followed by two hexadecimal digits",
+                            ))
+                        }
+                    }
+                    i += 3;
+                }
+                b => {
+                    out.push(b);
+                    i += 1;
+                }
+            }
+        }
+
+        match String::from_utf8(out) {
+            Ok(s) => Ok(CowStr::from(s)),
+            Err(_) => Err(self.error(offset, "percent-encoded bytes are not valid UTF-8")),
+        }
+    }
+
+    fn push_value(&self, events: &mut TextEvents<'s>, s: CowStr<'s>) {
+        if self.infer_types {
+            events.push_scalar(s);
+        } else {
+            events.push(Event::Str(s));
+        }
+    }
+
+    fn next_hinted(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
+        if !self.parsed {
+            self.parsed = true;
+            // (after whatever was put back)
+            let mut events = std::mem::take(&mut self.events);
+            self.parse(&mut events)?;
+            self.events = events;
+        }
+        self.events.next(hint).ok_or_else(MerdeError::eof)
+    }
+
+    fn error(&self, index: usize, message: &str) -> MerdeError<'s> {
+        MerdeError::StringParsingError {
+            format: "urlencoded",
+            source: self.source.into(),
+            index,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl<'s> Deserializer<'s> for UrlEncodedDeserializer<'s> {
+    async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
+        self.next_hinted(None)
+    }
+
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        self.next_hinted(Some(hint))
+    }
+
+    fn put_back(&mut self, ev: Event<'s>) -> Result<(), MerdeError<'s>> {
+        self.events.put_back(ev);
+        Ok(())
+    }
+}
+
+/// The keys of a form (or of one of its nested maps), in order of appearance.
+#[derive(Default)]
+struct Fields<'s> {
+    entries: Vec<(CowStr<'s>, Node<'s>)>,
+    index_of: HashMap<CowStr<'s>, usize>,
+}
+
+enum Node<'s> {
+    Values {
+        values: Vec<CowStr<'s>>,
+        force_array: bool,
+    },
+    Map(Fields<'s>),
+}
+
+impl<'s> Fields<'s> {
+    /// Adds `value` under `path` (a key, followed by the keys of nested maps),
+    /// or returns false if that needs a key to be both a value and a map.
+    fn insert(
+        &mut self,
+        mut path: Vec<CowStr<'s>>,
+        value: Option<CowStr<'s>>,
+        force_array: bool,
+    ) -> bool {
+        let Some(last) = path.pop() else {
+            return false;
+        };
+
+        let mut fields = self;
+        for key in path {
+            let index = fields.entry(key, || Node::Map(Fields::default()));
+            match &mut fields.entries[index].1 {
+                Node::Map(inner) => fields = inner,
+                Node::Values { .. } => return false,
+            }
+        }
+
+        let index = fields.entry(last, || Node::Values {
+            values: Vec::new(),
+            force_array: false,
+        });
+        match &mut fields.entries[index].1 {
+            Node::Values {
+                values,
+                force_array: forced,
+            } => {
+                values.extend(value);
+                *forced |= force_array;
+                true
+            }
+            Node::Map(_) => false,
+        }
+    }
+
+    /// Returns the index of `key` in `entries`, adding it (with the node
+    /// returned by `new`) if it isn't there yet.
+    fn entry(&mut self, key: CowStr<'s>, new: impl FnOnce() -> Node<'s>) -> usize {
+        if let Some(&index) = self.index_of.get(&key) {
+            return index;
+        }
+        self.index_of.insert(key.clone(), self.entries.len());
+        self.entries.push((key, new()));
+        self.entries.len() - 1
+    }
+}
+
+/// Splits a raw key like `filter[owner][name]` or `tags[]` into its base
+/// (`filter`), the keys of nested maps along with their offset in `key`
+/// (`owner`, `name`), and whether it ends with `[]`. Keys that don't follow
+/// that form are taken as they are.
+fn split_key(key: &str) -> (&str, Vec<(usize, &str)>, bool) {
+    let literal = (key, Vec::new(), false);
+    let Some(open) = key.find('[') else {
+        return literal;
+    };
+    if open == 0 {
+        return literal;
+    }
+
+    let mut segments = Vec::new();
+    let mut rest = &key[open..];
+    let mut offset = open;
+    while !rest.is_empty() {
+        let Some(close) = rest.find(']') else {
+            return literal;
+        };
+        if !rest.starts_with('[') || rest[1..close].contains('[') {
+            return literal;
+        }
+        if close == 1 {
+            // `[]` only makes sense at the end
+            return if rest.len() == 2 {
+                (&key[..open], segments, true)
+            } else {
+                literal
+            };
+        }
+        segments.push((offset + 1, &rest[1..close]));
+        rest = &rest[close + 1..];
+        offset += close + 1;
+    }
+    (&key[..open], segments, false)
+}
+
+/// Deserialize an instance of type `T` from a form body or a query string.
+pub fn from_str<'s, T>(s: &'s str) -> Result<T, MerdeError<'s>>
+where
+    T: Deserialize<'s>,
+{
+    let mut deser = UrlEncodedDeserializer::new(s);
+    deser.deserialize::<T>()
+}
+
+/// Deserialize an instance of type `T` from a form body or a query string,
+/// and return its static variant e.g. (CowStr<'static>, etc.)
+pub fn from_str_owned<T>(s: &str) -> Result<T, MerdeError<'_>>
+where
+    T: DeserializeOwned,
+{
+    let mut deser = UrlEncodedDeserializer::new(s);
+    T::deserialize_owned(&mut deser).run_sync_with_metastack()
+}
+
+/// Deserialize an instance of type `T` from a form body or a query string,
+/// given as bytes (e.g. an HTTP request body).
+pub fn from_bytes<'s, T>(b: &'s [u8]) -> Result<T, MerdeError<'s>>
+where
+    T: Deserialize<'s>,
+{
+    let s = std::str::from_utf8(b)?;
+    from_str(s)
+}
+
+/// Serialize as a form body (`key=value&...`)
+pub fn to_string(value: &dyn DynSerialize) -> Result<String, MerdeError<'static>> {
+    let mut s = String::new();
+    UrlEncodedSerializer::new(&mut s).dyn_serialize(value)?;
+    Ok(s)
+}
+
+/// Serialize as a query string, ready to be appended to a URL: `?key=value&...`,
+/// or an empty string if there's nothing to write.
+///
+/// With `nested_keys`, nested maps are written with brackets (`filter[status]=open`),
+/// see [`UrlEncodedSerializer::with_nested_keys`].
+pub fn to_query_string(
+    value: &dyn DynSerialize,
+    nested_keys: bool,
+) -> Result<String, MerdeError<'static>> {
+    let mut s = String::new();
+    UrlEncodedSerializer::new(&mut s)
+        .with_nested_keys(nested_keys)
+        .dyn_serialize(value)?;
+    if !s.is_empty() {
+        s.insert(0, '?');
+    }
+    Ok(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use merde_core::{Array, Map, Value};
+
+    use super::*;
+
+    #[test]
+    fn test_flat_map() {
+        let value: Value = from_str("a=1&b=hello&tags=x&tags=y&on=true&e=&flag").unwrap();
+        assert_eq!(
+            value,
+            Value::Map(
+                Map::new()
+                    .with("a", Value::I64(1))
+                    .with("b", Value::from("hello"))
+                    .with(
+                        "tags",
+                        Array::new().with(Value::from("x")).with(Value::from("y"))
+                    )
+                    .with("on", Value::Bool(true))
+                    .with("e", Value::from(""))
+                    .with("flag", Value::from(""))
+            )
+        );
+    }
+
+    #[test]
+    fn test_decoding() {
+        let value: Value = UrlEncodedDeserializer::new("q=caf%C3%A9+au+lait&a%26b=1%3D1&ids[]=3")
+            .with_type_inference(false)
+            .deserialize()
+            .unwrap();
+        assert_eq!(
+            value,
+            Value::Map(
+                Map::new()
+                    .with("q", Value::from("café au lait"))
+                    .with("a&b", Value::from("1=1"))
+                    .with("ids", Array::new().with(Value::from("3")))
+            )
+        );
+    }
+
+    #[test]
+    fn test_inference() {
+        let map: HashMap<String, Value> =
+            from_str("a=42&b=-1.5&c=007&d=+1&e=1e3&f=18446744073709551615&g=TRUE").unwrap();
+        assert_eq!(map["a"], Value::I64(42));
+        assert_eq!(map["b"], Value::from(-1.5));
+        assert_eq!(map["c"], Value::from("007"));
+        assert_eq!(map["d"], Value::from(" 1"));
+        assert_eq!(map["e"], Value::from("1e3"));
+        assert_eq!(map["f"], Value::U64(u64::MAX));
+        assert_eq!(map["g"], Value::from("TRUE"));
+    }
+
+    #[test]
+    fn test_string_hint() {
+        let map: HashMap<String, String> = from_str("name=42&zip=007&on=true&x=1.50").unwrap();
+        assert_eq!(map["name"], "42");
+        assert_eq!(map["zip"], "007");
+        assert_eq!(map["on"], "true");
+        assert_eq!(map["x"], "1.50");
+
+        // values that are put back can still be asked for as strings
+        let tags: HashMap<String, Option<Vec<String>>> = from_str("tags=1&tags=2").unwrap();
+        assert_eq!(tags["tags"], Some(vec!["1".to_string(), "2".to_string()]));
+    }
+
+    #[test]
+    fn test_nested_keys() {
+        let value: Value =
+            from_str("f[status]=open&f[owner][name]=a+b&f[ids][]&t[]=1&a[b=1&[c]=1&d[][e]=1")
+                .unwrap();
+        assert_eq!(
+            value,
+            Value::Map(
+                Map::new()
+                    .with(
+                        "f",
+                        Map::new()
+                            .with("status", Value::from("open"))
+                            .with("owner", Map::new().with("name", Value::from("a b")))
+                            .with("ids", Array::new())
+                    )
+                    .with("t", Array::new().with(Value::I64(1)))
+                    .with("a[b", Value::I64(1))
+                    .with("[c]", Value::I64(1))
+                    .with("d[][e]", Value::I64(1))
+            )
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        for (input, index) in [
+            ("a=%zz", 2),
+            ("a=1&b%2", 5),
+            ("a=%ff", 2),
+            ("a=%+f", 2),
+            ("a=x%-1", 3),
+            ("f[%zz]=1", 2),
+            ("f=1&f[a]=2", 4),
+            ("f[a]=1&f=2", 7),
+            ("f[a]=1&f[a][b]=2", 7),
+        ] {
+            match from_str::<Value>(input) {
+                Err(MerdeError::StringParsingError { index: i, .. }) => {
+                    assert_eq!(i, index, "{input}")
+                }
+                other => panic!("{input}: expected a parsing error, got {other:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use std::future::Future;
+
+use merde_core::{Event, EventType, MerdeError, Serializer};
+
+enum Frame {
+    Map {
+        // the (encoded) key of the map itself, for nested maps
+        prefix: Option<String>,
+        // the (encoded) key whose value comes next
+        key: Option<String>,
+    },
+    Array {
+        // the (encoded) key of the array, with its `[]`
+        key: String,
+        empty: bool,
+    },
+}
+
+/// Writes `application/x-www-form-urlencoded` data (a form body, or a query
+/// string without the leading `?`) to a `String`.
+///
+/// Only maps can be written at the top level. Arrays are written as repeated
+/// keys with brackets (`tags[]=x&tags[]=y`, or `tags[]` alone for an empty
+/// array), which is what [`UrlEncodedDeserializer`](crate::UrlEncodedDeserializer)
+/// reads back as arrays, and null values (like `None` options) are left out.
+///
+/// Nested maps have no standard representation, and are rejected unless
+/// [`UrlEncodedSerializer::with_nested_keys`] is enabled.
+///
+/// ```rust
+/// use merde_core::{DynSerializerExt, Map, Value};
+/// use merde_urlencoded::UrlEncodedSerializer;
+///
+/// let mut out = String::new();
+/// let value = Value::Map(Map::new().with("q", Value::from("café & co")));
+/// UrlEncodedSerializer::new(&mut out).serialize(&value).unwrap();
+/// assert_eq!(out, "q=caf%C3%A9+%26+co");
+/// ```
+pub struct UrlEncodedSerializer<'w> {
+    out: &'w mut String,
+    stack: Vec<Frame>,
+    nested_keys: bool,
+}
+
+impl<'w> UrlEncodedSerializer<'w> {
+    /// Uses the provided string as the target for serialization. Pairs are
+    /// appended to it, separated by `&`.
+    pub fn new(out: &'w mut String) -> Self {
+        Self {
+            out,
+            stack: Vec::new(),
+            nested_keys: false,
+        }
+    }
+
+    /// Write nested maps with brackets (`filter[status]=open`), the convention
+    /// used by Rails, PHP, `qs` and others. Disabled by default.
+    pub fn with_nested_keys(mut self, nested_keys: bool) -> Self {
+        self.nested_keys = nested_keys;
+        self
+    }
+
+    fn write_event(&mut self, ev: Event<'_>) -> Result<(), MerdeError<'static>> {
+        // figure out the key the event is the value of (if it is a value)
+        let key = match self.stack.last_mut() {
+            None => {
+                return match ev {
+                    Event::MapStart(_) => {
+                        self.stack.push(Frame::Map {
+                            prefix: None,
+                            key: None,
+                        });
+                        Ok(())
+                    }
+                    ev => Err(unexpected(
+                        &ev,
+                        &[EventType::MapStart],
+                        "only maps can be written at the top level",
+                    )),
+                };
+            }
+            Some(Frame::Map { prefix, key }) => match key.take() {
+                Some(key) => key,
+                None => {
+                    return match ev {
+                        Event::Str(k) => {
+                            let mut full = String::new();
+                            match prefix {
+                                Some(prefix) => {
+                                    full.push_str(prefix);
+                                    full.push('[');
+                                    encode_into(&mut full, k.as_bytes());
+                                    full.push(']');
+                                }
+                                None => encode_into(&mut full, k.as_bytes()),
+                            }
+                            *key = Some(full);
+                            Ok(())
+                        }
+                        Event::MapEnd => {
+                            self.stack.pop();
+                            Ok(())
+                        }
+                        ev => Err(unexpected(
+                            &ev,
+                            &[EventType::Str, EventType::MapEnd],
+                            "keys must be strings",
+                        )),
+                    };
+                }
+            },
+            Some(Frame::Array { key, empty }) => {
+                if matches!(ev, Event::ArrayEnd) {
+                    if *empty {
+                        let key = std::mem::take(key);
+                        self.write_separator();
+                        self.out.push_str(&key);
+                    }
+                    self.stack.pop();
+                    return Ok(());
+                }
+                if !matches!(ev, Event::Null) {
+                    *empty = false;
+                }
+                key.clone()
+            }
+        };
+
+        let in_array = matches!(self.stack.last(), Some(Frame::Array { .. }));
+        match ev {
+            Event::Null => {}
+            Event::Bool(b) => self.write_pair(&key, if b { "true" } else { "false" }),
+            Event::I64(i) => self.write_pair(&key, &i.to_string()),
+            Event::U64(u) => self.write_pair(&key, &u.to_string()),
+            Event::I128(i) => self.write_pair(&key, &i.to_string()),
+            Event::U128(u) => self.write_pair(&key, &u.to_string()),
+            Event::F64(f) => self.write_pair(&key, &f.to_string()),
+            Event::Str(s) => {
+                let mut value = String::with_capacity(s.len());
+                encode_into(&mut value, s.as_bytes());
+                self.write_pair(&key, &value);
+            }
+            Event::Bytes(b) => {
+                let mut value = String::with_capacity(b.len());
+                encode_into(&mut value, &b);
+                self.write_pair(&key, &value);
+            }
+            Event::ArrayStart(_) if !in_array => {
+                self.stack.push(Frame::Array {
+                    key: key + "[]",
+                    empty: true,
+                });
+            }
+            Event::MapStart(_) if self.nested_keys && !in_array => {
+                self.stack.push(Frame::Map {
+                    prefix: Some(key),
+                    key: None,
+                });
+            }
+            ev => {
+                return Err(unexpected(
+                    &ev,
+                    &[],
+                    if self.nested_keys {
+                        "values can be scalars, maps, or arrays of scalars"
+                    } else {
+                        "values can be scalars or arrays of scalars (see `UrlEncodedSerializer::with_nested_keys`)"
+                    },
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn write_pair(&mut self, key: &str, value: &str) {
+        self.write_separator();
+        self.out.push_str(key);
+        self.out.push('=');
+        self.out.push_str(value);
+    }
+
+    fn write_separator(&mut self) {
+        if !self.out.is_empty() {
+            self.out.push('&');
+        }
+    }
+}
+
+fn unexpected(ev: &Event<'_>, expected: &'static [EventType], why: &str) -> MerdeError<'static> {
+    MerdeError::UnexpectedEvent {
+        got: EventType::from(ev),
+        expected,
+        help: Some(format!("(While serializing to urlencoded: {why})")),
+    }
+}
+
+/// Percent-encodes `bytes` the way HTML forms do: alphanumerics and `*-._` are
+/// kept, spaces become `+`, and everything else is `%XX`-encoded.
+fn encode_into(out: &mut String, bytes: &[u8]) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    for &b in bytes {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                out.push(b as char)
+            }
+            b' ' => out.push('+'),
+            b => {
+                out.push('%');
+                out.push(HEX[(b >> 4) as usize] as char);
+                out.push(HEX[(b & 0xf) as usize] as char);
+            }
+        }
+    }
+}
+
+impl Serializer for UrlEncodedSerializer<'_> {
+    #[allow(clippy::manual_async_fn)]
+    fn write<'fut>(
+        &'fut mut self,
+        ev: Event<'fut>,
+    ) -> impl Future<Output = Result<(), MerdeError<'static>>> + 'fut {
+        async move { self.write_event(ev) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use merde_core::{Array, DynSerializerExt, Map, Value};
+
+    use super::*;
+
+    fn serialize(value: &Value, nested_keys: bool) -> Result<String, MerdeError<'static>> {
+        let mut out = String::new();
+        UrlEncodedSerializer::new(&mut out)
+            .with_nested_keys(nested_keys)
+            .serialize(value)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_flat() {
+        let mut map = form_map();
+        map.insert("none".into(), Value::Null);
+        let out = serialize(&Value::Map(map), false).unwrap();
+        let mut pairs: Vec<&str> = out.split('&').collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            [
+                "a%26b=1%3D1",
+                "n=-3",
+                "ok=true",
+                "q=caf%C3%A9+au+lait",
+                "tags[]=x",
+                "tags[]=y+z"
+            ]
+        );
+
+        // reads back as the same thing
+        let back: Value = crate::from_str(&out).unwrap();
+        assert_eq!(back, Value::Map(form_map()));
+    }
+
+    fn form_map() -> Map<'static> {
+        Map::new()
+            .with("q", Value::from("café au lait"))
+            .with("a&b", Value::from("1=1"))
+            .with("n", Value::I64(-3))
+            .with("ok", Value::Bool(true))
+            .with(
+                "tags",
+                Array::new().with(Value::from("x")).with(Value::from("y z")),
+            )
+    }
+
+    #[test]
+    fn test_nested() {
+        let value = Value::Map(
+            Map::new().with(
+                "filter",
+                Map::new()
+                    .with("status", Value::from("open"))
+                    .with("owner", Map::new().with("name", Value::from("a b"))),
+            ),
+        );
+        assert!(serialize(&value, false).is_err());
+
+        let out = serialize(&value, true).unwrap();
+        let mut pairs: Vec<&str> = out.split('&').collect();
+        pairs.sort();
+        assert_eq!(pairs, ["filter[owner][name]=a+b", "filter[status]=open"]);
+
+        // reads back as the same thing
+        let back: Value = crate::from_str(&out).unwrap();
+        assert_eq!(back, value);
+    }
+
+    #[test]
+    fn test_array_round_trip() {
+        for (tags, expected) in [
+            (Array::new(), "tags[]"),
+            (Array::new().with(Value::from("x")), "tags[]=x"),
+            (
+                Array::new().with(Value::from("x")).with(Value::from("y")),
+                "tags[]=x&tags[]=y",
+            ),
+        ] {
+            let value = Value::Map(Map::new().with("tags", tags));
+            let out = serialize(&value, false).unwrap();
+            assert_eq!(out, expected);
+            let back: Value = crate::from_str(&out).unwrap();
+            assert_eq!(back, value, "{out}");
+        }
+
+        // in nested maps too
+        let value = Value::Map(
+            Map::new().with(
+                "filter",
+                Map::new()
+                    .with("ids", Array::new())
+                    .with("tags", Array::new().with(Value::from("x"))),
+            ),
+        );
+        let out = serialize(&value, true).unwrap();
+        let back: Value = crate::from_str(&out).unwrap();
+        assert_eq!(back, value, "{out}");
+    }
+
+    #[test]
+    fn test_rejected() {
+        for value in [
+            Value::I64(1),
+            Value::Map(Map::new().with(
+                "matrix",
+                Array::new().with(Array::new().with(Value::I64(1))),
+            )),
+        ] {
+            assert!(serialize(&value, true).is_err(), "{value:?}");
+        }
+    }
+}
@@ -1,19 +1,39 @@
 #![deny(missing_docs)]
 #![doc = include_str!("../README.md")]
 
-use std::str::Chars;
+use std::{collections::VecDeque, str::Chars};
 
 use merde_core::{
-    ArrayStart, Deserialize, DeserializeOwned, Deserializer, DynDeserializerExt, Event, MapStart,
-    MerdeError,
+    ArrayStart, CowStr, Deserialize, DeserializeOwned, Deserializer, DuplicateKeyFilter,
+    DuplicateKeyPolicy, DynDeserializerExt, Event, MapStart, MerdeError, TypeHint,
 };
 use yaml_rust2::{parser::Parser, scanner::TScalarStyle};
 
 /// A YAML deserializer, that implements [`merde_core::Deserializer`].
+///
+/// Plain (unquoted) scalars are typed by their looks: `true` is a boolean,
+/// `1.10` a float, `012345` an integer, etc. When the target type asks for a
+/// string (see [`TypeHint::Str`]), it gets the scalar's text as written
+/// instead, so `version: 1.10` deserializes as `"1.10"` into a `String`.
+///
+/// Like with merde_json, strings borrow from the source when they can: that's
+/// the case for scalars whose text is there as-is, without escapes or line
+/// folding. Note that this doesn't save any allocations: the YAML parser
+/// (yaml-rust2) still hands over every scalar as an owned `String`, it's only
+/// dropped in favor of a slice of the source. What it buys is values that
+/// borrow from the input, like they would with merde_json.
 pub struct YamlDeserializer<'s> {
     source: &'s str,
     parser: Parser<Chars<'s>>,
-    starter: Option<Event<'s>>,
+    // events that were put back, along with their text if they're plain scalars
+    starters: VecDeque<(Event<'s>, Option<CowStr<'s>>)>,
+    // the last event returned, if it's a plain scalar that wasn't typed as a
+    // string: it may be put back, then asked for again as a string
+    last_plain: Option<(Event<'s>, CowStr<'s>)>,
+    // where the last scalar was, in chars (that's how the parser counts)
+    // and in bytes, to find the next one without starting over
+    cursor: (usize, usize),
+    keys: DuplicateKeyFilter<'s>,
 }
 
 impl std::fmt::Debug for YamlDeserializer<'_> {
@@ -30,19 +50,81 @@ impl<'s> YamlDeserializer<'s> {
         Self {
             source,
             parser: Parser::new_from_str(source),
-            starter: None,
+            starters: VecDeque::new(),
+            last_plain: None,
+            cursor: (0, 0),
+            keys: Default::default(),
         }
     }
+
+    /// Sets what to do when a mapping has the same key more than once.
+    /// Defaults to [`DuplicateKeyPolicy::LastWins`].
+    pub fn with_duplicate_key_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.keys = DuplicateKeyFilter::new(policy);
+        self
+    }
 }
 
 impl<'s> Deserializer<'s> for YamlDeserializer<'s> {
     async fn next(&mut self) -> Result<Event<'s>, MerdeError<'s>> {
-        loop {
-            if let Some(starter) = self.starter.take() {
-                return Ok(starter);
+        self.next_hinted(None)
+    }
+
+    async fn next_event_with_hint(&mut self, hint: TypeHint) -> Result<Event<'s>, MerdeError<'s>> {
+        self.next_hinted(Some(hint))
+    }
+
+    fn put_back(&mut self, event: Event<'s>) -> Result<(), MerdeError<'s>> {
+        let text = match self.last_plain.take() {
+            Some((last, text)) if same_event(&last, &event) => Some(text),
+            _ => None,
+        };
+        self.starters.push_front((event, text));
+        Ok(())
+    }
+}
+
+// `Event`'s `PartialEq` says NaNs differ, but `nan` is a plain scalar too
+fn same_event(a: &Event<'_>, b: &Event<'_>) -> bool {
+    match (a, b) {
+        (Event::F64(a), Event::F64(b)) => a.to_bits() == b.to_bits(),
+        _ => a == b,
+    }
+}
+
+impl<'s> YamlDeserializer<'s> {
+    fn next_hinted(&mut self, hint: Option<TypeHint>) -> Result<Event<'s>, MerdeError<'s>> {
+        let (ev, text) = match self.starters.pop_front() {
+            Some(starter) => starter,
+            None => loop {
+                let (ev, text) = self.next_event()?;
+                if let Some(ev) = self.keys.filter(ev)? {
+                    break (ev, text);
+                }
+            },
+        };
+
+        let wants_text = match hint {
+            Some(TypeHint::Str) => true,
+            Some(TypeHint::RawNumber) => matches!(ev, Event::I64(_) | Event::F64(_)),
+            _ => false,
+        };
+        self.last_plain = None;
+        match text {
+            Some(text) if wants_text => Ok(Event::Str(text)),
+            Some(text) => {
+                self.last_plain = Some((ev.clone(), text));
+                Ok(ev)
             }
+            None => Ok(ev),
+        }
+    }
 
-            let (ev, _marker) = match self.parser.next_token() {
+    /// Returns the next event, along with the scalar's text if it's a plain
+    /// scalar that was typed as something other than a string.
+    fn next_event(&mut self) -> Result<(Event<'s>, Option<CowStr<'s>>), MerdeError<'s>> {
+        loop {
+            let (ev, marker) = match self.parser.next_token() {
                 Ok(ev) => ev,
                 Err(e) => {
                     return Err(MerdeError::StringParsingError {
@@ -69,8 +151,9 @@ impl<'s> Deserializer<'s> for YamlDeserializer<'s> {
                     todo!("aliases?")
                 }
                 YEvent::Scalar(s, style, _anchor_id, tag) => {
+                    let s = self.scalar_text(s, style, marker.index());
                     if style != TScalarStyle::Plain {
-                        Ok(Event::Str(s.into()))
+                        Ok(Event::Str(s))
                     } else if let Some(tag) = tag {
                         if tag.handle == "tag:yaml.org,2002:" {
                             // TODO: use faster int/float parsers
@@ -111,24 +194,25 @@ impl<'s> Deserializer<'s> for YamlDeserializer<'s> {
                                         message: "failed to parse null".to_string(),
                                     }),
                                 },
-                                _ => Ok(Event::Str(s.into())),
+                                _ => Ok(Event::Str(s)),
                             }
                         } else {
-                            Ok(Event::Str(s.into()))
+                            Ok(Event::Str(s))
                         }
                     } else {
                         // Datatype is not specified, try to infer
-                        if let Ok(v) = s.parse::<bool>() {
-                            Ok(Event::Bool(v))
+                        let ev = if let Ok(v) = s.parse::<bool>() {
+                            Event::Bool(v)
                         } else if let Ok(v) = s.parse::<i64>() {
-                            Ok(Event::I64(v))
+                            Event::I64(v)
                         } else if let Ok(v) = s.parse::<f64>() {
-                            Ok(Event::F64(v))
+                            Event::F64(v)
                         } else if s == "~" || s == "null" {
-                            Ok(Event::Null)
+                            Event::Null
                         } else {
-                            Ok(Event::Str(s.into()))
-                        }
+                            return Ok((Event::Str(s), None));
+                        };
+                        return Ok((ev, Some(s)));
                     }
                 }
                 YEvent::SequenceStart(_, _tag) => {
@@ -138,16 +222,45 @@ impl<'s> Deserializer<'s> for YamlDeserializer<'s> {
                 YEvent::MappingStart(_, _tag) => Ok(Event::MapStart(MapStart { size_hint: None })),
                 YEvent::MappingEnd => Ok(Event::MapEnd),
             };
-            return res;
+            return res.map(|ev| (ev, None));
         }
     }
 
-    fn put_back(&mut self, event: Event<'s>) -> Result<(), MerdeError<'s>> {
-        if self.starter.is_some() {
-            return Err(MerdeError::PutBackCalledTwice);
+    /// Borrows a scalar's text from the source if it's there as-is, which
+    /// is the case for most plain and quoted scalars (but not for those with
+    /// escapes or that span several lines, for example).
+    ///
+    /// `text` has already been allocated by the parser by then: avoiding that
+    /// would take a parser that yields spans of the source instead.
+    fn scalar_text(&mut self, text: String, style: TScalarStyle, index: usize) -> CowStr<'s> {
+        let quote_len = match style {
+            TScalarStyle::Plain => 0,
+            TScalarStyle::SingleQuoted | TScalarStyle::DoubleQuoted => 1,
+            _ => return text.into(),
+        };
+        let Some(start) = self.byte_offset(index) else {
+            return text.into();
+        };
+        let start = start + quote_len;
+        match self.source.get(start..start + text.len()) {
+            Some(slice) if slice == text => CowStr::Borrowed(slice),
+            _ => text.into(),
         }
-        self.starter = Some(event);
-        Ok(())
+    }
+
+    // Converts an index in chars into an offset in bytes
+    fn byte_offset(&mut self, index: usize) -> Option<usize> {
+        if index < self.cursor.0 {
+            self.cursor = (0, 0);
+        }
+        let (chars, bytes) = self.cursor;
+        let mut offset = bytes;
+        let mut rest = self.source[bytes..].chars();
+        for _ in chars..index {
+            offset += rest.next()?.len_utf8();
+        }
+        self.cursor = (index, offset);
+        Some(offset)
     }
 }
 
@@ -156,6 +269,7 @@ pub fn from_str<'s, T>(s: &'s str) -> Result<T, MerdeError<'s>>
 where
     T: Deserialize<'s>,
 {
+    merde_core::entry_span!("merde_yaml::from_str", len = s.len());
     let mut deser = YamlDeserializer::new(s);
     deser.deserialize::<T>()
 }
@@ -166,7 +280,99 @@ pub fn from_str_owned<T>(s: &str) -> Result<T, MerdeError<'_>>
 where
     T: DeserializeOwned,
 {
-    use merde_core::MetastackExt;
+    merde_core::entry_span!("merde_yaml::from_str_owned", len = s.len());
     let mut deser = YamlDeserializer::new(s);
-    T::deserialize_owned(&mut deser).run_sync_with_metastack()
+    deser.deserialize_owned::<T>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use merde_core::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_duplicate_keys() {
+        let input = "a: 1\nb: [2]\na: 3\n";
+
+        let map: HashMap<String, Value> = from_str(input).unwrap();
+        assert_eq!(map["a"], Value::I64(3));
+
+        let mut de =
+            YamlDeserializer::new(input).with_duplicate_key_policy(DuplicateKeyPolicy::FirstWins);
+        let map = de.deserialize::<HashMap<String, Value>>().unwrap();
+        assert_eq!(map["a"], Value::I64(1));
+
+        let mut de =
+            YamlDeserializer::new(input).with_duplicate_key_policy(DuplicateKeyPolicy::Error);
+        assert!(matches!(
+            de.deserialize::<Value>(),
+            Err(MerdeError::DuplicateKey(k)) if k == "a"
+        ));
+    }
+
+    #[test]
+    fn test_string_scalars() {
+        let input = "version: 1.10\nzip: 012345\nflag: true\nnothing: ~\nnan: nan\n";
+
+        // plain scalars keep their text when the target wants a string
+        let map: HashMap<String, String> = from_str(input).unwrap();
+        assert_eq!(map["version"], "1.10");
+        assert_eq!(map["zip"], "012345");
+        assert_eq!(map["flag"], "true");
+        assert_eq!(map["nothing"], "~");
+        assert_eq!(map["nan"], "nan");
+
+        // ...even when they were peeked at first
+        let map: HashMap<String, Option<String>> = from_str(input).unwrap();
+        assert_eq!(map["version"].as_deref(), Some("1.10"));
+        assert_eq!(map["nan"].as_deref(), Some("nan"));
+        assert_eq!(map["nothing"], None);
+
+        // and are typed by their looks otherwise
+        let map: HashMap<String, Value> = from_str(input).unwrap();
+        assert_eq!(map["version"], Value::from(1.1));
+        assert_eq!(map["zip"], Value::I64(12345));
+        assert_eq!(map["flag"], Value::Bool(true));
+        assert_eq!(map["nothing"], Value::Null);
+
+        // numbers as written
+        let n: merde_core::RawNumber = from_str("1.10").unwrap();
+        assert_eq!(n.as_str(), "1.10");
+
+        // explicit tags still win
+        assert!(from_str::<String>("!!int 5").is_err());
+        let s: String = from_str("'1.10'").unwrap();
+        assert_eq!(s, "1.10");
+    }
+
+    #[test]
+    fn test_borrowed_scalars() {
+        let input = "é: plain\n\"clé\": 'single'\nk: \"double\"\nescaped: \"a\\tb\"\nfolded: a\n  b\nblock: |\n  text\nlist: [ü, 'x''y']\n";
+        let map: HashMap<CowStr, Value> = from_str(input).unwrap();
+        let borrowed = |k: &str| {
+            let (key, value) = map.get_key_value(k).unwrap();
+            let Value::Str(value) = value else {
+                panic!("{k}: {value:?}")
+            };
+            (
+                matches!(key, CowStr::Borrowed(_)),
+                matches!(value, CowStr::Borrowed(_)),
+            )
+        };
+        assert_eq!(borrowed("é"), (true, true));
+        assert_eq!(borrowed("clé"), (true, true));
+        assert_eq!(borrowed("k"), (true, true));
+        assert_eq!(borrowed("escaped"), (true, false));
+        assert_eq!(map["escaped"], Value::from("a\tb"));
+        assert_eq!(borrowed("folded"), (true, false));
+        assert_eq!(map["folded"], Value::from("a b"));
+        assert_eq!(borrowed("block"), (true, false));
+
+        let list: Vec<CowStr> = from_str("[ü, 'x''y', \"z\"]").unwrap();
+        assert_eq!(list, ["ü", "x'y", "z"]);
+        assert!(matches!(list[0], CowStr::Borrowed(_)));
+        assert!(!matches!(list[1], CowStr::Borrowed(_)));
+        assert!(matches!(list[2], CowStr::Borrowed(_)));
+    }
 }